@@ -0,0 +1,111 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Bindings of the editor camera controls to mouse input.
+
+use crate::fyrox::{
+    core::{algebra::Vector2, fxhash::FxHashSet},
+    engine::{
+        input::Mouse,
+        input_map::{InputBinding, InputMap, InputSource, MouseAxis, TouchGestureAxis},
+        touch::Touches,
+    },
+    gui::message::MouseButton,
+};
+
+/// Rotates the camera while held.
+pub const ORBIT: &str = "Orbit";
+/// Pans the camera while held.
+pub const PAN: &str = "Pan";
+/// Moves the camera along its look vector.
+pub const ZOOM: &str = "Zoom";
+/// Horizontal look axis.
+pub const LOOK_X: &str = "LookX";
+/// Vertical look axis.
+pub const LOOK_Y: &str = "LookY";
+/// Moves the camera along its look vector, driven by touch gestures.
+pub const PINCH: &str = "Pinch";
+/// Rotates the camera around the vertical axis, driven by touch gestures.
+pub const TWIST: &str = "Twist";
+
+/// Creates the default input map of the editor camera: orbit on the right mouse button, pan on
+/// the middle mouse button, zoom on the mouse wheel or pinch, rotation with two fingers.
+pub fn default_input_map() -> InputMap {
+    InputMap::default()
+        .with_action(ORBIT, [InputBinding::MouseButton(Mouse::RIGHT_BUTTON)])
+        .with_action(PAN, [InputBinding::MouseButton(Mouse::MIDDLE_BUTTON)])
+        .with_action(ZOOM, [InputBinding::MouseWheel(MouseAxis::Y)])
+        .with_action(LOOK_X, [InputBinding::MouseMotion(MouseAxis::X)])
+        .with_action(LOOK_Y, [InputBinding::MouseMotion(MouseAxis::Y)])
+        .with_action(PINCH, [InputBinding::TouchGesture(TouchGestureAxis::Pinch)])
+        .with_action(
+            TWIST,
+            [InputBinding::TouchGesture(TouchGestureAxis::Rotation)],
+        )
+}
+
+/// Converts a mouse button of the UI to the id used by input bindings.
+pub fn button_id(button: MouseButton) -> u32 {
+    match button {
+        MouseButton::Left => Mouse::LEFT_BUTTON,
+        MouseButton::Right => Mouse::RIGHT_BUTTON,
+        MouseButton::Middle => Mouse::MIDDLE_BUTTON,
+        MouseButton::Back => 3,
+        MouseButton::Forward => 4,
+        MouseButton::Other(id) => id as u32,
+    }
+}
+
+/// Raw mouse input of the scene preview. The editor does not use the input state of the engine,
+/// because the events come from the UI and must only be handled when the preview is hovered.
+#[derive(Default, Debug)]
+pub struct CameraInput {
+    pub pressed_buttons: FxHashSet<u32>,
+    pub motion: Vector2<f32>,
+    pub wheel: f32,
+    pub touches: Touches,
+}
+
+impl InputSource for CameraInput {
+    fn binding_value(&self, binding: &InputBinding) -> f32 {
+        match binding {
+            InputBinding::MouseButton(button) => {
+                if self.pressed_buttons.contains(button) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            InputBinding::MouseMotion(MouseAxis::X) => self.motion.x,
+            InputBinding::MouseMotion(MouseAxis::Y) => self.motion.y,
+            InputBinding::MouseWheel(MouseAxis::Y) => self.wheel,
+            InputBinding::TouchGesture(axis) => {
+                let gesture = self.touches.gesture();
+                match axis {
+                    TouchGestureAxis::Pinch => gesture.pinch,
+                    TouchGestureAxis::Rotation => gesture.rotation,
+                    TouchGestureAxis::PanX => gesture.pan.x,
+                    TouchGestureAxis::PanY => gesture.pan.y,
+                }
+            }
+            _ => 0.0,
+        }
+    }
+}
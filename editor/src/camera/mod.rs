@@ -20,6 +20,7 @@
 
 use crate::settings::selection::SelectionSettings;
 use crate::{
+    camera::input::CameraInput,
     fyrox::{
         core::{
             algebra::{clamp, Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
@@ -29,8 +30,9 @@ use crate::{
             },
             pool::Handle,
         },
+        engine::input_map::InputMap,
         graph::{SceneGraph, SceneGraphNode},
-        gui::message::{KeyCode, KeyboardModifiers, MouseButton},
+        gui::message::{KeyCode, KeyboardModifiers, MouseButton, TouchPhase},
         renderer::bundle::{RenderContext, RenderDataBundleStorage},
         scene::{
             base::BaseBuilder,
@@ -65,6 +67,7 @@ use std::{
     path::Path,
 };
 
+pub mod input;
 pub mod panel;
 
 pub const DEFAULT_Z_OFFSET: f32 = -3.0;
@@ -104,6 +107,9 @@ pub struct CameraController {
     pub editor_objects_root: Handle<Pivot>,
     pub scene_content_root: Handle<Node>,
     pub screen_size: Vector2<f32>,
+    /// Bindings of the camera controls, see [`input`] module for the names of the actions.
+    pub input_map: InputMap,
+    input: CameraInput,
 }
 
 #[derive(Clone, Debug)]
@@ -111,6 +117,9 @@ pub struct CameraPickResult {
     pub position: Vector3<f32>,
     pub node: Handle<Node>,
     pub toi: f32,
+    /// Normal of the picked surface, facing towards the camera. Available only for precise picking
+    /// of objects with a hull (meshes, terrains, etc.).
+    pub normal: Option<Vector3<f32>>,
 }
 
 #[derive(Default)]
@@ -211,6 +220,8 @@ impl CameraController {
             editor_objects_root,
             scene_content_root,
             screen_size: Default::default(),
+            input_map: input::default_input_map(),
+            input: Default::default(),
         }
     }
 
@@ -230,6 +241,16 @@ impl CameraController {
         self.yaw = yaw;
     }
 
+    /// Rotation of the pivot, that corresponds to the current yaw of the camera.
+    pub fn pivot_rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.yaw)
+    }
+
+    /// Rotation of the camera hinge, that corresponds to the current pitch of the camera.
+    pub fn hinge_rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.pitch)
+    }
+
     pub fn is_interacting(&self) -> bool {
         self.move_backward
             || self.move_forward
@@ -324,14 +345,20 @@ impl CameraController {
         delta: Vector2<f32>,
         settings: &Settings,
     ) {
+        self.input.motion = delta;
+        self.input_map.update(&self.input);
+        self.input.motion = Vector2::default();
+
         match self.mouse_control_mode {
             MouseControlMode::None => {}
             MouseControlMode::CenteredRotation { .. } | MouseControlMode::OrbitalRotation => {
                 const MAX_ANGLE_RAD: f32 = 90.0f32.to_radians();
                 const GLOBAL_MOUSE_SENSITIVITY: f32 = 0.01f32;
                 let mouse_sensitivity = GLOBAL_MOUSE_SENSITIVITY * settings.camera.sensitivity;
-                self.yaw -= delta.x * mouse_sensitivity;
-                self.pitch += delta.y * mouse_sensitivity;
+                let y_sign = if settings.camera.invert_y { -1.0 } else { 1.0 };
+                self.yaw -= self.input_map.action_value(input::LOOK_X) * mouse_sensitivity;
+                self.pitch +=
+                    y_sign * self.input_map.action_value(input::LOOK_Y) * mouse_sensitivity;
                 self.pitch = clamp(self.pitch, -MAX_ANGLE_RAD, MAX_ANGLE_RAD);
             }
             MouseControlMode::Drag {
@@ -362,6 +389,36 @@ impl CameraController {
     }
 
     pub fn on_mouse_wheel(&mut self, delta: f32, graph: &mut Graph, settings: &Settings) {
+        self.input.wheel = delta;
+        self.input_map.update(&self.input);
+        self.input.wheel = 0.0;
+        let delta = self.input_map.action_value(input::ZOOM);
+        self.zoom(delta, graph, settings);
+    }
+
+    pub fn on_touch(
+        &mut self,
+        phase: TouchPhase,
+        id: u64,
+        position: Vector2<f32>,
+        graph: &mut Graph,
+        settings: &Settings,
+    ) {
+        // Pinch is measured in pixels, while zoom is measured in wheel "lines".
+        const PINCH_TO_ZOOM: f32 = 0.02;
+
+        self.input.touches.handle_touch(phase, id, position);
+        self.input_map.update(&self.input);
+        self.input.touches.clear_gesture();
+
+        let zoom = self.input_map.action_value(input::PINCH) * PINCH_TO_ZOOM;
+        if zoom != 0.0 {
+            self.zoom(zoom * settings.camera.zoom_speed, graph, settings);
+        }
+        self.yaw -= self.input_map.action_value(input::TWIST);
+    }
+
+    fn zoom(&mut self, delta: f32, graph: &mut Graph, settings: &Settings) {
         let camera = &mut graph[self.camera];
 
         match *camera.projection_mut() {
@@ -427,8 +484,11 @@ impl CameraController {
     }
 
     pub fn on_mouse_button_up(&mut self, button: MouseButton, graph: &mut Graph) {
-        match button {
-            MouseButton::Right => match self.mouse_control_mode {
+        self.input.pressed_buttons.remove(&input::button_id(button));
+        self.input_map.update(&self.input);
+
+        if self.input_map.is_action_just_released(input::ORBIT) {
+            match self.mouse_control_mode {
                 MouseControlMode::CenteredRotation { prev_z_offset } => {
                     self.z_offset = prev_z_offset;
                     self.move_along_look_vector(-self.z_offset, graph);
@@ -439,11 +499,9 @@ impl CameraController {
                     self.mouse_control_mode = MouseControlMode::None;
                 }
                 _ => {}
-            },
-            MouseButton::Middle => {
-                self.mouse_control_mode = MouseControlMode::None;
             }
-            _ => (),
+        } else if self.input_map.is_action_just_released(input::PAN) {
+            self.mouse_control_mode = MouseControlMode::None;
         }
     }
 
@@ -456,34 +514,34 @@ impl CameraController {
     ) {
         let is_perspective = graph[self.camera].projection().is_perspective();
 
-        match button {
-            MouseButton::Right => {
-                if is_perspective {
-                    if modifiers.shift {
-                        self.mouse_control_mode = MouseControlMode::Drag {
-                            initial_position: self.position(graph),
-                            initial_mouse_position: mouse_position,
-                        };
-                    } else {
-                        self.mouse_control_mode = MouseControlMode::CenteredRotation {
-                            prev_z_offset: self.z_offset,
-                        };
-                        self.move_along_look_vector(self.z_offset, graph);
-                        self.z_offset = 0.0;
-                    }
-                }
-            }
-            MouseButton::Middle => {
-                if is_perspective {
-                    self.mouse_control_mode = MouseControlMode::OrbitalRotation;
-                } else {
+        self.input.pressed_buttons.insert(input::button_id(button));
+        self.input_map.update(&self.input);
+
+        if self.input_map.is_action_just_pressed(input::ORBIT) {
+            if is_perspective {
+                if modifiers.shift {
                     self.mouse_control_mode = MouseControlMode::Drag {
                         initial_position: self.position(graph),
                         initial_mouse_position: mouse_position,
                     };
+                } else {
+                    self.mouse_control_mode = MouseControlMode::CenteredRotation {
+                        prev_z_offset: self.z_offset,
+                    };
+                    self.move_along_look_vector(self.z_offset, graph);
+                    self.z_offset = 0.0;
                 }
             }
-            _ => (),
+        } else if self.input_map.is_action_just_pressed(input::PAN) {
+            // Alt allows to rotate the camera around the pivot without moving the pivot.
+            if is_perspective && modifiers.alt {
+                self.mouse_control_mode = MouseControlMode::OrbitalRotation;
+            } else {
+                self.mouse_control_mode = MouseControlMode::Drag {
+                    initial_position: self.position(graph),
+                    initial_mouse_position: mouse_position,
+                };
+            }
         }
     }
 
@@ -613,16 +671,13 @@ impl CameraController {
                     .local_transform_mut()
                     .set_position(Vector3::new(0.0, 0.0, self.z_offset));
 
-                graph[self.camera_hinge].local_transform_mut().set_rotation(
-                    UnitQuaternion::from_axis_angle(&Vector3::x_axis(), self.pitch),
-                );
+                graph[self.camera_hinge]
+                    .local_transform_mut()
+                    .set_rotation(self.hinge_rotation());
 
                 graph[self.pivot]
                     .local_transform_mut()
-                    .set_rotation(UnitQuaternion::from_axis_angle(
-                        &Vector3::y_axis(),
-                        self.yaw,
-                    ))
+                    .set_rotation(self.pivot_rotation())
                     .offset(move_vec);
             }
             Projection::Orthographic(_) => {
@@ -719,6 +774,7 @@ impl CameraController {
                             position: position.closest_point,
                             node: handle,
                             toi: position.closest_distance.max(toi_limit),
+                            normal: Some(position.closest_normal),
                         });
                         // Limit selection toi for descendants to always prefer the
                         // prefab root in selection.
@@ -743,6 +799,7 @@ impl CameraController {
                                 position: position.closest_point,
                                 node: handle,
                                 toi: position.closest_distance.max(toi_limit),
+                                normal: Some(position.closest_normal),
                             });
                             added = true;
                         }
@@ -766,6 +823,7 @@ impl CameraController {
                                 position: if da < db { points[0] } else { points[1] },
                                 node: handle,
                                 toi: closest_distance.max(toi_limit),
+                                normal: None,
                             });
                         }
                     }
@@ -887,7 +945,7 @@ fn transform_vertex(vertex: Vector3<f32>, transform: &Matrix4<f32>) -> Vector3<f
     transform.transform_point(&Point3::from(vertex)).coords
 }
 
-fn read_triangle(
+pub(crate) fn read_triangle(
     data: &SurfaceData,
     triangle: &TriangleDefinition,
     transform: &Matrix4<f32>,
@@ -902,6 +960,7 @@ fn read_triangle(
 struct PickPosition {
     closest_distance: f32,
     closest_point: Vector3<f32>,
+    closest_normal: Vector3<f32>,
 }
 
 #[derive(Clone, Debug)]
@@ -965,6 +1024,7 @@ fn precise_ray_test(
     });
     let mut closest_distance = f32::MAX;
     let mut closest_point = None;
+    let mut closest_normal = Vector3::default();
     let mut instance_count = 0;
     for bundle in bundle_storage.bundles {
         let data = bundle.data.data_ref();
@@ -976,13 +1036,12 @@ fn precise_ray_test(
                 .iter()
                 .filter_map(|t| read_triangle(&data, t, &instance.world_transform))
             {
-                if ignore_back_faces {
-                    // If normal of the triangle is facing in the same direction as ray's direction,
-                    // then we skip such a triangle.
-                    let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
-                    if normal.dot(&ray.dir) >= 0.0 {
-                        continue;
-                    }
+                let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+                let is_back_face = normal.dot(&ray.dir) >= 0.0;
+                // If normal of the triangle is facing in the same direction as ray's direction,
+                // then we skip such a triangle.
+                if ignore_back_faces && is_back_face {
+                    continue;
                 }
 
                 if let Some(pt) = ray.triangle_intersection_point(&triangle) {
@@ -991,6 +1050,11 @@ fn precise_ray_test(
                     if distance < closest_distance {
                         closest_distance = distance;
                         closest_point = Some(pt);
+                        // Picked surface normal always faces the observer.
+                        closest_normal = normal
+                            .try_normalize(f32::EPSILON)
+                            .map(|n| if is_back_face { -n } else { n })
+                            .unwrap_or_else(|| -ray.dir.normalize());
                     }
                 }
             }
@@ -1000,7 +1064,143 @@ fn precise_ray_test(
         pick_position: closest_point.map(|pt| PickPosition {
             closest_distance,
             closest_point: pt,
+            closest_normal,
         }),
         instance_count,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        camera::CameraController,
+        fyrox::{
+            core::{
+                algebra::{UnitQuaternion, Vector2, Vector3},
+                pool::Handle,
+            },
+            gui::message::{KeyboardModifiers, MouseButton, TouchPhase},
+            scene::{base::BaseBuilder, graph::Graph, mesh::MeshBuilder, pivot::PivotBuilder},
+        },
+        settings::Settings,
+    };
+
+    fn make_controller(graph: &mut Graph, settings: &Settings) -> CameraController {
+        let root = PivotBuilder::new(BaseBuilder::new()).build(graph);
+        let grid = MeshBuilder::new(BaseBuilder::new()).build(graph);
+        CameraController::new(
+            graph,
+            root,
+            settings,
+            Default::default(),
+            grid,
+            root,
+            Handle::NONE,
+        )
+    }
+
+    fn drag(
+        controller: &mut CameraController,
+        graph: &mut Graph,
+        settings: &Settings,
+        delta: Vector2<f32>,
+    ) {
+        controller.on_mouse_button_down(
+            Vector2::default(),
+            MouseButton::Right,
+            KeyboardModifiers::default(),
+            graph,
+        );
+        controller.on_mouse_move(graph, delta, Vector2::new(800.0, 600.0), delta, settings);
+        controller.on_mouse_button_up(MouseButton::Right, graph);
+    }
+
+    #[test]
+    fn test_rmb_drag_rotates_pivot() {
+        let mut graph = Graph::new();
+        let mut settings = Settings::default();
+        settings.camera.sensitivity = 2.0;
+        let mut controller = make_controller(&mut graph, &settings);
+
+        // 100 pixels with the sensitivity of 2 gives 2 radians of yaw.
+        drag(
+            &mut controller,
+            &mut graph,
+            &settings,
+            Vector2::new(100.0, 0.0),
+        );
+        assert!((controller.yaw() + 2.0).abs() < 1.0e-5);
+        assert_eq!(controller.pitch(), 0.0);
+
+        controller.update(
+            &mut graph,
+            &mut settings,
+            None,
+            controller.editor_objects_root,
+            controller.scene_content_root,
+            Vector2::new(800.0, 600.0),
+            1.0 / 60.0,
+        );
+        let expected = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), -2.0);
+        let rotation = **graph[controller.pivot].local_transform().rotation();
+        assert!(rotation.angle_to(&expected) < 1.0e-5);
+        assert_eq!(controller.pivot_rotation(), rotation);
+    }
+
+    #[test]
+    fn test_invert_y() {
+        let mut graph = Graph::new();
+        let mut settings = Settings::default();
+        let mut controller = make_controller(&mut graph, &settings);
+
+        drag(
+            &mut controller,
+            &mut graph,
+            &settings,
+            Vector2::new(0.0, 10.0),
+        );
+        let pitch = controller.pitch();
+        assert!(pitch > 0.0);
+
+        settings.camera.invert_y = true;
+        drag(
+            &mut controller,
+            &mut graph,
+            &settings,
+            Vector2::new(0.0, 10.0),
+        );
+        assert!(controller.pitch().abs() < 1.0e-5);
+
+        // Bindings could be changed.
+        controller.input_map.clear_bindings(super::input::ORBIT);
+        drag(
+            &mut controller,
+            &mut graph,
+            &settings,
+            Vector2::new(0.0, 10.0),
+        );
+        assert!(controller.pitch().abs() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_pinch_zoom() {
+        let mut graph = Graph::new();
+        let settings = Settings::default();
+        let mut controller = make_controller(&mut graph, &settings);
+        let initial = controller.z_offset;
+
+        let mut touch = |phase, id, x| {
+            controller.on_touch(phase, id, Vector2::new(x, 0.0), &mut graph, &settings)
+        };
+        touch(TouchPhase::Started, 0, 100.0);
+        touch(TouchPhase::Started, 1, 200.0);
+        touch(TouchPhase::Moved, 1, 300.0);
+        touch(TouchPhase::Ended, 0, 100.0);
+        touch(TouchPhase::Ended, 1, 300.0);
+
+        // Spreading the fingers moves the camera closer.
+        let expected = initial + 100.0 * 0.02 * settings.camera.zoom_speed;
+        assert!((controller.z_offset - expected).abs() < 1.0e-5);
+        assert_eq!(controller.yaw(), 0.0);
+    }
+}
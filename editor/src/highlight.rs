@@ -157,6 +157,10 @@ impl SceneRenderPass for HighlightRenderPass {
                     renderer_resources: ctx.renderer_resources,
                     ambient_light: Default::default(),
                     scene_depth: Some(ctx.depth_texture),
+                    fog: None,
+                    use_height_fog: false,
+                    transparency_stage: Default::default(),
+                    shadows: None,
                     viewport: ctx.observer.viewport,
                     uniform_memory_allocator: ctx.uniform_memory_allocator,
                     resource_manager: ctx.resource_manager,
@@ -54,7 +54,9 @@ use fyrox::scene::camera::Camera;
 pub mod gizmo;
 pub mod move_mode;
 pub mod navmesh;
+pub mod placement;
 pub mod plane;
+pub mod ruler;
 pub mod rotate_mode;
 pub mod scale_mode;
 pub mod select_mode;
@@ -0,0 +1,181 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Placement of scene nodes onto the surfaces under the cursor.
+
+use crate::{
+    camera::{CameraController, CameraPickResult, PickMethod, PickingOptions},
+    fyrox::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            math::ray::Ray,
+            pool::Handle,
+        },
+        graph::SceneGraph,
+        scene::{graph::Graph, node::Node},
+    },
+};
+
+/// Resulting global position and rotation of a node placed by [`SurfacePlacement`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Placement {
+    pub position: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    /// `true` if the node was placed on a surface, `false` - if it was placed in the air along the
+    /// ray.
+    pub on_surface: bool,
+}
+
+/// Places nodes onto the surfaces under the cursor using precise (per-triangle) ray test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfacePlacement {
+    /// If `true`, the up vector of a placed node will be aligned with the normal of the surface.
+    pub align_to_normal: bool,
+    /// Distance along the ray at which a node will be placed if there's no surface under the
+    /// cursor.
+    pub default_distance: f32,
+}
+
+impl Default for SurfacePlacement {
+    fn default() -> Self {
+        Self {
+            align_to_normal: true,
+            default_distance: 10.0,
+        }
+    }
+}
+
+impl SurfacePlacement {
+    /// Calculates placement of a node with the given current global rotation for the given ray
+    /// and the (optional) ray test result.
+    pub fn placement(
+        &self,
+        ray: &Ray,
+        hit: Option<&CameraPickResult>,
+        rotation: UnitQuaternion<f32>,
+    ) -> Placement {
+        match hit {
+            Some(hit) => Placement {
+                position: hit.position,
+                rotation: match hit.normal {
+                    Some(normal) if self.align_to_normal => {
+                        // Use the shortest arc, so the node keeps its heading.
+                        let up = rotation * Vector3::y();
+                        UnitQuaternion::rotation_between(&up, &normal).unwrap_or_else(|| {
+                            // Up vector is opposite to the normal.
+                            UnitQuaternion::from_axis_angle(
+                                &(rotation * Vector3::x_axis()),
+                                std::f32::consts::PI,
+                            )
+                        }) * rotation
+                    }
+                    _ => rotation,
+                },
+                on_surface: true,
+            },
+            None => Placement {
+                position: ray.origin
+                    + ray.dir.try_normalize(f32::EPSILON).unwrap_or_default()
+                        * self.default_distance,
+                rotation,
+                on_surface: false,
+            },
+        }
+    }
+
+    /// Picks a surface under the cursor and moves the given node onto it. The node itself is
+    /// excluded from picking. Returns the new placement of the node.
+    pub fn place(
+        &self,
+        node: Handle<Node>,
+        controller: &mut CameraController,
+        graph: &mut Graph,
+        mut options: PickingOptions,
+    ) -> Placement {
+        let ray = graph[controller.camera].make_ray(options.cursor_pos, controller.screen_size);
+
+        // Prevent picking the node itself and its descendants.
+        let ignored = graph
+            .traverse_iter(node)
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        let mut user_filter = options.filter.take();
+        let mut filter = |handle: Handle<Node>, node_ref: &Node| {
+            !ignored.contains(&handle)
+                && user_filter
+                    .as_mut()
+                    .is_none_or(|func| func(handle, node_ref))
+        };
+        let hit = controller.pick(
+            graph,
+            PickingOptions {
+                filter: Some(&mut filter),
+                use_picking_loop: false,
+                method: PickMethod::PRECISE_HULL_RAY_TEST,
+                ..options
+            },
+        );
+
+        let placement = self.placement(&ray, hit.as_ref(), graph.global_rotation(node));
+        graph.set_global_position(node, placement.position);
+        graph.set_global_rotation(node, placement.rotation);
+        placement
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_place_on_floor() {
+        let placement = SurfacePlacement::default();
+        let ray = Ray::from_two_points(Vector3::new(0.0, 5.0, -5.0), Vector3::new(0.0, 0.0, 0.0));
+        // The node is tilted initially.
+        let rotation = UnitQuaternion::from_euler_angles(0.4, 0.0, 0.3);
+
+        // A hit of a flat floor.
+        let hit = CameraPickResult {
+            position: Vector3::new(0.0, 0.0, 0.0),
+            node: Handle::NONE,
+            toi: 1.0,
+            normal: Some(Vector3::y()),
+        };
+        let result = placement.placement(&ray, Some(&hit), rotation);
+        assert!(result.on_surface);
+        assert_eq!(result.position, hit.position);
+        assert!((result.rotation * Vector3::y()).metric_distance(&Vector3::y()) < 1.0e-5);
+
+        // No alignment.
+        let result = SurfacePlacement {
+            align_to_normal: false,
+            ..Default::default()
+        }
+        .placement(&ray, Some(&hit), rotation);
+        assert_eq!(result.rotation, rotation);
+
+        // Nothing under the cursor.
+        let result = placement.placement(&ray, None, rotation);
+        assert!(!result.on_surface);
+        assert_eq!(result.rotation, rotation);
+        let distance = result.position.metric_distance(&ray.origin);
+        assert!((distance - placement.default_distance).abs() < 1.0e-4);
+    }
+}
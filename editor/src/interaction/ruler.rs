@@ -0,0 +1,231 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Backend of the measurement tool (ruler). It accumulates up to three picked world points and
+//! computes the distance between them and, when three points are picked, the angle at the middle
+//! one.
+
+use crate::{
+    camera::{CameraController, PickingOptions},
+    fyrox::{
+        core::{algebra::Vector3, math::plane::Plane, pool::Handle},
+        graph::SceneGraph,
+        scene::{graph::Graph, node::Node},
+    },
+};
+
+/// Maximum amount of points the ruler can hold. Two points define a segment, three points define
+/// an angle.
+pub const MAX_RULER_POINTS: usize = 3;
+
+/// Finds a descendant of `root` (including the root itself) with its global position closest to
+/// the given `point`. Only nodes within `radius` are considered.
+pub fn nearest_node(
+    graph: &Graph,
+    root: Handle<Node>,
+    point: Vector3<f32>,
+    radius: f32,
+) -> Option<(Handle<Node>, Vector3<f32>)> {
+    let mut closest = None;
+    let mut closest_distance = radius;
+    for (handle, node) in graph.traverse_iter(root) {
+        let position = node.global_position();
+        let distance = position.metric_distance(&point);
+        if distance <= closest_distance {
+            closest_distance = distance;
+            closest = Some((handle, position));
+        }
+    }
+    closest
+}
+
+/// Calculates the angle (in radians) between `a - vertex` and `b - vertex` segments. Returns
+/// `None` if any of the segments is degenerate.
+pub fn angle_between(a: Vector3<f32>, vertex: Vector3<f32>, b: Vector3<f32>) -> Option<f32> {
+    let da = (a - vertex).try_normalize(f32::EPSILON)?;
+    let db = (b - vertex).try_normalize(f32::EPSILON)?;
+    Some(da.dot(&db).clamp(-1.0, 1.0).acos())
+}
+
+/// Result of a measurement, ready to be displayed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    /// Total length of the polyline going through all the picked points.
+    pub distance: f32,
+    /// Angle (in radians) at the second point, available only when three points are picked.
+    pub angle: Option<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Ruler {
+    points: Vec<Vector3<f32>>,
+    /// Picked points will be snapped to the positions of the scene nodes within this radius.
+    /// `None` disables snapping.
+    pub snap_radius: Option<f32>,
+}
+
+impl Default for Ruler {
+    fn default() -> Self {
+        Self {
+            points: Vec::with_capacity(MAX_RULER_POINTS),
+            snap_radius: Some(0.25),
+        }
+    }
+}
+
+impl Ruler {
+    pub fn points(&self) -> &[Vector3<f32>] {
+        &self.points
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// Adds a new point, snapping it to a nearby node of the `root` hierarchy if snapping is
+    /// enabled. When the ruler is already full, the measurement starts over from the new point.
+    /// Returns the actual (possibly snapped) point.
+    pub fn add_point(
+        &mut self,
+        graph: &Graph,
+        root: Handle<Node>,
+        mut point: Vector3<f32>,
+    ) -> Vector3<f32> {
+        if let Some(radius) = self.snap_radius {
+            if let Some((_, position)) = nearest_node(graph, root, point, radius) {
+                point = position;
+            }
+        }
+
+        if self.points.len() >= MAX_RULER_POINTS {
+            self.points.clear();
+        }
+        self.points.push(point);
+
+        point
+    }
+
+    /// Picks a world point under the cursor and adds it to the ruler. The closest intersection
+    /// with scene objects is used first, if there's none the point is picked on the given plane.
+    pub fn pick_point(
+        &mut self,
+        controller: &mut CameraController,
+        graph: &Graph,
+        options: PickingOptions,
+        plane: Plane,
+    ) -> Option<Vector3<f32>> {
+        let cursor_pos = options.cursor_pos;
+        let point = match controller.pick(graph, options) {
+            Some(result) => result.position,
+            None => controller.pick_on_plane(
+                plane,
+                graph,
+                cursor_pos,
+                controller.screen_size,
+                Default::default(),
+            )?,
+        };
+        Some(self.add_point(graph, controller.scene_content_root, point))
+    }
+
+    /// Measures the distance (and the angle, if possible) between the picked points. Returns
+    /// `None` if there's less than two points.
+    pub fn measure(&self) -> Option<Measurement> {
+        if self.points.len() < 2 {
+            return None;
+        }
+
+        let distance = self
+            .points
+            .windows(2)
+            .map(|segment| segment[0].metric_distance(&segment[1]))
+            .sum();
+
+        let angle = if let [a, vertex, b] = self.points.as_slice() {
+            angle_between(*a, *vertex, *b)
+        } else {
+            None
+        };
+
+        Some(Measurement { distance, angle })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fyrox::scene::{
+        base::BaseBuilder, pivot::PivotBuilder, transform::TransformBuilder,
+    };
+
+    #[test]
+    fn test_ruler_measurements() {
+        let mut graph = Graph::new();
+        let root = graph.get_root();
+
+        let mut ruler = Ruler {
+            snap_radius: None,
+            ..Default::default()
+        };
+        assert!(ruler.measure().is_none());
+
+        ruler.add_point(&graph, root, Vector3::new(1.0, 2.0, 3.0));
+        ruler.add_point(&graph, root, Vector3::new(4.0, 6.0, 3.0));
+        let measurement = ruler.measure().unwrap();
+        assert!((measurement.distance - 5.0).abs() < 1e-5);
+        assert_eq!(measurement.angle, None);
+
+        ruler.clear();
+        ruler.add_point(&graph, root, Vector3::new(1.0, 0.0, 0.0));
+        ruler.add_point(&graph, root, Vector3::new(0.0, 0.0, 0.0));
+        ruler.add_point(&graph, root, Vector3::new(0.0, 0.0, 2.0));
+        let measurement = ruler.measure().unwrap();
+        assert!((measurement.distance - 3.0).abs() < 1e-5);
+        assert!((measurement.angle.unwrap() - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+
+        // Snapping to a nearby node.
+        let node = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(10.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        assert_eq!(
+            nearest_node(&graph, root, Vector3::new(10.1, 0.0, 0.0), 0.25),
+            Some((node, Vector3::new(10.0, 0.0, 0.0)))
+        );
+
+        ruler.clear();
+        ruler.snap_radius = Some(0.25);
+        assert_eq!(
+            ruler.add_point(&graph, root, Vector3::new(9.9, 0.1, 0.0)),
+            Vector3::new(10.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            ruler.add_point(&graph, root, Vector3::new(13.0, 4.0, 0.0)),
+            Vector3::new(13.0, 4.0, 0.0)
+        );
+        assert!((ruler.measure().unwrap().distance - 5.0).abs() < 1e-5);
+    }
+}
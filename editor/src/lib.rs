@@ -161,6 +161,7 @@ use crate::{
         probe::ReflectionProbePlugin,
         ragdoll::RagdollPlugin,
         settings::SettingsPlugin,
+        spline::SplinePlugin,
         stats::UiStatisticsPlugin,
         tilemap::TileMapEditorPlugin,
     },
@@ -690,6 +691,14 @@ fn make_dark_style() -> StyleResource {
         .set(ExportWindow::TITLE_BRUSH, Brush::Solid(Color::CORN_SILK))
         .set(AbsmEditor::NORMAL_ROOT_COLOR, Brush::solid(40, 80, 0))
         .set(AbsmEditor::SELECTED_ROOT_COLOR, Brush::solid(60, 100, 0))
+        .set(
+            AbsmEditor::NORMAL_ANY_STATE_COLOR,
+            Brush::solid(30, 60, 110),
+        )
+        .set(
+            AbsmEditor::SELECTED_ANY_STATE_COLOR,
+            Brush::solid(50, 80, 130),
+        )
         .set(
             AssetBrowser::ADD_ASSET_NORMAL_BRUSH,
             Brush::solid(23, 123, 53),
@@ -725,6 +734,14 @@ fn make_light_style() -> StyleResource {
         .set(ExportWindow::TITLE_BRUSH, Brush::Solid(Color::CORN_SILK))
         .set(AbsmEditor::NORMAL_ROOT_COLOR, Brush::solid(40, 80, 0))
         .set(AbsmEditor::SELECTED_ROOT_COLOR, Brush::solid(60, 100, 0))
+        .set(
+            AbsmEditor::NORMAL_ANY_STATE_COLOR,
+            Brush::solid(30, 60, 110),
+        )
+        .set(
+            AbsmEditor::SELECTED_ANY_STATE_COLOR,
+            Brush::solid(50, 80, 130),
+        )
         .set(
             AssetBrowser::ADD_ASSET_NORMAL_BRUSH,
             Brush::solid(23, 123, 53),
@@ -1093,6 +1110,7 @@ impl Editor {
                 .with(UiStatisticsPlugin::default())
                 .with(CurveEditorPlugin::default())
                 .with(ReflectionProbePlugin::default())
+                .with(SplinePlugin)
                 .with(inspector_plugin),
             // Apparently, some window managers (like Wayland), does not send `Focused` event after the window
             // was created. So we must assume that the editor is focused by default, otherwise editor's thread
@@ -80,6 +80,16 @@ struct LightmapperSettings {
     #[reflect(min_value = 0.0, max_value = 0.1, step = 0.001)]
     spacing: f32,
 
+    /// Amount of times the light is reflected between surfaces. Zero bounces means that only direct
+    /// lighting will be baked. Every bounce adds more indirect light, but also increases generation
+    /// time significantly. Default value is 1.
+    #[reflect(min_value = 0.0, max_value = 8.0)]
+    bounce_count: u32,
+
+    /// If set, the light map will be generated even if the scene has not changed since the last
+    /// generation. Otherwise the generation is skipped when the current light map is up-to-date.
+    force_rebake: bool,
+
     /// Path to the directory which will be used to save the generated light maps. Keep in mind, that
     /// the lightmapper automatically generates names for the files.
     path: PathBuf,
@@ -92,6 +102,8 @@ impl Default for LightmapperSettings {
             second_tex_coord_location: 6,
             texels_per_unit: 64,
             spacing: 0.005,
+            bounce_count: 1,
+            force_rebake: false,
             path: Default::default(),
         }
     }
@@ -362,19 +374,39 @@ impl LightPanel {
                     cancellation_token.clone(),
                     progress_indicator.clone(),
                 ) {
-                    let sender = self.sender.clone();
                     let texels_per_unit = self.settings.texels_per_unit;
                     let spacing = self.settings.spacing;
+                    let bounce_count = self.settings.bounce_count;
+
+                    if !self.settings.force_rebake
+                        && scene.graph.lightmap().is_some_and(|lightmap| {
+                            lightmap.is_up_to_date(
+                                &input_data,
+                                texels_per_unit,
+                                spacing,
+                                bounce_count,
+                            )
+                        })
+                    {
+                        Log::info("The light map is up-to-date, skipping generation.");
+                        if let Some(progress_window) = self.progress_window.take() {
+                            progress_window.close(engine.user_interfaces.first());
+                        }
+                        return;
+                    }
+
+                    let sender = self.sender.clone();
                     let path = self.settings.path.clone();
                     let resource_manager = engine.resource_manager.clone();
 
                     if let Err(e) = std::thread::Builder::new()
                         .name("LightmapGenerationThread".to_string())
                         .spawn(move || {
-                            match Lightmap::new(
+                            match Lightmap::new_with_bounces(
                                 input_data,
                                 texels_per_unit,
                                 spacing,
+                                bounce_count,
                                 cancellation_token,
                                 progress_indicator,
                             ) {
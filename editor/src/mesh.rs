@@ -21,7 +21,7 @@
 use crate::{
     command::{Command, CommandGroup},
     fyrox::{
-        core::pool::Handle,
+        core::{log::Log, pool::Handle},
         engine::Engine,
         graph::SceneGraph,
         gui::{
@@ -243,14 +243,21 @@ impl MeshControlPanel {
                 }
             } else if message.destination() == self.add_trimesh_collider {
                 for (mesh_handle, _) in meshes_iter(selection, scene) {
-                    if let Some((ancestor_rigid_body, _)) =
+                    if let Some((ancestor_rigid_body, rigid_body)) =
                         scene.graph.find_component_up::<RigidBody>(mesh_handle)
                     {
+                        let shape = ColliderShape::trimesh(vec![GeometrySource(mesh_handle)]);
+                        if !shape.is_compatible_with(rigid_body.body_type()) {
+                            Log::err(format!(
+                                "Unable to add a triangle mesh collider to {}, because it is a \
+                                dynamic rigid body. Use convex collider instead.",
+                                rigid_body.name()
+                            ));
+                            continue;
+                        }
                         let collider =
                             ColliderBuilder::new(BaseBuilder::new().with_name("TrimeshCollider"))
-                                .with_shape(ColliderShape::trimesh(vec![GeometrySource(
-                                    mesh_handle,
-                                )]))
+                                .with_shape(shape)
                                 .build_node();
                         commands.push(Command::new(AddNodeCommand::new(
                             collider,
@@ -215,6 +215,8 @@ impl AbsmEditor {
 
     pub const NORMAL_ROOT_COLOR: &'static str = "AsbmEditor.NormalRootColor";
     pub const SELECTED_ROOT_COLOR: &'static str = "AsbmEditor.SelectedRootColor";
+    pub const NORMAL_ANY_STATE_COLOR: &'static str = "AsbmEditor.NormalAnyStateColor";
+    pub const SELECTED_ANY_STATE_COLOR: &'static str = "AsbmEditor.SelectedAnyStateColor";
 
     pub fn new(
         ctx: &mut BuildContext,
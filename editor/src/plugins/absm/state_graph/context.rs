@@ -138,10 +138,14 @@ impl CanvasContextMenu {
                 ));
             } else if message.destination() == self.connect_all_nodes {
                 let canvas = &ui[self.canvas];
-                let state_nodes = canvas.children();
+                let state_nodes = canvas
+                    .children()
+                    .iter()
+                    .filter(|c| ui.node(**c).has_component::<AbsmNode<State<Handle<N>>>>())
+                    .collect::<Vec<_>>();
                 let mut states = Vec::default();
-                for source in state_nodes {
-                    for dest in state_nodes {
+                for &source in state_nodes.iter() {
+                    for &dest in state_nodes.iter() {
                         if source != dest {
                             states.push((source, dest));
                         }
@@ -364,6 +368,66 @@ impl NodeContextMenu {
     }
 }
 
+/// Context menu of the "Any State" node of the state graph.
+pub struct AnyStateContextMenu {
+    create_transition: Handle<MenuItem>,
+    pub menu: RcUiNodeHandle,
+    pub canvas: Handle<AbsmCanvas>,
+    placement_target: Handle<UiNode>,
+}
+
+impl AnyStateContextMenu {
+    pub const CREATE_TRANSITION: Uuid = uuid!("0b3bd1a2-5d5e-4c1f-9a0e-6f4a3c1b7d92");
+
+    pub fn new(ctx: &mut BuildContext) -> Self {
+        let create_transition;
+        let menu = ContextMenuBuilder::new(
+            PopupBuilder::new(WidgetBuilder::new().with_visibility(false))
+                .with_content(
+                    StackPanelBuilder::new(WidgetBuilder::new().with_child({
+                        create_transition = create_menu_item(
+                            "Create Any State Transition",
+                            Self::CREATE_TRANSITION,
+                            vec![],
+                            ctx,
+                        );
+                        create_transition
+                    }))
+                    .build(ctx),
+                )
+                .with_restrict_picking(false),
+        )
+        .build(ctx);
+        let menu = RcUiNodeHandle::new(menu, ctx.sender());
+
+        Self {
+            create_transition,
+            menu,
+            canvas: Default::default(),
+            placement_target: Default::default(),
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &UiMessage, ui: &mut UserInterface) {
+        if let Some(MenuItemMessage::Click) = message.data() {
+            if message.destination() == self.create_transition {
+                ui.send(
+                    self.canvas,
+                    AbsmCanvasMessage::SwitchMode(Mode::CreateTransition {
+                        source: self.placement_target,
+                        source_pos: ui.node(self.placement_target).center(),
+                        dest_pos: ui[self.canvas].screen_to_local(ui.cursor_position()),
+                    }),
+                )
+            }
+        } else if let Some(PopupMessage::Placement(Placement::Cursor(target))) = message.data() {
+            if message.destination() == self.menu.handle() {
+                self.placement_target = *target;
+            }
+        }
+    }
+}
+
 pub struct TransitionContextMenu {
     remove: Handle<MenuItem>,
     pub menu: RcUiNodeHandle,
@@ -20,7 +20,10 @@
 
 use crate::fyrox::graph::{PrefabData, SceneGraphNode};
 use crate::fyrox::{
-    core::pool::{ErasedHandle, Handle},
+    core::{
+        algebra::Vector2,
+        pool::{ErasedHandle, Handle},
+    },
     generic_animation::machine::{Machine, MachineLayer, State, Transition},
     graph::SceneGraph,
     gui::{
@@ -37,7 +40,9 @@ use crate::plugins::absm::{
     fetch_selection,
     node::{AbsmNode, AbsmNodeBuilder, AbsmNodeMessage},
     selection::{AbsmSelection, SelectedEntity},
-    state_graph::context::{CanvasContextMenu, NodeContextMenu, TransitionContextMenu},
+    state_graph::context::{
+        AnyStateContextMenu, CanvasContextMenu, NodeContextMenu, TransitionContextMenu,
+    },
     transition::{TransitionBuilder, TransitionMessage, TransitionView},
     AbsmEditor,
 };
@@ -55,11 +60,17 @@ use std::cmp::Ordering;
 
 mod context;
 
+/// The "Any State" node of the state graph, any-state transitions (see [`Transition::is_any_state`])
+/// are drawn from it. It does not represent a state, so it has the layer as its model. Its position
+/// is not stored in the machine.
+type AnyStateNode<N> = AbsmNode<MachineLayer<Handle<N>>>;
+
 pub struct StateGraphViewer {
     pub window: Handle<Window>,
     pub canvas: Handle<AbsmCanvas>,
     canvas_context_menu: CanvasContextMenu,
     node_context_menu: NodeContextMenu,
+    any_state_context_menu: AnyStateContextMenu,
     transition_context_menu: TransitionContextMenu,
     prev_absm: ErasedHandle,
     prev_layer: Option<usize>,
@@ -78,10 +89,22 @@ where
         .model_handle
 }
 
+/// Returns a position of the "Any State" node: to the left of the top-left corner of the states.
+fn any_state_node_position<N: Reflect>(machine_layer: &MachineLayer<Handle<N>>) -> Vector2<f32> {
+    machine_layer
+        .states()
+        .iter()
+        .map(|state| state.position)
+        .reduce(|a, b| a.inf(&b))
+        .map(|top_left| top_left - Vector2::new(250.0, 0.0))
+        .unwrap_or_default()
+}
+
 impl StateGraphViewer {
     pub fn new(ctx: &mut BuildContext) -> Self {
         let mut node_context_menu = NodeContextMenu::new(ctx);
         let mut canvas_context_menu = CanvasContextMenu::new(ctx);
+        let mut any_state_context_menu = AnyStateContextMenu::new(ctx);
         let transition_context_menu = TransitionContextMenu::new(ctx);
 
         let canvas = AbsmCanvasBuilder::new(
@@ -107,11 +130,13 @@ impl StateGraphViewer {
         canvas_context_menu.canvas = canvas;
         canvas_context_menu.node_context_menu = Some(node_context_menu.menu.clone());
         node_context_menu.canvas = canvas;
+        any_state_context_menu.canvas = canvas;
 
         Self {
             window,
             canvas,
             node_context_menu,
+            any_state_context_menu,
             canvas_context_menu,
             transition_context_menu,
             prev_absm: Default::default(),
@@ -183,19 +208,35 @@ impl StateGraphViewer {
                         source_node,
                         dest_node,
                     } => {
-                        if message.direction() == MessageDirection::FromWidget {
-                            let source = fetch_state_node_model_handle(*source_node, ui);
+                        // Transitions to the "Any State" node are not possible.
+                        if message.direction() == MessageDirection::FromWidget
+                            && ui
+                                .node(*dest_node)
+                                .has_component::<AbsmNode<State<Handle<N>>>>()
+                        {
                             let dest = fetch_state_node_model_handle(*dest_node, ui);
+                            let transition =
+                                if ui.node(*source_node).has_component::<AnyStateNode<N>>() {
+                                    Transition::new_any_state("Transition", dest, 1.0, "")
+                                } else {
+                                    let source = fetch_state_node_model_handle(*source_node, ui);
+                                    Transition::new("Transition", source, dest, 1.0, "")
+                                };
                             sender.do_command(AddTransitionCommand::new(
                                 absm_node_handle,
                                 layer_index,
-                                Transition::new("Transition", source, dest, 1.0, ""),
+                                transition,
                             ));
                         }
                     }
                     AbsmCanvasMessage::CommitDrag { entries } => {
+                        // The "Any State" node could be dragged too, but its position is not stored.
                         let commands = entries
                             .iter()
+                            .filter(|e| {
+                                ui.node(e.node)
+                                    .has_component::<AbsmNode<State<Handle<N>>>>()
+                            })
                             .map(|e| {
                                 let state_handle = fetch_state_node_model_handle(e.node, ui);
                                 let new_position = ui.node(e.node).actual_local_position();
@@ -279,6 +320,7 @@ impl StateGraphViewer {
             layer_index,
             editor_selection,
         );
+        self.any_state_context_menu.handle_ui_message(message, ui);
         self.canvas_context_menu.handle_ui_message(
             sender,
             message,
@@ -310,6 +352,7 @@ impl StateGraphViewer {
         let current_selection = fetch_selection(editor_selection);
 
         let mut states = Vec::new();
+        let mut any_state = Handle::<AnyStateNode<N>>::NONE;
         let mut transitions = Vec::new();
         if self.prev_layer != current_selection.layer
             || current_selection.absm_node_handle != Handle::<N>::from(self.prev_absm)
@@ -327,6 +370,13 @@ impl StateGraphViewer {
                 .map(|c| c.to_variant::<AbsmNode<State<Handle<N>>>>())
                 .collect::<Vec<_>>();
 
+            any_state = canvas
+                .children()
+                .iter()
+                .find(|c| ui.node(**c).has_component::<AnyStateNode<N>>())
+                .map(|c| c.to_variant())
+                .unwrap_or_default();
+
             transitions = canvas
                 .children()
                 .iter()
@@ -434,6 +484,20 @@ impl StateGraphViewer {
             );
         }
 
+        if any_state.is_none() {
+            any_state = AbsmNodeBuilder::new(
+                WidgetBuilder::new()
+                    .with_context_menu(self.any_state_context_menu.menu.clone())
+                    .with_desired_position(any_state_node_position(machine_layer)),
+            )
+            .with_normal_brush(ui.style.property(AbsmEditor::NORMAL_ANY_STATE_COLOR))
+            .with_selected_brush(ui.style.property(AbsmEditor::SELECTED_ANY_STATE_COLOR))
+            .with_name("Any State".to_string())
+            .build(&mut ui.build_ctx());
+
+            ui.send_sync(any_state, WidgetMessage::link_with(self.canvas));
+        }
+
         // Force update layout to be able to fetch positions of nodes for transitions.
         ui.update_layout(ui.screen_size());
 
@@ -467,7 +531,11 @@ impl StateGraphViewer {
                             WidgetBuilder::new()
                                 .with_context_menu(self.transition_context_menu.menu.clone()),
                         )
-                        .with_source(find_state_view(transition.source(), &states, ui).to_base())
+                        .with_source(if transition.is_any_state() {
+                            any_state.to_base()
+                        } else {
+                            find_state_view(transition.source(), &states, ui).to_base()
+                        })
                         .with_dest(find_state_view(transition.dest(), &states, ui).to_base())
                         .build(transition_handle.into(), &mut ui.build_ctx());
 
@@ -249,7 +249,9 @@ fn make_shape_gizmo(
             ColliderShape::Triangle(_) => Box::new(TriangleShapeGizmo::new(root, visible, scene)),
             ColliderShape::Trimesh(_)
             | ColliderShape::Heightfield(_)
-            | ColliderShape::Polyhedron(_) => Box::new(DummyShapeGizmo),
+            | ColliderShape::Polyhedron(_)
+            | ColliderShape::Convex(_)
+            | ColliderShape::Compound(_) => Box::new(DummyShapeGizmo),
         }
     } else if let Ok(collider) = scene
         .graph
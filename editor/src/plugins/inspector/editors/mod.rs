@@ -22,6 +22,7 @@ use crate::{
     fyrox::{
         asset::{manager::ResourceManager, untyped::UntypedResource, Resource},
         core::{
+            algebra::Vector3,
             pool::{ErasedHandle, Handle},
             reflect::Reflect,
         },
@@ -87,7 +88,9 @@ use crate::{
             RenderPassDefinition, SamplerFallback, Shader, ShaderDefinition, ShaderResource,
             ShaderResourceDefinition, ShaderResourceKind,
         },
-        renderer::{BloomSettings, CsmSettings, QualitySettings, ShadowMapPrecision},
+        renderer::{
+            BloomSettings, CsmSettings, QualitySettings, RenderingPath, ShadowMapPrecision,
+        },
         renderer::{HdrSettings, LuminanceCalculationMethod},
         resource::texture::TextureKind,
         resource::{
@@ -108,20 +111,28 @@ use crate::{
                 Camera, ColorGradingLut, Exposure, OrthographicProjection, PerspectiveProjection,
                 Projection,
             },
+            cloth::{Cloth, ClothPin},
             collider::{
                 BallShape, BitMask, CapsuleShape, Collider, ColliderShape, ConeShape,
                 ConvexPolyhedronShape, CuboidShape, CylinderShape, GeometrySource,
                 HeightfieldShape, InteractionGroups, SegmentShape, TriangleShape, TrimeshShape,
             },
+            constraint::{
+                attach::AttachConstraint,
+                look_at::{LookAtAxisLock, LookAtConstraint},
+            },
             decal::Decal,
             dim2,
+            fog::{FogMode, FogSettings, HeightFog},
+            foliage::Foliage,
             graph::physics::CoefficientCombineRule,
             joint::*,
             light::{
+                area::{AreaLight, AreaLightShape},
                 directional::{CsmOptions, DirectionalLight, FrustumSplitOptions},
                 point::PointLight,
                 spot::SpotLight,
-                BaseLight,
+                BaseLight, ShadowMapSettings, ShadowUpdateCadence,
             },
             mesh::{
                 surface::{BlendShape, Surface, SurfaceResource},
@@ -140,7 +151,8 @@ use crate::{
             probe::UpdateMode,
             ragdoll::{Limb, Ragdoll},
             rigidbody::{RigidBody, RigidBodyMassPropertiesType, RigidBodyType},
-            skybox::SkyBox,
+            rope::Rope,
+            skybox::{ProceduralSky, SkyBox},
             sound::{
                 self,
                 filter::{
@@ -152,6 +164,8 @@ use crate::{
                 Attenuate, AudioBus, Biquad, DistanceModel, Effect, Sound, SoundBuffer,
                 SoundBufferResource, Status,
             },
+            spline::{Spline, SplineKind},
+            spline_follower::{SplineFollowMode, SplineFollower},
             sprite::Sprite,
             terrain::{Chunk, Layer, Terrain},
             tilemap::{
@@ -160,7 +174,7 @@ use crate::{
                 Tile, TileCollider, TileDefinitionHandle, TileMap,
             },
             transform::Transform,
-            EnvironmentLightingSource,
+            EnvironmentLightingSource, TransparencyMode,
         },
     },
     message::MessageSender,
@@ -241,7 +255,7 @@ where
             BasePoseNode,
         },
         state::{StateAction, StateActionWrapper},
-        transition::{AndNode, LogicNode, NotNode, OrNode, XorNode},
+        transition::{AndNode, LogicNode, NotNode, OrNode, WeightThresholdNode, XorNode},
         BlendAnimations, BlendAnimationsByIndex, BlendPose, IndexedBlendInput, Machine,
         PlayAnimation, PoseNode, PoseWeight, State,
     };
@@ -295,6 +309,7 @@ where
     container.insert(InspectablePropertyEditorDefinition::<OrNode<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<XorNode<Handle<T>>>::new());
     container.insert(InspectablePropertyEditorDefinition::<NotNode<Handle<T>>>::new());
+    container.insert(InspectablePropertyEditorDefinition::<WeightThresholdNode>::new());
 }
 
 macro_rules! reg_node_handle_editors {
@@ -352,6 +367,9 @@ pub fn make_property_editors_container(
     container.register_inheritable_vec_collection::<dim2::collider::GeometrySource>();
     container.register_inheritable_inspectable::<dim2::collider::GeometrySource>();
 
+    container.register_inheritable_vec_collection::<ClothPin>();
+    container.register_inheritable_inspectable::<ClothPin>();
+
     container.insert(make_status_enum_editor_definition());
 
     container.insert(InspectablePropertyEditorDefinition::<SceneNodeId>::new());
@@ -469,6 +487,9 @@ pub fn make_property_editors_container(
     container.register_inheritable_inspectable::<OrthographicProjection>();
     container.register_inheritable_inspectable::<Transform>();
     container.register_inheritable_inspectable::<CsmOptions>();
+    container.register_inheritable_inspectable::<ShadowMapSettings>();
+    container.register_inheritable_inspectable::<FogSettings>();
+    container.register_inheritable_inspectable::<HeightFog>();
     container.register_inheritable_inspectable::<HdrSettings>();
 
     container.register_inheritable_inspectable::<Chunk>();
@@ -482,6 +503,8 @@ pub fn make_property_editors_container(
     container.register_inheritable_option::<SkyBox>();
 
     container.register_inheritable_inspectable::<SkyBox>();
+    container.register_inheritable_option::<ProceduralSky>();
+    container.register_inheritable_inspectable::<ProceduralSky>();
 
     container.register_inheritable_enum::<dim2::collider::ColliderShape, _>();
     container.register_inheritable_enum::<CoefficientCombineRule, _>();
@@ -496,15 +519,22 @@ pub fn make_property_editors_container(
     container.register_inheritable_enum::<RigidBodyType, _>();
     container.register_inheritable_enum::<Exposure, _>();
     container.register_inheritable_enum::<FrustumSplitOptions, _>();
+    container.register_inheritable_enum::<AreaLightShape, _>();
+    container.register_inheritable_enum::<ShadowUpdateCadence, _>();
     container.register_inheritable_enum::<MaterialSearchOptions, _>();
     container.register_inheritable_enum::<DistanceModel, _>();
     container.register_inheritable_enum::<sound::Renderer, _>();
     container.register_inheritable_enum::<RenderPath, _>();
     container.register_inheritable_enum::<TexturePixelKind, _>();
     container.register_inheritable_enum::<EnvironmentLightingSource, _>();
+    container.register_inheritable_enum::<TransparencyMode, _>();
+    container.register_inheritable_enum::<FogMode, _>();
     container.register_inheritable_enum::<CoordinateSystem, _>();
     container.register_inheritable_enum::<UpdateMode, _>();
     container.register_inheritable_enum::<LuminanceCalculationMethod, _>();
+    container.register_inheritable_enum::<SplineKind, _>();
+    container.register_inheritable_enum::<SplineFollowMode, _>();
+    container.register_inheritable_enum::<LookAtAxisLock, _>();
 
     container.insert(EnumPropertyEditorDefinition::<Vec<ScriptRecord>>::new_optional());
     container.insert(VecCollectionPropertyEditorDefinition::<ScriptRecord>::new());
@@ -582,6 +612,8 @@ pub fn make_property_editors_container(
 
     container.register_inheritable_enum::<BatchingMode, _>();
 
+    container.register_inheritable_vec_collection::<Vector3<f32>>();
+
     container.register_inheritable_inspectable::<Tile>();
     container.register_inheritable_vec_collection::<Tile>();
 
@@ -656,6 +688,7 @@ pub fn make_property_editors_container(
     container.insert(InspectablePropertyEditorDefinition::<GraphicsSettings>::new());
     container.insert(InspectablePropertyEditorDefinition::<SelectionSettings>::new());
     container.insert(EnumPropertyEditorDefinition::<ShadowMapPrecision>::new());
+    container.insert(EnumPropertyEditorDefinition::<RenderingPath>::new());
     container.insert(EnumPropertyEditorDefinition::<ScriptEditor>::new());
     container.insert(EnumPropertyEditorDefinition::<EditorStyle>::new());
     container.insert(InspectablePropertyEditorDefinition::<DebuggingSettings>::new());
@@ -752,12 +785,20 @@ pub fn make_property_editors_container(
         SpotLight,
         DirectionalLight,
         PointLight,
+        AreaLight,
         Mesh,
         ParticleSystem,
         Sound,
         Listener,
         Terrain,
-        TileMap
+        TileMap,
+        Spline,
+        SplineFollower,
+        LookAtConstraint,
+        AttachConstraint,
+        Cloth,
+        Rope,
+        Foliage
     );
 
     container
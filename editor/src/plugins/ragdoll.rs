@@ -905,29 +905,37 @@ impl RagdollPreset {
         graph[ragdoll].root_limb.set_value_and_mark_modified(Limb {
             bone: self.hips,
             physical_bone: hips,
+            physics_driven: false,
             children: vec![
                 Limb {
                     bone: self.spine,
                     physical_bone: spine,
+                    physics_driven: false,
                     children: vec![Limb {
                         bone: self.spine1,
                         physical_bone: spine1,
+                        physics_driven: false,
                         children: vec![Limb {
                             bone: self.spine2,
                             physical_bone: spine2,
+                            physics_driven: false,
                             children: vec![
                                 Limb {
                                     bone: self.left_shoulder,
                                     physical_bone: left_shoulder,
+                                    physics_driven: false,
                                     children: vec![Limb {
                                         bone: self.left_arm,
                                         physical_bone: left_arm,
+                                        physics_driven: false,
                                         children: vec![Limb {
                                             bone: self.left_fore_arm,
                                             physical_bone: left_fore_arm,
+                                            physics_driven: false,
                                             children: vec![Limb {
                                                 bone: self.left_hand,
                                                 physical_bone: left_hand,
+                                                physics_driven: false,
                                                 children: vec![],
                                             }],
                                         }],
@@ -936,15 +944,19 @@ impl RagdollPreset {
                                 Limb {
                                     bone: self.right_shoulder,
                                     physical_bone: right_shoulder,
+                                    physics_driven: false,
                                     children: vec![Limb {
                                         bone: self.right_arm,
                                         physical_bone: right_arm,
+                                        physics_driven: false,
                                         children: vec![Limb {
                                             bone: self.right_fore_arm,
                                             physical_bone: right_fore_arm,
+                                            physics_driven: false,
                                             children: vec![Limb {
                                                 bone: self.right_hand,
                                                 physical_bone: right_hand,
+                                                physics_driven: false,
                                                 children: vec![],
                                             }],
                                         }],
@@ -953,9 +965,11 @@ impl RagdollPreset {
                                 Limb {
                                     bone: self.neck,
                                     physical_bone: neck,
+                                    physics_driven: false,
                                     children: vec![Limb {
                                         bone: self.head,
                                         physical_bone: head,
+                                        physics_driven: false,
                                         children: vec![],
                                     }],
                                 },
@@ -966,12 +980,15 @@ impl RagdollPreset {
                 Limb {
                     bone: self.left_up_leg,
                     physical_bone: left_up_leg,
+                    physics_driven: false,
                     children: vec![Limb {
                         bone: self.left_leg,
                         physical_bone: left_leg,
+                        physics_driven: false,
                         children: vec![Limb {
                             bone: self.left_foot,
                             physical_bone: left_foot,
+                            physics_driven: false,
                             children: vec![],
                         }],
                     }],
@@ -979,12 +996,15 @@ impl RagdollPreset {
                 Limb {
                     bone: self.right_up_leg,
                     physical_bone: right_up_leg,
+                    physics_driven: false,
                     children: vec![Limb {
                         bone: self.right_leg,
                         physical_bone: right_leg,
+                        physics_driven: false,
                         children: vec![Limb {
                             bone: self.right_foot,
                             physical_bone: right_foot,
+                            physics_driven: false,
                             children: vec![],
                         }],
                     }],
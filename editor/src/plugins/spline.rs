@@ -0,0 +1,386 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+use crate::{
+    camera::PickingOptions,
+    command::SetPropertyCommand,
+    fyrox::{
+        core::{
+            algebra::{Vector2, Vector3},
+            color::Color,
+            math::Matrix4Ext,
+            pool::Handle,
+            reflect::Reflect,
+            some_or_return,
+            type_traits::prelude::*,
+            Uuid,
+        },
+        engine::Engine,
+        graph::SceneGraph,
+        gui::{button::Button, BuildContext},
+        material::{
+            shader::{ShaderResource, ShaderResourceExtension},
+            Material, MaterialResource,
+        },
+        scene::{
+            base::BaseBuilder,
+            node::Node,
+            pivot::Pivot,
+            spline::Spline,
+            sprite::{Sprite, SpriteBuilder},
+            Scene,
+        },
+    },
+    interaction::{
+        calculate_gizmo_distance_scaling, gizmo::move_gizmo::MoveGizmo,
+        make_interaction_mode_button, plane::PlaneKind, InteractionMode,
+    },
+    load_texture,
+    message::MessageSender,
+    plugin::EditorPlugin,
+    scene::{commands::GameSceneContext, controller::SceneController, GameScene, Selection},
+    settings::Settings,
+    Editor, Message,
+};
+use std::sync::LazyLock;
+
+static GIZMO_SHADER: LazyLock<ShaderResource> = LazyLock::new(|| {
+    ShaderResource::from_str(
+        Uuid::new_v4(),
+        include_str!("../../resources/shaders/sprite_gizmo.shader"),
+        Default::default(),
+    )
+    .unwrap()
+});
+
+fn make_handle(scene: &mut Scene, root: Handle<Pivot>) -> Handle<Sprite> {
+    let mut material = Material::from_shader(GIZMO_SHADER.clone());
+
+    material.bind(
+        "diffuseTexture",
+        load_texture!("../../resources/circle.png"),
+    );
+
+    let handle = SpriteBuilder::new(BaseBuilder::new())
+        .with_material(MaterialResource::new_embedded(material))
+        .with_size(0.05)
+        .with_color(Color::MAROON)
+        .build(&mut scene.graph);
+
+    scene.graph.link_nodes(handle, root);
+
+    handle
+}
+
+struct DragContext {
+    initial_points: Vec<Vector3<f32>>,
+    new_position: Vector3<f32>,
+    plane_kind: PlaneKind,
+}
+
+#[derive(TypeUuidProvider)]
+#[type_uuid(id = "0c4f3e1a-7d52-4b8e-9a61-3e5b2c7f9d14")]
+pub struct SplineInteractionMode {
+    spline: Handle<Spline>,
+    root: Handle<Pivot>,
+    handles: Vec<Handle<Sprite>>,
+    selected_point: Option<usize>,
+    move_gizmo: MoveGizmo,
+    message_sender: MessageSender,
+    drag_context: Option<DragContext>,
+}
+
+impl SplineInteractionMode {
+    fn destroy(self, scene: &mut Scene) {
+        for handle in self.handles {
+            scene.graph.remove_node(handle);
+        }
+        self.move_gizmo.destroy(&mut scene.graph)
+    }
+
+    fn set_visible(&self, controller: &dyn SceneController, engine: &mut Engine, visible: bool) {
+        let game_scene = some_or_return!(controller.downcast_ref::<GameScene>());
+        let scene = &mut engine.scenes[game_scene.scene];
+        for handle in self.handles.iter() {
+            scene.graph[*handle].set_visibility(visible);
+        }
+        self.move_gizmo
+            .set_visible(&mut scene.graph, visible && self.selected_point.is_some());
+    }
+
+    /// Keeps the amount of handles in sync with the amount of control points, which could be
+    /// changed from the inspector.
+    fn sync_handles(&mut self, scene: &mut Scene) {
+        let count = scene.graph[self.spline].control_points().len();
+        while self.handles.len() < count {
+            let handle = make_handle(scene, self.root);
+            self.handles.push(handle);
+        }
+        while self.handles.len() > count {
+            if let Some(handle) = self.handles.pop() {
+                scene.graph.remove_node(handle);
+            }
+        }
+        if self.selected_point.is_some_and(|i| i >= count) {
+            self.selected_point = None;
+        }
+    }
+
+    fn pick(
+        &self,
+        game_scene: &GameScene,
+        scene: &Scene,
+        mouse_position: Vector2<f32>,
+        settings: &Settings,
+    ) -> Option<Handle<Node>> {
+        game_scene
+            .camera_controller
+            .pick(
+                &scene.graph,
+                PickingOptions {
+                    cursor_pos: mouse_position,
+                    editor_only: true,
+                    filter: Some(&mut |handle, _| handle != self.move_gizmo.origin),
+                    ignore_back_faces: false,
+                    use_picking_loop: false,
+                    method: Default::default(),
+                    settings: &settings.selection,
+                },
+            )
+            .map(|result| result.node)
+    }
+}
+
+impl InteractionMode for SplineInteractionMode {
+    fn on_left_mouse_button_down(
+        &mut self,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        mouse_position: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        settings: &Settings,
+    ) {
+        let game_scene = some_or_return!(controller.downcast_mut::<GameScene>());
+        let scene = &mut engine.scenes[game_scene.scene];
+
+        let node = some_or_return!(self.pick(game_scene, scene, mouse_position, settings));
+
+        if let Some(index) = self.handles.iter().position(|h| *h == node) {
+            self.selected_point = Some(index);
+        } else if let Some(plane_kind) = self.move_gizmo.handle_pick(node, &mut scene.graph) {
+            let index = some_or_return!(self.selected_point);
+            let spline = &scene.graph[self.spline];
+            let initial_points = spline.control_points().to_vec();
+            self.drag_context = Some(DragContext {
+                new_position: spline
+                    .global_transform()
+                    .transform_point(&initial_points[index].into())
+                    .coords,
+                initial_points,
+                plane_kind,
+            })
+        }
+    }
+
+    fn on_left_mouse_button_up(
+        &mut self,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        _mouse_pos: Vector2<f32>,
+        _frame_size: Vector2<f32>,
+        _settings: &Settings,
+    ) {
+        let game_scene = some_or_return!(controller.downcast_mut::<GameScene>());
+        let scene = &mut engine.scenes[game_scene.scene];
+
+        let drag_context = some_or_return!(self.drag_context.take());
+        let spline = self.spline;
+        // Revert the points to their initial state, the command will set the new ones and will
+        // remember the old ones for undo.
+        let new_points = scene.graph[spline].set_control_points(drag_context.initial_points);
+        let command = SetPropertyCommand::new(
+            Spline::CONTROL_POINTS.into(),
+            Box::new(new_points),
+            move |ctx| {
+                ctx.get_mut::<GameSceneContext>()
+                    .scene
+                    .graph
+                    .try_get_node_mut(spline.transmute())
+                    .ok()
+                    .map(|n| n as &mut dyn Reflect)
+            },
+        );
+        self.message_sender.do_command(command);
+    }
+
+    fn on_mouse_move(
+        &mut self,
+        mouse_offset: Vector2<f32>,
+        mouse_position: Vector2<f32>,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        frame_size: Vector2<f32>,
+        settings: &Settings,
+    ) {
+        let game_scene = some_or_return!(controller.downcast_mut::<GameScene>());
+        let scene = &mut engine.scenes[game_scene.scene];
+
+        self.move_gizmo.reset_state(&mut scene.graph);
+        if let Some(node) = self.pick(game_scene, scene, mouse_position, settings) {
+            self.move_gizmo.handle_pick(node, &mut scene.graph);
+        }
+
+        let index = some_or_return!(self.selected_point);
+        if let Some(drag_context) = self.drag_context.as_mut() {
+            let global_offset = self.move_gizmo.calculate_offset(
+                &scene.graph,
+                game_scene.camera_controller.camera,
+                mouse_offset,
+                mouse_position,
+                frame_size,
+                drag_context.plane_kind,
+            );
+            drag_context.new_position += global_offset;
+
+            let spline = &mut scene.graph[self.spline];
+            let local_position = spline
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_default()
+                .transform_point(&drag_context.new_position.into())
+                .coords;
+            if let Some(point) = spline.control_points_mut().get_mut(index) {
+                *point = local_position;
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        _editor_selection: &Selection,
+        controller: &mut dyn SceneController,
+        engine: &mut Engine,
+        _settings: &Settings,
+    ) {
+        let game_scene = some_or_return!(controller.downcast_mut::<GameScene>());
+        let scene = &mut engine.scenes[game_scene.scene];
+
+        self.sync_handles(scene);
+
+        let spline = &scene.graph[self.spline];
+        let transform = spline.global_transform();
+        let positions = spline
+            .control_points()
+            .iter()
+            .map(|p| transform.transform_point(&(*p).into()).coords)
+            .collect::<Vec<_>>();
+
+        for (index, (handle, position)) in self.handles.iter().zip(positions.iter()).enumerate() {
+            let color = if Some(index) == self.selected_point {
+                Color::GREEN
+            } else {
+                Color::MAROON
+            };
+            let sprite = &mut scene.graph[*handle];
+            sprite.set_color(color);
+            sprite.local_transform_mut().set_position(*position);
+        }
+
+        let selected_position = self.selected_point.and_then(|i| positions.get(i).cloned());
+        self.move_gizmo
+            .set_visible(&mut scene.graph, selected_position.is_some());
+        if let Some(position) = selected_position {
+            let scale = calculate_gizmo_distance_scaling(
+                &scene.graph,
+                game_scene.camera_controller.camera,
+                self.move_gizmo.origin,
+            );
+            self.move_gizmo
+                .transform(&mut scene.graph)
+                .set_position(position)
+                .set_scale(scale);
+        }
+    }
+
+    fn activate(&mut self, controller: &dyn SceneController, engine: &mut Engine) {
+        self.set_visible(controller, engine, true);
+    }
+
+    fn deactivate(&mut self, controller: &dyn SceneController, engine: &mut Engine) {
+        self.set_visible(controller, engine, false);
+    }
+
+    fn make_button(&mut self, ctx: &mut BuildContext, selected: bool) -> Handle<Button> {
+        make_interaction_mode_button(
+            ctx,
+            include_bytes!("../../resources/triangle.png"),
+            "Edit Spline",
+            selected,
+        )
+    }
+
+    fn uuid(&self) -> Uuid {
+        Self::type_uuid()
+    }
+}
+
+#[derive(Default)]
+pub struct SplinePlugin;
+
+impl EditorPlugin for SplinePlugin {
+    fn on_message(&mut self, message: &Message, editor: &mut Editor) {
+        let entry = editor.scenes.current_scene_entry_mut();
+        let game_scene = some_or_return!(entry.controller.downcast_mut::<GameScene>());
+
+        if let Message::SelectionChanged { .. } = message {
+            let scene = &mut editor.engine.scenes[game_scene.scene];
+
+            if let Some(mode) = entry
+                .interaction_modes
+                .remove_typed::<SplineInteractionMode>()
+            {
+                mode.destroy(scene);
+            }
+
+            let selected_spline = entry.selection.as_graph().and_then(|s| {
+                s.nodes()
+                    .iter()
+                    .find(|h| scene.graph.has_component::<Spline>(**h))
+                    .cloned()
+            });
+
+            if let Some(selected_spline) = selected_spline {
+                let mut mode = SplineInteractionMode {
+                    spline: selected_spline.transmute(),
+                    root: game_scene.editor_objects_root,
+                    handles: Default::default(),
+                    selected_point: None,
+                    move_gizmo: MoveGizmo::new(game_scene, &mut editor.engine),
+                    message_sender: editor.message_sender.clone(),
+                    drag_context: None,
+                };
+                mode.sync_handles(&mut editor.engine.scenes[game_scene.scene]);
+                entry.interaction_modes.add(mode);
+            }
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Batch transform operations (align, distribute, randomize) over multiple scene nodes. Every
+//! operation produces a group of undoable commands, that should be sent to the command stack.
+
+use crate::{
+    command::CommandGroup,
+    fyrox::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+            rand::{rngs::StdRng, Rng, SeedableRng},
+        },
+        graph::SceneGraph,
+        scene::{graph::Graph, node::Node},
+    },
+    scene::commands::graph::{MoveNodeCommand, RotateNodeCommand},
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    fn index(self) -> usize {
+        match self {
+            Axis::X => 0,
+            Axis::Y => 1,
+            Axis::Z => 2,
+        }
+    }
+}
+
+/// Calculates a new local position of a node, that will move the node to the given global position.
+fn local_position_for(
+    graph: &Graph,
+    node: Handle<Node>,
+    global_position: Vector3<f32>,
+) -> Vector3<f32> {
+    let node_ref = &graph[node];
+    let offset = global_position - node_ref.global_position();
+    let local_offset = graph
+        .try_get_node(node_ref.parent())
+        .ok()
+        .and_then(|parent| parent.global_transform().try_inverse())
+        .map_or(offset, |inv_parent| inv_parent.transform_vector(&offset));
+    **node_ref.local_transform().position() + local_offset
+}
+
+fn valid_nodes(graph: &Graph, nodes: &[Handle<Node>]) -> Vec<Handle<Node>> {
+    nodes
+        .iter()
+        .filter(|node| graph.is_valid_handle(**node))
+        .cloned()
+        .collect()
+}
+
+/// Returns valid nodes, that have no ancestors in the given set. Descendants follow their moved
+/// ancestors, so moving them explicitly would shift them twice.
+fn top_level_nodes(graph: &Graph, nodes: &[Handle<Node>]) -> Vec<Handle<Node>> {
+    let nodes = valid_nodes(graph, nodes);
+    nodes
+        .iter()
+        .filter(|node| {
+            let mut parent = graph[**node].parent();
+            while let Ok(parent_ref) = graph.try_get_node(parent) {
+                if nodes.contains(&parent) {
+                    return false;
+                }
+                parent = parent_ref.parent();
+            }
+            true
+        })
+        .cloned()
+        .collect()
+}
+
+/// Calculates new local positions of the nodes, that places all the nodes at the average global
+/// coordinate along the given axis.
+fn aligned_positions(
+    graph: &Graph,
+    nodes: &[Handle<Node>],
+    axis: Axis,
+) -> Vec<(Handle<Node>, Vector3<f32>)> {
+    let nodes = top_level_nodes(graph, nodes);
+    if nodes.is_empty() {
+        return Default::default();
+    }
+
+    let i = axis.index();
+    let average = nodes
+        .iter()
+        .map(|node| graph[*node].global_position()[i])
+        .sum::<f32>()
+        / nodes.len() as f32;
+
+    nodes
+        .iter()
+        .map(|node| {
+            let mut position = graph[*node].global_position();
+            position[i] = average;
+            (*node, local_position_for(graph, *node, position))
+        })
+        .collect()
+}
+
+/// Calculates new local positions of the nodes, that spreads the nodes evenly along the given
+/// axis between the two outermost nodes.
+fn distributed_positions(
+    graph: &Graph,
+    nodes: &[Handle<Node>],
+    axis: Axis,
+) -> Vec<(Handle<Node>, Vector3<f32>)> {
+    let mut nodes = top_level_nodes(graph, nodes);
+    if nodes.len() < 2 {
+        return Default::default();
+    }
+
+    let i = axis.index();
+    nodes.sort_by(|a, b| graph[*a].global_position()[i].total_cmp(&graph[*b].global_position()[i]));
+    let first = graph[nodes[0]].global_position()[i];
+    let last = graph[*nodes.last().unwrap()].global_position()[i];
+    let step = (last - first) / (nodes.len() - 1) as f32;
+
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(n, node)| {
+            let mut position = graph[*node].global_position();
+            position[i] = first + step * n as f32;
+            (*node, local_position_for(graph, *node, position))
+        })
+        .collect()
+}
+
+fn make_move_commands(
+    graph: &Graph,
+    positions: Vec<(Handle<Node>, Vector3<f32>)>,
+    name: &str,
+) -> Option<CommandGroup> {
+    let mut group = CommandGroup::default().with_custom_name(name);
+    for (node, new_position) in positions {
+        let old_position = **graph[node].local_transform().position();
+        if old_position != new_position {
+            group.push(MoveNodeCommand::new(node, old_position, new_position));
+        }
+    }
+    (!group.is_empty()).then_some(group)
+}
+
+/// Moves all the nodes to the average global coordinate along the given axis. Returns `None` if
+/// there's nothing to change.
+pub fn align_to_axis(graph: &Graph, nodes: &[Handle<Node>], axis: Axis) -> Option<CommandGroup> {
+    make_move_commands(graph, aligned_positions(graph, nodes, axis), "Align Nodes")
+}
+
+/// Spreads the nodes evenly along the given axis, the two outermost nodes stay in place. Returns
+/// `None` if there's nothing to change (for example, when there's only one node).
+pub fn distribute_evenly(
+    graph: &Graph,
+    nodes: &[Handle<Node>],
+    axis: Axis,
+) -> Option<CommandGroup> {
+    make_move_commands(
+        graph,
+        distributed_positions(graph, nodes, axis),
+        "Distribute Nodes",
+    )
+}
+
+/// Rotates every node by a random angle around each of its local axes. `range` defines the maximum
+/// deviation (in degrees) for every axis, the actual angles are picked in `[-range, range]` range.
+/// The same `seed` produces the same rotations. Returns `None` if there's nothing to change.
+pub fn randomize_rotation(
+    graph: &Graph,
+    nodes: &[Handle<Node>],
+    range: Vector3<f32>,
+    seed: u64,
+) -> Option<CommandGroup> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut random_angle = |range: f32| {
+        let range = range.abs().to_radians();
+        rng.gen_range(-range..=range)
+    };
+
+    let mut group = CommandGroup::default().with_custom_name("Randomize Rotation");
+    for node in valid_nodes(graph, nodes) {
+        let old_rotation = **graph[node].local_transform().rotation();
+        let offset = UnitQuaternion::from_euler_angles(
+            random_angle(range.x),
+            random_angle(range.y),
+            random_angle(range.z),
+        );
+        let new_rotation = old_rotation * offset;
+        if old_rotation != new_rotation {
+            group.push(RotateNodeCommand::new(node, old_rotation, new_rotation));
+        }
+    }
+    (!group.is_empty()).then_some(group)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fyrox::scene::{
+        base::BaseBuilder, pivot::PivotBuilder, transform::TransformBuilder,
+    };
+
+    fn make_node(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .build(graph)
+        .to_base()
+    }
+
+    #[test]
+    fn test_align_to_axis() {
+        let mut graph = Graph::new();
+        let a = make_node(&mut graph, Vector3::new(1.0, 0.0, 0.0));
+        let b = make_node(&mut graph, Vector3::new(2.0, 5.0, 0.0));
+        let c = make_node(&mut graph, Vector3::new(6.0, 0.0, 3.0));
+        // A child of a scaled parent, to check conversion to local coordinates.
+        let parent = make_node(&mut graph, Vector3::new(1.0, 0.0, 0.0));
+        graph[parent]
+            .local_transform_mut()
+            .set_scale(Vector3::repeat(2.0));
+        let d = make_node(&mut graph, Vector3::new(1.0, 1.0, 0.0));
+        graph.link_nodes(d, parent);
+        graph.update_hierarchical_data();
+
+        let nodes = [a, b, c, d];
+        // Global X coordinates are 1, 2, 6 and 3.
+        let average = 3.0;
+        for (node, position) in aligned_positions(&graph, &nodes, Axis::X) {
+            graph[node].local_transform_mut().set_position(position);
+        }
+        graph.update_hierarchical_data();
+
+        for node in nodes {
+            assert!((graph[node].global_position().x - average).abs() < 1e-5);
+        }
+        assert_eq!(graph[b].global_position().y, 5.0);
+        assert_eq!(graph[c].global_position().z, 3.0);
+
+        // Everything is aligned already.
+        assert!(align_to_axis(&graph, &nodes, Axis::X).is_none());
+        assert!(align_to_axis(&graph, &nodes, Axis::Y).is_some());
+    }
+
+    #[test]
+    fn test_distribute_evenly() {
+        let mut graph = Graph::new();
+        let a = make_node(&mut graph, Vector3::new(0.0, 0.0, 0.0));
+        let b = make_node(&mut graph, Vector3::new(0.0, 8.0, 0.0));
+        let c = make_node(&mut graph, Vector3::new(0.0, 1.0, 0.0));
+        graph.update_hierarchical_data();
+
+        assert!(distribute_evenly(&graph, &[a], Axis::Y).is_none());
+
+        let positions = distributed_positions(&graph, &[a, b, c], Axis::Y);
+        assert_eq!(
+            positions,
+            vec![
+                (a, Vector3::new(0.0, 0.0, 0.0)),
+                (c, Vector3::new(0.0, 4.0, 0.0)),
+                (b, Vector3::new(0.0, 8.0, 0.0)),
+            ]
+        );
+        assert_eq!(
+            distribute_evenly(&graph, &[a, b, c], Axis::Y).map(|group| group.len()),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_selected_descendants_are_moved_once() {
+        let mut graph = Graph::new();
+        let a = make_node(&mut graph, Vector3::new(0.0, 0.0, 0.0));
+        let b = make_node(&mut graph, Vector3::new(4.0, 0.0, 0.0));
+        let child = make_node(&mut graph, Vector3::new(1.0, 0.0, 0.0));
+        let grandchild = make_node(&mut graph, Vector3::new(1.0, 0.0, 0.0));
+        graph.link_nodes(child, b);
+        graph.link_nodes(grandchild, child);
+        graph.update_hierarchical_data();
+
+        let nodes = [a, b, child, grandchild];
+        let positions = aligned_positions(&graph, &nodes, Axis::X);
+        assert_eq!(
+            positions,
+            vec![
+                (a, Vector3::new(2.0, 0.0, 0.0)),
+                (b, Vector3::new(2.0, 0.0, 0.0)),
+            ]
+        );
+        for (node, position) in positions {
+            graph[node].local_transform_mut().set_position(position);
+        }
+        graph.update_hierarchical_data();
+        assert_eq!(graph[child].global_position().x, 3.0);
+        assert_eq!(graph[grandchild].global_position().x, 4.0);
+
+        assert_eq!(
+            distributed_positions(&graph, &nodes, Axis::X)
+                .into_iter()
+                .map(|(node, _)| node)
+                .collect::<Vec<_>>(),
+            vec![a, b]
+        );
+    }
+
+    #[test]
+    fn test_randomize_rotation_is_deterministic() {
+        let mut graph = Graph::new();
+        let a = make_node(&mut graph, Vector3::default());
+
+        assert!(randomize_rotation(&graph, &[a], Vector3::default(), 1).is_none());
+        assert_eq!(
+            randomize_rotation(&graph, &[a], Vector3::new(0.0, 180.0, 0.0), 1)
+                .map(|group| group.len()),
+            Some(1)
+        );
+    }
+}
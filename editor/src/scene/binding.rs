@@ -0,0 +1,322 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Two-way binding between text fields and numeric properties of scene nodes. See
+//! [`NumericFieldBinding`] docs for more info.
+
+use crate::{
+    command::Command,
+    fyrox::{
+        core::pool::Handle,
+        graph::SceneGraph,
+        gui::{message::UiMessage, text::TextMessage, text_box::TextBox, UserInterface},
+        scene::{graph::Graph, node::Node},
+    },
+    message::MessageSender,
+    scene::commands::graph::MoveNodeCommand,
+};
+
+type Getter = Box<dyn Fn(&Node) -> f32>;
+type CommandMaker = Box<dyn Fn(Handle<Node>, &Node, f32) -> Command>;
+
+/// Two-way binding between a text box and a numeric property of a scene node. Edits of the text
+/// box are validated, clamped and sent to the command stack as commands, so they could be undone.
+/// External changes of the node are written back to the text box by [`Self::sync`].
+///
+/// Text boxes report every change of their text (depending on the commit mode), including the
+/// changes made by the binding itself. The binding remembers the text it has sent to the text
+/// box and ignores its echo, so programmatic updates never cause an edit of the node.
+pub struct NumericFieldBinding {
+    /// A node, which property is bound.
+    pub node: Handle<Node>,
+    /// A text box, that is used to edit the property.
+    pub field: Handle<TextBox>,
+    /// Minimal value of the property.
+    pub min: f32,
+    /// Maximal value of the property.
+    pub max: f32,
+    getter: Getter,
+    command_maker: CommandMaker,
+    value: Option<f32>,
+    pending_text: Option<String>,
+}
+
+impl NumericFieldBinding {
+    /// Creates a new binding with the given getter of the property. `command_maker` must create a
+    /// command, that sets the property of the given node to the given value.
+    pub fn new(
+        node: Handle<Node>,
+        field: Handle<TextBox>,
+        getter: impl Fn(&Node) -> f32 + 'static,
+        command_maker: impl Fn(Handle<Node>, &Node, f32) -> Command + 'static,
+    ) -> Self {
+        Self {
+            node,
+            field,
+            min: f32::MIN,
+            max: f32::MAX,
+            getter: Box::new(getter),
+            command_maker: Box::new(command_maker),
+            value: None,
+            pending_text: None,
+        }
+    }
+
+    /// Creates a binding for a component (0 - X, 1 - Y, 2 - Z) of the local position of the node.
+    /// Returns [`None`] if the component index is out of bounds.
+    pub fn local_position(
+        node: Handle<Node>,
+        field: Handle<TextBox>,
+        component: usize,
+    ) -> Option<Self> {
+        if component > 2 {
+            return None;
+        }
+
+        Some(Self::new(
+            node,
+            field,
+            move |node| node.local_transform().position()[component],
+            move |handle, node, value| {
+                let old_position = **node.local_transform().position();
+                let mut new_position = old_position;
+                new_position[component] = value;
+                Command::new(MoveNodeCommand::new(handle, old_position, new_position))
+            },
+        ))
+    }
+
+    /// Sets the range of valid values, any value out of the range will be clamped.
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Returns the last value, that was synchronized between the field and the node.
+    pub fn value(&self) -> Option<f32> {
+        self.value
+    }
+
+    fn send_value(&mut self, value: f32, ui: &UserInterface) {
+        let text = value.to_string();
+        self.pending_text = Some(text.clone());
+        ui.send(self.field, TextMessage::Text(text));
+    }
+
+    /// Handles a message of the user interface. Returns `true` if the message has produced a
+    /// command, that changes the property of the node.
+    pub fn handle_ui_message(
+        &mut self,
+        message: &UiMessage,
+        ui: &UserInterface,
+        graph: &Graph,
+        sender: &MessageSender,
+    ) -> bool {
+        let Some(TextMessage::Text(text)) = message.data_from::<TextMessage>(self.field) else {
+            return false;
+        };
+
+        if self.pending_text.as_ref() == Some(text) {
+            // Echo of a programmatic update.
+            self.pending_text = None;
+            return false;
+        }
+
+        let Ok(node) = graph.try_get(self.node) else {
+            return false;
+        };
+
+        match text.trim().parse::<f32>() {
+            Ok(value) if value.is_finite() => {
+                let clamped = value.clamp(self.min, self.max);
+                sender.send_command((self.command_maker)(self.node, node, clamped));
+                self.value = Some(clamped);
+                if clamped != value {
+                    self.send_value(clamped, ui);
+                }
+                true
+            }
+            _ => {
+                // Revert the invalid input.
+                let value = self.value.unwrap_or_else(|| (self.getter)(node));
+                self.send_value(value, ui);
+                false
+            }
+        }
+    }
+
+    /// Writes the value of the property to the text box if it has changed since the last
+    /// synchronization. Should be called every frame or when the node is known to be changed.
+    pub fn sync(&mut self, ui: &UserInterface, graph: &Graph) {
+        let Ok(node) = graph.try_get(self.node) else {
+            return;
+        };
+        let value = (self.getter)(node);
+        if self.value != Some(value) {
+            self.value = Some(value);
+            self.send_value(value, ui);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        command::Command,
+        fyrox::{
+            asset::{io::FsResourceIo, manager::ResourceManager},
+            core::{
+                algebra::{Vector2, Vector3},
+                pool::Handle,
+                task::TaskPool,
+            },
+            engine::SerializationContext,
+            gui::{
+                text::TextMessage,
+                text_box::{TextBoxBuilder, TextCommitMode},
+                widget::WidgetBuilder,
+                UserInterface,
+            },
+            scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+        },
+        message::{Message, MessageSender},
+        scene::{
+            binding::NumericFieldBinding, clipboard::Clipboard, commands::GameSceneContext,
+            Selection,
+        },
+    };
+    use std::sync::{
+        mpsc::{channel, Receiver},
+        Arc,
+    };
+
+    struct Env {
+        ui: UserInterface,
+        scene: Scene,
+        sender: MessageSender,
+        receiver: Receiver<Message>,
+        history: Vec<Command>,
+    }
+
+    impl Env {
+        fn with_command_context(&mut self, func: impl FnOnce(&mut GameSceneContext)) {
+            GameSceneContext::exec(
+                &mut Selection::default(),
+                &mut self.scene,
+                &mut Handle::NONE,
+                &mut Clipboard::default(),
+                self.sender.clone(),
+                ResourceManager::new(Arc::new(FsResourceIo), Arc::new(TaskPool::new())),
+                Arc::new(SerializationContext::new()),
+                func,
+            );
+        }
+
+        // Handles UI messages and executes the commands produced by the binding. Returns the
+        // amount of commands.
+        fn pump(&mut self, binding: &mut NumericFieldBinding) -> usize {
+            while let Some(message) = self.ui.poll_message() {
+                binding.handle_ui_message(&message, &self.ui, &self.scene.graph, &self.sender);
+            }
+            self.ui
+                .update(self.ui.screen_size(), 1.0 / 60.0, &Default::default());
+
+            let mut count = 0;
+            while let Ok(message) = self.receiver.try_recv() {
+                if let Message::DoCommand(mut command) = message {
+                    self.with_command_context(|ctx| command.0.execute(ctx));
+                    self.history.push(command);
+                    count += 1;
+                }
+            }
+            count
+        }
+
+        fn undo(&mut self) {
+            let mut command = self.history.pop().unwrap();
+            self.with_command_context(|ctx| command.0.revert(ctx));
+        }
+    }
+
+    #[test]
+    fn test_local_position_component_out_of_bounds() {
+        assert!(NumericFieldBinding::local_position(Handle::NONE, Handle::NONE, 2).is_some());
+        assert!(NumericFieldBinding::local_position(Handle::NONE, Handle::NONE, 3).is_none());
+    }
+
+    #[test]
+    fn test_two_way_binding() {
+        let (sender, receiver) = channel();
+        let mut env = Env {
+            ui: UserInterface::new(Vector2::repeat(1000.0)),
+            scene: Scene::new(),
+            sender: MessageSender(sender),
+            receiver,
+            history: Default::default(),
+        };
+        let field = TextBoxBuilder::new(WidgetBuilder::new())
+            .with_text_commit_mode(TextCommitMode::Immediate)
+            .build(&mut env.ui.build_ctx());
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut env.scene.graph);
+        let mut binding = NumericFieldBinding::local_position(node.to_base(), field, 0)
+            .unwrap()
+            .with_range(-10.0, 10.0);
+        let x = |env: &Env| env.scene.graph[node].local_transform().position().x;
+
+        // Initial sync must not produce an edit.
+        binding.sync(&env.ui, &env.scene.graph);
+        assert_eq!(env.pump(&mut binding), 0);
+        assert_eq!(env.ui[field].text(), "0");
+
+        // Edit the field.
+        env.ui.send(field, TextMessage::Text("5".to_string()));
+        assert_eq!(env.pump(&mut binding), 1);
+        assert_eq!(x(&env), 5.0);
+
+        // Out of range values are clamped.
+        env.ui.send(field, TextMessage::Text("50".to_string()));
+        assert_eq!(env.pump(&mut binding), 1);
+        assert_eq!(x(&env), 10.0);
+        assert_eq!(env.ui[field].text(), "10");
+
+        // Invalid input is reverted.
+        env.ui.send(field, TextMessage::Text("abc".to_string()));
+        assert_eq!(env.pump(&mut binding), 0);
+        assert_eq!(x(&env), 10.0);
+        assert_eq!(env.ui[field].text(), "10");
+
+        // Edits could be undone, the field follows the node.
+        env.undo();
+        assert_eq!(x(&env), 5.0);
+        binding.sync(&env.ui, &env.scene.graph);
+        assert_eq!(env.pump(&mut binding), 0);
+        assert_eq!(env.ui[field].text(), "5");
+
+        // External change of the node refreshes the field.
+        env.scene.graph[node]
+            .local_transform_mut()
+            .set_position(Vector3::new(3.0, 0.0, 0.0));
+        binding.sync(&env.ui, &env.scene.graph);
+        assert_eq!(env.pump(&mut binding), 0);
+        assert_eq!(env.ui[field].text(), "3");
+        assert_eq!(x(&env), 3.0);
+    }
+}
@@ -58,7 +58,7 @@ use crate::{
             camera::{Camera, Projection},
             debug::{Line, SceneDrawingContext},
             graph::{Graph, GraphUpdateSwitches},
-            light::{point::PointLight, spot::SpotLight},
+            light::{area::AreaLight, point::PointLight, spot::SpotLight},
             mesh::RenderPath,
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
@@ -113,6 +113,8 @@ use std::{
     },
 };
 
+pub mod arrange;
+pub mod binding;
 pub mod clipboard;
 pub mod dialog;
 mod nullscene;
@@ -200,6 +202,11 @@ impl GameScene {
         .with_render_path(RenderPath::Forward)
         .build(&mut scene.graph);
 
+        // Nodes could be deleted outside the editor, so remove bookmarks that point to nowhere.
+        if let Some(scene_settings) = path.and_then(|p| settings.scene_settings.get_mut(p)) {
+            scene_settings.prune_bookmarks(&scene.graph);
+        }
+
         let (settings_sender, settings_receiver) = mpsc::channel();
         settings.subscribers.push(settings_sender);
 
@@ -358,6 +365,7 @@ impl GameScene {
                 }
             } else if node.component_ref::<PointLight>().is_some()
                 || node.component_ref::<SpotLight>().is_some()
+                || node.component_ref::<AreaLight>().is_some()
             {
                 if settings.debugging.show_light_bounds {
                     node.debug_draw(ctx);
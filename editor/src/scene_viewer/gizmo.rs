@@ -18,7 +18,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::camera::CameraController;
+use crate::camera::{read_triangle, CameraController};
 use crate::fyrox::{
     core::{
         algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
@@ -48,11 +48,89 @@ use fyrox::scene::mesh::Mesh;
 use fyrox::scene::pivot::Pivot;
 use fyrox::scene::{EnvironmentLightingSource, SceneContainer};
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct CameraRotation {
     pub yaw: f32,
     pub pitch: f32,
 }
 
+/// A part of the scene gizmo. Directions of edges and corners are defined by three components,
+/// each of which is either -1, 0 or 1. Edges have exactly one zero component, corners have none.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GizmoAxis {
+    Center,
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+    Edge(Vector3<i8>),
+    Corner(Vector3<i8>),
+}
+
+impl GizmoAxis {
+    /// Returns a direction from the center of the gizmo to the part. The direction is not
+    /// normalized, it is a zero vector for the center.
+    pub fn direction(&self) -> Vector3<f32> {
+        match self {
+            GizmoAxis::Center => Vector3::default(),
+            GizmoAxis::PosX => Vector3::x(),
+            GizmoAxis::NegX => -Vector3::x(),
+            GizmoAxis::PosY => Vector3::y(),
+            GizmoAxis::NegY => -Vector3::y(),
+            GizmoAxis::PosZ => Vector3::z(),
+            GizmoAxis::NegZ => -Vector3::z(),
+            GizmoAxis::Edge(direction) | GizmoAxis::Corner(direction) => direction.cast::<f32>(),
+        }
+    }
+
+    /// Returns a rotation of the camera, that looks at the center of the gizmo from the part. For
+    /// example, a corner gives the classic isometric view with 45 degrees yaw and 35.26 degrees
+    /// pitch. Returns `None` for the center.
+    pub fn camera_rotation(&self) -> Option<CameraRotation> {
+        let direction = self.direction();
+        if direction == Vector3::default() {
+            return None;
+        }
+        Some(CameraRotation {
+            yaw: (-direction.x).atan2(-direction.z),
+            pitch: direction.y.atan2(direction.xz().norm()),
+        })
+    }
+
+    fn edges() -> [GizmoAxis; 12] {
+        let mut edges = [GizmoAxis::Center; 12];
+        let mut i = 0;
+        for zero_axis in 0..3 {
+            for a in [-1, 1] {
+                for b in [-1, 1] {
+                    let mut direction = Vector3::<i8>::repeat(0);
+                    direction[(zero_axis + 1) % 3] = a;
+                    direction[(zero_axis + 2) % 3] = b;
+                    edges[i] = GizmoAxis::Edge(direction);
+                    i += 1;
+                }
+            }
+        }
+        edges
+    }
+
+    fn corners() -> [GizmoAxis; 8] {
+        let mut corners = [GizmoAxis::Center; 8];
+        let mut i = 0;
+        for x in [-1, 1] {
+            for y in [-1, 1] {
+                for z in [-1, 1] {
+                    corners[i] = GizmoAxis::Corner(Vector3::new(x, y, z));
+                    i += 1;
+                }
+            }
+        }
+        corners
+    }
+}
+
 pub enum SceneGizmoAction {
     Rotate(CameraRotation),
     SwitchProjection,
@@ -76,29 +154,152 @@ pub struct SceneGizmo {
     pub pos_z: Handle<Mesh>,
     pub neg_z: Handle<Mesh>,
     pub center: Handle<Mesh>,
+    pub edges: [Handle<Mesh>; 12],
+    pub corners: [Handle<Mesh>; 8],
+    pub parts: Vec<GizmoPart>,
     pub drag_context: Option<DragContext>,
 }
 
-fn make_cone(transform: Matrix4<f32>, color: Color, graph: &mut Graph) -> Handle<Mesh> {
+/// A pickable part of the scene gizmo.
+pub struct GizmoPart {
+    pub handle: Handle<Mesh>,
+    pub axis: GizmoAxis,
+    /// A color of the part, when it is not highlighted.
+    pub base_color: Color,
+}
+
+const EDGE_COLOR: Color = Color::opaque(150, 150, 150);
+const CORNER_COLOR: Color = Color::opaque(190, 190, 190);
+const HIGHLIGHT_COLOR: Color = Color::opaque(255, 255, 0);
+
+/// Shape of the cones of the axes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct ConeParams {
+    segments: usize,
+    radius: f32,
+    height: f32,
+}
+
+fn make_cone(
+    transform: Matrix4<f32>,
+    color: Color,
+    cone: ConeParams,
+    graph: &mut Graph,
+) -> Handle<Mesh> {
     let mut material = Material::standard();
 
     material.set_property("diffuseColor", color);
 
     MeshBuilder::new(BaseBuilder::new().with_cast_shadows(false))
         .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
-            SurfaceData::make_cone(16, 0.3, 1.0, &transform),
+            SurfaceData::make_cone(cone.segments, cone.radius, cone.height, &transform),
         ))
         .with_material(MaterialResource::new_embedded(material))
         .build()])
         .build(graph)
 }
 
-impl SceneGizmo {
-    pub fn new(engine: &mut Engine) -> Self {
+fn make_box(
+    position: Vector3<f32>,
+    size: Vector3<f32>,
+    color: Color,
+    graph: &mut Graph,
+) -> Handle<Mesh> {
+    let mut material = Material::standard();
+
+    material.set_property("diffuseColor", color);
+
+    MeshBuilder::new(BaseBuilder::new().with_cast_shadows(false))
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+            SurfaceData::make_cube(
+                Matrix4::new_translation(&position) * Matrix4::new_nonuniform_scaling(&size),
+            ),
+        ))
+        .with_material(MaterialResource::new_embedded(material))
+        .build()])
+        .build(graph)
+}
+
+/// Allows configuring the size and the look of the scene gizmo.
+pub struct SceneGizmoBuilder {
+    width: u32,
+    height: u32,
+    x_color: Color,
+    y_color: Color,
+    z_color: Color,
+    neutral_color: Color,
+    cone: ConeParams,
+}
+
+impl Default for SceneGizmoBuilder {
+    fn default() -> Self {
+        Self {
+            width: 85,
+            height: 85,
+            x_color: Color::RED,
+            y_color: Color::GREEN,
+            z_color: Color::BLUE,
+            neutral_color: Color::WHITE,
+            cone: ConeParams {
+                segments: 16,
+                radius: 0.3,
+                height: 1.0,
+            },
+        }
+    }
+}
+
+impl SceneGizmoBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the size of the render target of the gizmo, in pixels.
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the colors of the positive axes.
+    pub fn with_axis_colors(mut self, x: Color, y: Color, z: Color) -> Self {
+        self.x_color = x;
+        self.y_color = y;
+        self.z_color = z;
+        self
+    }
+
+    /// Sets the color of the negative axes and the center.
+    pub fn with_neutral_color(mut self, color: Color) -> Self {
+        self.neutral_color = color;
+        self
+    }
+
+    /// Sets the number of segments, the radius and the height of the cones of the axes.
+    pub fn with_cone_params(mut self, segments: usize, radius: f32, height: f32) -> Self {
+        self.cone = ConeParams {
+            segments,
+            radius,
+            height,
+        };
+        self
+    }
+
+    pub fn build(self, engine: &mut Engine) -> SceneGizmo {
+        let Self {
+            width,
+            height,
+            x_color,
+            y_color,
+            z_color,
+            neutral_color,
+            cone,
+        } = self;
+
         let mut scene = Scene::new();
         scene.set_skybox(None);
 
-        let render_target = TextureResource::new_render_target(85, 85);
+        let render_target = TextureResource::new_render_target(width, height);
         scene.rendering_options.render_target = Some(render_target.clone());
         scene.rendering_options.clear_color = Some(Color::TRANSPARENT);
         scene.rendering_options.environment_lighting_source =
@@ -122,7 +323,8 @@ impl SceneGizmo {
                 .with_child({
                     neg_y = make_cone(
                         Matrix4::new_translation(&Vector3::new(0.0, -1.50, 0.0)),
-                        Color::WHITE,
+                        neutral_color,
+                        cone,
                         &mut scene.graph,
                     );
                     neg_y
@@ -135,7 +337,8 @@ impl SceneGizmo {
                                 180.0f32.to_radians(),
                             )
                             .to_homogeneous(),
-                        Color::GREEN,
+                        y_color,
+                        cone,
                         &mut scene.graph,
                     );
                     pos_y
@@ -148,7 +351,8 @@ impl SceneGizmo {
                                 90.0f32.to_radians(),
                             )
                             .to_homogeneous(),
-                        Color::RED,
+                        x_color,
+                        cone,
                         &mut scene.graph,
                     );
                     pos_x
@@ -161,7 +365,8 @@ impl SceneGizmo {
                                 (-90.0f32).to_radians(),
                             )
                             .to_homogeneous(),
-                        Color::WHITE,
+                        neutral_color,
+                        cone,
                         &mut scene.graph,
                     );
                     neg_x
@@ -174,7 +379,8 @@ impl SceneGizmo {
                                 (-90.0f32).to_radians(),
                             )
                             .to_homogeneous(),
-                        Color::BLUE,
+                        z_color,
+                        cone,
                         &mut scene.graph,
                     );
                     pos_z
@@ -187,7 +393,8 @@ impl SceneGizmo {
                                 90.0f32.to_radians(),
                             )
                             .to_homogeneous(),
-                        Color::WHITE,
+                        neutral_color,
+                        cone,
                         &mut scene.graph,
                     );
                     neg_z
@@ -199,6 +406,26 @@ impl SceneGizmo {
         .build()])
         .build(&mut scene.graph);
 
+        // Edges are thin bars along the edges of the central cube, corners are small cubes at the
+        // corners of the central cube.
+        let edges = GizmoAxis::edges().map(|edge| {
+            let direction = edge.direction();
+            let size = direction.map(|c| if c == 0.0 { 0.5 } else { 0.2 });
+            let handle = make_box(direction.scale(0.6), size, EDGE_COLOR, &mut scene.graph);
+            scene.graph.link_nodes(handle, center);
+            handle
+        });
+        let corners = GizmoAxis::corners().map(|corner| {
+            let handle = make_box(
+                corner.direction().scale(0.625),
+                Vector3::repeat(0.25),
+                CORNER_COLOR,
+                &mut scene.graph,
+            );
+            scene.graph.link_nodes(handle, center);
+            handle
+        });
+
         let camera_hinge;
         let camera;
         let camera_pivot = PivotBuilder::new(BaseBuilder::new().with_child({
@@ -220,7 +447,65 @@ impl SceneGizmo {
 
         scene.graph.update_hierarchical_data();
 
-        Self {
+        let mut parts = vec![
+            GizmoPart {
+                handle: center,
+                axis: GizmoAxis::Center,
+                base_color: neutral_color,
+            },
+            GizmoPart {
+                handle: pos_x,
+                axis: GizmoAxis::PosX,
+                base_color: x_color,
+            },
+            GizmoPart {
+                handle: neg_x,
+                axis: GizmoAxis::NegX,
+                base_color: neutral_color,
+            },
+            GizmoPart {
+                handle: pos_y,
+                axis: GizmoAxis::PosY,
+                base_color: y_color,
+            },
+            GizmoPart {
+                handle: neg_y,
+                axis: GizmoAxis::NegY,
+                base_color: neutral_color,
+            },
+            GizmoPart {
+                handle: pos_z,
+                axis: GizmoAxis::PosZ,
+                base_color: z_color,
+            },
+            GizmoPart {
+                handle: neg_z,
+                axis: GizmoAxis::NegZ,
+                base_color: neutral_color,
+            },
+        ];
+        parts.extend(
+            edges
+                .iter()
+                .zip(GizmoAxis::edges())
+                .map(|(handle, axis)| GizmoPart {
+                    handle: *handle,
+                    axis,
+                    base_color: EDGE_COLOR,
+                }),
+        );
+        parts.extend(
+            corners
+                .iter()
+                .zip(GizmoAxis::corners())
+                .map(|(handle, axis)| GizmoPart {
+                    handle: *handle,
+                    axis,
+                    base_color: CORNER_COLOR,
+                }),
+        );
+
+        let gizmo = SceneGizmo {
             scene: engine.scenes.add(scene),
             render_target,
             camera_pivot,
@@ -233,18 +518,46 @@ impl SceneGizmo {
             pos_z,
             neg_z,
             center,
+            edges,
+            corners,
+            parts,
             drag_context: None,
-        }
+        };
+
+        // Apply base colors to every part.
+        gizmo.sync_highlight(None, engine);
+
+        gizmo
+    }
+}
+
+impl SceneGizmo {
+    pub fn new(engine: &mut Engine) -> Self {
+        SceneGizmoBuilder::new().build(engine)
+    }
+
+    /// Returns the size of the render target of the gizmo, in pixels.
+    pub fn size(&self) -> Vector2<u32> {
+        self.render_target
+            .data_ref()
+            .kind()
+            .rectangle_size()
+            .unwrap_or_default()
     }
 
     pub fn sync_rotations(&self, game_scene: &GameScene, engine: &mut Engine) {
-        let graph = &engine.scenes[game_scene.scene].graph;
-        let hinge_rotation = **graph[game_scene.camera_controller.camera_hinge]
-            .local_transform()
-            .rotation();
-        let pivot_rotation = **graph[game_scene.camera_controller.pivot]
-            .local_transform()
-            .rotation();
+        // Take the rotations from the controller directly, so the gizmo reflects the input
+        // even if the camera itself is not updated yet.
+        let controller = &game_scene.camera_controller;
+        let (hinge_rotation, pivot_rotation) = if engine.scenes[game_scene.scene].graph
+            [controller.camera]
+            .projection()
+            .is_perspective()
+        {
+            (controller.hinge_rotation(), controller.pivot_rotation())
+        } else {
+            Default::default()
+        };
         let gizmo_graph = &mut engine.scenes[self.scene].graph;
 
         gizmo_graph[self.camera_hinge]
@@ -255,42 +568,49 @@ impl SceneGizmo {
             .set_rotation(pivot_rotation);
     }
 
-    fn parts(&self) -> [(Handle<Mesh>, Color); 7] {
-        [
-            (self.center, Color::WHITE),
-            (self.pos_x, Color::RED),
-            (self.neg_x, Color::WHITE),
-            (self.pos_y, Color::GREEN),
-            (self.neg_y, Color::WHITE),
-            (self.pos_z, Color::BLUE),
-            (self.neg_z, Color::WHITE),
-        ]
-    }
-
-    fn pick(&self, pos: Vector2<f32>, scenes: &SceneContainer) -> Handle<Mesh> {
+    fn pick(
+        &self,
+        pos: Vector2<f32>,
+        scenes: &SceneContainer,
+    ) -> Option<(Handle<Mesh>, GizmoAxis)> {
         let graph = &scenes[self.scene].graph;
-        let ray = graph[self.camera].make_ray(
-            pos,
-            self.render_target
-                .data_ref()
-                .kind()
-                .rectangle_size()
-                .unwrap()
-                .map(|c| c as f32),
-        );
+        let ray = graph[self.camera].make_ray(pos, self.size().map(|c| c as f32));
 
-        let mut closest = Handle::NONE;
+        let mut closest = None;
         let mut min_toi = f32::MAX;
-        for (node, _) in self.parts() {
+        for &GizmoPart {
+            handle: node, axis, ..
+        } in self.parts.iter()
+        {
             let node_ref = &graph[node];
-            if let Some(result) = ray.aabb_intersection(
+
+            // Bounding boxes of the cones are much larger than the cones themselves, so they're
+            // used only as a cheap broad-phase test.
+            let Some(result) = ray.aabb_intersection(
                 &node_ref
                     .local_bounding_box()
                     .transform(&node_ref.global_transform()),
-            ) {
-                if result.min < min_toi {
-                    closest = node;
-                    min_toi = result.min;
+            ) else {
+                continue;
+            };
+            if result.min >= min_toi {
+                continue;
+            }
+
+            let transform = node_ref.global_transform();
+            for surface in node_ref.surfaces() {
+                let data = surface.data_ref().data_ref();
+                for triangle in data
+                    .geometry_buffer
+                    .iter()
+                    .filter_map(|t| read_triangle(&data, t, &transform))
+                {
+                    if let Some((toi, _)) = ray.triangle_intersection(&triangle) {
+                        if toi < min_toi {
+                            closest = Some((node, axis));
+                            min_toi = toi;
+                        }
+                    }
                 }
             }
         }
@@ -309,72 +629,83 @@ impl SceneGizmo {
             camera_controller.set_yaw(drag_context.initial_rotation.yaw + delta.x * -sens);
             camera_controller.set_pitch(drag_context.initial_rotation.pitch + delta.y * sens);
         } else {
-            let graph = &engine.scenes[self.scene].graph;
-            let closest = self.pick(pos, &engine.scenes);
-            fn set_color(node: Handle<Mesh>, graph: &Graph, color: Color) {
-                graph[node].surfaces()[0]
-                    .material()
-                    .data_ref()
-                    .set_property("diffuseColor", color);
-            }
-            for (node, default_color) in self.parts() {
-                set_color(
-                    node,
-                    graph,
-                    if node == closest {
-                        Color::opaque(255, 255, 0)
-                    } else {
-                        default_color
-                    },
-                );
-            }
+            self.sync_highlight(Some(pos), engine);
         }
     }
 
+    /// Highlights a part of the gizmo under the given position and restores base colors of every
+    /// other part. `None` position removes the highlighting.
+    pub fn sync_highlight(&self, pos: Option<Vector2<f32>>, engine: &mut Engine) {
+        let hovered = pos
+            .and_then(|pos| self.pick(pos, &engine.scenes))
+            .map(|(node, _)| node);
+        let graph = &engine.scenes[self.scene].graph;
+        for part in self.parts.iter() {
+            let color = if Some(part.handle) == hovered {
+                HIGHLIGHT_COLOR
+            } else {
+                part.base_color
+            };
+            graph[part.handle].surfaces()[0]
+                .material()
+                .data_ref()
+                .set_property("diffuseColor", color);
+        }
+    }
+
+    /// Returns a part of the gizmo under the given position and the respective action.
     pub fn on_click(
         &mut self,
         pos: Vector2<f32>,
         scenes: &SceneContainer,
-    ) -> Option<SceneGizmoAction> {
+    ) -> Option<(GizmoAxis, SceneGizmoAction)> {
         if let Some(_drag_context) = self.drag_context.as_ref() {
             return None;
         }
 
-        let closest = self.pick(pos, scenes);
-        if closest == self.neg_x {
-            Some(SceneGizmoAction::Rotate(CameraRotation {
-                pitch: 0.0,
-                yaw: 90.0f32.to_radians(),
-            }))
-        } else if closest == self.pos_x {
-            Some(SceneGizmoAction::Rotate(CameraRotation {
-                pitch: 0.0,
-                yaw: (-90.0f32).to_radians(),
-            }))
-        } else if closest == self.neg_y {
-            Some(SceneGizmoAction::Rotate(CameraRotation {
-                pitch: (-90.0f32).to_radians(),
-                yaw: 0.0,
-            }))
-        } else if closest == self.pos_y {
-            Some(SceneGizmoAction::Rotate(CameraRotation {
-                pitch: 90.0f32.to_radians(),
-                yaw: 0.0,
-            }))
-        } else if closest == self.neg_z {
-            Some(SceneGizmoAction::Rotate(CameraRotation {
-                pitch: 0.0,
-                yaw: 0.0f32.to_radians(),
-            }))
-        } else if closest == self.pos_z {
-            Some(SceneGizmoAction::Rotate(CameraRotation {
-                pitch: 0.0,
-                yaw: (-180.0f32).to_radians(),
-            }))
-        } else if closest == self.center {
-            Some(SceneGizmoAction::SwitchProjection)
-        } else {
-            None
+        let (_, axis) = self.pick(pos, scenes)?;
+        let action = match axis.camera_rotation() {
+            Some(rotation) => SceneGizmoAction::Rotate(rotation),
+            None => SceneGizmoAction::SwitchProjection,
+        };
+        Some((axis, action))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GizmoAxis;
+
+    fn assert_angles(axis: GizmoAxis, yaw: f32, pitch: f32) {
+        let rotation = axis.camera_rotation().unwrap();
+        assert!((rotation.yaw - yaw.to_radians()).abs() < 1.0e-4, "{axis:?}");
+        assert!(
+            (rotation.pitch - pitch.to_radians()).abs() < 1.0e-4,
+            "{axis:?}"
+        );
+    }
+
+    #[test]
+    fn test_gizmo_axis_camera_rotation() {
+        assert!(GizmoAxis::Center.camera_rotation().is_none());
+        assert_angles(GizmoAxis::NegX, 90.0, 0.0);
+        assert_angles(GizmoAxis::PosX, -90.0, 0.0);
+        assert_angles(GizmoAxis::NegY, 0.0, -90.0);
+        assert_angles(GizmoAxis::PosY, 0.0, 90.0);
+        assert_angles(GizmoAxis::NegZ, 0.0, 0.0);
+        assert_angles(GizmoAxis::PosZ, 180.0, 0.0);
+        assert_angles(
+            GizmoAxis::Corner([1, 1, -1].into()),
+            -45.0,
+            (1.0f32 / 2.0f32.sqrt()).atan().to_degrees(),
+        );
+
+        let edges = GizmoAxis::edges();
+        let corners = GizmoAxis::corners();
+        for (i, a) in edges.iter().chain(corners.iter()).enumerate() {
+            for b in edges.iter().chain(corners.iter()).skip(i + 1) {
+                assert_ne!(a, b);
+            }
         }
     }
 }
@@ -38,7 +38,7 @@ use crate::{
             formatted_text::WrapMode,
             grid::{Column, GridBuilder, Row},
             image::{ImageBuilder, ImageMessage},
-            message::{MessageDirection, MouseButton, UiMessage},
+            message::{MessageDirection, MouseButton, TouchPhase, UiMessage},
             numeric::{NumericUpDownBuilder, NumericUpDownMessage},
             stack_panel::StackPanelBuilder,
             style::{resource::StyleResourceExt, Style},
@@ -481,10 +481,11 @@ impl SceneViewer {
         .with_wrap(WrapMode::Word)
         .build(ctx);
 
+        let scene_gizmo_size = scene_gizmo.size();
         let scene_gizmo_image = ImageBuilder::new(
             WidgetBuilder::new()
-                .with_width(85.0)
-                .with_height(85.0)
+                .with_width(scene_gizmo_size.x as f32)
+                .with_height(scene_gizmo_size.y as f32)
                 .with_horizontal_alignment(HorizontalAlignment::Right)
                 .with_vertical_alignment(VerticalAlignment::Top)
                 .with_margin(Thickness::uniform(1.0)),
@@ -772,6 +773,29 @@ impl SceneViewer {
         let entry = scenes.current_scene_entry_mut();
         if let (Some(msg), Mode::Edit) = (message.data::<WidgetMessage>(), mode) {
             if message.destination() == self.frame() {
+                let touch = match *msg {
+                    WidgetMessage::TouchStarted { pos, id, .. } => {
+                        Some((TouchPhase::Started, pos, id))
+                    }
+                    WidgetMessage::TouchMoved { pos, id, .. } => Some((TouchPhase::Moved, pos, id)),
+                    WidgetMessage::TouchEnded { pos, id } => Some((TouchPhase::Ended, pos, id)),
+                    WidgetMessage::TouchCancelled { pos, id } => {
+                        Some((TouchPhase::Cancelled, pos, id))
+                    }
+                    _ => None,
+                };
+                if let Some((phase, pos, id)) = touch {
+                    if let Some(game_scene) = entry.controller.downcast_mut::<GameScene>() {
+                        game_scene.camera_controller.on_touch(
+                            phase,
+                            id,
+                            pos,
+                            &mut engine.scenes[game_scene.scene].graph,
+                            settings,
+                        );
+                    }
+                }
+
                 let screen_bounds = self.frame_bounds(engine.user_interfaces.first());
                 match *msg {
                     WidgetMessage::MouseDown { button, pos, .. } => {
@@ -838,7 +862,8 @@ impl SceneViewer {
                                 ui.release_mouse_capture();
                             }
                             let rel_pos = pos - ui[self.scene_gizmo_image].screen_position();
-                            if let Some(action) = self.scene_gizmo.on_click(rel_pos, &engine.scenes)
+                            if let Some((_, action)) =
+                                self.scene_gizmo.on_click(rel_pos, &engine.scenes)
                             {
                                 match action {
                                     SceneGizmoAction::Rotate(rotation) => {
@@ -877,6 +902,11 @@ impl SceneViewer {
                                 &mut game_scene.camera_controller,
                             );
                         }
+                        WidgetMessage::MouseLeave => {
+                            if self.scene_gizmo.drag_context.is_none() {
+                                self.scene_gizmo.sync_highlight(None, engine);
+                            }
+                        }
                         _ => (),
                     }
                 }
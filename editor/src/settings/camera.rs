@@ -48,6 +48,8 @@ pub struct CameraSettings {
     pub zoom_range: Range<f32>,
     #[serde(default)]
     pub exposure: Exposure,
+    #[serde(default)]
+    pub invert_y: bool,
 }
 
 impl Default for CameraSettings {
@@ -58,6 +60,7 @@ impl Default for CameraSettings {
             zoom_speed: default_zoom_speed(),
             zoom_range: default_zoom_range(),
             exposure: Default::default(),
+            invert_y: false,
         }
     }
 }
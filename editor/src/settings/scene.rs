@@ -18,7 +18,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use crate::fyrox::{core::algebra::Vector3, core::pool::ErasedHandle, scene::camera::Projection};
+use crate::fyrox::{
+    core::algebra::Vector3,
+    core::pool::{ErasedHandle, Handle},
+    graph::SceneGraph,
+    scene::{camera::Projection, graph::Graph, node::Node},
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -53,8 +58,88 @@ impl Default for NodeInfo {
     }
 }
 
+/// A named reference to a node, that allows to quickly navigate to the node.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct NodeBookmark {
+    pub node: Handle<Node>,
+    pub label: String,
+}
+
 #[derive(Deserialize, Serialize, PartialEq, Clone, Debug, Default)]
 pub struct SceneSettings {
     pub camera_settings: SceneCameraSettings,
     pub node_infos: HashMap<ErasedHandle, NodeInfo>,
+    #[serde(default)]
+    pub bookmarks: Vec<NodeBookmark>,
+}
+
+impl SceneSettings {
+    /// Adds a bookmark to the given node. If the node is already bookmarked, its label is changed.
+    pub fn add_bookmark(&mut self, node: Handle<Node>, label: impl Into<String>) {
+        let label = label.into();
+        match self.bookmarks.iter_mut().find(|b| b.node == node) {
+            Some(bookmark) => bookmark.label = label,
+            None => self.bookmarks.push(NodeBookmark { node, label }),
+        }
+    }
+
+    /// Removes a bookmark to the given node. Returns `true` if the bookmark existed.
+    pub fn remove_bookmark(&mut self, node: Handle<Node>) -> bool {
+        let count = self.bookmarks.len();
+        self.bookmarks.retain(|b| b.node != node);
+        self.bookmarks.len() != count
+    }
+
+    pub fn bookmarks(&self) -> &[NodeBookmark] {
+        &self.bookmarks
+    }
+
+    /// Removes bookmarks to the nodes that do not exist in the given graph. Returns the amount of
+    /// removed bookmarks.
+    pub fn prune_bookmarks(&mut self, graph: &Graph) -> usize {
+        let count = self.bookmarks.len();
+        self.bookmarks.retain(|b| graph.is_valid_handle(b.node));
+        count - self.bookmarks.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        fyrox::{
+            graph::SceneGraph,
+            scene::{base::BaseBuilder, graph::Graph, pivot::PivotBuilder},
+        },
+        settings::scene::SceneSettings,
+    };
+
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let mut graph = Graph::new();
+        let kept = PivotBuilder::new(BaseBuilder::new())
+            .build(&mut graph)
+            .to_base();
+        let deleted = PivotBuilder::new(BaseBuilder::new())
+            .build(&mut graph)
+            .to_base();
+
+        let mut settings = SceneSettings::default();
+        settings.add_bookmark(kept, "Kept");
+        settings.add_bookmark(deleted, "Deleted");
+        settings.add_bookmark(kept, "Spawn Point");
+        assert_eq!(settings.bookmarks().len(), 2);
+
+        let saved = ron::to_string(&settings).unwrap();
+        graph.remove_node(deleted);
+
+        let mut loaded = ron::from_str::<SceneSettings>(&saved).unwrap();
+        assert_eq!(loaded, settings);
+        assert_eq!(loaded.prune_bookmarks(&graph), 1);
+        assert_eq!(loaded.bookmarks().len(), 1);
+        assert_eq!(loaded.bookmarks()[0].node, kept);
+        assert_eq!(loaded.bookmarks()[0].label, "Spawn Point");
+
+        assert!(loaded.remove_bookmark(kept));
+        assert!(!loaded.remove_bookmark(kept));
+    }
 }
@@ -175,15 +175,11 @@ impl WorldViewerDataProvider for EditorSceneWrapper<'_> {
             if selection.nodes.contains(&child) {
                 let mut commands = CommandGroup::default();
 
-                'selection_loop: for &node_handle in selection.nodes.iter() {
+                for &node_handle in selection.nodes.iter() {
                     // Make sure we won't create any loops - child must not have parent in its
                     // descendants.
-                    let mut p = parent;
-                    while p.is_some() {
-                        if p == node_handle {
-                            continue 'selection_loop;
-                        }
-                        p = self.scene.graph[p].parent();
+                    if !self.scene.graph.can_reparent(node_handle, parent) {
+                        continue;
                     }
 
                     match anchor {
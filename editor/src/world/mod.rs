@@ -79,6 +79,7 @@ pub mod graph;
 pub mod item;
 pub mod menu;
 pub mod selection;
+pub mod tree_model;
 
 pub trait WorldViewerDataProvider {
     fn root_node(&self) -> ErasedHandle;
@@ -0,0 +1,279 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! UI-independent model of the scene tree, that supports expansion state and keyboard navigation.
+//! See [`TreeModel`] docs for more info.
+
+use crate::{
+    fyrox::core::{fxhash::FxHashSet, pool::ErasedHandle},
+    world::WorldViewerDataProvider,
+};
+
+/// A source of hierarchical data for [`TreeModel`].
+pub trait TreeSource {
+    /// Returns a handle of the root node of the tree.
+    fn tree_root(&self) -> ErasedHandle;
+
+    /// Returns handles of the children of the given node, in order.
+    fn tree_children(&self, node: ErasedHandle) -> Vec<ErasedHandle>;
+}
+
+impl<T: WorldViewerDataProvider + ?Sized> TreeSource for T {
+    fn tree_root(&self) -> ErasedHandle {
+        self.root_node()
+    }
+
+    fn tree_children(&self, node: ErasedHandle) -> Vec<ErasedHandle> {
+        self.children_of(node)
+    }
+}
+
+/// A visible row of the tree.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TreeRow {
+    /// A node of the row.
+    pub node: ErasedHandle,
+    /// Depth of the node in the tree, the root node has zero depth.
+    pub depth: usize,
+    /// `true` if the node has children.
+    pub has_children: bool,
+    /// `true` if the node is expanded.
+    pub expanded: bool,
+}
+
+/// Expansion state and selection of the scene tree. The model does not store the tree itself, it
+/// takes it from a [`TreeSource`] on every call, so it is always in sync with the scene. Every
+/// node is collapsed by default, except the root node which is always expanded.
+#[derive(Default, Debug, Clone)]
+pub struct TreeModel {
+    expanded: FxHashSet<ErasedHandle>,
+    selected: Option<ErasedHandle>,
+}
+
+impl TreeModel {
+    /// Returns `true` if the given node is expanded.
+    pub fn is_expanded(&self, source: &dyn TreeSource, node: ErasedHandle) -> bool {
+        node == source.tree_root() || self.expanded.contains(&node)
+    }
+
+    /// Expands or collapses the given node.
+    pub fn set_expanded(&mut self, node: ErasedHandle, expanded: bool) {
+        if expanded {
+            self.expanded.insert(node);
+        } else {
+            self.expanded.remove(&node);
+        }
+    }
+
+    /// Returns currently selected node.
+    pub fn selected(&self) -> Option<ErasedHandle> {
+        self.selected
+    }
+
+    /// Selects the given node.
+    pub fn select(&mut self, node: Option<ErasedHandle>) {
+        self.selected = node;
+    }
+
+    /// Flattens the tree into a list of rows, that should be rendered. Children of collapsed nodes
+    /// are not included.
+    pub fn visible_rows(&self, source: &dyn TreeSource) -> Vec<TreeRow> {
+        let mut rows = Vec::new();
+        let mut stack = vec![(source.tree_root(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let children = source.tree_children(node);
+            let expanded = self.is_expanded(source, node);
+            rows.push(TreeRow {
+                node,
+                depth,
+                has_children: !children.is_empty(),
+                expanded,
+            });
+            if expanded {
+                stack.extend(children.into_iter().rev().map(|child| (child, depth + 1)));
+            }
+        }
+        rows
+    }
+
+    fn selected_row(&self, rows: &[TreeRow]) -> Option<usize> {
+        self.selected
+            .and_then(|selected| rows.iter().position(|row| row.node == selected))
+    }
+
+    /// Selects next visible row. Selects the first row if nothing is selected. Returns `true` if
+    /// the selection has changed.
+    pub fn select_next(&mut self, source: &dyn TreeSource) -> bool {
+        let rows = self.visible_rows(source);
+        let next = match self.selected_row(&rows) {
+            Some(index) => index + 1,
+            None => 0,
+        };
+        self.select_row(&rows, next)
+    }
+
+    /// Selects previous visible row. Selects the last row if nothing is selected. Returns `true`
+    /// if the selection has changed.
+    pub fn select_previous(&mut self, source: &dyn TreeSource) -> bool {
+        let rows = self.visible_rows(source);
+        let previous = match self.selected_row(&rows) {
+            Some(index) => index.checked_sub(1),
+            None => rows.len().checked_sub(1),
+        };
+        previous.is_some_and(|previous| self.select_row(&rows, previous))
+    }
+
+    fn select_row(&mut self, rows: &[TreeRow], index: usize) -> bool {
+        match rows.get(index) {
+            Some(row) if self.selected != Some(row.node) => {
+                self.selected = Some(row.node);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Expands the selected node. If it is already expanded, selects its first child. Returns
+    /// `true` if the state of the tree has changed.
+    pub fn expand_selected(&mut self, source: &dyn TreeSource) -> bool {
+        let Some(selected) = self.selected else {
+            return false;
+        };
+        let children = source.tree_children(selected);
+        if children.is_empty() {
+            false
+        } else if !self.is_expanded(source, selected) {
+            self.set_expanded(selected, true);
+            true
+        } else {
+            self.selected = children.first().cloned();
+            true
+        }
+    }
+
+    /// Collapses the selected node. If it is already collapsed (or has no children), selects its
+    /// parent. Returns `true` if the state of the tree has changed.
+    pub fn collapse_selected(&mut self, source: &dyn TreeSource) -> bool {
+        let Some(selected) = self.selected else {
+            return false;
+        };
+        if self.expanded.remove(&selected) {
+            return true;
+        }
+        let rows = self.visible_rows(source);
+        let Some(index) = self.selected_row(&rows) else {
+            return false;
+        };
+        let depth = rows[index].depth;
+        match rows[..index].iter().rev().find(|row| row.depth < depth) {
+            Some(parent) => {
+                self.selected = Some(parent.node);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        fyrox::{
+            core::pool::{ErasedHandle, Handle},
+            graph::{SceneGraph, SceneGraphNode},
+            scene::{base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder},
+        },
+        world::tree_model::{TreeModel, TreeSource},
+    };
+
+    struct GraphSource<'a> {
+        graph: &'a Graph,
+        root: Handle<Node>,
+    }
+
+    impl TreeSource for GraphSource<'_> {
+        fn tree_root(&self) -> ErasedHandle {
+            self.root.into()
+        }
+
+        fn tree_children(&self, node: ErasedHandle) -> Vec<ErasedHandle> {
+            self.graph
+                .try_get_node(node.into())
+                .map(|node| node.children().iter().map(|h| (*h).into()).collect())
+                .unwrap_or_default()
+        }
+    }
+
+    fn pivot(graph: &mut Graph, children: &[Handle<Node>]) -> Handle<Node> {
+        let mut builder = BaseBuilder::new();
+        for child in children {
+            builder = builder.with_child(*child);
+        }
+        PivotBuilder::new(builder).build(graph).to_base()
+    }
+
+    fn nodes(rows: &[super::TreeRow]) -> Vec<ErasedHandle> {
+        rows.iter().map(|row| row.node).collect()
+    }
+
+    #[test]
+    fn test_expand_inserts_children_in_order() {
+        let mut graph = Graph::new();
+        let a1 = pivot(&mut graph, &[]);
+        let a2 = pivot(&mut graph, &[]);
+        let a = pivot(&mut graph, &[a1, a2]);
+        let b = pivot(&mut graph, &[]);
+        let root = pivot(&mut graph, &[a, b]);
+        let source = GraphSource {
+            graph: &graph,
+            root,
+        };
+        let h = |handle: Handle<Node>| ErasedHandle::from(handle);
+
+        let mut model = TreeModel::default();
+        assert_eq!(
+            nodes(&model.visible_rows(&source)),
+            vec![h(root), h(a), h(b)]
+        );
+
+        // Navigate to the collapsed parent and expand it.
+        assert!(model.select_next(&source));
+        assert!(model.select_next(&source));
+        assert_eq!(model.selected(), Some(h(a)));
+        assert!(model.expand_selected(&source));
+        let rows = model.visible_rows(&source);
+        assert_eq!(nodes(&rows), vec![h(root), h(a), h(a1), h(a2), h(b)]);
+        assert_eq!(rows[2].depth, 2);
+
+        // Keyboard navigation goes through the new rows.
+        assert!(model.select_next(&source));
+        assert_eq!(model.selected(), Some(h(a1)));
+        assert!(model.collapse_selected(&source));
+        assert_eq!(model.selected(), Some(h(a)));
+        assert!(model.collapse_selected(&source));
+        assert_eq!(
+            nodes(&model.visible_rows(&source)),
+            vec![h(root), h(a), h(b)]
+        );
+        assert!(model.select_previous(&source));
+        assert_eq!(model.selected(), Some(h(root)));
+        assert!(!model.select_previous(&source));
+    }
+}
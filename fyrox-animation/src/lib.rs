@@ -25,6 +25,7 @@
 #![allow(clippy::doc_lazy_continuation)]
 #![allow(mismatched_lifetime_syntaxes)]
 
+use crate::property::PropertyTrack;
 use crate::track::TrackBinding;
 use crate::{
     core::{
@@ -44,7 +45,7 @@ pub use fyrox_core as core;
 use fyrox_core::pool::PoolError;
 use fyrox_resource::untyped::ResourceKind;
 use fyrox_resource::{Resource, ResourceData};
-pub use pose::{AnimationPose, NodePose};
+pub use pose::{AnimationPose, NodePose, PoseCrossFade};
 pub use signal::{AnimationEvent, AnimationSignal};
 use std::{
     collections::VecDeque,
@@ -59,6 +60,8 @@ use value::{nlerp, TrackValue, ValueBinding};
 pub mod container;
 pub mod machine;
 pub mod pose;
+pub mod property;
+pub mod sequence;
 pub mod signal;
 pub mod spritesheet;
 pub mod track;
@@ -481,19 +484,7 @@ impl<T: EntityId> Animation<T> {
         let current_time_position = self.time_position();
         let new_time_position = current_time_position + dt * self.speed();
 
-        for signal in self.signals.iter_mut().filter(|s| s.enabled) {
-            if self.speed >= 0.0
-                && (current_time_position < signal.time && new_time_position >= signal.time)
-                || self.speed < 0.0
-                    && (current_time_position > signal.time && new_time_position <= signal.time)
-                    && self.events.len() < self.max_event_capacity
-            {
-                self.events.push_back(AnimationEvent {
-                    signal_id: signal.id,
-                    name: signal.name.clone(),
-                });
-            }
-        }
+        self.collect_signal_events(current_time_position, new_time_position);
 
         let prev_time_position = current_time_position;
 
@@ -502,6 +493,55 @@ impl<T: EntityId> Animation<T> {
         self.update_root_motion(prev_time_position);
     }
 
+    /// Emits events for every signal that was passed while moving from `from` to `to` time positions. The
+    /// positions are not wrapped, so when the animation is looping a signal could be passed multiple times
+    /// (once per each loop cycle). The events are emitted in the order of playback, which means that in
+    /// case of reversed playback the order is reversed as well.
+    fn collect_signal_events(&mut self, from: f32, to: f32) {
+        let (min, max) = if from <= to { (from, to) } else { (to, from) };
+        let length = self.time_slice.end - self.time_slice.start;
+
+        let mut passed_signals = Vec::new();
+        for signal in self.signals.iter().filter(|s| s.enabled) {
+            // A signal is passed when it is in (from; to] interval for forward playback and in [to; from)
+            // for reversed playback.
+            let is_passed = |time: f32| {
+                if from <= to {
+                    from < time && time <= to
+                } else {
+                    to <= time && time < from
+                }
+            };
+
+            if self.looped && length > f32::EPSILON {
+                // Check every loop cycle in the interval.
+                let first_cycle = ((min - signal.time) / length).floor() as i32;
+                let last_cycle = ((max - signal.time) / length).ceil() as i32;
+                for cycle in first_cycle..=last_cycle {
+                    let time = signal.time + cycle as f32 * length;
+                    if is_passed(time) {
+                        passed_signals.push(((time - from).abs(), signal));
+                    }
+                }
+            } else if is_passed(signal.time) {
+                passed_signals.push(((signal.time - from).abs(), signal));
+            }
+        }
+
+        passed_signals.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        for (_, signal) in passed_signals {
+            if self.events.len() >= self.max_event_capacity {
+                break;
+            }
+
+            self.events.push_back(AnimationEvent {
+                signal_id: signal.id,
+                name: signal.name.clone(),
+            });
+        }
+    }
+
     fn update_root_motion(&mut self, prev_time_position: f32) {
         let state = self.tracks_data.state();
         let Some(tracks_data) = state.data_ref() else {
@@ -509,10 +549,34 @@ impl<T: EntityId> Animation<T> {
         };
         let tracks = &tracks_data.tracks;
 
-        fn fetch_position_at_time(tracks: &[Track], time: f32) -> Vector3<f32> {
-            tracks
-                .iter()
-                .find(|track| track.value_binding() == &ValueBinding::Position)
+        // Only the tracks that are bound to the root node must be used, otherwise the motion will be
+        // taken from some other node of the hierarchy.
+        fn find_root_track<'a, T: EntityId>(
+            tracks: &'a [Track],
+            bindings: &FxHashMap<Uuid, TrackBinding<T>>,
+            root: T,
+            value_binding: ValueBinding,
+        ) -> Option<&'a Track> {
+            tracks.iter().find(|track| {
+                track.value_binding() == &value_binding
+                    && bindings
+                        .get(&track.id())
+                        .is_some_and(|binding| binding.target() == root)
+            })
+        }
+
+        // If we have root motion enabled, try to extract the actual motion values. We'll take only relative motion
+        // here, relative to the previous values.
+        if let Some(root_motion_settings) = self.root_motion_settings.as_ref() {
+            let mut prev_root_motion = self.root_motion.clone().unwrap_or_default();
+
+            let fetch_position_at_time = |time: f32| -> Vector3<f32> {
+                find_root_track(
+                    tracks,
+                    &self.track_bindings,
+                    root_motion_settings.node,
+                    ValueBinding::Position,
+                )
                 .and_then(|track| track.fetch(time))
                 .and_then(|value| {
                     if let TrackValue::Vector3(position) = value.value {
@@ -522,12 +586,15 @@ impl<T: EntityId> Animation<T> {
                     }
                 })
                 .unwrap_or_default()
-        }
+            };
 
-        fn fetch_rotation_at_time(tracks: &[Track], time: f32) -> UnitQuaternion<f32> {
-            tracks
-                .iter()
-                .find(|track| track.value_binding() == &ValueBinding::Rotation)
+            let fetch_rotation_at_time = |time: f32| -> UnitQuaternion<f32> {
+                find_root_track(
+                    tracks,
+                    &self.track_bindings,
+                    root_motion_settings.node,
+                    ValueBinding::Rotation,
+                )
                 .and_then(|track| track.fetch(time))
                 .and_then(|value| {
                     if let TrackValue::UnitQuaternion(rotation) = value.value {
@@ -537,12 +604,7 @@ impl<T: EntityId> Animation<T> {
                     }
                 })
                 .unwrap_or_default()
-        }
-
-        // If we have root motion enabled, try to extract the actual motion values. We'll take only relative motion
-        // here, relative to the previous values.
-        if let Some(root_motion_settings) = self.root_motion_settings.as_ref() {
-            let mut prev_root_motion = self.root_motion.clone().unwrap_or_default();
+            };
 
             // Check if we've started another loop cycle.
             let new_loop_cycle_started = self.looped
@@ -569,10 +631,9 @@ impl<T: EntityId> Animation<T> {
                             if let TrackValue::Vector3(pose_position) = bound_value.value {
                                 if new_loop_cycle_started {
                                     root_motion.prev_position =
-                                        fetch_position_at_time(tracks, cycle_start_time);
+                                        fetch_position_at_time(cycle_start_time);
                                     root_motion.position_offset_remainder = Some(
-                                        fetch_position_at_time(tracks, cycle_end_time)
-                                            - pose_position,
+                                        fetch_position_at_time(cycle_end_time) - pose_position,
                                     );
                                 } else {
                                     root_motion.prev_position = pose_position;
@@ -605,8 +666,7 @@ impl<T: EntityId> Animation<T> {
                                     };
 
                                 // Reset position so the root won't move.
-                                let start_position =
-                                    fetch_position_at_time(tracks, self.time_slice.start);
+                                let start_position = fetch_position_at_time(self.time_slice.start);
 
                                 bound_value.value = TrackValue::Vector3(Vector3::new(
                                     if root_motion_settings.ignore_x_movement {
@@ -632,10 +692,9 @@ impl<T: EntityId> Animation<T> {
                                 if !root_motion_settings.ignore_rotations {
                                     if new_loop_cycle_started {
                                         root_motion.prev_rotation =
-                                            fetch_rotation_at_time(tracks, cycle_start_time);
+                                            fetch_rotation_at_time(cycle_start_time);
                                         root_motion.rotation_remainder = Some(
-                                            fetch_rotation_at_time(tracks, cycle_end_time)
-                                                .inverse()
+                                            fetch_rotation_at_time(cycle_end_time).inverse()
                                                 * pose_rotation,
                                         );
                                     } else {
@@ -654,7 +713,7 @@ impl<T: EntityId> Animation<T> {
 
                                     // Reset rotation so the root won't rotate.
                                     bound_value.value = TrackValue::UnitQuaternion(
-                                        fetch_rotation_at_time(tracks, self.time_slice.start),
+                                        fetch_rotation_at_time(self.time_slice.start),
                                     );
                                 }
                             }
@@ -769,6 +828,13 @@ impl<T: EntityId> Animation<T> {
         self.track_bindings.insert(id, binding);
     }
 
+    /// Adds a new property track to the animation. The track is converted to a regular track, the
+    /// interpolation mode of the property track is preserved.
+    pub fn add_property_track(&mut self, track: PropertyTrack<T>) {
+        let (binding, track) = track.into_track();
+        self.add_track_with_binding(binding, track);
+    }
+
     /// Removes last track from the current tracks data resource and the respective binding to it
     /// from the animation. This method will fail if the resource is not loaded, or if there's no
     /// tracks in it. It will also fail if there's no respective binding to the track in the
@@ -1139,3 +1205,91 @@ impl<T: EntityId> IndexMut<Handle<Animation<T>>> for AnimationContainer<T> {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        container::{TrackDataContainer, TrackValueKind},
+        core::uuid::Uuid,
+        core::{
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::ErasedHandle,
+        },
+        signal::AnimationSignal,
+        track::{Track, TrackBinding},
+        value::ValueBinding,
+        Animation, RootMotionSettings,
+    };
+
+    fn make_forward_track(from: f32, to: f32) -> Track {
+        let mut frames_container = TrackDataContainer::new(TrackValueKind::Vector3);
+        frames_container.curves_mut()[2] = Curve::from(vec![
+            CurveKey::new(0.0, from, CurveKeyKind::Linear),
+            CurveKey::new(1.0, to, CurveKeyKind::Linear),
+        ]);
+        Track::new(frames_container, ValueBinding::Position)
+    }
+
+    #[test]
+    fn test_root_motion_over_loop() {
+        let root = ErasedHandle::new(1, 1);
+        let other_bone = ErasedHandle::new(2, 1);
+
+        let mut animation = Animation::default();
+        // A track of some other bone must not affect root motion.
+        animation
+            .add_track_with_binding(TrackBinding::new(other_bone), make_forward_track(5.0, -5.0));
+        animation.add_track_with_binding(TrackBinding::new(root), make_forward_track(0.0, 1.0));
+        animation.set_time_slice(0.0..1.0);
+        animation.set_loop(true);
+        animation.set_root_motion_settings(Some(RootMotionSettings {
+            node: root,
+            ..Default::default()
+        }));
+
+        let dt = 0.1;
+        let mut total = 0.0;
+        for _ in 0..25 {
+            animation.tick(dt);
+            let delta = animation.root_motion().unwrap().delta_position;
+            // The motion must be continuous, even when the animation wraps around.
+            assert!(delta.z >= 0.0 && delta.z <= dt + 0.001);
+            total += delta.z;
+        }
+
+        // The first frame has no motion, because there is no previous frame.
+        assert!((total - 2.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_reversed_playback_events() {
+        let mut animation = Animation::<ErasedHandle>::default();
+        animation.set_time_slice(0.0..1.0);
+        animation.set_loop(true);
+        animation.set_speed(-1.0);
+        animation.add_signal(AnimationSignal::new(Uuid::new_v4(), "Begin", 0.1));
+        animation.add_signal(AnimationSignal::new(Uuid::new_v4(), "End", 0.9));
+        animation.set_time_position(0.5);
+
+        animation.tick(0.25);
+        assert!((animation.time_position() - 0.25).abs() < 0.001);
+        assert!(animation.pop_event().is_none());
+
+        // Crosses the loop boundary: passes "Begin" first, wraps around and then passes "End".
+        animation.tick(0.3);
+        assert!((animation.time_position() - 0.95).abs() < 0.001);
+        assert_eq!(animation.pop_event().unwrap().name, "Begin");
+
+        animation.tick(0.1);
+        assert!((animation.time_position() - 0.85).abs() < 0.001);
+        assert_eq!(animation.pop_event().unwrap().name, "End");
+        assert!(animation.pop_event().is_none());
+
+        // Both signals in a single step.
+        animation.set_time_position(0.15);
+        animation.tick(0.3);
+        assert_eq!(animation.pop_event().unwrap().name, "Begin");
+        assert_eq!(animation.pop_event().unwrap().name, "End");
+        assert!(animation.pop_event().is_none());
+    }
+}
@@ -326,7 +326,7 @@ impl<T: EntityId> MachineLayer<T> {
             };
         } else if let Ok(transition) = self.transitions.try_borrow(self.active_transition) {
             if let (Ok(source_state), Ok(dest_state)) = (
-                self.states.try_borrow(transition.source()),
+                self.states.try_borrow(transition.active_source()),
                 self.states.try_borrow(transition.dest()),
             ) {
                 let mut events = Vec::new();
@@ -380,11 +380,11 @@ impl<T: EntityId> MachineLayer<T> {
                 return LayerAnimationEventsCollection {
                     source: AnimationEventsSource::Transition {
                         handle: self.active_transition,
-                        source_state_handle: transition.source,
+                        source_state_handle: transition.active_source(),
                         dest_state_handle: transition.dest,
                         source_state_name: self
                             .states
-                            .try_borrow(transition.source)
+                            .try_borrow(transition.active_source())
                             .map(|s| s.name.clone())
                             .unwrap_or_default(),
                         dest_state_name: self
@@ -604,12 +604,16 @@ impl<T: EntityId> MachineLayer<T> {
                 // Find transition.
                 for (handle, transition) in self.transitions.pair_iter_mut() {
                     if transition.dest() == self.active_state
-                        || transition.source() != self.active_state
+                        || (!transition.any_state && transition.source() != self.active_state)
                     {
                         continue;
                     }
 
                     if transition.condition.calculate_value(parameters, animations) {
+                        if transition.any_state {
+                            transition.active_source = self.active_state;
+                        }
+
                         if let Ok(active_state) = self.states.try_borrow(self.active_state) {
                             for action in active_state.on_leave_actions.iter() {
                                 action.apply(animations);
@@ -654,7 +658,7 @@ impl<T: EntityId> MachineLayer<T> {
                 let transition = &mut self.transitions[self.active_transition];
 
                 // Blend between source and dest states.
-                if let Ok(source_pose) = self.states[transition.source()].pose(&self.nodes) {
+                if let Ok(source_pose) = self.states[transition.active_source()].pose(&self.nodes) {
                     self.final_pose
                         .blend_with(&source_pose, 1.0 - transition.blend_factor());
                 }
@@ -674,7 +678,7 @@ impl<T: EntityId> MachineLayer<T> {
 
                     self.active_state = transition.dest();
                     self.events.push(Event::ActiveStateChanged {
-                        prev: transition.source(),
+                        prev: transition.active_source(),
                         new: transition.dest(),
                     });
 
@@ -334,7 +334,7 @@ impl<T: EntityId> Machine<T> {
             let mut states_to_check = [Some(layer.active_state()), None, None];
             if let Ok(active_transition) = layer.transitions().try_borrow(layer.active_transition())
             {
-                states_to_check[1] = Some(active_transition.source);
+                states_to_check[1] = Some(active_transition.active_source());
                 states_to_check[2] = Some(active_transition.dest);
             }
             for state_to_check in states_to_check.iter().flatten() {
@@ -362,3 +362,80 @@ impl<T: EntityId> Machine<T> {
         &self.final_pose
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        machine::{
+            transition::{LogicNode, WeightThresholdNode},
+            Machine, Parameter, PlayAnimation, PoseNode, State, Transition,
+        },
+        Animation, AnimationContainer,
+    };
+    use fyrox_core::pool::{ErasedHandle, Handle};
+
+    #[test]
+    fn test_speed_threshold_transition() {
+        let mut animations = AnimationContainer::<ErasedHandle>::new();
+        let idle_animation = animations.add(Animation::default());
+        let walk_animation = animations.add(Animation::default());
+        let hit_animation = animations.add(Animation::default());
+
+        let mut machine = Machine::<ErasedHandle>::new();
+        machine
+            .set_parameter("Speed", Parameter::Weight(0.0))
+            .set_parameter("Hit", Parameter::Rule(false));
+
+        let layer = &mut machine.layers_mut()[0];
+        let idle_node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(idle_animation)));
+        let idle = layer.add_state(State::new("Idle", idle_node));
+        let walk_node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(walk_animation)));
+        let walk = layer.add_state(State::new("Walk", walk_node));
+        let hit_node = layer.add_node(PoseNode::PlayAnimation(PlayAnimation::new(hit_animation)));
+        let hit = layer.add_state(State::new("Hit", hit_node));
+
+        let mut idle_to_walk = Transition::new("Idle->Walk", idle, walk, 0.5, "");
+        idle_to_walk.set_condition(LogicNode::IsWeightAbove(WeightThresholdNode::new(
+            "Speed", 1.0,
+        )));
+        let idle_to_walk = layer.add_transition(idle_to_walk);
+        let any_to_hit =
+            layer.add_transition(Transition::new_any_state("Any->Hit", hit, 0.5, "Hit"));
+
+        // Below the threshold.
+        machine.set_parameter("Speed", Parameter::Weight(0.5));
+        machine.evaluate_pose(&mut animations, 0.25);
+        assert_eq!(machine.layers()[0].active_state(), idle);
+
+        // Above the threshold, the transition starts and lasts for its blend time.
+        machine.set_parameter("Speed", Parameter::Weight(2.0));
+        machine.evaluate_pose(&mut animations, 0.25);
+        assert_eq!(machine.layers()[0].active_transition(), idle_to_walk);
+        assert_eq!(
+            machine.layers()[0].transition(idle_to_walk).blend_factor(),
+            0.5
+        );
+        machine.evaluate_pose(&mut animations, 0.25);
+        assert_eq!(machine.layers()[0].active_state(), walk);
+
+        // Any-state transition interrupts the walk state.
+        machine.set_parameter("Hit", Parameter::Rule(true));
+        machine.evaluate_pose(&mut animations, 0.25);
+        assert_eq!(machine.layers()[0].active_transition(), any_to_hit);
+        // The source of the transition itself stays intact, only the runtime source is changed.
+        assert_eq!(
+            machine.layers()[0].transition(any_to_hit).active_source(),
+            walk
+        );
+        assert_eq!(
+            machine.layers()[0].transition(any_to_hit).source(),
+            Handle::NONE
+        );
+        machine.evaluate_pose(&mut animations, 0.25);
+        assert_eq!(machine.layers()[0].active_state(), hit);
+
+        // And never re-enters the state that is already active.
+        machine.evaluate_pose(&mut animations, 0.25);
+        assert_eq!(machine.layers()[0].active_state(), hit);
+    }
+}
@@ -55,6 +55,10 @@ impl<T: EntityId> TypeUuidProvider for BlendSpacePoint<T> {
     }
 }
 
+/// Blend space is a pose node that blends a set of poses, placed on a plane, using a sampling point. When all
+/// the points lie on a single line (for example, on the X axis), the blend space works as 1D blend space and
+/// could be driven by a `Weight` parameter (such as speed of a character). Sampling points outside of the
+/// area covered by the points are clamped to it.
 #[derive(Debug, Visit, Clone, Reflect, PartialEq)]
 pub struct BlendSpace<T: EntityId> {
     base: BasePoseNode<T>,
@@ -131,9 +135,8 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendSpace<T> {
 
         pose.reset();
 
-        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
-        {
-            if let Some(weights) = self.fetch_weights(*sampling_point) {
+        if let Some(sampling_point) = self.fetch_sampling_point(params) {
+            if let Some(weights) = self.fetch_weights(sampling_point) {
                 let (ia, wa) = weights[0];
                 let (ib, wb) = weights[1];
                 let (ic, wc) = weights[2];
@@ -166,9 +169,8 @@ impl<T: EntityId> AnimationPoseSource<T> for BlendSpace<T> {
         animations: &AnimationContainer<T>,
         strategy: AnimationEventCollectionStrategy,
     ) -> Vec<(Handle<Animation<T>>, AnimationEvent)> {
-        if let Some(Parameter::SamplingPoint(sampling_point)) = params.get(&self.sampling_parameter)
-        {
-            if let Some(weights) = self.fetch_weights(*sampling_point) {
+        if let Some(sampling_point) = self.fetch_sampling_point(params) {
+            if let Some(weights) = self.fetch_weights(sampling_point) {
                 let (ia, wa) = weights[0];
                 let (ib, wb) = weights[1];
                 let (ic, wc) = weights[2];
@@ -350,14 +352,10 @@ impl<T: EntityId> BlendSpace<T> {
             return Some([(0, 1.0), (0, 0.0), (0, 0.0)]);
         }
 
-        // Check if there's an edge that contains a projection of the sampling point.
-        if self.points.len() == 2 {
-            let edge = self.points[1].position - self.points[0].position;
-            let to_point = sampling_point - self.points[0].position;
-            let t = to_point.dot(&edge) / edge.dot(&edge);
-            if (0.0..=1.0).contains(&t) {
-                return Some([(0, (1.0 - t)), (1, t), (0, 0.0)]);
-            }
+        // All the points lie on a line (1D blend space), interpolate between the two closest points
+        // on the line.
+        if self.triangles.is_empty() {
+            return self.fetch_weights_1d(sampling_point);
         }
 
         let triangles = &self.triangles;
@@ -383,7 +381,8 @@ impl<T: EntityId> BlendSpace<T> {
         }
 
         // If none of the triangles contains the sampling point, then try to find a closest edge of a
-        // triangle and calculate weights.
+        // triangle and calculate weights. The projection is clamped to the edge, which effectively clamps
+        // the sampling point to the area covered by the points.
         let mut min_distance = f32::MAX;
         let mut weights = None;
 
@@ -399,18 +398,16 @@ impl<T: EntityId> BlendSpace<T> {
                 let edge = pt_b - pt_a;
                 let to_point = sampling_point - pt_a;
 
-                let t = to_point.dot(&edge) / edge.dot(&edge);
+                let t = (to_point.dot(&edge) / edge.dot(&edge)).clamp(0.0, 1.0);
 
-                if (0.0..=1.0).contains(&t) {
-                    let projection = pt_a + edge.scale(t);
+                let projection = pt_a + edge.scale(t);
 
-                    let distance = sampling_point.metric_distance(&projection);
+                let distance = sampling_point.metric_distance(&projection);
 
-                    if distance < min_distance {
-                        min_distance = distance;
+                if distance < min_distance {
+                    min_distance = distance;
 
-                        weights = Some([(a, (1.0 - t)), (b, t), (b, 0.0)]);
-                    }
+                    weights = Some([(a, (1.0 - t)), (b, t), (b, 0.0)]);
                 }
             }
         }
@@ -418,6 +415,59 @@ impl<T: EntityId> BlendSpace<T> {
         weights
     }
 
+    fn fetch_weights_1d(&self, sampling_point: Vector2<f32>) -> Option<[(usize, f32); 3]> {
+        // Find the axis of the blend space, it is defined by the farthest point from the first one.
+        let origin = self.points.first()?.position;
+        let axis = self
+            .points
+            .iter()
+            .map(|p| p.position - origin)
+            .max_by(|a, b| {
+                a.norm_squared()
+                    .partial_cmp(&b.norm_squared())
+                    .unwrap_or(Ordering::Equal)
+            })?;
+
+        let length_squared = axis.norm_squared();
+        if length_squared <= f32::EPSILON {
+            return Some([(0, 1.0), (0, 0.0), (0, 0.0)]);
+        }
+
+        let project = |p: Vector2<f32>| (p - origin).dot(&axis) / length_squared;
+
+        let mut sorted = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, project(p.position)))
+            .collect::<Vec<_>>();
+        sorted.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        // Clamp the sampling point to the range covered by the points.
+        let t = project(sampling_point).clamp(sorted.first()?.1, sorted.last()?.1);
+
+        for pair in sorted.windows(2) {
+            let (a, ta) = pair[0];
+            let (b, tb) = pair[1];
+            if tb - ta > f32::EPSILON && (ta..=tb).contains(&t) {
+                let k = (t - ta) / (tb - ta);
+                return Some([(a, 1.0 - k), (b, k), (a, 0.0)]);
+            }
+        }
+
+        None
+    }
+
+    fn fetch_sampling_point(&self, params: &ParameterContainer) -> Option<Vector2<f32>> {
+        match params.get(&self.sampling_parameter)? {
+            Parameter::SamplingPoint(sampling_point) => Some(*sampling_point),
+            // Allows to use the blend space as 1D blend space driven by a scalar parameter (for example
+            // speed of a character), the points in this case should be placed on the X axis.
+            Parameter::Weight(weight) => Some(Vector2::new(*weight, 0.0)),
+            _ => None,
+        }
+    }
+
     fn triangulate(&mut self) -> bool {
         self.triangles.clear();
 
@@ -452,8 +502,22 @@ impl<T: EntityId> BlendSpace<T> {
 #[cfg(test)]
 mod test {
     use crate::{
-        core::{algebra::Vector2, math::TriangleDefinition},
-        machine::node::blendspace::{BlendSpace, BlendSpacePoint},
+        container::{TrackDataContainer, TrackValueKind},
+        core::{
+            algebra::{Vector2, Vector3},
+            math::{
+                curve::{Curve, CurveKey, CurveKeyKind},
+                TriangleDefinition,
+            },
+            pool::Pool,
+        },
+        machine::{
+            node::blendspace::{BlendSpace, BlendSpacePoint},
+            AnimationPoseSource, Parameter, ParameterContainer, PlayAnimation, PoseNode,
+        },
+        track::{Track, TrackBinding},
+        value::{TrackValue, ValueBinding},
+        Animation, AnimationContainer,
     };
     use fyrox_core::pool::ErasedHandle;
 
@@ -540,4 +604,89 @@ mod test {
             Some([(0, 0.0), (1, 1.0), (0, 0.0)])
         );
     }
+
+    #[test]
+    fn test_blend_space_sampling_clamping() {
+        let mut blend_space = BlendSpace::<ErasedHandle>::default();
+
+        blend_space.set_points(vec![
+            BlendSpacePoint {
+                position: Vector2::new(0.0, 0.0),
+                pose_source: Default::default(),
+            },
+            BlendSpacePoint {
+                position: Vector2::new(2.0, 0.0),
+                pose_source: Default::default(),
+            },
+            BlendSpacePoint {
+                position: Vector2::new(1.0, 0.0),
+                pose_source: Default::default(),
+            },
+        ]);
+
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(1.5, 0.0)),
+            Some([(2, 0.5), (1, 0.5), (2, 0.0)])
+        );
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(-1.0, 0.0)),
+            Some([(0, 1.0), (2, 0.0), (0, 0.0)])
+        );
+        assert_eq!(
+            blend_space.fetch_weights(Vector2::new(5.0, 0.0)),
+            Some([(2, 0.0), (1, 1.0), (2, 0.0)])
+        );
+    }
+
+    fn make_animation(target: ErasedHandle, x: f32) -> Animation<ErasedHandle> {
+        let mut frames_container = TrackDataContainer::new(TrackValueKind::Vector3);
+        frames_container.curves_mut()[0] =
+            Curve::from(vec![CurveKey::new(0.0, x, CurveKeyKind::Constant)]);
+
+        let mut animation = Animation::default();
+        animation.add_track_with_binding(
+            TrackBinding::new(target),
+            Track::new(frames_container, ValueBinding::Position),
+        );
+        animation.set_time_slice(0.0..1.0);
+        // Calculate the pose.
+        animation.tick(0.0);
+        animation
+    }
+
+    #[test]
+    fn test_1d_blend_space_midpoint_pose() {
+        let target = ErasedHandle::new(1, 1);
+
+        let mut animations = AnimationContainer::new();
+        let idle = animations.add(make_animation(target, 0.0));
+        let walk = animations.add(make_animation(target, 2.0));
+
+        let mut nodes = Pool::new();
+        let idle = nodes.spawn(PoseNode::PlayAnimation(PlayAnimation::new(idle)));
+        let walk = nodes.spawn(PoseNode::PlayAnimation(PlayAnimation::new(walk)));
+
+        let mut blend_space = BlendSpace::default();
+        blend_space.set_sampling_parameter("Speed".to_string());
+        blend_space.set_points(vec![
+            BlendSpacePoint {
+                position: Vector2::new(0.0, 0.0),
+                pose_source: idle,
+            },
+            BlendSpacePoint {
+                position: Vector2::new(1.0, 0.0),
+                pose_source: walk,
+            },
+        ]);
+
+        let mut params = ParameterContainer::default();
+        params.add("Speed", Parameter::Weight(0.5));
+
+        let pose = blend_space.eval_pose(&nodes, &params, &animations, 0.0);
+        let node_pose = pose.poses().get(&target).unwrap();
+        assert_eq!(
+            node_pose.values.values[0].value,
+            TrackValue::Vector3(Vector3::new(1.0, 0.0, 0.0))
+        );
+    }
 }
@@ -351,6 +351,26 @@ impl<T: EntityId> Reflect for NotNode<T> {
     }
 }
 
+/// A node that compares a value of a `Weight` parameter with a threshold. It is useful to make transitions
+/// that depend on continuous values, such as the speed of a character.
+#[derive(Default, Debug, Visit, Clone, Reflect, PartialEq)]
+pub struct WeightThresholdNode {
+    /// A name of a `Weight` parameter.
+    pub parameter: String,
+    /// A threshold value to compare the parameter with.
+    pub threshold: f32,
+}
+
+impl WeightThresholdNode {
+    /// Creates a new threshold node for the given parameter.
+    pub fn new(parameter: &str, threshold: f32) -> Self {
+        Self {
+            parameter: parameter.to_owned(),
+            threshold,
+        }
+    }
+}
+
 /// A node responsible for logical operations evaluation. It can have any number of descendant nodes.
 ///
 /// # Examples
@@ -391,6 +411,10 @@ pub enum LogicNode<T: EntityId> {
     Not(NotNode<T>),
     /// Returns `true` if the animation has ended, `false` - otherwise.
     IsAnimationEnded(Handle<Animation<T>>),
+    /// Returns `true` if the value of a `Weight` parameter is strictly greater than a threshold. `false` if the
+    /// parameter is not found or has different type. Use it together with [`LogicNode::Not`] to check if a
+    /// parameter is below (or equal to) the threshold.
+    IsWeightAbove(WeightThresholdNode),
 }
 
 impl<T: EntityId> TypeUuidProvider for LogicNode<T> {
@@ -440,6 +464,13 @@ impl<T: EntityId> LogicNode<T> {
                 .try_get(*animation)
                 .ok()
                 .is_none_or(|a| a.has_ended()),
+            LogicNode::IsWeightAbove(node) => parameters.get(&node.parameter).is_some_and(|p| {
+                if let Parameter::Weight(weight) = p {
+                    *weight > node.threshold
+                } else {
+                    false
+                }
+            }),
         }
     }
 }
@@ -464,6 +495,16 @@ pub struct Transition<T: EntityId> {
     /// Computational graph that can use any amount of Rule parameters to calculate transition value.
     pub(crate) condition: LogicNode<T>,
 
+    /// Any-state transitions could be activated from any state of a layer (except the destination state).
+    /// Source state of such transitions is assigned at the moment of activation.
+    #[reflect(read_only)]
+    pub(crate) any_state: bool,
+
+    /// The state that was active at the moment of the last activation of an any-state transition. It is
+    /// runtime-only and never serialized.
+    #[reflect(hidden)]
+    pub(crate) active_source: Handle<State<T>>,
+
     /// 0 - evaluates `src` pose, 1 - `dest`, 0..1 - blends `src` and `dest`
     pub(crate) blend_factor: f32,
 }
@@ -477,6 +518,7 @@ impl<T: EntityId> Visit for Transition<T> {
         self.source.visit("Source", &mut guard)?;
         self.dest.visit("Dest", &mut guard)?;
         self.blend_factor.visit("BlendFactor", &mut guard)?;
+        let _ = self.any_state.visit("AnyState", &mut guard);
 
         if guard.is_reading() {
             if self.condition.visit("Condition", &mut guard).is_err() {
@@ -527,9 +569,32 @@ impl<T: EntityId> Transition<T> {
             dest,
             blend_factor: 0.0,
             condition: LogicNode::Parameter(rule.to_owned()),
+            any_state: false,
+            active_source: Handle::NONE,
         }
     }
 
+    /// Creates a new named transition that could be activated from any state of a layer. Such transitions
+    /// are useful for reactions that must interrupt everything else, such as hit reactions or death. The
+    /// transition is never activated when the destination state is the active one.
+    pub fn new_any_state(
+        name: &str,
+        dest: Handle<State<T>>,
+        time: f32,
+        rule: &str,
+    ) -> Transition<T> {
+        Self {
+            any_state: true,
+            ..Self::new(name, Handle::NONE, dest, time, rule)
+        }
+    }
+
+    /// Returns `true` if the transition could be activated from any state, `false` - otherwise.
+    #[inline]
+    pub fn is_any_state(&self) -> bool {
+        self.any_state
+    }
+
     /// Returns a reference to the name of the transition.
     #[inline]
     pub fn name(&self) -> &str {
@@ -542,12 +607,25 @@ impl<T: EntityId> Transition<T> {
         self.transition_time
     }
 
-    /// Returns a handle to source state.
+    /// Returns a handle to source state. It is [`Handle::NONE`] for any-state transitions, see
+    /// [`Self::active_source`].
     #[inline]
     pub fn source(&self) -> Handle<State<T>> {
         self.source
     }
 
+    /// Returns a handle to the state the transition blends from. For any-state transitions this is the state
+    /// that was active at the moment of the last activation of the transition, for other transitions it is
+    /// the same as [`Self::source`].
+    #[inline]
+    pub fn active_source(&self) -> Handle<State<T>> {
+        if self.any_state {
+            self.active_source
+        } else {
+            self.source
+        }
+    }
+
     /// Returns a handle to destination state.
     #[inline]
     pub fn dest(&self) -> Handle<State<T>> {
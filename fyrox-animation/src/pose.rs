@@ -122,3 +122,138 @@ impl<T: EntityId> AnimationPose<T> {
         &mut self.poses
     }
 }
+
+/// Pose cross-fade allows to smoothly switch from a snapshot of some pose to an other pose over a period
+/// of time. It is independent of animation blending state machines and could be used for one-off
+/// reactions, such as hit or stagger, to prevent "popping" when an animation is switched abruptly.
+///
+/// # Example
+///
+/// ```rust
+/// use fyrox_animation::{Animation, AnimationPose, PoseCrossFade};
+/// use fyrox_core::pool::ErasedHandle;
+///
+/// fn play_hit_reaction(
+///     current_pose: &AnimationPose<ErasedHandle>,
+///     hit_animation: &mut Animation<ErasedHandle>,
+///     dt: f32,
+/// ) -> PoseCrossFade<ErasedHandle> {
+///     // Take a snapshot of current pose and blend it out over 0.2 seconds.
+///     let mut cross_fade = PoseCrossFade::new(current_pose, 0.2);
+///
+///     // This should be done every frame until the cross-fade is finished.
+///     hit_animation.tick(dt);
+///     let final_pose = cross_fade.evaluate(hit_animation.pose(), dt);
+///
+///     cross_fade
+/// }
+/// ```
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct PoseCrossFade<T: EntityId> {
+    snapshot: AnimationPose<T>,
+    output: AnimationPose<T>,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl<T: EntityId> PoseCrossFade<T> {
+    /// Takes a snapshot of the given pose and creates a cross-fade from it that lasts for the given
+    /// `duration` (in seconds).
+    pub fn new(pose: &AnimationPose<T>, duration: f32) -> Self {
+        let mut snapshot = AnimationPose::default();
+        pose.clone_into(&mut snapshot);
+        // The snapshot is static and must not produce any motion.
+        snapshot.set_root_motion(None);
+
+        Self {
+            snapshot,
+            output: Default::default(),
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Returns a reference to the snapshot of the pose.
+    pub fn snapshot(&self) -> &AnimationPose<T> {
+        &self.snapshot
+    }
+
+    /// Returns total duration of the cross-fade in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Returns current blend factor. 0 - the snapshot pose, 1 - the target pose.
+    pub fn blend_factor(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns `true` if the cross-fade is finished and the target pose can be used directly.
+    pub fn is_finished(&self) -> bool {
+        self.blend_factor() >= 1.0
+    }
+
+    /// Advances the cross-fade by the given time step and blends the snapshot with the `target` pose.
+    /// Node poses that exist only in the snapshot are kept as is, which means that the target pose
+    /// should animate the same set of nodes.
+    pub fn evaluate(&mut self, target: &AnimationPose<T>, dt: f32) -> &AnimationPose<T> {
+        self.elapsed += dt;
+
+        self.snapshot.clone_into(&mut self.output);
+        self.output.blend_with(target, self.blend_factor());
+
+        &self.output
+    }
+
+    /// Returns a reference to the result of the last [`Self::evaluate`] call.
+    pub fn pose(&self) -> &AnimationPose<T> {
+        &self.output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::ErasedHandle},
+        pose::{AnimationPose, PoseCrossFade},
+        value::{BoundValue, TrackValue, ValueBinding},
+    };
+
+    fn make_pose(node: ErasedHandle, position: Vector3<f32>) -> AnimationPose<ErasedHandle> {
+        let mut pose = AnimationPose::default();
+        pose.add_to_node_pose(
+            node,
+            BoundValue {
+                binding: ValueBinding::Position,
+                value: TrackValue::Vector3(position),
+            },
+        );
+        pose
+    }
+
+    #[test]
+    fn test_pose_cross_fade() {
+        let node = ErasedHandle::new(1, 1);
+        let snapshot = make_pose(node, Vector3::new(0.0, 0.0, 0.0));
+        let target = make_pose(node, Vector3::new(2.0, 4.0, 0.0));
+
+        let mut cross_fade = PoseCrossFade::new(&snapshot, 0.2);
+        cross_fade.evaluate(&target, 0.1);
+        assert!(!cross_fade.is_finished());
+        assert_eq!(
+            cross_fade.pose().poses()[&node].values.values[0].value,
+            TrackValue::Vector3(Vector3::new(1.0, 2.0, 0.0))
+        );
+
+        cross_fade.evaluate(&target, 0.1);
+        assert!(cross_fade.is_finished());
+        assert_eq!(
+            cross_fade.pose().poses()[&node].values.values[0].value,
+            TrackValue::Vector3(Vector3::new(2.0, 4.0, 0.0))
+        );
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Property track is a standalone track, that animates a single property of an entity. See
+//! [`PropertyTrack`] docs for more info.
+
+use crate::{
+    container::{TrackDataContainer, TrackValueKind},
+    core::{
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        reflect::prelude::*,
+        visitor::prelude::*,
+        ImmutableString,
+    },
+    track::{Track, TrackBinding},
+    value::{BoundValue, TrackValue, ValueBinding, ValueType},
+    EntityId,
+};
+
+/// Defines how the values between the keys of a property track are calculated.
+#[derive(Visit, Reflect, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PropertyTrackInterpolation {
+    /// The value of the previous key is used until the next key is reached. Useful for discrete
+    /// properties, such as visibility.
+    Constant,
+    /// The value changes linearly between the keys.
+    #[default]
+    Linear,
+    /// The value changes smoothly between the keys, it eases in and out of each key.
+    Cubic,
+}
+
+impl PropertyTrackInterpolation {
+    fn curve_key_kind(self) -> CurveKeyKind {
+        match self {
+            PropertyTrackInterpolation::Constant => CurveKeyKind::Constant,
+            PropertyTrackInterpolation::Linear => CurveKeyKind::Linear,
+            PropertyTrackInterpolation::Cubic => CurveKeyKind::new_cubic(0.0, 0.0),
+        }
+    }
+}
+
+fn value_kind(value_type: ValueType) -> TrackValueKind {
+    match value_type {
+        ValueType::Bool
+        | ValueType::F32
+        | ValueType::F64
+        | ValueType::U64
+        | ValueType::I64
+        | ValueType::U32
+        | ValueType::I32
+        | ValueType::U16
+        | ValueType::I16
+        | ValueType::U8
+        | ValueType::I8 => TrackValueKind::Real,
+        ValueType::Vector2Bool
+        | ValueType::Vector2F32
+        | ValueType::Vector2F64
+        | ValueType::Vector2U64
+        | ValueType::Vector2I64
+        | ValueType::Vector2U32
+        | ValueType::Vector2I32
+        | ValueType::Vector2U16
+        | ValueType::Vector2I16
+        | ValueType::Vector2U8
+        | ValueType::Vector2I8 => TrackValueKind::Vector2,
+        ValueType::Vector3Bool
+        | ValueType::Vector3F32
+        | ValueType::Vector3F64
+        | ValueType::Vector3U64
+        | ValueType::Vector3I64
+        | ValueType::Vector3U32
+        | ValueType::Vector3I32
+        | ValueType::Vector3U16
+        | ValueType::Vector3I16
+        | ValueType::Vector3U8
+        | ValueType::Vector3I8 => TrackValueKind::Vector3,
+        ValueType::Vector4Bool
+        | ValueType::Vector4F32
+        | ValueType::Vector4F64
+        | ValueType::Vector4U64
+        | ValueType::Vector4I64
+        | ValueType::Vector4U32
+        | ValueType::Vector4I32
+        | ValueType::Vector4U16
+        | ValueType::Vector4I16
+        | ValueType::Vector4U8
+        | ValueType::Vector4I8 => TrackValueKind::Vector4,
+        ValueType::UnitQuaternionF32 | ValueType::UnitQuaternionF64 => {
+            TrackValueKind::UnitQuaternion
+        }
+    }
+}
+
+/// Property track animates a single property of an entity over time. Unlike [`crate::track::Track`],
+/// property track is self-contained - it stores a binding to the animated entity and could be used
+/// without an animation, for example in cutscenes. Arbitrary properties are set using reflection,
+/// the property is defined by its path (`foo.bar.baz[1].foobar@EnumVariant.stuff`).
+///
+/// Every track has its own interpolation mode, see [`PropertyTrackInterpolation`] for more info.
+///
+/// ```rust
+/// # use fyrox_animation::{
+/// #     core::pool::ErasedHandle,
+/// #     property::{PropertyTrack, PropertyTrackInterpolation},
+/// #     value::{TrackValue, ValueType},
+/// # };
+/// fn make_fade_track(light: ErasedHandle) -> PropertyTrack<ErasedHandle> {
+///     let mut track = PropertyTrack::new(light, "intensity", ValueType::F32)
+///         .with_interpolation(PropertyTrackInterpolation::Cubic);
+///     track.add_key(0.0, TrackValue::Real(1.0));
+///     track.add_key(2.0, TrackValue::Real(0.0));
+///     track
+/// }
+/// ```
+#[derive(Debug, Visit, Reflect, Clone, PartialEq)]
+pub struct PropertyTrack<T: EntityId> {
+    binding: TrackBinding<T>,
+    value_binding: ValueBinding,
+    interpolation: PropertyTrackInterpolation,
+    frames: TrackDataContainer,
+}
+
+impl<T: EntityId> Default for PropertyTrack<T> {
+    fn default() -> Self {
+        Self {
+            binding: Default::default(),
+            value_binding: Default::default(),
+            interpolation: Default::default(),
+            frames: TrackDataContainer::new(TrackValueKind::Vector3),
+        }
+    }
+}
+
+impl<T: EntityId> PropertyTrack<T> {
+    /// Creates a new track, that animates a property of the given type at the given path of the
+    /// target entity.
+    pub fn new<S: Into<ImmutableString>>(target: T, property: S, value_type: ValueType) -> Self {
+        Self {
+            binding: TrackBinding::new(target),
+            value_binding: ValueBinding::Property {
+                name: property.into(),
+                value_type,
+            },
+            interpolation: Default::default(),
+            frames: TrackDataContainer::new(value_kind(value_type)),
+        }
+    }
+
+    /// Creates a new track, that animates local position of the target entity.
+    pub fn new_position(target: T) -> Self {
+        Self {
+            binding: TrackBinding::new(target),
+            value_binding: ValueBinding::Position,
+            interpolation: Default::default(),
+            frames: TrackDataContainer::new(TrackValueKind::Vector3),
+        }
+    }
+
+    /// Creates a new track, that animates local scale of the target entity.
+    pub fn new_scale(target: T) -> Self {
+        Self {
+            binding: TrackBinding::new(target),
+            value_binding: ValueBinding::Scale,
+            interpolation: Default::default(),
+            frames: TrackDataContainer::new(TrackValueKind::Vector3),
+        }
+    }
+
+    /// Creates a new track, that animates local rotation of the target entity.
+    pub fn new_rotation(target: T) -> Self {
+        Self {
+            binding: TrackBinding::new(target),
+            value_binding: ValueBinding::Rotation,
+            interpolation: Default::default(),
+            frames: TrackDataContainer::new(TrackValueKind::UnitQuaternion),
+        }
+    }
+
+    /// Sets the interpolation mode of the track and returns self.
+    pub fn with_interpolation(mut self, interpolation: PropertyTrackInterpolation) -> Self {
+        self.set_interpolation(interpolation);
+        self
+    }
+
+    /// Sets the interpolation mode of the track. Every existing key of the track will use the new
+    /// mode.
+    pub fn set_interpolation(&mut self, interpolation: PropertyTrackInterpolation) {
+        self.interpolation = interpolation;
+        let kind = interpolation.curve_key_kind();
+        for curve in self.frames.curves_mut() {
+            let id = curve.id();
+            let mut new_curve = Curve::from(
+                curve
+                    .keys()
+                    .iter()
+                    .map(|key| CurveKey {
+                        kind: kind.clone(),
+                        ..key.clone()
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            new_curve.set_id(id);
+            new_curve.set_name(curve.name());
+            *curve = new_curve;
+        }
+    }
+
+    /// Returns current interpolation mode of the track.
+    pub fn interpolation(&self) -> PropertyTrackInterpolation {
+        self.interpolation
+    }
+
+    /// Adds a new key to the track. The value must match the type of the animated property, for
+    /// example [`TrackValue::Real`] for scalar properties, [`TrackValue::Vector3`] for 3D vectors,
+    /// etc. Mismatched values are ignored.
+    pub fn add_key(&mut self, time: f32, value: TrackValue) {
+        let components = match (self.frames.value_kind(), value) {
+            (TrackValueKind::Real, TrackValue::Real(v)) => vec![v],
+            (TrackValueKind::Vector2, TrackValue::Vector2(v)) => vec![v.x, v.y],
+            (TrackValueKind::Vector3, TrackValue::Vector3(v)) => vec![v.x, v.y, v.z],
+            (TrackValueKind::Vector4, TrackValue::Vector4(v)) => vec![v.x, v.y, v.z, v.w],
+            (TrackValueKind::UnitQuaternion, TrackValue::UnitQuaternion(q)) => {
+                vec![q.coords.x, q.coords.y, q.coords.z, q.coords.w]
+            }
+            _ => return,
+        };
+
+        let kind = self.interpolation.curve_key_kind();
+        for (curve, component) in self.frames.curves_mut().iter_mut().zip(components) {
+            curve.add_key(CurveKey::new(time, component, kind.clone()));
+        }
+    }
+
+    /// Returns a reference to the data container of the track.
+    pub fn data_container(&self) -> &TrackDataContainer {
+        &self.frames
+    }
+
+    /// Returns a reference to the binding of the track.
+    pub fn binding(&self) -> &TrackBinding<T> {
+        &self.binding
+    }
+
+    /// Returns a reference to the binding of the track.
+    pub fn binding_mut(&mut self) -> &mut TrackBinding<T> {
+        &mut self.binding
+    }
+
+    /// Returns the entity animated by the track.
+    pub fn target(&self) -> T {
+        self.binding.target
+    }
+
+    /// Returns a property to which the track is bound to.
+    pub fn value_binding(&self) -> &ValueBinding {
+        &self.value_binding
+    }
+
+    /// Tries to get a new property value at a given time position.
+    pub fn fetch(&self, time: f32) -> Option<BoundValue> {
+        self.frames.fetch(time).map(|value| BoundValue {
+            binding: self.value_binding.clone(),
+            value,
+        })
+    }
+
+    /// Returns length of the track in seconds.
+    pub fn time_length(&self) -> f32 {
+        self.frames.time_length()
+    }
+
+    /// Converts the property track into an animation track and a binding to its target. It could
+    /// be used to play the property track as a part of an animation, see
+    /// [`crate::Animation::add_property_track`].
+    pub fn into_track(self) -> (TrackBinding<T>, Track) {
+        (self.binding, Track::new(self.frames, self.value_binding))
+    }
+
+    /// Sets the value of the property at a given time position to the given object. Only arbitrary
+    /// properties could be set using this method, standard properties (position, rotation, scale)
+    /// must be set by the code that knows the actual type of the object. Does nothing if the track
+    /// is disabled.
+    pub fn apply_to_object(&self, object: &mut dyn Reflect, time: f32) {
+        if !self.binding.enabled {
+            return;
+        }
+
+        if let ValueBinding::Property {
+            ref name,
+            value_type,
+        } = self.value_binding
+        {
+            if let Some(value) = self.fetch(time) {
+                value.apply_to_object(object, name, value_type);
+            }
+        }
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Sequence is a timeline, that plays multiple tracks of different kinds simultaneously. See
+//! [`Sequence`] docs for more info.
+
+use crate::{
+    core::{math::wrapf, reflect::prelude::*, visitor::prelude::*, ImmutableString},
+    property::PropertyTrack,
+    signal::{AnimationEvent, AnimationSignal},
+    Animation, AnimationPose, EntityId,
+};
+use std::collections::VecDeque;
+
+/// An animation placed on the timeline of a sequence. The animation starts playing when the playhead
+/// of the sequence reaches the start time of the clip.
+#[derive(Debug, Visit, Reflect, Clone, PartialEq)]
+pub struct SequenceClip<T: EntityId> {
+    /// A time position (in seconds) on the timeline of the sequence, at which the animation starts.
+    pub start: f32,
+    /// An animation played by the clip.
+    pub animation: Animation<T>,
+}
+
+impl<T: EntityId> Default for SequenceClip<T> {
+    fn default() -> Self {
+        Self {
+            start: 0.0,
+            animation: Default::default(),
+        }
+    }
+}
+
+impl<T: EntityId> SequenceClip<T> {
+    /// Creates a new clip, that plays the given animation starting from the given time.
+    pub fn new(start: f32, animation: Animation<T>) -> Self {
+        Self { start, animation }
+    }
+
+    /// Returns a time position (in seconds) on the timeline of the sequence, at which the clip ends.
+    pub fn end(&self) -> f32 {
+        self.start + self.animation.length()
+    }
+}
+
+/// Sequence is a timeline, that holds multiple tracks and plays them using a shared playhead. It is
+/// mostly used for cutscenes, where a lot of objects must be animated in sync. The sequence could
+/// contain:
+///
+/// - Property tracks, that animate arbitrary properties of entities. See [`PropertyTrack`] docs for
+/// more info.
+/// - Clips, that plays existing animations (usually skeletal animations) starting from specific time
+/// positions. See [`SequenceClip`] docs for more info.
+/// - Signals, that emit events when the playhead passes them. See [`AnimationSignal`] docs for more
+/// info.
+///
+/// The output of the sequence is a pose, that contains values of every animated property at the
+/// current time position. The pose is calculated from scratch every time, which means that the state
+/// of the sequence depends only on its time position and does not depend on playback history.
+///
+/// ```rust
+/// # use fyrox_animation::{
+/// #     core::pool::ErasedHandle,
+/// #     property::PropertyTrack,
+/// #     sequence::Sequence,
+/// #     value::{TrackValue, ValueType},
+/// # };
+/// fn make_cutscene(door: ErasedHandle) -> Sequence<ErasedHandle> {
+///     let mut track = PropertyTrack::new(door, "opening", ValueType::F32);
+///     track.add_key(0.0, TrackValue::Real(0.0));
+///     track.add_key(2.0, TrackValue::Real(1.0));
+///
+///     let mut sequence = Sequence::new();
+///     sequence.add_property_track(track);
+///     sequence.play();
+///     sequence
+/// }
+/// ```
+#[derive(Debug, Visit, Reflect, Clone, PartialEq)]
+pub struct Sequence<T: EntityId> {
+    name: ImmutableString,
+    property_tracks: Vec<PropertyTrack<T>>,
+    clips: Vec<SequenceClip<T>>,
+    signals: Vec<AnimationSignal>,
+    time_position: f32,
+    speed: f32,
+    looped: bool,
+    playing: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pose: AnimationPose<T>,
+    #[reflect(hidden)]
+    #[visit(skip)]
+    events: VecDeque<AnimationEvent>,
+}
+
+impl<T: EntityId> Default for Sequence<T> {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            property_tracks: Default::default(),
+            clips: Default::default(),
+            signals: Default::default(),
+            time_position: 0.0,
+            speed: 1.0,
+            looped: false,
+            playing: false,
+            pose: Default::default(),
+            events: Default::default(),
+        }
+    }
+}
+
+impl<T: EntityId> Sequence<T> {
+    /// Creates a new empty sequence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a new name of the sequence.
+    pub fn set_name<S: AsRef<str>>(&mut self, name: S) {
+        self.name = ImmutableString::new(name);
+    }
+
+    /// Returns name of the sequence.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Adds a new property track to the sequence.
+    pub fn add_property_track(&mut self, track: PropertyTrack<T>) {
+        self.property_tracks.push(track);
+    }
+
+    /// Returns a reference to the property tracks of the sequence.
+    pub fn property_tracks(&self) -> &[PropertyTrack<T>] {
+        &self.property_tracks
+    }
+
+    /// Returns a reference to the property tracks of the sequence.
+    pub fn property_tracks_mut(&mut self) -> &mut Vec<PropertyTrack<T>> {
+        &mut self.property_tracks
+    }
+
+    /// Adds a new clip to the sequence. See [`SequenceClip`] docs for more info.
+    pub fn add_clip(&mut self, clip: SequenceClip<T>) {
+        self.clips.push(clip);
+    }
+
+    /// Returns a reference to the clips of the sequence.
+    pub fn clips(&self) -> &[SequenceClip<T>] {
+        &self.clips
+    }
+
+    /// Returns a reference to the clips of the sequence.
+    pub fn clips_mut(&mut self) -> &mut Vec<SequenceClip<T>> {
+        &mut self.clips
+    }
+
+    /// Adds a new signal to the sequence. The signal will emit an event every time the playhead
+    /// passes it.
+    pub fn add_signal(&mut self, signal: AnimationSignal) {
+        self.signals.push(signal);
+    }
+
+    /// Returns a reference to the signals of the sequence.
+    pub fn signals(&self) -> &[AnimationSignal] {
+        &self.signals
+    }
+
+    /// Returns a reference to the signals of the sequence.
+    pub fn signals_mut(&mut self) -> &mut Vec<AnimationSignal> {
+        &mut self.signals
+    }
+
+    /// Returns length of the sequence in seconds. The length is defined by the longest track (or
+    /// the farthest signal) of the sequence.
+    pub fn length(&self) -> f32 {
+        self.property_tracks
+            .iter()
+            .map(|track| track.time_length())
+            .chain(self.clips.iter().map(|clip| clip.end()))
+            .chain(self.signals.iter().map(|signal| signal.time))
+            .fold(0.0, f32::max)
+    }
+
+    /// Starts (or resumes) playback of the sequence.
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+
+    /// Pauses playback of the sequence. The playhead stays at its current position.
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Stops playback of the sequence and moves the playhead to the beginning.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        self.seek(0.0);
+    }
+
+    /// Returns `true` if the sequence is playing, `false` - otherwise.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Enables or disables looping of the sequence. Looping sequence starts from the beginning when
+    /// the playhead reaches the end, non-looping sequence stops at the end.
+    pub fn set_loop(&mut self, looped: bool) {
+        self.looped = looped;
+    }
+
+    /// Returns `true` if the sequence is looping, `false` - otherwise.
+    pub fn is_loop(&self) -> bool {
+        self.looped
+    }
+
+    /// Sets new speed multiplier of the sequence. Negative values can be used to play the sequence
+    /// in reverse.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Returns speed multiplier of the sequence.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Returns current position of the playhead in seconds.
+    pub fn time_position(&self) -> f32 {
+        self.time_position
+    }
+
+    fn fit_time_position(&self, time: f32) -> f32 {
+        let length = self.length();
+        if self.looped && length > 0.0 {
+            wrapf(time, 0.0, length)
+        } else {
+            time.clamp(0.0, length)
+        }
+    }
+
+    /// Moves the playhead to the given time position and evaluates every track of the sequence at
+    /// this position. The time position is wrapped (if the sequence is looping) or clamped to the
+    /// length of the sequence. Signals are not emitted when seeking.
+    pub fn seek(&mut self, time: f32) {
+        self.time_position = self.fit_time_position(time);
+        self.evaluate();
+    }
+
+    /// Advances the playhead by the given time step (if the sequence is playing), emits events for
+    /// every passed signal and evaluates every track of the sequence at the new position. This method
+    /// is low level, in normal circumstances the engine will call it for you.
+    pub fn tick(&mut self, dt: f32) {
+        if self.playing {
+            let length = self.length();
+            let from = self.time_position;
+            let to = from + dt * self.speed;
+
+            self.collect_signal_events(from, to, length);
+
+            if !self.looped && (to >= length || to <= 0.0) {
+                self.playing = false;
+            }
+
+            self.time_position = self.fit_time_position(to);
+        }
+
+        self.evaluate();
+    }
+
+    fn collect_signal_events(&mut self, from: f32, to: f32, length: f32) {
+        let (min, max) = if from <= to { (from, to) } else { (to, from) };
+        let is_passed = |time: f32| {
+            if from <= to {
+                from < time && time <= to
+            } else {
+                to <= time && time < from
+            }
+        };
+
+        let mut passed_signals = Vec::new();
+        for signal in self.signals.iter().filter(|signal| signal.enabled) {
+            if self.looped && length > 0.0 {
+                let first_cycle = ((min - signal.time) / length).floor() as i32;
+                let last_cycle = ((max - signal.time) / length).ceil() as i32;
+                for cycle in first_cycle..=last_cycle {
+                    let time = signal.time + cycle as f32 * length;
+                    if is_passed(time) {
+                        passed_signals.push(((time - from).abs(), signal));
+                    }
+                }
+            } else if is_passed(signal.time) {
+                passed_signals.push(((signal.time - from).abs(), signal));
+            }
+        }
+
+        passed_signals.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        self.events.extend(
+            passed_signals
+                .into_iter()
+                .map(|(_, signal)| AnimationEvent {
+                    signal_id: signal.id,
+                    name: signal.name.clone(),
+                }),
+        );
+    }
+
+    /// Calculates the output pose at the current time position.
+    fn evaluate(&mut self) {
+        self.pose.reset();
+
+        for clip in self.clips.iter_mut() {
+            let local_time = self.time_position - clip.start;
+            if local_time < 0.0 {
+                continue;
+            }
+
+            let animation = &mut clip.animation;
+            let start = animation.time_slice().start;
+            animation.set_time_position(start + local_time);
+            // Zero time step calculates the pose at the current position without emitting any
+            // events.
+            animation.tick(0.0);
+
+            for node_pose in animation.pose().poses().values() {
+                for bound_value in node_pose.values.values.iter() {
+                    self.pose
+                        .add_to_node_pose(node_pose.node, bound_value.clone());
+                }
+            }
+        }
+
+        for track in self.property_tracks.iter() {
+            if !track.binding().is_enabled() {
+                continue;
+            }
+
+            if let Some(bound_value) = track.fetch(self.time_position) {
+                self.pose.add_to_node_pose(track.target(), bound_value);
+            }
+        }
+    }
+
+    /// Returns the output pose of the sequence at the current time position.
+    pub fn pose(&self) -> &AnimationPose<T> {
+        &self.pose
+    }
+
+    /// Extracts a first event from the events queue of the sequence.
+    pub fn pop_event(&mut self) -> Option<AnimationEvent> {
+        self.events.pop_front()
+    }
+
+    /// Takes the events queue and returns it to the caller, leaving the internal queue empty.
+    pub fn take_events(&mut self) -> VecDeque<AnimationEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::pool::ErasedHandle,
+        property::PropertyTrack,
+        sequence::Sequence,
+        signal::AnimationSignal,
+        value::{TrackValue, ValueType},
+        AnimationPose,
+    };
+    use fyrox_core::uuid::Uuid;
+
+    fn value_of(pose: &AnimationPose<ErasedHandle>, target: ErasedHandle) -> TrackValue {
+        pose.poses()[&target].values.values[0].value.clone()
+    }
+
+    fn make_sequence(a: ErasedHandle, b: ErasedHandle) -> Sequence<ErasedHandle> {
+        let mut track_a = PropertyTrack::new(a, "foo", ValueType::F32);
+        track_a.add_key(0.0, TrackValue::Real(0.0));
+        track_a.add_key(2.0, TrackValue::Real(20.0));
+
+        let mut track_b = PropertyTrack::new(b, "bar", ValueType::F32);
+        track_b.add_key(0.0, TrackValue::Real(10.0));
+        track_b.add_key(1.0, TrackValue::Real(0.0));
+
+        let mut sequence = Sequence::new();
+        sequence.add_property_track(track_a);
+        sequence.add_property_track(track_b);
+        sequence.add_signal(AnimationSignal::new(Uuid::new_v4(), "Signal", 1.5));
+        sequence
+    }
+
+    #[test]
+    fn test_sequence_seek() {
+        let a = ErasedHandle::new(0, 1);
+        let b = ErasedHandle::new(1, 1);
+
+        let mut fresh = make_sequence(a, b);
+        fresh.seek(0.5);
+        assert_eq!(value_of(fresh.pose(), a), TrackValue::Real(5.0));
+        assert_eq!(value_of(fresh.pose(), b), TrackValue::Real(5.0));
+
+        // Play the sequence for a while, loop it a few times and play it in reverse, then seek to
+        // the same position. The result must not depend on playback history.
+        let mut played = make_sequence(a, b);
+        played.set_loop(true);
+        played.play();
+        for _ in 0..100 {
+            played.tick(0.07);
+        }
+        played.set_speed(-1.0);
+        for _ in 0..10 {
+            played.tick(0.03);
+        }
+        assert!(played.pop_event().is_some());
+        played.pause();
+        played.seek(0.5);
+        assert_eq!(played.time_position(), 0.5);
+        assert_eq!(played.pose(), fresh.pose());
+
+        // Paused sequence does not move.
+        played.tick(1.0);
+        assert_eq!(played.time_position(), 0.5);
+        assert_eq!(played.pose(), fresh.pose());
+
+        // Past the end of a track, its last value is held.
+        fresh.seek(1.5);
+        assert_eq!(value_of(fresh.pose(), a), TrackValue::Real(15.0));
+        assert_eq!(value_of(fresh.pose(), b), TrackValue::Real(0.0));
+    }
+}
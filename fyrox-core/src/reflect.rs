@@ -33,8 +33,9 @@ use std::{
 
 pub mod prelude {
     pub use super::{
-        FieldMetadata, FieldMut, FieldRef, FieldValue, Reflect, ReflectArray, ReflectHashMap,
-        ReflectInheritableVariable, ReflectList, ResolvePath, SetFieldByPathError, SetFieldError,
+        FieldMetadata, FieldMut, FieldRef, FieldValue, PropertyInfo, Reflect, ReflectArray,
+        ReflectHashMap, ReflectInheritableVariable, ReflectList, ResolvePath, SetFieldByPathError,
+        SetFieldError,
     };
 }
 
@@ -140,6 +141,26 @@ pub struct FieldMetadata<'s> {
     pub precision: Option<usize>,
 }
 
+/// A description of a property of an object, produced by `properties` method of `dyn Reflect`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyInfo {
+    /// Full path to the property, that could be used in [`ResolvePath::resolve_path`].
+    pub path: String,
+    /// A human-readable name of the property.
+    pub display_name: String,
+    /// Name of the type of the property.
+    pub type_name: &'static str,
+    /// Type id of the property.
+    pub type_id: TypeId,
+    /// A property is not meant to be edited, either because it is marked as read-only or because
+    /// it is a part of a read-only property.
+    pub read_only: bool,
+    /// A minimal value of the property. Works only with numeric properties!
+    pub min_value: Option<f64>,
+    /// A maximal value of the property. Works only with numeric properties!
+    pub max_value: Option<f64>,
+}
+
 pub struct FieldRef<'a, 'b> {
     /// A reference to field's metadata.
     pub metadata: &'a FieldMetadata<'b>,
@@ -1015,6 +1036,52 @@ impl dyn Reflect {
         }
     }
 
+    /// Collects descriptions of every property of the object (recursively), which could be used to
+    /// build a generic property editor. Properties are returned in the order of their declaration,
+    /// every property could be read using [`ResolvePath::resolve_path`] and changed using
+    /// [`Self::set_field_by_path`] with its path. Properties of read-only properties are read-only
+    /// as well. Inheritable variables are reported with the type of their inner value.
+    pub fn properties(&self, ignored_types: &[TypeId]) -> Vec<PropertyInfo> {
+        let mut properties = Vec::<PropertyInfo>::new();
+        let mut read_only_paths = Vec::<String>::new();
+        self.enumerate_fields_recursively(
+            &mut |path, field_info, value| {
+                let Some(field_info) = field_info else {
+                    return;
+                };
+                if path.is_empty() {
+                    return;
+                }
+
+                let read_only = field_info.read_only
+                    || read_only_paths.iter().any(|parent| {
+                        path.strip_prefix(parent.as_str())
+                            .is_some_and(|rest| rest.starts_with(['.', '[']))
+                    });
+                if read_only {
+                    read_only_paths.push(path.to_string());
+                }
+
+                let info = PropertyInfo {
+                    path: path.to_string(),
+                    display_name: field_info.display_name.to_string(),
+                    type_name: Reflect::type_name(value),
+                    type_id: value.type_id(),
+                    read_only,
+                    min_value: field_info.min_value,
+                    max_value: field_info.max_value,
+                };
+                match properties.last_mut() {
+                    // Inner value of an inheritable variable has the same path.
+                    Some(last) if last.path == info.path => *last = info,
+                    _ => properties.push(info),
+                }
+            },
+            ignored_types,
+        );
+        properties
+    }
+
     pub fn enumerate_fields_recursively<F>(&self, func: &mut F, ignored_types: &[TypeId])
     where
         F: FnMut(&str, Option<&FieldRef>, &dyn Reflect),
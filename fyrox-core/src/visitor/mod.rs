@@ -625,6 +625,15 @@ impl Visitor {
             .any(|child| self.nodes.borrow(*child).name == name)
     }
 
+    /// Returns an iterator over the names of the regions, that are children of the current node.
+    pub fn region_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes
+            .borrow(self.current_node)
+            .children
+            .iter()
+            .map(|child| self.nodes.borrow(*child).name.as_str())
+    }
+
     /// If [Visitor::is_reading], find a node with the given name that is a child of the current
     /// node, and return a Visitor for the found node. Return an error if no node with that name exists.
     ///
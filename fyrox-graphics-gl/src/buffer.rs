@@ -156,6 +156,34 @@ impl GpuBufferTrait for GlBuffer {
         Ok(())
     }
 
+    fn write_data_at(&self, offset: usize, data: &[u8]) -> Result<(), FrameworkError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if offset + data.len() > self.size.get() {
+            return Err(FrameworkError::Custom(format!(
+                "Unable to write {} bytes at {offset} offset to a buffer of {} bytes!",
+                data.len(),
+                self.size.get()
+            )));
+        }
+
+        let Some(server) = self.state.upgrade() else {
+            return Err(FrameworkError::GraphicsServerUnavailable);
+        };
+
+        unsafe {
+            let gl_kind = self.kind.into_gl();
+            server.gl.bind_buffer(gl_kind, Some(self.id));
+            server
+                .gl
+                .buffer_sub_data_u8_slice(gl_kind, offset as i32, data);
+        }
+
+        Ok(())
+    }
+
     fn read_data(&self, data: &mut [u8]) -> Result<(), FrameworkError> {
         let Some(server) = self.state.upgrade() else {
             return Err(FrameworkError::GraphicsServerUnavailable);
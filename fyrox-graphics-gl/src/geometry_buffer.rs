@@ -179,6 +179,19 @@ impl GpuGeometryBufferTrait for GlGeometryBuffer {
             .unwrap();
     }
 
+    fn set_buffer_data_at(
+        &self,
+        buffer: usize,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError> {
+        self.state
+            .upgrade()
+            .ok_or(FrameworkError::GraphicsServerUnavailable)?
+            .set_vertex_array_object(Some(self.vertex_array_object));
+        self.buffers[buffer].write_data_at(offset, data)
+    }
+
     fn element_count(&self) -> usize {
         self.element_count.get()
     }
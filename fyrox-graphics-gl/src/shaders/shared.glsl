@@ -145,6 +145,136 @@ vec3 S_PBR_CalculateLight(TPBRContext ctx) {
     return (kD * ctx.albedo / PI + specular) * ctx.lightColor * NdotL;
 }
 
+// Maximum amount of vertices of an emitting polygon of an area light.
+#define S_AREA_LIGHT_MAX_VERTICES 8
+
+// Integral of clamped cosine over a spherical arc between two unit vectors (scaled by 1 / 2PI),
+// uses cubic rational fit of theta / sin(theta).
+vec3 S_IntegrateEdge(vec3 v1, vec3 v2)
+{
+    float x = dot(v1, v2);
+    float y = abs(x);
+    float a = 0.8543985 + (0.4965155 + 0.0145206 * y) * y;
+    float b = 3.4175940 + (4.1616724 + y) * y;
+    float v = a / b;
+    float thetaSinTheta = (x > 0.0) ? v : 0.5 * inversesqrt(max(1.0 - x * x, 1e-7)) - v;
+    return cross(v1, v2) * thetaSinTheta;
+}
+
+// Calculates form factor of a polygon transformed by the given basis, clipping by the horizon is
+// approximated. Columns of the basis are tangent, bitangent and direction of the cosine lobe.
+float S_PolygonFormFactor(vec3 points[S_AREA_LIGHT_MAX_VERTICES], int count, vec3 origin, mat3 basis)
+{
+    vec3 transformed[S_AREA_LIGHT_MAX_VERTICES];
+    for (int i = 0; i < count; ++i) {
+        transformed[i] = normalize((points[i] - origin) * basis);
+    }
+
+    vec3 formFactor = vec3(0.0);
+    for (int i = 0; i < count; ++i) {
+        int next = (i + 1) % count;
+        formFactor += S_IntegrateEdge(transformed[i], transformed[next]);
+    }
+
+    float len = length(formFactor);
+    return max((len * len + formFactor.z) / (len + 1.0), 0.0);
+}
+
+// Builds the emitting polygon of an area light, side and look vectors must be scaled by half-extents
+// of the light. Discs are approximated by regular polygons. Returns the amount of vertices.
+int S_AreaLightPolygon(vec3 position, vec3 side, vec3 look, bool isDisc, out vec3 points[S_AREA_LIGHT_MAX_VERTICES])
+{
+    if (isDisc) {
+        for (int i = 0; i < S_AREA_LIGHT_MAX_VERTICES; ++i) {
+            float angle = -2.0 * PI * float(i) / float(S_AREA_LIGHT_MAX_VERTICES);
+            points[i] = position - side * cos(angle) - look * sin(angle);
+        }
+        return S_AREA_LIGHT_MAX_VERTICES;
+    }
+
+    points[0] = position - side - look;
+    points[1] = position - side + look;
+    points[2] = position + side + look;
+    points[3] = position + side - look;
+    return 4;
+}
+
+// Calculates lighting from an emitting polygon of an area light. Diffuse part is the form factor of
+// the polygon, specular part is the form factor of the polygon for a clamped cosine lobe around the
+// reflection vector, stretched in tangent directions by GGX alpha. The latter is a cheap approximation
+// of GGX lobe, that makes highlights softer for larger lights. Does not apply distance attenuation.
+vec3 S_AreaLight(TPBRContext ctx, vec3 fragmentPosition, vec3 points[S_AREA_LIGHT_MAX_VERTICES], int count)
+{
+    vec3 N = ctx.fragmentNormal;
+    vec3 V = ctx.viewVector;
+
+    // Diffuse - clamped cosine around the normal.
+    vec3 T1 = normalize(abs(N.x) < 0.9 ? cross(N, vec3(1.0, 0.0, 0.0)) : cross(N, vec3(0.0, 1.0, 0.0)));
+    vec3 T2 = cross(N, T1);
+    float diffuse = S_PolygonFormFactor(points, count, fragmentPosition, mat3(T1, T2, N));
+
+    // Specular - clamped cosine around the reflection vector.
+    vec3 R = reflect(-V, N);
+    vec3 S1 = normalize(abs(R.x) < 0.9 ? cross(R, vec3(1.0, 0.0, 0.0)) : cross(R, vec3(0.0, 1.0, 0.0)));
+    vec3 S2 = cross(R, S1);
+    float alpha = max(ctx.roughness * ctx.roughness, 0.01);
+    float specular = S_PolygonFormFactor(points, count, fragmentPosition, mat3(S1 / alpha, S2 / alpha, R));
+
+    vec3 F0 = mix(vec3(0.04), ctx.albedo, ctx.metallic);
+    vec3 F = S_FresnelSchlick(max(dot(N, V), 0.0), F0);
+    vec3 kD = (vec3(1.0) - F) * (1.0 - ctx.metallic);
+
+    return (kD * ctx.albedo * diffuse + F * specular) * ctx.lightColor;
+}
+
+// Calculates lighting from a light source of `fyrox_lightsBlock` for forward passes. Directional
+// lights have infinite radius, point lights have both cone angle cosines equal to -1. Area lights
+// have non-zero side and look vectors (scaled by half-extents of the light) and emit light along
+// the negative light direction, the first parameter is 1 for discs. Light color must be in linear
+// space.
+vec3 S_ForwardLight(
+    TPBRContext ctx,
+    vec3 fragmentPosition,
+    vec3 lightPosition,
+    vec3 lightDirection,
+    vec4 lightColorRadius,
+    vec2 parameters,
+    vec3 areaSide,
+    vec3 areaLook)
+{
+    float radius = lightColorRadius.w;
+    if (isinf(radius)) {
+        ctx.fragmentToLight = lightDirection;
+        ctx.lightColor = lightColorRadius.rgb;
+        return S_PBR_CalculateLight(ctx);
+    }
+
+    vec3 fragmentToLight = lightPosition - fragmentPosition;
+    float distance = length(fragmentToLight);
+    ctx.fragmentToLight = fragmentToLight / max(distance, 0.0001);
+    ctx.lightColor = lightColorRadius.rgb;
+
+    float attenuation = S_LightDistanceAttenuation(distance, radius);
+
+    if (dot(areaSide, areaSide) > 0.0) {
+        if (dot(fragmentToLight, lightDirection) < 0.0) {
+            return vec3(0.0);
+        }
+        vec3 points[S_AREA_LIGHT_MAX_VERTICES];
+        int count = S_AreaLightPolygon(lightPosition, areaSide, areaLook, parameters.x > 0.5, points);
+        return S_AreaLight(ctx, fragmentPosition, points, count) * attenuation;
+    }
+
+    float halfHotspotAngleCos = parameters.x;
+    float halfConeAngleCos = parameters.y;
+    if (halfConeAngleCos > -1.0) {
+        float spotAngleCos = dot(lightDirection, ctx.fragmentToLight);
+        attenuation *= smoothstep(halfConeAngleCos, halfHotspotAngleCos, spotAngleCos);
+    }
+
+    return S_PBR_CalculateLight(ctx) * attenuation;
+}
+
 // Returns scatter amount for given parameters.
 // https://cseweb.ucsd.edu/~ravir/papers/singlescat/scattering.pdf
 // https://blog.mmacklin.com/2010/05/29/in-scattering-demo/
@@ -281,6 +411,169 @@ in sampler2D spotShadowTexture)
     }
 }
 
+// Calculates shadow factor of a directional light with cascaded shadow maps, where 1.0 - no shadow,
+// 0.0 - fully in shadow. `viewDepth` is the distance from the camera to the fragment along the view
+// direction, it is used to pick a cascade.
+float S_CsmShadowFactor(
+    bool softShadows,
+    float shadowBias,
+    vec3 fragmentPosition,
+    float viewDepth,
+    mat4 lightViewProjMatrices[3],
+    float cascadeDistances[3],
+    in sampler2D cascade0,
+    in sampler2D cascade1,
+    in sampler2D cascade2)
+{
+    if (viewDepth <= cascadeDistances[0]) {
+        return S_SpotShadowFactor(true, softShadows, shadowBias, fragmentPosition,
+            lightViewProjMatrices[0], 1.0 / float(textureSize(cascade0, 0).x), cascade0);
+    } else if (viewDepth <= cascadeDistances[1]) {
+        return S_SpotShadowFactor(true, softShadows, shadowBias, fragmentPosition,
+            lightViewProjMatrices[1], 1.0 / float(textureSize(cascade1, 0).x), cascade1);
+    } else if (viewDepth <= cascadeDistances[2]) {
+        return S_SpotShadowFactor(true, softShadows, shadowBias, fragmentPosition,
+            lightViewProjMatrices[2], 1.0 / float(textureSize(cascade2, 0).x), cascade2);
+    }
+    return 1.0;
+}
+
+// Estimates width of the penumbra for percentage-closer soft shadows (PCSS) using similar triangles.
+// Perspective projection (spot lights) makes the penumbra narrower for occluders far from the light.
+float S_PcssPenumbraWidth(float receiverDepth, float blockerDepth, float lightSize, bool perspective)
+{
+    float width = lightSize * max(receiverDepth - blockerDepth, 0.0);
+    return perspective ? width / max(blockerDepth, 0.0001) : width;
+}
+
+// Calculates spot light shadow factor using percentage-closer soft shadows (PCSS), where 1.0 - no
+// shadow, 0.0 - fully in shadow. Unlike regular PCF, the penumbra gets wider with the distance between
+// an occluder and a receiver. `lightSize` is the size of the light source in shadow map texture space.
+float S_SpotShadowFactorPcss(
+    bool shadowsEnabled,
+    bool perspective,
+    float shadowBias,
+    vec3 fragmentPosition,
+    mat4 lightViewProjMatrix,
+    float shadowMapInvSize,
+    float lightSize,
+in sampler2D spotShadowTexture)
+{
+    if (shadowsEnabled)
+    {
+        vec3 lightSpacePosition = S_Project(fragmentPosition, lightViewProjMatrix);
+
+        float biasedLightSpaceFragmentDepth = lightSpacePosition.z - shadowBias;
+
+        // Find average depth of the occluders in the area of the light source.
+        float blockerDepthSum = 0.0;
+        float blockerCount = 0.0;
+        for (float y = -1.0; y <= 1.0; y += 0.5)
+        {
+            for (float x = -1.0; x <= 1.0; x += 0.5)
+            {
+                float depth = texture(spotShadowTexture, lightSpacePosition.xy + vec2(x, y) * lightSize).r;
+                if (depth < biasedLightSpaceFragmentDepth)
+                {
+                    blockerDepthSum += depth;
+                    blockerCount += 1.0;
+                }
+            }
+        }
+
+        if (blockerCount == 0.0)
+        {
+            return 1.0;
+        }
+
+        float blockerDepth = blockerDepthSum / blockerCount;
+
+        // Filter the shadow with the kernel of the size of the penumbra, it is never smaller than
+        // a texel, so the shadow is never sharper than the one with regular PCF.
+        float filterRadius = max(S_PcssPenumbraWidth(biasedLightSpaceFragmentDepth, blockerDepth, lightSize, perspective), shadowMapInvSize);
+
+        float accumulator = 0.0;
+        for (float y = -1.0; y <= 1.0; y += 0.5)
+        {
+            for (float x = -1.0; x <= 1.0; x += 0.5)
+            {
+                vec2 fetchTexCoord = lightSpacePosition.xy + vec2(x, y) * filterRadius;
+                if (biasedLightSpaceFragmentDepth > texture(spotShadowTexture, fetchTexCoord).r)
+                {
+                    accumulator += 1.0;
+                }
+            }
+        }
+
+        return clamp(1.0 - accumulator / 25.0, 0.0, 1.0);
+    } else {
+        return 1.0; // No shadow
+    }
+}
+
+// Calculates point shadow factor using percentage-closer soft shadows (PCSS), where 1.0 - no shadow,
+// 0.0 - fully in shadow. See S_SpotShadowFactorPcss for more info.
+float S_PointShadowPcss(
+    bool shadowsEnabled,
+    float fragmentDistance,
+    float shadowBias,
+    vec3 toLight,
+    float lightSize,
+in samplerCube shadowMap)
+{
+    if (shadowsEnabled)
+    {
+        float biasedFragmentDistance = fragmentDistance - shadowBias;
+
+        const int samples = 20;
+
+        const vec3 directions[samples] = vec3[samples](
+        vec3(1, 1, 1), vec3(1, -1, 1), vec3(-1, -1, 1), vec3(-1, 1, 1),
+        vec3(1, 1, -1), vec3(1, -1, -1), vec3(-1, -1, -1), vec3(-1, 1, -1),
+        vec3(1, 1, 0), vec3(1, -1, 0), vec3(-1, -1, 0), vec3(-1, 1, 0),
+        vec3(1, 0, 1), vec3(-1, 0, 1), vec3(1, 0, -1), vec3(-1, 0, -1),
+        vec3(0, 1, 1), vec3(0, -1, 1), vec3(0, -1, -1), vec3(0, 1, -1)
+        );
+
+        vec3 fragmentDirection = normalize(-toLight);
+
+        float blockerDistanceSum = 0.0;
+        float blockerCount = 0.0;
+        for (int i = 0; i < samples; ++i)
+        {
+            float distance = texture(shadowMap, fragmentDirection + directions[i] * lightSize).r;
+            if (distance < biasedFragmentDistance)
+            {
+                blockerDistanceSum += distance;
+                blockerCount += 1.0;
+            }
+        }
+
+        if (blockerCount == 0.0)
+        {
+            return 1.0;
+        }
+
+        float blockerDistance = blockerDistanceSum / blockerCount;
+
+        float diskRadius = max(S_PcssPenumbraWidth(biasedFragmentDistance, blockerDistance, lightSize, true), 0.0025);
+
+        float accumulator = 0.0;
+        for (int i = 0; i < samples; ++i)
+        {
+            vec3 fetchDirection = fragmentDirection + directions[i] * diskRadius;
+            if (biasedFragmentDistance > texture(shadowMap, fetchDirection).r)
+            {
+                accumulator += 1.0;
+            }
+        }
+
+        return clamp(1.0 - accumulator / float(samples), 0.0, 1.0);
+    } else {
+        return 1.0; // No shadow
+    }
+}
+
 float Internal_FetchHeight(in sampler2D heightTexture, vec2 texCoords, float center) {
     return clamp(texture(heightTexture, texCoords).r - center, 0.0, 1.0);
 }
@@ -339,6 +632,45 @@ float S_Luminance(vec3 x) {
     return dot(x, vec3(0.2125, 0.7154, 0.0721));
 }
 
+// Must match the order of variants of `FogMode`.
+#define S_FOG_MODE_LINEAR 0
+#define S_FOG_MODE_EXP 1
+#define S_FOG_MODE_EXP2 2
+
+// Returns the amount of distance fog at the given view-space depth.
+float S_FogFactor(int mode, float start, float end, float density, float viewDepth)
+{
+    float factor;
+    if (mode == S_FOG_MODE_LINEAR) {
+        float range = end - start;
+        factor = range > 0.0 ? (viewDepth - start) / range : step(start, viewDepth);
+    } else if (mode == S_FOG_MODE_EXP) {
+        factor = 1.0 - exp(-density * viewDepth);
+    } else {
+        float d = density * viewDepth;
+        factor = 1.0 - exp(-d * d);
+    }
+    return clamp(factor, 0.0, 1.0);
+}
+
+// Returns the amount of exponential height fog between the camera and the fragment. The density of the
+// fog along the view ray is integrated analytically.
+float S_HeightFog(vec3 cameraPosition, vec3 fragmentPosition, float baseHeight, float falloff, float density)
+{
+    // Limits the exponent to prevent overflow when the camera is deep below the base height.
+    const float maxExponent = 80.0;
+
+    float distance = length(fragmentPosition - cameraPosition);
+    float cameraDensity = exp(min(-falloff * (cameraPosition.y - baseHeight), maxExponent));
+    float heightDelta = falloff * (fragmentPosition.y - cameraPosition.y);
+    float integral = cameraDensity;
+    if (abs(heightDelta) > 1.0e-4) {
+        float fragmentDensity = exp(min(-falloff * (fragmentPosition.y - baseHeight), maxExponent));
+        integral = (cameraDensity - fragmentDensity) / heightDelta;
+    }
+    return clamp(1.0 - exp(-density * distance * integral), 0.0, 1.0);
+}
+
 ivec2 S_LinearIndexToPosition(int index, int textureWidth) {
     int y = index / textureWidth;
     int x = index - textureWidth * y; // index % textureWidth
@@ -357,6 +689,42 @@ mat4 S_FetchMatrix(in sampler2D storage, int index) {
     return mat4(col1, col2, col3, col4);
 }
 
+// Blends bone transforms stored as packed dual quaternions (real part in the first column, dual part
+// in the second one) and returns an equivalent rigid transformation matrix. The matrix is divided by the
+// sum of the weights, so it could be used in place of every bone matrix in linear blend skinning.
+mat4 S_DualQuaternionSkinningMatrix(mat4 m0, mat4 m1, mat4 m2, mat4 m3, vec4 weights)
+{
+    vec4 r0 = m0[0];
+
+    // Take the shortest path, by flipping quaternions to the hemisphere of the first one.
+    float w1 = dot(r0, m1[0]) < 0.0 ? -weights.y : weights.y;
+    float w2 = dot(r0, m2[0]) < 0.0 ? -weights.z : weights.z;
+    float w3 = dot(r0, m3[0]) < 0.0 ? -weights.w : weights.w;
+
+    vec4 real = r0 * weights.x + m1[0] * w1 + m2[0] * w2 + m3[0] * w3;
+    vec4 dual = m0[1] * weights.x + m1[1] * w1 + m2[1] * w2 + m3[1] * w3;
+
+    float len = max(length(real), 0.000001);
+    real /= len;
+    dual /= len;
+
+    vec3 translation = 2.0 * (real.w * dual.xyz - dual.w * real.xyz + cross(real.xyz, dual.xyz));
+
+    float x = real.x;
+    float y = real.y;
+    float z = real.z;
+    float w = real.w;
+
+    mat4 result = mat4(
+        vec4(1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y + w * z), 2.0 * (x * z - w * y), 0.0),
+        vec4(2.0 * (x * y - w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z + w * x), 0.0),
+        vec4(2.0 * (x * z + w * y), 2.0 * (y * z - w * x), 1.0 - 2.0 * (x * x + y * y), 0.0),
+        vec4(translation, 1.0)
+    );
+
+    return result / max(dot(weights, vec4(1.0)), 0.000001);
+}
+
 struct TBlendShapeOffsets {
     vec3 position;
     vec3 normal;
@@ -150,6 +150,10 @@ pub trait GpuBufferTrait: GpuBufferAsAny {
     fn size(&self) -> usize;
     /// Writes an arbitrary number of bytes from the given slice.
     fn write_data(&self, data: &[u8]) -> Result<(), FrameworkError>;
+    /// Writes the bytes from the given slice at the given offset (in bytes). Unlike [`Self::write_data`],
+    /// this method never reallocates the buffer, which makes it suitable for partial updates of the
+    /// buffer. Fails if the written range is out of bounds of the buffer.
+    fn write_data_at(&self, offset: usize, data: &[u8]) -> Result<(), FrameworkError>;
     /// Read an arbitrary number of bytes from the buffer (GPU memory) to the given slice. The
     /// amount of the data that will be attempted to read is defined by the length of the given
     /// slice.
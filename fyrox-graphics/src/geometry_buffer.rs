@@ -26,7 +26,9 @@
 use crate::{
     buffer::BufferUsage,
     core::{array_as_u8_slice, math::TriangleDefinition},
-    define_shared_wrapper, ElementKind,
+    define_shared_wrapper,
+    error::FrameworkError,
+    ElementKind,
 };
 use bytemuck::Pod;
 use fyrox_core::define_as_any_trait;
@@ -207,6 +209,16 @@ pub trait GpuGeometryBufferTrait: GpuGeometryBufferAsAny {
     /// Write untyped data to a vertex buffer with the given index.
     fn set_buffer_data(&self, buffer: usize, data: &[u8]);
 
+    /// Writes untyped data to a vertex buffer with the given index starting at the given offset
+    /// (in bytes). It could be used to update only a part of the buffer. Fails if the written range
+    /// is out of bounds of the buffer.
+    fn set_buffer_data_at(
+        &self,
+        buffer: usize,
+        offset: usize,
+        data: &[u8],
+    ) -> Result<(), FrameworkError>;
+
     /// Returns total number of elements in the geometry buffer.
     fn element_count(&self) -> usize;
 
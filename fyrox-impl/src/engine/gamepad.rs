@@ -0,0 +1,455 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Gamepad state tracking. The engine does not poll gamepads by itself, a platform backend (for
+//! example, a plugin that uses `gilrs` crate) should translate its events into [`GamepadEvent`]
+//! and pass them to [`crate::engine::Engine::handle_gamepad_event`]. The state of gamepads is then
+//! available in [`crate::engine::input::InputState::gamepads`] and could be bound to actions of
+//! [`crate::engine::input_map::InputMap`].
+
+use fxhash::FxHashMap;
+use fyrox_core::{algebra::Vector2, visitor::prelude::*};
+
+/// Unique identifier of a gamepad, assigned by the backend.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub usize);
+
+/// A button of a gamepad. Names of the face buttons are given by their position, so they do not
+/// depend on the layout of a particular gamepad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Visit)]
+pub enum GamepadButton {
+    /// The bottom face button (A on Xbox gamepads, Cross on PlayStation gamepads).
+    South,
+    /// The right face button (B on Xbox gamepads, Circle on PlayStation gamepads).
+    East,
+    /// The top face button (Y on Xbox gamepads, Triangle on PlayStation gamepads).
+    North,
+    /// The left face button (X on Xbox gamepads, Square on PlayStation gamepads).
+    West,
+    /// Left bumper.
+    LeftBumper,
+    /// Right bumper.
+    RightBumper,
+    /// Left trigger, when it is reported as a button.
+    LeftTrigger,
+    /// Right trigger, when it is reported as a button.
+    RightTrigger,
+    /// Select (Back, Share) button.
+    Select,
+    /// Start (Options) button.
+    Start,
+    /// Mode (Guide, Home) button.
+    Mode,
+    /// Press of the left stick.
+    LeftStick,
+    /// Press of the right stick.
+    RightStick,
+    /// Up button of the directional pad.
+    DPadUp,
+    /// Down button of the directional pad.
+    DPadDown,
+    /// Left button of the directional pad.
+    DPadLeft,
+    /// Right button of the directional pad.
+    DPadRight,
+}
+
+/// An analog axis of a gamepad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Visit)]
+pub enum GamepadAxis {
+    /// Horizontal axis of the left stick, positive values are to the right.
+    LeftStickX,
+    /// Vertical axis of the left stick, positive values are up.
+    LeftStickY,
+    /// Horizontal axis of the right stick, positive values are to the right.
+    RightStickX,
+    /// Vertical axis of the right stick, positive values are up.
+    RightStickY,
+    /// Left trigger, in `[0; 1]` range.
+    LeftTrigger,
+    /// Right trigger, in `[0; 1]` range.
+    RightTrigger,
+}
+
+/// A stick of a gamepad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadStick {
+    /// Left stick.
+    Left,
+    /// Right stick.
+    Right,
+}
+
+impl GamepadStick {
+    fn axes(self) -> (GamepadAxis, GamepadAxis) {
+        match self {
+            Self::Left => (GamepadAxis::LeftStickX, GamepadAxis::LeftStickY),
+            Self::Right => (GamepadAxis::RightStickX, GamepadAxis::RightStickY),
+        }
+    }
+}
+
+impl GamepadAxis {
+    /// Returns the stick the axis belongs to, [`None`] for triggers.
+    pub fn stick(self) -> Option<GamepadStick> {
+        match self {
+            Self::LeftStickX | Self::LeftStickY => Some(GamepadStick::Left),
+            Self::RightStickX | Self::RightStickY => Some(GamepadStick::Right),
+            Self::LeftTrigger | Self::RightTrigger => None,
+        }
+    }
+}
+
+/// Defines the shape of a deadzone.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DeadzoneKind {
+    /// The deadzone is a circle, the length of the stick vector is compared with the deadzone. It
+    /// keeps the direction of the stick and is suitable for movement.
+    #[default]
+    Radial,
+    /// The deadzone is a cross, each axis is compared with the deadzone independently. It snaps
+    /// the stick to axes and is suitable for menus.
+    Axial,
+}
+
+/// A deadzone of a stick or a trigger. Values below the inner threshold are reported as zero,
+/// values above the outer threshold are reported as one, values in between are remapped to the
+/// full `[0; 1]` range.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Deadzone {
+    /// Shape of the deadzone. It is ignored for triggers.
+    pub kind: DeadzoneKind,
+    /// Inner threshold, in `[0; 1]` range.
+    pub inner: f32,
+    /// Outer threshold, in `[0; 1]` range. Must be larger than the inner threshold.
+    pub outer: f32,
+}
+
+impl Default for Deadzone {
+    fn default() -> Self {
+        Self {
+            kind: DeadzoneKind::Radial,
+            inner: 0.15,
+            outer: 0.95,
+        }
+    }
+}
+
+impl Deadzone {
+    /// Remaps the given non-negative magnitude.
+    fn remap(&self, magnitude: f32) -> f32 {
+        if magnitude <= self.inner {
+            0.0
+        } else {
+            ((magnitude - self.inner) / (self.outer - self.inner).max(f32::EPSILON)).min(1.0)
+        }
+    }
+
+    /// Applies the deadzone to a single axis value.
+    pub fn apply_axis(&self, value: f32) -> f32 {
+        self.remap(value.abs()).copysign(value)
+    }
+
+    /// Applies the deadzone to a stick vector.
+    pub fn apply_stick(&self, value: Vector2<f32>) -> Vector2<f32> {
+        match self.kind {
+            DeadzoneKind::Radial => {
+                let magnitude = value.norm();
+                if magnitude <= self.inner {
+                    Vector2::default()
+                } else {
+                    value * (self.remap(magnitude) / magnitude)
+                }
+            }
+            DeadzoneKind::Axial => Vector2::new(self.apply_axis(value.x), self.apply_axis(value.y)),
+        }
+    }
+}
+
+/// An event of a gamepad, that is produced by a platform backend.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    /// A gamepad was connected.
+    Connected {
+        /// Identifier of the gamepad.
+        id: GamepadId,
+        /// Human-readable name of the gamepad.
+        name: String,
+    },
+    /// A gamepad was disconnected.
+    Disconnected {
+        /// Identifier of the gamepad.
+        id: GamepadId,
+    },
+    /// A button of a gamepad was pressed or released.
+    Button {
+        /// Identifier of the gamepad.
+        id: GamepadId,
+        /// The button.
+        button: GamepadButton,
+        /// New state of the button.
+        pressed: bool,
+    },
+    /// An axis of a gamepad has changed its value.
+    Axis {
+        /// Identifier of the gamepad.
+        id: GamepadId,
+        /// The axis.
+        axis: GamepadAxis,
+        /// Raw value of the axis, in `[-1; 1]` range for sticks and `[0; 1]` range for triggers.
+        value: f32,
+    },
+}
+
+/// The state of a single gamepad.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gamepad {
+    /// Human-readable name of the gamepad.
+    pub name: String,
+    connected: bool,
+    buttons: FxHashMap<GamepadButton, bool>,
+    axes: FxHashMap<GamepadAxis, f32>,
+    /// Deadzone of the left stick.
+    pub left_stick_deadzone: Deadzone,
+    /// Deadzone of the right stick.
+    pub right_stick_deadzone: Deadzone,
+    /// Deadzone of the triggers.
+    pub trigger_deadzone: Deadzone,
+}
+
+impl Gamepad {
+    /// Returns `true` if the gamepad is connected.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Returns `true` if the given button is pressed.
+    pub fn is_button_down(&self, button: GamepadButton) -> bool {
+        self.buttons.get(&button).copied().unwrap_or_default()
+    }
+
+    /// Returns the raw value of the given axis, without deadzone applied.
+    pub fn raw_axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes.get(&axis).copied().unwrap_or_default()
+    }
+
+    /// Returns the deadzone of the given stick.
+    pub fn stick_deadzone(&self, stick: GamepadStick) -> &Deadzone {
+        match stick {
+            GamepadStick::Left => &self.left_stick_deadzone,
+            GamepadStick::Right => &self.right_stick_deadzone,
+        }
+    }
+
+    /// Returns the value of the given stick with its deadzone applied.
+    pub fn stick(&self, stick: GamepadStick) -> Vector2<f32> {
+        let (x, y) = stick.axes();
+        self.stick_deadzone(stick)
+            .apply_stick(Vector2::new(self.raw_axis(x), self.raw_axis(y)))
+    }
+
+    /// Returns the value of the given axis with the deadzone applied. Stick axes use the deadzone
+    /// of their stick, so radial deadzones take both axes of the stick into account.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        match axis.stick() {
+            Some(stick) => {
+                let value = self.stick(stick);
+                if stick.axes().0 == axis {
+                    value.x
+                } else {
+                    value.y
+                }
+            }
+            None => self.trigger_deadzone.apply_axis(self.raw_axis(axis)),
+        }
+    }
+}
+
+/// A set of gamepads known to the engine. Disconnected gamepads are kept in the set (with their
+/// state reset), so their settings (deadzones) are preserved when they are connected again.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gamepads {
+    gamepads: FxHashMap<GamepadId, Gamepad>,
+    /// Gamepads that were connected in the current frame.
+    pub connected: Vec<GamepadId>,
+    /// Gamepads that were disconnected in the current frame.
+    pub disconnected: Vec<GamepadId>,
+}
+
+impl Gamepads {
+    /// Updates the state of gamepads using the given event.
+    pub fn handle_event(&mut self, event: &GamepadEvent) {
+        match event {
+            GamepadEvent::Connected { id, name } => {
+                let gamepad = self.gamepads.entry(*id).or_default();
+                gamepad.name.clone_from(name);
+                if !gamepad.connected {
+                    gamepad.connected = true;
+                    self.connected.push(*id);
+                }
+            }
+            GamepadEvent::Disconnected { id } => {
+                if let Some(gamepad) = self.gamepads.get_mut(id) {
+                    if gamepad.connected {
+                        gamepad.connected = false;
+                        gamepad.buttons.clear();
+                        gamepad.axes.clear();
+                        self.disconnected.push(*id);
+                    }
+                }
+            }
+            GamepadEvent::Button {
+                id,
+                button,
+                pressed,
+            } => {
+                if let Some(gamepad) = self.get_mut(*id) {
+                    gamepad.buttons.insert(*button, *pressed);
+                }
+            }
+            GamepadEvent::Axis { id, axis, value } => {
+                if let Some(gamepad) = self.get_mut(*id) {
+                    gamepad.axes.insert(*axis, value.clamp(-1.0, 1.0));
+                }
+            }
+        }
+    }
+
+    /// Returns a reference to the connected gamepad with the given id.
+    pub fn get(&self, id: GamepadId) -> Option<&Gamepad> {
+        self.gamepads.get(&id).filter(|gamepad| gamepad.connected)
+    }
+
+    /// Returns a reference to the connected gamepad with the given id.
+    pub fn get_mut(&mut self, id: GamepadId) -> Option<&mut Gamepad> {
+        self.gamepads
+            .get_mut(&id)
+            .filter(|gamepad| gamepad.connected)
+    }
+
+    /// Returns an iterator over every connected gamepad, sorted by id.
+    pub fn iter(&self) -> impl Iterator<Item = (GamepadId, &Gamepad)> {
+        let mut gamepads = self
+            .gamepads
+            .iter()
+            .filter(|(_, gamepad)| gamepad.connected)
+            .map(|(id, gamepad)| (*id, gamepad))
+            .collect::<Vec<_>>();
+        gamepads.sort_by_key(|(id, _)| *id);
+        gamepads.into_iter()
+    }
+
+    /// Returns the connected gamepad with the smallest id.
+    pub fn first(&self) -> Option<(GamepadId, &Gamepad)> {
+        self.iter().next()
+    }
+
+    /// Clears per-frame data (connection events).
+    pub fn clear_events(&mut self) {
+        self.connected.clear();
+        self.disconnected.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::gamepad::{
+        Deadzone, DeadzoneKind, GamepadAxis, GamepadEvent, GamepadId, GamepadStick, Gamepads,
+    };
+    use fyrox_core::algebra::Vector2;
+
+    fn assert_close(a: Vector2<f32>, b: Vector2<f32>) {
+        assert!((a - b).norm() < 1.0e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_deadzones() {
+        let radial = Deadzone {
+            kind: DeadzoneKind::Radial,
+            inner: 0.2,
+            outer: 1.0,
+        };
+        assert_eq!(
+            radial.apply_stick(Vector2::new(0.1, 0.1)),
+            Vector2::default()
+        );
+        assert_close(
+            radial.apply_stick(Vector2::new(0.6, 0.0)),
+            Vector2::new(0.5, 0.0),
+        );
+        assert_close(
+            radial.apply_stick(Vector2::new(0.0, -1.0)),
+            Vector2::new(0.0, -1.0),
+        );
+
+        let axial = Deadzone {
+            kind: DeadzoneKind::Axial,
+            inner: 0.2,
+            outer: 0.8,
+        };
+        assert_close(
+            axial.apply_stick(Vector2::new(0.1, 0.5)),
+            Vector2::new(0.0, 0.5),
+        );
+        assert_close(
+            axial.apply_stick(Vector2::new(-0.9, 0.0)),
+            Vector2::new(-1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn test_hot_plug() {
+        let id = GamepadId(3);
+        let mut gamepads = Gamepads::default();
+
+        gamepads.handle_event(&GamepadEvent::Connected {
+            id,
+            name: "Gamepad".to_string(),
+        });
+        assert_eq!(gamepads.connected, vec![id]);
+        gamepads.clear_events();
+
+        gamepads.get_mut(id).unwrap().left_stick_deadzone.inner = 0.5;
+        gamepads.handle_event(&GamepadEvent::Axis {
+            id,
+            axis: GamepadAxis::LeftStickX,
+            value: 0.4,
+        });
+        // Inside the deadzone.
+        assert_eq!(
+            gamepads.get(id).unwrap().stick(GamepadStick::Left),
+            Vector2::default()
+        );
+
+        gamepads.handle_event(&GamepadEvent::Disconnected { id });
+        assert_eq!(gamepads.disconnected, vec![id]);
+        assert!(gamepads.get(id).is_none());
+        assert_eq!(gamepads.iter().count(), 0);
+
+        // Settings are preserved, the state is not.
+        gamepads.handle_event(&GamepadEvent::Connected {
+            id,
+            name: "Gamepad".to_string(),
+        });
+        let gamepad = gamepads.get(id).unwrap();
+        assert_eq!(gamepad.left_stick_deadzone.inner, 0.5);
+        assert_eq!(gamepad.raw_axis(GamepadAxis::LeftStickX), 0.0);
+    }
+}
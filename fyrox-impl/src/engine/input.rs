@@ -23,6 +23,10 @@
 //! in simple scenarios where you just need to know if a button (on keyboard, mouse) was pressed
 //! and do something. You should always prefer the event-based approach when possible.
 
+use crate::engine::{
+    gamepad::{GamepadAxis, GamepadButton, GamepadId, Gamepads},
+    touch::Touches,
+};
 use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::algebra::Vector2;
 use winit::event::{ButtonId, ElementState};
@@ -38,6 +42,8 @@ pub struct Mouse {
     pub position: Vector2<f32>,
     /// Speed of the mouse in some units.
     pub speed: Vector2<f32>,
+    /// Amount of rotation of the mouse wheel in the current frame. Usually it is measured in lines.
+    pub wheel: Vector2<f32>,
     /// Physical state of mouse buttons. Usually, the button indices are the following:
     ///
     /// - 0 - left mouse button
@@ -101,9 +107,31 @@ pub struct InputState {
     pub mouse: Mouse,
     /// Represents the keyboard state in the current frame.
     pub keyboard: Keyboard,
+    /// Represents the state of every known gamepad in the current frame.
+    pub gamepads: Gamepads,
+    /// Represents the state of every touch and recognized gestures in the current frame.
+    pub touches: Touches,
 }
 
 impl InputState {
+    /// Returns `true` if the specified button is pressed on any connected gamepad.
+    #[inline]
+    pub fn is_gamepad_button_down(&self, button: GamepadButton) -> bool {
+        self.gamepads
+            .iter()
+            .any(|(_, gamepad)| gamepad.is_button_down(button))
+    }
+
+    /// Returns the value of the given axis (with the deadzone applied) of the specified gamepad.
+    /// Returns zero if there is no such gamepad connected.
+    #[inline]
+    pub fn gamepad_axis(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.gamepads
+            .get(id)
+            .map(|gamepad| gamepad.axis(axis))
+            .unwrap_or_default()
+    }
+
     /// Returns `true` if the specified key is pressed, `false` - otherwise.
     #[inline]
     pub fn is_key_down(&self, key: KeyCode) -> bool {
@@ -239,6 +267,12 @@ impl InputState {
         self.mouse.speed
     }
 
+    /// Returns amount of rotation of the mouse wheel in the current frame.
+    #[inline]
+    pub fn mouse_wheel(&self) -> Vector2<f32> {
+        self.mouse.wheel
+    }
+
     /// Returns mouse position in pixels relative to the top-left corner of the main window.
     #[inline]
     pub fn mouse_position(&self) -> Vector2<f32> {
@@ -0,0 +1,542 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Input map allows you to bind logical actions (for example "Jump" or "Fire") to physical inputs
+//! (keys, mouse buttons, etc.) and then check the state of the actions instead of the state of raw
+//! inputs. Bindings could be changed at runtime, which makes it easy to implement controls
+//! settings in games. See [`InputMap`] docs for more info.
+
+use crate::{
+    engine::{
+        gamepad::{GamepadAxis, GamepadButton},
+        input::InputState,
+    },
+    utils::{translate_key_from_ui, translate_key_to_ui},
+};
+use fxhash::FxHashMap;
+use fyrox_core::{
+    algebra::Vector2,
+    visitor::{error::VisitError, prelude::*},
+};
+use winit::{event::ButtonId, keyboard::KeyCode};
+
+/// An axis of the mouse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Visit)]
+pub enum MouseAxis {
+    /// Horizontal axis.
+    X,
+    /// Vertical axis.
+    Y,
+}
+
+impl MouseAxis {
+    fn value(self, v: Vector2<f32>) -> f32 {
+        match self {
+            Self::X => v.x,
+            Self::Y => v.y,
+        }
+    }
+}
+
+/// A component of a touch gesture, see [`crate::engine::touch::TouchGesture`] docs for more info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Visit)]
+pub enum TouchGestureAxis {
+    /// Change of the distance between two fingers, in pixels.
+    Pinch,
+    /// Rotation of two fingers, in radians.
+    Rotation,
+    /// Horizontal movement of fingers, in pixels.
+    PanX,
+    /// Vertical movement of fingers, in pixels.
+    PanY,
+}
+
+/// A physical input, that could be bound to an action.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InputBinding {
+    /// A key on the keyboard. Its value is either `0.0` or `1.0`.
+    Key(KeyCode),
+    /// A mouse button, see [`crate::engine::input::Mouse`] docs for button indices. Its value is
+    /// either `0.0` or `1.0`.
+    MouseButton(ButtonId),
+    /// Movement of the mouse along the given axis. Its value is the mouse speed along the axis.
+    MouseMotion(MouseAxis),
+    /// Rotation of the mouse wheel along the given axis. Its value is the amount of wheel rotation
+    /// in the current frame.
+    MouseWheel(MouseAxis),
+    /// A button of any connected gamepad. Its value is either `0.0` or `1.0`.
+    GamepadButton(GamepadButton),
+    /// An axis of any connected gamepad, with the deadzone of the gamepad applied. Its value is in
+    /// `[-1; 1]` range for sticks and `[0; 1]` range for triggers. If there are multiple gamepads,
+    /// the value with the largest magnitude is used.
+    GamepadAxis(GamepadAxis),
+    /// A component of a touch gesture. Its value is the amount of change in the current frame.
+    TouchGesture(TouchGestureAxis),
+}
+
+impl InputBinding {
+    fn kind(&self) -> u8 {
+        match self {
+            Self::Key(_) => 0,
+            Self::MouseButton(_) => 1,
+            Self::MouseMotion(_) => 2,
+            Self::MouseWheel(_) => 3,
+            Self::GamepadButton(_) => 4,
+            Self::GamepadAxis(_) => 5,
+            Self::TouchGesture(_) => 6,
+        }
+    }
+
+    fn from_kind(kind: u8) -> Option<Self> {
+        Some(match kind {
+            0 => Self::Key(KeyCode::Escape),
+            1 => Self::MouseButton(0),
+            2 => Self::MouseMotion(MouseAxis::X),
+            3 => Self::MouseWheel(MouseAxis::X),
+            4 => Self::GamepadButton(GamepadButton::South),
+            5 => Self::GamepadAxis(GamepadAxis::LeftStickX),
+            6 => Self::TouchGesture(TouchGestureAxis::Pinch),
+            _ => return None,
+        })
+    }
+}
+
+// Manual implementation, because key codes of winit do not implement `Visit`. Keys are stored
+// using key codes of the UI library, which have the same set of keys.
+impl Visit for InputBinding {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut region = visitor.enter_region(name)?;
+
+        let mut kind = self.kind();
+        kind.visit("Kind", &mut region)?;
+        if region.is_reading() {
+            *self = Self::from_kind(kind)
+                .ok_or_else(|| VisitError::User(format!("Unknown input binding kind {kind}!")))?;
+        }
+
+        match self {
+            Self::Key(key) => {
+                let mut ui_key = translate_key_to_ui(*key);
+                ui_key.visit("Value", &mut region)?;
+                *key = translate_key_from_ui(ui_key);
+                Ok(())
+            }
+            Self::MouseButton(button) => button.visit("Value", &mut region),
+            Self::MouseMotion(axis) | Self::MouseWheel(axis) => axis.visit("Value", &mut region),
+            Self::GamepadButton(button) => button.visit("Value", &mut region),
+            Self::GamepadAxis(axis) => axis.visit("Value", &mut region),
+            Self::TouchGesture(axis) => axis.visit("Value", &mut region),
+        }
+    }
+}
+
+/// A source of values of physical inputs.
+pub trait InputSource {
+    /// Returns the current value of the given binding. Digital inputs (keys, buttons) return either
+    /// `0.0` or `1.0`, analog inputs return values in arbitrary range.
+    fn binding_value(&self, binding: &InputBinding) -> f32;
+}
+
+impl InputSource for InputState {
+    fn binding_value(&self, binding: &InputBinding) -> f32 {
+        let digital = |state: bool| if state { 1.0 } else { 0.0 };
+        match binding {
+            InputBinding::Key(key) => digital(self.is_key_down(*key)),
+            InputBinding::MouseButton(button) => digital(self.is_mouse_button_down(*button)),
+            InputBinding::MouseMotion(axis) => axis.value(self.mouse.speed),
+            InputBinding::MouseWheel(axis) => axis.value(self.mouse.wheel),
+            InputBinding::GamepadButton(button) => digital(self.is_gamepad_button_down(*button)),
+            InputBinding::GamepadAxis(axis) => self
+                .gamepads
+                .iter()
+                .map(|(_, gamepad)| gamepad.axis(*axis))
+                .fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a }),
+            InputBinding::TouchGesture(axis) => {
+                let gesture = self.touches.gesture();
+                match axis {
+                    TouchGestureAxis::Pinch => gesture.pinch,
+                    TouchGestureAxis::Rotation => gesture.rotation,
+                    TouchGestureAxis::PanX => gesture.pan.x,
+                    TouchGestureAxis::PanY => gesture.pan.y,
+                }
+            }
+        }
+    }
+}
+
+/// State of a single action.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ActionState {
+    bindings: Vec<InputBinding>,
+    value: f32,
+    pressed: bool,
+    was_pressed: bool,
+}
+
+impl ActionState {
+    /// Returns a slice with every binding of the action.
+    pub fn bindings(&self) -> &[InputBinding] {
+        &self.bindings
+    }
+
+    /// Returns the value of the action in the current frame. See [`InputMap::action_value`] docs
+    /// for more info.
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Returns `true` if the action is active in the current frame.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Returns `true` if the action became active in the current frame.
+    pub fn is_just_pressed(&self) -> bool {
+        self.pressed && !self.was_pressed
+    }
+
+    /// Returns `true` if the action became inactive in the current frame.
+    pub fn is_just_released(&self) -> bool {
+        !self.pressed && self.was_pressed
+    }
+}
+
+/// Input map is a set of named actions, where each action could be bound to any number of
+/// physical inputs. The map must be updated once per frame using [`InputMap::update`], after that
+/// the state of the actions could be fetched. An action is considered pressed if any of its
+/// bindings is pressed, so pressing two keys of the same action at once results in a single press
+/// of the action.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     engine::{input::InputState, input_map::{InputBinding, InputMap}},
+/// #     keyboard::KeyCode,
+/// # };
+/// fn make_input_map() -> InputMap {
+///     InputMap::default()
+///         .with_action(
+///             "Jump",
+///             [InputBinding::Key(KeyCode::Space), InputBinding::Key(KeyCode::KeyW)],
+///         )
+///         .with_action("Fire", [InputBinding::MouseButton(0)])
+/// }
+///
+/// fn update(input_map: &mut InputMap, input_state: &InputState) {
+///     input_map.update(input_state);
+///
+///     if input_map.is_action_just_pressed("Jump") {
+///         // Jump.
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMap {
+    actions: FxHashMap<String, ActionState>,
+    /// Minimal absolute value of a binding at which it is considered pressed. It is used for analog
+    /// inputs, digital inputs are either `0.0` or `1.0`. Default is `0.5`.
+    pub press_threshold: f32,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self {
+            actions: Default::default(),
+            press_threshold: 0.5,
+        }
+    }
+}
+
+impl InputMap {
+    /// Adds a new action with the given bindings. Existing action with the same name is replaced.
+    pub fn with_action(
+        mut self,
+        name: impl Into<String>,
+        bindings: impl IntoIterator<Item = InputBinding>,
+    ) -> Self {
+        self.add_action(name, bindings);
+        self
+    }
+
+    /// Adds a new action with the given bindings. Existing action with the same name is replaced.
+    pub fn add_action(
+        &mut self,
+        name: impl Into<String>,
+        bindings: impl IntoIterator<Item = InputBinding>,
+    ) {
+        self.actions.insert(
+            name.into(),
+            ActionState {
+                bindings: bindings.into_iter().collect(),
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Removes the action with the given name. Returns the removed action, if any.
+    pub fn remove_action(&mut self, name: &str) -> Option<ActionState> {
+        self.actions.remove(name)
+    }
+
+    /// Returns a reference to the action with the given name.
+    pub fn action(&self, name: &str) -> Option<&ActionState> {
+        self.actions.get(name)
+    }
+
+    /// Returns an iterator over every action and its name.
+    pub fn actions(&self) -> impl Iterator<Item = (&str, &ActionState)> {
+        self.actions
+            .iter()
+            .map(|(name, action)| (name.as_str(), action))
+    }
+
+    /// Adds a new binding to the action with the given name. The action is created if it does not
+    /// exist. Duplicate bindings are ignored.
+    pub fn bind(&mut self, name: &str, binding: InputBinding) {
+        let action = self.actions.entry(name.to_string()).or_default();
+        if !action.bindings.contains(&binding) {
+            action.bindings.push(binding);
+        }
+    }
+
+    /// Removes the binding from the action with the given name. Returns `true` if the binding was
+    /// removed.
+    pub fn unbind(&mut self, name: &str, binding: &InputBinding) -> bool {
+        let Some(action) = self.actions.get_mut(name) else {
+            return false;
+        };
+        let count = action.bindings.len();
+        action.bindings.retain(|b| b != binding);
+        action.bindings.len() != count
+    }
+
+    /// Replaces the `old` binding of the action with the `new` one, keeping its position in the
+    /// list of bindings. Returns `false` if the action does not have the `old` binding.
+    pub fn rebind(&mut self, name: &str, old: &InputBinding, new: InputBinding) -> bool {
+        let Some(binding) = self
+            .actions
+            .get_mut(name)
+            .and_then(|action| action.bindings.iter_mut().find(|b| *b == old))
+        else {
+            return false;
+        };
+        *binding = new;
+        true
+    }
+
+    /// Removes every binding of the action with the given name. The action itself is kept.
+    pub fn clear_bindings(&mut self, name: &str) {
+        if let Some(action) = self.actions.get_mut(name) {
+            action.bindings.clear();
+        }
+    }
+
+    /// Updates the state of every action using the given source of input values. Must be called
+    /// once per frame, before fetching the state of the actions.
+    pub fn update(&mut self, source: &impl InputSource) {
+        for action in self.actions.values_mut() {
+            // The value with the largest magnitude wins.
+            let value = action
+                .bindings
+                .iter()
+                .map(|binding| source.binding_value(binding))
+                .fold(0.0f32, |acc, v| if v.abs() > acc.abs() { v } else { acc });
+
+            action.was_pressed = action.pressed;
+            action.value = value;
+            action.pressed = value.abs() >= self.press_threshold;
+        }
+    }
+
+    /// Returns `true` if any binding of the action is pressed in the current frame.
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.action(name).is_some_and(|action| action.is_pressed())
+    }
+
+    /// Returns `true` if the action was pressed in the current frame, but was not pressed in the
+    /// previous one. This method returns `true` only once per press of the action.
+    pub fn is_action_just_pressed(&self, name: &str) -> bool {
+        self.action(name)
+            .is_some_and(|action| action.is_just_pressed())
+    }
+
+    /// Returns `true` if the action was released in the current frame, but was pressed in the
+    /// previous one.
+    pub fn is_action_just_released(&self, name: &str) -> bool {
+        self.action(name)
+            .is_some_and(|action| action.is_just_released())
+    }
+
+    /// Returns the value of the action. It is the value of its binding with the largest magnitude.
+    /// Digital inputs (keys, buttons) produce either `0.0` or `1.0`, analog inputs produce values
+    /// in arbitrary range. Returns zero if there's no such action.
+    pub fn action_value(&self, name: &str) -> f32 {
+        self.action(name).map_or(0.0, |action| action.value())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::engine::{
+        gamepad::{Deadzone, DeadzoneKind, GamepadAxis, GamepadButton, GamepadEvent, GamepadId},
+        input::InputState,
+        input_map::{InputBinding, InputMap, MouseAxis},
+    };
+    use fyrox_core::algebra::Vector2;
+    use winit::{
+        event::ElementState,
+        keyboard::{KeyCode, PhysicalKey},
+    };
+
+    fn set_key(input: &mut InputState, key: KeyCode, state: ElementState) {
+        input.keyboard.keys.insert(PhysicalKey::Code(key), state);
+    }
+
+    #[test]
+    fn test_just_pressed_with_multiple_bindings() {
+        let mut input_map = InputMap::default().with_action(
+            "Jump",
+            [
+                InputBinding::Key(KeyCode::Space),
+                InputBinding::Key(KeyCode::KeyW),
+            ],
+        );
+        let mut input = InputState::default();
+
+        let mut just_pressed_count = 0;
+        let mut frame = |input_map: &mut InputMap, input: &InputState| {
+            input_map.update(input);
+            if input_map.is_action_just_pressed("Jump") {
+                just_pressed_count += 1;
+            }
+        };
+
+        // Press and hold the first key for a few frames.
+        set_key(&mut input, KeyCode::Space, ElementState::Pressed);
+        for _ in 0..3 {
+            frame(&mut input_map, &input);
+        }
+        assert!(input_map.is_action_pressed("Jump"));
+        set_key(&mut input, KeyCode::Space, ElementState::Released);
+        frame(&mut input_map, &input);
+        assert!(input_map.is_action_just_released("Jump"));
+
+        // Press and hold the second key.
+        set_key(&mut input, KeyCode::KeyW, ElementState::Pressed);
+        for _ in 0..3 {
+            frame(&mut input_map, &input);
+        }
+        // Pressing the first key while the second is held is not a new press of the action.
+        set_key(&mut input, KeyCode::Space, ElementState::Pressed);
+        frame(&mut input_map, &input);
+        set_key(&mut input, KeyCode::KeyW, ElementState::Released);
+        set_key(&mut input, KeyCode::Space, ElementState::Released);
+        frame(&mut input_map, &input);
+
+        assert_eq!(just_pressed_count, 2);
+        assert!(!input_map.is_action_pressed("Jump"));
+    }
+
+    #[test]
+    fn test_rebinding_and_analog_values() {
+        let mut input_map =
+            InputMap::default().with_action("Fire", [InputBinding::Key(KeyCode::KeyF)]);
+        let mut input = InputState::default();
+
+        assert!(input_map.rebind(
+            "Fire",
+            &InputBinding::Key(KeyCode::KeyF),
+            InputBinding::MouseButton(0)
+        ));
+        set_key(&mut input, KeyCode::KeyF, ElementState::Pressed);
+        input_map.update(&input);
+        assert!(!input_map.is_action_pressed("Fire"));
+
+        input.mouse.buttons_state.insert(0, ElementState::Pressed);
+        input_map.update(&input);
+        assert!(input_map.is_action_just_pressed("Fire"));
+        assert_eq!(input_map.action_value("Fire"), 1.0);
+
+        input_map.bind("Look", InputBinding::MouseMotion(MouseAxis::X));
+        input.mouse.speed = Vector2::new(-3.0, 1.0);
+        input_map.update(&input);
+        assert_eq!(input_map.action_value("Look"), -3.0);
+        assert!(input_map.is_action_pressed("Look"));
+        assert_eq!(input_map.action_value("Unknown"), 0.0);
+    }
+
+    #[test]
+    fn test_gamepad_stick_deadzone() {
+        let id = GamepadId(0);
+        let mut input = InputState::default();
+        input.gamepads.handle_event(&GamepadEvent::Connected {
+            id,
+            name: "Gamepad".to_string(),
+        });
+        input.gamepads.get_mut(id).unwrap().left_stick_deadzone = Deadzone {
+            kind: DeadzoneKind::Radial,
+            inner: 0.2,
+            outer: 0.8,
+        };
+
+        let mut input_map = InputMap::default()
+            .with_action(
+                "MoveX",
+                [InputBinding::GamepadAxis(GamepadAxis::LeftStickX)],
+            )
+            .with_action("Jump", [InputBinding::GamepadButton(GamepadButton::South)]);
+
+        let set_axis = |input: &mut InputState, value: f32| {
+            input.gamepads.handle_event(&GamepadEvent::Axis {
+                id,
+                axis: GamepadAxis::LeftStickX,
+                value,
+            })
+        };
+
+        // Inside the deadzone.
+        set_axis(&mut input, 0.15);
+        input_map.update(&input);
+        assert_eq!(input_map.action_value("MoveX"), 0.0);
+
+        // Outside the deadzone, remapped to the full range.
+        set_axis(&mut input, -0.5);
+        input_map.update(&input);
+        assert!((input_map.action_value("MoveX") + 0.5).abs() < 1.0e-5);
+        set_axis(&mut input, 0.9);
+        input_map.update(&input);
+        assert_eq!(input_map.action_value("MoveX"), 1.0);
+
+        input.gamepads.handle_event(&GamepadEvent::Button {
+            id,
+            button: GamepadButton::South,
+            pressed: true,
+        });
+        input_map.update(&input);
+        assert!(input_map.is_action_just_pressed("Jump"));
+
+        // Disconnection resets the state.
+        input
+            .gamepads
+            .handle_event(&GamepadEvent::Disconnected { id });
+        input_map.update(&input);
+        assert!(input_map.is_action_just_released("Jump"));
+        assert_eq!(input_map.action_value("MoveX"), 0.0);
+    }
+}
@@ -25,8 +25,12 @@
 
 pub mod error;
 pub mod executor;
+pub mod gamepad;
 pub mod input;
+pub mod input_map;
+pub mod replay;
 pub mod task;
+pub mod touch;
 
 mod hotreload;
 mod wasm_utils;
@@ -50,7 +54,7 @@ use crate::{
         task::TaskPool,
         warn, SafeLock,
     },
-    engine::{error::EngineError, input::InputState, task::TaskPoolHandler},
+    engine::{error::EngineError, gamepad::GamepadEvent, input::InputState, task::TaskPoolHandler},
     event::Event,
     graph::SceneGraph,
     graphics::error::FrameworkError,
@@ -60,6 +64,7 @@ use crate::{
             loader::FontLoader, Font, BOLD_ITALIC, BUILT_IN_BOLD, BUILT_IN_FONT, BUILT_IN_ITALIC,
         },
         loader::UserInterfaceLoader,
+        message::OsEvent,
         style::{self, resource::StyleLoader, Style},
         RenderMode, UiContainer, UiUpdateSwitches, UserInterface,
     },
@@ -105,6 +110,7 @@ use crate::{
         Script, ScriptContext, ScriptDeinitContext, ScriptMessage, ScriptMessageContext,
         ScriptMessageKind, ScriptMessageSender, UniversalScriptContext,
     },
+    utils,
     window::Window,
 };
 use fxhash::{FxHashMap, FxHashSet};
@@ -323,6 +329,9 @@ pub struct Engine {
 
     plugins_enabled: bool,
 
+    // Scenes that were already passed to `Plugin::on_scene_loaded`.
+    plugin_scenes: FxHashSet<Handle<Scene>>,
+
     // Amount of time (in seconds) that passed from creation of the engine.
     elapsed_time: f32,
 
@@ -1211,7 +1220,10 @@ pub(crate) fn process_scripts<T>(
     }
 }
 
-pub(crate) fn initialize_resource_manager_loaders(
+/// Registers standard resource loaders and built-in resources in the given resource manager. The
+/// engine does this automatically in [`Engine::new`], use this function only if you need a standalone
+/// resource manager (for example - in headless tools, that do not create an engine instance).
+pub fn initialize_resource_manager_loaders(
     resource_manager: &ResourceManager,
     serialization_context: Arc<SerializationContext>,
     widget_constructors: Arc<WidgetConstructorContainer>,
@@ -1447,6 +1459,7 @@ impl Engine {
             dyn_type_constructors,
             script_processor: Default::default(),
             plugins_enabled: false,
+            plugin_scenes: Default::default(),
             elapsed_time: 0.0,
             task_pool: TaskPoolHandler::new(task_pool),
             input_state: Default::default(),
@@ -1588,8 +1601,30 @@ impl Engine {
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
     ) {
-        self.pre_update(dt, controller, lag, switches);
-        self.post_update(dt, &Default::default(), lag, controller);
+        self.update_ex(dt, controller, lag, switches, false);
+    }
+
+    /// Performs single update tick without any rendering, which is useful for dedicated servers.
+    /// It runs the same update sequence as [`Self::update`] (plugins, scripts, every enabled scene
+    /// (see [`Scene::tick_headless`]), post update of plugins and clearing of per-frame input),
+    /// but it never touches the renderer, its caches or the user interfaces, even if the graphics
+    /// context is initialized.
+    ///
+    /// See [`Self::update`] docs for the meaning of `lag`.
+    pub fn tick_headless(&mut self, dt: f32, controller: ApplicationLoopController, lag: &mut f32) {
+        self.update_ex(dt, controller, lag, Default::default(), true);
+    }
+
+    fn update_ex(
+        &mut self,
+        dt: f32,
+        controller: ApplicationLoopController,
+        lag: &mut f32,
+        switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+        headless: bool,
+    ) {
+        self.pre_update_ex(dt, controller, lag, switches, headless);
+        self.post_update_ex(dt, &Default::default(), lag, controller, headless);
         self.handle_plugins_hot_reloading(dt, controller, lag, |_| {});
     }
 
@@ -1639,6 +1674,17 @@ impl Engine {
         controller: ApplicationLoopController,
         lag: &mut f32,
         switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+    ) {
+        self.pre_update_ex(dt, controller, lag, switches, false);
+    }
+
+    fn pre_update_ex(
+        &mut self,
+        dt: f32,
+        controller: ApplicationLoopController,
+        lag: &mut f32,
+        switches: FxHashMap<Handle<Scene>, GraphUpdateSwitches>,
+        headless: bool,
     ) {
         // Run some plugin and script methods, potentially causing nodes to be added
         // or removed. This is where most of the rules of the game happen.
@@ -1651,6 +1697,13 @@ impl Engine {
         self.resource_manager.state().update(dt);
         self.handle_model_events();
 
+        if headless {
+            for scene in self.scenes.iter_mut().filter(|s| *s.enabled) {
+                scene.tick_headless(dt);
+            }
+            return;
+        }
+
         let window_size = if let GraphicsContext::Initialized(ctx) = &mut self.graphics_context {
             let inner_size = ctx.window.inner_size();
             let window_size = Vector2::new(inner_size.width as f32, inner_size.height as f32);
@@ -1693,6 +1746,24 @@ impl Engine {
         lag: &mut f32,
         controller: ApplicationLoopController,
     ) {
+        self.post_update_ex(dt, ui_update_switches, lag, controller, false);
+    }
+
+    fn post_update_ex(
+        &mut self,
+        dt: f32,
+        ui_update_switches: &UiUpdateSwitches,
+        lag: &mut f32,
+        controller: ApplicationLoopController,
+        headless: bool,
+    ) {
+        if headless {
+            self.elapsed_time += dt;
+            self.post_update_plugins(dt, controller, lag);
+            self.clear_frame_input();
+            return;
+        }
+
         let screen_size = if let GraphicsContext::Initialized(ref ctx) = self.graphics_context {
             let inner_size = ctx.window.inner_size();
             Some(Vector2::new(
@@ -1713,13 +1784,20 @@ impl Engine {
 
         if let GraphicsContext::Initialized(_) = self.graphics_context {
             self.post_update_plugins(dt, controller, lag);
-
-            self.input_state.mouse.speed = Vector2::default();
-            self.input_state.keyboard.released_keys.clear();
-            self.input_state.keyboard.pressed_keys.clear();
+            self.clear_frame_input();
         }
     }
 
+    /// Clears the input state that is valid only for a single frame (mouse speed, pressed keys, etc.).
+    fn clear_frame_input(&mut self) {
+        self.input_state.mouse.speed = Vector2::default();
+        self.input_state.mouse.wheel = Vector2::default();
+        self.input_state.keyboard.released_keys.clear();
+        self.input_state.keyboard.pressed_keys.clear();
+        self.input_state.gamepads.clear_events();
+        self.input_state.touches.clear_gesture();
+    }
+
     /// Returns true if the scene is registered for script processing.
     pub fn has_scripted_scene(&self, scene: Handle<Scene>) -> bool {
         self.script_processor.has_scripted_scene(scene)
@@ -1879,6 +1957,25 @@ impl Engine {
                 input_state: &self.input_state,
             };
 
+            let loaded_scenes = context
+                .scenes
+                .pair_iter()
+                .map(|(handle, _)| handle)
+                .filter(|handle| !self.plugin_scenes.contains(handle))
+                .collect::<Vec<_>>();
+            self.plugin_scenes
+                .retain(|handle| context.scenes.is_valid_handle(*handle));
+            for scene in loaded_scenes {
+                self.plugin_scenes.insert(scene);
+                for plugin in self.plugins.iter_mut() {
+                    try_enqueue_plugin_error(
+                        "on_scene_loaded",
+                        plugin.on_scene_loaded(scene, &mut context),
+                        &mut self.error_queue,
+                    );
+                }
+            }
+
             for plugin in self.plugins.iter_mut() {
                 try_enqueue_plugin_error(
                     "update",
@@ -2007,6 +2104,13 @@ impl Engine {
         self.performance_statistics.plugins_time += instant::Instant::now() - time;
     }
 
+    /// Should be called on every gamepad event to update the state of gamepads in the input state.
+    /// The engine does not poll gamepads by itself, so this method should be called by a platform
+    /// backend. See [`gamepad`] module docs for more info.
+    pub fn handle_gamepad_event(&mut self, event: &GamepadEvent) {
+        self.input_state.gamepads.handle_event(event);
+    }
+
     /// Should be called on every OS event to update the internal state of the engine accordingly.
     pub fn handle_os_events(
         &mut self,
@@ -2047,6 +2151,26 @@ impl Engine {
                     self.input_state.mouse.position =
                         Vector2::new(position.x as f32, position.y as f32);
                 }
+                WindowEvent::MouseWheel { .. } => {
+                    if let Some(OsEvent::MouseWheel(x, y)) = utils::translate_event(event) {
+                        self.input_state.mouse.wheel += Vector2::new(x, y);
+                    }
+                }
+                WindowEvent::Touch(_) => {
+                    if let Some(OsEvent::Touch {
+                        phase,
+                        location,
+                        id,
+                        ..
+                    }) = utils::translate_event(event)
+                    {
+                        self.input_state.touches.handle_touch(phase, id, location);
+                    }
+                }
+                WindowEvent::Focused(false) => {
+                    // Touch ends are not delivered to unfocused windows.
+                    self.input_state.touches.clear();
+                }
                 _ => (),
             },
             Event::DeviceEvent { event, .. } => match event {
@@ -2369,6 +2493,7 @@ impl Engine {
                 }
             } else {
                 self.handle_scripts(0.0);
+                self.plugin_scenes.clear();
 
                 for mut plugin in self.plugins.drain(..) {
                     let ctx = PluginContext {
@@ -2431,7 +2556,7 @@ impl Engine {
         )
     }
 
-    /// Adds a new static plugin.
+    /// Adds a new static plugin. Plugins are updated in the order of their registration.
     pub fn add_plugin<P>(&mut self, plugin: P)
     where
         P: Plugin + 'static,
@@ -2802,12 +2927,19 @@ mod test {
     use crate::{
         asset::manager::ResourceManager,
         core::{
-            pool::Handle, reflect::prelude::*, task::TaskPool, type_traits::prelude::*,
-            visitor::prelude::*,
+            futures::executor::block_on, math::aabb::AxisAlignedBoundingBox, pool::Handle,
+            reflect::prelude::*, task::TaskPool, type_traits::prelude::*, visitor::prelude::*,
         },
         engine::{task::TaskPoolHandler, GraphicsContext, ScriptProcessor},
-        graph::SceneGraph,
-        scene::{base::BaseBuilder, node::Node, pivot::PivotBuilder, Scene, SceneContainer},
+        graph::{constructor::ConstructorProvider, SceneGraph},
+        plugin::{Plugin, PluginContext, PluginRegistrationContext},
+        scene::{
+            base::{Base, BaseBuilder},
+            graph::Graph,
+            node::{constructor::NodeConstructor, Node, NodeTrait},
+            pivot::PivotBuilder,
+            Scene, SceneContainer, SceneLoader,
+        },
         script::{
             ScriptContext, ScriptDeinitContext, ScriptMessageContext, ScriptMessagePayload,
             ScriptTrait,
@@ -2816,9 +2948,11 @@ mod test {
     use fyrox_resource::io::FsResourceIo;
     use fyrox_ui::UiContainer;
     use std::cell::Cell;
+    use std::ops::{Deref, DerefMut};
     use std::sync::{
+        atomic::{AtomicUsize, Ordering},
         mpsc::{self, Sender, TryRecvError},
-        Arc,
+        Arc, Mutex,
     };
 
     #[derive(PartialEq, Eq, Copy, Clone, Debug)]
@@ -3572,4 +3706,281 @@ mod test {
             }
         }
     }
+
+    #[derive(Clone, Reflect, Visit, Default, Debug, ComponentProvider, TypeUuidProvider)]
+    #[reflect(derived_type = "Node")]
+    #[type_uuid(id = "0e0a9d5b-1f7c-4c1e-9a43-6b8f0d2c7e51")]
+    struct PluginNode {
+        base: Base,
+        value: u32,
+    }
+
+    impl Deref for PluginNode {
+        type Target = Base;
+
+        fn deref(&self) -> &Self::Target {
+            &self.base
+        }
+    }
+
+    impl DerefMut for PluginNode {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            &mut self.base
+        }
+    }
+
+    impl ConstructorProvider<Node, Graph> for PluginNode {
+        fn constructor() -> NodeConstructor {
+            NodeConstructor::new::<Self>()
+        }
+    }
+
+    impl NodeTrait for PluginNode {
+        fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+            self.base.local_bounding_box()
+        }
+
+        fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+            self.base.world_bounding_box()
+        }
+
+        fn id(&self) -> Uuid {
+            Self::type_uuid()
+        }
+    }
+
+    #[derive(Default, Debug, Visit, Reflect)]
+    #[reflect(non_cloneable)]
+    struct CountingPlugin {
+        #[visit(skip)]
+        #[reflect(hidden)]
+        updates: Arc<AtomicUsize>,
+        #[visit(skip)]
+        #[reflect(hidden)]
+        post_updates: Arc<AtomicUsize>,
+        #[visit(skip)]
+        #[reflect(hidden)]
+        loaded_scenes: Arc<Mutex<Vec<Handle<Scene>>>>,
+    }
+
+    impl Plugin for CountingPlugin {
+        fn register(&self, context: PluginRegistrationContext) -> GameResult {
+            context
+                .serialization_context
+                .node_constructors
+                .add::<PluginNode>();
+            Ok(())
+        }
+
+        fn on_scene_loaded(
+            &mut self,
+            scene: Handle<Scene>,
+            _context: &mut PluginContext,
+        ) -> GameResult {
+            self.loaded_scenes.lock().unwrap().push(scene);
+            Ok(())
+        }
+
+        fn update(&mut self, _context: &mut PluginContext) -> GameResult {
+            self.updates.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn post_update(&mut self, _context: &mut PluginContext) -> GameResult {
+            self.post_updates.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_plugin_hooks() {
+        use crate::engine::{Engine, EngineInitParams};
+
+        let task_pool = Arc::new(TaskPool::default());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(Default::default()),
+            widget_constructors: Arc::new(Default::default()),
+            dyn_type_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(Arc::new(FsResourceIo), task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+
+        let updates = Arc::new(AtomicUsize::new(0));
+        let loaded_scenes = Arc::new(Mutex::new(Vec::new()));
+        engine.add_plugin(CountingPlugin {
+            updates: updates.clone(),
+            loaded_scenes: loaded_scenes.clone(),
+            ..Default::default()
+        });
+
+        // The node type registered by the plugin must be deserializable.
+        let mut scene = Scene::new();
+        scene.graph.add_node(Node::new(PluginNode {
+            value: 42,
+            ..Default::default()
+        }));
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let loader = SceneLoader::load(
+            "Scene",
+            engine.serialization_context.clone(),
+            engine.dyn_type_constructors.clone(),
+            engine.resource_manager.clone(),
+            &mut visitor,
+            None,
+        )
+        .unwrap();
+        let loaded = block_on(loader.finish());
+        assert_eq!(
+            loaded
+                .graph
+                .linear_iter()
+                .find_map(|node| node.cast::<PluginNode>())
+                .map(|node| node.value),
+            Some(42)
+        );
+        let scene_handle = engine.scenes.add(loaded);
+
+        let is_running = Cell::new(true);
+        let controller = ApplicationLoopController::Headless {
+            running: &is_running,
+        };
+        engine.enable_plugins(None, true, controller);
+
+        let mut lag = 0.0;
+        for _ in 0..5 {
+            engine.update(1.0 / 60.0, controller, &mut lag, Default::default());
+        }
+
+        assert_eq!(updates.load(Ordering::SeqCst), 5);
+        assert_eq!(*loaded_scenes.lock().unwrap(), vec![scene_handle]);
+    }
+
+    #[derive(Clone, Debug, Default, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "3f1d2c6e-8a4b-4e7f-9c5d-1b2a3e4f5d6c")]
+    struct UpdateCounter {
+        updates: usize,
+    }
+
+    impl ScriptTrait for UpdateCounter {
+        fn on_update(&mut self, _ctx: &mut ScriptContext) -> GameResult {
+            self.updates += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_tick_headless() {
+        use crate::{
+            core::algebra::{Vector2, Vector3},
+            engine::{Engine, EngineInitParams},
+            keyboard::{KeyCode, PhysicalKey},
+            scene::{
+                collider::{ColliderBuilder, ColliderShape},
+                rigidbody::{RigidBody, RigidBodyBuilder},
+                transform::TransformBuilder,
+            },
+        };
+
+        fn make_scene() -> (Scene, Handle<RigidBody>, Handle<Pivot>) {
+            let mut scene = Scene::new();
+            let collider = ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::ball(0.5))
+                .build(&mut scene.graph);
+            let body = RigidBodyBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(0.0, 10.0, 0.0))
+                            .build(),
+                    )
+                    .with_child(collider),
+            )
+            .build(&mut scene.graph);
+            let counter =
+                PivotBuilder::new(BaseBuilder::new().with_script(UpdateCounter::default()))
+                    .build(&mut scene.graph);
+            (scene, body, counter)
+        }
+
+        let task_pool = Arc::new(TaskPool::default());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            serialization_context: Arc::new(Default::default()),
+            widget_constructors: Arc::new(Default::default()),
+            dyn_type_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(Arc::new(FsResourceIo), task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+
+        let (scene, body, counter) = make_scene();
+        let scene_handle = engine.scenes.add(scene);
+        engine.register_scripted_scene(scene_handle);
+
+        let (mut reference, reference_body, _) = make_scene();
+
+        let updates = Arc::new(AtomicUsize::new(0));
+        let post_updates = Arc::new(AtomicUsize::new(0));
+        engine.add_plugin(CountingPlugin {
+            updates: updates.clone(),
+            post_updates: post_updates.clone(),
+            ..Default::default()
+        });
+
+        let is_running = Cell::new(true);
+        let controller = ApplicationLoopController::Headless {
+            running: &is_running,
+        };
+        engine.enable_plugins(None, true, controller);
+
+        let mut lag = 0.0;
+        let dt = 1.0 / 60.0;
+        for _ in 0..60 {
+            // Per-frame input must be cleared at the end of every tick.
+            engine.input_state.mouse.wheel = Vector2::new(0.0, 1.0);
+            engine.input_state.mouse.speed = Vector2::new(2.0, 3.0);
+            engine
+                .input_state
+                .keyboard
+                .pressed_keys
+                .insert(PhysicalKey::Code(KeyCode::Space));
+
+            engine.tick_headless(dt, controller, &mut lag);
+            reference.update(Vector2::new(1920.0, 1080.0), dt, Default::default());
+
+            assert_eq!(engine.input_state.mouse.wheel, Vector2::default());
+            assert_eq!(engine.input_state.mouse.speed, Vector2::default());
+            assert!(engine.input_state.keyboard.pressed_keys.is_empty());
+        }
+
+        // Plugins are updated in the same way as by the full update.
+        assert_eq!(updates.load(Ordering::SeqCst), 60);
+        assert_eq!(post_updates.load(Ordering::SeqCst), 60);
+        assert!((engine.elapsed_time() - 60.0 * dt).abs() < 1.0e-4);
+
+        // Scripts are processed and physics is simulated exactly as by the full update.
+        let graph = &engine.scenes[scene_handle].graph;
+        assert_eq!(
+            graph[counter]
+                .try_get_script::<UpdateCounter>()
+                .map(|script| script.updates),
+            Some(60)
+        );
+        let position = **graph[body].local_transform().position();
+        assert!(position.y < 10.0);
+        assert_eq!(
+            position,
+            **reference.graph[reference_body].local_transform().position()
+        );
+        assert!(matches!(
+            engine.graphics_context,
+            GraphicsContext::Uninitialized(_)
+        ));
+    }
 }
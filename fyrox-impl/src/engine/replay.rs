@@ -0,0 +1,361 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Frame capture and replay. It allows recording the input and the time step of every frame and
+//! then re-running them, which is useful to reproduce bugs. See [`InputRecorder`] and
+//! [`InputPlayer`] docs for more info.
+
+use crate::{
+    core::visitor::{prelude::*, Visitor},
+    engine::input_map::{InputBinding, InputMap, InputSource},
+};
+use std::path::Path;
+
+/// Value of a single physical input in a recorded frame.
+#[derive(Copy, Clone, Debug, PartialEq, Visit)]
+pub struct RecordedInput {
+    /// Physical input.
+    pub binding: InputBinding,
+    /// Value of the input in the frame, see [`InputSource::binding_value`] for more info.
+    pub value: f32,
+}
+
+impl Default for RecordedInput {
+    fn default() -> Self {
+        // Placeholder that is only used while loading.
+        Self {
+            binding: InputBinding::MouseButton(0),
+            value: 0.0,
+        }
+    }
+}
+
+/// Input and time step of a single frame. It implements [`InputSource`], so it could be used
+/// in place of the real input state, for example in [`InputMap::update`].
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct RecordedFrame {
+    /// Time step of the frame, in seconds.
+    pub dt: f32,
+    /// Values of every input that had non-zero value in the frame.
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl InputSource for RecordedFrame {
+    fn binding_value(&self, binding: &InputBinding) -> f32 {
+        self.inputs
+            .iter()
+            .find(|input| input.binding == *binding)
+            .map_or(0.0, |input| input.value)
+    }
+}
+
+/// A sequence of recorded frames, that could be saved to a file and loaded back.
+#[derive(Clone, Debug, Default, PartialEq, Visit)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Returns a slice with every recorded frame.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    /// Returns total duration of the recording, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.dt).sum()
+    }
+
+    /// Saves the recording to the given file.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> VisitResult {
+        let mut visitor = Visitor::new();
+        self.visit("InputRecording", &mut visitor)?;
+        visitor.save_binary_to_file(path)?;
+        Ok(())
+    }
+
+    /// Loads a recording from the given file.
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self, VisitError> {
+        let mut visitor = Visitor::load_from_file(path).await?;
+        let mut recording = Self::default();
+        recording.visit("InputRecording", &mut visitor)?;
+        Ok(recording)
+    }
+}
+
+/// Records the input and the time step of every frame. Only the inputs that are bound to the
+/// actions of an [`InputMap`] are recorded, which keeps recordings small.
+///
+/// ## Determinism
+///
+/// Replaying a recording reproduces the session only if the game logic is deterministic, which
+/// means that it must use a fixed time step and seeded random number generators (see
+/// [`crate::utils::random::SceneRandom`]). Any state that is not driven by the recorded input
+/// (network, wall clock time, etc.) will break the reproduction.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::engine::{
+/// #     input::InputState,
+/// #     input_map::InputMap,
+/// #     replay::InputRecorder,
+/// # };
+/// fn update(
+///     recorder: &mut InputRecorder,
+///     input_map: &mut InputMap,
+///     input_state: &InputState,
+///     dt: f32,
+/// ) {
+///     recorder.record_frame(dt, input_map, input_state);
+///     input_map.update(input_state);
+///
+///     // Game logic.
+/// }
+/// ```
+#[derive(Default, Debug)]
+pub struct InputRecorder {
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    /// Creates a new recorder with an empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a new frame with the given time step. Values of every binding of every action of
+    /// the input map are fetched from the given source.
+    pub fn record_frame(&mut self, dt: f32, input_map: &InputMap, source: &impl InputSource) {
+        let mut frame = RecordedFrame {
+            dt,
+            inputs: Vec::new(),
+        };
+        for (_, action) in input_map.actions() {
+            for binding in action.bindings() {
+                if frame.inputs.iter().any(|input| input.binding == *binding) {
+                    continue;
+                }
+                let value = source.binding_value(binding);
+                if value != 0.0 {
+                    frame.inputs.push(RecordedInput {
+                        binding: *binding,
+                        value,
+                    });
+                }
+            }
+        }
+        self.recording.frames.push(frame);
+    }
+
+    /// Returns a reference to the current recording.
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Stops the recording and returns it.
+    pub fn finish(self) -> InputRecording {
+        self.recording
+    }
+}
+
+/// Plays a recording back frame by frame. Every frame provides its time step, that should be
+/// used to update the engine, and the recorded input, that should be used instead of the real
+/// input state.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::engine::{input_map::InputMap, replay::InputPlayer};
+/// fn replay(player: &mut InputPlayer, input_map: &mut InputMap) {
+///     while let Some(frame) = player.next_frame() {
+///         input_map.update(frame);
+///
+///         // Game logic and the engine update with `frame.dt` time step.
+///     }
+/// }
+/// ```
+#[derive(Default, Debug)]
+pub struct InputPlayer {
+    recording: InputRecording,
+    position: usize,
+}
+
+impl InputPlayer {
+    /// Creates a new player for the given recording.
+    pub fn new(recording: InputRecording) -> Self {
+        Self {
+            recording,
+            position: 0,
+        }
+    }
+
+    /// Returns the next frame of the recording, or [`None`] if the playback is finished.
+    pub fn next_frame(&mut self) -> Option<&RecordedFrame> {
+        let frame = self.recording.frames.get(self.position)?;
+        self.position += 1;
+        Some(frame)
+    }
+
+    /// Returns index of the frame that will be returned by the next call of [`Self::next_frame`].
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns `true` if every frame of the recording was played.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.recording.frames.len()
+    }
+
+    /// Restarts the playback from the first frame.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Returns a reference to the recording.
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            futures::executor::block_on,
+            pool::Handle,
+        },
+        engine::{
+            gamepad::GamepadAxis,
+            input_map::{InputBinding, InputMap, InputSource},
+            replay::{InputPlayer, InputRecorder, InputRecording},
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            Scene,
+        },
+    };
+    use std::{fs, path::Path};
+    use winit::keyboard::KeyCode;
+
+    // Emulates a user that holds the key for a few frames and moves the stick back and forth.
+    struct ScriptedInput(usize);
+
+    impl InputSource for ScriptedInput {
+        fn binding_value(&self, binding: &InputBinding) -> f32 {
+            match binding {
+                InputBinding::Key(KeyCode::KeyW) if self.0 % 5 < 3 => 1.0,
+                InputBinding::GamepadAxis(GamepadAxis::LeftStickX) => (self.0 as f32 * 0.3).sin(),
+                _ => 0.0,
+            }
+        }
+    }
+
+    fn make_input_map() -> InputMap {
+        InputMap::default()
+            .with_action("Forward", [InputBinding::Key(KeyCode::KeyW)])
+            .with_action("Turn", [InputBinding::GamepadAxis(GamepadAxis::LeftStickX)])
+    }
+
+    // A scene with a ball, that rolls on the ground.
+    fn make_scene() -> (Scene, Handle<RigidBody>) {
+        let mut scene = Scene::new();
+        let ground_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::cuboid(50.0, 0.25, 50.0))
+            .build(&mut scene.graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_child(ground_collider))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut scene.graph);
+        let ball_collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut scene.graph);
+        let ball = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .build(),
+                )
+                .with_child(ball_collider),
+        )
+        .with_can_sleep(false)
+        .build(&mut scene.graph);
+        (scene, ball)
+    }
+
+    fn step(scene: &mut Scene, ball: Handle<RigidBody>, input_map: &InputMap, dt: f32) {
+        let body = &mut scene.graph[ball];
+        let forward = input_map.action_value("Forward") * 3.0;
+        let lin_vel = body.lin_vel();
+        body.set_lin_vel(Vector3::new(lin_vel.x, lin_vel.y, forward));
+        body.set_ang_vel(Vector3::new(0.0, input_map.action_value("Turn") * 2.0, 0.0));
+        scene.update(Vector2::new(100.0, 100.0), dt, Default::default());
+    }
+
+    fn replay(recording: InputRecording) -> Matrix4<f32> {
+        let (mut scene, ball) = make_scene();
+        let mut input_map = make_input_map();
+        let mut player = InputPlayer::new(recording);
+        while let Some(frame) = player.next_frame() {
+            input_map.update(frame);
+            step(&mut scene, ball, &input_map, frame.dt);
+        }
+        assert!(player.is_finished());
+        scene.graph[ball].global_transform()
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let (mut scene, ball) = make_scene();
+        let mut input_map = make_input_map();
+        let mut recorder = InputRecorder::new();
+        for i in 0..120 {
+            // Variable time step to make sure that it is recorded too.
+            let dt = 1.0 / 60.0 + (i % 7) as f32 * 0.001;
+            let input = ScriptedInput(i);
+            recorder.record_frame(dt, &input_map, &input);
+            input_map.update(&input);
+            step(&mut scene, ball, &input_map, dt);
+        }
+        // The ball must be moved by the input and must stay on the ground.
+        let expected = scene.graph[ball].global_transform();
+        assert!(expected[14] > 1.0, "{expected:?}");
+        assert!((expected[13] - 0.75).abs() < 0.05, "{expected:?}");
+
+        let recording = recorder.finish();
+        assert_eq!(recording.frames().len(), 120);
+        assert_eq!(replay(recording.clone()), expected);
+
+        // Saved and loaded recording must produce exactly the same result.
+        if !Path::new("test_output").exists() {
+            fs::create_dir_all("test_output").unwrap();
+        }
+        let path = Path::new("test_output/input_recording.bin");
+        let mut saved = recording.clone();
+        saved.save(path).unwrap();
+        let loaded = block_on(InputRecording::load(path)).unwrap();
+        assert_eq!(loaded, recording);
+        assert_eq!(replay(loaded), expected);
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Touch input tracking and recognition of multi-touch gestures (pinch, rotation, pan). See
+//! [`Touches`] docs for more info.
+
+use crate::gui::message::TouchPhase;
+use fyrox_core::algebra::Vector2;
+
+/// A finger that touches the screen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TouchPoint {
+    /// Unique id of the touch, it remains the same until the finger is lifted.
+    pub id: u64,
+    /// Current position of the touch in pixels, relative to the top-left corner of the window.
+    pub position: Vector2<f32>,
+    /// Position at which the touch has started.
+    pub start_position: Vector2<f32>,
+}
+
+/// Gestures that were recognized in the current frame.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TouchGesture {
+    /// Change of the distance (in pixels) between the first two touches. Positive values mean
+    /// that the fingers move apart (zoom in), negative - that the fingers move closer (zoom out).
+    pub pinch: f32,
+    /// Change of the angle (in radians) of the line between the first two touches. Positive values
+    /// mean clockwise rotation on the screen.
+    pub rotation: f32,
+    /// Movement of the center point of the first two touches (or the position of the single
+    /// touch), in pixels.
+    pub pan: Vector2<f32>,
+}
+
+/// The state of every touch on the screen with gestures recognition. Gestures are computed from
+/// the first two touches (in order of their appearance), any extra touches are tracked, but
+/// ignored by gestures.
+///
+/// Touch events may be lost (for example, when a window loses focus), so the tracker is tolerant to
+/// inconsistent sequences of events: a move of an unknown touch starts it, a start of an existing
+/// touch restarts it, an end of an unknown touch is ignored. Gestures are never produced by
+/// the starts and ends of touches, only by their movement, so fingers appearing or disappearing
+/// do not cause sudden jumps.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Touches {
+    points: Vec<TouchPoint>,
+    gesture: TouchGesture,
+}
+
+fn angle(v: Vector2<f32>) -> f32 {
+    v.y.atan2(v.x)
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    let pi = std::f32::consts::PI;
+    (angle + pi).rem_euclid(2.0 * pi) - pi
+}
+
+impl Touches {
+    /// Handles a touch event.
+    pub fn handle_touch(&mut self, phase: TouchPhase, id: u64, position: Vector2<f32>) {
+        let index = self.points.iter().position(|point| point.id == id);
+        match phase {
+            TouchPhase::Started => {
+                if let Some(index) = index {
+                    self.points.remove(index);
+                }
+                self.points.push(TouchPoint {
+                    id,
+                    position,
+                    start_position: position,
+                });
+            }
+            TouchPhase::Moved => match index {
+                Some(index) => self.move_point(index, position),
+                None => self.handle_touch(TouchPhase::Started, id, position),
+            },
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if let Some(index) = index {
+                    self.points.remove(index);
+                }
+            }
+        }
+    }
+
+    fn move_point(&mut self, index: usize, position: Vector2<f32>) {
+        let old_position = self.points[index].position;
+        self.points[index].position = position;
+
+        match self.points.as_slice() {
+            [_] => {
+                self.gesture.pan += position - old_position;
+            }
+            [first, second, ..] if index < 2 => {
+                let mut old = [first.position, second.position];
+                old[index] = old_position;
+                let old_span = old[1] - old[0];
+                let new_span = second.position - first.position;
+
+                self.gesture.pinch += new_span.norm() - old_span.norm();
+                self.gesture.rotation += wrap_angle(angle(new_span) - angle(old_span));
+                self.gesture.pan += (position - old_position).scale(0.5);
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns a slice with every active touch, in order of their appearance.
+    pub fn points(&self) -> &[TouchPoint] {
+        &self.points
+    }
+
+    /// Returns a touch with the given id.
+    pub fn point(&self, id: u64) -> Option<&TouchPoint> {
+        self.points.iter().find(|point| point.id == id)
+    }
+
+    /// Returns gestures that were recognized in the current frame.
+    pub fn gesture(&self) -> &TouchGesture {
+        &self.gesture
+    }
+
+    /// Removes every touch, this method could be used when the window loses focus and no more
+    /// touch events will come.
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.gesture = Default::default();
+    }
+
+    /// Clears per-frame data (gestures).
+    pub fn clear_gesture(&mut self) {
+        self.gesture = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{engine::touch::Touches, gui::message::TouchPhase};
+    use fyrox_core::algebra::Vector2;
+
+    #[test]
+    fn test_pinch() {
+        let mut touches = Touches::default();
+        touches.handle_touch(TouchPhase::Started, 1, Vector2::new(100.0, 100.0));
+        touches.handle_touch(TouchPhase::Started, 2, Vector2::new(200.0, 100.0));
+        assert_eq!(touches.gesture().pinch, 0.0);
+
+        // Move the fingers apart by 50 pixels each.
+        touches.handle_touch(TouchPhase::Moved, 1, Vector2::new(50.0, 100.0));
+        touches.handle_touch(TouchPhase::Moved, 2, Vector2::new(250.0, 100.0));
+        assert!((touches.gesture().pinch - 100.0).abs() < 1.0e-4);
+        assert!(touches.gesture().rotation.abs() < 1.0e-5);
+        assert!(touches.gesture().pan.norm() < 1.0e-5);
+
+        touches.clear_gesture();
+        // Twice the distance change gives twice the zoom.
+        touches.handle_touch(TouchPhase::Moved, 2, Vector2::new(450.0, 100.0));
+        assert!((touches.gesture().pinch - 200.0).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_rotation_and_lost_touches() {
+        let mut touches = Touches::default();
+        // Move without start.
+        touches.handle_touch(TouchPhase::Moved, 1, Vector2::new(0.0, 0.0));
+        touches.handle_touch(TouchPhase::Started, 2, Vector2::new(100.0, 0.0));
+        assert_eq!(touches.points().len(), 2);
+
+        touches.handle_touch(TouchPhase::Moved, 2, Vector2::new(0.0, 100.0));
+        let rotation = touches.gesture().rotation;
+        assert!((rotation - std::f32::consts::FRAC_PI_2).abs() < 1.0e-5);
+
+        // End without prior move, and an end of an unknown touch.
+        touches.handle_touch(TouchPhase::Started, 3, Vector2::new(10.0, 10.0));
+        touches.handle_touch(TouchPhase::Ended, 3, Vector2::new(10.0, 10.0));
+        touches.handle_touch(TouchPhase::Ended, 42, Vector2::new(10.0, 10.0));
+        assert_eq!(touches.points().len(), 2);
+
+        // Removal of a finger does not produce gestures.
+        touches.clear_gesture();
+        touches.handle_touch(TouchPhase::Ended, 1, Vector2::new(0.0, 0.0));
+        assert_eq!(touches.gesture(), &Default::default());
+        assert_eq!(touches.points()[0].id, 2);
+    }
+}
@@ -29,7 +29,7 @@ use crate::{
     plugin::Plugin,
 };
 use std::{
-    ffi::OsStr,
+    ffi::{c_char, CStr, OsStr},
     fs::File,
     io::Read,
     path::{Path, PathBuf},
@@ -56,8 +56,62 @@ pub struct DyLibHandle {
 #[cfg(any(unix, windows))]
 type PluginEntryPoint = fn() -> Box<dyn Plugin>;
 
+#[cfg(any(unix, windows))]
+type AbiVersionEntryPoint = unsafe extern "C" fn() -> *const c_char;
+
+/// Version of the interface between the engine and dynamic plugins. Layouts of the engine types
+/// (including `dyn Plugin` itself) may change between engine and compiler versions, so a plugin
+/// must be built against the same version of the engine, that loads it, using the same compiler.
+/// The version consists of the engine version and the version of `rustc`. Dynamic plugins should
+/// export it using `fyrox_plugin_abi_version` function with C calling convention, so it could be
+/// safely called even if the rest of the ABI does not match:
+///
+/// ```rust
+/// # use fyrox_impl::plugin::dylib::PLUGIN_ABI_VERSION;
+/// #[no_mangle]
+/// pub extern "C" fn fyrox_plugin_abi_version() -> *const std::ffi::c_char {
+///     PLUGIN_ABI_VERSION.as_ptr()
+/// }
+/// ```
+pub const PLUGIN_ABI_VERSION: &CStr = match CStr::from_bytes_with_nul(
+    concat!(
+        env!("CARGO_PKG_VERSION"),
+        " (",
+        env!("FYROX_RUSTC_VERSION"),
+        ")\0"
+    )
+    .as_bytes(),
+) {
+    Ok(version) => version,
+    Err(_) => panic!("ABI version must be a valid C string!"),
+};
+
+/// Checks whether a plugin with the given ABI version could be loaded by the engine. Plugins that
+/// do not export their ABI version (`None`) are loaded with a warning.
+#[cfg_attr(not(any(unix, windows)), allow(dead_code))]
+pub(crate) fn check_abi_version(plugin_abi_version: Option<&str>) -> Result<(), String> {
+    let engine_abi_version = PLUGIN_ABI_VERSION.to_string_lossy();
+    match plugin_abi_version {
+        Some(version) if version == engine_abi_version => Ok(()),
+        Some(version) => Err(format!(
+            "ABI mismatch: the plugin was built against Fyrox {version}, but the engine version \
+            is {engine_abi_version}. Rebuild the plugin with the same version of the engine and \
+            the same compiler."
+        )),
+        None => {
+            Log::warn(
+                "The plugin does not export its ABI version (`fyrox_plugin_abi_version`), \
+                its compatibility with the engine cannot be checked.",
+            );
+            Ok(())
+        }
+    }
+}
+
 impl DyLibHandle {
-    /// Tries to load a plugin from a dynamic library (*.dll on Windows, *.so on Unix).
+    /// Tries to load a plugin from a dynamic library (*.dll on Windows, *.so on Unix). The load is
+    /// rejected if the plugin was built against a different version of the engine (see
+    /// [`PLUGIN_ABI_VERSION`]).
     pub fn load<P>(#[allow(unused_variables)] path: P) -> Result<Self, String>
     where
         P: AsRef<OsStr>,
@@ -66,6 +120,12 @@ impl DyLibHandle {
         unsafe {
             let lib = libloading::Library::new(path).map_err(|e| e.to_string())?;
 
+            let plugin_abi_version = lib
+                .get::<AbiVersionEntryPoint>("fyrox_plugin_abi_version".as_bytes())
+                .ok()
+                .map(|abi_version| CStr::from_ptr(abi_version()).to_string_lossy().into_owned());
+            check_abi_version(plugin_abi_version.as_deref())?;
+
             let entry = lib
                 .get::<PluginEntryPoint>("fyrox_plugin".as_bytes())
                 .map_err(|e| e.to_string())?;
@@ -330,3 +390,182 @@ fn try_copy_library(source_lib_path: &Path, lib_path: &Path) -> Result<(), Strin
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::manager::ResourceManager,
+        core::{
+            pool::Handle, reflect::prelude::*, task::TaskPool, type_traits::prelude::*,
+            visitor::prelude::*,
+        },
+        engine::{ApplicationLoopController, Engine, EngineInitParams, SerializationContext},
+        graph::SceneGraph,
+        plugin::{
+            dylib::{check_abi_version, PLUGIN_ABI_VERSION},
+            error::GameResult,
+            DynamicPlugin, Plugin, PluginContext, PluginRegistrationContext,
+        },
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+        script::{constructor::ScriptConstructorContainer, ScriptTrait},
+    };
+    use fyrox_resource::io::FsResourceIo;
+    use std::{cell::Cell, sync::Arc};
+
+    #[derive(Clone, Debug, Default, Reflect, Visit, TypeUuidProvider, ComponentProvider)]
+    #[type_uuid(id = "7b0e5a4c-2f3d-4c1e-9a8b-6d5f4e3c2b1a")]
+    struct Accumulator {
+        value: u32,
+    }
+
+    impl ScriptTrait for Accumulator {}
+
+    #[derive(Default, Debug, Visit, Reflect)]
+    #[reflect(non_cloneable)]
+    struct SteppingPlugin {
+        // Emulates the code of the plugin, it is not serialized and comes from the new version.
+        #[visit(skip)]
+        #[reflect(hidden)]
+        step: u32,
+        scene: Handle<Scene>,
+    }
+
+    impl Plugin for SteppingPlugin {
+        fn register(&self, context: PluginRegistrationContext) -> GameResult {
+            context
+                .serialization_context
+                .script_constructors
+                .add::<Accumulator>("Accumulator");
+            Ok(())
+        }
+
+        fn update(&mut self, context: &mut PluginContext) -> GameResult {
+            for node in context.scenes[self.scene].graph.linear_iter_mut() {
+                if let Some(accumulator) = node.try_get_script_mut::<Accumulator>() {
+                    accumulator.value += self.step;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Emulates a dynamic library, that is replaced by a new version on reload.
+    struct SwappingDynamicPlugin {
+        plugin: Option<Box<dyn Plugin>>,
+        next: Option<Box<dyn Plugin>>,
+    }
+
+    impl DynamicPlugin for SwappingDynamicPlugin {
+        fn display_name(&self) -> String {
+            "SwappingDynamicPlugin".to_string()
+        }
+
+        fn is_reload_needed_now(&self) -> bool {
+            self.next.is_some()
+        }
+
+        fn as_loaded_ref(&self) -> &dyn Plugin {
+            &**self.plugin.as_ref().unwrap()
+        }
+
+        fn as_loaded_mut(&mut self) -> &mut dyn Plugin {
+            &mut **self.plugin.as_mut().unwrap()
+        }
+
+        fn is_loaded(&self) -> bool {
+            self.plugin.is_some()
+        }
+
+        fn reload(
+            &mut self,
+            fill_and_register: &mut dyn FnMut(&mut dyn Plugin) -> Result<(), String>,
+        ) -> Result<(), String> {
+            self.plugin = None;
+            let mut plugin = self
+                .next
+                .take()
+                .ok_or_else(|| "there is no new version of the plugin".to_string())?;
+            fill_and_register(&mut *plugin)?;
+            self.plugin = Some(plugin);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plugin_reload() {
+        let task_pool = Arc::new(TaskPool::default());
+        let mut engine = Engine::new(EngineInitParams {
+            graphics_context_params: Default::default(),
+            // Built-in nodes share the assembly with the test plugin, keep them out of the
+            // context, so the reloading won't treat them as the plugin's content.
+            serialization_context: Arc::new(SerializationContext {
+                node_constructors: Default::default(),
+                script_constructors: ScriptConstructorContainer::new(),
+            }),
+            widget_constructors: Arc::new(Default::default()),
+            dyn_type_constructors: Arc::new(Default::default()),
+            resource_manager: ResourceManager::new(Arc::new(FsResourceIo), task_pool.clone()),
+            task_pool,
+        })
+        .unwrap();
+
+        let mut scene = Scene::new();
+        let pivot = PivotBuilder::new(BaseBuilder::new().with_script(Accumulator::default()))
+            .build(&mut scene.graph);
+        let scene_handle = engine.scenes.add(scene);
+
+        engine.add_dynamic_plugin_custom(SwappingDynamicPlugin {
+            plugin: Some(Box::new(SteppingPlugin {
+                step: 1,
+                scene: scene_handle,
+            })),
+            next: Some(Box::new(SteppingPlugin {
+                step: 10,
+                scene: Handle::NONE,
+            })),
+        });
+
+        let is_running = Cell::new(true);
+        let controller = ApplicationLoopController::Headless {
+            running: &is_running,
+        };
+        engine.enable_plugins(None, true, controller);
+
+        let value = |engine: &Engine| {
+            engine.scenes[scene_handle].graph[pivot]
+                .try_get_script::<Accumulator>()
+                .map(|accumulator| accumulator.value)
+        };
+
+        let dt = 1.0 / 60.0;
+        let mut lag = 0.0;
+        for _ in 0..3 {
+            engine.update(dt, controller, &mut lag, Default::default());
+        }
+        assert_eq!(value(&engine), Some(3));
+
+        engine.reload_plugin(0, dt, controller, &mut lag).unwrap();
+
+        // The script was re-created from its serialized state, and the plugin restored its
+        // state as well.
+        assert_eq!(value(&engine), Some(3));
+
+        // The new version of the plugin changes the behavior.
+        for _ in 0..2 {
+            engine.update(dt, controller, &mut lag, Default::default());
+        }
+        assert_eq!(value(&engine), Some(23));
+    }
+
+    #[test]
+    fn test_abi_version_check() {
+        assert!(PLUGIN_ABI_VERSION
+            .to_string_lossy()
+            .contains(env!("FYROX_RUSTC_VERSION")));
+        assert!(check_abi_version(Some(&PLUGIN_ABI_VERSION.to_string_lossy())).is_ok());
+        assert!(check_abi_version(None).is_ok());
+        let error = check_abi_version(Some("0.1.0")).unwrap_err();
+        assert!(error.contains("ABI mismatch"));
+        assert!(error.contains("0.1.0"));
+    }
+}
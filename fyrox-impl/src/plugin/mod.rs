@@ -618,6 +618,18 @@ pub trait Plugin: PluginAsAny + Visit + Reflect {
         Ok(())
     }
 
+    /// The method is called once for every scene that was added to the engine's scene container,
+    /// no matter if it was loaded from a file or created manually. It is called at the beginning
+    /// of the next update, right before [`Self::update`]. The main use of this method is to
+    /// prepare scenes for the plugin's systems (for example, to find nodes by their names).
+    fn on_scene_loaded(
+        &mut self,
+        #[allow(unused_variables)] scene: Handle<Scene>,
+        #[allow(unused_variables)] context: &mut PluginContext,
+    ) -> GameResult {
+        Ok(())
+    }
+
     /// called after all Plugin and Script updates
     fn post_update(
         &mut self,
@@ -45,7 +45,7 @@ use crate::{
         gpu_texture::GpuTexture,
         server::GraphicsServer,
         uniform::{ByteStorage, StaticUniformBuffer, UniformBuffer},
-        ElementRange,
+        BlendFactor, BlendFunc, BlendParameters, ColorMask, DrawParameters, ElementRange,
     },
     material::{self, shader::ShaderDefinition, Material, MaterialPropertyRef, MaterialResource},
     renderer::{
@@ -62,20 +62,23 @@ use crate::{
     resource::texture::TextureResource,
     scene::{
         collider::BitMask,
+        fog::{FogSettings, FogShaderParameters},
         graph::Graph,
         light::{
+            area::{AreaLight, AreaLightShape},
             directional::{CsmOptions, DirectionalLight},
             point::PointLight,
             spot::SpotLight,
-            BaseLight,
+            BaseLight, ShadowMapSettings,
         },
         mesh::{
             buffer::{TriangleBufferRefMut, VertexAttributeDescriptor, VertexBufferRefMut},
+            skinning::SkinningMode,
             surface::SurfaceResource,
             RenderPath,
         },
         node::{Node, NodeTrait, RdcControlFlow},
-        probe::ReflectionProbe,
+        probe::{BoxProjection, ReflectionProbe},
     },
 };
 use fxhash::{FxBuildHasher, FxHashMap, FxHasher};
@@ -125,6 +128,27 @@ impl RenderContext<'_> {
 
         RANGE_CENTER.saturating_add_signed((view_space_point.z * GRANULARITY) as i64)
     }
+
+    /// Calculates sorting index of a transparent node using the distance from the given point to
+    /// the observer. Farther nodes will have lower index, which means that they will be rendered
+    /// first (back-to-front sorting). Nodes with the same distance to the observer are sorted
+    /// by the given render order (see [`crate::scene::base::Base::set_render_order`]).
+    pub fn calculate_transparency_sorting_index(
+        &self,
+        global_position: Vector3<f32>,
+        render_order: i32,
+    ) -> u64 {
+        let distance = self
+            .observer_position
+            .translation
+            .metric_distance(&global_position);
+        // Bit representation of non-negative floats preserves their order, so the distance could be
+        // compared without any quantization. Invert it, so farther nodes come first.
+        let distance_key = u32::MAX - distance.to_bits();
+        // Flip the sign bit to map [i32::MIN; i32::MAX] to [0; u32::MAX] keeping the order.
+        let render_order_key = (render_order as u32) ^ (1 << 31);
+        ((distance_key as u64) << 32) | render_order_key as u64
+    }
 }
 
 #[allow(missing_docs)] // TODO
@@ -143,7 +167,81 @@ pub struct BundleRenderContext<'a> {
     // TODO: Add depth pre-pass to remove Option here. Current architecture allows only forward
     // renderer to have access to depth buffer that is available from G-Buffer.
     pub scene_depth: Option<&'a GpuTexture>,
+    /// Fog settings of the scene. [`None`] means that the fog must not be applied (for example, in
+    /// shadow passes).
+    pub fog: Option<&'a FogSettings>,
+    /// Defines whether the height fog of the scene should be applied or not (see
+    /// [`crate::renderer::QualitySettings::height_fog_enabled`]).
+    pub use_height_fog: bool,
     pub renderer_resources: &'a RendererResources,
+    pub transparency_stage: TransparencyStage,
+    /// Shadow of a directional light, that should be applied in forward passes. [`None`] means that
+    /// the surfaces are not shadowed.
+    pub shadows: Option<ForwardShadows<'a>>,
+}
+
+/// Cascaded shadow maps of a directional light, that are available to forward passes via
+/// `fyrox_shadowData` and `fyrox_shadowCascadeN` built-in resources.
+#[derive(Copy, Clone)]
+pub struct ForwardShadows<'a> {
+    /// Index of the light in [`RenderDataBundleStorage::light_sources`].
+    pub light_index: usize,
+    /// Shadow map of each cascade.
+    pub cascades: [&'a GpuTexture; ShaderDefinition::MAX_SHADOW_CASCADES],
+    /// World-to-light-clip-space transformation of each cascade.
+    pub view_proj_matrices: [Matrix4<f32>; ShaderDefinition::MAX_SHADOW_CASCADES],
+    /// View-space distance at which each cascade ends.
+    pub cascade_distances: [f32; ShaderDefinition::MAX_SHADOW_CASCADES],
+    pub shadow_bias: f32,
+    pub soft_shadows: bool,
+}
+
+/// Defines which surfaces of a render pass will be drawn and how they will be blended. It is used
+/// to split rendering into opaque and transparent parts when order-independent transparency is
+/// used. A surface is considered transparent if its render pass has blending enabled.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TransparencyStage {
+    /// Every surface is drawn with the draw parameters of its render pass.
+    #[default]
+    All,
+    /// Only opaque surfaces are drawn, transparent ones are skipped.
+    Opaque,
+    /// Only transparent surfaces are drawn, their premultiplied color and alpha are summed up in
+    /// the frame buffer (weighted blended order-independent transparency).
+    Accumulation,
+    /// Only transparent surfaces are drawn, the frame buffer is multiplied by `1 - alpha` of each
+    /// surface (weighted blended order-independent transparency).
+    Revealage,
+}
+
+impl TransparencyStage {
+    /// Returns draw parameters that should be used to draw a surface with the given draw parameters
+    /// of its render pass at this stage. [`None`] means that the surface must not be drawn at this
+    /// stage.
+    pub fn draw_parameters(self, params: &DrawParameters) -> Option<DrawParameters> {
+        let transparent = params.blend.is_some();
+        let func = match self {
+            Self::All => return Some(params.clone()),
+            Self::Opaque => return (!transparent).then(|| params.clone()),
+            _ if !transparent => return None,
+            Self::Accumulation => BlendFunc::new_separate(
+                BlendFactor::SrcAlpha,
+                BlendFactor::One,
+                BlendFactor::One,
+                BlendFactor::One,
+            ),
+            Self::Revealage => BlendFunc::new(BlendFactor::Zero, BlendFactor::OneMinusSrcAlpha),
+        };
+        Some(DrawParameters {
+            color_write: ColorMask::default(),
+            depth_write: false,
+            blend: Some(BlendParameters {
+                func,
+                ..Default::default()
+            }),
+            ..params.clone()
+        })
+    }
 }
 
 /// A set of data of a surface for rendering.
@@ -154,6 +252,10 @@ pub struct SurfaceInstanceData {
     pub bone_matrices: Vec<Matrix4<f32>>,
     /// A set of weights for each blend shape in the surface.
     pub blend_shapes_weights: Vec<f32>,
+    /// Defines how bone matrices should be interpreted. In case of
+    /// [`SkinningMode::DualQuaternion`] each bone matrix is a packed dual quaternion (see
+    /// [`crate::scene::mesh::skinning::DualQuaternion::to_packed_matrix`]).
+    pub skinning_mode: SkinningMode,
     /// A range of elements of the instance. Allows you to draw either the full range ([`ElementRange::Full`])
     /// of the graphics primitives from the surface data or just a part of it ([`ElementRange::Specific`]).
     pub element_range: ElementRange,
@@ -167,6 +269,7 @@ impl Default for SurfaceInstanceData {
             world_transform: Matrix4::identity(),
             bone_matrices: Default::default(),
             blend_shapes_weights: Default::default(),
+            skinning_mode: Default::default(),
             element_range: Default::default(),
             node_handle: Default::default(),
         }
@@ -233,6 +336,10 @@ pub struct GlobalUniformData {
     pub lights_block: UniformBlockLocation,
     /// Graphics settings block location.
     pub graphics_settings_block: UniformBlockLocation,
+    /// Fog settings block location.
+    pub fog_block: UniformBlockLocation,
+    /// Directional light shadow block location.
+    pub shadow_block: UniformBlockLocation,
 }
 
 pub fn write_with_material<T, C, G>(
@@ -469,6 +576,7 @@ impl RenderDataBundle {
                 .with(&(view_projection_matrix * instance.world_transform))
                 .with(&(instance.blend_shapes_weights.len() as i32))
                 .with(&(!instance.bone_matrices.is_empty()))
+                .with(&(instance.skinning_mode == SkinningMode::DualQuaternion))
                 .with_slice_with_max_size(
                     &packed_blend_shape_weights,
                     ShaderDefinition::MAX_BLEND_SHAPE_WEIGHT_GROUPS,
@@ -487,7 +595,12 @@ impl RenderDataBundle {
                 );
                 let mut matrices = [INIT; ShaderDefinition::MAX_BONE_MATRICES];
                 const SIZE: usize = ShaderDefinition::MAX_BONE_MATRICES * size_of::<Matrix4<f32>>();
-                matrices[0..instance.bone_matrices.len()].copy_from_slice(&instance.bone_matrices);
+                // Bones above the limit are ignored, such surfaces should be skinned on CPU.
+                let count = instance
+                    .bone_matrices
+                    .len()
+                    .min(ShaderDefinition::MAX_BONE_MATRICES);
+                matrices[0..count].copy_from_slice(&instance.bone_matrices[0..count]);
 
                 let bone_matrices_block = render_context
                     .uniform_memory_allocator
@@ -577,6 +690,13 @@ impl RenderDataBundle {
             return Ok(stats);
         };
 
+        let Some(draw_params) = render_context
+            .transparency_stage
+            .draw_parameters(&render_pass.draw_params)
+        else {
+            return Ok(stats);
+        };
+
         let mut material_bindings = ArrayVec::<ResourceBinding, 32>::new();
         let shader_state = material.shader().state();
         let shader = shader_state
@@ -621,6 +741,14 @@ impl RenderDataBundle {
                         ),
                     );
                 }
+                "fyrox_fogData" => {
+                    material_bindings.push(
+                        render_context.uniform_memory_allocator.block_to_binding(
+                            global_uniform_data.fog_block,
+                            resource_definition.binding,
+                        ),
+                    );
+                }
                 "fyrox_lightsBlock" => {
                     material_bindings.push(
                         render_context.uniform_memory_allocator.block_to_binding(
@@ -629,6 +757,29 @@ impl RenderDataBundle {
                         ),
                     );
                 }
+                "fyrox_shadowData" => {
+                    material_bindings.push(
+                        render_context.uniform_memory_allocator.block_to_binding(
+                            global_uniform_data.shadow_block,
+                            resource_definition.binding,
+                        ),
+                    );
+                }
+                "fyrox_shadowCascade0" | "fyrox_shadowCascade1" | "fyrox_shadowCascade2" => {
+                    let cascade = match name {
+                        "fyrox_shadowCascade0" => 0,
+                        "fyrox_shadowCascade1" => 1,
+                        _ => 2,
+                    };
+                    material_bindings.push(ResourceBinding::texture(
+                        match render_context.shadows.as_ref() {
+                            Some(shadows) => shadows.cascades[cascade],
+                            None => &render_context.renderer_resources.white_dummy,
+                        },
+                        &render_context.renderer_resources.nearest_clamp_sampler,
+                        resource_definition.binding,
+                    ));
+                }
                 _ => match resource_definition.kind {
                     ShaderResourceKind::Texture { fallback, .. } => {
                         material_bindings.push(make_texture_binding(
@@ -711,7 +862,7 @@ impl RenderDataBundle {
                 geometry,
                 render_context.viewport,
                 &render_pass.program,
-                &render_pass.draw_params,
+                &draw_params,
                 &[
                     ResourceBindGroup {
                         bindings: &material_bindings,
@@ -790,6 +941,10 @@ pub enum LightSourceKind {
     Directional {
         csm_options: CsmOptions,
     },
+    Area {
+        shape: AreaLightShape,
+        distance: f32,
+    },
     Unknown,
 }
 
@@ -800,6 +955,9 @@ pub struct LightData<const N: usize = 16> {
     pub position: [Vector3<f32>; N],
     pub direction: [Vector3<f32>; N],
     pub parameters: [Vector2<f32>; N],
+    pub intensity: [f32; N],
+    pub area_side: [Vector3<f32>; N],
+    pub area_look: [Vector3<f32>; N],
 }
 
 impl<const N: usize> Default for LightData<N> {
@@ -810,10 +968,69 @@ impl<const N: usize> Default for LightData<N> {
             position: [Default::default(); N],
             direction: [Default::default(); N],
             parameters: [Default::default(); N],
+            intensity: [Default::default(); N],
+            area_side: [Default::default(); N],
+            area_look: [Default::default(); N],
         }
     }
 }
 
+impl<const N: usize> LightData<N> {
+    /// Packs the first `N` light sources in the layout of `fyrox_lightsBlock`.
+    pub fn new(light_sources: &[LightSource]) -> Self {
+        let mut light_data = Self::default();
+
+        for (i, light) in light_sources.iter().enumerate().take(N) {
+            let color = light.color.as_frgb();
+
+            light_data.color_radius[i] = Vector4::new(color.x, color.y, color.z, 0.0);
+            light_data.intensity[i] = light.intensity;
+            light_data.position[i] = light.position;
+            light_data.direction[i] = light.up_vector;
+
+            match light.kind {
+                LightSourceKind::Spot {
+                    full_cone_angle,
+                    hotspot_cone_angle,
+                    distance,
+                    ..
+                } => {
+                    light_data.color_radius[i].w = distance;
+                    light_data.parameters[i].x = (hotspot_cone_angle * 0.5).cos();
+                    light_data.parameters[i].y = (full_cone_angle * 0.5).cos();
+                }
+                LightSourceKind::Point { radius, .. } => {
+                    light_data.color_radius[i].w = radius;
+                    light_data.parameters[i].x = std::f32::consts::PI.cos();
+                    light_data.parameters[i].y = std::f32::consts::PI.cos();
+                }
+                LightSourceKind::Area { shape, distance } => {
+                    let (side, look) = shape.scaled_axes(light.side_vector, light.look_vector);
+                    light_data.color_radius[i].w = distance;
+                    light_data.parameters[i].x = if matches!(shape, AreaLightShape::Disc { .. }) {
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    light_data.parameters[i].y = std::f32::consts::PI.cos();
+                    light_data.area_side[i] = side;
+                    light_data.area_look[i] = look;
+                }
+                LightSourceKind::Directional { .. } => {
+                    light_data.color_radius[i].w = f32::INFINITY;
+                    light_data.parameters[i].x = std::f32::consts::PI.cos();
+                    light_data.parameters[i].y = std::f32::consts::PI.cos();
+                }
+                LightSourceKind::Unknown => {}
+            }
+
+            light_data.count += 1;
+        }
+
+        light_data
+    }
+}
+
 pub struct LightSource {
     pub handle: Handle<Node>,
     pub global_transform: Matrix4<f32>,
@@ -826,6 +1043,8 @@ pub struct LightSource {
     pub local_scale: Vector3<f32>,
     pub color: Color,
     pub intensity: f32,
+    pub light_size: f32,
+    pub shadow_map_settings: ShadowMapSettings,
     pub scatter_enabled: bool,
     pub scatter: Vector3<f32>,
 }
@@ -840,6 +1059,8 @@ pub struct RenderDataBundleStorage {
     pub bundles: Vec<RenderDataBundle>,
     pub light_sources: Vec<LightSource>,
     pub environment_map: Option<TextureResource>,
+    /// Parallax-correction parameters of the reflection probe, that provides the environment map.
+    pub environment_map_projection: Option<BoxProjection>,
 }
 
 pub struct RenderDataBundleStorageOptions {
@@ -862,6 +1083,7 @@ impl RenderDataBundleStorage {
             bundles: Default::default(),
             light_sources: Default::default(),
             environment_map: None,
+            environment_map_projection: None,
         }
     }
 
@@ -885,6 +1107,7 @@ impl RenderDataBundleStorage {
             bundles: Vec::with_capacity(capacity),
             light_sources: Default::default(),
             environment_map: None,
+            environment_map_projection: None,
         };
 
         let frustum = Frustum::from_view_projection_matrix(
@@ -918,6 +1141,7 @@ impl RenderDataBundleStorage {
                     .is_contains_point(observer_position.translation)
                 {
                     storage.environment_map = Some(reflection_probe.render_target().clone());
+                    storage.environment_map_projection = reflection_probe.box_projection();
                 }
             }
 
@@ -944,6 +1168,11 @@ impl RenderDataBundleStorage {
                             LightSourceKind::Directional {
                                 csm_options: (*directional_light.csm_options).clone(),
                             }
+                        } else if let Some(area_light) = node.cast::<AreaLight>() {
+                            LightSourceKind::Area {
+                                shape: area_light.shape(),
+                                distance: area_light.distance(),
+                            }
                         } else {
                             LightSourceKind::Unknown
                         };
@@ -960,6 +1189,8 @@ impl RenderDataBundleStorage {
                             local_scale: **base_light.local_transform().scale(),
                             color: base_light.color(),
                             intensity: base_light.intensity(),
+                            light_size: base_light.light_size(),
+                            shadow_map_settings: base_light.shadow_map_settings(),
                             scatter_enabled: base_light.is_scatter_enabled(),
                             scatter: base_light.scatter(),
                         };
@@ -1014,53 +1245,17 @@ impl RenderDataBundleStorage {
         &self,
         render_context: &mut BundleRenderContext,
     ) -> GlobalUniformData {
-        let mut light_data = LightData::<{ ShaderDefinition::MAX_LIGHTS }>::default();
-
-        for (i, light) in self
-            .light_sources
-            .iter()
-            .enumerate()
-            .take(ShaderDefinition::MAX_LIGHTS)
-        {
-            let color = light.color.as_frgb();
-
-            light_data.color_radius[i] = Vector4::new(color.x, color.y, color.z, 0.0);
-            light_data.position[i] = light.position;
-            light_data.direction[i] = light.up_vector;
-
-            match light.kind {
-                LightSourceKind::Spot {
-                    full_cone_angle,
-                    hotspot_cone_angle,
-                    distance,
-                    ..
-                } => {
-                    light_data.color_radius[i].w = distance;
-                    light_data.parameters[i].x = (hotspot_cone_angle * 0.5).cos();
-                    light_data.parameters[i].y = (full_cone_angle * 0.5).cos();
-                }
-                LightSourceKind::Point { radius, .. } => {
-                    light_data.color_radius[i].w = radius;
-                    light_data.parameters[i].x = std::f32::consts::PI.cos();
-                    light_data.parameters[i].y = std::f32::consts::PI.cos();
-                }
-                LightSourceKind::Directional { .. } => {
-                    light_data.color_radius[i].w = f32::INFINITY;
-                    light_data.parameters[i].x = std::f32::consts::PI.cos();
-                    light_data.parameters[i].y = std::f32::consts::PI.cos();
-                }
-                LightSourceKind::Unknown => {}
-            }
-
-            light_data.count += 1;
-        }
+        let light_data = LightData::<{ ShaderDefinition::MAX_LIGHTS }>::new(&self.light_sources);
 
         let lights_data = StaticUniformBuffer::<2048>::new()
             .with(&(light_data.count as i32))
             .with(&light_data.color_radius)
             .with(&light_data.parameters)
             .with(&light_data.position)
-            .with(&light_data.direction);
+            .with(&light_data.direction)
+            .with(&light_data.intensity)
+            .with(&light_data.area_side)
+            .with(&light_data.area_look);
         let lights_block = render_context
             .uniform_memory_allocator
             .allocate(lights_data);
@@ -1082,7 +1277,8 @@ impl RenderDataBundleStorage {
             .with(&camera_side)
             .with(&self.observer_position.z_near)
             .with(&self.observer_position.z_far)
-            .with(&(self.observer_position.z_far - self.observer_position.z_near));
+            .with(&(self.observer_position.z_far - self.observer_position.z_near))
+            .with(&self.observer_position.view_matrix);
         let camera_block = render_context
             .uniform_memory_allocator
             .allocate(camera_uniforms);
@@ -1092,10 +1288,37 @@ impl RenderDataBundleStorage {
             .uniform_memory_allocator
             .allocate(graphics_settings);
 
+        let fog = render_context
+            .fog
+            .map(|fog| fog.shader_parameters(render_context.use_height_fog))
+            .unwrap_or_default();
+        let fog_data = fog_uniform_data(&fog);
+        let fog_block = render_context.uniform_memory_allocator.allocate(fog_data);
+
+        let shadow_data = match render_context.shadows.as_ref() {
+            Some(shadows) => StaticUniformBuffer::<512>::new()
+                .with(&(shadows.light_index as i32))
+                .with(&shadows.view_proj_matrices)
+                .with(&shadows.cascade_distances)
+                .with(&shadows.shadow_bias)
+                .with(&shadows.soft_shadows),
+            None => StaticUniformBuffer::<512>::new()
+                .with(&-1i32)
+                .with(&[Matrix4::<f32>::identity(); ShaderDefinition::MAX_SHADOW_CASCADES])
+                .with(&[0.0f32; ShaderDefinition::MAX_SHADOW_CASCADES])
+                .with(&0.0f32)
+                .with(&false),
+        };
+        let shadow_block = render_context
+            .uniform_memory_allocator
+            .allocate(shadow_data);
+
         GlobalUniformData {
             camera_block,
             lights_block,
             graphics_settings_block,
+            fog_block,
+            shadow_block,
         }
     }
 
@@ -1150,6 +1373,39 @@ impl RenderDataBundleStorage {
     }
 }
 
+/// Packs fog parameters in the layout of `fyrox_fogData` built-in property group.
+fn fog_uniform_data(fog: &FogShaderParameters) -> StaticUniformBuffer<256> {
+    StaticUniformBuffer::<256>::new()
+        .with(&fog.color)
+        .with(&fog.enabled)
+        .with(&fog.mode)
+        .with(&fog.start)
+        .with(&fog.end)
+        .with(&fog.density)
+        .with(&fog.height_fog_enabled)
+        .with(&fog.height_fog_base_height)
+        .with(&fog.height_fog_falloff)
+        .with(&fog.height_fog_density)
+}
+
+/// Checks whether the forward render pass of the given material has blending enabled. Materials
+/// that aren't loaded yet are considered opaque.
+fn is_blended_in_forward_pass(material: &MaterialResource) -> bool {
+    let mut material_state = material.state();
+    let Some(material) = material_state.data() else {
+        return false;
+    };
+    let shader_state = material.shader().state();
+    let Some(shader) = shader_state.data_ref() else {
+        return false;
+    };
+    shader
+        .definition
+        .passes
+        .iter()
+        .any(|pass| pass.name == "Forward" && pass.draw_parameters.blend.is_some())
+}
+
 impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
     /// Adds a new mesh to the bundle storage using the given set of vertices and triangles. This
     /// method automatically creates a render bundle according to a hash of the following parameters:
@@ -1221,6 +1477,8 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
     /// Adds a new surface instance to the storage. The method will automatically put the instance in the appropriate
     /// bundle. Bundle selection is done using the material, surface data, render path. If only one
     /// of these parameters is different, then the surface instance will be put in a separate bundle.
+    /// Surfaces with [`RenderPath::Forward`] and a blended material are also separated by their
+    /// sort index, so they could be sorted individually.
     fn push(
         &mut self,
         data: &SurfaceResource,
@@ -1233,6 +1491,11 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
         hasher.write_u64(material.key());
         hasher.write_u64(data.key());
         hasher.write_u32(render_path as u32);
+        // Transparent surfaces must be sorted individually, so they can't share bundles with the
+        // surfaces at different depth. Opaque forward surfaces are still instanced.
+        if render_path == RenderPath::Forward && is_blended_in_forward_pass(material) {
+            hasher.write_u64(sort_index);
+        }
         let key = hasher.finish();
 
         let bundle = if let Some(&bundle_index) = self.bundle_map.get(&key) {
@@ -1256,9 +1519,36 @@ impl RenderDataBundleStorageTrait for RenderDataBundleStorage {
 
 #[cfg(test)]
 mod test {
-    use crate::renderer::bundle::{RenderContext, RenderDataBundleStorage};
+    use crate::graphics::{
+        BlendFactor, BlendFunc, BlendParameters, ColorMask, CompareFunc, DrawParameters,
+    };
+    use crate::material::{
+        shader::{Shader, ShaderResource, ShaderResourceExtension, ShaderResourceKind},
+        Material, MaterialResource,
+    };
+    use crate::renderer::bundle::{
+        fog_uniform_data, RenderContext, RenderDataBundleStorage, RenderDataBundleStorageOptions,
+        TransparencyStage,
+    };
     use crate::renderer::observer::ObserverPosition;
-    use fyrox_core::algebra::{Matrix4, Vector3};
+    use crate::scene::{
+        base::BaseBuilder,
+        fog::{FogMode, FogSettings, HeightFog},
+        graph::Graph,
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder, RenderPath,
+        },
+        node::Node,
+        transform::TransformBuilder,
+    };
+    use fyrox_core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+        uuid::Uuid,
+    };
+    use fyrox_resource::untyped::ResourceKind;
 
     //noinspection ALL
     #[test]
@@ -1305,4 +1595,331 @@ mod test {
             center - 3000
         );
     }
+
+    fn add_transparent_node(
+        graph: &mut Graph,
+        data: &SurfaceResource,
+        position: Vector3<f32>,
+        render_order: i32,
+    ) -> Handle<Node> {
+        MeshBuilder::new(
+            BaseBuilder::new()
+                .with_frustum_culling(false)
+                .with_render_order(render_order)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(data.clone()).build()])
+        .with_render_path(RenderPath::Forward)
+        .build(graph)
+        .to_base()
+    }
+
+    fn render_order_of(graph: &Graph, observer_position: &ObserverPosition) -> Vec<Handle<Node>> {
+        let storage = RenderDataBundleStorage::from_graph(
+            graph,
+            Default::default(),
+            0.0,
+            observer_position,
+            Default::default(),
+            RenderDataBundleStorageOptions {
+                collect_lights: false,
+            },
+            &mut Default::default(),
+        );
+        storage
+            .bundles
+            .iter()
+            .filter(|bundle| bundle.render_path == RenderPath::Forward)
+            .map(|bundle| bundle.instances[0].node_handle)
+            .collect()
+    }
+
+    #[test]
+    fn test_transparency_sorting() {
+        let observer_position = ObserverPosition {
+            translation: Vector3::new(0.0, 0.0, -10.0),
+            z_near: 0.0,
+            z_far: 0.0,
+            view_matrix: Matrix4::identity(),
+            projection_matrix: Matrix4::identity(),
+            view_projection_matrix: Matrix4::identity(),
+        };
+
+        // All nodes share the same surface data and material, yet they must be sorted individually.
+        let data = SurfaceResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        );
+
+        let mut graph = Graph::new();
+        let middle = add_transparent_node(&mut graph, &data, Vector3::new(0.0, 0.0, 5.0), 0);
+        let near = add_transparent_node(&mut graph, &data, Vector3::new(0.0, 0.0, 0.0), 0);
+        let far = add_transparent_node(&mut graph, &data, Vector3::new(0.0, 0.0, 10.0), 0);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        // Back-to-front.
+        assert_eq!(
+            render_order_of(&graph, &observer_position),
+            vec![far, middle, near]
+        );
+
+        // Render order is used when the distance is the same. Lower render order goes first.
+        let mut graph = Graph::new();
+        let top = add_transparent_node(&mut graph, &data, Vector3::new(1.0, 0.0, 0.0), 2);
+        let bottom = add_transparent_node(&mut graph, &data, Vector3::new(-1.0, 0.0, 0.0), -1);
+        let center = add_transparent_node(&mut graph, &data, Vector3::new(0.0, 1.0, 0.0), 0);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let sorted = render_order_of(&graph, &observer_position);
+        // The nodes are at the same distance to the observer.
+        for handle in [top, bottom, center] {
+            assert_eq!(
+                (graph[handle].global_position() - observer_position.translation).norm(),
+                101.0f32.sqrt()
+            );
+        }
+        assert_eq!(sorted, vec![bottom, center, top]);
+    }
+
+    #[test]
+    fn test_opaque_forward_instances_share_bundle() {
+        let data = SurfaceResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        );
+        let shader = Shader::from_string(
+            r#"(
+                name: "OpaqueForward",
+                resources: [],
+                passes: [
+                    (
+                        name: "Forward",
+                        vertex_shader: "<CODE>",
+                        fragment_shader: "<CODE>",
+                    ),
+                ],
+            )"#,
+        )
+        .unwrap();
+        let opaque = MaterialResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            Material::from_shader(ShaderResource::new_ok(
+                Uuid::new_v4(),
+                ResourceKind::Embedded,
+                shader,
+            )),
+        );
+        let blended =
+            MaterialResource::new_ok(Uuid::new_v4(), ResourceKind::Embedded, Material::standard());
+
+        let mut graph = Graph::new();
+        for material in [&opaque, &blended] {
+            for z in [0.0, 5.0] {
+                MeshBuilder::new(
+                    BaseBuilder::new()
+                        .with_frustum_culling(false)
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(0.0, 0.0, z))
+                                .build(),
+                        ),
+                )
+                .with_surfaces(vec![SurfaceBuilder::new(data.clone())
+                    .with_material(material.clone())
+                    .build()])
+                .with_render_path(RenderPath::Forward)
+                .build(&mut graph);
+            }
+        }
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let storage = RenderDataBundleStorage::from_graph(
+            &graph,
+            Default::default(),
+            0.0,
+            &ObserverPosition::default(),
+            Default::default(),
+            RenderDataBundleStorageOptions {
+                collect_lights: false,
+            },
+            &mut Default::default(),
+        );
+        let instance_counts = |material: &MaterialResource| {
+            storage
+                .bundles
+                .iter()
+                .filter(|bundle| bundle.material.key() == material.key())
+                .map(|bundle| bundle.instances.len())
+                .collect::<Vec<_>>()
+        };
+
+        // Both opaque instances are drawn in a single call.
+        assert_eq!(instance_counts(&opaque), vec![2]);
+        // Blended ones are sorted individually.
+        assert_eq!(instance_counts(&blended), vec![1, 1]);
+    }
+
+    #[test]
+    fn test_transparency_stage_draw_parameters() {
+        let opaque = DrawParameters {
+            cull_face: None,
+            depth_test: Some(CompareFunc::LessOrEqual),
+            ..Default::default()
+        };
+        let blended = DrawParameters {
+            color_write: ColorMask {
+                alpha: false,
+                ..Default::default()
+            },
+            blend: Some(BlendParameters {
+                func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                ..Default::default()
+            }),
+            ..opaque.clone()
+        };
+
+        // Without OIT every surface is drawn as is.
+        assert_eq!(
+            TransparencyStage::All.draw_parameters(&opaque),
+            Some(opaque.clone())
+        );
+        assert_eq!(
+            TransparencyStage::All.draw_parameters(&blended),
+            Some(blended.clone())
+        );
+
+        // Opaque stage skips blended surfaces.
+        assert_eq!(
+            TransparencyStage::Opaque.draw_parameters(&opaque),
+            Some(opaque.clone())
+        );
+        assert_eq!(TransparencyStage::Opaque.draw_parameters(&blended), None);
+
+        // Transparent stages skip opaque surfaces and replace blending of transparent ones, while
+        // keeping the depth test against the opaque geometry.
+        for stage in [
+            TransparencyStage::Accumulation,
+            TransparencyStage::Revealage,
+        ] {
+            assert_eq!(stage.draw_parameters(&opaque), None);
+
+            let params = stage.draw_parameters(&blended).unwrap();
+            assert!(!params.depth_write);
+            assert_eq!(params.depth_test, Some(CompareFunc::LessOrEqual));
+            assert_eq!(params.cull_face, None);
+            assert_eq!(params.color_write, ColorMask::default());
+
+            let func = params.blend.unwrap().func;
+            match stage {
+                TransparencyStage::Accumulation => {
+                    // Color is premultiplied by alpha and summed up, alpha is summed up as is.
+                    assert_eq!(
+                        func,
+                        BlendFunc::new_separate(
+                            BlendFactor::SrcAlpha,
+                            BlendFactor::One,
+                            BlendFactor::One,
+                            BlendFactor::One,
+                        )
+                    );
+                }
+                _ => {
+                    // Destination is multiplied by `1 - alpha` of the surface.
+                    assert_eq!(
+                        func,
+                        BlendFunc::new(BlendFactor::Zero, BlendFactor::OneMinusSrcAlpha)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fog_uniform_data() {
+        let fog = FogSettings {
+            enabled: true,
+            mode: FogMode::Linear {
+                start: 10.0,
+                end: 30.0,
+            },
+            color: Color::opaque(255, 128, 0),
+            height_fog: HeightFog {
+                enabled: true,
+                base_height: 1.0,
+                falloff: 0.3,
+                density: 0.1,
+            },
+        };
+        let parameters = fog.shader_parameters(true);
+        let data = fog_uniform_data(&parameters).finish();
+        let f32_at =
+            |offset: usize| f32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+        let i32_at =
+            |offset: usize| i32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap());
+
+        // The data is packed in the order of the properties of `fyrox_fogData` (std140 layout).
+        let standard = ShaderResource::standard();
+        let standard = standard.data_ref();
+        let fog_data = standard
+            .definition
+            .resources
+            .iter()
+            .find(|resource| resource.name.as_str() == "fyrox_fogData")
+            .unwrap();
+        let ShaderResourceKind::PropertyGroup(ref properties) = fog_data.kind else {
+            panic!("fyrox_fogData must be a property group")
+        };
+        let layout = [
+            ("fogColor", 0),
+            ("fogEnabled", 16),
+            ("fogMode", 20),
+            ("fogStart", 24),
+            ("fogEnd", 28),
+            ("fogDensity", 32),
+            ("heightFogEnabled", 36),
+            ("heightFogBaseHeight", 40),
+            ("heightFogFalloff", 44),
+            ("heightFogDensity", 48),
+        ];
+        assert_eq!(
+            properties
+                .iter()
+                .map(|property| property.name.as_str())
+                .collect::<Vec<_>>(),
+            layout.iter().map(|(name, _)| *name).collect::<Vec<_>>()
+        );
+
+        let color = fog.color.srgb_to_linear_f32();
+        for (i, component) in color.iter().enumerate() {
+            assert_eq!(f32_at(4 * i), *component);
+        }
+        assert_eq!(i32_at(16), 1);
+        assert_eq!(i32_at(20), 0);
+        assert_eq!(f32_at(24), 10.0);
+        assert_eq!(f32_at(28), 30.0);
+        assert_eq!(f32_at(32), 0.0);
+        assert_eq!(i32_at(36), 1);
+        assert_eq!(f32_at(40), 1.0);
+        assert_eq!(f32_at(44), 0.3);
+        assert_eq!(f32_at(48), 0.1);
+
+        // Height fog disabled by the quality settings.
+        let data = fog_uniform_data(&fog.shader_parameters(false)).finish();
+        assert_eq!(i32::from_ne_bytes(data[36..40].try_into().unwrap()), 0);
+
+        // Disabled fog is uploaded as all zeros, so the shaders skip it.
+        let disabled = FogSettings {
+            enabled: false,
+            ..fog
+        };
+        let data = fog_uniform_data(&disabled.shader_parameters(true)).finish();
+        assert!(data[..52].iter().all(|byte| *byte == 0));
+    }
 }
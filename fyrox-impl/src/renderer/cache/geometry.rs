@@ -33,8 +33,10 @@ use crate::{
 struct SurfaceRenderData {
     buffer: GpuGeometryBuffer,
     vertex_modifications_count: u64,
+    vertex_buffer_size: usize,
     triangles_modifications_count: u64,
     layout_hash: u64,
+    dynamic: bool,
 }
 
 #[derive(Default)]
@@ -51,15 +53,21 @@ fn create_geometry_buffer(
         // the parent scene node, surface index.
         "GeometryBuffer",
         data,
-        BufferUsage::StaticDraw,
+        if data.is_dynamic() {
+            BufferUsage::DynamicDraw
+        } else {
+            BufferUsage::StaticDraw
+        },
         server,
     )?;
 
     Ok(SurfaceRenderData {
         buffer: geometry_buffer,
         vertex_modifications_count: data.vertex_buffer.modifications_count(),
+        vertex_buffer_size: data.vertex_buffer.raw_data().len(),
         triangles_modifications_count: data.geometry_buffer.modifications_count(),
         layout_hash: data.vertex_buffer.layout_hash(),
+        dynamic: data.is_dynamic(),
     })
 }
 
@@ -70,7 +78,7 @@ impl GeometryCache {
         data: &SurfaceResource,
         time_to_live: TimeToLive,
     ) -> Result<&'a GpuGeometryBuffer, FrameworkError> {
-        let data = data.data_ref();
+        let mut data = data.data_ref();
 
         match self
             .buffer
@@ -78,17 +86,33 @@ impl GeometryCache {
                 create_geometry_buffer(&data, server)
             }) {
             Ok(entry) => {
-                // We also must check if buffer's layout changed, and if so - recreate the entire
-                // buffer.
-                if entry.layout_hash == data.vertex_buffer.layout_hash() {
+                // We also must check if buffer's layout or usage changed, and if so - recreate the
+                // entire buffer.
+                if entry.layout_hash == data.vertex_buffer.layout_hash()
+                    && entry.dynamic == data.is_dynamic()
+                {
                     if data.vertex_buffer.modifications_count() != entry.vertex_modifications_count
                     {
-                        // Vertices has changed, upload the new content.
-                        entry
-                            .buffer
-                            .set_buffer_data(0, data.vertex_buffer.raw_data());
+                        // Vertices has changed, upload the new content. Upload only the changed
+                        // range, if the size of the buffer is the same.
+                        let modified_range = data.vertex_buffer.take_modified_range();
+                        let raw_data = data.vertex_buffer.raw_data();
+                        match modified_range {
+                            Some(range)
+                                if entry.vertex_buffer_size == raw_data.len()
+                                    && range.end <= raw_data.len() =>
+                            {
+                                entry.buffer.set_buffer_data_at(
+                                    0,
+                                    range.start,
+                                    &raw_data[range],
+                                )?;
+                            }
+                            _ => entry.buffer.set_buffer_data(0, raw_data),
+                        }
 
                         entry.vertex_modifications_count = data.vertex_buffer.modifications_count();
+                        entry.vertex_buffer_size = raw_data.len();
                     }
 
                     if data.geometry_buffer.modifications_count()
@@ -103,16 +127,7 @@ impl GeometryCache {
                             data.geometry_buffer.modifications_count();
                     }
                 } else {
-                    let SurfaceRenderData {
-                        buffer,
-                        vertex_modifications_count,
-                        triangles_modifications_count,
-                        layout_hash,
-                    } = create_geometry_buffer(&data, server)?;
-                    entry.buffer = buffer;
-                    entry.vertex_modifications_count = vertex_modifications_count;
-                    entry.triangles_modifications_count = triangles_modifications_count;
-                    entry.layout_hash = layout_hash;
+                    entry.value = create_geometry_buffer(&data, server)?;
                 }
                 Ok(&entry.buffer)
             }
@@ -0,0 +1,345 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compositor combines outputs of multiple cameras into the final frame. It could be used for
+//! picture-in-picture, minimaps, various overlays, etc. Every camera that should be composited must
+//! render into its own render target (see [`Camera::set_render_target`]), the compositor then draws
+//! the render targets on top of the frame as a stack of layers. See [`Compositor`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{algebra::Vector2, math::Rect, sstorage::ImmutableString},
+    graphics::{
+        error::FrameworkError, framebuffer::GpuFrameBuffer, server::GraphicsServer, BlendFactor,
+        BlendFunc, BlendParameters, DrawParameters,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial},
+            texture::TextureCache,
+            uniform::UniformBufferCache,
+        },
+        make_viewport_matrix,
+        resources::RendererResources,
+        RenderPassStatistics,
+    },
+    resource::texture::TextureResource,
+    scene::camera::Camera,
+};
+
+/// Defines how colors of a layer are combined with colors that are already in the frame.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum CompositorBlendMode {
+    /// Standard alpha blending, the layer is drawn on top of the frame.
+    #[default]
+    Alpha,
+    /// Colors of the layer are added to the colors of the frame. Useful for glow-like overlays.
+    Additive,
+    /// Colors of the layer are multiplied with the colors of the frame. Makes the frame darker.
+    Multiply,
+    /// Inverse of multiplication of inverted colors. Makes the frame brighter.
+    Screen,
+}
+
+impl CompositorBlendMode {
+    fn blend_func(self) -> BlendFunc {
+        // The compositor shader outputs premultiplied colors for every mode except multiplication,
+        // which outputs the layer color mixed with white by its alpha.
+        match self {
+            Self::Alpha => BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            Self::Additive => BlendFunc::new(BlendFactor::One, BlendFactor::One),
+            Self::Multiply => BlendFunc::new(BlendFactor::DstColor, BlendFactor::Zero),
+            Self::Screen => BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcColor),
+        }
+    }
+
+    fn shader_index(self) -> i32 {
+        match self {
+            Self::Alpha => 0,
+            Self::Additive => 1,
+            Self::Multiply => 2,
+            Self::Screen => 3,
+        }
+    }
+}
+
+/// A single layer of the compositor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompositorLayer {
+    /// A texture that will be drawn. Usually it is a render target of some camera.
+    pub texture: TextureResource,
+    /// A rectangle in normalized coordinates (`[0; 1]` range, origin is at the top-left corner of
+    /// the frame) that defines where the texture will be drawn.
+    pub rect: Rect<f32>,
+    /// Opacity of the layer in `[0; 1]` range.
+    pub opacity: f32,
+    /// Blend mode of the layer.
+    pub blend_mode: CompositorBlendMode,
+}
+
+impl CompositorLayer {
+    /// Creates a new layer that covers the entire frame.
+    pub fn new(texture: TextureResource) -> Self {
+        Self {
+            texture,
+            rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            opacity: 1.0,
+            blend_mode: Default::default(),
+        }
+    }
+
+    /// Creates a new layer that shows the output of the given camera. The camera must have a render
+    /// target, otherwise [`None`] is returned.
+    pub fn from_camera(camera: &Camera) -> Option<Self> {
+        camera.render_target().cloned().map(Self::new)
+    }
+
+    /// Sets a rectangle (in normalized coordinates) in which the layer will be drawn.
+    pub fn with_rect(mut self, rect: Rect<f32>) -> Self {
+        self.rect = rect;
+        self
+    }
+
+    /// Sets opacity of the layer.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Sets blend mode of the layer.
+    pub fn with_blend_mode(mut self, blend_mode: CompositorBlendMode) -> Self {
+        self.blend_mode = blend_mode;
+        self
+    }
+
+    /// Calculates a viewport (in pixels, origin is at the bottom-left corner of the frame) of the
+    /// layer for a frame of the given size.
+    pub fn screen_rect(&self, frame_size: Vector2<f32>) -> Rect<i32> {
+        let x = (self.rect.x() * frame_size.x).round() as i32;
+        let y = ((1.0 - self.rect.y() - self.rect.h()) * frame_size.y).round() as i32;
+        let w = (self.rect.w() * frame_size.x).round() as i32;
+        let h = (self.rect.h() * frame_size.y).round() as i32;
+        Rect::new(x, y, w, h)
+    }
+
+    /// Returns draw parameters of the layer. Layers are drawn over the frame without depth test,
+    /// using the blending function of the blend mode of the layer.
+    fn draw_parameters(&self) -> DrawParameters {
+        DrawParameters {
+            cull_face: None,
+            depth_write: false,
+            depth_test: None,
+            blend: Some(BlendParameters {
+                func: self.blend_mode.blend_func(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Compositor draws a stack of layers on top of the final frame. Layers are drawn in order, after
+/// all scenes and before user interface. Typical usage is to render a secondary camera into its own
+/// render target and then add a layer for it:
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::math::Rect,
+/// #     renderer::{compositor::{CompositorBlendMode, CompositorLayer}, Renderer},
+/// #     scene::camera::Camera,
+/// # };
+/// fn add_minimap(renderer: &mut Renderer, minimap_camera: &Camera) {
+///     if let Some(layer) = CompositorLayer::from_camera(minimap_camera) {
+///         renderer.compositor_mut().layers.push(
+///             layer
+///                 .with_rect(Rect::new(0.75, 0.0, 0.25, 0.25))
+///                 .with_opacity(0.8)
+///                 .with_blend_mode(CompositorBlendMode::Alpha),
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Compositor {
+    /// A list of layers. The first layer is drawn first.
+    pub layers: Vec<CompositorLayer>,
+}
+
+impl Compositor {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        server: &dyn GraphicsServer,
+        frame_buffer: &GpuFrameBuffer,
+        frame_size: Vector2<f32>,
+        texture_cache: &mut TextureCache,
+        resource_manager: &ResourceManager,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        if self.layers.is_empty() {
+            return Ok(statistics);
+        }
+
+        let _debug_scope = server.begin_scope("Compositor");
+
+        for layer in self.layers.iter() {
+            let viewport = layer.screen_rect(frame_size);
+            if viewport.w() <= 0 || viewport.h() <= 0 || layer.opacity <= 0.0 {
+                continue;
+            }
+
+            let Some(texture) = texture_cache.get(server, resource_manager, &layer.texture) else {
+                continue;
+            };
+
+            let frame_matrix = make_viewport_matrix(viewport);
+            let blend_mode = layer.blend_mode.shader_index();
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &frame_matrix),
+                property("opacity", &layer.opacity),
+                property("blendMode", &blend_mode),
+            ]);
+            let material = RenderMaterial::from([
+                binding("layerTexture", (&texture.gpu_texture, &texture.gpu_sampler)),
+                binding("properties", &properties),
+            ]);
+
+            let draw_params = layer.draw_parameters();
+
+            statistics += renderer_resources.shaders.compositor.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                frame_buffer,
+                &renderer_resources.quad,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                Default::default(),
+                Some(&draw_params),
+            )?;
+        }
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::Rect},
+        graphics::{BlendEquation, BlendFactor, BlendFunc},
+        material::shader::Shader,
+        renderer::compositor::{CompositorBlendMode, CompositorLayer},
+        resource::texture::{TextureResource, TextureResourceExtension},
+        scene::{base::BaseBuilder, camera::CameraBuilder},
+    };
+
+    #[test]
+    fn test_secondary_camera_renders_into_corner() {
+        let camera = CameraBuilder::new(BaseBuilder::new())
+            .with_render_target(Some(TextureResource::new_render_target(200, 150)))
+            .build_camera();
+
+        let layer = CompositorLayer::from_camera(&camera)
+            .unwrap()
+            .with_rect(Rect::new(0.75, 0.0, 0.25, 0.25));
+        assert_eq!(layer.texture, *camera.render_target().unwrap());
+
+        // Top-right corner of the frame, viewport origin is at the bottom-left corner.
+        assert_eq!(
+            layer.screen_rect(Vector2::new(800.0, 600.0)),
+            Rect::new(600, 450, 200, 150)
+        );
+
+        let main_camera = CameraBuilder::new(BaseBuilder::new()).build_camera();
+        assert!(CompositorLayer::from_camera(&main_camera).is_none());
+    }
+
+    #[test]
+    fn test_screen_rect() {
+        let frame_size = Vector2::new(800.0, 600.0);
+        let layer = CompositorLayer::new(TextureResource::new_render_target(1, 1));
+        assert_eq!(layer.screen_rect(frame_size), Rect::new(0, 0, 800, 600));
+
+        // Bottom-left quarter, the origin of the viewport is at the bottom-left corner.
+        let layer = layer.with_rect(Rect::new(0.0, 0.5, 0.5, 0.5));
+        assert_eq!(layer.screen_rect(frame_size), Rect::new(0, 0, 400, 300));
+
+        // Coordinates are rounded to the nearest pixel.
+        let layer = layer.with_rect(Rect::new(0.1, 0.1, 0.333, 0.333));
+        assert_eq!(layer.screen_rect(frame_size), Rect::new(80, 340, 266, 200));
+
+        // Empty layers are not drawn at all.
+        let layer = layer.with_rect(Rect::new(0.5, 0.5, 0.0, 0.0));
+        let rect = layer.screen_rect(frame_size);
+        assert!(rect.w() <= 0 || rect.h() <= 0);
+    }
+
+    #[test]
+    fn test_draw_parameters() {
+        let layer = CompositorLayer::new(TextureResource::new_render_target(1, 1));
+        for (mode, shader_index, func) in [
+            (
+                CompositorBlendMode::Alpha,
+                0,
+                BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcAlpha),
+            ),
+            (
+                CompositorBlendMode::Additive,
+                1,
+                BlendFunc::new(BlendFactor::One, BlendFactor::One),
+            ),
+            (
+                CompositorBlendMode::Multiply,
+                2,
+                BlendFunc::new(BlendFactor::DstColor, BlendFactor::Zero),
+            ),
+            (
+                CompositorBlendMode::Screen,
+                3,
+                BlendFunc::new(BlendFactor::One, BlendFactor::OneMinusSrcColor),
+            ),
+        ] {
+            assert_eq!(mode.blend_func(), func);
+            assert_eq!(mode.shader_index(), shader_index);
+
+            let params = layer.clone().with_blend_mode(mode).draw_parameters();
+            let blend = params.blend.as_ref().unwrap();
+            assert_eq!(blend.func, func);
+            assert_eq!(blend.equation, BlendEquation::default());
+            assert!(!params.depth_write);
+            assert!(params.depth_test.is_none());
+            assert!(params.cull_face.is_none());
+        }
+
+        // Multiplication is the only mode that is handled specially by the shader, the rest
+        // output premultiplied colors.
+        let shader = Shader::from_string(include_str!("shaders/compositor.shader")).unwrap();
+        let fragment_shader = &shader.definition.passes[0].fragment_shader.0;
+        assert!(fragment_shader.contains(&format!(
+            "properties.blendMode == {}",
+            CompositorBlendMode::Multiply.shader_index()
+        )));
+        assert!(fragment_shader.contains("FragColor = vec4(color.rgb * alpha, alpha);"));
+    }
+}
@@ -240,6 +240,10 @@ impl GBuffer {
                 renderer_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,           // TODO. Add z-pre-pass.
+                fog: None,
+                use_height_fog: false,
+                transparency_stage: Default::default(),
+                shadows: None,
             },
         )?;
 
@@ -24,6 +24,7 @@ use crate::{
         algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
         color::Color,
         math::{frustum::Frustum, Matrix4Ext, Rect, TriangleDefinition},
+        pool::Handle,
         ImmutableString,
     },
     graphics::{
@@ -36,8 +37,9 @@ use crate::{
         ColorMask, CompareFunc, CullFace, DrawParameters, ElementRange, StencilAction, StencilFunc,
         StencilOp,
     },
+    material::shader::ShaderDefinition,
     renderer::{
-        bundle::{LightSourceKind, RenderDataBundleStorage},
+        bundle::{ForwardShadows, LightSource, LightSourceKind, RenderDataBundleStorage},
         cache::{
             shader::{binding, property, PropertyGroup, RenderMaterial, ShaderCache},
             uniform::{UniformBufferCache, UniformMemoryAllocator},
@@ -49,8 +51,9 @@ use crate::{
         light_volume::LightVolumeRenderer,
         make_viewport_matrix,
         observer::Observer,
-        resources::RendererResources,
+        resources::{RendererResources, CASCADE_DEBUG_TINTS},
         shadow::{
+            cache::{CachedShadowMapKind, CachedShadowMapRenderer, ShadowMapCache},
             csm::{CsmRenderContext, CsmRenderer},
             point::{PointShadowMapRenderContext, PointShadowMapRenderer},
             spot::SpotShadowMapRenderer,
@@ -61,6 +64,7 @@ use crate::{
         GeometryCache, LightingStatistics, QualitySettings, RenderPassStatistics, TextureCache,
     },
     scene::{
+        light::area::AreaLightShape,
         mesh::{
             buffer::{TriangleBuffer, VertexBuffer},
             surface::SurfaceData,
@@ -77,6 +81,7 @@ pub struct DeferredLightRenderer {
     spot_shadow_map_renderer: SpotShadowMapRenderer,
     point_shadow_map_renderer: PointShadowMapRenderer,
     csm_renderer: CsmRenderer,
+    shadow_map_cache: ShadowMapCache,
     light_volume: LightVolumeRenderer,
     brdf_lut: GpuTexture,
 }
@@ -85,6 +90,7 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub elapsed_time: f32,
     pub server: &'a dyn GraphicsServer,
     pub scene: &'a Scene,
+    pub scene_handle: Handle<Scene>,
     pub observer: &'a Observer,
     pub gbuffer: &'a mut GBuffer,
     pub ambient_color: Color,
@@ -105,6 +111,65 @@ pub(crate) struct DeferredRendererContext<'a> {
     pub environment_map_specular_convolution: &'a mut Option<EnvironmentMapSpecularConvolution>,
     pub environment_map_irradiance_convolution: &'a EnvironmentMapIrradianceConvolution,
     pub need_recalculate_convolution: &'a mut bool,
+    pub environment_map_key: &'a mut Option<u64>,
+    pub visualize_csm_cascades: bool,
+}
+
+pub(crate) struct ForwardShadowsContext<'a> {
+    pub elapsed_time: f32,
+    pub server: &'a dyn GraphicsServer,
+    pub scene: &'a Scene,
+    pub observer: &'a Observer,
+    pub frame_size: Vector2<f32>,
+    pub render_data_bundle: &'a RenderDataBundleStorage,
+    pub settings: &'a QualitySettings,
+    pub textures: &'a mut TextureCache,
+    pub geometry_cache: &'a mut GeometryCache,
+    pub shader_cache: &'a mut ShaderCache,
+    pub renderer_resources: &'a RendererResources,
+    pub uniform_memory_allocator: &'a mut UniformMemoryAllocator,
+    pub dynamic_surface_cache: &'a mut DynamicSurfaceCache,
+    pub resource_manager: &'a ResourceManager,
+}
+
+/// Returns an index of the light source, that casts shadows in forward passes. Only the first
+/// directional light that casts shadows is used, since forward passes are limited in the amount
+/// of shadow maps they can sample.
+pub(crate) fn forward_shadow_caster(
+    render_data_bundle: &RenderDataBundleStorage,
+    settings: &QualitySettings,
+) -> Option<usize> {
+    if !settings.csm_settings.enabled {
+        return None;
+    }
+    render_data_bundle
+        .light_sources
+        .iter()
+        .take(ShaderDefinition::MAX_LIGHTS)
+        .position(|light| {
+            light.cast_shadows && matches!(light.kind, LightSourceKind::Directional { .. })
+        })
+}
+
+/// Returns `true` if percentage-closer soft shadows (contact-hardening shadows) should be used for
+/// the given light source. They are used only if soft shadows are enabled for the light kind and
+/// the light has non-zero size.
+fn is_pcss_enabled(settings: &QualitySettings, light: &LightSource) -> bool {
+    let soft_shadows = match light.kind {
+        LightSourceKind::Spot { .. } => settings.spot_soft_shadows,
+        LightSourceKind::Point { .. } => settings.point_soft_shadows,
+        LightSourceKind::Directional { .. } => settings.csm_settings.pcf,
+        LightSourceKind::Area { .. } | LightSourceKind::Unknown => false,
+    };
+    settings.use_pcss && soft_shadows && light.light_size > 0.0
+}
+
+/// Returns `true` if the ambient light pass should take its lighting from the prefiltered specular
+/// and irradiance maps of the environment. Scenes without any environment (no probes and no skybox)
+/// fall back to constant ambient lighting, otherwise everything would be lit by black dummy
+/// environment.
+fn is_skybox_lighting(source: EnvironmentLightingSource, has_environment_map: bool) -> bool {
+    has_environment_map && matches!(source, EnvironmentLightingSource::SkyBox)
 }
 
 impl DeferredLightRenderer {
@@ -198,6 +263,7 @@ impl DeferredLightRenderer {
                 quality_defaults.point_shadow_map_precision,
             )?,
             light_volume: LightVolumeRenderer::new(server)?,
+            shadow_map_cache: Default::default(),
             csm_renderer: CsmRenderer::new(
                 server,
                 quality_defaults.csm_settings.size,
@@ -208,6 +274,11 @@ impl DeferredLightRenderer {
         })
     }
 
+    /// Must be called once per frame, before rendering anything.
+    pub fn begin_frame(&mut self) {
+        self.shadow_map_cache.begin_frame();
+    }
+
     pub fn set_quality_settings(
         &mut self,
         server: &dyn GraphicsServer,
@@ -244,6 +315,122 @@ impl DeferredLightRenderer {
         Ok(())
     }
 
+    /// Renders skybox of the scene (if any) into the given frame buffer. Skybox is rendered
+    /// without depth test, so it must be rendered before any other geometry.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render_skybox(
+        &self,
+        server: &dyn GraphicsServer,
+        scene: &Scene,
+        observer: &Observer,
+        textures: &mut TextureCache,
+        resource_manager: &ResourceManager,
+        frame_buffer: &GpuFrameBuffer,
+        viewport: Rect<i32>,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut pass_stats = RenderPassStatistics::default();
+        if let Some(skybox) = scene.skybox_ref().and_then(|s| s.cubemap_ref()) {
+            if let Some(texture_sampler_pair) = textures.get(server, resource_manager, skybox) {
+                let size = observer.position.z_far / 2.0f32.sqrt();
+                let scale = Matrix4::new_scaling(size);
+                let wvp = Matrix4::new_translation(&observer.position.translation) * scale;
+                let wvp = observer.position.view_projection_matrix * wvp;
+                let properties = PropertyGroup::from([property("worldViewProjection", &wvp)]);
+                let material = RenderMaterial::from([
+                    binding(
+                        "cubemapTexture",
+                        (
+                            &texture_sampler_pair.gpu_texture,
+                            &texture_sampler_pair.gpu_sampler,
+                        ),
+                    ),
+                    binding("properties", &properties),
+                ]);
+
+                pass_stats += renderer_resources.shaders.skybox.run_pass(
+                    1,
+                    &ImmutableString::new("Primary"),
+                    frame_buffer,
+                    &self.skybox,
+                    viewport,
+                    &material,
+                    uniform_buffer_cache,
+                    ElementRange::Specific {
+                        offset: 0,
+                        count: 12,
+                    },
+                    None,
+                )?;
+            }
+        }
+
+        Ok(pass_stats)
+    }
+
+    /// Renders cascaded shadow maps of the directional light, that casts shadows in forward passes
+    /// (if any). Returns the index of the light, its shadows could be fetched using
+    /// [`Self::forward_shadows`].
+    pub(crate) fn render_forward_shadow_maps(
+        &mut self,
+        args: ForwardShadowsContext,
+    ) -> Result<(RenderPassStatistics, Option<usize>), FrameworkError> {
+        let Some(light_index) = forward_shadow_caster(args.render_data_bundle, args.settings)
+        else {
+            return Ok((Default::default(), None));
+        };
+
+        let stats = self.csm_renderer.render(CsmRenderContext {
+            elapsed_time: args.elapsed_time,
+            frame_size: args.frame_size,
+            server: args.server,
+            graph: &args.scene.graph,
+            light: &args.render_data_bundle.light_sources[light_index],
+            observer: args.observer,
+            geom_cache: args.geometry_cache,
+            shader_cache: args.shader_cache,
+            texture_cache: args.textures,
+            renderer_resources: args.renderer_resources,
+            uniform_memory_allocator: args.uniform_memory_allocator,
+            dynamic_surface_cache: args.dynamic_surface_cache,
+            resource_manager: args.resource_manager,
+        })?;
+
+        Ok((stats, Some(light_index)))
+    }
+
+    /// Returns cascaded shadow maps of the light with the given index, that were rendered by
+    /// [`Self::render_forward_shadow_maps`].
+    pub(crate) fn forward_shadows(
+        &self,
+        light_index: usize,
+        render_data_bundle: &RenderDataBundleStorage,
+        settings: &QualitySettings,
+    ) -> ForwardShadows<'_> {
+        let shadow_bias = match render_data_bundle.light_sources[light_index].kind {
+            LightSourceKind::Directional { ref csm_options } => csm_options.shadow_bias(),
+            _ => 0.0,
+        };
+        let cascades = self.csm_renderer.cascades();
+        ForwardShadows {
+            light_index,
+            cascades: [
+                cascades[0].texture(),
+                cascades[1].texture(),
+                cascades[2].texture(),
+            ],
+            view_proj_matrices: [
+                cascades[0].view_proj_matrix,
+                cascades[1].view_proj_matrix,
+                cascades[2].view_proj_matrix,
+            ],
+            cascade_distances: [cascades[0].z_far, cascades[1].z_far, cascades[2].z_far],
+            shadow_bias,
+            soft_shadows: settings.csm_settings.pcf,
+        }
+    }
+
     pub(crate) fn render(
         &mut self,
         args: DeferredRendererContext,
@@ -257,6 +444,7 @@ impl DeferredLightRenderer {
             elapsed_time,
             server,
             scene,
+            scene_handle,
             observer,
             gbuffer,
             environment_lighting_source,
@@ -277,8 +465,11 @@ impl DeferredLightRenderer {
             environment_map_specular_convolution,
             environment_map_irradiance_convolution,
             need_recalculate_convolution,
+            environment_map_key,
+            visualize_csm_cascades,
         } = args;
 
+        let frame = self.shadow_map_cache.frame();
         let viewport = Rect::new(0, 0, gbuffer.width, gbuffer.height);
         let frustum =
             Frustum::from_view_projection_matrix(observer.position.view_projection_matrix)
@@ -310,52 +501,58 @@ impl DeferredLightRenderer {
         }
 
         // Render skybox (if any).
-        if let Some(skybox) = scene.skybox_ref().and_then(|s| s.cubemap_ref()) {
-            if let Some(texture_sampler_pair) = textures.get(server, resource_manager, skybox) {
-                let size = observer.position.z_far / 2.0f32.sqrt();
-                let scale = Matrix4::new_scaling(size);
-                let wvp = Matrix4::new_translation(&observer.position.translation) * scale;
-                let wvp = observer.position.view_projection_matrix * wvp;
-                let properties = PropertyGroup::from([property("worldViewProjection", &wvp)]);
-                let material = RenderMaterial::from([
-                    binding(
-                        "cubemapTexture",
-                        (
-                            &texture_sampler_pair.gpu_texture,
-                            &texture_sampler_pair.gpu_sampler,
-                        ),
-                    ),
-                    binding("properties", &properties),
-                ]);
-
-                pass_stats += renderer_resources.shaders.skybox.run_pass(
-                    1,
-                    &ImmutableString::new("Primary"),
-                    frame_buffer,
-                    &self.skybox,
-                    viewport,
-                    &material,
-                    uniform_buffer_cache,
-                    ElementRange::Specific {
-                        offset: 0,
-                        count: 12,
-                    },
-                    None,
-                )?;
-            }
-        }
+        pass_stats += self.render_skybox(
+            server,
+            scene,
+            observer,
+            textures,
+            resource_manager,
+            frame_buffer,
+            viewport,
+            uniform_buffer_cache,
+            renderer_resources,
+        )?;
 
-        let environment_map = observer
+        let environment_map_resource = observer
             .environment_map
             .as_ref()
             .or(render_data_bundle.environment_map.as_ref())
-            .or_else(|| scene.skybox_ref().and_then(|s| s.cubemap_ref()))
-            .and_then(|c| {
-                textures
-                    .get(server, resource_manager, c)
-                    .map(|d| &d.gpu_texture)
-            })
-            .unwrap_or(&renderer_resources.environment_dummy);
+            .or_else(|| scene.skybox_ref().and_then(|s| s.cubemap_ref()));
+        // The environment map could be replaced (for example, when procedural sky is regenerated),
+        // the convolution must be recalculated in this case.
+        let new_environment_map_key = environment_map_resource.map(|c| c.key());
+        if *environment_map_key != new_environment_map_key {
+            *environment_map_key = new_environment_map_key;
+            *need_recalculate_convolution = true;
+        }
+        let environment_map = environment_map_resource.and_then(|c| {
+            textures
+                .get(server, resource_manager, c)
+                .map(|d| &d.gpu_texture)
+        });
+        let has_environment_map = environment_map.is_some();
+        // Parallax correction is used only if the environment map is provided by a reflection probe.
+        let box_projection = if observer.environment_map.is_none() {
+            render_data_bundle.environment_map_projection
+        } else {
+            None
+        };
+        let (box_projection_enabled, probe_bounds_min, probe_bounds_max, probe_capture_position) =
+            match box_projection {
+                Some(projection) => (
+                    true,
+                    projection.bounds.min,
+                    projection.bounds.max,
+                    projection.capture_position,
+                ),
+                None => (
+                    false,
+                    Vector3::default(),
+                    Vector3::default(),
+                    Vector3::default(),
+                ),
+            };
+        let environment_map = environment_map.unwrap_or(&renderer_resources.environment_dummy);
 
         if *need_recalculate_convolution {
             // Prepare the specular convolution.
@@ -408,10 +605,7 @@ impl DeferredLightRenderer {
         let gbuffer_ambient_map = gbuffer.ambient_texture();
         let ao_map = ssao_renderer.ao_map();
 
-        let skybox_lighting = matches!(
-            environment_lighting_source,
-            EnvironmentLightingSource::SkyBox
-        );
+        let skybox_lighting = is_skybox_lighting(environment_lighting_source, has_environment_map);
         let ambient_color = ambient_color.srgb_to_linear_f32();
         let properties = PropertyGroup::from([
             property("worldViewProjection", &frame_matrix),
@@ -419,6 +613,10 @@ impl DeferredLightRenderer {
             property("cameraPosition", &observer.position.translation),
             property("invViewProj", &inv_view_projection),
             property("skyboxLighting", &skybox_lighting),
+            property("boxProjection", &box_projection_enabled),
+            property("probeBoundsMin", &probe_bounds_min),
+            property("probeBoundsMax", &probe_bounds_max),
+            property("probeCapturePosition", &probe_capture_position),
             property(
                 "environmentLightingBrightness",
                 &scene.rendering_options.environment_lighting_brightness,
@@ -544,6 +742,15 @@ impl DeferredLightRenderer {
                     &self.sphere,
                     Matrix4::new_scaling(radius * 1.05),
                 ),
+                // Area lights do not cast shadows.
+                LightSourceKind::Area { distance, .. } => (
+                    distance,
+                    0.0,
+                    false,
+                    0.0,
+                    &self.sphere,
+                    Matrix4::new_scaling(distance * 1.05),
+                ),
                 LightSourceKind::Directional { .. } => {
                     (
                         f32::MAX,
@@ -709,32 +916,119 @@ impl DeferredLightRenderer {
 
                         light_view_projection = light_projection_matrix * light_view_matrix;
 
-                        pass_stats += self.spot_shadow_map_renderer.render(
-                            server,
-                            &scene.graph,
-                            observer.render_mask,
-                            elapsed_time,
-                            light.position,
-                            light_view_matrix,
-                            z_near,
-                            z_far,
-                            light_projection_matrix,
-                            geometry_cache,
-                            cascade_index,
-                            shader_cache,
-                            textures,
-                            renderer_resources,
-                            uniform_memory_allocator,
-                            dynamic_surface_cache,
-                            resource_manager,
-                        )?;
+                        let shadow_map_settings = &light.shadow_map_settings;
+                        if shadow_map_settings.is_custom() {
+                            // The light has its own shadow map, that is updated only when needed.
+                            let cached = self.shadow_map_cache.get_or_create(
+                                server,
+                                scene_handle,
+                                light.handle,
+                                CachedShadowMapKind::Spot,
+                                shadow_map_settings,
+                                settings.spot_shadow_map_size,
+                                settings.spot_shadow_map_precision,
+                            )?;
+                            if cached.tracker.needs_update(
+                                shadow_map_settings.cadence,
+                                frame,
+                                &light_view_projection,
+                            ) {
+                                cached.light_view_projection = light_view_projection;
+                                if let CachedShadowMapRenderer::Spot(ref mut renderer) =
+                                    cached.renderer
+                                {
+                                    pass_stats += renderer.render(
+                                        server,
+                                        &scene.graph,
+                                        observer.render_mask,
+                                        elapsed_time,
+                                        light.position,
+                                        light_view_matrix,
+                                        z_near,
+                                        z_far,
+                                        light_projection_matrix,
+                                        geometry_cache,
+                                        0,
+                                        shader_cache,
+                                        textures,
+                                        renderer_resources,
+                                        uniform_memory_allocator,
+                                        dynamic_surface_cache,
+                                        resource_manager,
+                                    )?;
+                                    light_stats.spot_shadow_maps_rendered += 1;
+                                }
+                            }
+                            light_view_projection = cached.light_view_projection;
+                        } else {
+                            pass_stats += self.spot_shadow_map_renderer.render(
+                                server,
+                                &scene.graph,
+                                observer.render_mask,
+                                elapsed_time,
+                                light.position,
+                                light_view_matrix,
+                                z_near,
+                                z_far,
+                                light_projection_matrix,
+                                geometry_cache,
+                                cascade_index,
+                                shader_cache,
+                                textures,
+                                renderer_resources,
+                                uniform_memory_allocator,
+                                dynamic_surface_cache,
+                                resource_manager,
+                            )?;
 
-                        light_stats.spot_shadow_maps_rendered += 1;
+                            light_stats.spot_shadow_maps_rendered += 1;
+                        }
                     }
                     LightSourceKind::Point { .. } => {
-                        pass_stats +=
-                            self.point_shadow_map_renderer
-                                .render(PointShadowMapRenderContext {
+                        let shadow_map_settings = &light.shadow_map_settings;
+                        if shadow_map_settings.is_custom() {
+                            // The light has its own shadow map, that is updated only when needed.
+                            let cached = self.shadow_map_cache.get_or_create(
+                                server,
+                                scene_handle,
+                                light.handle,
+                                CachedShadowMapKind::Point,
+                                shadow_map_settings,
+                                settings.point_shadow_map_size,
+                                settings.point_shadow_map_precision,
+                            )?;
+                            let light_matrix = Matrix4::new_translation(&light.position)
+                                * Matrix4::new_scaling(light_radius);
+                            if cached.tracker.needs_update(
+                                shadow_map_settings.cadence,
+                                frame,
+                                &light_matrix,
+                            ) {
+                                if let CachedShadowMapRenderer::Point(ref mut renderer) =
+                                    cached.renderer
+                                {
+                                    pass_stats += renderer.render(PointShadowMapRenderContext {
+                                        render_mask: observer.render_mask,
+                                        elapsed_time,
+                                        server,
+                                        graph: &scene.graph,
+                                        light_pos: light.position,
+                                        light_radius,
+                                        geom_cache: geometry_cache,
+                                        cascade: 0,
+                                        shader_cache,
+                                        texture_cache: textures,
+                                        renderer_resources,
+                                        uniform_memory_allocator,
+                                        dynamic_surface_cache,
+                                        resource_manager,
+                                    })?;
+                                    light_stats.point_shadow_maps_rendered += 1;
+                                }
+                            }
+                        } else {
+                            pass_stats += self.point_shadow_map_renderer.render(
+                                PointShadowMapRenderContext {
                                     render_mask: observer.render_mask,
                                     elapsed_time,
                                     server,
@@ -749,9 +1043,11 @@ impl DeferredLightRenderer {
                                     uniform_memory_allocator,
                                     dynamic_surface_cache,
                                     resource_manager,
-                                })?;
+                                },
+                            )?;
 
-                        light_stats.point_shadow_maps_rendered += 1;
+                            light_stats.point_shadow_maps_rendered += 1;
+                        }
                     }
                     LightSourceKind::Directional { .. } => {
                         pass_stats += self.csm_renderer.render(CsmRenderContext {
@@ -772,13 +1068,14 @@ impl DeferredLightRenderer {
 
                         light_stats.csm_rendered += 1;
                     }
-                    LightSourceKind::Unknown => {}
+                    LightSourceKind::Area { .. } | LightSourceKind::Unknown => {}
                 }
             }
 
             if needs_lighting {
                 let quad = &renderer_resources.quad;
                 let color = light.color.srgb_to_linear_f32();
+                let pcss = is_pcss_enabled(settings, light);
 
                 pass_stats += match light.kind {
                     LightSourceKind::Spot {
@@ -814,8 +1111,18 @@ impl DeferredLightRenderer {
 
                         light_stats.spot_lights_rendered += 1;
 
-                        let inv_size = 1.0
-                            / (self.spot_shadow_map_renderer.cascade_size(cascade_index) as f32);
+                        let (spot_shadow_map, spot_shadow_map_size) = match self
+                            .shadow_map_cache
+                            .get(scene_handle, light.handle)
+                            .filter(|_| light.shadow_map_settings.is_custom())
+                        {
+                            Some(cached) => (cached.texture(), cached.size()),
+                            None => (
+                                self.spot_shadow_map_renderer.cascade_texture(cascade_index),
+                                self.spot_shadow_map_renderer.cascade_size(cascade_index),
+                            ),
+                        };
+                        let inv_size = 1.0 / (spot_shadow_map_size as f32);
 
                         let half_hotspot_cone_angle_cos = (hotspot_cone_angle * 0.5).cos();
                         let half_cone_angle_cos = (full_cone_angle * 0.5).cos();
@@ -837,6 +1144,8 @@ impl DeferredLightRenderer {
                             property("cookieEnabled", &cookie_enabled),
                             property("shadowsEnabled", &shadows_enabled),
                             property("softShadows", &settings.spot_soft_shadows),
+                            property("pcss", &pcss),
+                            property("lightSize", &light.light_size),
                         ]);
                         let material = RenderMaterial::from([
                             binding(
@@ -866,10 +1175,7 @@ impl DeferredLightRenderer {
                             ),
                             binding(
                                 "spotShadowTexture",
-                                (
-                                    self.spot_shadow_map_renderer.cascade_texture(cascade_index),
-                                    &renderer_resources.nearest_clamp_sampler,
-                                ),
+                                (spot_shadow_map, &renderer_resources.nearest_clamp_sampler),
                             ),
                             binding("cookieTexture", cookie_texture),
                             binding("properties", &properties),
@@ -890,6 +1196,17 @@ impl DeferredLightRenderer {
                     LightSourceKind::Point { shadow_bias, .. } => {
                         light_stats.point_lights_rendered += 1;
 
+                        let point_shadow_map = match self
+                            .shadow_map_cache
+                            .get(scene_handle, light.handle)
+                            .filter(|_| light.shadow_map_settings.is_custom())
+                        {
+                            Some(cached) => cached.texture(),
+                            None => self
+                                .point_shadow_map_renderer
+                                .cascade_texture(cascade_index),
+                        };
+
                         let properties = PropertyGroup::from([
                             property("worldViewProjection", &frame_matrix),
                             property("invViewProj", &inv_view_projection),
@@ -902,6 +1219,8 @@ impl DeferredLightRenderer {
                             property("shadowAlpha", &shadows_alpha),
                             property("shadowsEnabled", &shadows_enabled),
                             property("softShadows", &settings.point_soft_shadows),
+                            property("pcss", &pcss),
+                            property("lightSize", &light.light_size),
                         ]);
                         let material = RenderMaterial::from([
                             binding(
@@ -931,11 +1250,7 @@ impl DeferredLightRenderer {
                             ),
                             binding(
                                 "pointShadowTexture",
-                                (
-                                    self.point_shadow_map_renderer
-                                        .cascade_texture(cascade_index),
-                                    &renderer_resources.nearest_clamp_sampler,
-                                ),
+                                (point_shadow_map, &renderer_resources.nearest_clamp_sampler),
                             ),
                             binding("properties", &properties),
                         ]);
@@ -978,7 +1293,11 @@ impl DeferredLightRenderer {
                             property("shadowsEnabled", &shadows_enabled),
                             property("shadowBias", &shadow_bias),
                             property("softShadows", &settings.csm_settings.pcf),
+                            property("pcss", &pcss),
+                            property("lightSize", &light.light_size),
                             property("cascadeDistances", distances.as_slice()),
+                            property("visualizeCascades", &visualize_csm_cascades),
+                            property("cascadeTints", CASCADE_DEBUG_TINTS.as_slice()),
                         ]);
                         let cascades = self.csm_renderer.cascades();
                         let material = RenderMaterial::from([
@@ -1043,6 +1362,66 @@ impl DeferredLightRenderer {
                             None,
                         )?
                     }
+                    LightSourceKind::Area { shape, .. } => {
+                        light_stats.area_lights_rendered += 1;
+
+                        let (light_side, light_look) =
+                            shape.scaled_axes(light.side_vector, light.look_vector);
+                        let is_disc = matches!(shape, AreaLightShape::Disc { .. });
+                        let properties = PropertyGroup::from([
+                            property("worldViewProjection", &frame_matrix),
+                            property("invViewProj", &inv_view_projection),
+                            property("lightPos", &light.position),
+                            property("lightSide", &light_side),
+                            property("lightLook", &light_look),
+                            property("lightDirection", &emit_direction),
+                            property("lightColor", &color),
+                            property("cameraPosition", &observer.position.translation),
+                            property("lightRadius", &light_radius),
+                            property("lightIntensity", &light.intensity),
+                            property("isDisc", &is_disc),
+                        ]);
+                        let material = RenderMaterial::from([
+                            binding(
+                                "depthTexture",
+                                (gbuffer_depth_map, &renderer_resources.nearest_clamp_sampler),
+                            ),
+                            binding(
+                                "colorTexture",
+                                (
+                                    gbuffer_diffuse_map,
+                                    &renderer_resources.nearest_clamp_sampler,
+                                ),
+                            ),
+                            binding(
+                                "normalTexture",
+                                (
+                                    gbuffer_normal_map,
+                                    &renderer_resources.nearest_clamp_sampler,
+                                ),
+                            ),
+                            binding(
+                                "materialTexture",
+                                (
+                                    gbuffer_material_map,
+                                    &renderer_resources.nearest_clamp_sampler,
+                                ),
+                            ),
+                            binding("properties", &properties),
+                        ]);
+
+                        renderer_resources.shaders.area_light.run_pass(
+                            1,
+                            &ImmutableString::new("Primary"),
+                            frame_buffer,
+                            quad,
+                            viewport,
+                            &material,
+                            uniform_buffer_cache,
+                            Default::default(),
+                            None,
+                        )?
+                    }
                     LightSourceKind::Unknown => Default::default(),
                 };
             }
@@ -1066,6 +1445,330 @@ impl DeferredLightRenderer {
             }
         }
 
+        let fog = scene
+            .rendering_options
+            .fog
+            .shader_parameters(settings.height_fog_enabled);
+        if fog.enabled {
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &frame_matrix),
+                property("invViewProj", &inv_view_projection),
+                property("viewMatrix", &observer.position.view_matrix),
+                property("fogColor", &fog.color),
+                property("fogMode", &fog.mode),
+                property("fogStart", &fog.start),
+                property("fogEnd", &fog.end),
+                property("fogDensity", &fog.density),
+                property("cameraPosition", &observer.position.translation),
+                property("heightFogEnabled", &fog.height_fog_enabled),
+                property("heightFogBaseHeight", &fog.height_fog_base_height),
+                property("heightFogFalloff", &fog.height_fog_falloff),
+                property("heightFogDensity", &fog.height_fog_density),
+            ]);
+            let material = RenderMaterial::from([
+                binding(
+                    "depthTexture",
+                    (gbuffer_depth_map, &renderer_resources.nearest_clamp_sampler),
+                ),
+                binding("properties", &properties),
+            ]);
+            pass_stats += renderer_resources.shaders.fog.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                frame_buffer,
+                &renderer_resources.quad,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                Default::default(),
+                None,
+            )?;
+        }
+
         Ok((pass_stats, light_stats))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        graphics::gpu_program::SamplerKind,
+        material::shader::{Shader, ShaderResource, ShaderResourceExtension, ShaderResourceKind},
+        renderer::{
+            bundle::{
+                LightData, LightSourceKind, RenderDataBundleStorage, RenderDataBundleStorageOptions,
+            },
+            light::{is_pcss_enabled, is_skybox_lighting},
+            observer::ObserverPosition,
+            resources::directional_light_shader_source,
+            QualitySettings,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            light::{
+                area::{area_light_polygon, AreaLight, AreaLightBuilder, AreaLightShape},
+                directional::DirectionalLightBuilder,
+                point::PointLightBuilder,
+                spot::SpotLightBuilder,
+                BaseLightBuilder,
+            },
+            transform::TransformBuilder,
+            EnvironmentLightingSource,
+        },
+    };
+    use half::f16;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_skybox_lighting_falls_back_to_ambient_color() {
+        assert!(is_skybox_lighting(EnvironmentLightingSource::SkyBox, true));
+        // No environment map - constant ambient color is used.
+        assert!(!is_skybox_lighting(
+            EnvironmentLightingSource::SkyBox,
+            false
+        ));
+        assert!(!is_skybox_lighting(
+            EnvironmentLightingSource::AmbientColor,
+            true
+        ));
+        assert!(!is_skybox_lighting(
+            EnvironmentLightingSource::AmbientColor,
+            false
+        ));
+    }
+
+    #[test]
+    fn test_skybox_image_based_lighting() {
+        let shader = Shader::from_string(include_str!("shaders/ambient_light.shader")).unwrap();
+
+        // Prefiltered specular and irradiance maps of the environment are bound as cube maps.
+        for name in ["prefilteredSpecularMap", "irradianceMap"] {
+            let resource = shader
+                .definition
+                .resources
+                .iter()
+                .find(|resource| resource.name.as_str() == name)
+                .unwrap();
+            assert!(matches!(
+                resource.kind,
+                ShaderResourceKind::Texture {
+                    kind: SamplerKind::SamplerCube,
+                    ..
+                }
+            ));
+        }
+
+        let fragment_shader = &shader.definition.passes[0].fragment_shader.0;
+        assert!(fragment_shader.contains(
+            "properties.skyboxLighting ? S_SRGBToLinear(textureLod(prefilteredSpecularMap, reflectionVector, mip)).rgb"
+        ));
+        assert!(fragment_shader.contains("properties.skyboxLighting ? irradiance"));
+
+        // Metallic surfaces have no diffuse term, the specular term is added regardless of the
+        // metalness.
+        assert!(fragment_shader.contains("vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);"));
+        assert!(
+            fragment_shader.contains("vec3 specular = reflection * (F * envBRDF.x + envBRDF.y);")
+        );
+        assert!(fragment_shader
+            .contains("FragColor.rgb = (kD * diffuse + specular) * ambientOcclusion;"));
+
+        // Fresnel reflectance of a metal is its albedo, take a dark metal. The scale and bias
+        // from the BRDF lookup table must give a non-zero reflection for every view angle and
+        // roughness, otherwise metals would be black when lit by the sky box.
+        let f0 = 0.3;
+        let lut = include_bytes!("brdf_256x256_256samples.bin");
+        assert_eq!(lut.len(), 256 * 256 * 4);
+        for texel in lut.chunks_exact(4) {
+            let scale = f16::from_le_bytes([texel[0], texel[1]]).to_f32();
+            let bias = f16::from_le_bytes([texel[2], texel[3]]).to_f32();
+            assert!(f0 * scale + bias > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_pcss_uniform() {
+        let mut graph = Graph::new();
+        let base_light = |light_size: f32| {
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, -5.0))
+                        .build(),
+                ),
+            )
+            .with_light_size(light_size)
+        };
+        for light_size in [0.0, 0.5] {
+            SpotLightBuilder::new(base_light(light_size)).build(&mut graph);
+            PointLightBuilder::new(base_light(light_size)).build(&mut graph);
+            DirectionalLightBuilder::new(base_light(light_size)).build(&mut graph);
+        }
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let storage = RenderDataBundleStorage::from_graph(
+            &graph,
+            Default::default(),
+            0.0,
+            &ObserverPosition::default(),
+            Default::default(),
+            RenderDataBundleStorageOptions {
+                collect_lights: true,
+            },
+            &mut Default::default(),
+        );
+        assert_eq!(storage.light_sources.len(), 6);
+
+        let mut settings = QualitySettings::ultra();
+        settings.use_pcss = true;
+        settings.spot_soft_shadows = true;
+        settings.point_soft_shadows = true;
+        settings.csm_settings.pcf = true;
+        for light in storage.light_sources.iter() {
+            // Lights without size cast regular soft shadows.
+            assert_eq!(is_pcss_enabled(&settings, light), light.light_size > 0.0);
+
+            let mut settings = settings;
+            settings.use_pcss = false;
+            assert!(!is_pcss_enabled(&settings, light));
+
+            // PCSS requires soft shadows of the light kind.
+            let mut settings = settings;
+            settings.use_pcss = true;
+            match light.kind {
+                LightSourceKind::Spot { .. } => settings.spot_soft_shadows = false,
+                LightSourceKind::Point { .. } => settings.point_soft_shadows = false,
+                LightSourceKind::Directional { .. } => settings.csm_settings.pcf = false,
+                LightSourceKind::Area { .. } | LightSourceKind::Unknown => continue,
+            }
+            assert!(!is_pcss_enabled(&settings, light));
+        }
+
+        // The flag is consumed by the deferred light shaders.
+        for source in [
+            Cow::Borrowed(include_str!("shaders/deferred_spot_light.shader")),
+            Cow::Borrowed(include_str!("shaders/deferred_point_light.shader")),
+            directional_light_shader_source(),
+        ] {
+            let shader = Shader::from_string(&source).unwrap();
+            let properties = shader
+                .definition
+                .resources
+                .iter()
+                .find_map(|resource| match resource.kind {
+                    ShaderResourceKind::PropertyGroup(ref properties)
+                        if resource.name.as_str() == "properties" =>
+                    {
+                        Some(properties)
+                    }
+                    _ => None,
+                })
+                .unwrap();
+            for name in ["pcss", "lightSize"] {
+                assert!(properties
+                    .iter()
+                    .any(|property| property.name.as_str() == name));
+            }
+            assert!(shader.definition.passes[0]
+                .fragment_shader
+                .0
+                .contains("properties.pcss"));
+        }
+    }
+
+    #[test]
+    fn test_area_light_data() {
+        let mut graph = Graph::new();
+        let base_light = || {
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, -5.0))
+                        .with_local_scale(Vector3::new(2.0, 1.0, 3.0))
+                        .build(),
+                ),
+            )
+        };
+        let rect = AreaLightBuilder::new(base_light())
+            .with_shape(AreaLightShape::Rect {
+                width: 1.0,
+                height: 0.5,
+            })
+            .with_distance(7.0)
+            .build(&mut graph);
+        let disc = AreaLightBuilder::new(base_light())
+            .with_shape(AreaLightShape::Disc { radius: 0.5 })
+            .with_distance(7.0)
+            .build(&mut graph);
+        PointLightBuilder::new(base_light()).build(&mut graph);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let storage = RenderDataBundleStorage::from_graph(
+            &graph,
+            Default::default(),
+            0.0,
+            &ObserverPosition::default(),
+            Default::default(),
+            RenderDataBundleStorageOptions {
+                collect_lights: true,
+            },
+            &mut Default::default(),
+        );
+        assert_eq!(storage.light_sources.len(), 3);
+
+        // Forward passes get the same emitting polygon as the deferred renderer and the debug
+        // drawing of the light.
+        let light_data = LightData::<16>::new(&storage.light_sources);
+        assert_eq!(light_data.count, 3);
+        for (i, light) in storage.light_sources.iter().enumerate() {
+            let LightSourceKind::Area { shape, distance } = light.kind else {
+                assert_eq!(light_data.area_side[i], Vector3::default());
+                assert_eq!(light_data.area_look[i], Vector3::default());
+                continue;
+            };
+
+            let node = graph[light.handle].cast::<AreaLight>().unwrap();
+            assert_eq!(
+                area_light_polygon(&shape, light.position, light.side_vector, light.look_vector),
+                node.world_polygon()
+            );
+            assert_eq!(
+                (light_data.area_side[i], light_data.area_look[i]),
+                shape.scaled_axes(light.side_vector, light.look_vector)
+            );
+            assert!(light_data.area_side[i].norm() > 0.0);
+            assert_eq!(light_data.color_radius[i].w, distance);
+            // No cone attenuation.
+            assert_eq!(light_data.parameters[i].y, -1.0);
+            let is_disc = light.handle == disc.transmute();
+            assert_eq!(light_data.parameters[i].x, if is_disc { 1.0 } else { 0.0 });
+            assert!(is_disc || light.handle == rect.transmute());
+        }
+
+        // Both renderers shade the polygon with the same shared shader functions.
+        let shader =
+            Shader::from_string(include_str!("shaders/deferred_area_light.shader")).unwrap();
+        assert!(shader.definition.resources.iter().any(|resource| {
+            matches!(resource.kind, ShaderResourceKind::PropertyGroup(ref properties)
+                if ["lightSide", "lightLook", "isDisc", "lightRadius", "lightIntensity"]
+                    .iter()
+                    .all(|name| properties.iter().any(|p| p.name.as_str() == *name)))
+        }));
+        let fragment_shader = &shader.definition.passes[0].fragment_shader.0;
+        assert!(fragment_shader.contains("S_AreaLightPolygon"));
+        assert!(fragment_shader.contains("S_AreaLight("));
+
+        let standard = ShaderResource::standard();
+        let standard = standard.data_ref();
+        let forward = standard
+            .definition
+            .passes
+            .iter()
+            .find(|pass| pass.name == "Forward")
+            .unwrap();
+        assert!(forward.fragment_shader.0.contains("lightsAreaSide[i]"));
+        assert!(forward.fragment_shader.0.contains("lightsAreaLook[i]"));
+    }
+}
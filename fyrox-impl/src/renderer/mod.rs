@@ -28,11 +28,14 @@ pub mod framework;
 
 pub mod bundle;
 pub mod cache;
+pub mod compositor;
 pub mod debug_renderer;
 pub mod observer;
 pub mod resources;
+pub mod sprite_layer;
 pub mod stats;
 pub mod storage;
+pub mod text;
 pub mod ui_renderer;
 pub mod utils;
 pub mod visibility;
@@ -45,6 +48,7 @@ mod hdr;
 mod light;
 mod light_volume;
 mod occlusion;
+mod oit;
 mod settings;
 mod shadow;
 mod ssao;
@@ -71,7 +75,10 @@ use crate::{
     },
     material::shader::Shader,
     renderer::{
-        bundle::{BundleRenderContext, RenderDataBundleStorage, RenderDataBundleStorageOptions},
+        bundle::{
+            BundleRenderContext, RenderDataBundle, RenderDataBundleStorage,
+            RenderDataBundleStorageOptions, TransparencyStage,
+        },
         cache::texture::convert_pixel_kind,
         cache::{
             geometry::GeometryCache,
@@ -81,19 +88,25 @@ use crate::{
             texture::TextureCache,
             uniform::{UniformBufferCache, UniformMemoryAllocator},
         },
+        compositor::Compositor,
         convolution::{EnvironmentMapIrradianceConvolution, EnvironmentMapSpecularConvolution},
         debug_renderer::DebugRenderer,
         fxaa::FxaaRenderer,
         gbuffer::{GBuffer, GBufferRenderContext},
         hdr::HighDynamicRangeRenderer,
-        light::{DeferredLightRenderer, DeferredRendererContext},
+        light::{DeferredLightRenderer, DeferredRendererContext, ForwardShadowsContext},
+        oit::WeightedBlendedOitRenderer,
+        sprite_layer::{SpriteLayer, SpriteLayerRenderer},
         ssao::ScreenSpaceAmbientOcclusionRenderer,
         ui_renderer::UiRenderInfo,
         ui_renderer::{UiRenderContext, UiRenderer},
         visibility::VisibilityCache,
     },
     resource::texture::{Texture, TextureKind, TextureResource},
-    scene::{mesh::RenderPath, node::Node, Scene, SceneContainer},
+    scene::{
+        decal::Decal, graph::Graph, mesh::RenderPath, node::Node, Scene, SceneContainer,
+        TransparencyMode,
+    },
 };
 use cache::DynamicSurfaceCache;
 use fxhash::FxHashMap;
@@ -137,6 +150,9 @@ pub struct SceneRenderData {
     pub camera_data: FxHashMap<Handle<Node>, RenderDataContainer>,
     /// Scene-specific render data.
     pub scene_data: RenderDataContainer,
+    /// Whether the user was warned that the decals of the scene are not rendered by
+    /// [`RenderingPath::Forward`].
+    forward_decals_warned: bool,
 }
 
 impl SceneRenderData {
@@ -149,6 +165,7 @@ impl SceneRenderData {
         Ok(Self {
             camera_data: Default::default(),
             scene_data: RenderDataContainer::new(server, frame_size, final_frame_texture)?,
+            forward_decals_warned: false,
         })
     }
 
@@ -199,6 +216,9 @@ pub struct RenderDataContainer {
     /// for environment maps.
     pub need_recalculate_convolution: bool,
 
+    /// Key of the environment map resource, that was used to calculate the convolution.
+    pub environment_map_key: Option<u64>,
+
     /// Screen space ambient occlusion renderer.
     pub ssao_renderer: ScreenSpaceAmbientOcclusionRenderer,
 
@@ -218,6 +238,10 @@ pub struct RenderDataContainer {
     /// scene luminance.
     pub hdr_renderer: HighDynamicRangeRenderer,
 
+    /// Order-independent transparency renderer. It is created on demand, when a scene uses
+    /// [`crate::scene::TransparencyMode::WeightedBlended`].
+    pub oit_renderer: Option<WeightedBlendedOitRenderer>,
+
     /// Rendering statistics for a container.
     pub statistics: SceneStatistics,
 }
@@ -303,6 +327,7 @@ impl RenderDataContainer {
 
         Ok(Self {
             need_recalculate_convolution: true,
+            environment_map_key: None,
             environment_map_specular_convolution: Default::default(),
             environment_map_irradiance_convolution: EnvironmentMapIrradianceConvolution::new(
                 server, 32,
@@ -316,6 +341,7 @@ impl RenderDataContainer {
                 make_ldr_temp_frame_buffer(server, width, height, depth_stencil.clone())?,
                 make_ldr_temp_frame_buffer(server, width, height, depth_stencil.clone())?,
             ],
+            oit_renderer: None,
             statistics: Default::default(),
         })
     }
@@ -337,6 +363,30 @@ impl RenderDataContainer {
         );
     }
 
+    /// Replaces the depth and stencil of the G-Buffer with the ones of the scene frame buffer and
+    /// clears the rest of the G-Buffer. Forward rendering path does not fill the G-Buffer, so
+    /// this keeps custom render passes from reading data left by the deferred path.
+    fn copy_depth_stencil_to_gbuffer(&self) {
+        let viewport = Rect::new(0, 0, self.gbuffer.width, self.gbuffer.height);
+        self.gbuffer
+            .framebuffer()
+            .clear(viewport, Some(Color::from_rgba(0, 0, 0, 0)), None, None);
+        self.hdr_scene_framebuffer.blit_to(
+            self.gbuffer.framebuffer(),
+            0,
+            0,
+            self.gbuffer.width,
+            self.gbuffer.height,
+            0,
+            0,
+            self.gbuffer.width,
+            self.gbuffer.height,
+            false,
+            true,
+            true,
+        );
+    }
+
     /// Returns high-dynamic range frame buffer texture.
     pub fn hdr_scene_frame_texture(&self) -> &GpuTexture {
         &self.hdr_scene_framebuffer.color_attachments()[0].texture
@@ -374,6 +424,53 @@ pub fn make_viewport_matrix(viewport: Rect<i32>) -> Matrix4<f32> {
     ))
 }
 
+/// Defines which passes and render targets are used to render a scene with a rendering path.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct RenderingPathTargets {
+    /// Whether the geometry is rendered into the G-Buffer and lit in screen space.
+    fill_gbuffer: bool,
+    /// Whether forward passes can sample depth of the G-Buffer (soft particles, etc.).
+    gbuffer_depth_in_forward_pass: bool,
+    /// Whether depth of the scene frame buffer must be copied to the G-Buffer after forward
+    /// geometry is drawn.
+    copy_depth_to_gbuffer: bool,
+}
+
+impl RenderingPathTargets {
+    fn new(rendering_path: RenderingPath) -> Self {
+        let deferred = rendering_path == RenderingPath::Deferred;
+        Self {
+            fill_gbuffer: deferred,
+            gbuffer_depth_in_forward_pass: deferred,
+            copy_depth_to_gbuffer: !deferred,
+        }
+    }
+
+    /// Checks whether the given bundle must be drawn by the forward pass.
+    fn is_drawn_by_forward_pass(&self, bundle: &RenderDataBundle) -> bool {
+        !self.fill_gbuffer || bundle.render_path == RenderPath::Forward
+    }
+}
+
+/// Disables the features that are not supported by the rendering path of the given settings.
+fn restrict_quality_settings(settings: &QualitySettings) -> QualitySettings {
+    let mut settings = *settings;
+    for feature in settings.disable_unsupported_features() {
+        Log::warn(format!(
+            "{feature} is not supported by {:?} rendering path and was disabled!",
+            settings.rendering_path
+        ));
+    }
+    settings
+}
+
+/// Checks whether the given graph has at least one decal.
+fn has_decals(graph: &Graph) -> bool {
+    graph
+        .linear_iter()
+        .any(|node| node.cast::<Decal>().is_some())
+}
+
 /// See module docs.
 pub struct Renderer {
     backbuffer: GpuFrameBuffer,
@@ -387,6 +484,8 @@ pub struct Renderer {
     statistics: Statistics,
     frame_size: (u32, u32),
     quality_settings: QualitySettings,
+    #[cfg(feature = "render_debug")]
+    debug_features: RenderDebugFeatures,
     /// Debug renderer instance can be used for debugging purposes
     pub debug_renderer: DebugRenderer,
     /// Screen space debug renderer instance can be used for debugging purposes to draw lines directly
@@ -414,6 +513,9 @@ pub struct Renderer {
     pub visibility_cache: VisibilityCache,
     /// Graphics server.
     pub server: SharedGraphicsServer,
+    compositor: Compositor,
+    sprite_layer: SpriteLayer,
+    sprite_layer_renderer: SpriteLayerRenderer,
 }
 
 fn make_ui_frame_buffer(
@@ -489,7 +591,8 @@ pub struct SceneRenderPassContext<'a, 'b> {
     /// your own texture of this kind.
     pub renderer_resources: &'a RendererResources,
 
-    /// A texture with depth values from G-Buffer.
+    /// A texture with depth values from G-Buffer. With [`RenderingPath::Forward`] it contains
+    /// a copy of the depth of the forward geometry.
     ///
     /// # Important notes
     ///
@@ -498,7 +601,8 @@ pub struct SceneRenderPassContext<'a, 'b> {
     /// the frame buffer as you'd normally do.
     pub depth_texture: &'a GpuTexture,
 
-    /// A texture with world-space normals from G-Buffer.
+    /// A texture with world-space normals from G-Buffer. It is cleared with zeros when
+    /// [`RenderingPath::Forward`] is used.
     ///
     /// # Important notes
     ///
@@ -506,7 +610,8 @@ pub struct SceneRenderPassContext<'a, 'b> {
     /// have an ability to write to this texture.
     pub normal_texture: &'a GpuTexture,
 
-    /// A texture with ambient lighting values from G-Buffer.
+    /// A texture with ambient lighting values from G-Buffer. It is cleared with zeros when
+    /// [`RenderingPath::Forward`] is used.
     ///
     /// # Important notes
     ///
@@ -655,10 +760,15 @@ impl Renderer {
             renderer_resources: RendererResources::new(&*server)?,
             ui_renderer: UiRenderer::new(&*server)?,
             quality_settings: settings,
+            #[cfg(feature = "render_debug")]
+            debug_features: Default::default(),
             debug_renderer: DebugRenderer::new(&*server)?,
             screen_space_debug_renderer: DebugRenderer::new(&*server)?,
             scene_data_map: Default::default(),
             backbuffer_clear_color: Color::BLACK,
+            compositor: Default::default(),
+            sprite_layer: Default::default(),
+            sprite_layer_renderer: SpriteLayerRenderer::new(&*server)?,
             texture_cache: Default::default(),
             geometry_cache: Default::default(),
             ui_frame_buffers: Default::default(),
@@ -756,12 +866,13 @@ impl Renderer {
         &mut self,
         settings: &QualitySettings,
     ) -> Result<(), FrameworkError> {
-        self.quality_settings = *settings;
+        let settings = restrict_quality_settings(settings);
+        self.quality_settings = settings;
         for data in self.scene_data_map.values_mut() {
-            data.set_quality_settings(settings);
+            data.set_quality_settings(&settings);
         }
         self.deferred_light_renderer
-            .set_quality_settings(&*self.server, settings)
+            .set_quality_settings(&*self.server, &settings)
     }
 
     /// Returns current quality settings.
@@ -769,6 +880,56 @@ impl Renderer {
         self.quality_settings
     }
 
+    /// Returns a reference to the compositor. See [`Compositor`] docs for more info.
+    pub fn compositor(&self) -> &Compositor {
+        &self.compositor
+    }
+
+    /// Returns a reference to the compositor. Could be used to add or remove layers. See
+    /// [`Compositor`] docs for more info.
+    pub fn compositor_mut(&mut self) -> &mut Compositor {
+        &mut self.compositor
+    }
+
+    /// Returns a reference to the screen-space sprite layer. See [`SpriteLayer`] docs for more info.
+    pub fn sprite_layer(&self) -> &SpriteLayer {
+        &self.sprite_layer
+    }
+
+    /// Returns a reference to the screen-space sprite layer. Could be used to draw sprites. See
+    /// [`SpriteLayer`] docs for more info.
+    pub fn sprite_layer_mut(&mut self) -> &mut SpriteLayer {
+        &mut self.sprite_layer
+    }
+
+    /// Returns the rendering path that is currently used to render scenes.
+    pub fn rendering_path(&self) -> RenderingPath {
+        self.quality_settings.rendering_path
+    }
+
+    /// Sets new set of debug visualization features. See [`RenderDebugFeatures`] docs for more info.
+    #[cfg(feature = "render_debug")]
+    pub fn set_debug_features(&mut self, features: RenderDebugFeatures) {
+        self.debug_features = features;
+    }
+
+    /// Returns current set of debug visualization features.
+    #[cfg(feature = "render_debug")]
+    pub fn debug_features(&self) -> RenderDebugFeatures {
+        self.debug_features
+    }
+
+    fn visualize_csm_cascades(&self) -> bool {
+        #[cfg(feature = "render_debug")]
+        {
+            self.debug_features.csm_cascades
+        }
+        #[cfg(not(feature = "render_debug"))]
+        {
+            false
+        }
+    }
+
     /// Removes all cached GPU data, forces renderer to re-upload data to GPU.
     /// Do not call this method until you absolutely need! It may cause **significant**
     /// performance lag!
@@ -937,12 +1098,20 @@ impl Renderer {
         need_recalculate_convolution: bool,
     ) -> Result<&mut RenderDataContainer, FrameworkError> {
         let server = &*self.server;
+        let visualize_csm_cascades = self.visualize_csm_cascades();
 
         let scene_render_data = self.scene_data_map.get_mut(&scene_handle).ok_or_else(|| {
             FrameworkError::Custom(format!(
                 "No associated render data for {scene_handle} scene!"
             ))
         })?;
+        if self.quality_settings.rendering_path == RenderingPath::Forward
+            && !scene_render_data.forward_decals_warned
+            && has_decals(&scene.graph)
+        {
+            Log::warn("Decals are not supported by Forward rendering path and won't be rendered!");
+            scene_render_data.forward_decals_warned = true;
+        }
         let render_data = if let Some(render_target) = observer.render_target.as_ref() {
             let (rt_size, final_frame_texture) = render_target_size(render_target)?;
             let observer_render_data = match scene_render_data.camera_data.entry(observer.handle) {
@@ -1016,79 +1185,156 @@ impl Renderer {
             scene.rendering_options.polygon_rasterization_mode,
         );
 
-        render_data.statistics += render_data.gbuffer.fill(GBufferRenderContext {
-            server,
-            observer,
-            geom_cache: &mut self.geometry_cache,
-            bundle_storage: &bundle_storage,
-            texture_cache: &mut self.texture_cache,
-            shader_cache: &mut self.shader_cache,
-            quality_settings: &self.quality_settings,
-            renderer_resources: &self.renderer_resources,
-            graph: &scene.graph,
-            uniform_buffer_cache: &mut self.uniform_buffer_cache,
-            uniform_memory_allocator: &mut self.uniform_memory_allocator,
-            screen_space_debug_renderer: &mut self.screen_space_debug_renderer,
-            resource_manager,
-        })?;
-
-        server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+        let targets = RenderingPathTargets::new(self.quality_settings.rendering_path);
 
-        render_data.copy_depth_stencil_to_scene_framebuffer();
+        let mut forward_shadow_light = None;
+        if !targets.fill_gbuffer {
+            server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
-        render_data.hdr_scene_framebuffer.clear(
-            observer.viewport,
-            Some(
-                scene
-                    .rendering_options
-                    .clear_color
-                    .unwrap_or(self.backbuffer_clear_color),
-            ),
-            None, // Keep depth, we've just copied valid data in it.
-            Some(0),
-        );
-
-        let (pass_stats, light_stats) =
-            self.deferred_light_renderer
-                .render(DeferredRendererContext {
+            // The surfaces are lit by their forward passes, which can sample only the shadow maps
+            // of a single directional light.
+            let (shadow_stats, shadow_light) = self
+                .deferred_light_renderer
+                .render_forward_shadow_maps(ForwardShadowsContext {
                     elapsed_time,
                     server,
                     scene,
                     observer,
-                    gbuffer: &mut render_data.gbuffer,
-                    ambient_color: match observer.reflection_probe_data.as_ref() {
-                        None => scene.rendering_options.ambient_lighting_color,
-                        Some(probe_data) => probe_data.ambient_lighting_color,
-                    },
-                    environment_lighting_source: match observer.reflection_probe_data.as_ref() {
-                        None => scene.rendering_options.environment_lighting_source,
-                        Some(probe_data) => probe_data.environment_lighting_source,
-                    },
+                    frame_size: Vector2::new(
+                        render_data.gbuffer.width as f32,
+                        render_data.gbuffer.height as f32,
+                    ),
                     render_data_bundle: &bundle_storage,
                     settings: &self.quality_settings,
                     textures: &mut self.texture_cache,
                     geometry_cache: &mut self.geometry_cache,
-                    frame_buffer: &render_data.hdr_scene_framebuffer,
                     shader_cache: &mut self.shader_cache,
                     renderer_resources: &self.renderer_resources,
-                    uniform_buffer_cache: &mut self.uniform_buffer_cache,
-                    visibility_cache,
                     uniform_memory_allocator: &mut self.uniform_memory_allocator,
                     dynamic_surface_cache: &mut self.dynamic_surface_cache,
-                    ssao_renderer: &render_data.ssao_renderer,
                     resource_manager,
-                    environment_map_specular_convolution: &mut render_data
-                        .environment_map_specular_convolution,
-                    environment_map_irradiance_convolution: &render_data
-                        .environment_map_irradiance_convolution,
-                    need_recalculate_convolution: &mut render_data.need_recalculate_convolution,
                 })?;
+            render_data.statistics += shadow_stats;
+            forward_shadow_light = shadow_light;
+
+            // There's no G-Buffer to take depth from, so clear everything and draw the skybox
+            // first, the rest of the geometry will be drawn on top of it.
+            render_data.hdr_scene_framebuffer.clear(
+                observer.viewport,
+                Some(
+                    scene
+                        .rendering_options
+                        .clear_color
+                        .unwrap_or(self.backbuffer_clear_color),
+                ),
+                Some(1.0),
+                Some(0),
+            );
+
+            render_data.statistics += self.deferred_light_renderer.render_skybox(
+                server,
+                scene,
+                observer,
+                &mut self.texture_cache,
+                resource_manager,
+                &render_data.hdr_scene_framebuffer,
+                observer.viewport,
+                &mut self.uniform_buffer_cache,
+                &self.renderer_resources,
+            )?;
 
-        render_data.statistics += light_stats;
-        render_data.statistics += pass_stats;
+            server.set_polygon_fill_mode(
+                PolygonFace::FrontAndBack,
+                scene.rendering_options.polygon_rasterization_mode,
+            );
+        } else {
+            render_data.statistics += render_data.gbuffer.fill(GBufferRenderContext {
+                server,
+                observer,
+                geom_cache: &mut self.geometry_cache,
+                bundle_storage: &bundle_storage,
+                texture_cache: &mut self.texture_cache,
+                shader_cache: &mut self.shader_cache,
+                quality_settings: &self.quality_settings,
+                renderer_resources: &self.renderer_resources,
+                graph: &scene.graph,
+                uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                screen_space_debug_renderer: &mut self.screen_space_debug_renderer,
+                resource_manager,
+            })?;
+
+            server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+
+            render_data.copy_depth_stencil_to_scene_framebuffer();
+
+            render_data.hdr_scene_framebuffer.clear(
+                observer.viewport,
+                Some(
+                    scene
+                        .rendering_options
+                        .clear_color
+                        .unwrap_or(self.backbuffer_clear_color),
+                ),
+                None, // Keep depth, we've just copied valid data in it.
+                Some(0),
+            );
+
+            let (pass_stats, light_stats) =
+                self.deferred_light_renderer
+                    .render(DeferredRendererContext {
+                        elapsed_time,
+                        server,
+                        scene,
+                        scene_handle,
+                        observer,
+                        gbuffer: &mut render_data.gbuffer,
+                        ambient_color: match observer.reflection_probe_data.as_ref() {
+                            None => scene.rendering_options.ambient_lighting_color,
+                            Some(probe_data) => probe_data.ambient_lighting_color,
+                        },
+                        environment_lighting_source: match observer.reflection_probe_data.as_ref() {
+                            None => scene.rendering_options.environment_lighting_source,
+                            Some(probe_data) => probe_data.environment_lighting_source,
+                        },
+                        render_data_bundle: &bundle_storage,
+                        settings: &self.quality_settings,
+                        textures: &mut self.texture_cache,
+                        geometry_cache: &mut self.geometry_cache,
+                        frame_buffer: &render_data.hdr_scene_framebuffer,
+                        shader_cache: &mut self.shader_cache,
+                        renderer_resources: &self.renderer_resources,
+                        uniform_buffer_cache: &mut self.uniform_buffer_cache,
+                        visibility_cache,
+                        uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                        dynamic_surface_cache: &mut self.dynamic_surface_cache,
+                        ssao_renderer: &render_data.ssao_renderer,
+                        resource_manager,
+                        environment_map_specular_convolution: &mut render_data
+                            .environment_map_specular_convolution,
+                        environment_map_irradiance_convolution: &render_data
+                            .environment_map_irradiance_convolution,
+                        need_recalculate_convolution: &mut render_data.need_recalculate_convolution,
+                        environment_map_key: &mut render_data.environment_map_key,
+                        visualize_csm_cascades,
+                    })?;
+
+            render_data.statistics += light_stats;
+            render_data.statistics += pass_stats;
+        }
 
         let depth = render_data.gbuffer.depth();
 
+        let forward_shadows = forward_shadow_light.map(|light_index| {
+            self.deferred_light_renderer.forward_shadows(
+                light_index,
+                &bundle_storage,
+                &self.quality_settings,
+            )
+        });
+
+        let oit = scene.rendering_options.transparency_mode == TransparencyMode::WeightedBlended;
+
         {
             let _debug_scope = server.begin_scope("ForwardRendering");
 
@@ -1096,7 +1342,7 @@ impl Renderer {
                 server,
                 &mut self.geometry_cache,
                 &mut self.shader_cache,
-                |bundle| bundle.render_path == RenderPath::Forward,
+                |bundle| targets.is_drawn_by_forward_pass(bundle),
                 |_| true,
                 BundleRenderContext {
                     texture_cache: &mut self.texture_cache,
@@ -1109,9 +1355,92 @@ impl Renderer {
                     light_position: &Default::default(),
                     renderer_resources: &self.renderer_resources,
                     ambient_light: scene.rendering_options.ambient_lighting_color,
-                    scene_depth: Some(depth),
+                    scene_depth: targets.gbuffer_depth_in_forward_pass.then_some(depth),
+                    fog: Some(&scene.rendering_options.fog),
+                    use_height_fog: self.quality_settings.height_fog_enabled,
+                    transparency_stage: if oit {
+                        TransparencyStage::Opaque
+                    } else {
+                        TransparencyStage::All
+                    },
+                    shadows: forward_shadows,
                 },
             )?;
+
+            server.set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
+        }
+
+        if targets.copy_depth_to_gbuffer {
+            render_data.copy_depth_stencil_to_gbuffer();
+        }
+
+        if oit {
+            let _debug_scope = server.begin_scope("OrderIndependentTransparency");
+
+            if render_data.oit_renderer.is_none() {
+                let depth_stencil = render_data
+                    .hdr_scene_framebuffer
+                    .depth_attachment()
+                    .ok_or_else(|| {
+                        FrameworkError::Custom("Scene frame buffer has no depth!".to_string())
+                    })?
+                    .texture
+                    .clone();
+                render_data.oit_renderer = Some(WeightedBlendedOitRenderer::new(
+                    server,
+                    depth_stencil,
+                    render_data.gbuffer.width as usize,
+                    render_data.gbuffer.height as usize,
+                )?);
+            }
+
+            if let Some(oit_renderer) = render_data.oit_renderer.as_ref() {
+                oit_renderer.clear(observer.viewport);
+
+                for (stage, frame_buffer) in [
+                    (
+                        TransparencyStage::Accumulation,
+                        oit_renderer.accumulation_frame_buffer(),
+                    ),
+                    (
+                        TransparencyStage::Revealage,
+                        oit_renderer.revealage_frame_buffer(),
+                    ),
+                ] {
+                    render_data.statistics += bundle_storage.render_to_frame_buffer(
+                        server,
+                        &mut self.geometry_cache,
+                        &mut self.shader_cache,
+                        |bundle| targets.is_drawn_by_forward_pass(bundle),
+                        |_| true,
+                        BundleRenderContext {
+                            texture_cache: &mut self.texture_cache,
+                            render_pass_name: &ImmutableString::new("Forward"),
+                            frame_buffer,
+                            viewport: observer.viewport,
+                            uniform_memory_allocator: &mut self.uniform_memory_allocator,
+                            resource_manager,
+                            use_pom: self.quality_settings.use_parallax_mapping,
+                            light_position: &Default::default(),
+                            renderer_resources: &self.renderer_resources,
+                            ambient_light: scene.rendering_options.ambient_lighting_color,
+                            scene_depth: targets.gbuffer_depth_in_forward_pass.then_some(depth),
+                            fog: Some(&scene.rendering_options.fog),
+                            use_height_fog: self.quality_settings.height_fog_enabled,
+                            transparency_stage: stage,
+                            shadows: forward_shadows,
+                        },
+                    )?;
+                }
+
+                render_data.statistics += oit_renderer.compose(
+                    server,
+                    observer.viewport,
+                    &render_data.hdr_scene_framebuffer,
+                    &mut self.uniform_buffer_cache,
+                    &self.renderer_resources,
+                )?;
+            }
         }
 
         for render_pass in self.scene_render_passes.iter() {
@@ -1320,6 +1649,15 @@ impl Renderer {
             .camera_data
             .retain(|h, _| graph.is_valid_handle(*h));
 
+        // Areas of the frame that are not covered by any camera (for example, letterbox bars of
+        // cameras with fixed aspect ratio) must be black.
+        scene_render_data.scene_data.ldr_scene_framebuffer.clear(
+            Rect::new(0, 0, frame_size.x as i32, frame_size.y as i32),
+            Some(Color::BLACK),
+            None,
+            None,
+        );
+
         let observers = ObserversCollection::from_scene(scene, frame_size);
 
         // At first, render the reflection probes to off-screen render target.
@@ -1407,6 +1745,7 @@ impl Renderer {
         self.server.invalidate_resource_bindings_cache();
         let dt = self.statistics.capped_frame_time;
         self.statistics.begin_frame();
+        self.deferred_light_renderer.begin_frame();
 
         let window_viewport = Rect::new(0, 0, self.frame_size.0 as i32, self.frame_size.1 as i32);
         self.backbuffer.clear(
@@ -1426,6 +1765,28 @@ impl Renderer {
         self.graphics_server()
             .set_polygon_fill_mode(PolygonFace::FrontAndBack, PolygonFillMode::Fill);
 
+        // Composite outputs of other cameras on top of the scenes.
+        self.statistics += self.compositor.render(
+            &*self.server,
+            &self.backbuffer,
+            Vector2::new(backbuffer_width, backbuffer_height),
+            &mut self.texture_cache,
+            resource_manager,
+            &mut self.uniform_buffer_cache,
+            &self.renderer_resources,
+        )?;
+
+        self.statistics += self.sprite_layer_renderer.render(
+            &*self.server,
+            &self.sprite_layer,
+            &self.backbuffer,
+            window_viewport,
+            &mut self.texture_cache,
+            resource_manager,
+            &mut self.uniform_buffer_cache,
+            &self.renderer_resources,
+        )?;
+
         // Render UI on top of everything without gamma correction.
         for info in ui_render_info {
             self.render_ui(info)?;
@@ -1467,3 +1828,206 @@ impl Renderer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2},
+            uuid::Uuid,
+        },
+        material::{
+            shader::{Shader, ShaderResource},
+            Material, MaterialResource,
+        },
+        renderer::{
+            bundle::{RenderDataBundleStorage, RenderDataBundleStorageOptions},
+            light::forward_shadow_caster,
+            observer::ObserverPosition,
+            restrict_quality_settings, QualitySettings, RenderingPath, RenderingPathTargets,
+            GBUFFER_PASS_NAME,
+        },
+        resource::untyped::ResourceKind,
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                MeshBuilder, RenderPath,
+            },
+        },
+    };
+
+    #[test]
+    fn test_rendering_path_targets() {
+        let data = SurfaceResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        );
+        let mut graph = Graph::new();
+        for render_path in [RenderPath::Deferred, RenderPath::Forward] {
+            MeshBuilder::new(BaseBuilder::new().with_frustum_culling(false))
+                .with_surfaces(vec![SurfaceBuilder::new(data.clone()).build()])
+                .with_render_path(render_path)
+                .build(&mut graph);
+        }
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let storage = RenderDataBundleStorage::from_graph(
+            &graph,
+            Default::default(),
+            0.0,
+            &ObserverPosition::default(),
+            GBUFFER_PASS_NAME.clone(),
+            RenderDataBundleStorageOptions {
+                collect_lights: false,
+            },
+            &mut Default::default(),
+        );
+        assert_eq!(storage.bundles.len(), 2);
+        let forward_pass_bundles = |targets: &RenderingPathTargets| {
+            storage
+                .bundles
+                .iter()
+                .filter(|bundle| targets.is_drawn_by_forward_pass(bundle))
+                .map(|bundle| bundle.render_path)
+                .collect::<Vec<_>>()
+        };
+
+        let mut settings = QualitySettings::ultra();
+
+        settings.rendering_path = RenderingPath::Deferred;
+        let deferred = restrict_quality_settings(&settings);
+        // `Renderer::rendering_path` returns the path of the restricted settings.
+        assert_eq!(deferred.rendering_path, RenderingPath::Deferred);
+        assert!(deferred.use_ssao);
+        let targets = RenderingPathTargets::new(deferred.rendering_path);
+        assert!(targets.fill_gbuffer);
+        assert!(targets.gbuffer_depth_in_forward_pass);
+        assert!(!targets.copy_depth_to_gbuffer);
+        assert_eq!(forward_pass_bundles(&targets), vec![RenderPath::Forward]);
+
+        settings.rendering_path = RenderingPath::Forward;
+        let forward = restrict_quality_settings(&settings);
+        assert_eq!(forward.rendering_path, RenderingPath::Forward);
+        assert!(!forward.use_ssao);
+        let targets = RenderingPathTargets::new(forward.rendering_path);
+        // The G-Buffer is neither filled nor sampled by the forward passes, it only receives depth
+        // of the forward geometry for custom render passes.
+        assert!(!targets.fill_gbuffer);
+        assert!(!targets.gbuffer_depth_in_forward_pass);
+        assert!(targets.copy_depth_to_gbuffer);
+        assert_eq!(forward_pass_bundles(&targets).len(), 2);
+    }
+
+    #[test]
+    fn test_forward_path_renders_gizmo_scene() {
+        let data = SurfaceResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            SurfaceData::make_cube(Matrix4::identity()),
+        );
+        // Gizmos are unlit and drawn by a forward pass of their own shader.
+        let gizmo_shader = Shader::from_string(
+            r#"(
+                name: "Gizmo",
+                resources: [],
+                passes: [
+                    (
+                        name: "Forward",
+                        vertex_shader: "<CODE>",
+                        fragment_shader: "<CODE>",
+                    ),
+                ],
+                disabled_passes: ["GBuffer", "DirectionalShadow", "PointShadow", "SpotShadow"],
+            )"#,
+        )
+        .unwrap();
+        let gizmo_material = MaterialResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            Material::from_shader(ShaderResource::new_ok(
+                Uuid::new_v4(),
+                ResourceKind::Embedded,
+                gizmo_shader,
+            )),
+        );
+
+        let mut graph = Graph::new();
+        MeshBuilder::new(BaseBuilder::new().with_frustum_culling(false))
+            .with_surfaces(vec![SurfaceBuilder::new(data.clone()).build()])
+            .build(&mut graph);
+        MeshBuilder::new(BaseBuilder::new().with_frustum_culling(false))
+            .with_surfaces(vec![SurfaceBuilder::new(data)
+                .with_material(gizmo_material.clone())
+                .build()])
+            .with_render_path(RenderPath::Forward)
+            .build(&mut graph);
+        DirectionalLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new())).build(&mut graph);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let storage = RenderDataBundleStorage::from_graph(
+            &graph,
+            Default::default(),
+            0.0,
+            &ObserverPosition::default(),
+            GBUFFER_PASS_NAME.clone(),
+            RenderDataBundleStorageOptions {
+                collect_lights: true,
+            },
+            &mut Default::default(),
+        );
+        assert_eq!(storage.bundles.len(), 2);
+        assert_eq!(storage.light_sources.len(), 1);
+
+        let settings = restrict_quality_settings(&QualitySettings {
+            rendering_path: RenderingPath::Forward,
+            ..QualitySettings::ultra()
+        });
+        let targets = RenderingPathTargets::new(settings.rendering_path);
+
+        for bundle in storage.bundles.iter() {
+            // Every bundle is dispatched to the forward pass and has it, otherwise it would be
+            // skipped with an error.
+            assert!(targets.is_drawn_by_forward_pass(bundle));
+            let material = bundle.material.data_ref();
+            let shader = material.shader().data_ref();
+            let definition = &shader.definition;
+            let forward = definition
+                .passes
+                .iter()
+                .find(|pass| pass.name == "Forward")
+                .unwrap();
+
+            if bundle.material == gizmo_material {
+                continue;
+            }
+
+            // The rest of the scene is lit by the forward pass.
+            for resource in [
+                "fyrox_lightsBlock",
+                "fyrox_shadowData",
+                "fyrox_shadowCascade0",
+            ] {
+                assert!(
+                    definition
+                        .resources
+                        .iter()
+                        .any(|definition| definition.name.as_str() == resource),
+                    "{} must have {resource}",
+                    definition.name
+                );
+            }
+            assert!(forward.fragment_shader.0.contains("S_ForwardLight"));
+            assert!(forward.fragment_shader.0.contains("S_CsmShadowFactor"));
+        }
+
+        // The directional light casts shadows on the forward geometry.
+        assert_eq!(forward_shadow_caster(&storage, &settings), Some(0));
+        let mut without_shadows = settings;
+        without_shadows.csm_settings.enabled = false;
+        assert_eq!(forward_shadow_caster(&storage, &without_shadows), None);
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Weighted blended order-independent transparency (OIT). Transparent surfaces are drawn twice in
+//! two separate render targets: the first one accumulates premultiplied color and alpha of every
+//! surface, the second one accumulates the product of `1 - alpha` of every surface (revealage).
+//! Both operations are commutative, so the result does not depend on the order of surfaces. The
+//! final color is then composed over the opaque geometry. See [`crate::scene::TransparencyMode`]
+//! docs for more info.
+
+use crate::{
+    core::{color::Color, math::Rect, sstorage::ImmutableString},
+    graphics::{
+        error::FrameworkError,
+        framebuffer::{Attachment, GpuFrameBuffer},
+        gpu_texture::{GpuTexture, PixelKind},
+        server::GraphicsServer,
+    },
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial},
+            uniform::UniformBufferCache,
+        },
+        make_viewport_matrix,
+        resources::RendererResources,
+        RenderPassStatistics,
+    },
+};
+
+// Accumulated color may exceed 1.0 when many surfaces overlap, so it must be stored in a floating
+// point texture.
+const ACCUMULATION_PIXEL_KIND: PixelKind = PixelKind::RGBA16F;
+const REVEALAGE_PIXEL_KIND: PixelKind = PixelKind::R8;
+// Clear colors are neutral elements of the blending operations of the corresponding stages: the
+// accumulation is a sum, the revealage is a product.
+const ACCUMULATION_CLEAR_COLOR: Color = Color::TRANSPARENT;
+const REVEALAGE_CLEAR_COLOR: Color = Color::WHITE;
+
+/// A pair of render targets for transparent surfaces and a composition pass.
+pub struct WeightedBlendedOitRenderer {
+    accumulation: GpuFrameBuffer,
+    revealage: GpuFrameBuffer,
+}
+
+impl WeightedBlendedOitRenderer {
+    /// Creates a new renderer. Depth buffer of opaque geometry must be given so transparent surfaces
+    /// could be occluded by it.
+    pub fn new(
+        server: &dyn GraphicsServer,
+        depth_stencil: GpuTexture,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, FrameworkError> {
+        let accumulation_texture = server.create_2d_render_target(
+            "OitAccumulation",
+            ACCUMULATION_PIXEL_KIND,
+            width,
+            height,
+        )?;
+        let revealage_texture =
+            server.create_2d_render_target("OitRevealage", REVEALAGE_PIXEL_KIND, width, height)?;
+
+        Ok(Self {
+            accumulation: server.create_frame_buffer(
+                Some(Attachment::depth_stencil(depth_stencil.clone())),
+                vec![Attachment::color(accumulation_texture)],
+            )?,
+            revealage: server.create_frame_buffer(
+                Some(Attachment::depth_stencil(depth_stencil)),
+                vec![Attachment::color(revealage_texture)],
+            )?,
+        })
+    }
+
+    /// Frame buffer that accumulates premultiplied color and alpha of transparent surfaces.
+    pub fn accumulation_frame_buffer(&self) -> &GpuFrameBuffer {
+        &self.accumulation
+    }
+
+    /// Frame buffer that accumulates the product of `1 - alpha` of transparent surfaces.
+    pub fn revealage_frame_buffer(&self) -> &GpuFrameBuffer {
+        &self.revealage
+    }
+
+    /// Prepares the render targets for a new frame. Depth buffer is left untouched, it is shared
+    /// with the opaque geometry.
+    pub fn clear(&self, viewport: Rect<i32>) {
+        self.accumulation
+            .clear(viewport, Some(ACCUMULATION_CLEAR_COLOR), None, None);
+        self.revealage
+            .clear(viewport, Some(REVEALAGE_CLEAR_COLOR), None, None);
+    }
+
+    /// Composes accumulated transparent surfaces over the given frame buffer.
+    pub(crate) fn compose(
+        &self,
+        server: &dyn GraphicsServer,
+        viewport: Rect<i32>,
+        frame_buffer: &GpuFrameBuffer,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let _debug_scope = server.begin_scope("OitComposite");
+
+        let mut statistics = RenderPassStatistics::default();
+
+        let frame_matrix = make_viewport_matrix(viewport);
+        let properties = PropertyGroup::from([property("worldViewProjection", &frame_matrix)]);
+        let material = RenderMaterial::from([
+            binding(
+                "accumulationTexture",
+                (
+                    &self.accumulation.color_attachments()[0].texture,
+                    &renderer_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding(
+                "revealageTexture",
+                (
+                    &self.revealage.color_attachments()[0].texture,
+                    &renderer_resources.nearest_clamp_sampler,
+                ),
+            ),
+            binding("properties", &properties),
+        ]);
+
+        statistics += renderer_resources.shaders.oit_composite.run_pass(
+            1,
+            &ImmutableString::new("Primary"),
+            frame_buffer,
+            &renderer_resources.quad,
+            viewport,
+            &material,
+            uniform_buffer_cache,
+            Default::default(),
+            None,
+        )?;
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::color::Color,
+        graphics::{
+            gpu_texture::PixelKind, BlendFactor, BlendFunc, BlendParameters, DrawParameters,
+        },
+        material::shader::{Shader, ShaderResourceKind},
+        renderer::{
+            bundle::TransparencyStage,
+            oit::{
+                ACCUMULATION_CLEAR_COLOR, ACCUMULATION_PIXEL_KIND, REVEALAGE_CLEAR_COLOR,
+                REVEALAGE_PIXEL_KIND,
+            },
+        },
+    };
+
+    fn stage_blend_func(stage: TransparencyStage) -> BlendFunc {
+        let transparent = DrawParameters {
+            blend: Some(BlendParameters {
+                func: BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        stage
+            .draw_parameters(&transparent)
+            .unwrap()
+            .blend
+            .unwrap()
+            .func
+    }
+
+    #[test]
+    fn test_oit_render_targets() {
+        // The accumulation target sums up colors of surfaces, so it must not be clamped to 1.0 and
+        // must start from zero.
+        assert_eq!(ACCUMULATION_PIXEL_KIND, PixelKind::RGBA16F);
+        assert_eq!(ACCUMULATION_CLEAR_COLOR, Color::TRANSPARENT);
+        let accumulation = stage_blend_func(TransparencyStage::Accumulation);
+        assert_eq!(accumulation.dfactor, BlendFactor::One);
+        assert_eq!(accumulation.alpha_dfactor, BlendFactor::One);
+
+        // The revealage target multiplies `1 - alpha` of surfaces, so it must start from one. Only
+        // the red channel is read by the composition pass.
+        assert_eq!(REVEALAGE_PIXEL_KIND, PixelKind::R8);
+        assert_eq!(REVEALAGE_CLEAR_COLOR.r, 255);
+        let revealage = stage_blend_func(TransparencyStage::Revealage);
+        assert_eq!(revealage.sfactor, BlendFactor::Zero);
+        assert_eq!(revealage.dfactor, BlendFactor::OneMinusSrcAlpha);
+    }
+
+    #[test]
+    fn test_oit_composite_shader() {
+        let shader = Shader::from_string(include_str!("shaders/oit_composite.shader")).unwrap();
+
+        // Every resource bound by `WeightedBlendedOitRenderer::compose` must exist in the shader.
+        for (name, is_texture) in [
+            ("accumulationTexture", true),
+            ("revealageTexture", true),
+            ("properties", false),
+        ] {
+            let resource = shader
+                .definition
+                .resources
+                .iter()
+                .find(|resource| resource.name.as_str() == name)
+                .unwrap();
+            assert_eq!(
+                matches!(resource.kind, ShaderResourceKind::Texture { .. }),
+                is_texture
+            );
+        }
+
+        let pass = shader
+            .definition
+            .passes
+            .iter()
+            .find(|pass| pass.name == "Primary")
+            .unwrap();
+        // The result is blended over the opaque geometry using the total coverage of transparent
+        // surfaces, the depth buffer is left untouched.
+        assert_eq!(
+            pass.draw_parameters.blend.as_ref().unwrap().func,
+            BlendFunc::new(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha)
+        );
+        assert!(pass.draw_parameters.depth_test.is_none());
+        assert!(!pass.draw_parameters.depth_write);
+        // Pixels that were not covered by transparent surfaces keep the cleared revealage and must
+        // not be touched.
+        assert!(pass.fragment_shader.0.contains("if (revealage >= 1.0)"));
+    }
+}
@@ -21,7 +21,10 @@
 //! A set of textures of certain kinds. See [`RendererResources`] docs for more info.
 
 use crate::{
-    core::{algebra::Matrix4, array_as_u8_slice},
+    core::{
+        algebra::{Matrix4, Vector3},
+        array_as_u8_slice,
+    },
     graphics::{
         buffer::GpuBufferDescriptor,
         buffer::{BufferKind, BufferUsage, GpuBuffer},
@@ -35,9 +38,10 @@ use crate::{
         server::GraphicsServer,
     },
     renderer::{cache::shader::RenderPassContainer, framework::GeometryBufferExt},
-    scene::mesh::surface::SurfaceData,
+    scene::{light::directional::CSM_NUM_CASCADES, mesh::surface::SurfaceData},
 };
 use fyrox_material::shader::ShaderDefinition;
+use std::borrow::Cow;
 
 /// A set of standard shaders used by the engine.
 pub struct ShadersContainer {
@@ -49,6 +53,10 @@ pub struct ShadersContainer {
     pub point_light: RenderPassContainer,
     /// A directional light shader for deferred renderer.
     pub directional_light: RenderPassContainer,
+    /// An area light shader for deferred renderer.
+    pub area_light: RenderPassContainer,
+    /// A shader that applies fog to the lit scene in deferred renderer.
+    pub fog: RenderPassContainer,
     /// A ambient light shader for deferred renderer.
     pub ambient_light: RenderPassContainer,
     /// A shader that is used to mark pixels affected by a light source in deferred renderer.
@@ -96,6 +104,32 @@ pub struct ShadersContainer {
     pub environment_map_specular_convolution: RenderPassContainer,
     /// Environment map irradiance convolution shader.
     pub environment_map_irradiance_convolution: RenderPassContainer,
+    /// A shader that composes accumulated transparent surfaces over opaque geometry (weighted
+    /// blended order-independent transparency).
+    pub oit_composite: RenderPassContainer,
+    /// A shader that draws layers of the compositor.
+    pub compositor: RenderPassContainer,
+    /// A shader that draws sprites of the sprite layer.
+    pub sprite_layer: RenderPassContainer,
+}
+
+/// Tints of shadow cascades (from the closest to the farthest one) that are used by the deferred
+/// directional light shader when the cascades visualization is enabled.
+pub(crate) const CASCADE_DEBUG_TINTS: [Vector3<f32>; CSM_NUM_CASCADES] = [
+    Vector3::new(1.0, 0.25, 0.25),
+    Vector3::new(0.25, 1.0, 0.25),
+    Vector3::new(0.25, 0.25, 1.0),
+];
+
+/// Returns the source of the deferred directional light shader, with the cascade visualization
+/// compiled in only when the `render_debug` feature is enabled.
+pub(crate) fn directional_light_shader_source() -> Cow<'static, str> {
+    let source = include_str!("shaders/deferred_directional_light.shader");
+    if cfg!(feature = "render_debug") {
+        Cow::Owned(source.replace("#define RENDER_DEBUG 0", "#define RENDER_DEBUG 1"))
+    } else {
+        Cow::Borrowed(source)
+    }
 }
 
 impl ShadersContainer {
@@ -113,8 +147,13 @@ impl ShadersContainer {
             )?,
             directional_light: RenderPassContainer::from_str(
                 server,
-                include_str!("shaders/deferred_directional_light.shader"),
+                &directional_light_shader_source(),
             )?,
+            area_light: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/deferred_area_light.shader"),
+            )?,
+            fog: RenderPassContainer::from_str(server, include_str!("shaders/fog.shader"))?,
             ambient_light: RenderPassContainer::from_str(
                 server,
                 include_str!("shaders/ambient_light.shader"),
@@ -191,6 +230,18 @@ impl ShadersContainer {
                 server,
                 include_str!("shaders/irradiance.shader"),
             )?,
+            oit_composite: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/oit_composite.shader"),
+            )?,
+            compositor: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/compositor.shader"),
+            )?,
+            sprite_layer: RenderPassContainer::from_str(
+                server,
+                include_str!("shaders/sprite_layer.shader"),
+            )?,
         })
     }
 }
@@ -374,3 +425,60 @@ impl RendererResources {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{directional_light_shader_source, CASCADE_DEBUG_TINTS};
+    use crate::{
+        graphics::gpu_program::{ShaderPropertyKind, ShaderResourceKind},
+        scene::light::directional::CSM_NUM_CASCADES,
+    };
+    use fyrox_material::shader::Shader;
+
+    #[test]
+    fn test_directional_light_render_debug_define() {
+        let source = directional_light_shader_source();
+        let expected = if cfg!(feature = "render_debug") {
+            "#define RENDER_DEBUG 1"
+        } else {
+            "#define RENDER_DEBUG 0"
+        };
+        assert_eq!(source.matches(expected).count(), 1);
+        assert!(fyrox_material::shader::Shader::from_string(&source).is_ok());
+    }
+
+    #[test]
+    fn test_cascade_debug_tints() {
+        // Every cascade must be distinguishable from the others.
+        for (i, a) in CASCADE_DEBUG_TINTS.iter().enumerate() {
+            for b in CASCADE_DEBUG_TINTS.iter().skip(i + 1) {
+                assert_ne!(a, b);
+            }
+        }
+
+        // The shader picks the tint by the index of the cascade the fragment is in.
+        let shader = Shader::from_string(&directional_light_shader_source()).unwrap();
+        let properties = shader
+            .definition
+            .resources
+            .iter()
+            .find(|resource| resource.name.as_str() == "properties")
+            .unwrap();
+        let ShaderResourceKind::PropertyGroup(ref properties) = properties.kind else {
+            panic!("properties must be a property group");
+        };
+        let tints = properties
+            .iter()
+            .find(|property| property.name.as_str() == "cascadeTints")
+            .unwrap();
+        assert!(matches!(
+            tints.kind,
+            ShaderPropertyKind::Vector3Array { max_len, .. } if max_len == CSM_NUM_CASCADES
+        ));
+        let fragment_shader = &shader.definition.passes[0].fragment_shader.0;
+        for i in 0..CSM_NUM_CASCADES {
+            assert!(fragment_shader.contains(&format!("cascade = {i};")));
+        }
+        assert!(fragment_shader.contains("* properties.cascadeTints[cascade];"));
+    }
+}
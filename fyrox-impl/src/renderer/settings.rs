@@ -162,6 +162,27 @@ pub struct QualitySettings {
     /// HDR pipeline settings.
     #[serde(default)]
     pub hdr_settings: HdrSettings,
+
+    /// Global switch to enable or disable height fog. Each scene has its own fog settings, but this
+    /// one is able to globally disable height fog.
+    #[serde(default = "default_height_fog_enabled")]
+    pub height_fog_enabled: bool,
+
+    /// Whether to use percentage-closer soft shadows (PCSS) or not. PCSS makes penumbrae of shadows
+    /// wider with the distance between an occluder and a receiver (contact-hardening shadows),
+    /// the width is defined by the size of each light source. Works only for lights with enabled
+    /// soft shadows, if disabled - regular percentage-closer filtering is used.
+    #[serde(default)]
+    pub use_pcss: bool,
+
+    /// Rendering path that will be used to render scenes. See [`RenderingPath`] docs for more
+    /// info.
+    #[serde(default)]
+    pub rendering_path: RenderingPath,
+}
+
+fn default_height_fog_enabled() -> bool {
+    true
 }
 
 impl Default for QualitySettings {
@@ -204,6 +225,10 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            height_fog_enabled: true,
+            use_pcss: true,
+            rendering_path: RenderingPath::Deferred,
         }
     }
 
@@ -245,6 +270,10 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            height_fog_enabled: true,
+            use_pcss: false,
+            rendering_path: RenderingPath::Deferred,
         }
     }
 
@@ -286,6 +315,10 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            height_fog_enabled: true,
+            use_pcss: false,
+            rendering_path: RenderingPath::Deferred,
         }
     }
 
@@ -333,10 +366,72 @@ impl QualitySettings {
 
             use_occlusion_culling: false,
             use_light_occlusion_culling: false,
+
+            height_fog_enabled: false,
+            use_pcss: false,
+            rendering_path: RenderingPath::Deferred,
+        }
+    }
+}
+
+impl QualitySettings {
+    /// Disables every feature that is not supported by the current rendering path and returns
+    /// names of the disabled features. Does nothing for [`RenderingPath::Deferred`], since it
+    /// supports every feature.
+    pub fn disable_unsupported_features(&mut self) -> Vec<&'static str> {
+        let mut disabled = Vec::new();
+        if self.rendering_path == RenderingPath::Forward {
+            if self.use_ssao {
+                self.use_ssao = false;
+                disabled.push("screen space ambient occlusion");
+            }
+            if self.light_scatter_enabled {
+                self.light_scatter_enabled = false;
+                disabled.push("light scattering");
+            }
+            if self.use_light_occlusion_culling {
+                self.use_light_occlusion_culling = false;
+                disabled.push("light occlusion culling");
+            }
         }
+        disabled
     }
 }
 
+/// Rendering path defines how the renderer draws scene geometry and applies lighting to it.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Hash,
+    PartialEq,
+    Eq,
+    Default,
+    Serialize,
+    Deserialize,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "3c1d8f5e-7a2b-4e9c-9d64-1f0b8a7e5c23")]
+pub enum RenderingPath {
+    /// Geometry is rendered into the G-Buffer first and then lighting is applied in screen space.
+    /// This path handles a lot of light sources efficiently and supports all the features of the
+    /// renderer (decals, screen space ambient occlusion, light scattering, etc.).
+    #[default]
+    Deferred,
+
+    /// Every surface is drawn directly into the frame using `Forward` render pass of its material.
+    /// This path has lower memory bandwidth requirements and works better with transparency, but
+    /// it does not support deferred-only features, such as decals, screen space ambient occlusion
+    /// and light scattering. These features are disabled when this path is active. Standard shaders
+    /// light the surfaces in their forward passes using up to 16 visible light sources, only the
+    /// first directional light casts shadows (point and spot lights are not shadowed).
+    Forward,
+}
+
 /// Cascaded-shadow maps settings.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize, Reflect, Eq)]
 pub struct CsmSettings {
@@ -392,3 +487,47 @@ pub enum ShadowMapPrecision {
     /// but could be less performant than `Half`.
     Full,
 }
+
+/// A set of debug visualization features of the renderer. These features are available only when
+/// `render_debug` feature is enabled, so they never end up in shipping builds. See
+/// [`super::Renderer::set_debug_features`].
+#[cfg(feature = "render_debug")]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct RenderDebugFeatures {
+    /// Tints the scene by the index of the shadow cascade of a directional light each fragment
+    /// samples. The first cascade is tinted red, the second - green, the third - blue. Fragments
+    /// beyond the last cascade are not tinted. It is useful to tune split distances of cascaded
+    /// shadow maps.
+    pub csm_cascades: bool,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::renderer::{QualitySettings, RenderingPath};
+
+    #[test]
+    fn test_forward_rendering_path_disables_deferred_only_features() {
+        let mut settings = QualitySettings::ultra();
+        assert_eq!(settings.rendering_path, RenderingPath::Deferred);
+        assert!(settings.disable_unsupported_features().is_empty());
+        assert!(settings.use_ssao);
+
+        settings.rendering_path = RenderingPath::Forward;
+        settings.use_light_occlusion_culling = true;
+        assert_eq!(
+            settings.disable_unsupported_features(),
+            vec![
+                "screen space ambient occlusion",
+                "light scattering",
+                "light occlusion culling"
+            ]
+        );
+        assert!(!settings.use_ssao);
+        assert!(!settings.light_scatter_enabled);
+        assert!(!settings.use_light_occlusion_culling);
+        assert_eq!(settings.rendering_path, RenderingPath::Forward);
+
+        // Nothing else to disable.
+        assert!(settings.disable_unsupported_features().is_empty());
+    }
+}
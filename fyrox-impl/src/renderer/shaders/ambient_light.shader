@@ -55,6 +55,10 @@
                 (name: "invViewProj", kind: Matrix4()),
                 (name: "skyboxLighting", kind: Bool()),
                 (name: "environmentLightingBrightness", kind: Float()),
+                (name: "boxProjection", kind: Bool()),
+                (name: "probeBoundsMin", kind: Vector3()),
+                (name: "probeBoundsMax", kind: Vector3()),
+                (name: "probeCapturePosition", kind: Vector3()),
             ]),
             binding: 0
         ),
@@ -131,6 +135,17 @@
                         vec3 viewVector = normalize(properties.cameraPosition - fragmentPosition);
                         vec3 reflectionVector = -reflect(viewVector, fragmentNormal);
 
+                        // Parallax-corrected reflections of a reflection probe.
+                        if (properties.boxProjection
+                            && all(greaterThanEqual(fragmentPosition, properties.probeBoundsMin))
+                            && all(lessThanEqual(fragmentPosition, properties.probeBoundsMax))) {
+                            vec3 firstPlane = (properties.probeBoundsMax - fragmentPosition) / reflectionVector;
+                            vec3 secondPlane = (properties.probeBoundsMin - fragmentPosition) / reflectionVector;
+                            vec3 furthestPlane = max(firstPlane, secondPlane);
+                            float distance = min(min(furthestPlane.x, furthestPlane.y), furthestPlane.z);
+                            reflectionVector = fragmentPosition + reflectionVector * distance - properties.probeCapturePosition;
+                        }
+
                         float clampedCosViewAngle = max(dot(fragmentNormal, viewVector), 0.0);
 
                         ivec2 cubeMapSize = textureSize(prefilteredSpecularMap, 0);
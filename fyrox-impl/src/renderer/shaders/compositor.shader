@@ -0,0 +1,81 @@
+(
+    name: "Compositor",
+    resources: [
+        (
+            name: "layerTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "opacity", kind: Float()),
+                (name: "blendMode", kind: Int()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            // Blending function is defined by the blend mode of a layer, see `CompositorBlendMode`.
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: None,
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+
+                    void main()
+                    {
+                        vec4 color = texture(layerTexture, texCoord);
+                        float alpha = color.a * properties.opacity;
+
+                        if (properties.blendMode == 2) {
+                            // Multiply.
+                            FragColor = vec4(mix(vec3(1.0), color.rgb, alpha), alpha);
+                        } else {
+                            // Alpha, additive and screen use premultiplied color.
+                            FragColor = vec4(color.rgb * alpha, alpha);
+                        }
+                    }
+                "#,
+        )
+    ]
+)
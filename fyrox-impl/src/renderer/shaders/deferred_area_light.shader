@@ -0,0 +1,142 @@
+(
+    name: "DeferredAreaLight",
+    resources: [
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "colorTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 1
+        ),
+        (
+            name: "normalTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 2
+        ),
+        (
+            name: "materialTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 3
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "lightColor", kind: Vector4()),
+                (name: "lightPos", kind: Vector3()),
+                (name: "lightSide", kind: Vector3()),
+                (name: "lightLook", kind: Vector3()),
+                (name: "lightDirection", kind: Vector3()),
+                (name: "cameraPosition", kind: Vector3()),
+                (name: "lightRadius", kind: Float()),
+                (name: "lightIntensity", kind: Float()),
+                (name: "isDisc", kind: Bool()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: Some(StencilFunc(
+                    func: NotEqual,
+                    ref_value: 0,
+                    mask: 0xFFFF_FFFF
+                )),
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: One,
+                        dfactor: One,
+                        alpha_sfactor: One,
+                        alpha_dfactor: One,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Zero,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                        texCoord = vertexTexCoord;
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec2 texCoord;
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        vec3 material = texture(materialTexture, texCoord).rgb;
+
+                        vec3 fragmentPosition = S_UnProject(vec3(texCoord, texture(depthTexture, texCoord).r), properties.invViewProj);
+                        vec3 fragmentToLight = properties.lightPos - fragmentPosition;
+
+                        // Surfaces behind the emitting side of the light are not lit (light emits along -lightDirection).
+                        if (dot(fragmentToLight, properties.lightDirection) < 0.0) {
+                            discard;
+                        }
+
+                        float distance = length(fragmentToLight);
+
+                        vec4 diffuseColor = texture(colorTexture, texCoord);
+                        vec3 albedo = S_SRGBToLinear(diffuseColor).rgb;
+                        vec3 N = normalize(texture(normalTexture, texCoord).xyz * 2.0 - 1.0);
+                        vec3 V = normalize(properties.cameraPosition - fragmentPosition);
+                        float metallic = material.x;
+                        float roughness = material.y;
+
+                        TPBRContext ctx;
+                        ctx.lightColor = properties.lightColor.rgb;
+                        ctx.viewVector = V;
+                        ctx.fragmentToLight = fragmentToLight / max(distance, 0.0001);
+                        ctx.fragmentNormal = N;
+                        ctx.metallic = metallic;
+                        ctx.roughness = roughness;
+                        ctx.albedo = albedo;
+
+                        vec3 points[S_AREA_LIGHT_MAX_VERTICES];
+                        int count = S_AreaLightPolygon(properties.lightPos, properties.lightSide, properties.lightLook, properties.isDisc, points);
+                        vec3 lighting = S_AreaLight(ctx, fragmentPosition, points, count);
+
+                        float distanceAttenuation = S_LightDistanceAttenuation(distance, properties.lightRadius);
+
+                        FragColor = vec4(properties.lightIntensity * distanceAttenuation * lighting, diffuseColor.a);
+                    }
+                "#,
+        )
+    ]
+)
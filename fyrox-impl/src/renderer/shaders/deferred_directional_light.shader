@@ -50,7 +50,11 @@
                 (name: "shadowsEnabled", kind: Bool()),
                 (name: "shadowBias", kind: Float()),
                 (name: "softShadows", kind: Bool()),
+                (name: "pcss", kind: Bool()),
+                (name: "lightSize", kind: Float()),
                 (name: "cascadeDistances", kind: FloatArray(max_len: 3, value: [])),
+                (name: "visualizeCascades", kind: Bool()),
+                (name: "cascadeTints", kind: Vector3Array(max_len: 3, value: [])),
             ]),
             binding: 0
         ),
@@ -107,6 +111,9 @@
 
             fragment_shader:
                 r#"
+                    // Set to 1 by the renderer when the `render_debug` feature is enabled.
+                    #define RENDER_DEBUG 0
+
                     in vec2 texCoord;
                     out vec4 FragColor;
 
@@ -114,6 +121,11 @@
                     float CsmGetShadow(in sampler2D sampler, in vec3 fragmentPosition, in mat4 lightViewProjMatrix)
                     {
                         float invSize = 1.0 / float(textureSize(sampler, 0).x);
+                        if (properties.pcss) {
+                            // Cascades use orthographic projection.
+                            return S_SpotShadowFactorPcss(properties.shadowsEnabled, false, properties.shadowBias,
+                                fragmentPosition, lightViewProjMatrix, invSize, properties.lightSize, sampler);
+                        }
                         return S_SpotShadowFactor(properties.shadowsEnabled, properties.softShadows,
                             properties.shadowBias, fragmentPosition, lightViewProjMatrix, invSize, sampler);
                     }
@@ -139,15 +151,26 @@
                         float fragmentZViewSpace = abs((properties.viewMatrix * vec4(fragmentPosition, 1.0)).z);
 
                         float shadow = 1.0;
+                        int cascade = -1;
                         if (fragmentZViewSpace <= properties.cascadeDistances[0]) {
                             shadow = CsmGetShadow(shadowCascade0, fragmentPosition, properties.lightViewProjMatrices[0]);
+                            cascade = 0;
                         } else if (fragmentZViewSpace <= properties.cascadeDistances[1]) {
                             shadow = CsmGetShadow(shadowCascade1, fragmentPosition, properties.lightViewProjMatrices[1]);
+                            cascade = 1;
                         } else if (fragmentZViewSpace <= properties.cascadeDistances[2]) {
                             shadow = CsmGetShadow(shadowCascade2, fragmentPosition, properties.lightViewProjMatrices[2]);
+                            cascade = 2;
                         }
 
                         FragColor = shadow * vec4(properties.lightIntensity * lighting, diffuseColor.a);
+
+                    #if RENDER_DEBUG
+                        // Debug visualization of the cascades: each cascade gets its own tint.
+                        if (properties.visualizeCascades && cascade >= 0) {
+                            FragColor.rgb = (FragColor.rgb + 0.1) * properties.cascadeTints[cascade];
+                        }
+                    #endif
                     }
                 "#,
         )
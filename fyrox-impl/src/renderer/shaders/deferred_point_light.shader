@@ -40,6 +40,8 @@
                 (name: "shadowAlpha", kind: Float()),
                 (name: "softShadows", kind: Bool()),
                 (name: "shadowsEnabled", kind: Bool()),
+                (name: "pcss", kind: Bool()),
+                (name: "lightSize", kind: Float()),
             ]),
             binding: 0
         ),
@@ -126,8 +128,13 @@
 
                         float distanceAttenuation = S_LightDistanceAttenuation(distance, properties.lightRadius);
 
-                        float shadow = S_PointShadow(
-                            properties.shadowsEnabled, properties.softShadows, distance, properties.shadowBias, ctx.fragmentToLight, pointShadowTexture);
+                        float shadow = properties.pcss
+                            ? S_PointShadowPcss(
+                                properties.shadowsEnabled, distance, properties.shadowBias, ctx.fragmentToLight,
+                                properties.lightSize, pointShadowTexture)
+                            : S_PointShadow(
+                                properties.shadowsEnabled, properties.softShadows, distance, properties.shadowBias,
+                                ctx.fragmentToLight, pointShadowTexture);
                         float finalShadow = mix(1.0, shadow, properties.shadowAlpha);
 
                         FragColor = vec4(properties.lightIntensity * distanceAttenuation * finalShadow * lighting, diffuseColor.a);
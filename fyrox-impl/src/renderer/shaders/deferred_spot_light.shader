@@ -51,6 +51,8 @@
                 (name: "cookieEnabled", kind: Bool()),
                 (name: "shadowsEnabled", kind: Bool()),
                 (name: "softShadows", kind: Bool()),
+                (name: "pcss", kind: Bool()),
+                (name: "lightSize", kind: Float()),
             ]),
             binding: 0
         ),
@@ -139,9 +141,14 @@
                         float spotAngleCos = dot(properties.lightDirection, ctx.fragmentToLight);
                         float coneFactor = smoothstep(properties.halfConeAngleCos, properties.halfHotspotConeAngleCos, spotAngleCos);
 
-                        float shadow = S_SpotShadowFactor(
-                            properties.shadowsEnabled, properties.softShadows, properties.shadowBias, fragmentPosition,
-                            properties.lightViewProjMatrix, properties.shadowMapInvSize, spotShadowTexture);
+                        float shadow = properties.pcss
+                            ? S_SpotShadowFactorPcss(
+                                properties.shadowsEnabled, true, properties.shadowBias, fragmentPosition,
+                                properties.lightViewProjMatrix, properties.shadowMapInvSize, properties.lightSize,
+                                spotShadowTexture)
+                            : S_SpotShadowFactor(
+                                properties.shadowsEnabled, properties.softShadows, properties.shadowBias, fragmentPosition,
+                                properties.lightViewProjMatrix, properties.shadowMapInvSize, spotShadowTexture);
                         float finalShadow = mix(1.0, shadow, properties.shadowAlpha);
 
                         vec4 cookieAttenuation = vec4(1.0);
@@ -0,0 +1,120 @@
+(
+    name: "Fog",
+    resources: [
+        (
+            name: "depthTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "invViewProj", kind: Matrix4()),
+                (name: "viewMatrix", kind: Matrix4()),
+                (name: "fogColor", kind: Vector4()),
+                (name: "fogMode", kind: Int()),
+                (name: "fogStart", kind: Float()),
+                (name: "fogEnd", kind: Float()),
+                (name: "fogDensity", kind: Float()),
+                (name: "cameraPosition", kind: Vector3()),
+                (name: "heightFogEnabled", kind: Bool()),
+                (name: "heightFogBaseHeight", kind: Float()),
+                (name: "heightFogFalloff", kind: Float()),
+                (name: "heightFogDensity", kind: Float()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: false,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+
+                    out vec2 texCoord;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    in vec2 texCoord;
+                    out vec4 FragColor;
+
+                    void main()
+                    {
+                        float depth = texture(depthTexture, texCoord).r;
+
+                        // Skybox and clear color at the far plane are not affected by the fog.
+                        if (depth >= 1.0) {
+                            discard;
+                        }
+
+                        vec3 fragmentPosition = S_UnProject(vec3(texCoord, depth), properties.invViewProj);
+                        float viewDepth = max(-(properties.viewMatrix * vec4(fragmentPosition, 1.0)).z, 0.0);
+
+                        float fogFactor = S_FogFactor(
+                            properties.fogMode,
+                            properties.fogStart,
+                            properties.fogEnd,
+                            properties.fogDensity,
+                            viewDepth
+                        );
+
+                        if (properties.heightFogEnabled) {
+                            float heightFog = S_HeightFog(
+                                properties.cameraPosition,
+                                fragmentPosition,
+                                properties.heightFogBaseHeight,
+                                properties.heightFogFalloff,
+                                properties.heightFogDensity
+                            );
+                            fogFactor = 1.0 - (1.0 - fogFactor) * (1.0 - heightFog);
+                        }
+
+                        FragColor = vec4(properties.fogColor.rgb, fogFactor);
+                    }
+                "#,
+        )
+    ]
+)
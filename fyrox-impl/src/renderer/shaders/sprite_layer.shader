@@ -0,0 +1,119 @@
+(
+    name: "SpriteLayer",
+    resources: [
+        (
+            name: "spriteTexture",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 0
+        ),
+        (
+            name: "properties",
+            kind: PropertyGroup([
+                (name: "worldViewProjection", kind: Matrix4()),
+                (name: "mode", kind: Int()),
+                (name: "outlineWidth", kind: Float()),
+                (name: "outlineColor", kind: Vector4()),
+                (name: "glowWidth", kind: Float()),
+                (name: "glowColor", kind: Vector4()),
+            ]),
+            binding: 0
+        ),
+    ],
+    passes: [
+        (
+            name: "Primary",
+
+            draw_parameters: DrawParameters(
+                cull_face: None,
+                color_write: ColorMask(
+                    red: true,
+                    green: true,
+                    blue: true,
+                    alpha: true,
+                ),
+                depth_write: false,
+                stencil_test: None,
+                depth_test: None,
+                blend: Some(BlendParameters(
+                    func: BlendFunc(
+                        sfactor: SrcAlpha,
+                        dfactor: OneMinusSrcAlpha,
+                        alpha_sfactor: SrcAlpha,
+                        alpha_dfactor: OneMinusSrcAlpha,
+                    ),
+                    equation: BlendEquation(
+                        rgb: Add,
+                        alpha: Add
+                    )
+                )),
+                stencil_op: StencilOp(
+                    fail: Keep,
+                    zfail: Keep,
+                    zpass: Keep,
+                    write_mask: 0xFFFF_FFFF,
+                ),
+                scissor_box: None
+            ),
+
+            vertex_shader:
+                r#"
+                    layout (location = 0) in vec3 vertexPosition;
+                    layout (location = 1) in vec2 vertexTexCoord;
+                    layout (location = 2) in vec4 vertexColor;
+
+                    out vec2 texCoord;
+                    out vec4 color;
+
+                    void main()
+                    {
+                        texCoord = vertexTexCoord;
+                        color = vertexColor;
+                        gl_Position = properties.worldViewProjection * vec4(vertexPosition, 1.0);
+                    }
+                "#,
+
+            fragment_shader:
+                r#"
+                    out vec4 FragColor;
+
+                    in vec2 texCoord;
+                    in vec4 color;
+
+                    // Composes two non-premultiplied colors using "over" operator.
+                    vec4 over(vec4 top, vec4 bottom)
+                    {
+                        float alpha = top.a + bottom.a * (1.0 - top.a);
+                        vec3 rgb = top.rgb * top.a + bottom.rgb * bottom.a * (1.0 - top.a);
+                        return vec4(rgb / max(alpha, 0.00001), alpha);
+                    }
+
+                    void main()
+                    {
+                        vec4 texel = texture(spriteTexture, texCoord);
+                        if (properties.mode == 1) {
+                            // Alpha mask.
+                            FragColor = color * vec4(1.0, 1.0, 1.0, texel.r);
+                        } else if (properties.mode == 2) {
+                            // Signed distance field, 0.5 is the edge.
+                            float dist = texel.r;
+                            float smoothing = max(fwidth(dist), 0.0001);
+                            float fill = smoothstep(0.5 - smoothing, 0.5 + smoothing, dist);
+                            float outlineEdge = 0.5 - properties.outlineWidth;
+                            float outline = smoothstep(outlineEdge - smoothing, outlineEdge + smoothing, dist);
+                            float glowEdge = outlineEdge - properties.glowWidth;
+                            float glow = properties.glowWidth > 0.0
+                                ? smoothstep(glowEdge, outlineEdge, dist)
+                                : 0.0;
+
+                            vec4 body = vec4(color.rgb, color.a * fill);
+                            vec4 ring = vec4(properties.outlineColor.rgb, properties.outlineColor.a * outline);
+                            vec4 halo = vec4(properties.glowColor.rgb, properties.glowColor.a * glow);
+                            FragColor = over(over(body, ring), halo);
+                        } else {
+                            FragColor = color * texel;
+                        }
+                    }
+                "#,
+        )
+    ]
+)
@@ -0,0 +1,370 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Per-light shadow maps for lights with custom shadow map settings (see [`ShadowMapSettings`]).
+//! Such shadow maps are kept between frames and re-rendered only when required by the update
+//! cadence of the light.
+
+use crate::{
+    core::{algebra::Matrix4, pool::Handle},
+    graphics::{error::FrameworkError, gpu_texture::GpuTexture, server::GraphicsServer},
+    renderer::{
+        settings::ShadowMapPrecision,
+        shadow::{point::PointShadowMapRenderer, spot::SpotShadowMapRenderer},
+    },
+    scene::{
+        light::{ShadowMapSettings, ShadowUpdateCadence},
+        node::Node,
+        Scene,
+    },
+};
+use fxhash::FxHashMap;
+
+/// Decides whether a shadow map must be re-rendered, depending on the update cadence.
+#[derive(Default, Debug)]
+pub struct ShadowMapUpdateTracker {
+    last_update_frame: Option<u64>,
+    last_light_matrix: Option<Matrix4<f32>>,
+}
+
+impl ShadowMapUpdateTracker {
+    /// Checks whether the shadow map must be updated at the given frame. `light_matrix` is a matrix
+    /// that defines the state of the light (i.e. its view-projection matrix), any change of it is
+    /// treated as movement of the light. The method assumes that the shadow map will be updated if
+    /// it returns `true`.
+    pub fn needs_update(
+        &mut self,
+        cadence: ShadowUpdateCadence,
+        frame: u64,
+        light_matrix: &Matrix4<f32>,
+    ) -> bool {
+        let needs_update = match self.last_update_frame {
+            None => true,
+            Some(last_update_frame) => match cadence {
+                ShadowUpdateCadence::EveryFrame => frame != last_update_frame,
+                ShadowUpdateCadence::Interval { frames } => {
+                    frame >= last_update_frame + frames.max(1) as u64
+                }
+                ShadowUpdateCadence::OnMovement => {
+                    self.last_light_matrix.as_ref() != Some(light_matrix)
+                }
+                ShadowUpdateCadence::Static => false,
+            },
+        };
+
+        if needs_update {
+            self.last_update_frame = Some(frame);
+            self.last_light_matrix = Some(*light_matrix);
+        }
+
+        needs_update
+    }
+}
+
+pub enum CachedShadowMapRenderer {
+    Spot(SpotShadowMapRenderer),
+    Point(PointShadowMapRenderer),
+}
+
+pub struct CachedShadowMap<R = CachedShadowMapRenderer> {
+    pub renderer: R,
+    pub tracker: ShadowMapUpdateTracker,
+    /// View-projection matrix of the light at the moment of the last update.
+    pub light_view_projection: Matrix4<f32>,
+    kind: CachedShadowMapKind,
+    size: usize,
+    precision: ShadowMapPrecision,
+    last_used_frame: u64,
+}
+
+impl<R> CachedShadowMap<R> {
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl CachedShadowMap {
+    pub fn texture(&self) -> &GpuTexture {
+        match self.renderer {
+            CachedShadowMapRenderer::Spot(ref renderer) => renderer.cascade_texture(0),
+            CachedShadowMapRenderer::Point(ref renderer) => renderer.cascade_texture(0),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CachedShadowMapKind {
+    Spot,
+    Point,
+}
+
+/// Shadow maps of the lights with custom shadow map settings. `R` is a renderer of a shadow map,
+/// it is created on demand by [`ShadowMapCache::get_or_create_with`].
+pub struct ShadowMapCache<R = CachedShadowMapRenderer> {
+    maps: FxHashMap<(Handle<Scene>, Handle<Node>), CachedShadowMap<R>>,
+    frame: u64,
+}
+
+impl<R> Default for ShadowMapCache<R> {
+    fn default() -> Self {
+        Self {
+            maps: Default::default(),
+            frame: 0,
+        }
+    }
+}
+
+impl<R> ShadowMapCache<R> {
+    /// Amount of frames after which shadow maps of unused lights are destroyed.
+    const MAX_UNUSED_FRAMES: u64 = 600;
+
+    /// Advances the frame counter and removes shadow maps of the lights that weren't used for a
+    /// long time (they're most likely deleted).
+    pub fn begin_frame(&mut self) {
+        self.frame += 1;
+        let frame = self.frame;
+        self.maps
+            .retain(|_, map| frame - map.last_used_frame <= Self::MAX_UNUSED_FRAMES);
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns a shadow map for the given light, the shadow map is (re)created using the given
+    /// function if it does not exist or its parameters are changed. The function takes the size
+    /// of the shadow map.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_or_create_with<F>(
+        &mut self,
+        scene: Handle<Scene>,
+        light: Handle<Node>,
+        kind: CachedShadowMapKind,
+        settings: &ShadowMapSettings,
+        default_size: usize,
+        precision: ShadowMapPrecision,
+        create: F,
+    ) -> Result<&mut CachedShadowMap<R>, FrameworkError>
+    where
+        F: FnOnce(usize) -> Result<R, FrameworkError>,
+    {
+        let size = if settings.resolution == 0 {
+            default_size
+        } else {
+            settings.resolution as usize
+        };
+
+        let key = (scene, light);
+        let is_valid = self
+            .maps
+            .get(&key)
+            .is_some_and(|map| map.size == size && map.precision == precision && map.kind == kind);
+
+        if !is_valid {
+            self.maps.insert(
+                key,
+                CachedShadowMap {
+                    renderer: create(size)?,
+                    tracker: Default::default(),
+                    light_view_projection: Matrix4::identity(),
+                    kind,
+                    size,
+                    precision,
+                    last_used_frame: self.frame,
+                },
+            );
+        }
+
+        let map = self.maps.get_mut(&key).unwrap();
+        map.last_used_frame = self.frame;
+        Ok(map)
+    }
+
+    pub fn get(&self, scene: Handle<Scene>, light: Handle<Node>) -> Option<&CachedShadowMap<R>> {
+        self.maps.get(&(scene, light))
+    }
+}
+
+impl ShadowMapCache {
+    /// Returns a shadow map for the given light, the shadow map is (re)created if it does not exist
+    /// or its parameters are changed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn get_or_create(
+        &mut self,
+        server: &dyn GraphicsServer,
+        scene: Handle<Scene>,
+        light: Handle<Node>,
+        kind: CachedShadowMapKind,
+        settings: &ShadowMapSettings,
+        default_size: usize,
+        precision: ShadowMapPrecision,
+    ) -> Result<&mut CachedShadowMap, FrameworkError> {
+        self.get_or_create_with(
+            scene,
+            light,
+            kind,
+            settings,
+            default_size,
+            precision,
+            |size| {
+                Ok(match kind {
+                    CachedShadowMapKind::Spot => CachedShadowMapRenderer::Spot(
+                        SpotShadowMapRenderer::new(server, size, precision)?,
+                    ),
+                    CachedShadowMapKind::Point => CachedShadowMapRenderer::Point(
+                        PointShadowMapRenderer::new(server, size, precision)?,
+                    ),
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector3},
+            pool::Handle,
+        },
+        renderer::{
+            settings::ShadowMapPrecision,
+            shadow::cache::{CachedShadowMapKind, ShadowMapCache, ShadowMapUpdateTracker},
+        },
+        scene::light::{ShadowMapSettings, ShadowUpdateCadence},
+    };
+
+    fn count_updates(
+        cadence: ShadowUpdateCadence,
+        frames: u64,
+        light_matrix: impl Fn(u64) -> Matrix4<f32>,
+    ) -> usize {
+        let mut tracker = ShadowMapUpdateTracker::default();
+        let mut updates = 0;
+        for frame in 0..frames {
+            // Two observers (i.e. two cameras) per frame.
+            for _ in 0..2 {
+                if tracker.needs_update(cadence, frame, &light_matrix(frame)) {
+                    updates += 1;
+                }
+            }
+        }
+        updates
+    }
+
+    #[test]
+    fn test_shadow_update_cadence() {
+        let still = |_| Matrix4::identity();
+        let moving = |frame| Matrix4::new_translation(&Vector3::new(frame as f32, 0.0, 0.0));
+        let moving_rarely =
+            |frame: u64| Matrix4::new_translation(&Vector3::new((frame / 5) as f32, 0.0, 0.0));
+
+        // Static light renders its shadow map only once.
+        assert_eq!(count_updates(ShadowUpdateCadence::Static, 10, still), 1);
+        assert_eq!(count_updates(ShadowUpdateCadence::Static, 10, moving), 1);
+
+        assert_eq!(
+            count_updates(ShadowUpdateCadence::EveryFrame, 10, still),
+            10
+        );
+        assert_eq!(
+            count_updates(ShadowUpdateCadence::Interval { frames: 3 }, 10, still),
+            4
+        );
+        assert_eq!(count_updates(ShadowUpdateCadence::OnMovement, 10, still), 1);
+        assert_eq!(
+            count_updates(ShadowUpdateCadence::OnMovement, 10, moving),
+            10
+        );
+        assert_eq!(
+            count_updates(ShadowUpdateCadence::OnMovement, 10, moving_rarely),
+            2
+        );
+    }
+
+    // Stands for a shadow map renderer, counts the amount of rendered frames.
+    struct ShadowMapRenderer {
+        id: usize,
+        renders: usize,
+    }
+
+    #[test]
+    fn test_static_shadow_map_is_cached() {
+        let mut cache = ShadowMapCache::<ShadowMapRenderer>::default();
+        let light = Handle::new(1, 1);
+        let mut settings = ShadowMapSettings {
+            cadence: ShadowUpdateCadence::Static,
+            ..Default::default()
+        };
+        let mut created = 0;
+
+        let mut render_frames = |cache: &mut ShadowMapCache<ShadowMapRenderer>,
+                                 settings: &ShadowMapSettings,
+                                 frames: usize| {
+            for _ in 0..frames {
+                cache.begin_frame();
+                let frame = cache.frame();
+                // Two cameras per frame.
+                for _ in 0..2 {
+                    let map = cache
+                        .get_or_create_with(
+                            Handle::NONE,
+                            light,
+                            CachedShadowMapKind::Spot,
+                            settings,
+                            256,
+                            ShadowMapPrecision::Full,
+                            |_| {
+                                created += 1;
+                                Ok(ShadowMapRenderer {
+                                    id: created,
+                                    renders: 0,
+                                })
+                            },
+                        )
+                        .unwrap();
+                    // The light is moving, but static shadow maps ignore it.
+                    let light_matrix =
+                        Matrix4::new_translation(&Vector3::new(frame as f32, 0.0, 0.0));
+                    if map
+                        .tracker
+                        .needs_update(settings.cadence, frame, &light_matrix)
+                    {
+                        map.renderer.renders += 1;
+                    }
+                }
+            }
+        };
+
+        render_frames(&mut cache, &settings, 10);
+        let map = cache.get(Handle::NONE, light).unwrap();
+        assert_eq!(map.renderer.id, 1);
+        assert_eq!(map.renderer.renders, 1);
+        assert_eq!(map.size(), 256);
+
+        // Change of the resolution re-creates the shadow map and it must be rendered again.
+        settings.resolution = 512;
+        render_frames(&mut cache, &settings, 10);
+        let map = cache.get(Handle::NONE, light).unwrap();
+        assert_eq!(map.renderer.id, 2);
+        assert_eq!(map.renderer.renders, 1);
+        assert_eq!(map.size(), 512);
+    }
+}
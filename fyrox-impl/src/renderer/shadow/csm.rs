@@ -294,6 +294,10 @@ impl CsmRenderer {
                     renderer_resources,
                     ambient_light: Color::WHITE, // TODO
                     scene_depth: None,
+                    fog: None,
+                    use_height_fog: false,
+                    transparency_stage: Default::default(),
+                    shadows: None,
                 },
             )?;
         }
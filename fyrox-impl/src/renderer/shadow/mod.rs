@@ -20,6 +20,7 @@
 
 #![warn(clippy::too_many_arguments)]
 
+pub mod cache;
 pub mod csm;
 pub mod point;
 pub mod spot;
@@ -176,6 +176,10 @@ impl SpotShadowMapRenderer {
                 renderer_resources,
                 ambient_light: Color::WHITE, // TODO
                 scene_depth: None,
+                fog: None,
+                use_height_fog: false,
+                transparency_stage: Default::default(),
+                shadows: None,
             },
         )?;
 
@@ -0,0 +1,752 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Sprite layer is a screen-space 2D layer, that is drawn on top of the frame. It is suitable for
+//! HUDs and simple 2D games, that does not need a scene. Sprites are sorted by their z-order and
+//! then grouped by texture, so every group of sprites with the same texture is drawn in a single
+//! draw call. See [`SpriteLayer`] docs for more info.
+
+use crate::{
+    asset::manager::ResourceManager,
+    core::{
+        algebra::{Matrix4, Vector2, Vector3},
+        color::Color,
+        math::{Rect, TriangleDefinition},
+        sstorage::ImmutableString,
+    },
+    fxhash::FxHashMap,
+    graphics::{
+        buffer::BufferUsage,
+        error::FrameworkError,
+        framebuffer::GpuFrameBuffer,
+        geometry_buffer::{
+            AttributeDefinition, AttributeKind, ElementsDescriptor, GpuGeometryBuffer,
+            GpuGeometryBufferDescriptor, VertexBufferData, VertexBufferDescriptor,
+        },
+        server::GraphicsServer,
+    },
+    gui::font::{Font, FontHeight, FontResource},
+    renderer::text::{self, DistanceFieldAtlas, TextGlyphQuad},
+    renderer::{
+        cache::{
+            shader::{binding, property, PropertyGroup, RenderMaterial},
+            texture::TextureCache,
+            uniform::UniformBufferCache,
+        },
+        resources::RendererResources,
+        RenderPassStatistics,
+    },
+    resource::texture::TextureResource,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// A vertex of a sprite in the sprite layer.
+#[repr(C)]
+#[derive(Copy, Pod, Zeroable, Clone, Debug, PartialEq)]
+pub struct SpriteLayerVertex {
+    /// Position of the vertex in screen coordinates (in pixels).
+    pub position: Vector3<f32>,
+    /// Texture coordinates of the vertex.
+    pub tex_coord: Vector2<f32>,
+    /// Color of the vertex.
+    pub color: Color,
+}
+
+/// Style of a text drawn using signed distance fields.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DistanceFieldStyle {
+    /// Width of the outline relative to the spread of the distance field, in `[0; 0.5]` range.
+    /// Zero means no outline.
+    pub outline_width: f32,
+    /// Color of the outline.
+    pub outline_color: Color,
+    /// Width of the glow (outside of the outline) relative to the spread of the distance field, in
+    /// `[0; 0.5]` range. Zero means no glow.
+    pub glow_width: f32,
+    /// Color of the glow.
+    pub glow_color: Color,
+}
+
+impl Default for DistanceFieldStyle {
+    fn default() -> Self {
+        Self {
+            outline_width: 0.0,
+            outline_color: Color::TRANSPARENT,
+            glow_width: 0.0,
+            glow_color: Color::TRANSPARENT,
+        }
+    }
+}
+
+/// Defines how the texture of a sprite is interpreted.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum SpriteTextureMode {
+    /// The texture is multiplied with the color of the sprite.
+    #[default]
+    Color,
+    /// The red channel of the texture is used as the alpha of the sprite. It is used to draw glyphs
+    /// from bitmap font atlases, that store glyph coverage in a single channel.
+    AlphaMask,
+    /// The red channel of the texture is a signed distance field, the edges are reconstructed at
+    /// any scale. It is used to draw glyphs from [`text::DistanceFieldAtlas`].
+    DistanceField(DistanceFieldStyle),
+}
+
+impl SpriteTextureMode {
+    fn index(&self) -> i32 {
+        match self {
+            Self::Color => 0,
+            Self::AlphaMask => 1,
+            Self::DistanceField(_) => 2,
+        }
+    }
+}
+
+/// A sprite in screen space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenSprite {
+    /// Position of the anchor point of the sprite in screen coordinates (in pixels, origin is at
+    /// the top-left corner of the screen).
+    pub position: Vector2<f32>,
+    /// Size of the sprite in pixels.
+    pub size: Vector2<f32>,
+    /// Rotation of the sprite (in radians) around its anchor point.
+    pub rotation: f32,
+    /// Anchor point of the sprite in normalized coordinates of the sprite rectangle. `(0.0, 0.0)`
+    /// is the top-left corner, `(0.5, 0.5)` is the center, `(1.0, 1.0)` is the bottom-right
+    /// corner.
+    pub anchor: Vector2<f32>,
+    /// A rectangle in texture coordinates that will be mapped on the sprite.
+    pub uv_rect: Rect<f32>,
+    /// Color of the sprite. It is multiplied with the color of the texture.
+    pub color: Color,
+    /// Texture of the sprite. [`None`] means that the sprite will be filled with its color.
+    pub texture: Option<TextureResource>,
+    /// Defines drawing order of sprites. Sprites with lower z-order are drawn first, so sprites
+    /// with higher z-order are drawn on top of them. Sprites with the same z-order and texture
+    /// are drawn in the order of their addition.
+    pub z_order: i32,
+    /// Defines how the texture of the sprite is interpreted.
+    pub texture_mode: SpriteTextureMode,
+}
+
+impl Default for ScreenSprite {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            size: Vector2::new(1.0, 1.0),
+            rotation: 0.0,
+            anchor: Default::default(),
+            uv_rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            color: Color::WHITE,
+            texture: None,
+            z_order: 0,
+            texture_mode: Default::default(),
+        }
+    }
+}
+
+impl ScreenSprite {
+    /// Creates a new sprite with the given position and size. The anchor point of the sprite is
+    /// at its top-left corner.
+    pub fn new(position: Vector2<f32>, size: Vector2<f32>) -> Self {
+        Self {
+            position,
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the rotation of the sprite (in radians).
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Sets the anchor point of the sprite in normalized coordinates of the sprite rectangle.
+    pub fn with_anchor(mut self, anchor: Vector2<f32>) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sets a rectangle in texture coordinates that will be mapped on the sprite.
+    pub fn with_uv_rect(mut self, uv_rect: Rect<f32>) -> Self {
+        self.uv_rect = uv_rect;
+        self
+    }
+
+    /// Sets the color of the sprite.
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets the texture of the sprite.
+    pub fn with_texture(mut self, texture: Option<TextureResource>) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Sets the z-order of the sprite.
+    pub fn with_z_order(mut self, z_order: i32) -> Self {
+        self.z_order = z_order;
+        self
+    }
+
+    /// Sets how the texture of the sprite is interpreted.
+    pub fn with_texture_mode(mut self, texture_mode: SpriteTextureMode) -> Self {
+        self.texture_mode = texture_mode;
+        self
+    }
+
+    /// Calculates screen-space positions of the corners of the sprite in the following order:
+    /// top-left, top-right, bottom-right, bottom-left (before rotation).
+    pub fn corners(&self) -> [Vector2<f32>; 4] {
+        let origin = self.anchor.component_mul(&self.size);
+        let (sin, cos) = self.rotation.sin_cos();
+        [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(self.size.x, 0.0),
+            Vector2::new(self.size.x, self.size.y),
+            Vector2::new(0.0, self.size.y),
+        ]
+        .map(|corner| {
+            let local = corner - origin;
+            self.position
+                + Vector2::new(local.x * cos - local.y * sin, local.x * sin + local.y * cos)
+        })
+    }
+
+    fn texture_key(&self) -> Option<u64> {
+        self.texture.as_ref().map(|texture| texture.key())
+    }
+}
+
+/// A group of sprites that share the same texture and could be drawn in a single draw call.
+#[derive(Clone, Debug, Default)]
+pub struct SpriteBatch {
+    /// A texture of the sprites in the batch.
+    pub texture: Option<TextureResource>,
+    /// Defines how the texture of the sprites is interpreted.
+    pub texture_mode: SpriteTextureMode,
+    /// Vertices of the sprites.
+    pub vertices: Vec<SpriteLayerVertex>,
+    /// Triangles of the sprites.
+    pub triangles: Vec<TriangleDefinition>,
+}
+
+impl SpriteBatch {
+    fn push_sprite(&mut self, sprite: &ScreenSprite) {
+        let first = self.vertices.len() as u32;
+
+        let uv = &sprite.uv_rect;
+        let tex_coords = [
+            Vector2::new(uv.x(), uv.y()),
+            Vector2::new(uv.x() + uv.w(), uv.y()),
+            Vector2::new(uv.x() + uv.w(), uv.y() + uv.h()),
+            Vector2::new(uv.x(), uv.y() + uv.h()),
+        ];
+
+        for (position, tex_coord) in sprite.corners().into_iter().zip(tex_coords) {
+            self.vertices.push(SpriteLayerVertex {
+                position: position.to_homogeneous(),
+                tex_coord,
+                color: sprite.color,
+            });
+        }
+
+        self.triangles
+            .push(TriangleDefinition([first, first + 1, first + 2]));
+        self.triangles
+            .push(TriangleDefinition([first, first + 2, first + 3]));
+    }
+
+    /// Returns amount of sprites in the batch.
+    pub fn sprite_count(&self) -> usize {
+        self.vertices.len() / 4
+    }
+}
+
+/// A screen-space 2D layer with sprites. The layer is drawn on top of every scene (and compositor
+/// layers), but below user interface. The layer is not cleared automatically, use
+/// [`SpriteLayer::clear`] to remove the sprites drawn in the previous frame.
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::Vector2,
+/// #     renderer::{sprite_layer::ScreenSprite, Renderer},
+/// #     resource::texture::TextureResource,
+/// # };
+/// fn draw_hud(renderer: &mut Renderer, icon: &TextureResource) {
+///     let layer = renderer.sprite_layer_mut();
+///     layer.clear();
+///     for i in 0..10 {
+///         layer.draw_sprite(
+///             ScreenSprite::new(
+///                 Vector2::new(10.0 + i as f32 * 36.0, 10.0),
+///                 Vector2::new(32.0, 32.0),
+///             )
+///             .with_texture(Some(icon.clone())),
+///         );
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SpriteLayer {
+    sprites: Vec<ScreenSprite>,
+    distance_field_atlases: FxHashMap<u64, DistanceFieldAtlas>,
+}
+
+impl SpriteLayer {
+    /// Adds a new sprite to the layer.
+    pub fn draw_sprite(&mut self, sprite: ScreenSprite) {
+        self.sprites.push(sprite);
+    }
+
+    /// Draws the given text using the given font. `position` defines the top-left corner of the
+    /// text, `size` defines the height of the font in pixels. Characters, that are missing in the
+    /// font, are drawn as boxes.
+    pub fn draw_text(
+        &mut self,
+        position: Vector2<f32>,
+        text: &str,
+        font: &FontResource,
+        size: f32,
+        color: Color,
+    ) {
+        self.draw_text_wrapped(position, text, font, size, color, None)
+    }
+
+    /// The same as [`Self::draw_text`], but also wraps lines of the text by words if they do not
+    /// fit into the given maximum width.
+    pub fn draw_text_wrapped(
+        &mut self,
+        position: Vector2<f32>,
+        text: &str,
+        font: &FontResource,
+        size: f32,
+        color: Color,
+        max_width: Option<f32>,
+    ) {
+        let mut state = font.state();
+        let Some(font) = state.data() else {
+            return;
+        };
+
+        for quad in text::layout_text(font, text, size, max_width) {
+            if let Some(sprite) = glyph_sprite(font, position, &quad, size, color) {
+                self.sprites.extend(sprite);
+            }
+        }
+    }
+
+    /// Draws the given text using signed distance fields of glyphs. Unlike [`Self::draw_text`],
+    /// that uses bitmaps of glyphs, the text stays sharp at any size and could have an outline and
+    /// a glow. Glyphs, that cannot be stored as distance fields (for example the ones from fallback
+    /// fonts), are drawn as bitmaps.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_text_sdf(
+        &mut self,
+        position: Vector2<f32>,
+        text: &str,
+        font_resource: &FontResource,
+        size: f32,
+        color: Color,
+        style: DistanceFieldStyle,
+        max_width: Option<f32>,
+    ) {
+        let mut state = font_resource.state();
+        let Some(font) = state.data() else {
+            return;
+        };
+
+        let atlas = self
+            .distance_field_atlases
+            .entry(font_resource.key())
+            .or_default();
+        let scale = size / atlas.base_size();
+        let ascender = font.ascender(size);
+
+        for placement in text::place_glyphs(font, text, size, max_width) {
+            if !placement.missing {
+                if let Some(glyph) = atlas.glyph(font, placement.character).cloned() {
+                    let Some(texture) = atlas.page_texture(glyph.page_index) else {
+                        continue;
+                    };
+                    let uv_min = glyph.tex_coords[0];
+                    let uv_max = glyph.tex_coords[2];
+                    self.sprites.push(
+                        ScreenSprite::new(
+                            Vector2::new(
+                                position.x + placement.position.x + glyph.bitmap_left * scale,
+                                position.y + placement.position.y + ascender.floor()
+                                    - (glyph.bitmap_top + glyph.bitmap_height) * scale,
+                            ),
+                            Vector2::new(glyph.bitmap_width, glyph.bitmap_height) * scale,
+                        )
+                        .with_texture(Some(texture))
+                        .with_uv_rect(Rect::new(
+                            uv_min.x,
+                            uv_min.y,
+                            uv_max.x - uv_min.x,
+                            uv_max.y - uv_min.y,
+                        ))
+                        .with_color(color)
+                        .with_texture_mode(SpriteTextureMode::DistanceField(style)),
+                    );
+                    continue;
+                }
+            }
+
+            // Fallback to bitmap glyphs.
+            if let Some(quad) = text::glyph_quad(font, placement, size, ascender) {
+                if let Some(sprites) = glyph_sprite(font, position, &quad, size, color) {
+                    self.sprites.extend(sprites);
+                }
+            }
+        }
+    }
+
+    /// Returns a slice with every sprite in the layer.
+    pub fn sprites(&self) -> &[ScreenSprite] {
+        &self.sprites
+    }
+
+    /// Removes every sprite from the layer. Distance field atlases of fonts are kept.
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    /// Sorts the sprites by their z-order and groups them by texture. Every batch could be drawn
+    /// in a single draw call.
+    pub fn build_batches(&self) -> Vec<SpriteBatch> {
+        let mut order = (0..self.sprites.len()).collect::<Vec<_>>();
+        // The sort is stable, so sprites with the same z-order and texture keep the order of
+        // their addition.
+        order.sort_by_key(|i| {
+            let sprite = &self.sprites[*i];
+            (
+                sprite.z_order,
+                sprite.texture_key(),
+                sprite.texture_mode.index(),
+            )
+        });
+
+        let mut batches = Vec::<SpriteBatch>::new();
+        for i in order {
+            let sprite = &self.sprites[i];
+            match batches.last_mut() {
+                Some(batch)
+                    if batch.texture == sprite.texture
+                        && batch.texture_mode == sprite.texture_mode =>
+                {
+                    batch.push_sprite(sprite)
+                }
+                _ => {
+                    let mut batch = SpriteBatch {
+                        texture: sprite.texture.clone(),
+                        texture_mode: sprite.texture_mode,
+                        ..Default::default()
+                    };
+                    batch.push_sprite(sprite);
+                    batches.push(batch);
+                }
+            }
+        }
+        batches
+    }
+}
+
+/// Creates sprites for the given glyph from the bitmap atlas of the font. Missing glyphs are drawn
+/// as boxes.
+fn glyph_sprite(
+    font: &mut Font,
+    position: Vector2<f32>,
+    quad: &TextGlyphQuad,
+    size: f32,
+    color: Color,
+) -> Option<Vec<ScreenSprite>> {
+    let bounds = Rect::new(
+        position.x + quad.bounds.x(),
+        position.y + quad.bounds.y(),
+        quad.bounds.w(),
+        quad.bounds.h(),
+    );
+
+    if quad.missing {
+        let thickness = (size / 16.0).max(1.0);
+        return Some(
+            [
+                Rect::new(bounds.x(), bounds.y(), bounds.w(), thickness),
+                Rect::new(
+                    bounds.x(),
+                    bounds.y() + bounds.h() - thickness,
+                    bounds.w(),
+                    thickness,
+                ),
+                Rect::new(bounds.x(), bounds.y(), thickness, bounds.h()),
+                Rect::new(
+                    bounds.x() + bounds.w() - thickness,
+                    bounds.y(),
+                    thickness,
+                    bounds.h(),
+                ),
+            ]
+            .into_iter()
+            .map(|rect| ScreenSprite::new(rect.position, rect.size).with_color(color))
+            .collect(),
+        );
+    }
+
+    let page_size = font.page_size() as u32;
+    let texture = font
+        .atlases
+        .get_mut(&FontHeight(size))
+        .and_then(|atlas| atlas.pages.get_mut(quad.page_index))
+        .and_then(|page| text::page_texture(page, page_size))?;
+
+    let uv_min = quad.tex_coords[0];
+    let uv_max = quad.tex_coords[2];
+    Some(vec![ScreenSprite::new(bounds.position, bounds.size)
+        .with_texture(Some(texture))
+        .with_uv_rect(Rect::new(
+            uv_min.x,
+            uv_min.y,
+            uv_max.x - uv_min.x,
+            uv_max.y - uv_min.y,
+        ))
+        .with_color(color)
+        .with_texture_mode(SpriteTextureMode::AlphaMask)])
+}
+
+pub(crate) struct SpriteLayerRenderer {
+    geometry: GpuGeometryBuffer,
+}
+
+impl SpriteLayerRenderer {
+    pub(crate) fn new(server: &dyn GraphicsServer) -> Result<Self, FrameworkError> {
+        let desc = GpuGeometryBufferDescriptor {
+            name: "SpriteLayerGeometryBuffer",
+            elements: ElementsDescriptor::Triangles(&[]),
+            buffers: &[VertexBufferDescriptor {
+                usage: BufferUsage::DynamicDraw,
+                attributes: &[
+                    AttributeDefinition {
+                        location: 0,
+                        kind: AttributeKind::Float,
+                        component_count: 3,
+                        normalized: false,
+                        divisor: 0,
+                    },
+                    AttributeDefinition {
+                        location: 1,
+                        kind: AttributeKind::Float,
+                        component_count: 2,
+                        normalized: false,
+                        divisor: 0,
+                    },
+                    AttributeDefinition {
+                        location: 2,
+                        kind: AttributeKind::UnsignedByte,
+                        component_count: 4,
+                        normalized: true,
+                        divisor: 0,
+                    },
+                ],
+                data: VertexBufferData::new::<SpriteLayerVertex>(None),
+            }],
+            usage: BufferUsage::DynamicDraw,
+        };
+
+        Ok(Self {
+            geometry: server.create_geometry_buffer(desc)?,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &mut self,
+        server: &dyn GraphicsServer,
+        layer: &SpriteLayer,
+        frame_buffer: &GpuFrameBuffer,
+        viewport: Rect<i32>,
+        texture_cache: &mut TextureCache,
+        resource_manager: &ResourceManager,
+        uniform_buffer_cache: &mut UniformBufferCache,
+        renderer_resources: &RendererResources,
+    ) -> Result<RenderPassStatistics, FrameworkError> {
+        let mut statistics = RenderPassStatistics::default();
+
+        if layer.sprites.is_empty() {
+            return Ok(statistics);
+        }
+
+        let _debug_scope = server.begin_scope("SpriteLayer");
+
+        let view_projection = Matrix4::new_orthographic(
+            0.0,
+            viewport.w() as f32,
+            viewport.h() as f32,
+            0.0,
+            -1.0,
+            1.0,
+        );
+        for batch in layer.build_batches() {
+            let (texture, sampler) = match batch
+                .texture
+                .as_ref()
+                .and_then(|texture| texture_cache.get(server, resource_manager, texture))
+            {
+                Some(data) => (&data.gpu_texture, &data.gpu_sampler),
+                None => (
+                    &renderer_resources.white_dummy,
+                    &renderer_resources.linear_clamp_sampler,
+                ),
+            };
+
+            self.geometry.set_buffer_data_of_type(0, &batch.vertices);
+            self.geometry.set_triangles(&batch.triangles);
+
+            let mode = batch.texture_mode.index();
+            let style = match batch.texture_mode {
+                SpriteTextureMode::DistanceField(style) => style,
+                _ => Default::default(),
+            };
+            let outline_color = style.outline_color.as_frgba();
+            let glow_color = style.glow_color.as_frgba();
+            let properties = PropertyGroup::from([
+                property("worldViewProjection", &view_projection),
+                property("mode", &mode),
+                property("outlineWidth", &style.outline_width),
+                property("outlineColor", &outline_color),
+                property("glowWidth", &style.glow_width),
+                property("glowColor", &glow_color),
+            ]);
+            let material = RenderMaterial::from([
+                binding("spriteTexture", (texture, sampler)),
+                binding("properties", &properties),
+            ]);
+
+            statistics += renderer_resources.shaders.sprite_layer.run_pass(
+                1,
+                &ImmutableString::new("Primary"),
+                frame_buffer,
+                &self.geometry,
+                viewport,
+                &material,
+                uniform_buffer_cache,
+                Default::default(),
+                None,
+            )?;
+        }
+
+        Ok(statistics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, math::TriangleDefinition},
+        renderer::sprite_layer::{ScreenSprite, SpriteLayer},
+        resource::texture::{TextureResource, TextureResourceExtension},
+    };
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_close(a: Vector2<f32>, b: Vector2<f32>) {
+        assert!((a - b).norm() < 1.0e-4, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_sprites_with_same_texture_batch_into_single_draw_call() {
+        let texture = TextureResource::new_render_target(32, 32);
+
+        let mut layer = SpriteLayer::default();
+        for i in 0..100 {
+            layer.draw_sprite(
+                ScreenSprite::new(Vector2::new(i as f32, 0.0), Vector2::new(16.0, 16.0))
+                    .with_texture(Some(texture.clone())),
+            );
+        }
+
+        let batches = layer.build_batches();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].sprite_count(), 100);
+        assert_eq!(batches[0].triangles.len(), 200);
+        assert_eq!(batches[0].texture, Some(texture));
+        assert_eq!(batches[0].triangles[1], TriangleDefinition([0, 2, 3]));
+        assert_eq!(batches[0].triangles[2], TriangleDefinition([4, 5, 6]));
+    }
+
+    #[test]
+    fn test_z_ordering() {
+        let a = TextureResource::new_render_target(32, 32);
+        let b = TextureResource::new_render_target(32, 32);
+
+        let mut layer = SpriteLayer::default();
+        let size = Vector2::new(1.0, 1.0);
+        layer.draw_sprite(
+            ScreenSprite::new(Vector2::new(0.0, 0.0), size)
+                .with_texture(Some(a.clone()))
+                .with_z_order(1),
+        );
+        layer.draw_sprite(
+            ScreenSprite::new(Vector2::new(1.0, 0.0), size).with_texture(Some(b.clone())),
+        );
+        layer.draw_sprite(
+            ScreenSprite::new(Vector2::new(2.0, 0.0), size)
+                .with_texture(Some(a.clone()))
+                .with_z_order(1),
+        );
+        layer.draw_sprite(
+            ScreenSprite::new(Vector2::new(3.0, 0.0), size)
+                .with_texture(Some(b.clone()))
+                .with_z_order(2),
+        );
+
+        let batches = layer.build_batches();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[0].texture, Some(b.clone()));
+        assert_eq!(batches[0].sprite_count(), 1);
+        // Sprites with the same z-order and texture are merged and keep the order of addition.
+        assert_eq!(batches[1].texture, Some(a));
+        assert_eq!(batches[1].sprite_count(), 2);
+        assert_eq!(batches[1].vertices[0].position.x, 0.0);
+        assert_eq!(batches[1].vertices[4].position.x, 2.0);
+        assert_eq!(batches[2].texture, Some(b));
+    }
+
+    #[test]
+    fn test_rotation_around_anchor() {
+        let sprite = ScreenSprite::new(Vector2::new(100.0, 100.0), Vector2::new(20.0, 10.0));
+        let corners = sprite.corners();
+        assert_close(corners[0], Vector2::new(100.0, 100.0));
+        assert_close(corners[2], Vector2::new(120.0, 110.0));
+
+        let centered = sprite
+            .clone()
+            .with_anchor(Vector2::new(0.5, 0.5))
+            .with_rotation(FRAC_PI_2);
+        let corners = centered.corners();
+        // Top-left corner (-10, -5) relative to the center goes to (5, -10) after rotation.
+        assert_close(corners[0], Vector2::new(105.0, 90.0));
+        assert_close(corners[2], Vector2::new(95.0, 110.0));
+    }
+}
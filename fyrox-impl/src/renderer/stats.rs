@@ -41,6 +41,8 @@ pub struct LightingStatistics {
     pub spot_shadow_maps_rendered: usize,
     /// How many directional lights were rendered.
     pub directional_lights_rendered: usize,
+    /// How many area lights were rendered.
+    pub area_lights_rendered: usize,
 }
 
 impl AddAssign for LightingStatistics {
@@ -50,6 +52,7 @@ impl AddAssign for LightingStatistics {
         self.spot_lights_rendered += rhs.spot_lights_rendered;
         self.spot_shadow_maps_rendered += rhs.spot_shadow_maps_rendered;
         self.directional_lights_rendered += rhs.directional_lights_rendered;
+        self.area_lights_rendered += rhs.area_lights_rendered;
         self.csm_rendered += rhs.csm_rendered;
     }
 }
@@ -62,12 +65,14 @@ impl Display for LightingStatistics {
             \tPoint Lights: {}\n\
             \tSpot Lights: {}\n\
             \tDirectional Lights: {}\n\
+            \tArea Lights: {}\n\
             \tPoint Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n\
             \tSpot Shadow Maps: {}\n",
             self.point_lights_rendered,
             self.spot_lights_rendered,
             self.directional_lights_rendered,
+            self.area_lights_rendered,
             self.point_shadow_maps_rendered,
             self.spot_shadow_maps_rendered,
             self.csm_rendered
@@ -0,0 +1,632 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Text layout for the sprite layer. Glyphs are rasterized into font atlases (see
+//! [`crate::gui::font::Atlas`]) or into signed distance field atlases (see [`DistanceFieldAtlas`])
+//! on demand, this module positions the glyphs on screen with kerning and optional line wrapping. See [`crate::renderer::sprite_layer::SpriteLayer::draw_text`] for
+//! the drawing API.
+
+use crate::{
+    asset::untyped::ResourceKind,
+    core::{algebra::Vector2, math::Rect, rectpack::RectPacker, uuid::Uuid},
+    fxhash::FxHashMap,
+    gui::font::{Font, Page},
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
+};
+use std::fmt::{Debug, Formatter};
+
+/// Horizontal distance between glyphs of a missing character, relative to the font size.
+const MISSING_GLYPH_ADVANCE: f32 = 0.6;
+/// Width of the fallback box of a missing character, relative to the font size.
+const MISSING_GLYPH_WIDTH: f32 = 0.5;
+
+/// A positioned glyph of a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextGlyphQuad {
+    /// A character of the glyph.
+    pub character: char,
+    /// Screen-space bounds of the glyph relative to the origin of the text.
+    pub bounds: Rect<f32>,
+    /// Texture coordinates of the glyph in the atlas page.
+    pub tex_coords: [Vector2<f32>; 4],
+    /// Index of the atlas page with the glyph.
+    pub page_index: usize,
+    /// `true` if the font (and its fallbacks) has no glyph for the character. Such glyphs are
+    /// drawn as a fallback box.
+    pub missing: bool,
+}
+
+fn has_glyph(font: &Font, character: char) -> bool {
+    let contains = |font: &Font| {
+        font.inner
+            .as_ref()
+            .is_some_and(|inner| inner.chars().contains_key(&character))
+    };
+    contains(font)
+        || font
+            .fallbacks
+            .iter()
+            .flatten()
+            .any(|fallback| fallback.state().data_ref().is_some_and(|f| contains(f)))
+}
+
+fn advance(font: &mut Font, character: char, size: f32) -> f32 {
+    if let Some(inner) = font
+        .inner
+        .as_ref()
+        .filter(|inner| inner.chars().contains_key(&character))
+    {
+        inner.metrics(character, size).advance_width
+    } else if has_glyph(font, character) {
+        font.glyph_advance(character, size)
+    } else {
+        size * MISSING_GLYPH_ADVANCE
+    }
+}
+
+fn kerning(font: &Font, prev: Option<char>, character: char, size: f32) -> f32 {
+    prev.and_then(|prev| font.horizontal_kerning(size, prev, character))
+        .unwrap_or_default()
+}
+
+fn measure(font: &mut Font, text: &str, size: f32, mut prev: Option<char>) -> f32 {
+    let mut width = 0.0;
+    for character in text.chars() {
+        width += kerning(font, prev, character, size) + advance(font, character, size);
+        prev = Some(character);
+    }
+    width
+}
+
+/// A position of a character of a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlyphPlacement {
+    /// A character of the glyph.
+    pub character: char,
+    /// Horizontal position of the pen and vertical position of the top of the line of the glyph,
+    /// relative to the origin of the text.
+    pub position: Vector2<f32>,
+    /// `true` if the font (and its fallbacks) has no glyph for the character.
+    pub missing: bool,
+}
+
+/// Calculates positions of characters of the given text. Lines are separated by `\n` and every
+/// line is wrapped by words if it does not fit into the given `max_width`. Whitespace characters
+/// do not produce any placements.
+pub fn place_glyphs(
+    font: &mut Font,
+    text: &str,
+    size: f32,
+    max_width: Option<f32>,
+) -> Vec<GlyphPlacement> {
+    let mut placements = Vec::new();
+
+    if !size.is_finite() || size <= f32::EPSILON || font.inner.is_none() {
+        return placements;
+    }
+
+    // Minus here is because descender has negative value.
+    let line_height = font.ascender(size) - font.descender(size);
+
+    let mut y = 0.0;
+    for line in text.split('\n') {
+        let mut x = 0.0;
+        let mut prev = None;
+
+        for word in line.split_inclusive(' ') {
+            if let Some(max_width) = max_width {
+                if x > 0.0 && x + measure(font, word.trim_end(), size, prev) > max_width {
+                    x = 0.0;
+                    y += line_height;
+                    prev = None;
+                }
+            }
+
+            for character in word.chars() {
+                x += kerning(font, prev, character, size);
+                prev = Some(character);
+
+                if !character.is_whitespace() {
+                    placements.push(GlyphPlacement {
+                        character,
+                        position: Vector2::new(x, y),
+                        missing: !has_glyph(font, character),
+                    });
+                }
+
+                x += advance(font, character, size);
+            }
+        }
+
+        y += line_height;
+    }
+
+    placements
+}
+
+/// Calculates bounds of glyphs of the given text, see [`place_glyphs`] for more info. Glyphs are
+/// rasterized into the atlas of the font if needed.
+pub fn layout_text(
+    font: &mut Font,
+    text: &str,
+    size: f32,
+    max_width: Option<f32>,
+) -> Vec<TextGlyphQuad> {
+    let placements = place_glyphs(font, text, size, max_width);
+    let ascender = font.ascender(size);
+
+    placements
+        .into_iter()
+        .filter_map(|placement| glyph_quad(font, placement, size, ascender))
+        .collect()
+}
+
+/// Calculates bounds of a glyph at the given placement using the bitmap atlas of the font.
+pub(crate) fn glyph_quad(
+    font: &mut Font,
+    placement: GlyphPlacement,
+    size: f32,
+    ascender: f32,
+) -> Option<TextGlyphQuad> {
+    let GlyphPlacement {
+        character,
+        position,
+        missing,
+    } = placement;
+
+    if missing {
+        return Some(TextGlyphQuad {
+            character,
+            bounds: Rect::new(position.x, position.y, size * MISSING_GLYPH_WIDTH, ascender),
+            tex_coords: Default::default(),
+            page_index: 0,
+            missing: true,
+        });
+    }
+
+    let glyph = font.glyph(character, size)?;
+    Some(TextGlyphQuad {
+        character,
+        bounds: Rect::new(
+            position.x + glyph.bitmap_left,
+            position.y + ascender.floor() - glyph.bitmap_top - glyph.bitmap_height,
+            glyph.bitmap_width,
+            glyph.bitmap_height,
+        ),
+        tex_coords: glyph.tex_coords,
+        page_index: glyph.page_index,
+        missing: false,
+    })
+}
+
+fn make_mask_texture(pixels: &[u8], page_size: u32) -> Option<TextureResource> {
+    let details = Texture::from_bytes(
+        TextureKind::Rectangle {
+            width: page_size,
+            height: page_size,
+        },
+        TexturePixelKind::R8,
+        pixels.to_vec(),
+    )?;
+    Some(TextureResource::new_ok(
+        Uuid::new_v4(),
+        ResourceKind::Embedded,
+        details,
+    ))
+}
+
+/// Returns a texture of the given atlas page. The texture is (re)created if the page was modified.
+pub(crate) fn page_texture(page: &mut Page, page_size: u32) -> Option<TextureResource> {
+    if page.texture.is_none() || page.modified {
+        page.texture = Some(make_mask_texture(&page.pixels, page_size)?.into());
+        page.modified = false;
+    }
+    page.texture.as_ref()?.try_cast::<Texture>()
+}
+
+/// Generates a signed distance field from the given glyph coverage (one byte per pixel). The
+/// resulting field is larger than the source image by `spread` pixels on each side. `0.5` (`128`)
+/// in the field corresponds to the edge of the glyph, larger values are inside the glyph. Distances
+/// larger than `spread` are clamped.
+pub fn generate_distance_field(
+    coverage: &[u8],
+    width: usize,
+    height: usize,
+    spread: usize,
+) -> Vec<u8> {
+    let field_width = width + 2 * spread;
+    let field_height = height + 2 * spread;
+    let spread = spread.max(1) as isize;
+
+    let is_inside = |x: isize, y: isize| {
+        let (x, y) = (x - spread, y - spread);
+        x >= 0
+            && y >= 0
+            && (x as usize) < width
+            && (y as usize) < height
+            && coverage[y as usize * width + x as usize] >= 128
+    };
+
+    let mut field = vec![0; field_width * field_height];
+    for y in 0..field_height as isize {
+        for x in 0..field_width as isize {
+            let inside = is_inside(x, y);
+
+            // Find the closest pixel of the opposite state.
+            let mut closest_sqr = f32::MAX;
+            for dy in -spread..=spread {
+                for dx in -spread..=spread {
+                    if is_inside(x + dx, y + dy) != inside {
+                        closest_sqr = closest_sqr.min((dx * dx + dy * dy) as f32);
+                    }
+                }
+            }
+
+            // The edge is between pixel centers.
+            let distance = (closest_sqr.sqrt() - 0.5).min(spread as f32);
+            let signed = if inside { distance } else { -distance };
+            let value = (0.5 + signed / (2.0 * spread as f32)).clamp(0.0, 1.0);
+            field[y as usize * field_width + x as usize] = (value * 255.0).round() as u8;
+        }
+    }
+    field
+}
+
+/// A glyph in a distance field atlas. Metrics of the glyph are given for the base size of the atlas
+/// and include the spread of the field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DistanceFieldGlyph {
+    /// Horizontal offset of the glyph relative to the pen position.
+    pub bitmap_left: f32,
+    /// Vertical offset of the bottom of the glyph relative to the baseline.
+    pub bitmap_top: f32,
+    /// Width of the glyph.
+    pub bitmap_width: f32,
+    /// Height of the glyph.
+    pub bitmap_height: f32,
+    /// Texture coordinates of the corners of the glyph in the atlas page.
+    pub tex_coords: [Vector2<f32>; 4],
+    /// Index of the atlas page.
+    pub page_index: usize,
+}
+
+#[derive(Clone)]
+struct DistanceFieldPage {
+    pixels: Vec<u8>,
+    texture: Option<TextureResource>,
+    rect_packer: RectPacker<usize>,
+    modified: bool,
+}
+
+/// An atlas with glyphs stored as signed distance fields. Unlike bitmap atlases, that store glyphs
+/// for every requested size, it stores glyphs at a single base size, the glyphs then could be drawn
+/// at any size with sharp edges.
+#[derive(Clone)]
+pub struct DistanceFieldAtlas {
+    base_size: f32,
+    spread: usize,
+    page_size: usize,
+    pages: Vec<DistanceFieldPage>,
+    glyphs: FxHashMap<char, Option<DistanceFieldGlyph>>,
+}
+
+impl Debug for DistanceFieldAtlas {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DistanceFieldAtlas")
+            .field("BaseSize", &self.base_size)
+            .field("Spread", &self.spread)
+            .field("PageSize", &self.page_size)
+            .field("Pages", &self.pages.len())
+            .field("Glyphs", &self.glyphs.len())
+            .finish()
+    }
+}
+
+impl Default for DistanceFieldAtlas {
+    fn default() -> Self {
+        Self::new(48.0, 6, 1024)
+    }
+}
+
+impl DistanceFieldAtlas {
+    /// Creates a new atlas, that stores glyphs rasterized at the given base size (in pixels). The
+    /// spread defines the maximum distance (in pixels) stored in the field.
+    pub fn new(base_size: f32, spread: usize, page_size: usize) -> Self {
+        Self {
+            base_size,
+            spread: spread.max(1),
+            page_size,
+            pages: Default::default(),
+            glyphs: Default::default(),
+        }
+    }
+
+    /// Returns the size at which the glyphs are rasterized.
+    pub fn base_size(&self) -> f32 {
+        self.base_size
+    }
+
+    /// Returns the maximum distance (in pixels at the base size) stored in the field.
+    pub fn spread(&self) -> usize {
+        self.spread
+    }
+
+    /// Returns a glyph for the given character, generating its distance field if needed. [`None`]
+    /// is returned if the font has no such character or the glyph does not fit into a page. Such
+    /// characters should be drawn using bitmap atlases.
+    pub fn glyph(&mut self, font: &Font, character: char) -> Option<&DistanceFieldGlyph> {
+        if !self.glyphs.contains_key(&character) {
+            let glyph = self.render_glyph(font, character);
+            self.glyphs.insert(character, glyph);
+        }
+        self.glyphs.get(&character)?.as_ref()
+    }
+
+    fn render_glyph(&mut self, font: &Font, character: char) -> Option<DistanceFieldGlyph> {
+        let inner = font.inner.as_ref()?;
+        let char_index = inner.chars().get(&character)?.get();
+        let (metrics, raster) = inner.rasterize_indexed(char_index, self.base_size);
+
+        let spread = self.spread;
+        let field = generate_distance_field(&raster, metrics.width, metrics.height, spread);
+        let width = metrics.width + 2 * spread;
+        let height = metrics.height + 2 * spread;
+
+        let border = 2;
+        let mut placement = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+            page.rect_packer
+                .find_free(width + border, height + border)
+                .map(|rect| (index, rect))
+        });
+        if placement.is_none() {
+            let mut page = DistanceFieldPage {
+                pixels: vec![0; self.page_size * self.page_size],
+                texture: None,
+                rect_packer: RectPacker::new(self.page_size, self.page_size),
+                modified: true,
+            };
+            let rect = page
+                .rect_packer
+                .find_free(width + border, height + border)?;
+            placement = Some((self.pages.len(), rect));
+            self.pages.push(page);
+        }
+        let (page_index, rect) = placement?;
+
+        let page = &mut self.pages[page_index];
+        page.modified = true;
+
+        let x = rect.x() + border / 2;
+        let y = rect.y() + border / 2;
+        for row in 0..height {
+            let dest = (y + row) * self.page_size + x;
+            page.pixels[dest..dest + width].copy_from_slice(&field[row * width..(row + 1) * width]);
+        }
+
+        let k = 1.0 / self.page_size as f32;
+        let (tx, ty) = (x as f32 * k, y as f32 * k);
+        let (tw, th) = (width as f32 * k, height as f32 * k);
+
+        Some(DistanceFieldGlyph {
+            bitmap_left: metrics.xmin as f32 - spread as f32,
+            bitmap_top: metrics.ymin as f32 - spread as f32,
+            bitmap_width: width as f32,
+            bitmap_height: height as f32,
+            tex_coords: [
+                Vector2::new(tx, ty),
+                Vector2::new(tx + tw, ty),
+                Vector2::new(tx + tw, ty + th),
+                Vector2::new(tx, ty + th),
+            ],
+            page_index,
+        })
+    }
+
+    /// Returns a texture of the given page. The texture is (re)created if the page was modified.
+    pub fn page_texture(&mut self, page_index: usize) -> Option<TextureResource> {
+        let page_size = self.page_size as u32;
+        let page = self.pages.get_mut(page_index)?;
+        if page.texture.is_none() || page.modified {
+            page.texture = Some(make_mask_texture(&page.pixels, page_size)?);
+            page.modified = false;
+        }
+        page.texture.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector2, color::Color},
+        gui::font::BUILT_IN_FONT,
+        renderer::{
+            sprite_layer::{SpriteLayer, SpriteTextureMode},
+            text::{generate_distance_field, layout_text},
+        },
+    };
+
+    #[test]
+    fn test_text_produces_glyph_quads_from_atlas() {
+        let font = BUILT_IN_FONT.resource();
+        let quads = {
+            let mut state = font.state();
+            let font = state.data().unwrap();
+            layout_text(font, "Hi", 16.0, None)
+        };
+
+        assert_eq!(quads.len(), 2);
+        assert_eq!(quads[0].character, 'H');
+        assert_eq!(quads[1].character, 'i');
+        assert!(quads.iter().all(|quad| !quad.missing));
+        assert_ne!(quads[0].tex_coords, quads[1].tex_coords);
+        assert!(quads[1].bounds.x() > quads[0].bounds.x());
+
+        let mut layer = SpriteLayer::default();
+        layer.draw_text(Vector2::new(10.0, 20.0), "Hi", &font, 16.0, Color::WHITE);
+        assert_eq!(layer.sprites().len(), 2);
+        assert!(layer.sprites().iter().all(|sprite| sprite.texture.is_some()
+            && sprite.texture_mode == SpriteTextureMode::AlphaMask));
+        assert_eq!(layer.build_batches().len(), 1);
+    }
+
+    #[test]
+    fn test_line_wrapping_and_missing_glyphs() {
+        let font = BUILT_IN_FONT.resource();
+        let mut state = font.state();
+        let font = state.data().unwrap();
+
+        let single_line = layout_text(font, "Hi Hi", 16.0, None);
+        assert_eq!(single_line.len(), 4);
+        assert_eq!(single_line[0].bounds.y(), single_line[2].bounds.y());
+
+        let word_width = single_line[2].bounds.x();
+        let wrapped = layout_text(font, "Hi Hi", 16.0, Some(word_width));
+        assert_eq!(wrapped.len(), 4);
+        assert!(wrapped[2].bounds.y() > wrapped[0].bounds.y());
+        assert_eq!(wrapped[2].bounds.x(), wrapped[0].bounds.x());
+
+        let missing = layout_text(font, "H\u{E000}", 16.0, None);
+        assert_eq!(missing.len(), 2);
+        assert!(!missing[0].missing);
+        assert!(missing[1].missing);
+    }
+
+    // Samples the given single-channel image with bilinear filtering, the samples outside of the
+    // image are zero.
+    fn sample_bilinear(image: &[u8], width: usize, height: usize, x: f32, y: f32) -> f32 {
+        let texel = |x: isize, y: isize| {
+            if x < 0 || y < 0 || x as usize >= width || y as usize >= height {
+                0.0
+            } else {
+                image[y as usize * width + x as usize] as f32 / 255.0
+            }
+        };
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+        let top = texel(x0, y0) * (1.0 - tx) + texel(x0 + 1, y0) * tx;
+        let bottom = texel(x0, y0 + 1) * (1.0 - tx) + texel(x0 + 1, y0 + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+
+    // Renders the given image scaled by the given factor, the same way as GPU does with linear
+    // filtering.
+    fn capture_scaled(image: &[u8], width: usize, height: usize, scale: usize) -> Vec<f32> {
+        let mut capture = Vec::new();
+        for y in 0..height * scale {
+            for x in 0..width * scale {
+                let u = (x as f32 + 0.5) / scale as f32 - 0.5;
+                let v = (y as f32 + 0.5) / scale as f32 - 0.5;
+                capture.push(sample_bilinear(image, width, height, u, v));
+            }
+        }
+        capture
+    }
+
+    // Amount of pixels, that are neither inside, nor outside of a glyph. Lower values mean sharper
+    // edges.
+    fn blurred_pixel_count(capture: &[f32]) -> usize {
+        capture
+            .iter()
+            .filter(|alpha| **alpha > 0.1 && **alpha < 0.9)
+            .count()
+    }
+
+    // Coverage of a glyph reconstructed from its distance field, the same as the `smoothstep` in
+    // `sprite_layer.shader`.
+    fn distance_field_alpha(distance: f32, smoothing: f32) -> f32 {
+        let smoothing = smoothing.max(1.0e-4);
+        let t = ((distance - (0.5 - smoothing)) / (2.0 * smoothing)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    #[test]
+    fn test_distance_field_text_is_sharper_than_bitmap_when_scaled() {
+        let scale = 4;
+        let spread = 4;
+
+        let font = BUILT_IN_FONT.resource();
+        let state = font.state();
+        let inner = state.data_ref().unwrap().inner.as_ref().unwrap();
+        let (metrics, coverage) = inner.rasterize('H', 16.0);
+        let (width, height) = (metrics.width, metrics.height);
+
+        // Bitmap glyph, padded to the same size as the distance field.
+        let padded_width = width + 2 * spread;
+        let padded_height = height + 2 * spread;
+        let mut bitmap = vec![0; padded_width * padded_height];
+        for y in 0..height {
+            for x in 0..width {
+                bitmap[(y + spread) * padded_width + x + spread] = coverage[y * width + x];
+            }
+        }
+        let bitmap_capture = capture_scaled(&bitmap, padded_width, padded_height, scale);
+
+        let field = generate_distance_field(&coverage, width, height, spread);
+        assert_eq!(field.len(), padded_width * padded_height);
+        let distances = capture_scaled(&field, padded_width, padded_height, scale);
+        let capture_width = padded_width * scale;
+        let capture_height = padded_height * scale;
+        let mut sdf_capture = Vec::new();
+        for y in 0..capture_height {
+            for x in 0..capture_width {
+                // Screen-space derivative of the distance, the same as `fwidth` in the shader.
+                let d = distances[y * capture_width + x];
+                let dx = distances[y * capture_width + (x + 1).min(capture_width - 1)] - d;
+                let dy = distances[(y + 1).min(capture_height - 1) * capture_width + x] - d;
+                sdf_capture.push(distance_field_alpha(d, dx.abs() + dy.abs()));
+            }
+        }
+
+        // Both captures must show the same glyph.
+        let solid = |capture: &[f32]| capture.iter().filter(|a| **a >= 0.5).count() as f32;
+        let ratio = solid(&sdf_capture) / solid(&bitmap_capture);
+        assert!((0.8..1.25).contains(&ratio), "{ratio}");
+
+        let bitmap_blur = blurred_pixel_count(&bitmap_capture);
+        let sdf_blur = blurred_pixel_count(&sdf_capture);
+        assert!(sdf_blur < bitmap_blur, "{sdf_blur} >= {bitmap_blur}");
+    }
+
+    #[test]
+    fn test_distance_field_text_uses_distance_field_atlas() {
+        let font = BUILT_IN_FONT.resource();
+
+        let mut layer = SpriteLayer::default();
+        layer.draw_text_sdf(
+            Vector2::new(0.0, 0.0),
+            "Hi\u{E000}",
+            &font,
+            64.0,
+            Color::WHITE,
+            Default::default(),
+            None,
+        );
+
+        let sprites = layer.sprites();
+        // Two glyphs and four sides of the box of the missing glyph.
+        assert_eq!(sprites.len(), 6);
+        assert!(sprites[..2]
+            .iter()
+            .all(|sprite| matches!(sprite.texture_mode, SpriteTextureMode::DistanceField(_))));
+        assert_eq!(sprites[0].texture, sprites[1].texture);
+        assert!(sprites[2..].iter().all(|sprite| sprite.texture.is_none()));
+    }
+}
@@ -42,6 +42,21 @@
             kind: Texture(kind: Sampler3D, fallback: Volume),
             binding: 7
         ),
+        (
+            name: "fyrox_shadowCascade0",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 8
+        ),
+        (
+            name: "fyrox_shadowCascade1",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 9
+        ),
+        (
+            name: "fyrox_shadowCascade2",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 10
+        ),
         (
             name: "properties",
             kind: PropertyGroup([
@@ -115,6 +130,27 @@
             ]),
             binding: 5
         ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
+        (
+            name: "fyrox_lightsBlock",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 7
+        ),
+        (
+            name: "fyrox_shadowData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 8
+        ),
     ],
 
     passes: [
@@ -187,6 +223,14 @@
                         mat4 m2 = fyrox_boneMatrices.matrices[i2];
                         mat4 m3 = fyrox_boneMatrices.matrices[i3];
 
+                        if (fyrox_instanceData.useDualQuaternionSkinning)
+                        {
+                            m0 = S_DualQuaternionSkinningMatrix(m0, m1, m2, m3, boneWeights);
+                            m1 = m0;
+                            m2 = m0;
+                            m3 = m0;
+                        }
+
                         localPosition += m0 * inputPosition * boneWeights.x;
                         localPosition += m1 * inputPosition * boneWeights.y;
                         localPosition += m2 * inputPosition * boneWeights.z;
@@ -311,25 +355,38 @@
                 scissor_box: None
             ),
             vertex_shader:
-               r#"
+                r#"
                 layout(location = 0) in vec3 vertexPosition;
                 layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
                 layout(location = 4) in vec4 boneWeights;
                 layout(location = 5) in vec4 boneIndices;
+                layout(location = 6) in vec2 vertexSecondTexCoord;
 
                 out vec3 position;
+                out vec3 normal;
                 out vec2 texCoord;
+                out vec3 tangent;
+                out vec3 binormal;
+                out vec2 secondTexCoord;
 
                 void main()
                 {
                     vec4 localPosition = vec4(0);
+                    vec3 localNormal = vec3(0);
+                    vec3 localTangent = vec3(0);
 
                     vec4 inputPosition = vec4(vertexPosition, 1.0);
+                    vec3 inputNormal = vertexNormal;
+                    vec3 inputTangent = vertexTangent.xyz;
 
                     for (int i = 0; i < fyrox_instanceData.blendShapesCount; ++i) {
                         TBlendShapeOffsets offsets = S_FetchBlendShapeOffsets(blendShapesStorage, gl_VertexID, i);
                         float weight = fyrox_instanceData.blendShapesWeights[i / 4][i % 4];
                         inputPosition.xyz += offsets.position * weight;
+                        inputNormal += offsets.normal * weight;
+                        inputTangent += offsets.tangent * weight;
                     }
 
                     if (fyrox_instanceData.useSkeletalAnimation)
@@ -344,31 +401,106 @@
                         mat4 m2 = fyrox_boneMatrices.matrices[i2];
                         mat4 m3 = fyrox_boneMatrices.matrices[i3];
 
+                        if (fyrox_instanceData.useDualQuaternionSkinning)
+                        {
+                            m0 = S_DualQuaternionSkinningMatrix(m0, m1, m2, m3, boneWeights);
+                            m1 = m0;
+                            m2 = m0;
+                            m3 = m0;
+                        }
+
                         localPosition += m0 * inputPosition * boneWeights.x;
                         localPosition += m1 * inputPosition * boneWeights.y;
                         localPosition += m2 * inputPosition * boneWeights.z;
                         localPosition += m3 * inputPosition * boneWeights.w;
+
+                        localNormal += mat3(m0) * inputNormal * boneWeights.x;
+                        localNormal += mat3(m1) * inputNormal * boneWeights.y;
+                        localNormal += mat3(m2) * inputNormal * boneWeights.z;
+                        localNormal += mat3(m3) * inputNormal * boneWeights.w;
+
+                        localTangent += mat3(m0) * inputTangent * boneWeights.x;
+                        localTangent += mat3(m1) * inputTangent * boneWeights.y;
+                        localTangent += mat3(m2) * inputTangent * boneWeights.z;
+                        localTangent += mat3(m3) * inputTangent * boneWeights.w;
                     }
                     else
                     {
                         localPosition = inputPosition;
+                        localNormal = inputNormal;
+                        localTangent = inputTangent;
                     }
-                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+
+                    mat3 nm = mat3(fyrox_instanceData.worldMatrix);
+                    normal = normalize(nm * localNormal);
+                    tangent = normalize(nm * localTangent);
+                    binormal = normalize(vertexTangent.w * cross(normal, tangent));
                     texCoord = vertexTexCoord;
-                }
-               "#,
+                    position = vec3(fyrox_instanceData.worldMatrix * localPosition);
+                    secondTexCoord = vertexSecondTexCoord;
 
-           fragment_shader:
-               r#"
+                    gl_Position = fyrox_instanceData.worldViewProjection * localPosition;
+                }
+                "#,
+            fragment_shader:
+                r#"
                 out vec4 FragColor;
 
+                in vec3 position;
+                in vec3 normal;
                 in vec2 texCoord;
+                in vec3 tangent;
+                in vec3 binormal;
+                in vec2 secondTexCoord;
 
                 void main()
                 {
-                    FragColor = properties.diffuseColor * texture(diffuseTexture, texCoord);
+                    mat3 tangentSpace = mat3(tangent, binormal, normal);
+                    vec2 tc = texCoord * properties.texCoordScale;
+
+                    vec4 diffuseColor = properties.diffuseColor * S_SRGBToLinear(texture(diffuseTexture, tc));
+
+                    TPBRContext ctx;
+                    ctx.albedo = diffuseColor.rgb;
+                    ctx.fragmentNormal = normalize(tangentSpace * (texture(normalTexture, tc).xyz * 2.0 - 1.0));
+                    ctx.metallic = properties.metallicFactor * texture(metallicRoughnessTexture, tc).b;
+                    ctx.roughness = properties.roughnessFactor * texture(metallicRoughnessTexture, tc).g;
+                    ctx.viewVector = normalize(fyrox_cameraData.position - position);
+
+                    vec3 ambient = fyrox_lightData.ambientLightColor.rgb
+                        + properties.emissionStrength * texture(emissionTexture, tc).rgb
+                        + texture(lightmapTexture, secondTexCoord).rgb;
+                    vec3 lighting = ambient * ctx.albedo * texture(aoTexture, tc).r;
+
+                    float viewDepth = max(-(fyrox_cameraData.viewMatrix * vec4(position, 1.0)).z, 0.0);
+
+                    for (int i = 0; i < min(fyrox_lightsBlock.lightCount, 16); ++i) {
+                        vec4 colorRadius = fyrox_lightsBlock.lightsColorRadius[i];
+                        colorRadius.rgb = S_SRGBToLinear(vec4(colorRadius.rgb, 1.0)).rgb * fyrox_lightsBlock.lightsIntensity[i];
+                        vec3 light = S_ForwardLight(ctx, position, fyrox_lightsBlock.lightsPosition[i],
+                            fyrox_lightsBlock.lightsDirection[i], colorRadius, fyrox_lightsBlock.lightsParameters[i],
+                            fyrox_lightsBlock.lightsAreaSide[i], fyrox_lightsBlock.lightsAreaLook[i]);
+                        if (i == fyrox_shadowData.shadowLightIndex) {
+                            light *= S_CsmShadowFactor(fyrox_shadowData.softShadows, fyrox_shadowData.shadowBias,
+                                position, viewDepth, fyrox_shadowData.lightViewProjMatrices,
+                                fyrox_shadowData.cascadeDistances, fyrox_shadowCascade0, fyrox_shadowCascade1,
+                                fyrox_shadowCascade2);
+                        }
+                        lighting += light;
+                    }
+
+                    FragColor = vec4(lighting, diffuseColor.a);
+
+                    if (fyrox_fogData.fogEnabled) {
+                        float fogFactor = S_FogFactor(fyrox_fogData.fogMode, fyrox_fogData.fogStart, fyrox_fogData.fogEnd, fyrox_fogData.fogDensity, viewDepth);
+                        if (fyrox_fogData.heightFogEnabled) {
+                            float heightFog = S_HeightFog(fyrox_cameraData.position, position, fyrox_fogData.heightFogBaseHeight, fyrox_fogData.heightFogFalloff, fyrox_fogData.heightFogDensity);
+                            fogFactor = 1.0 - (1.0 - fogFactor) * (1.0 - heightFog);
+                        }
+                        FragColor.rgb = mix(FragColor.rgb, fyrox_fogData.fogColor.rgb, fogFactor);
+                    }
                 }
-               "#,
+                "#,
         ),
         (
             name: "DirectionalShadow",
@@ -424,6 +556,14 @@
                         mat4 m2 = fyrox_boneMatrices.matrices[int(boneIndices.z)];
                         mat4 m3 = fyrox_boneMatrices.matrices[int(boneIndices.w)];
 
+                        if (fyrox_instanceData.useDualQuaternionSkinning)
+                        {
+                            m0 = S_DualQuaternionSkinningMatrix(m0, m1, m2, m3, boneWeights);
+                            m1 = m0;
+                            m2 = m0;
+                            m3 = m0;
+                        }
+
                         localPosition += m0 * inputPosition * boneWeights.x;
                         localPosition += m1 * inputPosition * boneWeights.y;
                         localPosition += m2 * inputPosition * boneWeights.z;
@@ -503,6 +643,14 @@
                         mat4 m2 = fyrox_boneMatrices.matrices[int(boneIndices.z)];
                         mat4 m3 = fyrox_boneMatrices.matrices[int(boneIndices.w)];
 
+                        if (fyrox_instanceData.useDualQuaternionSkinning)
+                        {
+                            m0 = S_DualQuaternionSkinningMatrix(m0, m1, m2, m3, boneWeights);
+                            m1 = m0;
+                            m2 = m0;
+                            m3 = m0;
+                        }
+
                         localPosition += m0 * inputPosition * boneWeights.x;
                         localPosition += m1 * inputPosition * boneWeights.y;
                         localPosition += m2 * inputPosition * boneWeights.z;
@@ -583,6 +731,14 @@
                         mat4 m2 = fyrox_boneMatrices.matrices[int(boneIndices.z)];
                         mat4 m3 = fyrox_boneMatrices.matrices[int(boneIndices.w)];
 
+                        if (fyrox_instanceData.useDualQuaternionSkinning)
+                        {
+                            m0 = S_DualQuaternionSkinningMatrix(m0, m1, m2, m3, boneWeights);
+                            m1 = m0;
+                            m2 = m0;
+                            m3 = m0;
+                        }
+
                         localPosition += m0 * inputPosition * boneWeights.x;
                         localPosition += m1 * inputPosition * boneWeights.y;
                         localPosition += m2 * inputPosition * boneWeights.z;
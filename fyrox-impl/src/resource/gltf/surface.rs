@@ -249,15 +249,7 @@ pub fn build_surface_data(
     let tris: TriangleBuffer = build_triangle_data(primitive, vs.vertex_count(), buffers)?;
     #[cfg(feature = "mesh_analysis")]
     update_statistics(&vs, &tris, stats)?;
-    let morphs: Vec<InputBlendShapeData> = build_morph_data(primitive, morph_info, buffers)?;
-    let mut surf = if !morphs.is_empty() {
-        let shapes = mesh::surface::BlendShapesContainer::from_lists(&vs, morphs.as_slice());
-        let mut surf = SurfaceData::new(vs, tris);
-        surf.blend_shapes_container = Some(shapes);
-        surf
-    } else {
-        SurfaceData::new(vs, tris)
-    };
+    let mut surf = SurfaceData::new(vs, tris);
     let has_tex = primitive.get(&Semantic::TexCoords(0)).is_some();
     let has_norm = primitive.get(&Semantic::Normals).is_some();
     let has_tang = primitive.get(&Semantic::Tangents).is_some();
@@ -267,6 +259,13 @@ pub fn build_surface_data(
     } else if has_tex && has_norm && !has_tang {
         surf.calculate_tangents()?;
     }
+    // Blend shapes are bound to vertex indices, so they must be built from the final vertices.
+    let morphs: Vec<InputBlendShapeData> = build_morph_data(primitive, morph_info, buffers)?;
+    if !morphs.is_empty() {
+        let shapes =
+            mesh::surface::BlendShapesContainer::from_lists(&surf.vertex_buffer, morphs.as_slice());
+        surf.blend_shapes_container = Some(shapes);
+    }
     Ok(Some(surf))
 }
 
@@ -450,7 +449,9 @@ where
     if reader.read_weights(0).is_some() {
         let vs: Vec<AnimatedVertex> = AnimatedVertex::convert(primitive, get_buffer_data)?;
         Ok(VertexBuffer::new(vs.len(), vs)?)
-    } else if reader.read_normals().is_some() {
+    } else if reader.read_normals().is_some() || reader.read_tex_coords(0).is_some() {
+        // Missing normals of textured meshes are calculated later, so they need a vertex with
+        // normals and tangents.
         let vs: Vec<StaticVertex> = StaticVertex::convert(primitive, get_buffer_data)?;
         Ok(VertexBuffer::new(vs.len(), vs)?)
     } else {
@@ -606,3 +607,102 @@ fn read_valid_index(reader: &mut ReadJoints) -> Result<Option<[u8; 4]>> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        resource::gltf::import_morph_info,
+        scene::mesh::buffer::{VertexAttributeUsage, VertexReadTrait},
+    };
+
+    fn f32_bytes(values: &[f32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn test_import_morph_targets() {
+        // A bent quad without normals, so the normals are calculated on import.
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 1.0],
+        ];
+        let offsets = [
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [0.0, 0.0, 2.0],
+            [0.0, 0.0, 3.0],
+        ];
+        let mut buffer = f32_bytes(positions.as_flattened());
+        buffer.extend(f32_bytes(&[0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]));
+        buffer.extend(f32_bytes(offsets.as_flattened()));
+        buffer.extend([0u16, 1, 2, 0, 2, 3].iter().flat_map(|i| i.to_le_bytes()));
+
+        let json = format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "buffers": [{{ "byteLength": {} }}],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+                    {{ "buffer": 0, "byteOffset": 48, "byteLength": 32 }},
+                    {{ "buffer": 0, "byteOffset": 80, "byteLength": 48 }},
+                    {{ "buffer": 0, "byteOffset": 128, "byteLength": 12 }}
+                ],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3",
+                       "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 1.0] }},
+                    {{ "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC2" }},
+                    {{ "bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC3",
+                       "min": [0.0, 0.0, 0.0], "max": [0.0, 0.0, 3.0] }},
+                    {{ "bufferView": 3, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+                ],
+                "meshes": [{{
+                    "primitives": [{{
+                        "attributes": {{ "POSITION": 0, "TEXCOORD_0": 1 }},
+                        "indices": 3,
+                        "targets": [{{ "POSITION": 2 }}]
+                    }}],
+                    "weights": [0.5],
+                    "extras": {{ "targetNames": ["Bend"] }}
+                }}]
+            }}"#,
+            buffer.len()
+        );
+        let gltf = gltf::Gltf::from_slice(json.as_bytes()).unwrap();
+        let mesh = gltf.meshes().next().unwrap();
+        let morph_info = import_morph_info(&mesh).unwrap();
+        let primitive = mesh.primitives().next().unwrap();
+        let surf = build_surface_data(
+            &primitive,
+            &morph_info,
+            &[buffer],
+            &mut GeometryStatistics::default(),
+        )
+        .unwrap()
+        .unwrap();
+
+        // Normals are calculated without changing the vertices, the blend shape is still bound to
+        // the same vertices.
+        assert_eq!(surf.vertex_buffer.vertex_count(), 4);
+        for vertex in surf.vertex_buffer.iter() {
+            let normal = vertex.read_3_f32(VertexAttributeUsage::Normal).unwrap();
+            assert!((normal.norm() - 1.0).abs() < 1.0e-5);
+        }
+
+        let container = surf.blend_shapes_container.as_ref().unwrap();
+        assert_eq!(container.blend_shapes.len(), 1);
+        assert_eq!(container.blend_shapes[0].name, "Bend");
+        assert_eq!(container.blend_shapes[0].weight, 50.0);
+
+        let blended = surf.blend_morph_targets(&[1.0]).unwrap();
+        for (i, vertex) in blended.iter().enumerate() {
+            let expected = Vector3::from(positions[i]) + Vector3::from(offsets[i]);
+            assert_eq!(
+                vertex.read_3_f32(VertexAttributeUsage::Position).unwrap(),
+                expected
+            );
+        }
+    }
+}
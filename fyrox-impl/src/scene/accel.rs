@@ -30,13 +30,22 @@ use crate::{
     scene::{graph::Graph, node::Node},
 };
 
-#[allow(dead_code)]
 #[derive(Clone, Debug)]
 pub struct Entry {
     node: Handle<Node>,
     world_aabb: AxisAlignedBoundingBox,
 }
 
+impl Entry {
+    pub fn node(&self) -> Handle<Node> {
+        self.node
+    }
+
+    pub fn world_aabb(&self) -> &AxisAlignedBoundingBox {
+        &self.world_aabb
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum OctreeNode {
     Leaf {
@@ -77,14 +86,16 @@ impl Octree {
 
         let mut nodes = Pool::new();
 
-        let root = build_recursive(&mut nodes, entries, bounds, split_threshold);
+        let root = build_recursive(&mut nodes, entries, bounds, split_threshold, 0);
 
         Self { nodes, root }
     }
 
     pub fn sphere_query(&self, position: Vector3<f32>, radius: f32, buffer: &mut Vec<Entry>) {
         buffer.clear();
-        self.sphere_recursive_query(self.root, position, radius, buffer);
+        if self.root.is_some() {
+            self.sphere_recursive_query(self.root, position, radius, buffer);
+        }
     }
 
     fn sphere_recursive_query(
@@ -112,7 +123,9 @@ impl Octree {
 
     pub fn aabb_query(&self, aabb: &AxisAlignedBoundingBox, buffer: &mut Vec<Entry>) {
         buffer.clear();
-        self.aabb_recursive_query(self.root, aabb, buffer);
+        if self.root.is_some() {
+            self.aabb_recursive_query(self.root, aabb, buffer);
+        }
     }
 
     fn aabb_recursive_query(
@@ -147,7 +160,9 @@ impl Octree {
 
     pub fn point_query(&self, point: Vector3<f32>, buffer: &mut Vec<Entry>) {
         buffer.clear();
-        self.point_recursive_query(self.root, point, buffer);
+        if self.root.is_some() {
+            self.point_recursive_query(self.root, point, buffer);
+        }
     }
 
     fn point_recursive_query(
@@ -173,13 +188,17 @@ impl Octree {
     }
 }
 
+// Overlapping entries cannot be separated by splitting, so the depth must be limited.
+const MAX_DEPTH: usize = 12;
+
 fn build_recursive(
     nodes: &mut Pool<OctreeNode>,
     entries: Vec<Entry>,
     bounds: AxisAlignedBoundingBox,
     split_threshold: usize,
+    depth: usize,
 ) -> Handle<OctreeNode> {
-    if entries.len() <= split_threshold {
+    if entries.len() <= split_threshold || depth >= MAX_DEPTH {
         nodes.spawn(OctreeNode::Leaf { bounds, entries })
     } else {
         let mut leaves = [Handle::NONE; 8];
@@ -192,11 +211,11 @@ fn build_recursive(
             leaf_entries.extend(
                 entries
                     .iter()
-                    .filter(|entry| entry.world_aabb.is_intersects_aabb(&bounds))
+                    .filter(|entry| entry.world_aabb.is_intersects_aabb(&leaf_bounds))
                     .cloned(),
             );
 
-            *leaf = build_recursive(nodes, leaf_entries, leaf_bounds, split_threshold);
+            *leaf = build_recursive(nodes, leaf_entries, leaf_bounds, split_threshold, depth + 1);
         }
 
         nodes.spawn(OctreeNode::Branch { leaves, bounds })
@@ -319,7 +319,7 @@ impl NodeTrait for AnimationBlendingStateMachine {
 
             let pose = self.machine.get_value_mut_silent().evaluate_pose(
                 animation_player.animations.get_value_mut_silent(),
-                context.dt,
+                context.dt * context.animation_time_scale,
             );
 
             pose.apply_internal(context.nodes);
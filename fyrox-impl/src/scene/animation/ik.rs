@@ -0,0 +1,435 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Inverse kinematics solvers. They modify local rotations of bones of a hierarchy so the end of a chain
+//! of bones reaches a target. See [`TwoBoneIkSolver`] and [`FabrikSolver`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    scene::{graph::Graph, node::Node},
+};
+
+const EPSILON: f32 = 1.0e-6;
+
+/// Calculates rotational part of the global transform of a node.
+fn global_rotation(graph: &Graph, node: Handle<Node>) -> UnitQuaternion<f32> {
+    graph
+        .try_get_node(node)
+        .map(|node| {
+            UnitQuaternion::from_matrix_eps(
+                &node.global_transform().basis(),
+                10.0 * f32::EPSILON,
+                16,
+                Default::default(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Rotates a bone around its origin so its direction `from` (in world space) will match the direction `to`.
+/// Global transforms of the bone and its descendants are updated after that.
+fn rotate_bone(graph: &mut Graph, bone: Handle<Node>, from: Vector3<f32>, to: Vector3<f32>) {
+    let Some(rotation) = UnitQuaternion::rotation_between(&from, &to) else {
+        return;
+    };
+
+    let Ok(bone_ref) = graph.try_get_node(bone) else {
+        return;
+    };
+    let transform = bone_ref.local_transform();
+    let local_rotation = **transform.rotation();
+    let pre_rotation = **transform.pre_rotation();
+    let parent_rotation = global_rotation(graph, bone_ref.parent()) * pre_rotation;
+
+    graph[bone]
+        .local_transform_mut()
+        .set_rotation(parent_rotation.inverse() * rotation * parent_rotation * local_rotation);
+    graph.update_hierarchical_data_for_descendants(bone);
+}
+
+/// Returns any unit vector perpendicular to the given one.
+fn any_perpendicular(v: Vector3<f32>) -> Vector3<f32> {
+    let axis = if v.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    v.cross(&axis).normalize()
+}
+
+/// Two-bone inverse kinematics solver. It is used for limbs, such as arms and legs, to make the end of
+/// the limb (a hand or a foot) reach a target. The chain consists of three bones: root (shoulder, hip),
+/// mid (elbow, knee) and end (wrist, ankle). The solver rotates the root and the mid bones, the end bone
+/// keeps its local rotation.
+///
+/// Unreachable targets are handled by fully extending the chain toward them.
+///
+/// ## Important notes
+///
+/// The solver uses global transforms of the bones, they must be up-to-date before solving. The solver
+/// updates global transforms of the chain and all its descendants, so it is possible to chain multiple
+/// solvers one after another.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TwoBoneIkSolver {
+    /// A handle of the root bone of the chain.
+    pub root: Handle<Node>,
+    /// A handle of the middle bone of the chain.
+    pub mid: Handle<Node>,
+    /// A handle of the end bone of the chain (end effector).
+    pub end: Handle<Node>,
+    /// Target position in world space.
+    pub target: Vector3<f32>,
+    /// A point in world space that defines the direction in which the middle joint bends.
+    pub pole: Vector3<f32>,
+}
+
+impl TwoBoneIkSolver {
+    /// Modifies local rotations of the root and the middle bones, so the end bone reaches the target.
+    /// Returns `false` if any of the bones does not exist or the chain is degenerate.
+    pub fn solve(&self, graph: &mut Graph) -> bool {
+        let (Ok(root), Ok(mid), Ok(end)) = (
+            graph.try_get_node(self.root),
+            graph.try_get_node(self.mid),
+            graph.try_get_node(self.end),
+        ) else {
+            return false;
+        };
+
+        let a = root.global_position();
+        let b = mid.global_position();
+        let c = end.global_position();
+
+        let upper_length = b.metric_distance(&a);
+        let lower_length = c.metric_distance(&b);
+        if upper_length <= EPSILON || lower_length <= EPSILON {
+            return false;
+        }
+
+        let to_target = self.target - a;
+        let target_distance = to_target.norm();
+        let direction = if target_distance > EPSILON {
+            to_target.scale(1.0 / target_distance)
+        } else {
+            (c - a)
+                .try_normalize(EPSILON)
+                .unwrap_or_else(|| any_perpendicular(b - a))
+        };
+
+        // Unreachable targets are clamped, so the chain will be fully extended toward them.
+        let distance = target_distance.clamp(
+            (upper_length - lower_length).abs(),
+            upper_length + lower_length,
+        );
+
+        // Find the direction of bending using the pole, fallback to the current bending direction.
+        let bend_direction = [self.pole - a, b - a]
+            .into_iter()
+            .find_map(|v| (v - direction.scale(v.dot(&direction))).try_normalize(EPSILON))
+            .unwrap_or_else(|| any_perpendicular(direction));
+
+        // Law of cosines gives the angle between the upper bone and the direction to the target.
+        let cos_angle = ((upper_length * upper_length + distance * distance
+            - lower_length * lower_length)
+            / (2.0 * upper_length * distance.max(EPSILON)))
+        .clamp(-1.0, 1.0);
+        let sin_angle = (1.0 - cos_angle * cos_angle).sqrt();
+
+        let new_mid =
+            a + (direction.scale(cos_angle) + bend_direction.scale(sin_angle)).scale(upper_length);
+        let new_end = a + direction.scale(distance);
+
+        rotate_bone(graph, self.root, b - a, new_mid - a);
+
+        // The middle and the end bones has moved together with the root bone.
+        let (Ok(mid), Ok(end)) = (graph.try_get_node(self.mid), graph.try_get_node(self.end))
+        else {
+            return false;
+        };
+        let b = mid.global_position();
+        let c = end.global_position();
+
+        rotate_bone(graph, self.mid, c - b, new_end - b);
+
+        true
+    }
+}
+
+/// Angular constraint of a joint of a [`FabrikSolver`] chain.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JointConstraint {
+    /// Maximum angle (in radians) between a bone and its previous bone in the chain.
+    pub max_angle: f32,
+}
+
+impl Default for JointConstraint {
+    fn default() -> Self {
+        Self {
+            max_angle: std::f32::consts::PI,
+        }
+    }
+}
+
+/// Multi-bone inverse kinematics solver, that uses FABRIK (Forward And Backward Reaching Inverse Kinematics)
+/// algorithm. It is suitable for long chains of bones, such as spines, tails or tentacles. The solver
+/// iteratively moves joint positions of the chain toward the target and back to the root, then rotates
+/// the bones to match the new joint positions.
+///
+/// Unreachable targets are handled by fully extending the chain toward them. If the solver is unable to
+/// reach the target within the tolerance (for example, because of the constraints), it stops after the
+/// maximum number of iterations with the closest solution found.
+///
+/// ## Important notes
+///
+/// The solver uses global transforms of the bones, they must be up-to-date before solving.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FabrikSolver {
+    /// Ordered list of bones of the chain, starting from the root bone and ending with the end effector.
+    pub bones: Vec<Handle<Node>>,
+    /// Target position in world space.
+    pub target: Vector3<f32>,
+    /// Constraints of the joints, the i-th constraint limits the angle between the i-th and the previous
+    /// bone. Joints without constraints are unconstrained.
+    pub constraints: Vec<JointConstraint>,
+    /// Maximum number of iterations of the algorithm.
+    pub max_iterations: usize,
+    /// Maximum distance between the end effector and the target, at which the chain is considered solved.
+    pub tolerance: f32,
+}
+
+impl Default for FabrikSolver {
+    fn default() -> Self {
+        Self {
+            bones: Default::default(),
+            target: Default::default(),
+            constraints: Default::default(),
+            max_iterations: 16,
+            tolerance: 0.001,
+        }
+    }
+}
+
+impl FabrikSolver {
+    fn constrain(&self, joints: &mut [Vector3<f32>], lengths: &[f32], index: usize) {
+        let Some(constraint) = self.constraints.get(index) else {
+            return;
+        };
+        if index == 0 || index + 1 >= joints.len() {
+            return;
+        }
+
+        let prev_direction = joints[index] - joints[index - 1];
+        let direction = joints[index + 1] - joints[index];
+        let angle = prev_direction.angle(&direction);
+        if angle > constraint.max_angle {
+            if let (Some(rotation), Some(prev_direction)) = (
+                UnitQuaternion::rotation_between(&prev_direction, &direction),
+                prev_direction.try_normalize(EPSILON),
+            ) {
+                let limited = rotation.powf(constraint.max_angle / angle);
+                joints[index + 1] =
+                    joints[index] + (limited * prev_direction).scale(lengths[index]);
+            }
+        }
+    }
+
+    /// Modifies local rotations of the bones of the chain, so the end effector reaches the target. Returns
+    /// the final distance between the end effector and the target, or `None` if the chain is invalid.
+    pub fn solve(&self, graph: &mut Graph) -> Option<f32> {
+        if self.bones.len() < 2 {
+            return None;
+        }
+
+        let mut joints = self
+            .bones
+            .iter()
+            .map(|bone| graph.try_get_node(*bone).map(|bone| bone.global_position()))
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        let lengths = joints
+            .windows(2)
+            .map(|pair| pair[1].metric_distance(&pair[0]))
+            .collect::<Vec<_>>();
+
+        let root = joints[0];
+        let last = joints.len() - 1;
+
+        if self.target.metric_distance(&root) >= lengths.iter().sum::<f32>() {
+            // Unreachable target, fully extend the chain toward it.
+            let direction = (self.target - root)
+                .try_normalize(EPSILON)
+                .unwrap_or_else(Vector3::y);
+            for i in 0..last {
+                joints[i + 1] = joints[i] + direction.scale(lengths[i]);
+            }
+        } else {
+            for _ in 0..self.max_iterations {
+                if joints[last].metric_distance(&self.target) <= self.tolerance {
+                    break;
+                }
+
+                // Backward pass - from the end effector to the root.
+                joints[last] = self.target;
+                for i in (0..last).rev() {
+                    let direction = (joints[i] - joints[i + 1])
+                        .try_normalize(EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    joints[i] = joints[i + 1] + direction.scale(lengths[i]);
+                }
+
+                // Forward pass - from the root to the end effector.
+                joints[0] = root;
+                for i in 0..last {
+                    let direction = (joints[i + 1] - joints[i])
+                        .try_normalize(EPSILON)
+                        .unwrap_or_else(Vector3::y);
+                    joints[i + 1] = joints[i] + direction.scale(lengths[i]);
+                    self.constrain(&mut joints, &lengths, i);
+                }
+            }
+        }
+
+        // Rotate the bones to match the new joint positions.
+        for i in 0..last {
+            let current = graph.try_get_node(self.bones[i]).ok()?.global_position();
+            let next = graph
+                .try_get_node(self.bones[i + 1])
+                .ok()?
+                .global_position();
+            rotate_bone(
+                graph,
+                self.bones[i],
+                next - current,
+                joints[i + 1] - current,
+            );
+        }
+
+        graph
+            .try_get_node(self.bones[last])
+            .ok()
+            .map(|end| end.global_position().metric_distance(&self.target))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle},
+        graph::SceneGraph,
+        scene::{
+            animation::ik::{FabrikSolver, JointConstraint, TwoBoneIkSolver},
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_bone(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        children: &[Handle<Node>],
+    ) -> Handle<Node> {
+        let mut base = BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(position)
+                .build(),
+        );
+        for child in children {
+            base = base.with_child(*child);
+        }
+        PivotBuilder::new(base).build(graph)
+    }
+
+    #[test]
+    fn test_two_bone_ik() {
+        let mut graph = Graph::new();
+        let end = make_bone(&mut graph, Vector3::new(0.0, 1.0, 0.0), &[]);
+        let mid = make_bone(&mut graph, Vector3::new(0.0, 1.0, 0.0), &[end]);
+        let root = make_bone(&mut graph, Vector3::new(1.0, 0.0, 0.0), &[mid]);
+        graph.update_hierarchical_data();
+
+        let mut solver = TwoBoneIkSolver {
+            root,
+            mid,
+            end,
+            target: Vector3::new(2.0, 1.0, 1.0),
+            pole: Vector3::new(1.0, 0.0, 5.0),
+        };
+        assert!(solver.solve(&mut graph));
+        graph.update_hierarchical_data();
+        assert!(graph[end].global_position().metric_distance(&solver.target) < 0.001);
+        // Upper and lower parts of the arm must keep their lengths.
+        assert!(
+            (graph[mid]
+                .global_position()
+                .metric_distance(&graph[root].global_position())
+                - 1.0)
+                .abs()
+                < 0.001
+        );
+
+        // Unreachable target, the chain must be fully extended toward it.
+        solver.target = Vector3::new(1.0, 0.0, -10.0);
+        assert!(solver.solve(&mut graph));
+        graph.update_hierarchical_data();
+        assert!(
+            graph[end]
+                .global_position()
+                .metric_distance(&Vector3::new(1.0, 0.0, -2.0))
+                < 0.001
+        );
+    }
+
+    #[test]
+    fn test_fabrik() {
+        let mut graph = Graph::new();
+        let mut bones = vec![make_bone(&mut graph, Vector3::new(0.0, 1.0, 0.0), &[])];
+        for _ in 0..3 {
+            let child = *bones.last().unwrap();
+            bones.push(make_bone(&mut graph, Vector3::new(0.0, 1.0, 0.0), &[child]));
+        }
+        bones.reverse();
+        graph.update_hierarchical_data();
+
+        let mut solver = FabrikSolver {
+            bones: bones.clone(),
+            target: Vector3::new(1.5, 2.5, 0.5),
+            max_iterations: 64,
+            tolerance: 0.001,
+            ..Default::default()
+        };
+        let error = solver.solve(&mut graph).unwrap();
+        assert!(error <= solver.tolerance);
+        graph.update_hierarchical_data();
+        let end = *bones.last().unwrap();
+        assert!(graph[end].global_position().metric_distance(&solver.target) <= solver.tolerance);
+
+        // Constrained chain cannot bend enough to reach the target, the closest solution must be used.
+        solver.target = Vector3::new(0.0, 1.0, 0.5);
+        solver.constraints = vec![JointConstraint { max_angle: 0.1 }; bones.len()];
+        let error = solver.solve(&mut graph).unwrap();
+        assert!(error > solver.tolerance);
+    }
+}
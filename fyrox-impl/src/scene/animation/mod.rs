@@ -24,6 +24,7 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
+        algebra::Vector3,
         log::{Log, MessageKind},
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
@@ -33,7 +34,10 @@ use crate::{
         variable::InheritableVariable,
         visitor::prelude::*,
     },
-    generic_animation::value::{BoundValueCollection, TrackValue, ValueBinding},
+    generic_animation::{
+        value::{BoundValue, BoundValueCollection, TrackValue, ValueBinding},
+        RootMotion,
+    },
     scene::{
         base::{Base, BaseBuilder},
         graph::{Graph, NodePool},
@@ -45,6 +49,9 @@ use fyrox_graph::SceneGraph;
 use std::ops::{Deref, DerefMut};
 
 pub mod absm;
+pub mod ik;
+pub mod retarget;
+pub mod sequence;
 pub mod spritesheet;
 
 /// Scene specific animation.
@@ -57,16 +64,22 @@ pub type AnimationContainer = crate::generic_animation::AnimationContainer<Handl
 pub type AnimationPose = crate::generic_animation::AnimationPose<Handle<Node>>;
 /// Scene specific animation node pose.
 pub type NodePose = crate::generic_animation::NodePose<Handle<Node>>;
+/// Scene specific pose cross-fade.
+pub type PoseCrossFade = crate::generic_animation::PoseCrossFade<Handle<Node>>;
+/// Scene specific property track.
+pub type PropertyTrack = crate::generic_animation::property::PropertyTrack<Handle<Node>>;
 
 /// Standard prelude for animations, that contains all most commonly used types and traits.
 pub mod prelude {
+    pub use super::sequence::{Sequence, SequenceClip, SequencePlayer, SequencePlayerBuilder};
     pub use super::{
         Animation, AnimationContainer, AnimationContainerExt, AnimationPlayer,
         AnimationPlayerBuilder, AnimationPose, AnimationPoseExt, BoundValueCollectionExt, NodePose,
-        Track,
+        PoseCrossFade, PropertyTrack, PropertyTrackExt, RootMotionExt, Track,
     };
     pub use crate::generic_animation::{
         container::{TrackDataContainer, TrackValueKind},
+        property::PropertyTrackInterpolation,
         signal::AnimationSignal,
         value::{BoundValueCollection, TrackValue, ValueBinding, ValueType},
         AnimationEvent,
@@ -139,6 +152,54 @@ impl AnimationPoseExt for AnimationPose {
     }
 }
 
+/// Extension trait for [`RootMotion`].
+pub trait RootMotionExt {
+    /// Transforms relative movement of a root node into world space using global transform of the given
+    /// `model` (usually a root node of an animated hierarchy). The result could be used to drive a character
+    /// controller, for example as a velocity of a rigid body (do not forget to divide it by the time step).
+    fn world_space_delta_position(&self, graph: &Graph, model: Handle<Node>) -> Vector3<f32>;
+
+    /// Moves and rotates the given `character` node using the root motion, instead of moving the root bone.
+    /// The visual model must be a descendant of the character, so it will follow the character. The
+    /// movement is converted to the local space of the parent of the character.
+    fn apply_to_character(&self, graph: &mut Graph, model: Handle<Node>, character: Handle<Node>);
+}
+
+impl RootMotionExt for RootMotion {
+    fn world_space_delta_position(&self, graph: &Graph, model: Handle<Node>) -> Vector3<f32> {
+        graph
+            .try_get_node(model)
+            .map(|model| {
+                model
+                    .global_transform()
+                    .transform_vector(&self.delta_position)
+            })
+            .unwrap_or_default()
+    }
+
+    fn apply_to_character(&self, graph: &mut Graph, model: Handle<Node>, character: Handle<Node>) {
+        let world_space_delta = self.world_space_delta_position(graph, model);
+
+        let Ok(character_ref) = graph.try_get_node(character) else {
+            return;
+        };
+        let local_delta = graph
+            .try_get_node(character_ref.parent())
+            .ok()
+            .and_then(|parent| parent.global_transform().try_inverse())
+            .map(|inv_parent_transform| inv_parent_transform.transform_vector(&world_space_delta))
+            .unwrap_or(world_space_delta);
+
+        if let Ok(character_ref) = graph.try_get_node_mut(character) {
+            let transform = character_ref.local_transform_mut();
+            let rotation = **transform.rotation();
+            transform
+                .offset(local_delta)
+                .set_rotation(rotation * self.delta_rotation);
+        }
+    }
+}
+
 /// Extension trait for [`BoundValueCollection`].
 pub trait BoundValueCollectionExt {
     /// Tries to set each value from the collection to the respective property (by binding) of the given scene node.
@@ -148,34 +209,57 @@ pub trait BoundValueCollectionExt {
 impl BoundValueCollectionExt for BoundValueCollection {
     fn apply(&self, node_ref: &mut Node) {
         for bound_value in self.values.iter() {
-            match bound_value.binding {
-                ValueBinding::Position => {
-                    if let TrackValue::Vector3(v) = bound_value.value {
-                        node_ref.local_transform_mut().set_position(v);
-                    } else {
-                        Log::err(
-                            "Unable to apply position, because underlying type is not Vector3!",
-                        )
-                    }
-                }
-                ValueBinding::Scale => {
-                    if let TrackValue::Vector3(v) = bound_value.value {
-                        node_ref.local_transform_mut().set_scale(v);
-                    } else {
-                        Log::err("Unable to apply scaling, because underlying type is not Vector3!")
-                    }
-                }
-                ValueBinding::Rotation => {
-                    if let TrackValue::UnitQuaternion(v) = bound_value.value {
-                        node_ref.local_transform_mut().set_rotation(v);
-                    } else {
-                        Log::err("Unable to apply rotation, because underlying type is not UnitQuaternion!")
-                    }
-                }
-                ValueBinding::Property {
-                    name: ref property_name,
-                    value_type,
-                } => bound_value.apply_to_object(node_ref, property_name, value_type),
+            apply_bound_value(bound_value, node_ref);
+        }
+    }
+}
+
+fn apply_bound_value(bound_value: &BoundValue, node_ref: &mut Node) {
+    match bound_value.binding {
+        ValueBinding::Position => {
+            if let TrackValue::Vector3(v) = bound_value.value {
+                node_ref.local_transform_mut().set_position(v);
+            } else {
+                Log::err("Unable to apply position, because underlying type is not Vector3!")
+            }
+        }
+        ValueBinding::Scale => {
+            if let TrackValue::Vector3(v) = bound_value.value {
+                node_ref.local_transform_mut().set_scale(v);
+            } else {
+                Log::err("Unable to apply scaling, because underlying type is not Vector3!")
+            }
+        }
+        ValueBinding::Rotation => {
+            if let TrackValue::UnitQuaternion(v) = bound_value.value {
+                node_ref.local_transform_mut().set_rotation(v);
+            } else {
+                Log::err("Unable to apply rotation, because underlying type is not UnitQuaternion!")
+            }
+        }
+        ValueBinding::Property {
+            name: ref property_name,
+            value_type,
+        } => bound_value.apply_to_object(node_ref, property_name, value_type),
+    }
+}
+
+/// Extension trait for [`PropertyTrack`].
+pub trait PropertyTrackExt {
+    /// Sets the value of the animated property at the given time position to the target node of
+    /// the track. Does nothing if the track is disabled or its target does not exist.
+    fn apply(&self, graph: &mut Graph, time: f32);
+}
+
+impl PropertyTrackExt for PropertyTrack {
+    fn apply(&self, graph: &mut Graph, time: f32) {
+        if !self.binding().is_enabled() {
+            return;
+        }
+
+        if let Some(bound_value) = self.fetch(time) {
+            if let Ok(node_ref) = graph.try_get_node_mut(self.target()) {
+                apply_bound_value(&bound_value, node_ref);
             }
         }
     }
@@ -349,7 +433,7 @@ impl NodeTrait for AnimationPlayer {
         if self.auto_apply {
             self.animations
                 .get_value_mut_silent()
-                .update_animations(context.nodes, context.dt);
+                .update_animations(context.nodes, context.dt * context.animation_time_scale);
         }
     }
 }
@@ -397,3 +481,40 @@ impl AnimationPlayerBuilder {
         graph.add_node(self.build_node())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{animation::prelude::*, base::BaseBuilder, graph::Graph, pivot::PivotBuilder},
+    };
+
+    #[test]
+    fn test_property_track() {
+        let mut graph = Graph::new();
+        let node = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let mut track = PropertyTrack::new(
+            node.to_base(),
+            "base.local_transform.local_position",
+            ValueType::Vector3F32,
+        );
+        track.add_key(0.0, TrackValue::Vector3(Vector3::new(0.0, 0.0, 0.0)));
+        track.add_key(1.0, TrackValue::Vector3(Vector3::new(10.0, 0.0, 0.0)));
+        assert_eq!(track.time_length(), 1.0);
+
+        track.apply(&mut graph, 0.5);
+        assert_eq!(
+            **graph[node].local_transform().position(),
+            Vector3::new(5.0, 0.0, 0.0)
+        );
+
+        // Constant interpolation holds the value of the previous key.
+        track.set_interpolation(PropertyTrackInterpolation::Constant);
+        track.apply(&mut graph, 0.5);
+        assert_eq!(
+            **graph[node].local_transform().position(),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+    }
+}
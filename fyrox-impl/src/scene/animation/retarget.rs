@@ -0,0 +1,577 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Animation retargeting allows to reuse animations across skeletons with different proportions and
+//! rest poses. See [`AnimationRetargeter`] docs for more info.
+
+use crate::{
+    asset::Resource,
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3, Vector4},
+        math::curve::{CurveKey, CurveKeyKind},
+        pool::Handle,
+    },
+    generic_animation::{
+        container::{TrackDataContainer, TrackValueKind},
+        value::{TrackValue, ValueBinding},
+        AnimationTracksData,
+    },
+    graph::SceneGraph,
+    scene::{
+        animation::{Animation, Track},
+        graph::Graph,
+        node::Node,
+    },
+};
+use fxhash::FxHashMap;
+
+/// Local transform of a bone in its rest pose.
+struct RestPose {
+    position: Vector3<f32>,
+    rotation: UnitQuaternion<f32>,
+    scale: Vector3<f32>,
+}
+
+impl RestPose {
+    fn from_node(node: &Node) -> Self {
+        let transform = node.local_transform();
+        Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            scale: **transform.scale(),
+        }
+    }
+}
+
+/// Animation retargeter maps tracks of an animation made for a source skeleton onto a target skeleton.
+/// Bones are matched by names, optionally using a bone-name mapping (for example, `mixamorig:Hips` ->
+/// `Hips`). Current local transforms of the bones of both skeletons are considered as their rest poses.
+///
+/// Transform tracks are adjusted to compensate differences between the rest poses:
+///
+/// - Rotations are applied relative to the rest orientation of the target bone.
+/// - Translations are applied relative to the rest position of the target bone and scaled by the ratio of
+/// bone lengths.
+/// - Scaling is applied relative to the rest scale of the target bone.
+///
+/// Transform tracks are resampled at the locations of their keys, interpolation kinds of the keys are
+/// preserved (tangents of cubic keys are adjusted as well). Tracks of source bones, that do not have a
+/// matching bone in the target skeleton, are skipped. Tracks, that animate other properties, are copied
+/// as is.
+#[derive(Default, Clone, Debug)]
+pub struct AnimationRetargeter {
+    bone_map: FxHashMap<String, String>,
+}
+
+impl AnimationRetargeter {
+    /// Creates a new retargeter without any bone-name mapping, bones will be matched by their names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mapping between a source bone name and a target bone name.
+    pub fn with_bone_mapping(mut self, source: &str, target: &str) -> Self {
+        self.map_bone(source, target);
+        self
+    }
+
+    /// Adds a mapping between a source bone name and a target bone name.
+    pub fn map_bone(&mut self, source: &str, target: &str) {
+        self.bone_map.insert(source.to_owned(), target.to_owned());
+    }
+
+    /// Returns a name of a target bone for the given source bone name.
+    pub fn target_bone_name<'a>(&'a self, source: &'a str) -> &'a str {
+        self.bone_map
+            .get(source)
+            .map(|s| s.as_str())
+            .unwrap_or(source)
+    }
+
+    fn find_target_bone<'a>(
+        &self,
+        source_graph: &Graph,
+        source_bone: Handle<Node>,
+        target_graph: &'a Graph,
+        target_root: Handle<Node>,
+    ) -> Option<(Handle<Node>, &'a Node)> {
+        let source_bone = source_graph.try_get_node(source_bone).ok()?;
+        target_graph.find_by_name(target_root, self.target_bone_name(source_bone.name()))
+    }
+
+    /// Creates a new animation for a target skeleton (a hierarchy starting from `target_root`) from the given
+    /// `animation` of the source skeleton. The new animation has its own track data.
+    pub fn retarget(
+        &self,
+        animation: &Animation,
+        source_graph: &Graph,
+        target_graph: &Graph,
+        target_root: Handle<Node>,
+    ) -> Animation {
+        let mut result = animation.clone();
+        result.track_bindings_mut().clear();
+
+        let mut tracks = Vec::new();
+        let state = animation.tracks_data().state();
+        if let Some(tracks_data) = state.data_ref() {
+            for track in tracks_data.tracks.iter() {
+                let Some(binding) = animation.track_bindings().get(&track.id()) else {
+                    continue;
+                };
+                let Ok(source_bone_ref) = source_graph.try_get_node(binding.target()) else {
+                    continue;
+                };
+                // Skip the tracks of missing bones.
+                let Some((target_bone, target_bone_ref)) = self.find_target_bone(
+                    source_graph,
+                    binding.target(),
+                    target_graph,
+                    target_root,
+                ) else {
+                    continue;
+                };
+
+                let new_track = retarget_track(
+                    track,
+                    &RestPose::from_node(source_bone_ref),
+                    &RestPose::from_node(target_bone_ref),
+                );
+
+                let mut new_binding = binding.clone();
+                new_binding.set_target(target_bone);
+                result
+                    .track_bindings_mut()
+                    .insert(new_track.id(), new_binding);
+                tracks.push(new_track);
+            }
+        }
+
+        result.set_tracks_data(Resource::new_embedded(AnimationTracksData { tracks }));
+
+        if let Some(settings) = animation.root_motion_settings_ref() {
+            let root = self
+                .find_target_bone(source_graph, settings.node, target_graph, target_root)
+                .map(|(handle, _)| handle);
+            if let Some(settings) = result.root_motion_settings_mut() {
+                settings.node = root.unwrap_or_default();
+            }
+        }
+
+        result
+    }
+}
+
+fn key_locations(track: &Track) -> Vec<f32> {
+    let mut locations = track
+        .data_container()
+        .curves_ref()
+        .iter()
+        .flat_map(|curve| curve.keys().iter().map(|key| key.location))
+        .collect::<Vec<_>>();
+    locations.sort_by(|a, b| a.total_cmp(b));
+    locations.dedup_by(|a, b| (*a - *b).abs() <= f32::EPSILON);
+    locations
+}
+
+fn components(value: &TrackValue) -> Option<Vec<f32>> {
+    match value {
+        TrackValue::Vector3(v) => Some(v.as_slice().to_vec()),
+        TrackValue::UnitQuaternion(q) => Some(q.coords.as_slice().to_vec()),
+        _ => None,
+    }
+}
+
+fn offset(value: &TrackValue, delta: &[f32]) -> Option<TrackValue> {
+    match value {
+        TrackValue::Vector3(v) => Some(TrackValue::Vector3(v + Vector3::from_column_slice(delta))),
+        TrackValue::UnitQuaternion(q) => {
+            Some(TrackValue::UnitQuaternion(UnitQuaternion::new_unchecked(
+                Quaternion::from(q.coords + Vector4::from_column_slice(delta)),
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the kind of the first key at the given location and the tangents of all the curves at this
+/// location (zero for the curves that have no cubic key there).
+fn key_kind(track: &Track, location: f32) -> (CurveKeyKind, Vec<f32>, Vec<f32>) {
+    let curves = track.data_container().curves_ref();
+    let mut kind = None;
+    let mut left = vec![0.0; curves.len()];
+    let mut right = vec![0.0; curves.len()];
+    for (i, curve) in curves.iter().enumerate() {
+        let Some(key) = curve
+            .keys()
+            .iter()
+            .find(|key| (key.location - location).abs() <= f32::EPSILON)
+        else {
+            continue;
+        };
+        kind.get_or_insert(key.kind.clone());
+        if let CurveKeyKind::Cubic {
+            left_tangent,
+            right_tangent,
+        } = key.kind
+        {
+            left[i] = left_tangent;
+            right[i] = right_tangent;
+        }
+    }
+    (kind.unwrap_or(CurveKeyKind::Linear), left, right)
+}
+
+/// Resamples the track at the locations of its keys, every value is mapped using the given function.
+/// The function must be affine, this way tangents of cubic keys could be mapped as
+/// `func(value + tangent) - func(value)`.
+fn resample<F>(track: &Track, kind: TrackValueKind, func: F) -> Track
+where
+    F: Fn(TrackValue) -> Option<TrackValue>,
+{
+    let map = |value: TrackValue| func(value).as_ref().and_then(components);
+    let mut container = TrackDataContainer::new(kind);
+    let mut prev: Option<Vec<f32>> = None;
+    for location in key_locations(track) {
+        let Some(value) = track.fetch(location).map(|v| v.value) else {
+            continue;
+        };
+        let Some(mut components) = map(value.clone()) else {
+            continue;
+        };
+
+        let (source_kind, left, right) = key_kind(track, location);
+        let mut tangents = None;
+        if let CurveKeyKind::Cubic { .. } = source_kind {
+            let map_tangent = |tangent: &[f32]| {
+                offset(&value, tangent).and_then(map).map(|shifted| {
+                    shifted
+                        .iter()
+                        .zip(components.iter())
+                        .map(|(a, b)| a - b)
+                        .collect::<Vec<_>>()
+                })
+            };
+            let (Some(left), Some(right)) = (map_tangent(&left), map_tangent(&right)) else {
+                continue;
+            };
+            tangents = Some((left, right));
+        }
+
+        // Keep the quaternions in the same hemisphere, so the interpolation between the keys will take
+        // the shortest path.
+        if let TrackValueKind::UnitQuaternion = kind {
+            let dot = prev.as_ref().map_or(0.0, |prev| {
+                prev.iter()
+                    .zip(components.iter())
+                    .map(|(a, b)| a * b)
+                    .sum::<f32>()
+            });
+            if dot < 0.0 {
+                for value in components
+                    .iter_mut()
+                    .chain(tangents.iter_mut().flat_map(|(l, r)| l.iter_mut().chain(r)))
+                {
+                    *value = -*value;
+                }
+            }
+            prev = Some(components.clone());
+        }
+
+        for (i, (curve, component)) in container
+            .curves_mut()
+            .iter_mut()
+            .zip(components)
+            .enumerate()
+        {
+            let kind = match (&source_kind, &tangents) {
+                (CurveKeyKind::Cubic { .. }, Some((left, right))) => CurveKeyKind::Cubic {
+                    left_tangent: left[i],
+                    right_tangent: right[i],
+                },
+                (kind, _) => kind.clone(),
+            };
+            curve.add_key(CurveKey::new(location, component, kind));
+        }
+    }
+    Track::new(container, track.value_binding().clone())
+}
+
+fn retarget_track(track: &Track, source: &RestPose, target: &RestPose) -> Track {
+    match track.value_binding() {
+        ValueBinding::Position => {
+            let source_length = source.position.norm();
+            let length_ratio = if source_length > f32::EPSILON {
+                target.position.norm() / source_length
+            } else {
+                1.0
+            };
+            resample(track, TrackValueKind::Vector3, |value| match value {
+                TrackValue::Vector3(position) => Some(TrackValue::Vector3(
+                    target.position + (position - source.position).scale(length_ratio),
+                )),
+                _ => None,
+            })
+        }
+        ValueBinding::Rotation => {
+            resample(track, TrackValueKind::UnitQuaternion, |value| match value {
+                TrackValue::UnitQuaternion(rotation) => Some(TrackValue::UnitQuaternion(
+                    target.rotation * (source.rotation.inverse() * rotation),
+                )),
+                _ => None,
+            })
+        }
+        ValueBinding::Scale => resample(track, TrackValueKind::Vector3, |value| match value {
+            TrackValue::Vector3(scale) => Some(TrackValue::Vector3(target.scale.component_mul(
+                &scale.zip_map(&source.scale, |s, rest| {
+                    if rest.abs() > f32::EPSILON {
+                        s / rest
+                    } else {
+                        s
+                    }
+                }),
+            ))),
+            _ => None,
+        }),
+        ValueBinding::Property { .. } => track.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            math::curve::{Curve, CurveKey, CurveKeyKind},
+            pool::Handle,
+        },
+        generic_animation::{
+            container::{TrackDataContainer, TrackValueKind},
+            track::TrackBinding,
+            value::{TrackValue, ValueBinding},
+        },
+        graph::SceneGraph,
+        scene::{
+            animation::{retarget::AnimationRetargeter, Animation, Track},
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_skeleton(graph: &mut Graph, with_tail: bool) -> [Handle<Node>; 3] {
+        let spine = PivotBuilder::new(
+            BaseBuilder::new().with_name("Spine").with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.5, 0.0))
+                    .with_local_rotation(UnitQuaternion::from_euler_angles(0.1, 0.2, 0.3))
+                    .build(),
+            ),
+        )
+        .build(graph);
+        let mut hips = BaseBuilder::new()
+            .with_name("Hips")
+            .with_child(spine)
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .build(),
+            );
+        let mut tail = Handle::NONE;
+        if with_tail {
+            tail = PivotBuilder::new(BaseBuilder::new().with_name("Tail")).build(graph);
+            hips = hips.with_child(tail);
+        }
+        let hips = PivotBuilder::new(hips).build(graph);
+        [hips, spine, tail]
+    }
+
+    fn make_track(binding: ValueBinding, kind: TrackValueKind, keys: &[(f32, &[f32])]) -> Track {
+        make_track_with_kind(binding, kind, CurveKeyKind::Linear, keys)
+    }
+
+    fn make_track_with_kind(
+        binding: ValueBinding,
+        kind: TrackValueKind,
+        key_kind: CurveKeyKind,
+        keys: &[(f32, &[f32])],
+    ) -> Track {
+        let mut container = TrackDataContainer::new(kind);
+        for (i, curve) in container.curves_mut().iter_mut().enumerate() {
+            *curve = Curve::from(
+                keys.iter()
+                    .map(|(t, values)| CurveKey::new(*t, values[i], key_kind.clone()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        Track::new(container, binding)
+    }
+
+    fn assert_values_eq(a: &TrackValue, b: &TrackValue) {
+        match (a, b) {
+            (TrackValue::Vector3(a), TrackValue::Vector3(b)) => {
+                assert!(a.metric_distance(b) < 0.001, "{a:?} != {b:?}")
+            }
+            (TrackValue::UnitQuaternion(a), TrackValue::UnitQuaternion(b)) => {
+                assert!(a.angle_to(b) < 0.001, "{a:?} != {b:?}")
+            }
+            _ => panic!("value type mismatch"),
+        }
+    }
+
+    #[test]
+    fn test_retarget_same_topology() {
+        let mut source_graph = Graph::new();
+        let [source_hips, source_spine, source_tail] = make_skeleton(&mut source_graph, true);
+
+        // Add a dummy node to make the handles of the target skeleton different.
+        let mut target_graph = Graph::new();
+        PivotBuilder::new(BaseBuilder::new()).build(&mut target_graph);
+        let [target_hips, target_spine, _] = make_skeleton(&mut target_graph, false);
+
+        let half_turn = UnitQuaternion::from_euler_angles(0.0, std::f32::consts::FRAC_PI_2, 0.0);
+        let mut animation = Animation::default();
+        animation.add_track_with_binding(
+            TrackBinding::new(source_hips),
+            make_track(
+                ValueBinding::Position,
+                TrackValueKind::Vector3,
+                &[(0.0, &[0.0, 1.0, 0.0]), (1.0, &[0.0, 1.0, 2.0])],
+            ),
+        );
+        animation.add_track_with_binding(
+            TrackBinding::new(source_spine),
+            make_track(
+                ValueBinding::Rotation,
+                TrackValueKind::UnitQuaternion,
+                &[
+                    (0.0, &[0.0, 0.0, 0.0, 1.0]),
+                    (1.0, half_turn.coords.as_slice()),
+                ],
+            ),
+        );
+        animation.add_track_with_binding(
+            TrackBinding::new(source_tail),
+            make_track(
+                ValueBinding::Position,
+                TrackValueKind::Vector3,
+                &[(0.0, &[0.0, 0.0, 0.0]), (1.0, &[1.0, 0.0, 0.0])],
+            ),
+        );
+        animation.set_time_slice(0.0..1.0);
+
+        let mut retargeted = AnimationRetargeter::new().retarget(
+            &animation,
+            &source_graph,
+            &target_graph,
+            target_graph.get_root(),
+        );
+
+        // The track of the missing tail bone must be skipped.
+        assert_eq!(retargeted.track_bindings().len(), 2);
+
+        for time in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            animation.set_time_position(time);
+            animation.tick(0.0);
+            retargeted.set_time_position(time);
+            retargeted.tick(0.0);
+
+            for (source, target) in [(source_hips, target_hips), (source_spine, target_spine)] {
+                let source_pose = &animation.pose().poses()[&source];
+                let target_pose = &retargeted.pose().poses()[&target];
+                assert_values_eq(
+                    &source_pose.values.values[0].value,
+                    &target_pose.values.values[0].value,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_retarget_preserves_interpolation() {
+        let mut source_graph = Graph::new();
+        let [source_hips, source_spine, _] = make_skeleton(&mut source_graph, false);
+
+        let mut target_graph = Graph::new();
+        let [target_hips, target_spine, _] = make_skeleton(&mut target_graph, false);
+        // Different rest orientation of the target spine, so the rotation track must be adjusted.
+        target_graph[target_spine]
+            .local_transform_mut()
+            .set_rotation(UnitQuaternion::from_euler_angles(0.3, 0.0, 0.0));
+
+        let turn = UnitQuaternion::from_euler_angles(0.0, 1.0, 0.0);
+        let mut animation = Animation::default();
+        animation.add_track_with_binding(
+            TrackBinding::new(source_hips),
+            make_track_with_kind(
+                ValueBinding::Position,
+                TrackValueKind::Vector3,
+                CurveKeyKind::Constant,
+                &[(0.0, &[0.0, 1.0, 0.0]), (1.0, &[0.0, 1.0, 2.0])],
+            ),
+        );
+        animation.add_track_with_binding(
+            TrackBinding::new(source_spine),
+            make_track_with_kind(
+                ValueBinding::Rotation,
+                TrackValueKind::UnitQuaternion,
+                CurveKeyKind::Cubic {
+                    left_tangent: 0.5,
+                    right_tangent: -0.5,
+                },
+                &[(0.0, &[0.0, 0.0, 0.0, 1.0]), (1.0, turn.coords.as_slice())],
+            ),
+        );
+        animation.set_time_slice(0.0..1.0);
+
+        let mut retargeted = AnimationRetargeter::new().retarget(
+            &animation,
+            &source_graph,
+            &target_graph,
+            target_graph.get_root(),
+        );
+
+        let spine_offset = **target_graph[target_spine].local_transform().rotation();
+        for time in [0.0, 0.25, 0.5, 0.75, 1.0] {
+            animation.set_time_position(time);
+            animation.tick(0.0);
+            retargeted.set_time_position(time);
+            retargeted.tick(0.0);
+
+            // Constant keys stay constant.
+            assert_values_eq(
+                &animation.pose().poses()[&source_hips].values.values[0].value,
+                &retargeted.pose().poses()[&target_hips].values.values[0].value,
+            );
+
+            // Cubic keys produce the same curve relative to the rest pose.
+            let TrackValue::UnitQuaternion(source_rotation) =
+                animation.pose().poses()[&source_spine].values.values[0].value
+            else {
+                unreachable!()
+            };
+            assert_values_eq(
+                &TrackValue::UnitQuaternion(spine_offset * source_rotation),
+                &retargeted.pose().poses()[&target_spine].values.values[0].value,
+            );
+        }
+    }
+}
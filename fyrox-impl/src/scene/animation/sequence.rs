@@ -0,0 +1,164 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Sequence player is a node that plays a sequence (a timeline with multiple tracks). See
+//! [`SequencePlayer`] docs for more info.
+
+use crate::{
+    core::{
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        animation::AnimationPoseExt,
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+
+/// Scene specific sequence.
+pub type Sequence = crate::generic_animation::sequence::Sequence<Handle<Node>>;
+/// Scene specific sequence clip.
+pub type SequenceClip = crate::generic_animation::sequence::SequenceClip<Handle<Node>>;
+
+/// Sequence player is a node that plays a [`Sequence`] - a timeline with property tracks, animation
+/// clips and signals, that share the same playhead. It is mostly used for cutscenes. The player
+/// ticks the sequence every frame and applies its output pose to the scene graph. Use
+/// [`Sequence::play`], [`Sequence::pause`] and [`Sequence::seek`] to control the playback.
+#[derive(Visit, Reflect, Clone, Debug, Default, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct SequencePlayer {
+    base: Base,
+    #[component(include)]
+    sequence: InheritableVariable<Sequence>,
+}
+
+impl SequencePlayer {
+    /// Returns a reference to the sequence of the player.
+    pub fn sequence(&self) -> &InheritableVariable<Sequence> {
+        &self.sequence
+    }
+
+    /// Returns a reference to the sequence of the player. Keep in mind that mutable access to
+    /// [`InheritableVariable`] may have side effects if used inappropriately. Checks docs for
+    /// [`InheritableVariable`] for more info.
+    pub fn sequence_mut(&mut self) -> &mut InheritableVariable<Sequence> {
+        &mut self.sequence
+    }
+
+    /// Sets new sequence of the player.
+    pub fn set_sequence(&mut self, sequence: Sequence) {
+        self.sequence.set_value_and_mark_modified(sequence);
+    }
+}
+
+impl TypeUuidProvider for SequencePlayer {
+    fn type_uuid() -> Uuid {
+        uuid!("6b0f1d6e-93f4-4c6e-a8f7-2b1f4c0d7e35")
+    }
+}
+
+impl Deref for SequencePlayer {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for SequencePlayer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for SequencePlayer {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Sequence Player", |_| {
+                SequencePlayerBuilder::new(BaseBuilder::new().with_name("Sequence Player"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Animation")
+    }
+}
+
+impl NodeTrait for SequencePlayer {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let sequence = self.sequence.get_value_mut_silent();
+        sequence.tick(context.dt * context.animation_time_scale);
+        sequence.pose().apply_internal(context.nodes);
+    }
+}
+
+/// A builder for [`SequencePlayer`] node.
+pub struct SequencePlayerBuilder {
+    base_builder: BaseBuilder,
+    sequence: Sequence,
+}
+
+impl SequencePlayerBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            sequence: Default::default(),
+        }
+    }
+
+    /// Sets the sequence, that will be played by the player.
+    pub fn with_sequence(mut self, sequence: Sequence) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    /// Creates an instance of [`SequencePlayer`] node.
+    pub fn build_node(self) -> Node {
+        Node::new(SequencePlayer {
+            base: self.base_builder.build_base(),
+            sequence: self.sequence.into(),
+        })
+    }
+
+    /// Creates an instance of [`SequencePlayer`] node and adds it to the given scene graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<SequencePlayer> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
@@ -26,6 +26,7 @@ use super::collider::BitMask;
 use crate::{
     core::{
         algebra::{Matrix4, Vector3},
+        dyntype::{DynType, DynTypeContainer, DynTypeWrapper},
         log::Log,
         math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         pool::{ErasedHandle, Handle},
@@ -486,6 +487,9 @@ pub struct Base {
     #[reflect(setter = "set_frustum_culling")]
     frustum_culling: InheritableVariable<bool>,
 
+    #[reflect(setter = "set_render_order")]
+    render_order: InheritableVariable<i32>,
+
     /// When `true` it means that this node is instance of `resource`.
     /// More precisely - this node is root of whole descendant nodes
     /// hierarchy which was instantiated from resource.
@@ -533,6 +537,10 @@ pub struct Base {
     // Use it at your own risk only when you're completely sure what you are doing.
     pub(crate) scripts: Vec<ScriptRecord>,
 
+    /// User-defined data. Could be any registered [`DynType`] instance, see [`Base::set_user_data`]
+    /// for more info.
+    pub(crate) user_data: DynTypeContainer,
+
     #[reflect(read_only)]
     pub(crate) global_enabled: Cell<bool>,
 }
@@ -682,6 +690,70 @@ impl Base {
         self.set_scale(Vector3::repeat(scale))
     }
 
+    /// Sets the new position of the node in world coordinates. The given position is converted to
+    /// the local space of the parent node using its global transform and then set as the local
+    /// position of the node. Pass [`Matrix4::identity`] for nodes without a parent. Global
+    /// transform of the node itself will be updated on the next graph update, use
+    /// [`super::Graph::set_global_position`] if you need the change to be visible immediately.
+    #[inline]
+    pub fn set_global_position(
+        &mut self,
+        position: Vector3<f32>,
+        parent_global_transform: &Matrix4<f32>,
+    ) {
+        let local_position = parent_global_transform
+            .try_inverse()
+            .unwrap_or_default()
+            .transform_point(&position.into())
+            .coords;
+        self.set_position(local_position);
+    }
+
+    /// Sets the new transform of the node in world coordinates. The given matrix is converted to
+    /// the local space of the parent node using its global transform and then decomposed into
+    /// position, rotation and scale of the local transform. Pass [`Matrix4::identity`] for nodes
+    /// without a parent.
+    ///
+    /// ## Important
+    ///
+    /// The decomposition assumes that the matrix has no shear. Pre- and post-rotations, pivots
+    /// and offsets of the local transform are reset, because otherwise they would be applied on
+    /// top of the requested transform.
+    pub fn set_global_transform_matrix(
+        &mut self,
+        transform: &Matrix4<f32>,
+        parent_global_transform: &Matrix4<f32>,
+    ) {
+        const EPSILON: f32 = 10.0 * f32::EPSILON;
+
+        let local = parent_global_transform.try_inverse().unwrap_or_default() * transform;
+        let basis = local.basis();
+        let scale = Vector3::new(
+            basis.column(0).norm(),
+            basis.column(1).norm(),
+            basis.column(2).norm(),
+        );
+        let mut rotation_basis = basis;
+        for (i, s) in scale.iter().enumerate() {
+            if *s > f32::EPSILON {
+                rotation_basis.column_mut(i).unscale_mut(*s);
+            }
+        }
+        let rotation =
+            UnitQuaternion::from_matrix_eps(&rotation_basis, EPSILON, 16, Default::default());
+
+        self.local_transform_mut()
+            .set_position(local.position())
+            .set_rotation(rotation)
+            .set_scale(scale)
+            .set_pre_rotation(UnitQuaternion::identity())
+            .set_post_rotation(UnitQuaternion::identity())
+            .set_rotation_offset(Vector3::zeros())
+            .set_rotation_pivot(Vector3::zeros())
+            .set_scaling_offset(Vector3::zeros())
+            .set_scaling_pivot(Vector3::zeros());
+    }
+
     /// Tries to find properties by the name. The method returns an iterator because it possible
     /// to have multiple properties with the same name.
     #[inline]
@@ -907,6 +979,47 @@ impl Base {
         self.tag.set_value_and_mark_modified(tag)
     }
 
+    /// Attaches arbitrary user data to the node and returns the previous one (if any). Unlike
+    /// scripts, user data is pure data - it has no logic and it is never updated by the engine.
+    /// It is useful to store gameplay data (loot tables, dialogue ids, etc.) without defining a
+    /// new node type.
+    ///
+    /// The data is serialized together with the node. To be able to load it back, the type of
+    /// the data must be registered in [`crate::core::dyntype::DynTypeConstructorContainer`]
+    /// (see [`crate::engine::Engine::dyn_type_constructors`]), otherwise the data will be
+    /// discarded on load with an error message in the log.
+    #[inline]
+    pub fn set_user_data<T: DynType>(&mut self, data: T) -> Option<Box<dyn DynType>> {
+        std::mem::replace(&mut self.user_data.0, Some(DynTypeWrapper(Box::new(data))))
+            .map(|wrapper| wrapper.0)
+    }
+
+    /// Removes user data from the node and returns it (if any).
+    #[inline]
+    pub fn take_user_data(&mut self) -> Option<Box<dyn DynType>> {
+        self.user_data.0.take().map(|wrapper| wrapper.0)
+    }
+
+    /// Returns `true` if the node has user data of any type.
+    #[inline]
+    pub fn has_user_data(&self) -> bool {
+        self.user_data.0.is_some()
+    }
+
+    /// Tries to borrow user data of the given type. Returns `None` if there's no user data or
+    /// it has different type.
+    #[inline]
+    pub fn user_data_ref<T: DynType>(&self) -> Option<&T> {
+        self.user_data.data_ref().ok()
+    }
+
+    /// Tries to borrow user data of the given type. Returns `None` if there's no user data or
+    /// it has different type.
+    #[inline]
+    pub fn user_data_mut<T: DynType>(&mut self) -> Option<&mut T> {
+        self.user_data.data_mut().ok()
+    }
+
     /// Return the frustum_culling flag
     #[inline]
     pub fn frustum_culling(&self) -> bool {
@@ -920,6 +1033,22 @@ impl Base {
             .set_value_and_mark_modified(frustum_culling)
     }
 
+    /// Returns the render order of the node. See [`Self::set_render_order`] for more info.
+    #[inline]
+    pub fn render_order(&self) -> i32 {
+        *self.render_order
+    }
+
+    /// Sets the render order of the node. Transparent nodes are rendered back-to-front (sorted by
+    /// the distance to the camera), the render order is used to sort the nodes that have the same
+    /// distance to the camera (for example, layers of a user interface in the world). Nodes with
+    /// lower render order are rendered first, so nodes with higher render order appear on top.
+    /// Default value is zero.
+    #[inline]
+    pub fn set_render_order(&mut self, render_order: i32) -> i32 {
+        self.render_order.set_value_and_mark_modified(render_order)
+    }
+
     /// Returns true if the node should cast shadows, false - otherwise.
     #[inline]
     pub fn cast_shadows(&self) -> bool {
@@ -1268,6 +1397,17 @@ impl Visit for Base {
         self.instance_id.visit("InstanceId", &mut region)?;
         self.enabled.visit("Enabled", &mut region)?;
         self.render_mask.visit("RenderMask", &mut region)?;
+        let _ = self.render_order.visit("RenderOrder", &mut region);
+
+        // User data is optional, but its loss (because of a missing constructor, for example)
+        // must be reported.
+        match self.user_data.visit("UserData", &mut region) {
+            Ok(_) | Err(VisitError::RegionDoesNotExist(_)) => (),
+            Err(err) => Log::err(format!(
+                "Unable to visit user data of {} node. Reason: {err:?}",
+                self.name()
+            )),
+        }
 
         // Script visiting may fail for various reasons:
         //
@@ -1297,6 +1437,7 @@ pub struct BaseBuilder {
     tag: String,
     frustum_culling: bool,
     cast_shadows: bool,
+    render_order: i32,
     scripts: Vec<ScriptRecord>,
     instance_id: SceneNodeId,
     enabled: bool,
@@ -1324,6 +1465,7 @@ impl BaseBuilder {
             tag: Default::default(),
             frustum_culling: true,
             cast_shadows: true,
+            render_order: 0,
             scripts: vec![],
             instance_id: SceneNodeId(Uuid::new_v4()),
             enabled: true,
@@ -1415,6 +1557,13 @@ impl BaseBuilder {
         self
     }
 
+    /// Sets the render order of the node. See [`Base::set_render_order`] for more info.
+    #[inline]
+    pub fn with_render_order(mut self, render_order: i32) -> Self {
+        self.render_order = render_order;
+        self
+    }
+
     /// Sets script of the node.
     #[inline]
     pub fn with_script<T>(mut self, script: T) -> Self
@@ -1467,8 +1616,10 @@ impl BaseBuilder {
             properties: Default::default(),
             frustum_culling: self.frustum_culling.into(),
             cast_shadows: self.cast_shadows.into(),
+            render_order: self.render_order.into(),
             scripts: self.scripts,
-            instance_id: SceneNodeId(Uuid::new_v4()),
+            instance_id: self.instance_id,
+            user_data: Default::default(),
 
             global_enabled: Cell::new(true),
         }
@@ -23,7 +23,7 @@
 use crate::{
     asset::{state::LoadError, untyped::ResourceKind},
     core::{
-        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        algebra::{Matrix4, Point3, Unit, UnitQuaternion, Vector2, Vector3, Vector4},
         color::Color,
         math::{aabb::AxisAlignedBoundingBox, frustum::Frustum, ray::Ray, Rect},
         pool::Handle,
@@ -40,6 +40,7 @@ use crate::{
     },
     scene::{
         base::{Base, BaseBuilder},
+        camera_shake::CameraShake,
         debug::SceneDrawingContext,
         graph::Graph,
         node::constructor::NodeConstructor,
@@ -364,6 +365,10 @@ pub struct Camera {
     #[reflect(setter = "set_viewport")]
     viewport: InheritableVariable<Rect<f32>>,
 
+    #[reflect(setter = "set_target_aspect_ratio")]
+    #[visit(optional)]
+    target_aspect_ratio: InheritableVariable<Option<f32>>,
+
     #[reflect(setter = "set_enabled")]
     enabled: InheritableVariable<bool>,
 
@@ -393,6 +398,10 @@ pub struct Camera {
     #[visit(skip)]
     #[reflect(hidden)]
     projection_matrix: Matrix4<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    shakes: Vec<CameraShake>,
 }
 
 impl Deref for Camera {
@@ -452,17 +461,76 @@ impl FitParameters {
     }
 }
 
+// Viewport rectangle has its origin at the left bottom corner of the screen (as in OpenGL), while
+// screen coordinates have it at the left top corner. Returns the left top corner of the viewport in
+// screen coordinates.
+fn screen_viewport_origin(viewport: Rect<i32>, screen_size: Vector2<f32>) -> Vector2<f32> {
+    Vector2::new(
+        viewport.x() as f32,
+        screen_size.y - (viewport.y() + viewport.h()) as f32,
+    )
+}
+
 impl Camera {
     /// Explicitly calculates view and projection matrices. Normally, you should not call
     /// this method, it will be called automatically when new frame starts.
     #[inline]
     pub fn calculate_matrices(&mut self, frame_size: Vector2<f32>) {
-        let pos = self.base.global_position();
-        let look = self.base.look_vector();
-        let up = self.base.up_vector();
+        let mut pos = self.base.global_position();
+        let mut look = self.base.look_vector();
+        let mut up = self.base.up_vector();
+
+        // Shakes are applied on top of the transform, so the transform itself remains untouched.
+        if !self.shakes.is_empty() {
+            let side = self
+                .base
+                .side_vector()
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::x);
+            let up_axis = up.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::y);
+            let look_axis = look.try_normalize(f32::EPSILON).unwrap_or_else(Vector3::z);
+
+            let (offset, angles) = self.shake_offset();
+            pos += side.scale(offset.x) + up_axis.scale(offset.y) + look_axis.scale(offset.z);
+
+            let rotation = UnitQuaternion::from_axis_angle(&Unit::new_unchecked(side), angles.x)
+                * UnitQuaternion::from_axis_angle(&Unit::new_unchecked(up_axis), angles.y)
+                * UnitQuaternion::from_axis_angle(&Unit::new_unchecked(look_axis), angles.z);
+            look = rotation * look;
+            up = rotation * up;
+        }
 
         self.view_matrix = Matrix4::look_at_rh(&Point3::from(pos), &Point3::from(pos + look), &up);
-        self.projection_matrix = self.projection.matrix(frame_size);
+        self.projection_matrix = match self.target_aspect_ratio() {
+            // Only the aspect ratio of the frame matters for projection matrices.
+            Some(aspect_ratio) => self.projection.matrix(Vector2::new(aspect_ratio, 1.0)),
+            None => self.projection.matrix(frame_size),
+        };
+    }
+
+    /// Adds a new shake to the camera. The shake is removed automatically when it fades out. See
+    /// [`CameraShake`] docs for more info.
+    pub fn add_shake(&mut self, shake: CameraShake) {
+        self.shakes.push(shake);
+    }
+
+    /// Returns a reference to the active shakes of the camera.
+    pub fn shakes(&self) -> &[CameraShake] {
+        &self.shakes
+    }
+
+    /// Removes every active shake from the camera.
+    pub fn clear_shakes(&mut self) {
+        self.shakes.clear();
+    }
+
+    /// Returns the sum of offsets (in local space of the camera) and the sum of rotation angles
+    /// (around local X, Y, Z axes of the camera) of every active shake.
+    pub fn shake_offset(&self) -> (Vector3<f32>, Vector3<f32>) {
+        self.shakes.iter().fold(
+            (Vector3::default(), Vector3::default()),
+            |(offset, angles), shake| (offset + shake.offset(), angles + shake.angles()),
+        )
     }
 
     /// Sets new viewport in resolution-independent format. In other words
@@ -494,14 +562,66 @@ impl Camera {
     /// range `[1; infinity]`. This is strictly needed because having viewport of 0 in size
     /// will cause panics in various places. It happens because viewport size is used as
     /// divisor in math formulas, but you cannot divide by zero.
+    ///
+    /// If the camera has target aspect ratio (see [`Self::set_target_aspect_ratio`]), then the
+    /// returned rectangle is the active (letterboxed) area of the viewport.
     #[inline]
     pub fn viewport_pixels(&self, frame_size: Vector2<f32>) -> Rect<i32> {
-        Rect::new(
-            (self.viewport.x() * frame_size.x) as i32,
-            (self.viewport.y() * frame_size.y) as i32,
-            ((self.viewport.w() * frame_size.x) as i32).max(1),
-            ((self.viewport.h() * frame_size.y) as i32).max(1),
-        )
+        let mut x = self.viewport.x() * frame_size.x;
+        let mut y = self.viewport.y() * frame_size.y;
+        let mut w = self.viewport.w() * frame_size.x;
+        let mut h = self.viewport.h() * frame_size.y;
+
+        if let Some(aspect_ratio) = self.target_aspect_ratio() {
+            if h > 0.0 && w / h > aspect_ratio {
+                // Viewport is wider than needed - add bars on the left and right sides (pillarbox).
+                let active_width = h * aspect_ratio;
+                x += (w - active_width) * 0.5;
+                w = active_width;
+            } else {
+                // Viewport is taller than needed - add bars on the top and bottom sides (letterbox).
+                let active_height = w / aspect_ratio;
+                y += (h - active_height) * 0.5;
+                h = active_height;
+            }
+        }
+
+        Rect::new(x as i32, y as i32, (w as i32).max(1), (h as i32).max(1))
+    }
+
+    /// Sets the aspect ratio (width / height) the camera should maintain. When set, the active area
+    /// of the camera is the largest rectangle of the given aspect ratio that fits in the viewport,
+    /// it is centered in the viewport and the rest of it is filled with black bars (pillarbox or
+    /// letterbox). [`None`] (default) means that the whole viewport is used. Non-positive values
+    /// are treated as [`None`].
+    #[inline]
+    pub fn set_target_aspect_ratio(&mut self, aspect_ratio: Option<f32>) -> Option<f32> {
+        self.target_aspect_ratio
+            .set_value_and_mark_modified(aspect_ratio)
+    }
+
+    /// Returns the aspect ratio the camera maintains. See [`Self::set_target_aspect_ratio`] for
+    /// more info.
+    #[inline]
+    pub fn target_aspect_ratio(&self) -> Option<f32> {
+        (*self.target_aspect_ratio)
+            .filter(|aspect_ratio| aspect_ratio.is_finite() && *aspect_ratio > 0.0)
+    }
+
+    /// Returns `true` if the given screen coordinates (with origin at the left top corner of the
+    /// screen) are inside the active area of the camera viewport.
+    #[inline]
+    pub fn is_inside_viewport(
+        &self,
+        screen_coord: Vector2<f32>,
+        screen_size: Vector2<f32>,
+    ) -> bool {
+        let viewport = self.viewport_pixels(screen_size);
+        let local = screen_coord - screen_viewport_origin(viewport, screen_size);
+        local.x >= 0.0
+            && local.y >= 0.0
+            && local.x < viewport.w() as f32
+            && local.y < viewport.h() as f32
     }
 
     /// Returns current view-projection matrix.
@@ -601,13 +721,16 @@ impl Camera {
         *self.hdr_adaptation_speed
     }
 
-    /// Creates picking ray from given screen coordinates.
+    /// Creates picking ray from given screen coordinates. The coordinates are relative to the active
+    /// area of the viewport of the camera, see [`Self::try_make_ray`] if you need to ignore the
+    /// points outside of it.
     pub fn make_ray(&self, screen_coord: Vector2<f32>, screen_size: Vector2<f32>) -> Ray {
         let viewport = self.viewport_pixels(screen_size);
-        let nx = screen_coord.x / (viewport.w() as f32) * 2.0 - 1.0;
+        let local = screen_coord - screen_viewport_origin(viewport, screen_size);
+        let nx = local.x / (viewport.w() as f32) * 2.0 - 1.0;
         // Invert y here because OpenGL has origin at left bottom corner,
         // but window coordinates starts from left *upper* corner.
-        let ny = (viewport.h() as f32 - screen_coord.y) / (viewport.h() as f32) * 2.0 - 1.0;
+        let ny = (viewport.h() as f32 - local.y) / (viewport.h() as f32) * 2.0 - 1.0;
         let inv_view_proj = self
             .view_projection_matrix()
             .try_inverse()
@@ -619,6 +742,21 @@ impl Camera {
         Ray::from_two_points(begin, end)
     }
 
+    /// Creates picking ray from given screen coordinates, but only if the coordinates are inside
+    /// the active area of the viewport of the camera. Returns [`None`] for the points outside of it
+    /// (for example, on the letterbox bars).
+    pub fn try_make_ray(
+        &self,
+        screen_coord: Vector2<f32>,
+        screen_size: Vector2<f32>,
+    ) -> Option<Ray> {
+        if self.is_inside_viewport(screen_coord, screen_size) {
+            Some(self.make_ray(screen_coord, screen_size))
+        } else {
+            None
+        }
+    }
+
     /// Calculates new fitting parameters for the given axis-aligned bounding box using current camera's
     /// global transform and provided aspect ratio. See [`FitParameters`] docs for more info.
     ///
@@ -706,10 +844,10 @@ impl Camera {
             * Vector4::new(world_pos.x, world_pos.y, world_pos.z, 1.0);
         if proj.w != 0.0 && proj.z >= 0.0 {
             let k = (1.0 / proj.w) * 0.5;
+            let origin = screen_viewport_origin(viewport, screen_size);
             Some(Vector2::new(
-                viewport.x() as f32 + viewport.w() as f32 * (proj.x * k + 0.5),
-                viewport.h() as f32
-                    - (viewport.y() as f32 + viewport.h() as f32 * (proj.y * k + 0.5)),
+                origin.x + viewport.w() as f32 * (proj.x * k + 0.5),
+                origin.y + viewport.h() as f32 * (0.5 - proj.y * k),
             ))
         } else {
             None
@@ -826,6 +964,11 @@ impl NodeTrait for Camera {
             context.frame_size
         };
 
+        for shake in self.shakes.iter_mut() {
+            shake.update(context.dt);
+        }
+        self.shakes.retain(|shake| !shake.is_finished());
+
         self.calculate_matrices(frame_size);
     }
 
@@ -1039,6 +1182,7 @@ pub struct CameraBuilder {
     z_near: f32,
     z_far: f32,
     viewport: Rect<f32>,
+    target_aspect_ratio: Option<f32>,
     enabled: bool,
     environment: Option<TextureResource>,
     exposure: Exposure,
@@ -1059,6 +1203,7 @@ impl CameraBuilder {
             z_near: 0.025,
             z_far: 2048.0,
             viewport: Rect::new(0.0, 0.0, 1.0, 1.0),
+            target_aspect_ratio: None,
             environment: None,
             exposure: Default::default(),
             color_grading_lut: None,
@@ -1093,6 +1238,13 @@ impl CameraBuilder {
         self
     }
 
+    /// Sets the aspect ratio the camera should maintain. See [`Camera::set_target_aspect_ratio`]
+    /// for more info.
+    pub fn with_target_aspect_ratio(mut self, aspect_ratio: Option<f32>) -> Self {
+        self.target_aspect_ratio = aspect_ratio;
+        self
+    }
+
     /// Sets desired initial state of camera: enabled or disabled.
     pub fn enabled(mut self, enabled: bool) -> Self {
         self.enabled = enabled;
@@ -1148,10 +1300,12 @@ impl CameraBuilder {
             base: self.base_builder.build_base(),
             projection: self.projection.into(),
             viewport: self.viewport.into(),
+            target_aspect_ratio: self.target_aspect_ratio.into(),
             // No need to calculate these matrices - they'll be automatically
             // recalculated before rendering.
             view_matrix: Matrix4::identity(),
             projection_matrix: Matrix4::identity(),
+            shakes: Default::default(),
             environment: self.environment.into(),
             exposure: self.exposure.into(),
             color_grading_lut: self.color_grading_lut.into(),
@@ -1171,3 +1325,164 @@ impl CameraBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            math::Rect,
+        },
+        scene::{
+            base::BaseBuilder,
+            camera::{CameraBuilder, Projection},
+            camera_shake::CameraShake,
+            graph::Graph,
+            transform::TransformBuilder,
+        },
+    };
+    use fyrox_core::reflect::Reflect;
+
+    #[test]
+    fn test_reflected_properties() {
+        let mut camera = CameraBuilder::new(BaseBuilder::new()).build_node();
+        let properties = (&camera as &dyn Reflect).properties(&[]);
+        let find = |path: &str| {
+            properties
+                .iter()
+                .find(|property| property.path == path)
+                .unwrap_or_else(|| panic!("{path} is not reflected!"))
+        };
+
+        let fov = find("projection.Perspective@fov");
+        assert_eq!(fov.type_name, std::any::type_name::<f32>());
+        assert!(!fov.read_only);
+
+        assert!(!find("base.name").read_only);
+        assert!(!find("base.visibility").read_only);
+        assert!(!find("base.local_transform").read_only);
+        assert!(find("base.global_transform").read_only);
+
+        (&mut camera as &mut dyn Reflect).set_field_by_path(
+            "projection.Perspective@fov",
+            Box::new(1.25f32),
+            &mut |result| assert!(result.is_ok()),
+        );
+        let Projection::Perspective(perspective) = camera.as_camera().projection() else {
+            unreachable!()
+        };
+        assert_eq!(perspective.fov, 1.25);
+    }
+
+    #[test]
+    fn test_letterbox_picking() {
+        let screen_size = Vector2::new(1000.0, 1000.0);
+        let mut camera = CameraBuilder::new(BaseBuilder::new())
+            .with_target_aspect_ratio(Some(16.0 / 9.0))
+            .build_camera();
+        camera.calculate_matrices(screen_size);
+
+        // Square screen with 16:9 aspect ratio - bars on the top and bottom.
+        assert_eq!(
+            camera.viewport_pixels(screen_size),
+            Rect::new(0, 218, 1000, 562)
+        );
+
+        // Clicks on the bars produce no rays.
+        assert!(camera
+            .try_make_ray(Vector2::new(500.0, 100.0), screen_size)
+            .is_none());
+        assert!(camera
+            .try_make_ray(Vector2::new(500.0, 900.0), screen_size)
+            .is_none());
+
+        // Center of the active area looks straight forward.
+        let ray = camera
+            .try_make_ray(Vector2::new(500.0, 500.0), screen_size)
+            .unwrap();
+        assert!((ray.dir.normalize() - camera.look_vector()).norm() < 1.0e-2);
+
+        // A click at the projection of a point maps back to the point.
+        let point = Vector3::new(1.0, 0.5, 10.0);
+        let screen_point = camera.project(point, screen_size).unwrap();
+        assert!(camera.is_inside_viewport(screen_point, screen_size));
+        // The point is above the center, so it is in the upper half of the active area.
+        assert!(screen_point.y > 218.0 && screen_point.y < 500.0);
+        let ray = camera.try_make_ray(screen_point, screen_size).unwrap();
+        let distance = (point - ray.origin).cross(&ray.dir).norm() / ray.dir.norm();
+        assert!(distance < 1.0e-3);
+
+        // Wide screen - bars on the left and right sides.
+        let wide_screen_size = Vector2::new(2000.0, 900.0);
+        camera.calculate_matrices(wide_screen_size);
+        assert_eq!(
+            camera.viewport_pixels(wide_screen_size),
+            Rect::new(200, 0, 1600, 900)
+        );
+        assert!(camera
+            .try_make_ray(Vector2::new(100.0, 450.0), wide_screen_size)
+            .is_none());
+        assert!(camera
+            .try_make_ray(Vector2::new(1000.0, 450.0), wide_screen_size)
+            .is_some());
+
+        // Without target aspect ratio the whole viewport is used.
+        camera.set_target_aspect_ratio(None);
+        assert_eq!(
+            camera.viewport_pixels(screen_size),
+            Rect::new(0, 0, 1000, 1000)
+        );
+        assert!(camera
+            .try_make_ray(Vector2::new(500.0, 100.0), screen_size)
+            .is_some());
+    }
+
+    #[test]
+    fn test_camera_shake() {
+        let mut graph = Graph::new();
+        let camera = CameraBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+
+        let frame_size = Vector2::new(100.0, 100.0);
+        graph.update(frame_size, 0.0, Default::default());
+        let base_view_matrix = graph[camera].view_matrix();
+        let base_transform = graph[camera].local_transform().matrix();
+
+        // Both shakes last for 0.5 seconds.
+        let first = CameraShake::new(1.0).with_decay(2.0).with_seed(1);
+        let second = CameraShake::new(0.5).with_decay(1.0).with_seed(2);
+        graph[camera].add_shake(first.clone());
+        graph[camera].add_shake(second.clone());
+        let (offset, angles) = graph[camera].shake_offset();
+        assert_eq!(offset, first.offset() + second.offset());
+        assert_eq!(angles, first.angles() + second.angles());
+
+        let mut shaken = false;
+        let mut prev_trauma = 1.0;
+        for _ in 0..40 {
+            graph.update(frame_size, 1.0 / 60.0, Default::default());
+
+            let camera = &graph[camera];
+            if let Some(shake) = camera.shakes().first() {
+                assert!(shake.trauma() < prev_trauma);
+                prev_trauma = shake.trauma();
+            }
+            if camera.view_matrix() != base_view_matrix {
+                shaken = true;
+            }
+            assert_eq!(camera.local_transform().matrix(), base_transform);
+        }
+
+        assert!(shaken);
+        assert!(graph[camera].shakes().is_empty());
+        assert_eq!(graph[camera].shake_offset(), Default::default());
+        assert_eq!(graph[camera].view_matrix(), base_view_matrix);
+        assert_eq!(graph[camera].local_transform().matrix(), base_transform);
+    }
+}
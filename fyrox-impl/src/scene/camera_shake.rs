@@ -0,0 +1,183 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Camera shake is a short-living perturbation of a camera, that is used to emphasize impacts,
+//! explosions, etc. See [`CameraShake`] docs for more info.
+
+use crate::core::{
+    algebra::{UnitQuaternion, Vector3},
+    math::lerpf,
+};
+
+/// Camera shake perturbs position and orientation of a camera using smooth noise. The strength of
+/// the shake is defined by its _trauma_ - a value in `[0; 1]` range, that decreases linearly with
+/// the speed defined by [`Self::decay`]. The actual strength is proportional to the square of the
+/// trauma, which makes the shake fade out smoothly.
+///
+/// The shake is applied on top of the transform of a camera and it never modifies the transform
+/// itself, only the view matrix of the camera is affected. It means that any code, that controls
+/// the camera, works as usual while the camera is shaking. Multiple shakes could be added to the
+/// same camera, their offsets are summed.
+///
+/// ```rust
+/// # use fyrox_impl::scene::{camera::Camera, camera_shake::CameraShake};
+/// fn on_explosion(camera: &mut Camera, distance: f32) {
+///     let trauma = (1.0 - distance / 50.0).clamp(0.0, 1.0);
+///     camera.add_shake(CameraShake::new(trauma).with_amplitude(0.3).with_frequency(20.0));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraShake {
+    /// Maximum offset (in meters) of the camera along each of its local axes.
+    pub amplitude: f32,
+    /// Maximum rotation (in radians) of the camera around each of its local axes.
+    pub angular_amplitude: f32,
+    /// Frequency of the noise in Hz. Larger values make the shake more violent.
+    pub frequency: f32,
+    /// Amount of trauma (per second) that is removed from the shake. The shake lasts for
+    /// `initial_trauma / decay` seconds.
+    pub decay: f32,
+    trauma: f32,
+    time: f32,
+    seed: u32,
+}
+
+impl Default for CameraShake {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+fn hash(seed: u32, n: i32) -> f32 {
+    let mut x = seed ^ (n as u32).wrapping_mul(0x9E37_79B9);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    // Map to [-1; 1] range.
+    (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+// Smooth value noise in [-1; 1] range.
+fn noise(seed: u32, t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let k = f * f * (3.0 - 2.0 * f);
+    lerpf(hash(seed, i as i32), hash(seed, i as i32 + 1), k)
+}
+
+impl CameraShake {
+    /// Creates a new shake with the given initial trauma (clamped to `[0; 1]` range) and default
+    /// parameters.
+    pub fn new(trauma: f32) -> Self {
+        Self {
+            amplitude: 0.2,
+            angular_amplitude: 2.0f32.to_radians(),
+            frequency: 15.0,
+            decay: 1.0,
+            trauma: trauma.clamp(0.0, 1.0),
+            time: 0.0,
+            seed: crate::rand::random(),
+        }
+    }
+
+    /// Sets the maximum offset of the camera.
+    pub fn with_amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    /// Sets the maximum rotation of the camera.
+    pub fn with_angular_amplitude(mut self, angular_amplitude: f32) -> Self {
+        self.angular_amplitude = angular_amplitude;
+        self
+    }
+
+    /// Sets the frequency of the noise.
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Sets the decay speed of the trauma.
+    pub fn with_decay(mut self, decay: f32) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    /// Sets the seed of the noise. By default, every shake has a random seed.
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Adds the given amount of trauma to the shake. The resulting trauma is clamped to `[0; 1]`
+    /// range.
+    pub fn add_trauma(&mut self, trauma: f32) {
+        self.trauma = (self.trauma + trauma).clamp(0.0, 1.0);
+    }
+
+    /// Returns current trauma of the shake.
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    /// Returns `true` if the shake has faded out completely.
+    pub fn is_finished(&self) -> bool {
+        self.trauma <= 0.0
+    }
+
+    /// Advances the shake by the given time step.
+    pub fn update(&mut self, dt: f32) {
+        self.time += dt;
+        self.trauma = (self.trauma - self.decay * dt).max(0.0);
+    }
+
+    fn strength(&self) -> f32 {
+        self.trauma * self.trauma
+    }
+
+    fn noise_vector(&self, channel: u32) -> Vector3<f32> {
+        let t = self.time * self.frequency;
+        let seed = self.seed.wrapping_add(channel.wrapping_mul(3));
+        Vector3::new(
+            noise(seed, t),
+            noise(seed.wrapping_add(1), t),
+            noise(seed.wrapping_add(2), t),
+        )
+    }
+
+    /// Returns current offset of the camera in its local space.
+    pub fn offset(&self) -> Vector3<f32> {
+        self.noise_vector(0) * self.amplitude * self.strength()
+    }
+
+    /// Returns current rotation angles (around X, Y, Z axes in radians) of the camera in its local
+    /// space.
+    pub fn angles(&self) -> Vector3<f32> {
+        self.noise_vector(1) * self.angular_amplitude * self.strength()
+    }
+
+    /// Returns current rotation of the camera in its local space.
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        let angles = self.angles();
+        UnitQuaternion::from_euler_angles(angles.x, angles.y, angles.z)
+    }
+}
@@ -0,0 +1,861 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Cloth is a piece of fabric simulated using Verlet integration. See [`Cloth`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, Vector2, Vector3, Vector4},
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{constructor::ConstructorProvider, SceneGraph},
+    material::{Material, MaterialResource},
+    renderer::{
+        self,
+        bundle::{RenderContext, SurfaceInstanceData},
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{Graph, NodePool},
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            RenderPath,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, RdcControlFlow, UpdateContext},
+        verlet::{DistanceConstraint, VerletParticle, VerletState},
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+/// Maximum relative elongation of a structural constraint. Constraints that are still stretched
+/// more than that after the solver iterations are projected back to this length. It prevents the
+/// cloth from being over-stretched when the solver cannot converge in the given amount of
+/// iterations (for example, when a pin moves too fast).
+const MAX_STRETCH: f32 = 1.1;
+
+/// A pin attaches a particle of a cloth to a scene node.
+#[derive(Visit, Reflect, Clone, Debug, Default, PartialEq)]
+pub struct ClothPin {
+    /// Index of a particle of the cloth. Particles are stored row by row, so the index of a
+    /// particle at `(x, z)` is `z * resolution.x + x`.
+    pub particle: u32,
+    /// A node to which the particle is attached. It could be any node, including bones of skinned
+    /// meshes. If the handle is [`Handle::NONE`], the particle is attached to the cloth node
+    /// itself.
+    pub node: Handle<Node>,
+}
+
+impl ClothPin {
+    /// Creates a new pin that attaches the given particle to the given node.
+    pub fn new(particle: u32, node: Handle<Node>) -> Self {
+        Self { particle, node }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PinState {
+    particle: usize,
+    node: Handle<Node>,
+    // Position of the particle in the local coordinates of the pin target.
+    offset: Vector3<f32>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ClothState {
+    body: VerletState,
+    // Indices of the structural constraints of the body.
+    structural: Vec<usize>,
+    pins: Vec<PinState>,
+}
+
+/// Cloth is a rectangular piece of fabric, that is simulated as a grid of particles connected
+/// with distance constraints. It could be used for flags, capes, curtains and so on.
+///
+/// # Simulation
+///
+/// Particles are integrated using Verlet integration and affected by gravity and wind. After the
+/// integration, distance constraints between neighbouring particles (structural, shear and
+/// bending ones) are solved iteratively - the more iterations, the stiffer the cloth. Structural
+/// constraints that are still stretched too much after the iterations are projected to the
+/// maximum allowed length, so the cloth never stretches more than by 10%. The solver is shared
+/// with [`super::rope::Rope`] (see [`super::verlet`]).
+///
+/// The simulation is performed in world coordinates, which means that moving the cloth node does
+/// not move the particles directly - only the pinned particles follow their pins and drag the
+/// rest of the cloth with them.
+///
+/// # Layout
+///
+/// In the rest state, the cloth is a grid of particles in the local XZ plane of the node, centred
+/// at the origin. The grid has `resolution.x` particles along the X axis and `resolution.y`
+/// particles along the Z axis, its physical size is defined by [`Self::set_size`].
+///
+/// # Pinning
+///
+/// Any particle could be pinned to a scene node (see [`ClothPin`]). The offset of the particle
+/// relative to the node is calculated when the simulation starts, after that the particle
+/// follows the node. Particles pinned to [`Handle::NONE`] stay at their rest position relative
+/// to the cloth node. A cloth without pins just falls down.
+///
+/// # Rendering
+///
+/// The shape of the cloth is written to a dynamic surface (see [`SurfaceData::update_vertices`])
+/// every frame, so only the changed part of the vertex buffer is uploaded to the GPU.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector2, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         cloth::{Cloth, ClothBuilder, ClothPin},
+/// #         graph::Graph,
+/// #     },
+/// # };
+/// fn create_curtain(graph: &mut Graph) -> Handle<Cloth> {
+///     ClothBuilder::new(BaseBuilder::new())
+///         .with_resolution(Vector2::new(16, 16))
+///         // Attach both corners of the first row to the cloth node itself.
+///         .with_pins(vec![
+///             ClothPin::new(0, Handle::NONE),
+///             ClothPin::new(15, Handle::NONE),
+///         ])
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Cloth {
+    base: Base,
+
+    #[reflect(setter = "set_size")]
+    size: InheritableVariable<Vector2<f32>>,
+
+    #[reflect(setter = "set_resolution")]
+    resolution: InheritableVariable<Vector2<u32>>,
+
+    #[reflect(setter = "set_gravity")]
+    gravity: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(setter = "set_wind")]
+    wind: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_damping")]
+    damping: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_stiffness")]
+    stiffness: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, step = 1.0)]
+    #[reflect(setter = "set_solver_iterations")]
+    solver_iterations: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_pins")]
+    pins: InheritableVariable<Vec<ClothPin>>,
+
+    #[reflect(setter = "set_material")]
+    material: InheritableVariable<MaterialResource>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state: ClothState,
+}
+
+impl Default for Cloth {
+    fn default() -> Self {
+        ClothBuilder::new(BaseBuilder::new()).build_cloth()
+    }
+}
+
+impl Deref for Cloth {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Cloth {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Cloth {
+    fn type_uuid() -> Uuid {
+        uuid!("b7e2c4a1-6d3f-4f8e-a5b9-0c1d2e3f4a5b")
+    }
+}
+
+impl Cloth {
+    /// Sets physical size of the cloth (in meters) along the local X and Z axes. Changing the
+    /// size restarts the simulation.
+    pub fn set_size(&mut self, size: Vector2<f32>) -> Vector2<f32> {
+        self.reset();
+        self.size
+            .set_value_and_mark_modified(size.sup(&Vector2::repeat(0.001)))
+    }
+
+    /// Returns physical size of the cloth.
+    pub fn size(&self) -> Vector2<f32> {
+        *self.size
+    }
+
+    /// Sets amount of particles along the local X and Z axes. There must be at least two
+    /// particles along each axis. Changing the resolution restarts the simulation.
+    pub fn set_resolution(&mut self, resolution: Vector2<u32>) -> Vector2<u32> {
+        self.reset();
+        self.resolution
+            .set_value_and_mark_modified(resolution.sup(&Vector2::repeat(2)))
+    }
+
+    /// Returns amount of particles along the local X and Z axes.
+    pub fn resolution(&self) -> Vector2<u32> {
+        *self.resolution
+    }
+
+    /// Sets gravity acceleration (in m/s²) in world coordinates.
+    pub fn set_gravity(&mut self, gravity: Vector3<f32>) -> Vector3<f32> {
+        self.gravity.set_value_and_mark_modified(gravity)
+    }
+
+    /// Returns gravity acceleration.
+    pub fn gravity(&self) -> Vector3<f32> {
+        *self.gravity
+    }
+
+    /// Sets wind acceleration (in m/s²) in world coordinates. Wind pushes every particle along
+    /// the normal of the cloth at the particle, proportionally to how much the cloth faces the
+    /// wind. It means that a cloth which is parallel to the wind is not affected by it.
+    pub fn set_wind(&mut self, wind: Vector3<f32>) -> Vector3<f32> {
+        self.wind.set_value_and_mark_modified(wind)
+    }
+
+    /// Returns wind acceleration.
+    pub fn wind(&self) -> Vector3<f32> {
+        *self.wind
+    }
+
+    /// Sets velocity damping (in 1/s). It defines how fast particles lose their velocity.
+    pub fn set_damping(&mut self, damping: f32) -> f32 {
+        self.damping.set_value_and_mark_modified(damping.max(0.0))
+    }
+
+    /// Returns velocity damping.
+    pub fn damping(&self) -> f32 {
+        *self.damping
+    }
+
+    /// Sets stiffness of the distance constraints in `[0; 1]` range. Lower values make the cloth
+    /// more elastic.
+    pub fn set_stiffness(&mut self, stiffness: f32) -> f32 {
+        self.stiffness
+            .set_value_and_mark_modified(stiffness.clamp(0.0, 1.0))
+    }
+
+    /// Returns stiffness of the distance constraints.
+    pub fn stiffness(&self) -> f32 {
+        *self.stiffness
+    }
+
+    /// Sets amount of iterations of the constraint solver per update. More iterations make the
+    /// cloth stiffer, but the simulation becomes more expensive.
+    pub fn set_solver_iterations(&mut self, iterations: u32) -> u32 {
+        self.solver_iterations
+            .set_value_and_mark_modified(iterations.max(1))
+    }
+
+    /// Returns amount of iterations of the constraint solver per update.
+    pub fn solver_iterations(&self) -> u32 {
+        *self.solver_iterations
+    }
+
+    /// Sets a new set of pins. Changing the pins restarts the simulation.
+    pub fn set_pins(&mut self, pins: Vec<ClothPin>) -> Vec<ClothPin> {
+        self.reset();
+        self.pins.set_value_and_mark_modified(pins)
+    }
+
+    /// Returns a set of pins of the cloth.
+    pub fn pins(&self) -> &[ClothPin] {
+        &self.pins
+    }
+
+    /// Sets a material that will be used to render the cloth.
+    pub fn set_material(&mut self, material: MaterialResource) -> MaterialResource {
+        self.material.set_value_and_mark_modified(material)
+    }
+
+    /// Returns current material of the cloth.
+    pub fn material(&self) -> &MaterialResource {
+        &self.material
+    }
+
+    /// Returns simulated particles of the cloth. The slice is empty until the first update of
+    /// the cloth.
+    pub fn particles(&self) -> &[VerletParticle] {
+        &self.state.body.particles
+    }
+
+    /// Returns a surface with the current shape of the cloth in world coordinates. It is [`None`]
+    /// until the first update of the cloth.
+    pub fn surface(&self) -> Option<&SurfaceResource> {
+        self.state.body.surface.as_ref()
+    }
+
+    /// Drops the current simulation state, the cloth will be returned to its rest state on the
+    /// next update.
+    pub fn reset(&mut self) {
+        self.state = Default::default();
+    }
+
+    fn grid_size(&self) -> (usize, usize) {
+        (
+            self.resolution.x.max(2) as usize,
+            self.resolution.y.max(2) as usize,
+        )
+    }
+
+    fn initialize(&mut self, nodes: &NodePool) {
+        let (width, height) = self.grid_size();
+        let global_transform = self.global_transform();
+
+        let mut local_positions = Vec::with_capacity(width * height);
+        let mut particles = Vec::with_capacity(width * height);
+        for z in 0..height {
+            for x in 0..width {
+                let local_position = Vector3::new(
+                    (x as f32 / (width - 1) as f32 - 0.5) * self.size.x,
+                    0.0,
+                    (z as f32 / (height - 1) as f32 - 0.5) * self.size.y,
+                );
+                let position = global_transform
+                    .transform_point(&Point3::from(local_position))
+                    .coords;
+                local_positions.push(local_position);
+                particles.push(VerletParticle::new(position));
+            }
+        }
+
+        let mut constraints = Vec::new();
+        let mut structural_constraints = Vec::new();
+        let mut link = |a: usize, b: usize, structural: bool| {
+            if structural {
+                structural_constraints.push(constraints.len());
+            }
+            constraints.push(DistanceConstraint::new(&particles, a, b))
+        };
+        for z in 0..height {
+            for x in 0..width {
+                let i = z * width + x;
+                if x + 1 < width {
+                    link(i, i + 1, true);
+                }
+                if z + 1 < height {
+                    link(i, i + width, true);
+                }
+                if x + 1 < width && z + 1 < height {
+                    link(i, i + width + 1, false);
+                    link(i + 1, i + width, false);
+                }
+                if x + 2 < width {
+                    link(i, i + 2, false);
+                }
+                if z + 2 < height {
+                    link(i, i + 2 * width, false);
+                }
+            }
+        }
+
+        let mut pins = Vec::new();
+        for pin in self.pins.iter() {
+            let particle = pin.particle as usize;
+            if particle >= particles.len() {
+                continue;
+            }
+
+            let offset = if pin.node.is_none() {
+                local_positions[particle]
+            } else if let Ok(node) = nodes.try_get(pin.node) {
+                node.global_transform()
+                    .try_inverse()
+                    .unwrap_or_default()
+                    .transform_point(&Point3::from(particles[particle].position))
+                    .coords
+            } else {
+                continue;
+            };
+
+            particles[particle].pinned = true;
+            pins.push(PinState {
+                particle,
+                node: pin.node,
+                offset,
+            });
+        }
+
+        let mut triangles = Vec::with_capacity((width - 1) * (height - 1) * 2);
+        for z in 0..height - 1 {
+            for x in 0..width - 1 {
+                let i0 = (z * width + x) as u32;
+                let i1 = i0 + 1;
+                let i2 = i0 + width as u32;
+                let i3 = i2 + 1;
+                triangles.push(TriangleDefinition([i0, i2, i1]));
+                triangles.push(TriangleDefinition([i1, i2, i3]));
+            }
+        }
+
+        let vertices = make_vertices(&particles, width, height, &triangles);
+        let mut data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+        );
+        data.set_dynamic(true);
+
+        self.state = ClothState {
+            body: VerletState {
+                particles,
+                constraints,
+                surface: Some(SurfaceResource::new_embedded(data)),
+                world_bounding_box: Default::default(),
+            },
+            structural: structural_constraints,
+            pins,
+        };
+        self.state.body.update_bounding_box(0.0);
+    }
+
+    fn simulate(&mut self, nodes: &NodePool, dt: f32) {
+        let global_transform = self.global_transform();
+        let ClothState {
+            body,
+            structural,
+            pins,
+        } = &mut self.state;
+
+        for pin in pins.iter() {
+            let transform = if pin.node.is_none() {
+                global_transform
+            } else if let Ok(node) = nodes.try_get(pin.node) {
+                node.global_transform()
+            } else {
+                // The pin target was deleted, keep the particle where it is.
+                continue;
+            };
+            body.particles[pin.particle]
+                .teleport(transform.transform_point(&Point3::from(pin.offset)).coords);
+        }
+
+        // Wind force depends on the normals of the cloth, they're taken from the surface that
+        // was generated at the previous step.
+        let normals = body
+            .surface
+            .as_ref()
+            .and_then(|surface| {
+                surface
+                    .data_ref()
+                    .vertex_buffer
+                    .cast_data_ref::<StaticVertex>()
+                    .ok()
+                    .map(|vertices| vertices.iter().map(|v| v.normal).collect::<Vec<_>>())
+            })
+            .unwrap_or_default();
+
+        let gravity = *self.gravity;
+        let wind = *self.wind;
+        body.integrate(*self.damping, dt, |i| {
+            let mut acceleration = gravity;
+            if let Some(normal) = normals.get(i) {
+                acceleration += normal.scale(normal.dot(&wind));
+            }
+            acceleration
+        });
+
+        body.solve_constraints(*self.stiffness, *self.solver_iterations);
+
+        for &index in structural.iter() {
+            let constraint = body.constraints[index];
+            let max_length = constraint.rest_length * MAX_STRETCH;
+            if constraint.length(&body.particles) > max_length {
+                constraint.solve(&mut body.particles, max_length, 1.0);
+            }
+        }
+
+        body.update_bounding_box(0.0);
+    }
+
+    fn update_surface(&self) {
+        let Some(surface) = self.state.body.surface.as_ref() else {
+            return;
+        };
+        let (width, height) = self.grid_size();
+        let mut data = surface.data_ref();
+        let vertices = make_vertices(
+            &self.state.body.particles,
+            width,
+            height,
+            data.geometry_buffer.triangles_ref(),
+        );
+        // Vertex layout and amount of vertices never change, so it cannot fail.
+        data.update_vertices(&vertices).unwrap();
+    }
+}
+
+fn make_vertices(
+    particles: &[VerletParticle],
+    width: usize,
+    height: usize,
+    triangles: &[TriangleDefinition],
+) -> Vec<StaticVertex> {
+    let mut normals = vec![Vector3::<f32>::zeros(); particles.len()];
+    for triangle in triangles {
+        let [a, b, c] = triangle.0.map(|i| i as usize);
+        let normal = (particles[b].position - particles[a].position)
+            .cross(&(particles[c].position - particles[a].position));
+        for i in [a, b, c] {
+            normals[i] += normal;
+        }
+    }
+
+    let mut vertices = Vec::with_capacity(particles.len());
+    for z in 0..height {
+        for x in 0..width {
+            let i = z * width + x;
+            let normal = normals[i]
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+            let next = if x + 1 < width { i + 1 } else { i };
+            let prev = if x > 0 { i - 1 } else { i };
+            let direction = particles[next].position - particles[prev].position;
+            let tangent = (direction - normal.scale(direction.dot(&normal)))
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::x);
+            vertices.push(StaticVertex {
+                position: particles[i].position,
+                tex_coord: Vector2::new(
+                    x as f32 / (width - 1) as f32,
+                    z as f32 / (height - 1) as f32,
+                ),
+                normal,
+                tangent: Vector4::new(tangent.x, tangent.y, tangent.z, 1.0),
+            });
+        }
+    }
+    vertices
+}
+
+impl ConstructorProvider<Node, Graph> for Cloth {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Cloth", |_| {
+                ClothBuilder::new(BaseBuilder::new().with_name("Cloth"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for Cloth {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let half_size = Vector3::new(self.size.x, 0.0, self.size.y).scale(0.5);
+        AxisAlignedBoundingBox::from_min_max(-half_size, half_size)
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.state.body.particles.is_empty() {
+            self.local_bounding_box()
+                .transform(&self.global_transform())
+        } else {
+            self.state.body.world_bounding_box
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.is_globally_enabled() || context.dt <= 0.0 {
+            return;
+        }
+
+        let (width, height) = self.grid_size();
+        if self.state.body.particles.len() != width * height {
+            self.initialize(context.nodes);
+        }
+
+        self.simulate(context.nodes, context.dt);
+        self.update_surface();
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) -> RdcControlFlow {
+        if !self.should_be_rendered(ctx.frustum, ctx.render_mask) {
+            return RdcControlFlow::Continue;
+        }
+
+        if renderer::is_shadow_pass(ctx.render_pass_name) && !self.cast_shadows() {
+            return RdcControlFlow::Continue;
+        }
+
+        let Some(surface) = self.state.body.surface.as_ref() else {
+            return RdcControlFlow::Continue;
+        };
+
+        ctx.storage.push(
+            surface,
+            &self.material,
+            RenderPath::Deferred,
+            ctx.calculate_sorting_index(self.world_bounding_box().center()),
+            SurfaceInstanceData {
+                // Vertices of the surface are already in world coordinates.
+                world_transform: Matrix4::identity(),
+                node_handle: self.handle(),
+                ..Default::default()
+            },
+        );
+
+        RdcControlFlow::Continue
+    }
+}
+
+/// Allows you to create a cloth in a declarative manner.
+pub struct ClothBuilder {
+    base_builder: BaseBuilder,
+    size: Vector2<f32>,
+    resolution: Vector2<u32>,
+    gravity: Vector3<f32>,
+    wind: Vector3<f32>,
+    damping: f32,
+    stiffness: f32,
+    solver_iterations: u32,
+    pins: Vec<ClothPin>,
+    material: MaterialResource,
+}
+
+impl ClothBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            size: Vector2::new(1.0, 1.0),
+            resolution: Vector2::new(16, 16),
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            wind: Vector3::zeros(),
+            damping: 0.5,
+            stiffness: 1.0,
+            solver_iterations: 8,
+            pins: Default::default(),
+            material: MaterialResource::new_ok(
+                Uuid::new_v4(),
+                Default::default(),
+                Material::standard_two_sides(),
+            ),
+        }
+    }
+
+    /// Sets desired physical size of the cloth.
+    pub fn with_size(mut self, size: Vector2<f32>) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Sets desired amount of particles along the local X and Z axes.
+    pub fn with_resolution(mut self, resolution: Vector2<u32>) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Sets desired gravity acceleration.
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets desired wind acceleration.
+    pub fn with_wind(mut self, wind: Vector3<f32>) -> Self {
+        self.wind = wind;
+        self
+    }
+
+    /// Sets desired velocity damping.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets desired stiffness of the distance constraints.
+    pub fn with_stiffness(mut self, stiffness: f32) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    /// Sets desired amount of iterations of the constraint solver.
+    pub fn with_solver_iterations(mut self, iterations: u32) -> Self {
+        self.solver_iterations = iterations;
+        self
+    }
+
+    /// Sets desired pins.
+    pub fn with_pins(mut self, pins: Vec<ClothPin>) -> Self {
+        self.pins = pins;
+        self
+    }
+
+    /// Sets desired material.
+    pub fn with_material(mut self, material: MaterialResource) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Creates new cloth.
+    pub fn build_cloth(self) -> Cloth {
+        Cloth {
+            base: self.base_builder.build_base(),
+            size: self.size.sup(&Vector2::repeat(0.001)).into(),
+            resolution: self.resolution.sup(&Vector2::repeat(2)).into(),
+            gravity: self.gravity.into(),
+            wind: self.wind.into(),
+            damping: self.damping.max(0.0).into(),
+            stiffness: self.stiffness.clamp(0.0, 1.0).into(),
+            solver_iterations: self.solver_iterations.max(1).into(),
+            pins: self.pins.into(),
+            material: self.material.into(),
+            state: Default::default(),
+        }
+    }
+
+    /// Creates new cloth node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_cloth())
+    }
+
+    /// Creates new instance of cloth node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Cloth> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            cloth::{ClothBuilder, ClothPin},
+            graph::Graph,
+        },
+    };
+
+    #[test]
+    fn test_pinned_cloth_drapes() {
+        let mut graph = Graph::new();
+
+        // 2x2 meters cloth, pinned at both corners of the first row.
+        let cloth = ClothBuilder::new(BaseBuilder::new())
+            .with_size(Vector2::new(2.0, 2.0))
+            .with_resolution(Vector2::new(10, 10))
+            .with_damping(2.0)
+            .with_pins(vec![
+                ClothPin::new(0, Handle::NONE),
+                ClothPin::new(9, Handle::NONE),
+            ])
+            .build(&mut graph);
+
+        let mut previous = Vec::new();
+        for _ in 0..600 {
+            previous = graph[cloth]
+                .particles()
+                .iter()
+                .map(|p| p.position)
+                .collect::<Vec<_>>();
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+
+        let cloth = &graph[cloth];
+        let particles = cloth.particles();
+        assert_eq!(particles.len(), 100);
+        assert!(cloth.surface().is_some());
+        assert!(particles
+            .iter()
+            .all(|p| p.position.iter().all(|c| c.is_finite())));
+
+        // Pinned particles must stay in place.
+        assert!(
+            particles[0]
+                .position
+                .metric_distance(&Vector3::new(-1.0, 0.0, -1.0))
+                < 1.0e-5
+        );
+        assert!(
+            particles[9]
+                .position
+                .metric_distance(&Vector3::new(1.0, 0.0, -1.0))
+                < 1.0e-5
+        );
+
+        // The rest of the cloth hangs below the pins.
+        let lowest = particles
+            .iter()
+            .map(|p| p.position.y)
+            .fold(f32::MAX, f32::min);
+        assert!(lowest < -1.5 && lowest > -2.5);
+
+        // Structural constraints must not be over-stretched.
+        let spacing = 2.0 / 9.0;
+        for z in 0..10 {
+            for x in 0..10 {
+                let i = z * 10 + x;
+                if x + 1 < 10 {
+                    let distance = particles[i]
+                        .position
+                        .metric_distance(&particles[i + 1].position);
+                    assert!(distance <= spacing * 1.11);
+                }
+                if z + 1 < 10 {
+                    let distance = particles[i]
+                        .position
+                        .metric_distance(&particles[i + 10].position);
+                    assert!(distance <= spacing * 1.11);
+                }
+            }
+        }
+
+        // The cloth must come to rest.
+        for (particle, previous) in particles.iter().zip(previous) {
+            assert!(particle.position.metric_distance(&previous) < 1.0e-3);
+        }
+    }
+}
@@ -24,7 +24,7 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
-        algebra::Vector3,
+        algebra::{Point3, UnitQuaternion, Vector2, Vector3},
         log::Log,
         math::aabb::AxisAlignedBoundingBox,
         num_traits::{NumCast, One, ToPrimitive, Zero},
@@ -41,11 +41,13 @@ use crate::{
             physics::{CoefficientCombineRule, ContactPair, IntersectionPair, PhysicsWorld},
             Graph,
         },
+        mesh::Mesh,
         node::{Node, NodeTrait, SyncContext},
-        rigidbody::RigidBody,
+        rigidbody::{RigidBody, RigidBodyType},
         Scene,
     },
 };
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_core::algebra::{Isometry3, Translation3};
 use fyrox_core::uuid_provider;
 
@@ -209,6 +211,33 @@ pub struct TrimeshShape {
     pub sources: Vec<GeometrySource>,
 }
 
+/// An error that may occur when a triangle mesh collider is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimeshColliderError {
+    /// The given handle does not point to a rigid body.
+    InvalidBody,
+    /// The given handle does not point to a mesh.
+    InvalidMesh,
+    /// Triangle meshes have no volume, and thus no mass and inertia, so they cannot be used with
+    /// dynamic rigid bodies.
+    DynamicBody,
+}
+
+impl std::fmt::Display for TrimeshColliderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrimeshColliderError::InvalidBody => write!(f, "Rigid body handle is invalid!"),
+            TrimeshColliderError::InvalidMesh => write!(f, "Mesh handle is invalid!"),
+            TrimeshColliderError::DynamicBody => write!(
+                f,
+                "Triangle mesh colliders cannot be used with dynamic rigid bodies!"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TrimeshColliderError {}
+
 /// Arbitrary height field shape.
 #[derive(Default, Clone, Debug, Visit, Reflect, PartialEq, Eq)]
 pub struct HeightfieldShape {
@@ -223,6 +252,265 @@ pub struct ConvexPolyhedronShape {
     pub geometry_source: GeometrySource,
 }
 
+/// A child shape of a [`CompoundShape`].
+#[derive(Clone, Debug, Default, Visit, Reflect, PartialEq)]
+pub struct CompoundShapeChild {
+    /// Position of the shape relative to the collider.
+    pub position: Vector3<f32>,
+    /// Rotation of the shape relative to the collider.
+    pub rotation: UnitQuaternion<f32>,
+    /// Child shape. Triangle meshes, height fields, polyhedra and other compound shapes cannot be
+    /// used as child shapes and will be ignored.
+    pub shape: ColliderShape,
+}
+
+/// A shape that is composed of multiple child shapes, each with its own local transform. It allows
+/// you to approximate complex objects with a set of simple shapes (boxes, balls, capsules, etc.)
+/// using a single collider of a rigid body. Child shapes could be added or removed at any time,
+/// use [`Collider::shape_mut`] to get the shape so the changes will be applied to the physical
+/// entity.
+#[derive(Clone, Debug, Default, Visit, Reflect, PartialEq)]
+pub struct CompoundShape {
+    /// Child shapes of the compound shape.
+    pub children: Vec<CompoundShapeChild>,
+}
+
+impl CompoundShape {
+    /// Adds a new child shape with the given local transform and returns its index.
+    pub fn add_child(
+        &mut self,
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+        shape: ColliderShape,
+    ) -> usize {
+        self.children.push(CompoundShapeChild {
+            position,
+            rotation,
+            shape,
+        });
+        self.children.len() - 1
+    }
+
+    /// Removes a child shape at the given index. Indices of all the following child shapes are
+    /// shifted by one.
+    pub fn remove_child(&mut self, index: usize) -> Option<CompoundShapeChild> {
+        (index < self.children.len()).then(|| self.children.remove(index))
+    }
+}
+
+/// A single face of a [`ConvexShape`].
+#[derive(Default, Clone, Debug, Visit, Reflect, PartialEq, Eq)]
+pub struct ConvexFace {
+    /// Indices of the vertices of the face. The face is a convex polygon, its vertices are listed
+    /// in counter-clockwise order when looking at the face from outside of the hull.
+    pub indices: Vec<u32>,
+}
+
+/// An error that may occur during convex hull computation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvexHullError {
+    /// There are less than three distinct points in the input set.
+    NotEnoughPoints,
+    /// All the points lie on a single line.
+    Collinear,
+}
+
+impl std::fmt::Display for ConvexHullError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConvexHullError::NotEnoughPoints => {
+                write!(
+                    f,
+                    "At least three distinct points are needed to build a convex hull!"
+                )
+            }
+            ConvexHullError::Collinear => {
+                write!(
+                    f,
+                    "Unable to build a convex hull, all the points are collinear!"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConvexHullError {}
+
+/// Convex hull shape defined by a set of vertices and faces in local coordinates of the collider.
+/// Usually created from a set of points using [`ConvexShape::from_points`] or from a mesh surface
+/// using [`crate::scene::mesh::surface::SurfaceData::to_convex_hull`].
+///
+/// Unlike [`ConvexPolyhedronShape`], it does not depend on any other scene node, which makes it
+/// suitable for dynamic bodies that should be moved between scenes or spawned at runtime.
+#[derive(Default, Clone, Debug, Visit, Reflect, PartialEq)]
+pub struct ConvexShape {
+    /// Vertices of the hull.
+    pub vertices: Vec<Vector3<f32>>,
+    /// Faces of the hull.
+    pub faces: Vec<ConvexFace>,
+}
+
+impl ConvexShape {
+    /// Computes the convex hull of the given set of points using quickhull algorithm. Coincident
+    /// points are merged. If all the points are lying on a single plane, the result is a flat hull
+    /// with two faces (one per each side), however such hull cannot be used for collision detection
+    /// and the respective collider won't be created. Collinear points produce an error.
+    pub fn from_points(points: &[Vector3<f32>]) -> Result<Self, ConvexHullError> {
+        let mut unique = FxHashSet::default();
+        let points = points
+            .iter()
+            .filter(|p| unique.insert(p.map(f32::to_bits)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if points.len() < 3 {
+            return Err(ConvexHullError::NotEnoughPoints);
+        }
+
+        let farthest = |metric: &dyn Fn(&Vector3<f32>) -> f32| {
+            points
+                .iter()
+                .map(|p| (*p, metric(p)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap()
+        };
+
+        let origin = points[0];
+        let (end, length) = farthest(&|p| p.metric_distance(&origin));
+        let epsilon = length * 1.0e-5;
+        let axis = (end - origin).normalize();
+        let (third, distance) = farthest(&|p| {
+            let d = p - origin;
+            (d - axis.scale(axis.dot(&d))).norm()
+        });
+        if distance <= epsilon {
+            return Err(ConvexHullError::Collinear);
+        }
+
+        let normal = axis.cross(&(third - origin)).normalize();
+        let (_, height) = farthest(&|p| normal.dot(&(p - origin)).abs());
+        if height <= epsilon {
+            return Ok(Self::flat(&points, origin, axis, normal));
+        }
+
+        let points = points.into_iter().map(Point3::from).collect::<Vec<_>>();
+        let (vertices, triangles) = rapier3d::parry::transformation::convex_hull(&points);
+        let vertices = vertices.into_iter().map(|p| p.coords).collect::<Vec<_>>();
+
+        // Quickhull produces triangles, merge the coplanar ones into polygonal faces. Since the hull
+        // is convex, triangles with the same normal are always lying on the same face.
+        let normals = triangles
+            .iter()
+            .map(|t| {
+                let [a, b, c] = t.map(|i| vertices[i as usize]);
+                (b - a).cross(&(c - a)).try_normalize(f32::EPSILON)
+            })
+            .collect::<Vec<_>>();
+        let mut merged = vec![false; triangles.len()];
+        let mut faces = Vec::new();
+        for i in 0..triangles.len() {
+            if merged[i] {
+                continue;
+            }
+            merged[i] = true;
+            let Some(normal) = normals[i] else {
+                continue;
+            };
+
+            let mut edges = FxHashSet::default();
+            for j in i..triangles.len() {
+                let coplanar = j == i
+                    || (!merged[j] && normals[j].is_some_and(|n| n.dot(&normal) > 1.0 - 1.0e-4));
+                if coplanar {
+                    merged[j] = true;
+                    let [a, b, c] = triangles[j];
+                    edges.extend([(a, b), (b, c), (c, a)]);
+                }
+            }
+
+            // Inner edges are shared by two triangles of the face, only boundary edges left.
+            let next = edges
+                .iter()
+                .filter(|(a, b)| !edges.contains(&(*b, *a)))
+                .cloned()
+                .collect::<FxHashMap<u32, u32>>();
+            let Some(start) = next.keys().min().cloned() else {
+                continue;
+            };
+            let mut indices = vec![start];
+            let mut current = start;
+            while let Some(&index) = next.get(&current) {
+                if index == start || indices.len() > next.len() {
+                    break;
+                }
+                indices.push(index);
+                current = index;
+            }
+            faces.push(ConvexFace { indices });
+        }
+
+        Ok(Self { vertices, faces })
+    }
+
+    fn flat(
+        points: &[Vector3<f32>],
+        origin: Vector3<f32>,
+        axis: Vector3<f32>,
+        normal: Vector3<f32>,
+    ) -> Self {
+        // Andrew's monotone chain in the plane of the points.
+        let bitangent = normal.cross(&axis);
+        let mut projected = points
+            .iter()
+            .map(|p| {
+                let d = p - origin;
+                (Vector2::new(axis.dot(&d), bitangent.dot(&d)), *p)
+            })
+            .collect::<Vec<_>>();
+        projected.sort_by(|(a, _), (b, _)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+
+        let cross = |o: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>| {
+            (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+        };
+        let mut hull: Vec<(Vector2<f32>, Vector3<f32>)> = Vec::new();
+        for pass in [projected.clone(), projected.into_iter().rev().collect()] {
+            let start = hull.len();
+            for point in pass {
+                while hull.len() >= start + 2
+                    && cross(hull[hull.len() - 2].0, hull[hull.len() - 1].0, point.0) <= 0.0
+                {
+                    hull.pop();
+                }
+                hull.push(point);
+            }
+            hull.pop();
+        }
+
+        let count = hull.len() as u32;
+        Self {
+            vertices: hull.into_iter().map(|(_, p)| p).collect(),
+            faces: vec![
+                ConvexFace {
+                    indices: (0..count).collect(),
+                },
+                ConvexFace {
+                    indices: (0..count).rev().collect(),
+                },
+            ],
+        }
+    }
+
+    /// Returns an iterator over triangles of the faces of the hull.
+    pub fn triangles(&self) -> impl Iterator<Item = [u32; 3]> + '_ {
+        self.faces.iter().flat_map(|face| {
+            face.indices
+                .windows(2)
+                .skip(1)
+                .map(|pair| [face.indices[0], pair[0], pair[1]])
+        })
+    }
+}
+
 /// A set of bits used for pairwise collision filtering.
 #[derive(Clone, Copy, Default, PartialEq, Reflect, Eq)]
 pub struct BitMask(pub u32);
@@ -521,6 +809,10 @@ pub enum ColliderShape {
     Heightfield(HeightfieldShape),
     /// See [`ConvexPolyhedronShape`] docs.
     Polyhedron(ConvexPolyhedronShape),
+    /// See [`ConvexShape`] docs.
+    Convex(ConvexShape),
+    /// See [`CompoundShape`] docs.
+    Compound(CompoundShape),
 }
 
 uuid_provider!(ColliderShape = "2e627337-71ea-4b33-a5f1-be697f705a86");
@@ -532,6 +824,12 @@ impl Default for ColliderShape {
 }
 
 impl ColliderShape {
+    /// Returns `true` if the shape can be used with a rigid body of the given type. Triangle
+    /// meshes can only be used with static and kinematic rigid bodies.
+    pub fn is_compatible_with(&self, body_type: RigidBodyType) -> bool {
+        !matches!(self, ColliderShape::Trimesh(_)) || body_type != RigidBodyType::Dynamic
+    }
+
     /// Initializes a ball shape defined by its radius.
     pub fn ball(radius: f32) -> Self {
         Self::Ball(BallShape { radius })
@@ -562,6 +860,20 @@ impl ColliderShape {
         })
     }
 
+    /// Initializes a compound shape from a set of child shapes with their local positions.
+    pub fn compound(children: impl IntoIterator<Item = (Vector3<f32>, ColliderShape)>) -> Self {
+        Self::Compound(CompoundShape {
+            children: children
+                .into_iter()
+                .map(|(position, shape)| CompoundShapeChild {
+                    position,
+                    rotation: UnitQuaternion::identity(),
+                    shape,
+                })
+                .collect(),
+        })
+    }
+
     /// Initializes a capsule shape from its endpoints and radius.
     pub fn capsule(begin: Vector3<f32>, end: Vector3<f32>, radius: f32) -> Self {
         Self::Capsule(CapsuleShape { begin, end, radius })
@@ -885,6 +1197,13 @@ impl Collider {
         *self.restitution_combine_rule
     }
 
+    /// Returns world-space bounding box of the physical shape of the collider. `None` is returned
+    /// if the collider has no physical representation yet (for example, if it is not attached
+    /// to a rigid body).
+    pub fn aabb(&self, physics: &PhysicsWorld) -> Option<AxisAlignedBoundingBox> {
+        physics.collider_aabb(self.native.get())
+    }
+
     /// Returns an iterator that yields contact information for the collider.
     /// Contacts checks between two non-sensor colliders.
     /// This includes only cases where two colliders are pressing against each other,
@@ -1042,13 +1361,17 @@ impl NodeTrait for Collider {
     fn validate(&self, scene: &Scene) -> Result<(), String> {
         let mut message = String::new();
 
-        if scene
-            .graph
-            .try_get_of_type::<RigidBody>(self.parent())
-            .is_err()
-        {
-            message += "3D Collider must be a direct child of a 3D Rigid Body node, \
-            otherwise it will not have any effect!";
+        match scene.graph.try_get_of_type::<RigidBody>(self.parent()) {
+            Ok(body) => {
+                if !self.shape.is_compatible_with(body.body_type()) {
+                    message += "Triangle mesh colliders cannot be used with dynamic rigid bodies, \
+                    use convex shapes instead!";
+                }
+            }
+            Err(_) => {
+                message += "3D Collider must be a direct child of a 3D Rigid Body node, \
+                otherwise it will not have any effect!";
+            }
         }
 
         match &*self.shape {
@@ -1197,16 +1520,52 @@ impl ColliderBuilder {
     pub fn build(self, graph: &mut Graph) -> Handle<Collider> {
         graph.add_node(self.build_node()).to_variant()
     }
+
+    /// Creates a triangle mesh collider, that uses all the surfaces of the given mesh as a geometry
+    /// source, and attaches it to the given rigid body. The shape of the builder is ignored.
+    /// Physics engine builds a bounding volume hierarchy for the triangles, so ray casts and
+    /// contact generation remain fast even for large meshes. This is the preferred way of making
+    /// static level geometry. Triangle meshes cannot be used with dynamic rigid bodies, use convex
+    /// shapes (see [`ConvexShape`]) for them instead.
+    pub fn build_trimesh(
+        self,
+        body: Handle<RigidBody>,
+        mesh: Handle<Mesh>,
+        graph: &mut Graph,
+    ) -> Result<Handle<Collider>, TrimeshColliderError> {
+        let body_type = graph
+            .try_get(body)
+            .map_err(|_| TrimeshColliderError::InvalidBody)?
+            .body_type();
+        if body_type == RigidBodyType::Dynamic {
+            return Err(TrimeshColliderError::DynamicBody);
+        }
+        if graph.try_get(mesh).is_err() {
+            return Err(TrimeshColliderError::InvalidMesh);
+        }
+        let collider = self
+            .with_shape(ColliderShape::trimesh(vec![GeometrySource(mesh.to_base())]))
+            .build(graph);
+        graph.link_nodes(collider, body);
+        Ok(collider)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::core::algebra::Vector2;
+    use crate::core::algebra::{Matrix4, Vector2};
     use crate::scene::{
         base::BaseBuilder,
         collider::{ColliderBuilder, ColliderShape},
-        graph::Graph,
+        graph::{
+            physics::{FeatureId, RayCastOptions},
+            Graph,
+        },
+        mesh::{
+            surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            MeshBuilder,
+        },
         rigidbody::{RigidBodyBuilder, RigidBodyType},
     };
 
@@ -1255,6 +1614,131 @@ mod test {
                 .count()
         );
     }
+    #[test]
+    fn test_trimesh_collider() {
+        let mut graph = Graph::new();
+
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut graph);
+
+        let dynamic_body = RigidBodyBuilder::new(BaseBuilder::new()).build(&mut graph);
+        assert_eq!(
+            ColliderBuilder::new(BaseBuilder::new()).build_trimesh(dynamic_body, mesh, &mut graph),
+            Err(TrimeshColliderError::DynamicBody)
+        );
+
+        let body = RigidBodyBuilder::new(BaseBuilder::new())
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .build_trimesh(body, mesh, &mut graph)
+            .unwrap();
+
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+        graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+
+        for direction in [Vector3::x(), Vector3::y(), -Vector3::z()] {
+            let mut intersections = Vec::new();
+            graph.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(-direction.scale(5.0)),
+                    ray_direction: direction,
+                    max_len: 10.0,
+                    groups: Default::default(),
+                    sort_results: true,
+                },
+                &mut intersections,
+            );
+            let first = intersections.first().unwrap();
+            assert_eq!(first.collider, collider);
+            assert!(matches!(first.feature, FeatureId::Face(_)));
+            // The ray must hit the face of the box that is facing the ray origin.
+            assert!(first.normal.dot(&direction).abs() > 0.999);
+            assert!(
+                first
+                    .position
+                    .coords
+                    .metric_distance(&-direction.scale(0.5))
+                    < 1.0e-4
+            );
+        }
+    }
+
+    #[test]
+    fn test_compound_shape() {
+        let mut graph = Graph::new();
+
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::compound([
+                (
+                    Vector3::new(-2.0, 0.0, 0.0),
+                    ColliderShape::cuboid(0.5, 0.5, 0.5),
+                ),
+                (
+                    Vector3::new(2.0, 0.0, 0.0),
+                    ColliderShape::cuboid(0.5, 0.5, 0.5),
+                ),
+            ]))
+            .build(&mut graph);
+        RigidBodyBuilder::new(BaseBuilder::new().with_child(collider))
+            .with_body_type(RigidBodyType::Static)
+            .build(&mut graph);
+
+        let update = |graph: &mut Graph| {
+            for _ in 0..2 {
+                graph.update(Vector2::new(800.0, 600.0), 1.0, Default::default());
+            }
+        };
+
+        let cast_down = |graph: &Graph, x: f32| {
+            let mut intersections = Vec::new();
+            graph.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::new(x, 5.0, 0.0),
+                    ray_direction: -Vector3::y(),
+                    max_len: 10.0,
+                    groups: Default::default(),
+                    sort_results: true,
+                },
+                &mut intersections,
+            );
+            intersections.first().map(|i| (i.collider, i.position.y))
+        };
+
+        update(&mut graph);
+
+        let aabb = graph[collider].aabb(&graph.physics).unwrap();
+        assert!(aabb.min.metric_distance(&Vector3::new(-2.5, -0.5, -0.5)) < 1.0e-3);
+        assert!(aabb.max.metric_distance(&Vector3::new(2.5, 0.5, 0.5)) < 1.0e-3);
+
+        for x in [-2.0, 2.0] {
+            let (hit, y) = cast_down(&graph, x).unwrap();
+            assert_eq!(hit, collider);
+            assert!((y - 0.5).abs() < 1.0e-4);
+        }
+        assert_eq!(cast_down(&graph, 0.0), None);
+
+        // Modify the shape at runtime.
+        if let ColliderShape::Compound(compound) = graph[collider].shape_mut() {
+            compound.add_child(
+                Vector3::default(),
+                UnitQuaternion::identity(),
+                ColliderShape::ball(0.5),
+            );
+            assert!(compound.remove_child(0).is_some());
+        }
+        update(&mut graph);
+
+        assert!(cast_down(&graph, 0.0).is_some());
+        assert_eq!(cast_down(&graph, -2.0), None);
+        let aabb = graph[collider].aabb(&graph.physics).unwrap();
+        assert!(aabb.min.metric_distance(&Vector3::new(-0.5, -0.5, -0.5)) < 1.0e-3);
+    }
+
     #[test]
     fn test_bitmask_display() {
         assert_eq!(
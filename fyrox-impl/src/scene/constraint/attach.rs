@@ -0,0 +1,342 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Attach constraint makes a node to follow another node. See [`AttachConstraint`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        log::Log,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        constraint,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+
+/// Attach constraint is a node that copies world position and rotation of a source node (with an
+/// optional offset) every frame. It allows a node to follow another node without being its child
+/// in the graph. For example, a weapon could be attached to a hand bone of a character, while being
+/// owned by some other part of the scene. Attach any other node to the constraint to make it follow
+/// the source node.
+///
+/// The offset is defined in the local space of the source node (without scaling), so it rotates
+/// together with the source node. The constraint is deactivated automatically when the source node
+/// is deleted.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         constraint::attach::{AttachConstraint, AttachConstraintBuilder},
+/// #         graph::Graph,
+/// #         node::Node,
+/// #     },
+/// # };
+/// fn attach_weapon(graph: &mut Graph, hand: Handle<Node>) -> Handle<AttachConstraint> {
+///     AttachConstraintBuilder::new(BaseBuilder::new())
+///         .with_source(hand)
+///         .with_position_offset(Vector3::new(0.0, 0.05, 0.1))
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct AttachConstraint {
+    base: Base,
+
+    #[reflect(setter = "set_source")]
+    source: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_position_offset")]
+    position_offset: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(setter = "set_rotation_offset")]
+    rotation_offset: InheritableVariable<UnitQuaternion<f32>>,
+
+    #[reflect(setter = "set_active")]
+    active: InheritableVariable<bool>,
+}
+
+impl Default for AttachConstraint {
+    fn default() -> Self {
+        AttachConstraintBuilder::new(BaseBuilder::new()).build_attach_constraint()
+    }
+}
+
+impl Deref for AttachConstraint {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for AttachConstraint {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for AttachConstraint {
+    fn type_uuid() -> Uuid {
+        uuid!("1f7a3c9e-8b2d-4e6f-9a5c-3d8e1b7f2c46")
+    }
+}
+
+impl AttachConstraint {
+    /// Sets a node to follow.
+    pub fn set_source(&mut self, source: Handle<Node>) -> Handle<Node> {
+        self.source.set_value_and_mark_modified(source)
+    }
+
+    /// Returns a handle of the node to follow.
+    pub fn source(&self) -> Handle<Node> {
+        *self.source
+    }
+
+    /// Sets new position offset (in the local space of the source node).
+    pub fn set_position_offset(&mut self, offset: Vector3<f32>) -> Vector3<f32> {
+        self.position_offset.set_value_and_mark_modified(offset)
+    }
+
+    /// Returns current position offset (in the local space of the source node).
+    pub fn position_offset(&self) -> Vector3<f32> {
+        *self.position_offset
+    }
+
+    /// Sets new rotation offset (relative to the rotation of the source node).
+    pub fn set_rotation_offset(&mut self, offset: UnitQuaternion<f32>) -> UnitQuaternion<f32> {
+        self.rotation_offset.set_value_and_mark_modified(offset)
+    }
+
+    /// Returns current rotation offset (relative to the rotation of the source node).
+    pub fn rotation_offset(&self) -> UnitQuaternion<f32> {
+        *self.rotation_offset
+    }
+
+    /// Activates or deactivates the constraint. Inactive constraint does not change its transform.
+    pub fn set_active(&mut self, active: bool) -> bool {
+        self.active.set_value_and_mark_modified(active)
+    }
+
+    /// Returns `true` if the constraint is active, `false` - otherwise.
+    pub fn is_active(&self) -> bool {
+        *self.active
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for AttachConstraint {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Attach Constraint", |_| {
+                AttachConstraintBuilder::new(BaseBuilder::new().with_name("Attach Constraint"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Constraint")
+    }
+}
+
+impl NodeTrait for AttachConstraint {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !*self.active || self.source.is_none() {
+            return;
+        }
+
+        let Ok(source) = context.nodes.try_get(*self.source) else {
+            Log::warn(format!(
+                "The source node {} of the attach constraint {} does not exist anymore, \
+                the constraint was deactivated.",
+                *self.source,
+                self.name()
+            ));
+            self.set_active(false);
+            return;
+        };
+
+        let source_position = source.global_position();
+        let source_rotation = constraint::global_rotation(context.nodes, *self.source);
+        let position = source_position + source_rotation.transform_vector(&self.position_offset);
+        let rotation = source_rotation * *self.rotation_offset;
+
+        let parent_inv_transform =
+            constraint::inverse_global_transform(context.nodes, self.parent());
+        let parent_rotation = constraint::global_rotation(context.nodes, self.parent());
+
+        let local_transform = self.local_transform_mut();
+        local_transform.set_position(
+            parent_inv_transform
+                .transform_point(&position.into())
+                .coords,
+        );
+        local_transform.set_rotation(parent_rotation.inverse() * rotation);
+    }
+}
+
+/// Allows you to create an attach constraint in a declarative manner.
+pub struct AttachConstraintBuilder {
+    base_builder: BaseBuilder,
+    source: Handle<Node>,
+    position_offset: Vector3<f32>,
+    rotation_offset: UnitQuaternion<f32>,
+    active: bool,
+}
+
+impl AttachConstraintBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            source: Default::default(),
+            position_offset: Default::default(),
+            rotation_offset: UnitQuaternion::identity(),
+            active: true,
+        }
+    }
+
+    /// Sets a node to follow.
+    pub fn with_source(mut self, source: Handle<Node>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Sets desired position offset (in the local space of the source node).
+    pub fn with_position_offset(mut self, offset: Vector3<f32>) -> Self {
+        self.position_offset = offset;
+        self
+    }
+
+    /// Sets desired rotation offset (relative to the rotation of the source node).
+    pub fn with_rotation_offset(mut self, offset: UnitQuaternion<f32>) -> Self {
+        self.rotation_offset = offset;
+        self
+    }
+
+    /// Sets whether the constraint is active or not.
+    pub fn with_active(mut self, active: bool) -> Self {
+        self.active = active;
+        self
+    }
+
+    /// Creates new attach constraint.
+    pub fn build_attach_constraint(self) -> AttachConstraint {
+        AttachConstraint {
+            base: self.base_builder.build_base(),
+            source: self.source.into(),
+            position_offset: self.position_offset.into(),
+            rotation_offset: self.rotation_offset.into(),
+            active: self.active.into(),
+        }
+    }
+
+    /// Creates new attach constraint node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_attach_constraint())
+    }
+
+    /// Creates new instance of attach constraint node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<AttachConstraint> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector2, Vector3},
+        scene::{
+            base::BaseBuilder, constraint::attach::AttachConstraintBuilder, graph::Graph,
+            pivot::PivotBuilder,
+        },
+    };
+
+    fn assert_near(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!(a.metric_distance(&b) < 1.0e-4, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_attach_constraint() {
+        let mut graph = Graph::new();
+
+        let a = PivotBuilder::new(BaseBuilder::new())
+            .build(&mut graph)
+            .to_base();
+
+        let offset = Vector3::new(0.0, 1.0, 2.0);
+        let b = AttachConstraintBuilder::new(BaseBuilder::new())
+            .with_source(a)
+            .with_position_offset(offset)
+            .build(&mut graph);
+
+        for position in [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(5.0, -2.0, 3.0),
+            Vector3::new(-4.0, 7.0, 0.5),
+        ] {
+            graph[a].local_transform_mut().set_position(position);
+            graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+            graph.update_hierarchical_data();
+            assert_near(graph[b].global_position(), position + offset);
+        }
+
+        // Offset must be rotated together with the source.
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 90.0f32.to_radians());
+        graph[a].local_transform_mut().set_rotation(rotation);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        graph.update_hierarchical_data();
+        assert_near(
+            graph[b].global_position(),
+            Vector3::new(-4.0, 7.0, 0.5) + rotation.transform_vector(&offset),
+        );
+        assert_near(graph[b].look_vector(), graph[a].look_vector());
+
+        // Deleting the source disables the constraint.
+        graph.remove_node(a);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        assert!(!graph[b].is_active());
+    }
+}
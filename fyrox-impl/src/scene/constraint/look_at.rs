@@ -0,0 +1,396 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Look-at constraint orients a node toward a target node. See [`LookAtConstraint`] docs for more
+//! info.
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        constraint,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines which rotation axes could be used by a [`LookAtConstraint`]. All axes are defined in
+/// the local space of the parent node of the constraint.
+#[derive(
+    Default, Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum LookAtAxisLock {
+    /// The node could be rotated freely.
+    #[default]
+    None,
+    /// The node could be rotated only around `Y` axis. It is useful for turret bases, characters,
+    /// etc.
+    Yaw,
+    /// The node could be rotated only around `X` axis. It is useful for turret barrels, that are
+    /// attached to a turret base, that rotates around `Y` axis.
+    Pitch,
+}
+
+uuid_provider!(LookAtAxisLock = "4e2a9c7b-1d3f-4b8a-8e5c-6f0d2b9a3c71");
+
+/// Look-at constraint is a node that orients itself toward a target node every frame, so its look
+/// vector (`+Z` axis) points at the target. Attach any other node to the constraint to make it
+/// track the target. It is useful for cameras, turrets, eyes, etc.
+///
+/// The rotation could be restricted to a single axis using [`LookAtAxisLock`] and it could be
+/// smoothed by limiting rotation speed of the node. The constraint does nothing if the target is
+/// at the same position as the constraint, or if the target does not exist.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         constraint::look_at::{LookAtAxisLock, LookAtConstraint, LookAtConstraintBuilder},
+/// #         graph::Graph,
+/// #         node::Node,
+/// #     },
+/// # };
+/// fn create_turret_base(graph: &mut Graph, target: Handle<Node>) -> Handle<LookAtConstraint> {
+///     LookAtConstraintBuilder::new(BaseBuilder::new())
+///         .with_target(target)
+///         .with_axis_lock(LookAtAxisLock::Yaw)
+///         // Radians per second.
+///         .with_rotation_speed(2.0)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct LookAtConstraint {
+    base: Base,
+
+    #[reflect(setter = "set_target")]
+    target: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_axis_lock")]
+    axis_lock: InheritableVariable<LookAtAxisLock>,
+
+    #[reflect(setter = "set_rotation_speed", min_value = 0.0)]
+    rotation_speed: InheritableVariable<f32>,
+}
+
+impl Default for LookAtConstraint {
+    fn default() -> Self {
+        LookAtConstraintBuilder::new(BaseBuilder::new()).build_look_at_constraint()
+    }
+}
+
+impl Deref for LookAtConstraint {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for LookAtConstraint {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for LookAtConstraint {
+    fn type_uuid() -> Uuid {
+        uuid!("9b3d6e1f-2c4a-4f7e-a8d1-5e7c3b2f9a04")
+    }
+}
+
+impl LookAtConstraint {
+    /// Sets a node to look at.
+    pub fn set_target(&mut self, target: Handle<Node>) -> Handle<Node> {
+        self.target.set_value_and_mark_modified(target)
+    }
+
+    /// Returns a handle of the node to look at.
+    pub fn target(&self) -> Handle<Node> {
+        *self.target
+    }
+
+    /// Sets new axis lock. See [`LookAtAxisLock`] docs for more info.
+    pub fn set_axis_lock(&mut self, axis_lock: LookAtAxisLock) -> LookAtAxisLock {
+        self.axis_lock.set_value_and_mark_modified(axis_lock)
+    }
+
+    /// Returns current axis lock.
+    pub fn axis_lock(&self) -> LookAtAxisLock {
+        *self.axis_lock
+    }
+
+    /// Sets maximum rotation speed (in radians per second). Zero speed means that the node will be
+    /// rotated toward the target immediately.
+    pub fn set_rotation_speed(&mut self, rotation_speed: f32) -> f32 {
+        self.rotation_speed
+            .set_value_and_mark_modified(rotation_speed.max(0.0))
+    }
+
+    /// Returns maximum rotation speed (in radians per second).
+    pub fn rotation_speed(&self) -> f32 {
+        *self.rotation_speed
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for LookAtConstraint {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Look-At Constraint", |_| {
+                LookAtConstraintBuilder::new(BaseBuilder::new().with_name("Look-At Constraint"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Constraint")
+    }
+}
+
+impl NodeTrait for LookAtConstraint {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let Ok(target) = context.nodes.try_get(*self.target) else {
+            return;
+        };
+
+        // Calculate the direction in the local space of the parent, where the rotation of the node
+        // is defined.
+        let parent_inv_transform =
+            constraint::inverse_global_transform(context.nodes, self.parent());
+        let mut direction = parent_inv_transform
+            .transform_vector(&(target.global_position() - self.global_position()));
+        match *self.axis_lock {
+            LookAtAxisLock::None => (),
+            LookAtAxisLock::Yaw => direction.y = 0.0,
+            LookAtAxisLock::Pitch => direction.x = 0.0,
+        }
+
+        let Some(desired_rotation) = constraint::look_rotation(&direction) else {
+            return;
+        };
+
+        let current_rotation = **self.local_transform().rotation();
+        let max_angle = *self.rotation_speed * context.dt;
+        let angle = current_rotation.angle_to(&desired_rotation);
+        let new_rotation = if *self.rotation_speed > 0.0 && angle > max_angle {
+            current_rotation
+                .try_slerp(&desired_rotation, max_angle / angle, f32::EPSILON)
+                .unwrap_or(desired_rotation)
+        } else {
+            desired_rotation
+        };
+
+        self.local_transform_mut().set_rotation(new_rotation);
+    }
+}
+
+/// Allows you to create a look-at constraint in a declarative manner.
+pub struct LookAtConstraintBuilder {
+    base_builder: BaseBuilder,
+    target: Handle<Node>,
+    axis_lock: LookAtAxisLock,
+    rotation_speed: f32,
+}
+
+impl LookAtConstraintBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            target: Default::default(),
+            axis_lock: Default::default(),
+            rotation_speed: 0.0,
+        }
+    }
+
+    /// Sets a node to look at.
+    pub fn with_target(mut self, target: Handle<Node>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Sets desired axis lock.
+    pub fn with_axis_lock(mut self, axis_lock: LookAtAxisLock) -> Self {
+        self.axis_lock = axis_lock;
+        self
+    }
+
+    /// Sets maximum rotation speed (in radians per second). Zero speed means that the node will be
+    /// rotated toward the target immediately.
+    pub fn with_rotation_speed(mut self, rotation_speed: f32) -> Self {
+        self.rotation_speed = rotation_speed.max(0.0);
+        self
+    }
+
+    /// Creates new look-at constraint.
+    pub fn build_look_at_constraint(self) -> LookAtConstraint {
+        LookAtConstraint {
+            base: self.base_builder.build_base(),
+            target: self.target.into(),
+            axis_lock: self.axis_lock.into(),
+            rotation_speed: self.rotation_speed.into(),
+        }
+    }
+
+    /// Creates new look-at constraint node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_look_at_constraint())
+    }
+
+    /// Creates new instance of look-at constraint node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<LookAtConstraint> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{UnitQuaternion, Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            constraint::look_at::{LookAtAxisLock, LookAtConstraintBuilder},
+            graph::Graph,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_look_at_constraint() {
+        let mut graph = Graph::new();
+
+        let target = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(3.0, 4.0, -5.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph)
+        .to_base();
+
+        let position = Vector3::new(1.0, 1.0, 1.0);
+        let constraint = LookAtConstraintBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_target(target)
+        .build(&mut graph);
+
+        let turret = LookAtConstraintBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_target(target)
+        .with_axis_lock(LookAtAxisLock::Yaw)
+        .build(&mut graph);
+
+        // Target at the same position must not change the rotation.
+        let same_position = LookAtConstraintBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(3.0, 4.0, -5.0))
+                    .build(),
+            ),
+        )
+        .with_target(target)
+        .build(&mut graph);
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+        graph.update_hierarchical_data();
+
+        let expected = (Vector3::new(3.0, 4.0, -5.0) - position).normalize();
+        let look = graph[constraint].look_vector().normalize();
+        assert!(look.metric_distance(&expected) < 1.0e-4, "{look:?}");
+
+        let expected_yaw = Vector3::new(expected.x, 0.0, expected.z).normalize();
+        let look = graph[turret].look_vector().normalize();
+        assert!(look.metric_distance(&expected_yaw) < 1.0e-4, "{look:?}");
+
+        assert_eq!(
+            **graph[same_position].local_transform().rotation(),
+            UnitQuaternion::identity()
+        );
+    }
+
+    #[test]
+    fn test_look_at_constraint_rotation_speed() {
+        let mut graph = Graph::new();
+
+        let target = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph)
+        .to_base();
+
+        let constraint = LookAtConstraintBuilder::new(BaseBuilder::new())
+            .with_target(target)
+            .with_rotation_speed(std::f32::consts::FRAC_PI_4)
+            .build(&mut graph);
+
+        // The target is 90 degrees away, it takes two seconds to reach it.
+        graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        let rotation = **graph[constraint].local_transform().rotation();
+        assert!((rotation.angle() - std::f32::consts::FRAC_PI_4).abs() < 1.0e-4);
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        graph.update_hierarchical_data();
+        let look = graph[constraint].look_vector().normalize();
+        assert!(look.metric_distance(&Vector3::x()) < 1.0e-4, "{look:?}");
+    }
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Constraints are nodes that control their own transform using other nodes of the graph, without
+//! being linked to them in the hierarchy. See respective modules for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, UnitQuaternion, Vector3},
+        math::Matrix4Ext,
+        pool::Handle,
+    },
+    scene::{graph::NodePool, node::Node},
+};
+
+pub mod attach;
+pub mod look_at;
+
+/// Returns global rotation of a node with the given handle, without scaling. Returns identity
+/// rotation if the handle is invalid.
+pub(crate) fn global_rotation(nodes: &NodePool, handle: Handle<Node>) -> UnitQuaternion<f32> {
+    nodes
+        .try_get(handle)
+        .map(|node| {
+            UnitQuaternion::from_matrix_eps(
+                &node.global_transform().basis(),
+                10.0 * f32::EPSILON,
+                16,
+                Default::default(),
+            )
+        })
+        .unwrap_or_else(|_| UnitQuaternion::identity())
+}
+
+/// Returns inverse global transform of a node with the given handle. It could be used to transform
+/// points from world space into local space of the node. Returns identity matrix if the handle is
+/// invalid.
+pub(crate) fn inverse_global_transform(nodes: &NodePool, handle: Handle<Node>) -> Matrix4<f32> {
+    nodes
+        .try_get(handle)
+        .ok()
+        .and_then(|node| node.global_transform().try_inverse())
+        .unwrap_or_else(Matrix4::identity)
+}
+
+/// Calculates a rotation, that makes the look vector (`+Z` axis) of a node to point in the given
+/// direction. The up vector of the rotation is `+Y` axis, unless the direction is collinear with
+/// it. Returns `None` if the direction is a zero vector.
+pub(crate) fn look_rotation(direction: &Vector3<f32>) -> Option<UnitQuaternion<f32>> {
+    let direction = direction.try_normalize(f32::EPSILON)?;
+    let up = if direction.cross(&Vector3::y()).norm_squared() <= f32::EPSILON {
+        Vector3::z()
+    } else {
+        Vector3::y()
+    };
+    Some(UnitQuaternion::face_towards(&direction, &up))
+}
@@ -652,8 +652,9 @@ impl PhysicsWorld {
         };
 
         if *self.enabled {
+            let num_substeps = self.integration_parameters.num_substeps.max(1);
             let integration_parameters = rapier2d::dynamics::IntegrationParameters {
-                dt,
+                dt: dt / num_substeps as f32,
                 min_ccd_dt: self.integration_parameters.min_ccd_dt,
                 contact_damping_ratio: self.integration_parameters.contact_damping_ratio,
                 contact_natural_frequency: self.integration_parameters.contact_natural_frequency,
@@ -677,20 +678,22 @@ impl PhysicsWorld {
                 max_ccd_substeps: self.integration_parameters.max_ccd_substeps as usize,
             };
 
-            self.pipeline.step(
-                &self.gravity,
-                &integration_parameters,
-                &mut self.islands,
-                &mut self.broad_phase,
-                &mut self.narrow_phase,
-                &mut self.bodies,
-                &mut self.colliders,
-                &mut self.joints.set,
-                &mut self.multibody_joints.set,
-                &mut self.ccd_solver,
-                &(),
-                &*self.event_handler,
-            );
+            for _ in 0..num_substeps {
+                self.pipeline.step(
+                    &self.gravity,
+                    &integration_parameters,
+                    &mut self.islands,
+                    &mut self.broad_phase,
+                    &mut self.narrow_phase,
+                    &mut self.bodies,
+                    &mut self.colliders,
+                    &mut self.joints.set,
+                    &mut self.multibody_joints.set,
+                    &mut self.ccd_solver,
+                    &(),
+                    &*self.event_handler,
+                );
+            }
         }
 
         self.performance_statistics.step_time += instant::Instant::now() - time;
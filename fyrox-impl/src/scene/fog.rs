@@ -0,0 +1,318 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Fog is a simple atmospheric effect, that blends distant surfaces with a color of the fog. It
+//! is used to hide the far clipping plane of a camera, to improve depth perception and to make
+//! scenes look more natural. See [`FogSettings`] docs for more info.
+//!
+//! Besides uniform (distance-based) fog, there's height fog (see [`HeightFog`]), which density
+//! decreases exponentially with the height. It is used to simulate ground-hugging mist.
+
+use crate::core::{
+    algebra::Vector4, color::Color, reflect::prelude::*, type_traits::prelude::*,
+    visitor::prelude::*,
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the fog density changes with the distance.
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    PartialEq,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "5a3f1a2e-0b0a-4d4e-8d38-6c0e2e8f9d61")]
+pub enum FogMode {
+    /// The amount of fog grows linearly from zero at the `start` distance to one at the `end`
+    /// distance.
+    Linear {
+        /// Distance at which the fog starts.
+        #[reflect(min_value = 0.0)]
+        start: f32,
+        /// Distance at which the fog fully covers the surfaces.
+        #[reflect(min_value = 0.0)]
+        end: f32,
+    },
+    /// The amount of fog grows exponentially with the distance (`1 - e^(-density * distance)`).
+    Exp {
+        /// Density of the fog.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        density: f32,
+    },
+    /// The amount of fog grows exponentially with the squared distance
+    /// (`1 - e^(-(density * distance)^2)`). It keeps nearby surfaces clearer than [`Self::Exp`].
+    Exp2 {
+        /// Density of the fog.
+        #[reflect(min_value = 0.0, step = 0.001)]
+        density: f32,
+    },
+}
+
+impl Default for FogMode {
+    fn default() -> Self {
+        Self::Linear {
+            start: 20.0,
+            end: 100.0,
+        }
+    }
+}
+
+impl FogMode {
+    /// Returns the index of the mode along with the start, end and density of the fog in the layout
+    /// expected by `S_FogFactor` shader function.
+    fn shader_parameters(&self) -> (i32, f32, f32, f32) {
+        match *self {
+            Self::Linear { start, end } => (0, start, end, 0.0),
+            Self::Exp { density } => (1, 0.0, 0.0, density),
+            Self::Exp2 { density } => (2, 0.0, 0.0, density),
+        }
+    }
+}
+
+/// Height fog, which density decreases exponentially with the height: `density * e^(-falloff * (y - base_height))`.
+/// The amount of fog is calculated by analytical integration of the density along the view ray, so
+/// it works correctly for any position of the camera - above, inside or below the fog layer.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct HeightFog {
+    /// A flag, that defines whether the height fog is enabled or not. Default is `false`.
+    pub enabled: bool,
+    /// Height (world-space Y coordinate) at which the density of the fog is equal to `density`.
+    pub base_height: f32,
+    /// Defines how fast the density decreases with the height. Larger values make the fog layer
+    /// thinner.
+    #[reflect(min_value = 0.0, step = 0.01)]
+    pub falloff: f32,
+    /// Density of the fog at the base height.
+    #[reflect(min_value = 0.0, step = 0.001)]
+    pub density: f32,
+}
+
+impl Default for HeightFog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            base_height: 0.0,
+            falloff: 0.2,
+            density: 0.05,
+        }
+    }
+}
+
+/// Fog settings of a scene. The fog is applied to the surfaces rendered using both the deferred and
+/// the forward renderer, the amount of the fog depends on the view-space depth of the surface.
+/// Pixels at the far clipping plane (skybox or clear color) are not affected by the fog.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct FogSettings {
+    /// A flag, that defines whether the fog is enabled or not. Default is `false`.
+    pub enabled: bool,
+    /// Defines how the fog density changes with the distance. See [`FogMode`] docs for more info.
+    pub mode: FogMode,
+    /// Color of the fog.
+    pub color: Color,
+    /// Height fog settings. Height fog is combined with the distance fog. To use the height fog
+    /// alone, set the distance fog mode to [`FogMode::Exp`] with zero density. See [`HeightFog`] docs
+    /// for more info.
+    #[visit(optional)]
+    pub height_fog: HeightFog,
+}
+
+impl Default for FogSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: Default::default(),
+            color: Color::opaque(180, 180, 190),
+            height_fog: Default::default(),
+        }
+    }
+}
+
+/// Parameters of the fog in the form they are passed to the shaders (`fyrox_fogData` built-in
+/// property group and the fog pass of the deferred renderer).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct FogShaderParameters {
+    pub color: Vector4<f32>,
+    pub enabled: bool,
+    pub mode: i32,
+    pub start: f32,
+    pub end: f32,
+    pub density: f32,
+    pub height_fog_enabled: bool,
+    pub height_fog_base_height: f32,
+    pub height_fog_falloff: f32,
+    pub height_fog_density: f32,
+}
+
+impl FogSettings {
+    /// Returns the parameters of the fog for the shaders. The height fog is enabled only if both
+    /// the given flag (see [`crate::renderer::QualitySettings::height_fog_enabled`]) and
+    /// [`HeightFog::enabled`] are set. Disabled fog gives default (zero) parameters.
+    pub(crate) fn shader_parameters(&self, height_fog_enabled: bool) -> FogShaderParameters {
+        if !self.enabled {
+            return Default::default();
+        }
+        let (mode, start, end, density) = self.mode.shader_parameters();
+        FogShaderParameters {
+            color: self.color.srgb_to_linear_f32(),
+            enabled: true,
+            mode,
+            start,
+            end,
+            density,
+            height_fog_enabled: height_fog_enabled && self.height_fog.enabled,
+            height_fog_base_height: self.height_fog.base_height,
+            height_fog_falloff: self.height_fog.falloff,
+            height_fog_density: self.height_fog.density,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::color::Color,
+        material::shader::{Shader, ShaderResource, ShaderResourceExtension, ShaderResourceKind},
+        scene::fog::{FogMode, FogSettings, HeightFog},
+    };
+
+    #[test]
+    fn test_fog_shader_parameters() {
+        let mut fog = FogSettings {
+            enabled: true,
+            mode: FogMode::Linear {
+                start: 10.0,
+                end: 30.0,
+            },
+            color: Color::WHITE,
+            height_fog: Default::default(),
+        };
+
+        let parameters = fog.shader_parameters(true);
+        assert!(parameters.enabled);
+        assert_eq!(parameters.color, Color::WHITE.srgb_to_linear_f32());
+        // Indices must match `S_FOG_MODE_*` constants of the shaders.
+        assert_eq!(
+            (
+                parameters.mode,
+                parameters.start,
+                parameters.end,
+                parameters.density
+            ),
+            (0, 10.0, 30.0, 0.0)
+        );
+
+        fog.mode = FogMode::Exp { density: 0.1 };
+        let parameters = fog.shader_parameters(true);
+        assert_eq!((parameters.mode, parameters.density), (1, 0.1));
+
+        fog.mode = FogMode::Exp2 { density: 0.2 };
+        let parameters = fog.shader_parameters(true);
+        assert_eq!((parameters.mode, parameters.density), (2, 0.2));
+
+        // Disabled fog is not applied at all.
+        fog.enabled = false;
+        assert_eq!(fog.shader_parameters(true), Default::default());
+    }
+
+    #[test]
+    fn test_height_fog_shaders() {
+        // Both the deferred fog pass and the forward passes combine the distance fog with the
+        // height fog the same way.
+        const BLEND: &str = "fogFactor = 1.0 - (1.0 - fogFactor) * (1.0 - heightFog);";
+
+        let deferred = Shader::from_string(include_str!("../renderer/shaders/fog.shader")).unwrap();
+        let properties = deferred
+            .definition
+            .resources
+            .iter()
+            .find_map(|resource| match resource.kind {
+                ShaderResourceKind::PropertyGroup(ref properties)
+                    if resource.name.as_str() == "properties" =>
+                {
+                    Some(properties)
+                }
+                _ => None,
+            })
+            .unwrap();
+        for name in [
+            "heightFogEnabled",
+            "heightFogBaseHeight",
+            "heightFogFalloff",
+            "heightFogDensity",
+        ] {
+            assert!(properties
+                .iter()
+                .any(|property| property.name.as_str() == name));
+        }
+        let fragment_shader = &deferred.definition.passes[0].fragment_shader.0;
+        assert!(fragment_shader.contains("if (properties.heightFogEnabled)"));
+        assert!(fragment_shader.contains("S_HeightFog("));
+        assert!(fragment_shader.contains(BLEND));
+
+        let standard = ShaderResource::standard();
+        let standard = standard.data_ref();
+        let forward = standard
+            .definition
+            .passes
+            .iter()
+            .find(|pass| pass.name == "Forward")
+            .unwrap();
+        assert!(forward
+            .fragment_shader
+            .0
+            .contains("if (fyrox_fogData.heightFogEnabled)"));
+        assert!(forward.fragment_shader.0.contains("S_HeightFog("));
+        assert!(forward.fragment_shader.0.contains(BLEND));
+    }
+
+    #[test]
+    fn test_height_fog_alone() {
+        let mut fog = FogSettings {
+            enabled: true,
+            mode: FogMode::Exp { density: 0.0 },
+            color: Color::WHITE,
+            height_fog: HeightFog {
+                enabled: true,
+                base_height: 1.0,
+                falloff: 0.3,
+                density: 0.1,
+            },
+        };
+
+        // Zero density of the distance fog leaves only the height fog.
+        let parameters = fog.shader_parameters(true);
+        assert_eq!((parameters.mode, parameters.density), (1, 0.0));
+        assert!(parameters.height_fog_enabled);
+        assert_eq!(parameters.height_fog_base_height, 1.0);
+        assert_eq!(parameters.height_fog_falloff, 0.3);
+        assert_eq!(parameters.height_fog_density, 0.1);
+
+        // The height fog could be disabled globally.
+        assert!(!fog.shader_parameters(false).height_fog_enabled);
+        fog.height_fog.enabled = false;
+        assert!(!fog.shader_parameters(true).height_fog_enabled);
+    }
+}
@@ -0,0 +1,855 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Foliage scatters instances of a mesh over a terrain. See [`Foliage`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Point3, UnitQuaternion, Vector2, Vector3},
+        log::Log,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        rand::{Rng, SeedableRng},
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{constructor::ConstructorProvider, SceneGraph},
+    renderer::{
+        self,
+        bundle::{RenderContext, SurfaceInstanceData},
+    },
+    resource::texture::{Texture, TextureKind, TexturePixelKind, TextureResource},
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{Graph, NodePool},
+        mesh::{buffer::VertexAttributeUsage, surface::Surface, RenderPath},
+        node::{constructor::NodeConstructor, Node, NodeTrait, RdcControlFlow, UpdateContext},
+        terrain::{BrushTarget, Terrain},
+    },
+};
+use rand_chacha::ChaCha8Rng;
+use std::ops::{Deref, DerefMut, Range};
+
+/// Size of a culling cell in the local space of the terrain.
+const CELL_SIZE: f32 = 8.0;
+
+/// A single placed instance of foliage.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FoliageInstance {
+    /// Position of the instance in world coordinates.
+    pub position: Vector3<f32>,
+    /// Full world transform of the instance.
+    pub transform: Matrix4<f32>,
+}
+
+#[derive(Clone, Debug)]
+struct FoliageCell {
+    // Range of the instances of the cell in the instances array.
+    instances: Range<usize>,
+    world_bounding_box: AxisAlignedBoundingBox,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FoliageState {
+    // Instances are sorted by cells.
+    instances: Vec<FoliageInstance>,
+    cells: Vec<FoliageCell>,
+    // Global transform of the terrain at the moment of generation, `None` means that the instances
+    // must be generated.
+    terrain_transform: Option<Matrix4<f32>>,
+    world_bounding_box: AxisAlignedBoundingBox,
+}
+
+/// Foliage scatters instances of a set of surfaces (grass, bushes, rocks, etc.) over a terrain.
+/// All instances share the same surfaces, so the renderer draws them using instancing.
+///
+/// # Placement
+///
+/// The terrain is split into cells of one square unit of the local space of the terrain. Every
+/// cell gets `density * density_map_value * cell_area` instances at random positions in the cell,
+/// where `density_map_value` is the value of the density map at the center of the cell. The
+/// density map is stretched over the whole terrain - its first row corresponds to the smallest
+/// local Z coordinate of the terrain. If there's no density map, the density is uniform.
+/// Supported density map formats are `R8`, `Luminance8`, `RGBA8`, `SRGBA8` (red channel is used)
+/// and `R32F`.
+///
+/// Instances are not placed on the parts of the terrain that are steeper than the maximum slope
+/// (see [`Self::set_max_slope`]), so the grass does not grow on cliffs. Every instance has random
+/// rotation around the world Y axis and random scale (see [`Self::set_scale_variation`]). The
+/// placement is deterministic - the same settings always produce the same instances.
+///
+/// Instances are generated on the first update and re-generated when the terrain moves or when
+/// any placement parameter changes. Changes of the height map of the terrain or of the contents
+/// of the density map are not tracked, call [`Self::regenerate`] after such changes.
+///
+/// # Culling and fading
+///
+/// Instances are grouped into cells of 8x8 units of the local space of the terrain. Every render
+/// pass (shadow passes included) culls the cells first - the cells that are outside of the
+/// frustum of the pass or farther than the draw distance (see [`Self::set_draw_distance`]) from
+/// the observer are skipped entirely. Only the instances of the cells near the draw distance are
+/// checked individually.
+///
+/// Instances within the fade distance (see [`Self::set_fade_distance`]) before the draw distance
+/// are scaled down smoothly, so they do not pop out abruptly. Fading only scales the instances,
+/// there's no alpha blending or dithering - the instances shrink instead of becoming transparent.
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Foliage {
+    base: Base,
+
+    #[reflect(setter = "set_terrain")]
+    terrain: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_surfaces")]
+    surfaces: InheritableVariable<Vec<Surface>>,
+
+    #[reflect(setter = "set_density_map")]
+    density_map: InheritableVariable<Option<TextureResource>>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_density")]
+    density: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.57, step = 0.01)]
+    #[reflect(setter = "set_max_slope")]
+    max_slope: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_scale_variation")]
+    scale_variation: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_seed")]
+    seed: InheritableVariable<u64>,
+
+    #[reflect(min_value = 0.0, step = 1.0)]
+    #[reflect(setter = "set_draw_distance")]
+    draw_distance: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 1.0)]
+    #[reflect(setter = "set_fade_distance")]
+    fade_distance: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state: FoliageState,
+}
+
+impl Default for Foliage {
+    fn default() -> Self {
+        FoliageBuilder::new(BaseBuilder::new()).build_foliage()
+    }
+}
+
+impl Deref for Foliage {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Foliage {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Foliage {
+    fn type_uuid() -> Uuid {
+        uuid!("8f4e2a6c-0b1d-4e3f-9a7c-5d6e8f9a0b1c")
+    }
+}
+
+impl Foliage {
+    /// Sets a terrain, over which the instances will be scattered.
+    pub fn set_terrain(&mut self, terrain: Handle<Node>) -> Handle<Node> {
+        self.regenerate();
+        self.terrain.set_value_and_mark_modified(terrain)
+    }
+
+    /// Returns a terrain, over which the instances are scattered.
+    pub fn terrain(&self) -> Handle<Node> {
+        *self.terrain
+    }
+
+    /// Sets surfaces that will be rendered for every instance.
+    pub fn set_surfaces(&mut self, surfaces: Vec<Surface>) -> Vec<Surface> {
+        self.regenerate();
+        self.surfaces.set_value_and_mark_modified(surfaces)
+    }
+
+    /// Returns surfaces that are rendered for every instance.
+    pub fn surfaces(&self) -> &[Surface] {
+        &self.surfaces
+    }
+
+    /// Sets a density map. See [`Foliage`] docs for more info.
+    pub fn set_density_map(
+        &mut self,
+        density_map: Option<TextureResource>,
+    ) -> Option<TextureResource> {
+        self.regenerate();
+        self.density_map.set_value_and_mark_modified(density_map)
+    }
+
+    /// Returns current density map.
+    pub fn density_map(&self) -> Option<&TextureResource> {
+        self.density_map.as_ref()
+    }
+
+    /// Sets amount of instances per square unit of the terrain where the density map is at its
+    /// maximum.
+    pub fn set_density(&mut self, density: f32) -> f32 {
+        self.regenerate();
+        self.density.set_value_and_mark_modified(density.max(0.0))
+    }
+
+    /// Returns amount of instances per square unit of the terrain at full density.
+    pub fn density(&self) -> f32 {
+        *self.density
+    }
+
+    /// Sets maximum slope of the terrain (in radians) on which instances could be placed.
+    pub fn set_max_slope(&mut self, max_slope: f32) -> f32 {
+        self.regenerate();
+        self.max_slope
+            .set_value_and_mark_modified(max_slope.clamp(0.0, std::f32::consts::FRAC_PI_2))
+    }
+
+    /// Returns maximum slope of the terrain on which instances could be placed.
+    pub fn max_slope(&self) -> f32 {
+        *self.max_slope
+    }
+
+    /// Sets scale variation in `[0; 1]` range. Scale of every instance is randomly selected from
+    /// `[1 - variation; 1 + variation]` range.
+    pub fn set_scale_variation(&mut self, variation: f32) -> f32 {
+        self.regenerate();
+        self.scale_variation
+            .set_value_and_mark_modified(variation.clamp(0.0, 1.0))
+    }
+
+    /// Returns scale variation.
+    pub fn scale_variation(&self) -> f32 {
+        *self.scale_variation
+    }
+
+    /// Sets a seed of the random number generator, that is used to place the instances.
+    pub fn set_seed(&mut self, seed: u64) -> u64 {
+        self.regenerate();
+        self.seed.set_value_and_mark_modified(seed)
+    }
+
+    /// Returns a seed of the random number generator, that is used to place the instances.
+    pub fn seed(&self) -> u64 {
+        *self.seed
+    }
+
+    /// Sets maximum distance from the observer at which the instances are rendered.
+    pub fn set_draw_distance(&mut self, distance: f32) -> f32 {
+        self.draw_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns maximum distance from the observer at which the instances are rendered.
+    pub fn draw_distance(&self) -> f32 {
+        *self.draw_distance
+    }
+
+    /// Sets a distance before the draw distance, at which the instances start to fade out.
+    pub fn set_fade_distance(&mut self, distance: f32) -> f32 {
+        self.fade_distance
+            .set_value_and_mark_modified(distance.max(0.0))
+    }
+
+    /// Returns a distance before the draw distance, at which the instances start to fade out.
+    pub fn fade_distance(&self) -> f32 {
+        *self.fade_distance
+    }
+
+    /// Returns placed instances. The slice is empty until the first update of the foliage.
+    pub fn instances(&self) -> &[FoliageInstance] {
+        &self.state.instances
+    }
+
+    /// Forces the instances to be re-generated on the next update.
+    pub fn regenerate(&mut self) {
+        self.state = Default::default();
+    }
+
+    fn generate(&mut self, terrain: &Terrain) {
+        let terrain_transform = terrain.global_transform();
+        self.state.terrain_transform = Some(terrain_transform);
+        self.state.instances.clear();
+        self.state.cells.clear();
+
+        let density_map = match self.density_map.as_ref() {
+            Some(density_map) if !density_map.is_ok() => {
+                // Wait until the density map is loaded.
+                self.state.terrain_transform = None;
+                return;
+            }
+            Some(density_map) => Some(density_map.data_ref()),
+            None => None,
+        };
+
+        let bounds = terrain.local_bounding_box();
+        let min = bounds.min.xz();
+        let size = bounds.max.xz() - min;
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return;
+        }
+
+        let up = Vector3::y();
+        let min_cos = self.max_slope.cos();
+        let step = terrain.height_grid_scale();
+        let height_at = |p: Vector2<f32>| {
+            let p = p.sup(&min).inf(&(min + size));
+            terrain.interpolate_value(p, BrushTarget::HeightMap)
+        };
+
+        let cell_count_x = (size.x / CELL_SIZE).ceil().max(1.0) as usize;
+        let cell_count_z = (size.y / CELL_SIZE).ceil().max(1.0) as usize;
+        let cell_index = |p: Vector2<f32>| {
+            let cell = (p - min).unscale(CELL_SIZE);
+            let x = (cell.x.max(0.0) as usize).min(cell_count_x - 1);
+            let z = (cell.y.max(0.0) as usize).min(cell_count_z - 1);
+            z * cell_count_x + x
+        };
+
+        let mut instances = Vec::new();
+        let mut rng = ChaCha8Rng::seed_from_u64(*self.seed);
+        for cell_z in 0..size.y.ceil() as usize {
+            for cell_x in 0..size.x.ceil() as usize {
+                let cell_min = min + Vector2::new(cell_x as f32, cell_z as f32);
+                let cell_size = (min + size - cell_min).inf(&Vector2::repeat(1.0));
+                let cell_center = cell_min + cell_size.scale(0.5);
+
+                let density_map_value = match density_map.as_ref() {
+                    Some(density_map) => {
+                        let uv = (cell_center - min).component_div(&size);
+                        match sample_density(density_map, uv) {
+                            Some(value) => value,
+                            None => {
+                                Log::warn(format!(
+                                    "Unsupported density map format {:?} of foliage {}. \
+                                    No instances will be placed.",
+                                    density_map.pixel_kind(),
+                                    self.name()
+                                ));
+                                return;
+                            }
+                        }
+                    }
+                    None => 1.0,
+                };
+
+                let count = (*self.density * density_map_value * cell_size.x * cell_size.y).round()
+                    as usize;
+                for _ in 0..count {
+                    let offset = Vector2::new(rng.gen::<f32>(), rng.gen::<f32>());
+                    let yaw = rng.gen_range(0.0..std::f32::consts::TAU);
+                    let scale = 1.0 + rng.gen_range(-1.0f32..=1.0) * *self.scale_variation;
+
+                    let p = cell_min + offset.component_mul(&cell_size);
+                    let height = height_at(p);
+                    let local_normal = Vector3::new(
+                        height_at(p - Vector2::new(step.x, 0.0))
+                            - height_at(p + Vector2::new(step.x, 0.0)),
+                        2.0 * step.x.max(step.y),
+                        height_at(p - Vector2::new(0.0, step.y))
+                            - height_at(p + Vector2::new(0.0, step.y)),
+                    );
+                    let normal = terrain_transform
+                        .transform_vector(&local_normal)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or(up);
+                    if normal.dot(&up) < min_cos {
+                        continue;
+                    }
+
+                    let position = terrain_transform
+                        .transform_point(&Point3::new(p.x, height, p.y))
+                        .coords;
+                    let transform = Matrix4::new_translation(&position)
+                        * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw).to_homogeneous()
+                        * Matrix4::new_scaling(scale);
+                    instances.push((
+                        cell_index(p),
+                        FoliageInstance {
+                            position,
+                            transform,
+                        },
+                    ));
+                }
+            }
+        }
+
+        let mut surfaces_bounds = AxisAlignedBoundingBox::default();
+        for surface in self.surfaces.iter() {
+            let data = surface.data_ref();
+            if !data.is_ok() {
+                continue;
+            }
+            let data = data.data_ref();
+            if let Some(positions) = data
+                .vertex_buffer
+                .attribute_view::<Vector3<f32>>(VertexAttributeUsage::Position)
+            {
+                for i in 0..data.vertex_buffer.vertex_count() as usize {
+                    surfaces_bounds.add_point(*positions.get(i).unwrap());
+                }
+            }
+        }
+
+        // The sort is stable, so the order of the instances in a cell stays deterministic.
+        instances.sort_by_key(|(cell_index, _)| *cell_index);
+
+        let mut world_bounding_box = AxisAlignedBoundingBox::default();
+        let mut current_cell_index = None;
+        for (cell_index, instance) in instances {
+            if current_cell_index != Some(cell_index) {
+                current_cell_index = Some(cell_index);
+                let first = self.state.instances.len();
+                self.state.cells.push(FoliageCell {
+                    instances: first..first,
+                    world_bounding_box: Default::default(),
+                });
+            }
+
+            let cell = self.state.cells.last_mut().unwrap();
+            if surfaces_bounds.is_invalid_or_degenerate() {
+                cell.world_bounding_box.add_point(instance.position);
+            } else {
+                cell.world_bounding_box
+                    .add_box(surfaces_bounds.transform(&instance.transform));
+            }
+            cell.instances.end += 1;
+            self.state.instances.push(instance);
+        }
+        for cell in self.state.cells.iter() {
+            world_bounding_box.add_box(cell.world_bounding_box);
+        }
+        self.state.world_bounding_box = world_bounding_box;
+    }
+}
+
+fn sample_density(texture: &Texture, uv: Vector2<f32>) -> Option<f32> {
+    let TextureKind::Rectangle { width, height } = texture.kind() else {
+        return None;
+    };
+    let x = ((uv.x * width as f32) as u32).min(width.saturating_sub(1));
+    let y = ((uv.y * height as f32) as u32).min(height.saturating_sub(1));
+    let index = (y * width + x) as usize;
+    let data = texture.data();
+    match texture.pixel_kind() {
+        TexturePixelKind::R8 | TexturePixelKind::Luminance8 => {
+            data.get(index).map(|v| *v as f32 / 255.0)
+        }
+        TexturePixelKind::RGBA8 | TexturePixelKind::SRGBA8 => {
+            data.get(index * 4).map(|v| *v as f32 / 255.0)
+        }
+        TexturePixelKind::R32F => texture
+            .data_of_type::<f32>()
+            .and_then(|data| data.get(index).cloned()),
+        _ => None,
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Foliage {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Foliage", |_| {
+                FoliageBuilder::new(BaseBuilder::new().with_name("Foliage"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Mesh")
+    }
+}
+
+impl NodeTrait for Foliage {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.state.instances.is_empty() {
+            return AxisAlignedBoundingBox::collapsed();
+        }
+
+        self.state.world_bounding_box.transform(
+            &self
+                .global_transform()
+                .try_inverse()
+                .unwrap_or_else(Matrix4::identity),
+        )
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.state.instances.is_empty() {
+            AxisAlignedBoundingBox::collapsed().transform(&self.global_transform())
+        } else {
+            self.state.world_bounding_box
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.is_globally_enabled() {
+            return;
+        }
+
+        let nodes: &NodePool = context.nodes;
+        let Some(terrain) = nodes
+            .try_get(*self.terrain)
+            .ok()
+            .and_then(|node| node.cast::<Terrain>())
+        else {
+            if !self.state.instances.is_empty() {
+                self.regenerate();
+            }
+            return;
+        };
+
+        if self.state.terrain_transform != Some(terrain.global_transform()) {
+            self.generate(terrain);
+        }
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) -> RdcControlFlow {
+        if !self.should_be_rendered(ctx.frustum, ctx.render_mask) {
+            return RdcControlFlow::Continue;
+        }
+
+        if renderer::is_shadow_pass(ctx.render_pass_name) && !self.cast_shadows() {
+            return RdcControlFlow::Continue;
+        }
+
+        let observer_position = ctx.observer_position.translation;
+        let draw_distance = *self.draw_distance;
+        let fade_distance = (*self.fade_distance).min(draw_distance);
+        let fade_start = draw_distance - fade_distance;
+        for cell in self.state.cells.iter() {
+            let aabb = &cell.world_bounding_box;
+            let closest_point = observer_position.sup(&aabb.min).inf(&aabb.max);
+            if closest_point.metric_distance(&observer_position) >= draw_distance {
+                continue;
+            }
+
+            if ctx
+                .frustum
+                .is_some_and(|frustum| !frustum.is_intersects_aabb(aabb))
+            {
+                continue;
+            }
+
+            // Cells that are entirely closer than the fade distance do not need per-instance
+            // distance checks.
+            let farthest_distance = (observer_position - aabb.min)
+                .abs()
+                .sup(&(observer_position - aabb.max).abs())
+                .norm();
+            let is_fully_visible = farthest_distance < fade_start;
+
+            for instance in self.state.instances[cell.instances.clone()].iter() {
+                let world_transform = if is_fully_visible {
+                    instance.transform
+                } else {
+                    let distance = instance.position.metric_distance(&observer_position);
+                    if distance >= draw_distance {
+                        continue;
+                    }
+
+                    let fade = if fade_distance > 0.0 {
+                        ((draw_distance - distance) / fade_distance).min(1.0)
+                    } else {
+                        1.0
+                    };
+                    if fade < 1.0 {
+                        instance.transform * Matrix4::new_scaling(fade)
+                    } else {
+                        instance.transform
+                    }
+                };
+
+                let sort_index = ctx.calculate_sorting_index(instance.position);
+                for surface in self.surfaces.iter() {
+                    // All instances share the same surface data and material, so the renderer
+                    // batches them into a single instanced draw call.
+                    ctx.storage.push(
+                        surface.data_ref(),
+                        surface.material(),
+                        RenderPath::Deferred,
+                        sort_index,
+                        SurfaceInstanceData {
+                            world_transform,
+                            node_handle: self.handle(),
+                            ..Default::default()
+                        },
+                    );
+                }
+            }
+        }
+
+        RdcControlFlow::Continue
+    }
+}
+
+/// Allows you to create foliage in a declarative manner.
+pub struct FoliageBuilder {
+    base_builder: BaseBuilder,
+    terrain: Handle<Node>,
+    surfaces: Vec<Surface>,
+    density_map: Option<TextureResource>,
+    density: f32,
+    max_slope: f32,
+    scale_variation: f32,
+    seed: u64,
+    draw_distance: f32,
+    fade_distance: f32,
+}
+
+impl FoliageBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            terrain: Default::default(),
+            surfaces: Default::default(),
+            density_map: None,
+            density: 4.0,
+            max_slope: 35.0f32.to_radians(),
+            scale_variation: 0.2,
+            seed: 0,
+            draw_distance: 50.0,
+            fade_distance: 10.0,
+        }
+    }
+
+    /// Sets desired terrain.
+    pub fn with_terrain(mut self, terrain: Handle<Node>) -> Self {
+        self.terrain = terrain;
+        self
+    }
+
+    /// Sets desired surfaces.
+    pub fn with_surfaces(mut self, surfaces: Vec<Surface>) -> Self {
+        self.surfaces = surfaces;
+        self
+    }
+
+    /// Sets desired density map.
+    pub fn with_density_map(mut self, density_map: Option<TextureResource>) -> Self {
+        self.density_map = density_map;
+        self
+    }
+
+    /// Sets desired amount of instances per square unit at full density.
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets desired maximum slope (in radians).
+    pub fn with_max_slope(mut self, max_slope: f32) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    /// Sets desired scale variation.
+    pub fn with_scale_variation(mut self, variation: f32) -> Self {
+        self.scale_variation = variation;
+        self
+    }
+
+    /// Sets desired seed of the random number generator.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets desired draw distance.
+    pub fn with_draw_distance(mut self, distance: f32) -> Self {
+        self.draw_distance = distance;
+        self
+    }
+
+    /// Sets desired fade distance.
+    pub fn with_fade_distance(mut self, distance: f32) -> Self {
+        self.fade_distance = distance;
+        self
+    }
+
+    /// Creates new foliage.
+    pub fn build_foliage(self) -> Foliage {
+        Foliage {
+            base: self.base_builder.build_base(),
+            terrain: self.terrain.into(),
+            surfaces: self.surfaces.into(),
+            density_map: self.density_map.into(),
+            density: self.density.max(0.0).into(),
+            max_slope: self
+                .max_slope
+                .clamp(0.0, std::f32::consts::FRAC_PI_2)
+                .into(),
+            scale_variation: self.scale_variation.clamp(0.0, 1.0).into(),
+            seed: self.seed.into(),
+            draw_distance: self.draw_distance.max(0.0).into(),
+            fade_distance: self.fade_distance.max(0.0).into(),
+            state: Default::default(),
+        }
+    }
+
+    /// Creates new foliage node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_foliage())
+    }
+
+    /// Creates new instance of foliage node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Foliage> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::untyped::ResourceKind,
+        core::{
+            algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+            pool::Handle,
+            uuid::Uuid,
+        },
+        resource::texture::{
+            TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+        scene::{
+            base::BaseBuilder,
+            foliage::{Foliage, FoliageBuilder},
+            graph::Graph,
+            mesh::surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+            terrain::TerrainBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_scene(
+        density_map: Option<TextureResource>,
+        rotation: UnitQuaternion<f32>,
+    ) -> (Graph, Handle<Foliage>) {
+        let mut graph = Graph::new();
+
+        // 16x16 meters flat terrain.
+        let terrain = TerrainBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(rotation)
+                    .build(),
+            ),
+        )
+        .with_chunk_size(Vector2::new(16.0, 16.0))
+        .with_width_chunks(0..1)
+        .with_length_chunks(0..1)
+        .build(&mut graph);
+
+        let foliage = FoliageBuilder::new(BaseBuilder::new())
+            .with_terrain(terrain.to_base())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_cube(Matrix4::identity()),
+            ))
+            .build()])
+            .with_density_map(density_map)
+            .with_density(2.0)
+            .build(&mut graph);
+
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        (graph, foliage)
+    }
+
+    #[test]
+    fn test_foliage_density_map() {
+        // Left half of the terrain has zero density, right half - full density.
+        let mut bytes = vec![0u8; 16 * 16];
+        for row in bytes.chunks_mut(16) {
+            row[8..].fill(255);
+        }
+        let density_map = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: 16,
+                height: 16,
+            },
+            TexturePixelKind::R8,
+            bytes,
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+
+        let (graph, foliage) = make_scene(Some(density_map), UnitQuaternion::identity());
+        let instances = graph[foliage].instances();
+        // 8x16 square meters with 2 instances per square meter.
+        assert_eq!(instances.len(), 256);
+        assert!(instances
+            .iter()
+            .all(|i| i.position.x >= 8.0 && i.position.x <= 16.0 && i.position.y.abs() < 1.0e-5));
+
+        // No density map - uniform full density.
+        let (graph, foliage) = make_scene(None, UnitQuaternion::identity());
+        assert_eq!(graph[foliage].instances().len(), 512);
+    }
+
+    #[test]
+    fn test_foliage_slope_limit() {
+        // The terrain is tilted by 60 degrees, which is more than the default max slope.
+        let (graph, foliage) = make_scene(
+            None,
+            UnitQuaternion::from_axis_angle(&Vector3::x_axis(), 60.0f32.to_radians()),
+        );
+        assert!(graph[foliage].instances().is_empty());
+    }
+
+    #[test]
+    fn test_foliage_cells() {
+        let (graph, foliage) = make_scene(None, UnitQuaternion::identity());
+        let foliage = &graph[foliage];
+        let instances = foliage.instances();
+
+        // 16x16 meters terrain is split into 2x2 cells, which cover all the instances in order.
+        let cells = &foliage.state.cells;
+        assert_eq!(cells.len(), 4);
+        assert_eq!(cells.first().unwrap().instances.start, 0);
+        assert_eq!(cells.last().unwrap().instances.end, instances.len());
+        for (cell, next) in cells.iter().zip(cells.iter().skip(1)) {
+            assert_eq!(cell.instances.end, next.instances.start);
+        }
+        for cell in cells {
+            assert!(!cell.instances.is_empty());
+            assert!(instances[cell.instances.clone()]
+                .iter()
+                .all(|i| cell.world_bounding_box.is_contains_point(i.position)));
+            let size = cell.world_bounding_box.max - cell.world_bounding_box.min;
+            assert!(size.x < 10.0 && size.z < 10.0);
+        }
+    }
+}
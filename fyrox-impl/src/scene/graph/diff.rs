@@ -0,0 +1,588 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Structural diff and three-way merge of scene graphs. See [`SceneChangeset`] and [`merge`] docs
+//! for more info.
+
+use crate::{
+    core::{pool::Handle, reflect::prelude::*, uuid::Uuid},
+    graph::{SceneGraph, SceneGraphNode},
+    scene::{
+        base::SceneNodeId,
+        graph::{
+            text::{collect_properties, TextSceneError, TextValue},
+            Graph,
+        },
+        node::{constructor::NodeConstructorContainer, Node},
+    },
+};
+use fxhash::FxHashMap;
+use std::collections::BTreeMap;
+
+/// A path of the pseudo-field that holds name of a node.
+pub const NAME_PATH: &str = "@name";
+
+/// A state of a single node, that is used to compare scenes. Nodes are matched by their
+/// [`SceneNodeId`], which is stable across saves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeSnapshot {
+    /// Type uuid of the node.
+    pub type_uuid: Uuid,
+    /// Id of the parent node. `None` means that the node is attached to the root of the graph.
+    pub parent: Option<SceneNodeId>,
+    /// Reflected properties of the node that could be represented in text form (see
+    /// [`TextValue`]), including local transform and the name (stored at [`NAME_PATH`]).
+    pub fields: BTreeMap<String, TextValue>,
+}
+
+/// A state of a whole graph (except its root), that is used to compare scenes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GraphSnapshot {
+    /// Node states, sorted by their ids.
+    pub nodes: BTreeMap<SceneNodeId, NodeSnapshot>,
+}
+
+impl GraphSnapshot {
+    /// Captures the state of every node of the graph, except the root.
+    pub fn from_graph(graph: &Graph) -> Self {
+        let root = graph.get_root();
+        let nodes = graph
+            .pair_iter()
+            .filter(|(handle, _)| *handle != root)
+            .map(|(_, node)| {
+                let parent = node.parent();
+                let mut fields = collect_properties(node, true);
+                fields.insert(NAME_PATH.to_string(), TextValue::String(node.name_owned()));
+                (
+                    node.instance_id(),
+                    NodeSnapshot {
+                        type_uuid: node.id(),
+                        parent: (parent != root)
+                            .then(|| graph.try_get_node(parent).ok())
+                            .flatten()
+                            .map(|parent| parent.instance_id()),
+                        fields,
+                    },
+                )
+            })
+            .collect();
+        Self { nodes }
+    }
+}
+
+/// A new node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AddedNode {
+    /// Id of the node.
+    pub id: SceneNodeId,
+    /// State of the node.
+    pub node: NodeSnapshot,
+}
+
+/// A change of a single field of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Path of the field.
+    pub path: String,
+    /// Previous value of the field. `None` if the field did not exist.
+    pub old: Option<TextValue>,
+    /// New value of the field. `None` if the field does not exist anymore.
+    pub new: Option<TextValue>,
+}
+
+/// A change of the parent of a node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParentChange {
+    /// Previous parent, `None` means the graph root.
+    pub old: Option<SceneNodeId>,
+    /// New parent, `None` means the graph root.
+    pub new: Option<SceneNodeId>,
+}
+
+/// A set of changes of an existing node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModifiedNode {
+    /// Id of the node.
+    pub id: SceneNodeId,
+    /// A change of the parent, if any.
+    pub parent: Option<ParentChange>,
+    /// Changed fields, sorted by their paths.
+    pub fields: Vec<FieldChange>,
+}
+
+/// A structured set of changes between two states of a scene: added, removed and modified nodes
+/// and their fields. It could be applied to a graph to transfer the changes.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::{
+/// #     graph::{diff::{GraphSnapshot, SceneChangeset}, Graph},
+/// #     node::constructor::new_node_constructor_container,
+/// # };
+/// fn transfer_changes(before: &Graph, after: &Graph, dest: &mut Graph) {
+///     let changeset = SceneChangeset::diff(
+///         &GraphSnapshot::from_graph(before),
+///         &GraphSnapshot::from_graph(after),
+///     );
+///     changeset
+///         .apply(dest, &new_node_constructor_container())
+///         .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SceneChangeset {
+    /// New nodes.
+    pub added: Vec<AddedNode>,
+    /// Ids of removed nodes.
+    pub removed: Vec<SceneNodeId>,
+    /// Modified nodes.
+    pub modified: Vec<ModifiedNode>,
+}
+
+impl SceneChangeset {
+    /// Calculates a set of changes that turns `old` state into `new` state.
+    pub fn diff(old: &GraphSnapshot, new: &GraphSnapshot) -> Self {
+        let mut changeset = Self::default();
+
+        for (id, old_node) in old.nodes.iter() {
+            let Some(new_node) = new.nodes.get(id) else {
+                changeset.removed.push(*id);
+                continue;
+            };
+
+            let parent = (old_node.parent != new_node.parent).then(|| ParentChange {
+                old: old_node.parent,
+                new: new_node.parent,
+            });
+
+            let mut fields = Vec::new();
+            for (path, old_value) in old_node.fields.iter() {
+                let new_value = new_node.fields.get(path);
+                if new_value != Some(old_value) {
+                    fields.push(FieldChange {
+                        path: path.clone(),
+                        old: Some(old_value.clone()),
+                        new: new_value.cloned(),
+                    });
+                }
+            }
+            for (path, new_value) in new_node.fields.iter() {
+                if !old_node.fields.contains_key(path) {
+                    fields.push(FieldChange {
+                        path: path.clone(),
+                        old: None,
+                        new: Some(new_value.clone()),
+                    });
+                }
+            }
+            fields.sort_by(|a, b| a.path.cmp(&b.path));
+
+            if parent.is_some() || !fields.is_empty() {
+                changeset.modified.push(ModifiedNode {
+                    id: *id,
+                    parent,
+                    fields,
+                });
+            }
+        }
+
+        for (id, new_node) in new.nodes.iter() {
+            if !old.nodes.contains_key(id) {
+                changeset.added.push(AddedNode {
+                    id: *id,
+                    node: new_node.clone(),
+                });
+            }
+        }
+
+        changeset
+    }
+
+    /// Returns `true` if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+
+    /// Applies the changes to the given graph. Nodes are matched by their ids, changes of the
+    /// nodes that do not exist in the graph are ignored. New nodes, whose parents do not exist in
+    /// the graph are attached to the root of the graph.
+    pub fn apply(
+        &self,
+        graph: &mut Graph,
+        constructors: &NodeConstructorContainer,
+    ) -> Result<(), TextSceneError> {
+        let mut handles = graph
+            .pair_iter()
+            .map(|(handle, node)| (node.instance_id(), handle))
+            .collect::<FxHashMap<_, _>>();
+
+        // Parents must be created before their children.
+        let mut pending = self.added.iter().collect::<Vec<_>>();
+        while !pending.is_empty() {
+            let count = pending.len();
+            let mut i = 0;
+            while i < pending.len() {
+                let added = pending[i];
+                let parent_exists = added.node.parent.is_none_or(|parent| {
+                    handles.contains_key(&parent) || !pending.iter().any(|other| other.id == parent)
+                });
+                if parent_exists {
+                    let handle = add_node(graph, constructors, added)?;
+                    handles.insert(added.id, handle);
+                    pending.remove(i);
+                } else {
+                    i += 1;
+                }
+            }
+            if pending.len() == count {
+                // A cycle, it could only happen in a malformed changeset.
+                for added in pending.drain(..) {
+                    let handle = add_node(graph, constructors, added)?;
+                    handles.insert(added.id, handle);
+                }
+            }
+        }
+
+        // Links are made when all the nodes are created.
+        let root = graph.get_root();
+        for added in self.added.iter() {
+            let parent = added
+                .node
+                .parent
+                .and_then(|parent| handles.get(&parent).copied())
+                .unwrap_or(root);
+            graph.link_nodes(handles[&added.id], parent);
+        }
+
+        for modified in self.modified.iter() {
+            let Some(&handle) = handles.get(&modified.id) else {
+                continue;
+            };
+            if let Some(parent) = modified.parent.as_ref() {
+                let parent = parent
+                    .new
+                    .and_then(|parent| handles.get(&parent).copied())
+                    .unwrap_or(root);
+                graph.link_nodes(handle, parent);
+            }
+            if let Ok(node) = graph.try_get_node_mut(handle) {
+                for field in modified.fields.iter() {
+                    if let Some(value) = field.new.as_ref() {
+                        set_field(node, &field.path, value);
+                    }
+                }
+            }
+        }
+
+        for id in self.removed.iter() {
+            if let Some(handle) = handles.get(id) {
+                // Descendants could already be removed with their ancestor.
+                if graph.is_valid_handle(*handle) {
+                    graph.remove_node(*handle);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn add_node(
+    graph: &mut Graph,
+    constructors: &NodeConstructorContainer,
+    added: &AddedNode,
+) -> Result<Handle<Node>, TextSceneError> {
+    let mut node = constructors
+        .try_create(&added.node.type_uuid)
+        .ok_or_else(|| {
+            let name = match added.node.fields.get(NAME_PATH) {
+                Some(TextValue::String(name)) => name.clone(),
+                _ => Default::default(),
+            };
+            TextSceneError::UnknownNodeType {
+                name,
+                type_uuid: added.node.type_uuid,
+            }
+        })?;
+    for (path, value) in added.node.fields.iter() {
+        set_field(&mut node, path, value);
+    }
+    node.instance_id = added.id;
+    Ok(graph.add_node(node))
+}
+
+fn set_field(node: &mut Node, path: &str, value: &TextValue) {
+    if path == NAME_PATH {
+        if let TextValue::String(name) = value {
+            node.set_name(name);
+        }
+    } else {
+        // Missing or mismatched properties are ignored, the same as in text scenes.
+        (node as &mut dyn Reflect).set_field_by_path(
+            path,
+            value.clone().into_reflect(),
+            &mut |_| {},
+        );
+    }
+}
+
+/// A change that was made in both branches of a three-way merge in an incompatible way.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeConflict {
+    /// The same field of a node was changed to different values.
+    Field {
+        /// Id of the node.
+        id: SceneNodeId,
+        /// Path of the field.
+        path: String,
+        /// Value of the field in "our" branch.
+        ours: Option<TextValue>,
+        /// Value of the field in "their" branch.
+        theirs: Option<TextValue>,
+    },
+    /// A node was moved to different parents.
+    Parent {
+        /// Id of the node.
+        id: SceneNodeId,
+        /// Parent of the node in "our" branch.
+        ours: Option<SceneNodeId>,
+        /// Parent of the node in "their" branch.
+        theirs: Option<SceneNodeId>,
+    },
+    /// A node was removed in one branch and modified in the other.
+    RemovedModified {
+        /// Id of the node.
+        id: SceneNodeId,
+    },
+    /// A node with the same id was added in both branches with different state.
+    Added {
+        /// Id of the node.
+        id: SceneNodeId,
+    },
+}
+
+/// A result of a three-way merge.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MergeResult {
+    /// Non-conflicting changes of "their" branch, that should be applied to "our" graph to get
+    /// the merged graph.
+    pub changeset: SceneChangeset,
+    /// A list of conflicts, conflicting changes of "their" branch are not included in the
+    /// changeset, which means that "our" version wins unless the conflict is resolved manually.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    /// Returns `true` if the merge has no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Performs three-way merge of two branches (`ours` and `theirs`) with their common ancestor
+/// (`base`). The resulting changeset contains only the changes of "their" branch that do not
+/// conflict with the changes of "our" branch.
+pub fn merge(base: &GraphSnapshot, ours: &GraphSnapshot, theirs: &GraphSnapshot) -> MergeResult {
+    let our_changes = SceneChangeset::diff(base, ours);
+    let their_changes = SceneChangeset::diff(base, theirs);
+
+    let our_modified = our_changes
+        .modified
+        .iter()
+        .map(|modified| (modified.id, modified))
+        .collect::<FxHashMap<_, _>>();
+
+    let mut result = MergeResult::default();
+
+    for added in their_changes.added {
+        match ours.nodes.get(&added.id) {
+            Some(node) if *node == added.node => (),
+            Some(_) => result.conflicts.push(MergeConflict::Added { id: added.id }),
+            None => result.changeset.added.push(added),
+        }
+    }
+
+    for id in their_changes.removed {
+        if our_modified.contains_key(&id) {
+            result.conflicts.push(MergeConflict::RemovedModified { id });
+        } else if !our_changes.removed.contains(&id) {
+            result.changeset.removed.push(id);
+        }
+    }
+
+    for their in their_changes.modified {
+        if our_changes.removed.contains(&their.id) {
+            result
+                .conflicts
+                .push(MergeConflict::RemovedModified { id: their.id });
+            continue;
+        }
+
+        let our = our_modified.get(&their.id);
+
+        let parent = match (their.parent, our.and_then(|our| our.parent.as_ref())) {
+            (Some(their_parent), Some(our_parent)) => {
+                if their_parent.new != our_parent.new {
+                    result.conflicts.push(MergeConflict::Parent {
+                        id: their.id,
+                        ours: our_parent.new,
+                        theirs: their_parent.new,
+                    });
+                }
+                None
+            }
+            (their_parent, _) => their_parent,
+        };
+
+        let mut fields = Vec::new();
+        for their_field in their.fields {
+            let our_field = our.and_then(|our| {
+                our.fields
+                    .iter()
+                    .find(|field| field.path == their_field.path)
+            });
+            match our_field {
+                Some(our_field) => {
+                    if our_field.new != their_field.new {
+                        result.conflicts.push(MergeConflict::Field {
+                            id: their.id,
+                            path: their_field.path,
+                            ours: our_field.new.clone(),
+                            theirs: their_field.new,
+                        });
+                    }
+                }
+                None => fields.push(their_field),
+            }
+        }
+
+        if parent.is_some() || !fields.is_empty() {
+            result.changeset.modified.push(ModifiedNode {
+                id: their.id,
+                parent,
+                fields,
+            });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{algebra::Vector3, pool::Handle, uuid::Uuid},
+        graph::SceneGraph,
+        scene::{
+            base::{BaseBuilder, SceneNodeId},
+            graph::{
+                diff::{merge, GraphSnapshot, MergeConflict},
+                Graph,
+            },
+            node::{constructor::new_node_constructor_container, Node},
+            pivot::PivotBuilder,
+        },
+    };
+
+    const A: SceneNodeId = SceneNodeId(Uuid::from_u128(1));
+    const B: SceneNodeId = SceneNodeId(Uuid::from_u128(2));
+
+    fn make_graph() -> (Graph, Handle<Node>, Handle<Node>) {
+        let mut graph = Graph::new();
+        let a = PivotBuilder::new(BaseBuilder::new().with_name("A").with_instance_id(A))
+            .build(&mut graph);
+        let b = PivotBuilder::new(BaseBuilder::new().with_name("B").with_instance_id(B))
+            .build(&mut graph);
+        (graph, a, b)
+    }
+
+    #[test]
+    fn test_merge_different_nodes() {
+        let (base, _, _) = make_graph();
+        let (mut ours, our_a, our_b) = make_graph();
+        let (mut theirs, _, their_b) = make_graph();
+
+        ours[our_a]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 0.0, 0.0));
+        theirs[their_b]
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, 2.0, 0.0));
+        PivotBuilder::new(BaseBuilder::new().with_name("C")).build(&mut theirs);
+
+        let result = merge(
+            &GraphSnapshot::from_graph(&base),
+            &GraphSnapshot::from_graph(&ours),
+            &GraphSnapshot::from_graph(&theirs),
+        );
+        assert!(result.is_clean());
+        assert_eq!(result.changeset.added.len(), 1);
+        assert_eq!(result.changeset.modified.len(), 1);
+
+        result
+            .changeset
+            .apply(&mut ours, &new_node_constructor_container())
+            .unwrap();
+        assert_eq!(
+            **ours[our_a].local_transform().position(),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+        assert_eq!(
+            **ours[our_b].local_transform().position(),
+            Vector3::new(0.0, 2.0, 0.0)
+        );
+        assert!(ours.find_by_name_from_root("C").is_some());
+
+        // The merged graph is the same as "their" graph with "our" changes.
+        theirs[theirs.find_by_name_from_root("A").unwrap().0]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(
+            GraphSnapshot::from_graph(&ours),
+            GraphSnapshot::from_graph(&theirs)
+        );
+    }
+
+    #[test]
+    fn test_merge_same_field_conflict() {
+        let (base, _, _) = make_graph();
+        let (mut ours, our_a, _) = make_graph();
+        let (mut theirs, their_a, _) = make_graph();
+
+        ours[our_a]
+            .local_transform_mut()
+            .set_position(Vector3::new(1.0, 0.0, 0.0));
+        theirs[their_a]
+            .local_transform_mut()
+            .set_position(Vector3::new(-1.0, 0.0, 0.0));
+
+        let result = merge(
+            &GraphSnapshot::from_graph(&base),
+            &GraphSnapshot::from_graph(&ours),
+            &GraphSnapshot::from_graph(&theirs),
+        );
+        assert_eq!(result.conflicts.len(), 1);
+        assert!(matches!(
+            &result.conflicts[0],
+            MergeConflict::Field { id, path, .. } if *id == A && path.ends_with("local_position")
+        ));
+        assert!(result.changeset.is_empty());
+    }
+}
@@ -62,12 +62,14 @@ use crate::{
         dim2::{self},
         graph::{
             event::{GraphEvent, GraphEventBroadcaster},
-            physics::{PhysicsPerformanceStatistics, PhysicsWorld},
+            physics::{PhysicsPerformanceStatistics, PhysicsSnapshot, PhysicsWorld},
         },
+        joint::Joint,
         mesh::Mesh,
         navmesh,
         node::{container::NodeContainer, Node, NodeAsAny, SyncContext, UpdateContext},
         pivot::Pivot,
+        rigidbody::RigidBody,
         sound::context::SoundContext,
         transform::TransformBuilder,
     },
@@ -85,8 +87,11 @@ use std::{
     time::Duration,
 };
 
+pub mod diff;
 pub mod event;
 pub mod physics;
+pub mod search;
+pub mod text;
 
 /// Graph performance statistics. Allows you to find out "hot" parts of the scene graph, which
 /// parts takes the most time to update.
@@ -159,6 +164,9 @@ pub struct Graph {
     /// Current lightmap.
     lightmap: Option<Lightmap>,
 
+    /// A multiplier for the time step of animations of the graph.
+    animation_time_scale: f32,
+
     #[reflect(hidden)]
     pub(crate) script_message_sender: Sender<NodeScriptMessage>,
     #[reflect(hidden)]
@@ -220,6 +228,7 @@ impl Default for Graph {
             message_sender,
             script_message_sender,
             lightmap: None,
+            animation_time_scale: 1.0,
             instance_id_map: Default::default(),
             message_receiver,
             user_data: Default::default(),
@@ -259,6 +268,15 @@ fn remap_handles(old_new_mapping: &NodeHandleMap<Node>, dest_graph: &mut Graph)
     }
 }
 
+fn joint_bodies(node: &Node) -> Option<(Handle<Node>, Handle<Node>)> {
+    if let Some(joint) = node.cast::<Joint>() {
+        Some((joint.body1().to_base(), joint.body2().to_base()))
+    } else {
+        node.cast::<dim2::joint::Joint>()
+            .map(|joint| (joint.body1().to_base(), joint.body2().to_base()))
+    }
+}
+
 /// Calculates local transform of a scene node without scaling.
 pub fn isometric_local_transform(
     nodes: &NodePool,
@@ -329,6 +347,18 @@ impl Default for GraphUpdateSwitches {
     }
 }
 
+/// Defines where a node is placed relative to a target node, when it is dropped on the target in
+/// a tree view. See [`Graph::reparent`] for more info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DropPosition {
+    /// The node becomes a sibling of the target, placed right before it.
+    Before,
+    /// The node becomes a sibling of the target, placed right after it.
+    After,
+    /// The node becomes the last child of the target.
+    Inside,
+}
+
 /// A set of potential errors that may occur when using the [`Graph`] API.
 #[derive(PartialEq)]
 pub enum GraphError {
@@ -431,6 +461,7 @@ impl Graph {
             message_sender,
             script_message_sender,
             lightmap: None,
+            animation_time_scale: 1.0,
             instance_id_map,
             message_receiver,
             user_data: Default::default(),
@@ -574,17 +605,10 @@ impl Graph {
             .pool
             .try_borrow_dependant_mut(node_handle, |node| node.parent());
         if let Ok(node) = node {
-            if let Ok(parent) = parent {
-                let relative_position = parent
-                    .global_transform()
-                    .try_inverse()
-                    .unwrap_or_default()
-                    .transform_point(&position.into())
-                    .coords;
-                node.local_transform_mut().set_position(relative_position);
-            } else {
-                node.local_transform_mut().set_position(position);
-            }
+            let parent_global_transform = parent
+                .map(|parent| parent.global_transform())
+                .unwrap_or_else(|_| Matrix4::identity());
+            node.set_global_position(position, &parent_global_transform);
             self.update_hierarchical_data_for_descendants(node_handle);
         }
     }
@@ -728,6 +752,76 @@ impl Graph {
         self.link_nodes(child, parent);
     }
 
+    /// Checks whether the given node could be attached to the given parent node. Returns `false`
+    /// if any of the handles is invalid, if the node is the root node, or if the parent is the node
+    /// itself or one of its descendants (reparenting would create a cycle).
+    #[inline]
+    pub fn can_reparent(
+        &self,
+        node: Handle<impl ObjectOrVariant<Node>>,
+        parent: Handle<impl ObjectOrVariant<Node>>,
+    ) -> bool {
+        let node = node.to_base();
+        let mut parent = parent.to_base();
+        if node == self.root || !self.is_valid_handle(node) || !self.is_valid_handle(parent) {
+            return false;
+        }
+        while parent.is_some() {
+            if parent == node {
+                return false;
+            }
+            parent = self.pool[parent].parent;
+        }
+        true
+    }
+
+    /// Moves the node to a new place in the hierarchy defined by the target node and the drop
+    /// position, keeping the global transform of the node. Returns `false` if the move is not
+    /// possible (see [`Self::can_reparent`]), the graph is left unchanged in this case. Global
+    /// transforms of the nodes must be up-to-date.
+    #[inline]
+    pub fn reparent(
+        &mut self,
+        node: Handle<impl ObjectOrVariant<Node>>,
+        target: Handle<impl ObjectOrVariant<Node>>,
+        position: DropPosition,
+    ) -> bool {
+        let node = node.to_base();
+        let target = target.to_base();
+        let parent = match position {
+            DropPosition::Inside => target,
+            DropPosition::Before | DropPosition::After => {
+                if node == target {
+                    return false;
+                }
+                match self.pool.try_borrow(target) {
+                    Ok(target_ref) => target_ref.parent,
+                    Err(_) => return false,
+                }
+            }
+        };
+
+        if !self.can_reparent(node, parent) {
+            return false;
+        }
+
+        self.link_nodes_keep_global_transform(node, parent);
+
+        if position != DropPosition::Inside {
+            let parent_ref = &mut self.pool[parent];
+            if let Some(target_position) = parent_ref.children.iter().position(|c| *c == target) {
+                let destination = if position == DropPosition::Before {
+                    target_position
+                } else {
+                    target_position + 1
+                };
+                parent_ref.set_child_position(node, destination);
+            }
+        }
+
+        true
+    }
+
     /// Searches for a **first** node with a script of the given type `S` in the hierarchy starting from the
     /// given `root_node`.
     #[inline]
@@ -776,6 +870,11 @@ impl Graph {
     ///
     /// Filter allows to exclude some nodes from copied hierarchy. It must return false for
     /// odd nodes. Filtering applied only to descendant nodes.
+    ///
+    /// Physics joints (both 3D and 2D) of the copied hierarchy that connect its bodies will connect
+    /// the copies of the bodies. Descendant joints that reference a body outside of the copied
+    /// hierarchy are not copied (a warning will be written to the log), so the copy never
+    /// cross-links with the original bodies.
     #[inline]
     pub fn copy_node<F, Pre, Post>(
         &self,
@@ -802,11 +901,58 @@ impl Graph {
             post_process_callback,
         );
 
+        let old_new_mapping =
+            self.remove_dangling_joints(node_handle.to_base(), old_new_mapping, dest_graph);
+
         remap_handles(&old_new_mapping, dest_graph);
 
         (root_handle, old_new_mapping)
     }
 
+    /// Removes copies of the joints, that reference a body that wasn't copied. Returns the mapping
+    /// without the removed nodes.
+    fn remove_dangling_joints(
+        &self,
+        root: Handle<Node>,
+        old_new_mapping: NodeHandleMap<Node>,
+        dest_graph: &mut Graph,
+    ) -> NodeHandleMap<Node> {
+        let is_copied =
+            |body: Handle<Node>| body.is_none() || old_new_mapping.inner().contains_key(&body);
+
+        let mut removed_any = false;
+        for (original, copy) in old_new_mapping.inner().iter() {
+            if *original == root || !dest_graph.is_valid_handle(*copy) {
+                continue;
+            }
+            let Some((body1, body2)) = joint_bodies(&self.pool[*original]) else {
+                continue;
+            };
+            if !is_copied(body1) || !is_copied(body2) {
+                Log::warn(format!(
+                    "Joint {} references a body outside of the copied hierarchy, \
+                    so it won't be copied.",
+                    self.pool[*original].name()
+                ));
+                dest_graph.remove_node(*copy);
+                removed_any = true;
+            }
+        }
+
+        if !removed_any {
+            return old_new_mapping;
+        }
+
+        // Removed joints (and their descendants) must not be in the mapping.
+        let mut mapping = NodeHandleMap::default();
+        for (original, copy) in old_new_mapping.inner().iter() {
+            if dest_graph.is_valid_handle(*copy) {
+                mapping.insert(*original, *copy);
+            }
+        }
+        mapping
+    }
+
     /// Creates deep copy of node with all children. This is relatively heavy operation!
     /// In case if any error happened it returns `Handle::NONE`. This method can be used
     /// to create exact copy of given node hierarchy. For example you can prepare rocket
@@ -1061,6 +1207,18 @@ impl Graph {
         self.lightmap.as_ref()
     }
 
+    /// Sets a multiplier for the time step of all animations (animation players and animation blending
+    /// state machines) of the graph. It could be used for slow-motion effects, zero value pauses the
+    /// animations. Playback direction of each animation is defined by its own speed.
+    pub fn set_animation_time_scale(&mut self, time_scale: f32) {
+        self.animation_time_scale = time_scale;
+    }
+
+    /// Returns current multiplier for the time step of all animations of the graph.
+    pub fn animation_time_scale(&self) -> f32 {
+        self.animation_time_scale
+    }
+
     fn apply_lightmap(&mut self) {
         // Re-apply lightmap if any. This has to be done after resolve because we must patch surface
         // data at this stage, but if we'd do this before we wouldn't be able to do this because
@@ -1233,6 +1391,34 @@ impl Graph {
         }
     }
 
+    /// Restores the state of 3D rigid bodies from the given snapshot (see [`PhysicsWorld::snapshot`]
+    /// and [`PhysicsWorld::restore`]) and immediately syncs transforms, velocities of respective
+    /// rigid body scene nodes (and global transforms of their descendants) with it.
+    pub fn restore_physics(&mut self, snapshot: &PhysicsSnapshot) {
+        self.physics.restore(snapshot);
+
+        for &handle in snapshot.bodies.keys() {
+            let Ok(node) = self.pool.try_borrow(handle) else {
+                continue;
+            };
+            let parent_transform = self
+                .pool
+                .try_borrow(node.parent())
+                .ok()
+                .map(|parent| parent.global_transform())
+                .unwrap_or_else(Matrix4::identity);
+            if let Some(rigid_body) = self.pool[handle].cast_mut::<RigidBody>() {
+                self.physics
+                    .sync_rigid_body_node(rigid_body, parent_transform);
+            }
+            self.update_hierarchical_data_for_descendants(handle);
+        }
+
+        // Hierarchy update could move the bodies using their global transforms, which are not
+        // precise enough, so the state must be restored once again.
+        self.physics.restore(snapshot);
+    }
+
     /// Calculates local and global transform, global visibility for each node in graph starting from the
     /// specified node and down the tree. The main use case of the method is to update global position (etc.)
     /// of an hierarchy of the nodes of some new prefab instance.
@@ -1419,6 +1605,7 @@ impl Graph {
                 node.update(&mut UpdateContext {
                     frame_size,
                     dt,
+                    animation_time_scale: self.animation_time_scale,
                     nodes: &mut self.pool,
                     physics: &mut self.physics,
                     physics2d: &mut self.physics2d,
@@ -1494,6 +1681,9 @@ impl Graph {
                 );
             }
         }
+
+        // Reverb zones are resolved only when all of them were updated.
+        self.sound_context.apply_reverb_zones();
     }
 
     /// Returns capacity of internal pool. Can be used to iterate over all **potentially**
@@ -1683,6 +1873,7 @@ impl Graph {
             physics: self.physics.clone(),
             physics2d: self.physics2d.clone(),
             user_data: self.user_data.clone(),
+            animation_time_scale: self.animation_time_scale,
             ..Default::default()
         };
 
@@ -2000,6 +2191,9 @@ impl Visit for Graph {
         self.physics.visit("PhysicsWorld", &mut region)?;
         self.physics2d.visit("PhysicsWorld2D", &mut region)?;
         self.lightmap.visit("Lightmap", &mut region)?;
+        let _ = self
+            .animation_time_scale
+            .visit("AnimationTimeScale", &mut region);
 
         Log::verify(self.user_data.visit("UserData", &mut region));
 
@@ -2208,11 +2402,13 @@ impl SceneGraph for Graph {
 
 #[cfg(test)]
 mod test {
+    use crate::scene::collider::{Collider, ColliderBuilder, ColliderShape};
+    use crate::scene::joint::{Joint, JointBuilder};
     use crate::scene::rigidbody::{RigidBody, RigidBodyBuilder};
     use crate::{
         asset::{io::FsResourceIo, manager::ResourceManager},
         core::{
-            algebra::{Matrix4, Vector3},
+            algebra::{Matrix4, UnitQuaternion, Vector3},
             futures::executor::block_on,
             pool::Handle,
             reflect::prelude::*,
@@ -2224,7 +2420,7 @@ mod test {
         resource::model::{Model, ModelResourceExtension},
         scene::{
             base::BaseBuilder,
-            graph::Graph,
+            graph::{DropPosition, Graph},
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
                 MeshBuilder,
@@ -2723,4 +2919,248 @@ mod test {
             .is_ok());
         assert!(graph.pool.try_get(rigid_body.transmute::<Pivot>()).is_err());
     }
+
+    #[test]
+    fn test_reparent() {
+        let mut graph = Graph::new();
+
+        let b;
+        let c;
+        let a = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                        .build(),
+                )
+                .with_child({
+                    b = PivotBuilder::new(
+                        BaseBuilder::new()
+                            .with_local_transform(
+                                TransformBuilder::new()
+                                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                                    .build(),
+                            )
+                            .with_child({
+                                c = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+                                c
+                            }),
+                    )
+                    .build(&mut graph);
+                    b
+                }),
+        )
+        .build(&mut graph);
+        let d = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(5.0, 0.0, 0.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        // Dropping a node onto itself or its descendants is rejected.
+        assert!(!graph.can_reparent(a, a));
+        assert!(!graph.can_reparent(a, c));
+        assert!(!graph.reparent(a, c, DropPosition::Inside));
+        assert!(!graph.reparent(a, c, DropPosition::After));
+        assert!(!graph.can_reparent(graph.get_root(), d));
+        assert_eq!(graph[a].parent(), graph.get_root());
+        assert!(graph.can_reparent(c, d));
+
+        // Global transform is preserved.
+        assert!(graph.reparent(b, d, DropPosition::Inside));
+        graph.update_hierarchical_data();
+        assert_eq!(graph[b].parent(), d.to_base());
+        assert_eq!(graph[b].global_position(), Vector3::new(1.0, 1.0, 0.0));
+        assert_eq!(
+            **graph[b].local_transform().position(),
+            Vector3::new(-4.0, 1.0, 0.0)
+        );
+
+        // Sibling ordering.
+        let root = graph.get_root();
+        assert_eq!(graph[root].children(), &[a.to_base(), d.to_base()]);
+        assert!(graph.reparent(d, a, DropPosition::Before));
+        assert_eq!(graph[root].children(), &[d.to_base(), a.to_base()]);
+        assert!(graph.reparent(d, a, DropPosition::After));
+        assert_eq!(graph[root].children(), &[a.to_base(), d.to_base()]);
+    }
+
+    #[test]
+    fn test_set_global_transform_after_reparent() {
+        let mut graph = Graph::new();
+
+        let node = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        let parent = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(-2.0, 4.0, 1.0))
+                    .with_local_rotation(UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 1.0))
+                    .with_local_scale(Vector3::new(2.0, 2.0, 2.0))
+                    .build(),
+            ),
+        )
+        .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let global_position = graph[node].global_position();
+        let global_transform = graph[node].global_transform();
+
+        graph.link_nodes(node, parent);
+        let parent_global_transform = graph[parent].global_transform();
+        graph[node].set_global_position(global_position, &parent_global_transform);
+        graph.update_hierarchical_data();
+        assert!(graph[node]
+            .global_position()
+            .relative_eq(&global_position, 1.0e-5, 1.0e-5));
+
+        // Full matrix round-trip, including rotation and scale of the parent.
+        graph[node].set_global_transform_matrix(&global_transform, &parent_global_transform);
+        graph.update_hierarchical_data();
+        assert!(graph[node]
+            .global_transform()
+            .relative_eq(&global_transform, 1.0e-5, 1.0e-5));
+
+        // Root-level nodes use identity as the parent transform.
+        let root_level = PivotBuilder::new(BaseBuilder::new()).build(&mut graph);
+        graph[root_level].set_global_position(Vector3::new(3.0, 2.0, 1.0), &Matrix4::identity());
+        graph.update_hierarchical_data();
+        assert_eq!(
+            graph[root_level].global_position(),
+            Vector3::new(3.0, 2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_copy_node_remaps_joints() {
+        let mut graph = Graph::new();
+        let outside_body =
+            RigidBodyBuilder::new(BaseBuilder::new().with_name("Outside")).build(&mut graph);
+        let body_a = RigidBodyBuilder::new(BaseBuilder::new().with_name("A")).build(&mut graph);
+        let body_b = RigidBodyBuilder::new(BaseBuilder::new().with_name("B")).build(&mut graph);
+        let inner_joint = JointBuilder::new(BaseBuilder::new().with_name("Inner"))
+            .with_body1(body_a)
+            .with_body2(body_b)
+            .build(&mut graph);
+        let dangling_joint = JointBuilder::new(BaseBuilder::new().with_name("Dangling"))
+            .with_body1(body_b)
+            .with_body2(outside_body)
+            .build(&mut graph);
+        let root = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Ragdoll")
+                .with_child(body_a)
+                .with_child(body_b)
+                .with_child(inner_joint)
+                .with_child(dangling_joint),
+        )
+        .build(&mut graph);
+
+        let mut dest = Graph::new();
+        let copy = |dest: &mut Graph| {
+            graph.copy_node(
+                root,
+                dest,
+                false,
+                &mut |_, _| true,
+                &mut |_, _| {},
+                &mut |_, _, _| {},
+            )
+        };
+        let (copy1, map1) = copy(&mut dest);
+        let (copy2, _) = copy(&mut dest);
+
+        let joint_bodies = |copy_root: Handle<Node>, joint_name: &str| {
+            let (joint, _) = dest.find_by_name(copy_root, joint_name).unwrap();
+            let joint = dest[joint].cast::<Joint>().unwrap();
+            (joint.body1(), joint.body2())
+        };
+        let bodies = |copy_root: Handle<Node>| {
+            (
+                dest.find_by_name(copy_root, "A").unwrap().0,
+                dest.find_by_name(copy_root, "B").unwrap().0,
+            )
+        };
+
+        let (a1, b1) = bodies(copy1);
+        let (a2, b2) = bodies(copy2);
+        assert_ne!(a1, a2);
+        assert_ne!(b1, b2);
+        assert_eq!(map1.inner()[&body_a.to_base::<Node>()], a1);
+
+        for (copy_root, a, b) in [(copy1, a1, b1), (copy2, a2, b2)] {
+            assert_eq!(
+                joint_bodies(copy_root, "Inner"),
+                (a.to_variant(), b.to_variant())
+            );
+            assert!(dest.find_by_name(copy_root, "Dangling").is_none());
+            assert!(dest[a].cast::<RigidBody>().is_some());
+        }
+        assert!(!map1.inner().contains_key(&dangling_joint.to_base::<Node>()));
+
+        // The originals are untouched.
+        assert_eq!(graph[inner_joint].body1(), body_a);
+        assert_eq!(graph[dangling_joint].body2(), outside_body);
+    }
+
+    #[test]
+    fn test_copy_rigid_body_chain() {
+        let mut graph = Graph::new();
+        let collider = ColliderBuilder::new(BaseBuilder::new().with_name("Collider"))
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut graph);
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_name("Body")
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                        .build(),
+                )
+                .with_child(collider),
+        )
+        .build(&mut graph);
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        let (body_copy, map) = graph.copy_node_inplace(body.to_base(), &mut |_, _| true);
+        assert_ne!(body_copy, body.to_base());
+        assert_eq!(map.inner()[&body.to_base::<Node>()], body_copy);
+        let collider_copy = map.inner()[&collider.to_base::<Node>()];
+        assert_eq!(graph[collider_copy].parent(), body_copy);
+        assert_eq!(
+            graph[body_copy].local_transform().position(),
+            graph[body].local_transform().position()
+        );
+
+        graph.update(Vector2::new(100.0, 100.0), 1.0 / 60.0, Default::default());
+
+        // The copy must have its own native body, and its collider must be attached to it,
+        // not to the body of the original.
+        let native_body = graph[body].native.get();
+        let native_body_copy = graph[body_copy].cast::<RigidBody>().unwrap().native.get();
+        assert_ne!(native_body, native_body_copy);
+        let native_collider_copy = graph[collider_copy]
+            .cast::<Collider>()
+            .unwrap()
+            .native
+            .get();
+        assert_eq!(
+            graph
+                .physics
+                .colliders
+                .get(native_collider_copy)
+                .and_then(|c| c.parent()),
+            Some(native_body_copy)
+        );
+    }
 }
@@ -29,9 +29,9 @@ use crate::{
         arrayvec::ArrayVec,
         instant,
         log::{Log, MessageKind},
-        math::Matrix4Ext,
+        math::{aabb::AxisAlignedBoundingBox, Matrix4Ext},
         parking_lot::Mutex,
-        pool::Handle,
+        pool::{Handle, ObjectOrVariant},
         reflect::prelude::*,
         uuid_provider,
         variable::{InheritableVariable, VariableFlags},
@@ -54,6 +54,7 @@ use crate::{
     },
     utils::raw_mesh::{RawMeshBuilder, RawVertex},
 };
+use fxhash::{FxHashMap, FxHashSet};
 use rapier3d::{
     dynamics::{
         CCDSolver, GenericJoint, GenericJointBuilder, ImpulseJointHandle, ImpulseJointSet,
@@ -61,8 +62,8 @@ use rapier3d::{
         RigidBodyActivation, RigidBodyBuilder, RigidBodyHandle, RigidBodySet, RigidBodyType,
     },
     geometry::{
-        Collider, ColliderBuilder, ColliderHandle, ColliderSet, Cuboid, DefaultBroadPhase,
-        InteractionGroups, NarrowPhase, Ray, SharedShape,
+        ActiveCollisionTypes, BroadPhase, Collider, ColliderBuilder, ColliderHandle, ColliderSet,
+        Cuboid, DefaultBroadPhase, InteractionGroups, NarrowPhase, Ray, SharedShape,
     },
     parry::{query::ShapeCastOptions, shape::HeightField},
     pipeline::{DebugRenderPipeline, EventHandler, PhysicsPipeline},
@@ -432,6 +433,47 @@ impl IntersectionPair {
     }
 }
 
+/// Kind of an [`IntersectionEvent`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum IntersectionEventKind {
+    /// Colliders started to overlap.
+    Started,
+    /// Colliders stopped to overlap (or one of them was removed).
+    Stopped,
+}
+
+/// An event that is generated when a pair of colliders started or stopped to overlap due to at
+/// least one of the colliders being a sensor. See [`PhysicsWorld::intersection_events`] for more
+/// info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntersectionEvent {
+    /// The first node involved in the intersection. It could be either a collider or a trigger.
+    pub collider1: Handle<Node>,
+    /// The second node involved in the intersection. It could be either a collider or a trigger.
+    pub collider2: Handle<Node>,
+    /// Kind of the event.
+    pub kind: IntersectionEventKind,
+}
+
+impl IntersectionEvent {
+    /// Returns `true` if the given node is involved in the event.
+    #[inline]
+    pub fn involves(&self, subject: Handle<Node>) -> bool {
+        self.collider1 == subject || self.collider2 == subject
+    }
+
+    /// Given the handle of a node that is expected to be part of the event, return the other node
+    /// involved in the event.
+    #[inline]
+    pub fn other(&self, subject: Handle<Node>) -> Handle<Node> {
+        if subject == self.collider1 {
+            self.collider2
+        } else {
+            self.collider1
+        }
+    }
+}
+
 pub(super) struct Container<S, A>
 where
     A: Hash + Eq + Clone,
@@ -440,6 +482,37 @@ where
     map: BiDirHashMap<A, Handle<Node>>,
 }
 
+/// Applies the given motor parameters to a native joint. For prismatic and revolute joints, the
+/// free axis is defined to be the x axis. If you want the joint to translate / rotate along a
+/// different axis, you can rotate the joint itself. Returns `false` if the joint type does not
+/// support motors.
+fn apply_joint_motor(
+    native: &mut GenericJoint,
+    params: &JointParams,
+    motor_params: &JointMotorParams,
+) -> bool {
+    let joint_axes: &[JointAxis] = match params {
+        JointParams::PrismaticJoint(_) => &[JointAxis::LinX],
+        JointParams::RevoluteJoint(_) => &[JointAxis::AngX],
+        JointParams::BallJoint(_) => &[JointAxis::AngX, JointAxis::AngY, JointAxis::AngZ],
+        _ => return false,
+    };
+    let JointMotorParams {
+        target_vel,
+        target_pos,
+        stiffness,
+        damping,
+        max_force,
+    } = *motor_params;
+    for joint_axis in joint_axes {
+        // Force based motor model is better in the Fyrox's context
+        native.set_motor_model(*joint_axis, rapier3d::prelude::MotorModel::ForceBased);
+        native.set_motor(*joint_axis, target_pos, target_vel, stiffness, damping);
+        native.set_motor_max_force(*joint_axis, max_force);
+    }
+    true
+}
+
 fn convert_joint_params(
     params: scene::joint::JointParams,
     local_frame1: Isometry3<f32>,
@@ -807,6 +880,47 @@ fn collider_shape_into_native_shape(
             .try_get_component_of_type::<Mesh>(polyhedron.geometry_source.0)
             .ok()
             .map(|mesh| make_polyhedron_shape(owner_inv_global_transform, mesh)),
+        ColliderShape::Compound(compound) => {
+            let shapes = compound
+                .children
+                .iter()
+                .filter(|child| {
+                    // Composite shapes cannot be nested.
+                    !matches!(
+                        child.shape,
+                        ColliderShape::Trimesh(_)
+                            | ColliderShape::Heightfield(_)
+                            | ColliderShape::Polyhedron(_)
+                            | ColliderShape::Compound(_)
+                    )
+                })
+                .filter_map(|child| {
+                    Some((
+                        Isometry3 {
+                            rotation: child.rotation,
+                            translation: Translation3 {
+                                vector: child.position,
+                            },
+                        },
+                        collider_shape_into_native_shape(
+                            &child.shape,
+                            owner_inv_global_transform,
+                            owner_collider,
+                            pool,
+                        )?,
+                    ))
+                })
+                .collect::<Vec<_>>();
+            if shapes.is_empty() {
+                None
+            } else {
+                Some(SharedShape::compound(shapes))
+            }
+        }
+        ColliderShape::Convex(convex) => SharedShape::convex_mesh(
+            convex.vertices.iter().cloned().map(Point3::from).collect(),
+            &convex.triangles().collect::<Vec<_>>(),
+        ),
     }
 }
 
@@ -903,6 +1017,14 @@ pub struct IntegrationParameters {
 
     /// The number of stabilization iterations run at each solver iterations (default: `2`).
     pub num_internal_stabilization_iterations: usize,
+
+    /// The number of substeps each physics step is split into (default: `1`). Every substep runs
+    /// the full simulation pipeline with `dt / num_substeps` time step, which greatly improves
+    /// stability of fast-moving bodies and tall stacks of bodies. The cost of the simulation step
+    /// grows linearly with this value, so keep it as low as possible and prefer to increase it only
+    /// for scenes that really need it. Values less than `1` are treated as `1`.
+    #[reflect(min_value = 1.0)]
+    pub num_substeps: u32,
 }
 
 impl Default for IntegrationParameters {
@@ -924,7 +1046,71 @@ impl Default for IntegrationParameters {
             max_ccd_substeps: 4,
             length_unit: 1.0,
             num_internal_stabilization_iterations: 4,
+            num_substeps: 1,
+        }
+    }
+}
+
+/// Dynamic state of a single rigid body. See [`PhysicsSnapshot`] docs for more info.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RigidBodyState {
+    /// Global position of the body.
+    pub position: Vector3<f32>,
+    /// Global rotation of the body.
+    pub rotation: UnitQuaternion<f32>,
+    /// Linear velocity of the body.
+    pub lin_vel: Vector3<f32>,
+    /// Angular velocity of the body.
+    pub ang_vel: Vector3<f32>,
+    /// Whether the body is sleeping or not.
+    pub sleeping: bool,
+}
+
+impl RigidBodyState {
+    /// Interpolates between this state and the other one, `t` must be in `[0; 1]` range.
+    pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self {
+            position: self.position.lerp(&other.position, t),
+            rotation: self.rotation.slerp(&other.rotation, t),
+            lin_vel: self.lin_vel.lerp(&other.lin_vel, t),
+            ang_vel: self.ang_vel.lerp(&other.ang_vel, t),
+            sleeping: if t < 0.5 {
+                self.sleeping
+            } else {
+                other.sleeping
+            },
+        }
+    }
+}
+
+/// A snapshot of the dynamic state (transforms and velocities) of every rigid body of a physics
+/// world. It could be used to implement rewind mechanics or rollback in networked games. Bodies
+/// are identified by handles of their scene nodes. See [`PhysicsWorld::snapshot`] and
+/// [`PhysicsWorld::restore`] for more info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhysicsSnapshot {
+    /// States of the bodies.
+    pub bodies: FxHashMap<Handle<Node>, RigidBodyState>,
+}
+
+impl PhysicsSnapshot {
+    /// Returns the state of the body of the given scene node.
+    pub fn body(&self, handle: Handle<impl ObjectOrVariant<Node>>) -> Option<&RigidBodyState> {
+        self.bodies.get(&handle.to_base())
+    }
+
+    /// Interpolates between this snapshot and the other one, `t` must be in `[0; 1]` range. Bodies
+    /// that are present in only one of the snapshots are taken as is.
+    pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let mut bodies = other.bodies.clone();
+        for (handle, state) in self.bodies.iter() {
+            let interpolated = match other.bodies.get(handle) {
+                Some(other_state) => state.interpolate(other_state, t),
+                None => state.clone(),
+            };
+            bodies.insert(*handle, interpolated);
         }
+        Self { bodies }
     }
 }
 
@@ -989,6 +1175,15 @@ pub struct PhysicsWorld {
     #[visit(skip)]
     #[reflect(hidden)]
     event_handler: Box<dyn EventHandler>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    active_intersections: FxHashSet<(Handle<Node>, Handle<Node>)>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    intersection_events: Vec<IntersectionEvent>,
+
     #[visit(skip)]
     #[reflect(hidden)]
     debug_render_pipeline: Mutex<DebugRenderPipeline>,
@@ -1099,11 +1294,71 @@ impl PhysicsWorld {
                 map: Default::default(),
             },
             event_handler: Box::new(()),
+            active_intersections: Default::default(),
+            intersection_events: Default::default(),
             performance_statistics: Default::default(),
             debug_render_pipeline: Default::default(),
         }
     }
 
+    fn native_integration_parameters(&self, dt: f32) -> rapier3d::dynamics::IntegrationParameters {
+        rapier3d::dynamics::IntegrationParameters {
+            dt,
+            min_ccd_dt: self.integration_parameters.min_ccd_dt,
+            contact_damping_ratio: self.integration_parameters.contact_damping_ratio,
+            contact_natural_frequency: self.integration_parameters.contact_natural_frequency,
+            joint_natural_frequency: self.integration_parameters.joint_natural_frequency,
+            joint_damping_ratio: self.integration_parameters.joint_damping_ratio,
+            warmstart_coefficient: self.integration_parameters.warmstart_coefficient,
+            length_unit: self.integration_parameters.length_unit,
+            normalized_allowed_linear_error: self.integration_parameters.allowed_linear_error,
+            normalized_max_corrective_velocity: self
+                .integration_parameters
+                .normalized_max_corrective_velocity,
+            normalized_prediction_distance: self.integration_parameters.prediction_distance,
+            num_solver_iterations: self.integration_parameters.num_solver_iterations,
+            num_internal_pgs_iterations: self.integration_parameters.num_internal_pgs_iterations,
+            num_internal_stabilization_iterations: self
+                .integration_parameters
+                .num_internal_stabilization_iterations,
+            min_island_size: self.integration_parameters.min_island_size as usize,
+            max_ccd_substeps: self.integration_parameters.max_ccd_substeps as usize,
+            friction_model: FrictionModel::default(),
+        }
+    }
+
+    /// Moves colliders to the current positions of their rigid bodies and updates acceleration
+    /// structures used by scene queries (ray casts, shape casts, etc.) without advancing the
+    /// simulation. The state restored by [`Self::restore`] is visible to scene queries only after
+    /// the next simulation step, this method allows to query the restored state immediately.
+    ///
+    /// Contacts are not updated, so the method should be used to query a temporary state, that is
+    /// reverted before the next simulation step (as [`crate::scene::lag_compensation`] does).
+    pub fn update_scene_queries(&mut self) {
+        let mut modified_colliders = Vec::new();
+        for (_, body) in self.bodies.iter() {
+            for &collider_handle in body.colliders() {
+                if let Some(collider) = self.colliders.get_mut(collider_handle) {
+                    if let Some(position_wrt_parent) = collider.position_wrt_parent().copied() {
+                        collider.set_position(body.position() * position_wrt_parent);
+                        modified_colliders.push(collider_handle);
+                    }
+                }
+            }
+        }
+
+        // Pair events are ignored, the narrow phase is updated by the simulation step only.
+        let mut events = Vec::new();
+        self.broad_phase.update(
+            &self.native_integration_parameters(0.0),
+            &self.colliders,
+            &self.bodies,
+            &modified_colliders,
+            &[],
+            &mut events,
+        );
+    }
+
     /// Update the physics pipeline with a timestep of the given length.
     ///
     /// * `dt`: The amount of time that has passed since the previous update.
@@ -1129,51 +1384,82 @@ impl PhysicsWorld {
         };
 
         if *self.enabled {
-            let integration_parameters = rapier3d::dynamics::IntegrationParameters {
-                dt,
-                min_ccd_dt: self.integration_parameters.min_ccd_dt,
-                contact_damping_ratio: self.integration_parameters.contact_damping_ratio,
-                contact_natural_frequency: self.integration_parameters.contact_natural_frequency,
-                joint_natural_frequency: self.integration_parameters.joint_natural_frequency,
-                joint_damping_ratio: self.integration_parameters.joint_damping_ratio,
-                warmstart_coefficient: self.integration_parameters.warmstart_coefficient,
-                length_unit: self.integration_parameters.length_unit,
-                normalized_allowed_linear_error: self.integration_parameters.allowed_linear_error,
-                normalized_max_corrective_velocity: self
-                    .integration_parameters
-                    .normalized_max_corrective_velocity,
-                normalized_prediction_distance: self.integration_parameters.prediction_distance,
-                num_solver_iterations: self.integration_parameters.num_solver_iterations,
-                num_internal_pgs_iterations: self
-                    .integration_parameters
-                    .num_internal_pgs_iterations,
-                num_internal_stabilization_iterations: self
-                    .integration_parameters
-                    .num_internal_stabilization_iterations,
-                min_island_size: self.integration_parameters.min_island_size as usize,
-                max_ccd_substeps: self.integration_parameters.max_ccd_substeps as usize,
-                friction_model: FrictionModel::default(),
-            };
+            let num_substeps = self.integration_parameters.num_substeps.max(1);
+            let integration_parameters =
+                self.native_integration_parameters(dt / num_substeps as f32);
+
+            for _ in 0..num_substeps {
+                self.pipeline.step(
+                    &self.gravity,
+                    &integration_parameters,
+                    &mut self.islands,
+                    &mut self.broad_phase,
+                    &mut self.narrow_phase,
+                    &mut self.bodies,
+                    &mut self.colliders,
+                    &mut self.joints.set,
+                    &mut self.multibody_joints.set,
+                    &mut self.ccd_solver,
+                    &(),
+                    &*self.event_handler,
+                );
+            }
 
-            self.pipeline.step(
-                &self.gravity,
-                &integration_parameters,
-                &mut self.islands,
-                &mut self.broad_phase,
-                &mut self.narrow_phase,
-                &mut self.bodies,
-                &mut self.colliders,
-                &mut self.joints.set,
-                &mut self.multibody_joints.set,
-                &mut self.ccd_solver,
-                &(),
-                &*self.event_handler,
-            );
+            self.update_intersection_events();
+        } else {
+            self.intersection_events.clear();
         }
 
         self.performance_statistics.step_time += instant::Instant::now() - time;
     }
 
+    fn update_intersection_events(&mut self) {
+        let current = self
+            .narrow_phase
+            .intersection_pairs()
+            .filter(|(_, _, intersecting)| *intersecting)
+            .filter_map(|(collider1, collider2, _)| {
+                Some((
+                    Handle::decode_from_u128(self.colliders.get(collider1)?.user_data),
+                    Handle::decode_from_u128(self.colliders.get(collider2)?.user_data),
+                ))
+            })
+            .collect::<FxHashSet<(Handle<Node>, Handle<Node>)>>();
+
+        self.intersection_events.clear();
+        for (pairs, other, kind) in [
+            (
+                &current,
+                &self.active_intersections,
+                IntersectionEventKind::Started,
+            ),
+            (
+                &self.active_intersections,
+                &current,
+                IntersectionEventKind::Stopped,
+            ),
+        ] {
+            self.intersection_events.extend(pairs.difference(other).map(
+                |(collider1, collider2)| IntersectionEvent {
+                    collider1: *collider1,
+                    collider2: *collider2,
+                    kind,
+                },
+            ));
+        }
+        self.active_intersections = current;
+    }
+
+    /// Returns a list of intersection events generated during the last simulation step. Intersection
+    /// events are produced only for pairs of colliders where at least one collider is a sensor (or a
+    /// trigger). Each overlap produces exactly one [`IntersectionEventKind::Started`] event when the
+    /// colliders start to overlap and one [`IntersectionEventKind::Stopped`] event when they stop
+    /// overlapping. Use [`crate::scene::collider::Collider::intersects`] to get the list of current
+    /// overlaps of a collider.
+    pub fn intersection_events(&self) -> &[IntersectionEvent] {
+        &self.intersection_events
+    }
+
     pub(super) fn add_body(&mut self, owner: Handle<Node>, mut body: RigidBody) -> RigidBodyHandle {
         body.user_data = owner.encode_to_u128();
         self.bodies.insert(body)
@@ -1201,6 +1487,115 @@ impl PhysicsWorld {
             .insert_with_parent(collider, parent_body, &mut self.bodies)
     }
 
+    pub(super) fn add_parentless_collider(
+        &mut self,
+        owner: Handle<Node>,
+        mut collider: Collider,
+    ) -> ColliderHandle {
+        collider.user_data = owner.encode_to_u128();
+        self.colliders.insert(collider)
+    }
+
+    pub(crate) fn apply_buoyancy(
+        &mut self,
+        volume: &AxisAlignedBoundingBox,
+        fluid_density: f32,
+        linear_drag: f32,
+        angular_drag: f32,
+        dt: f32,
+    ) {
+        if !*self.enabled {
+            return;
+        }
+
+        let mut submerged_bodies = Vec::new();
+        for (handle, body) in self.bodies.iter() {
+            if !body.is_dynamic() {
+                continue;
+            }
+
+            let mut aabb = AxisAlignedBoundingBox::default();
+            let mut body_volume = 0.0;
+            for collider in body
+                .colliders()
+                .iter()
+                .filter_map(|c| self.colliders.get(*c))
+                .filter(|c| !c.is_sensor())
+            {
+                let collider_aabb = collider.compute_aabb();
+                aabb.add_box(AxisAlignedBoundingBox::from_min_max(
+                    collider_aabb.mins.coords,
+                    collider_aabb.maxs.coords,
+                ));
+                body_volume += collider.shape().mass_properties(1.0).mass();
+            }
+
+            if !aabb.is_valid() || !aabb.is_intersects_aabb(volume) {
+                continue;
+            }
+
+            // Submerged fraction is approximated using vertical extents of the bounding box of
+            // the body.
+            let height = aabb.max.y - aabb.min.y;
+            let submerged_height = aabb.max.y.min(volume.max.y) - aabb.min.y.max(volume.min.y);
+            let fraction = if height > f32::EPSILON {
+                (submerged_height / height).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+
+            if fraction > 0.0 {
+                submerged_bodies.push((handle, fraction * body_volume, fraction));
+            }
+        }
+
+        for (handle, submerged_volume, fraction) in submerged_bodies {
+            let body = &mut self.bodies[handle];
+            body.apply_impulse(
+                -self.gravity.scale(fluid_density * submerged_volume * dt),
+                true,
+            );
+            let linear_damping = (1.0 - linear_drag * fraction * dt).max(0.0);
+            body.set_linvel(body.linvel().scale(linear_damping), true);
+            let angular_damping = (1.0 - angular_drag * fraction * dt).max(0.0);
+            body.set_angvel(body.angvel().scale(angular_damping), true);
+        }
+    }
+
+    pub(crate) fn collider_aabb(&self, handle: ColliderHandle) -> Option<AxisAlignedBoundingBox> {
+        self.colliders.get(handle).map(|collider| {
+            let aabb = collider.compute_aabb();
+            AxisAlignedBoundingBox::from_min_max(aabb.mins.coords, aabb.maxs.coords)
+        })
+    }
+
+    pub(crate) fn body_state(&self, handle: RigidBodyHandle) -> Option<RigidBodyState> {
+        self.bodies.get(handle).map(|body| {
+            let position = body.position();
+            RigidBodyState {
+                position: position.translation.vector,
+                rotation: position.rotation,
+                lin_vel: *body.linvel(),
+                ang_vel: *body.angvel(),
+                sleeping: body.is_sleeping(),
+            }
+        })
+    }
+
+    pub(crate) fn body_mass(&self, handle: RigidBodyHandle) -> Option<f32> {
+        self.bodies.get(handle).map(|body| body.mass())
+    }
+
+    pub(crate) fn body_velocity_at_point(
+        &self,
+        handle: RigidBodyHandle,
+        point: Vector3<f32>,
+    ) -> Option<Vector3<f32>> {
+        self.bodies
+            .get(handle)
+            .map(|body| body.velocity_at_point(&Point3::from(point)))
+    }
+
     pub(crate) fn remove_collider(&mut self, handle: ColliderHandle) -> bool {
         self.colliders
             .remove(handle, &mut self.islands, &mut self.bodies, false)
@@ -1225,6 +1620,66 @@ impl PhysicsWorld {
         }
     }
 
+    /// Captures the dynamic state of every rigid body of the world. See [`PhysicsSnapshot`] docs
+    /// for more info.
+    pub fn snapshot(&self) -> PhysicsSnapshot {
+        PhysicsSnapshot {
+            bodies: self
+                .bodies
+                .iter()
+                .map(|(_, body)| {
+                    let position = body.position();
+                    (
+                        Handle::<Node>::decode_from_u128(body.user_data),
+                        RigidBodyState {
+                            position: position.translation.vector,
+                            rotation: position.rotation,
+                            lin_vel: *body.linvel(),
+                            ang_vel: *body.angvel(),
+                            sleeping: body.is_sleeping(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Restores the dynamic state of rigid bodies from the given snapshot. Bodies are matched by
+    /// handles of their scene nodes: bodies that were added after the snapshot was made are left
+    /// untouched, and states of the bodies that were removed since then are ignored.
+    ///
+    /// This method changes the state of the physics world only, use [`Graph::restore_physics`]
+    /// to restore the state of a scene, so the rigid body scene nodes will be synced with the
+    /// restored state immediately.
+    pub fn restore(&mut self, snapshot: &PhysicsSnapshot) {
+        for (_, body) in self.bodies.iter_mut() {
+            let Some(state) = snapshot
+                .bodies
+                .get(&Handle::<Node>::decode_from_u128(body.user_data))
+            else {
+                continue;
+            };
+
+            body.set_position(
+                Isometry3 {
+                    translation: Translation3::from(state.position),
+                    rotation: state.rotation,
+                },
+                false,
+            );
+            body.set_linvel(state.lin_vel, false);
+            body.set_angvel(state.ang_vel, false);
+            // Forces of the current step must not leak into the restored state.
+            body.reset_forces(false);
+            body.reset_torques(false);
+            if state.sleeping {
+                body.sleep();
+            } else {
+                body.wake_up(true);
+            }
+        }
+    }
+
     /// Draws physics world. Very useful for debugging, it allows you to see where are
     /// rigid bodies, which colliders they have and so on.
     pub fn draw(&self, context: &mut SceneDrawingContext) {
@@ -1653,6 +2108,106 @@ impl PhysicsWorld {
         }
     }
 
+    // Synchronizes properties of an existing native collider with its scene node. Does nothing if
+    // the node has no native collider.
+    fn sync_native_collider_properties(
+        &mut self,
+        nodes: &NodePool,
+        handle: Handle<Node>,
+        collider_node: &scene::collider::Collider,
+    ) {
+        // Important note: `get_mut` is **very** expensive because it forces physics engine to
+        // recalculate contacts and a lot of other stuff, this is why we need this check.
+        if !collider_node.needs_sync_model() {
+            return;
+        }
+
+        if let Some(native) = self.colliders.get_mut(collider_node.native.get()) {
+            collider_node
+                .restitution
+                .try_sync_model(|v| native.set_restitution(v));
+            collider_node.collision_groups.try_sync_model(|v| {
+                native.set_collision_groups(InteractionGroups::new(
+                    u32_to_group(v.memberships.0),
+                    u32_to_group(v.filter.0),
+                ))
+            });
+            collider_node.solver_groups.try_sync_model(|v| {
+                native.set_solver_groups(InteractionGroups::new(
+                    u32_to_group(v.memberships.0),
+                    u32_to_group(v.filter.0),
+                ))
+            });
+            collider_node
+                .friction
+                .try_sync_model(|v| native.set_friction(v));
+            collider_node
+                .is_sensor
+                .try_sync_model(|v| native.set_sensor(v));
+            collider_node
+                .friction_combine_rule
+                .try_sync_model(|v| native.set_friction_combine_rule(v.into()));
+            collider_node
+                .restitution_combine_rule
+                .try_sync_model(|v| native.set_restitution_combine_rule(v.into()));
+            let mut remove_collider = false;
+            collider_node.shape.try_sync_model(|v| {
+                let inv_global_transform = isometric_global_transform(nodes, handle)
+                    .try_inverse()
+                    .unwrap_or_default();
+
+                if let Some(shape) =
+                    collider_shape_into_native_shape(&v, inv_global_transform, handle, nodes)
+                {
+                    native.set_shape(shape);
+                } else {
+                    remove_collider = true;
+                }
+            });
+            if remove_collider {
+                self.remove_collider(collider_node.native.get());
+                collider_node.native.set(ColliderHandle::invalid());
+            }
+        }
+    }
+
+    // Creates a native collider builder with all the properties of the given collider node.
+    fn native_collider_builder(
+        nodes: &NodePool,
+        handle: Handle<Node>,
+        collider_node: &scene::collider::Collider,
+    ) -> Option<ColliderBuilder> {
+        let inv_global_transform = isometric_global_transform(nodes, handle)
+            .try_inverse()
+            .unwrap_or_default();
+        let shape = collider_shape_into_native_shape(
+            collider_node.shape(),
+            inv_global_transform,
+            handle,
+            nodes,
+        )?;
+        let mut builder = ColliderBuilder::new(shape)
+            .friction(collider_node.friction())
+            .restitution(collider_node.restitution())
+            .collision_groups(InteractionGroups::new(
+                u32_to_group(collider_node.collision_groups().memberships.0),
+                u32_to_group(collider_node.collision_groups().filter.0),
+            ))
+            .friction_combine_rule(collider_node.friction_combine_rule().into())
+            .restitution_combine_rule(collider_node.restitution_combine_rule().into())
+            .solver_groups(InteractionGroups::new(
+                u32_to_group(collider_node.solver_groups().memberships.0),
+                u32_to_group(collider_node.solver_groups().filter.0),
+            ))
+            .sensor(collider_node.is_sensor());
+
+        if let Some(density) = collider_node.density() {
+            builder = builder.density(density);
+        }
+
+        Some(builder)
+    }
+
     pub(crate) fn sync_to_collider_node(
         &mut self,
         nodes: &NodePool,
@@ -1665,104 +2220,35 @@ impl PhysicsWorld {
             return;
         }
 
-        let anything_changed = collider_node.needs_sync_model();
-
-        // Important notes!
-        // 1) The collider node may lack backing native physics collider in case if it
-        //    is not attached to a rigid body.
-        // 2) `get_mut` is **very** expensive because it forces physics engine to recalculate contacts
-        //    and a lot of other stuff, this is why we need `anything_changed` flag.
+        // Important note: the collider node may lack backing native physics collider in case if
+        // it is not attached to a rigid body.
         if collider_node.native.get() != ColliderHandle::invalid() {
-            if anything_changed {
-                if let Some(native) = self.colliders.get_mut(collider_node.native.get()) {
-                    collider_node
-                        .restitution
-                        .try_sync_model(|v| native.set_restitution(v));
-                    collider_node.collision_groups.try_sync_model(|v| {
-                        native.set_collision_groups(InteractionGroups::new(
-                            u32_to_group(v.memberships.0),
-                            u32_to_group(v.filter.0),
-                        ))
-                    });
-                    collider_node.solver_groups.try_sync_model(|v| {
-                        native.set_solver_groups(InteractionGroups::new(
-                            u32_to_group(v.memberships.0),
-                            u32_to_group(v.filter.0),
-                        ))
-                    });
-                    collider_node
-                        .friction
-                        .try_sync_model(|v| native.set_friction(v));
-                    collider_node
-                        .is_sensor
-                        .try_sync_model(|v| native.set_sensor(v));
-                    collider_node
-                        .friction_combine_rule
-                        .try_sync_model(|v| native.set_friction_combine_rule(v.into()));
-                    collider_node
-                        .restitution_combine_rule
-                        .try_sync_model(|v| native.set_restitution_combine_rule(v.into()));
-                    let mut remove_collider = false;
-                    collider_node.shape.try_sync_model(|v| {
-                        let inv_global_transform = isometric_global_transform(nodes, handle)
-                            .try_inverse()
-                            .unwrap_or_default();
-
-                        if let Some(shape) = collider_shape_into_native_shape(
-                            &v,
-                            inv_global_transform,
-                            handle,
-                            nodes,
-                        ) {
-                            native.set_shape(shape);
-                        } else {
-                            remove_collider = true;
-                        }
-                    });
-                    if remove_collider {
-                        self.remove_collider(collider_node.native.get());
-                        collider_node.native.set(ColliderHandle::invalid());
-                    }
-                }
-            }
+            self.sync_native_collider_properties(nodes, handle, collider_node);
         } else if let Ok(parent_body) =
             nodes.try_get_component_of_type::<scene::rigidbody::RigidBody>(collider_node.parent())
         {
             if parent_body.native.get() != RigidBodyHandle::invalid() {
-                let inv_global_transform = isometric_global_transform(nodes, handle)
-                    .try_inverse()
-                    .unwrap();
+                if !collider_node
+                    .shape()
+                    .is_compatible_with(parent_body.body_type())
+                {
+                    Log::err(format!(
+                        "Collider {} uses a triangle mesh shape, but its rigid body {} is \
+                        dynamic. Triangle meshes have no volume, so the body will have wrong \
+                        mass and inertia. Use convex shapes for dynamic rigid bodies instead!",
+                        collider_node.name(),
+                        parent_body.name()
+                    ));
+                }
+
                 let rigid_body_native = parent_body.native.get();
-                if let Some(shape) = collider_shape_into_native_shape(
-                    collider_node.shape(),
-                    inv_global_transform,
-                    handle,
-                    nodes,
-                ) {
-                    let mut builder = ColliderBuilder::new(shape)
-                        .position(Isometry3 {
-                            rotation: **collider_node.local_transform().rotation(),
-                            translation: Translation3 {
-                                vector: **collider_node.local_transform().position(),
-                            },
-                        })
-                        .friction(collider_node.friction())
-                        .restitution(collider_node.restitution())
-                        .collision_groups(InteractionGroups::new(
-                            u32_to_group(collider_node.collision_groups().memberships.0),
-                            u32_to_group(collider_node.collision_groups().filter.0),
-                        ))
-                        .friction_combine_rule(collider_node.friction_combine_rule().into())
-                        .restitution_combine_rule(collider_node.restitution_combine_rule().into())
-                        .solver_groups(InteractionGroups::new(
-                            u32_to_group(collider_node.solver_groups().memberships.0),
-                            u32_to_group(collider_node.solver_groups().filter.0),
-                        ))
-                        .sensor(collider_node.is_sensor());
-
-                    if let Some(density) = collider_node.density() {
-                        builder = builder.density(density);
-                    }
+                if let Some(builder) = Self::native_collider_builder(nodes, handle, collider_node) {
+                    let builder = builder.position(Isometry3 {
+                        rotation: **collider_node.local_transform().rotation(),
+                        translation: Translation3 {
+                            vector: **collider_node.local_transform().position(),
+                        },
+                    });
 
                     let native_handle =
                         self.add_collider(handle, rigid_body_native, builder.build());
@@ -1781,6 +2267,56 @@ impl PhysicsWorld {
         }
     }
 
+    pub(crate) fn sync_to_trigger_node(
+        &mut self,
+        nodes: &NodePool,
+        handle: Handle<Node>,
+        collider_node: &scene::collider::Collider,
+    ) {
+        if !collider_node.is_globally_enabled() {
+            self.remove_collider(collider_node.native.get());
+            collider_node.native.set(Default::default());
+            return;
+        }
+
+        if collider_node.native.get() != ColliderHandle::invalid() {
+            self.sync_native_collider_properties(nodes, handle, collider_node);
+        } else if let Some(builder) = Self::native_collider_builder(nodes, handle, collider_node) {
+            // Triggers are not attached to any rigid body, so they're positioned in world space and
+            // must detect kinematic bodies (such as character controllers) as well.
+            let collider = builder
+                .position(isometry_from_global_transform(&isometric_global_transform(
+                    nodes, handle,
+                )))
+                .active_collision_types(ActiveCollisionTypes::all())
+                .build();
+
+            let native_handle = self.add_parentless_collider(handle, collider);
+
+            collider_node.native.set(native_handle);
+
+            Log::writeln(
+                MessageKind::Information,
+                format!(
+                    "Native collider was created for trigger {}",
+                    collider_node.name()
+                ),
+            );
+        }
+    }
+
+    pub(crate) fn set_trigger_position(
+        &mut self,
+        collider_node: &scene::collider::Collider,
+        new_isometric_global_transform: &Matrix4<f32>,
+    ) {
+        if let Some(native) = self.colliders.get_mut(collider_node.native.get()) {
+            native.set_position(isometry_from_global_transform(
+                new_isometric_global_transform,
+            ));
+        }
+    }
+
     pub(crate) fn sync_to_joint_node(
         &mut self,
         nodes: &NodePool,
@@ -1804,48 +2340,14 @@ impl PhysicsWorld {
                     native.body2 = rigid_body_node.native.get();
                 }
             });
-            joint.params.try_sync_model(|v| {
+            // Native joint data is rebuilt from scratch when joint parameters or local frames
+            // are changed, so the motor must be re-applied in this case, otherwise it will be
+            // silently disabled.
+            let mut rebuilt = joint.params.try_sync_model(|v| {
                 native.data =
                     // Preserve local frames.
                     convert_joint_params(v, native.data.local_frame1, native.data.local_frame2)
             });
-            joint.motor_params.try_sync_model(|v|{
-                // For prismatic and revolute joints, the free axis is defined to be the x axis.
-                // If you want the joint to translate / rotate along a different axis, you can rotate the joint itself.
-                let joint_axes: &[JointAxis] = match joint.params.get_value_ref(){
-                    JointParams::PrismaticJoint(_) => &[JointAxis::LinX][..], // slice the array to unify arrays of different lengths.
-                    JointParams::RevoluteJoint(_) => &[JointAxis::AngX][..],
-                    JointParams::BallJoint(_) => &[JointAxis::AngX, JointAxis::AngY, JointAxis::AngZ][..],
-                    _ => {
-                        Log::warn("Try to modify motor parameters for unsupported joint type, this operation will be ignored.");
-                        return;
-                    }
-                };
-                for joint_axis in joint_axes {
-                    // Force based motor model is better in the Fyrox's context
-                    native.data.set_motor_model(*joint_axis, rapier3d::prelude::MotorModel::ForceBased);
-                    let JointMotorParams {
-                        target_vel,
-                        target_pos,
-                        stiffness,
-                        damping,
-                        max_force
-                    } = v;
-                    native.data.set_motor(*joint_axis, target_pos, target_vel, stiffness, damping);
-                    native.data.set_motor_max_force(*joint_axis, max_force);
-                }
-                // wake up the bodies connected to the joint to ensure they respond to the motor changes immediately
-                // however, the rigid bodies may fall asleep any time later unless Joint::set_motor_* functions are called periodically,
-                // or the rigid bodies are set to cannot sleep
-                let Some(body1) = self.bodies.get_mut(native.body1) else {
-                    return;
-                };
-                body1.wake_up(true);
-                let Some(body2) = self.bodies.get_mut(native.body2) else {
-                    return;
-                };
-                body2.wake_up(true);
-            });
             joint.contacts_enabled.try_sync_model(|v| {
                 native.data.set_contacts_enabled(v);
             });
@@ -1858,7 +2360,31 @@ impl PhysicsWorld {
                     let (local_frame1, local_frame2) = calculate_local_frames(joint, body1, body2);
                     native.data =
                         convert_joint_params((*joint.params).clone(), local_frame1, local_frame2);
+                    native
+                        .data
+                        .set_contacts_enabled(joint.is_contacts_enabled());
                     *local_frames = Some(JointLocalFrames::new(&local_frame1, &local_frame2));
+                    rebuilt = true;
+                }
+            }
+
+            let motor_changed = joint.motor_params.try_sync_model(|_| {});
+            if rebuilt || motor_changed {
+                let supported =
+                    apply_joint_motor(&mut native.data, &joint.params, &joint.motor_params);
+                if motor_changed {
+                    if !supported {
+                        Log::warn("Try to modify motor parameters for unsupported joint type, this operation will be ignored.");
+                    } else {
+                        // wake up the bodies connected to the joint to ensure they respond to the motor changes immediately
+                        // however, the rigid bodies may fall asleep any time later unless Joint::set_motor_* functions are called periodically,
+                        // or the rigid bodies are set to cannot sleep
+                        for body in [native.body1, native.body2] {
+                            if let Some(body) = self.bodies.get_mut(body) {
+                                body.wake_up(true);
+                            }
+                        }
+                    }
                 }
             }
         } else {
@@ -1914,6 +2440,7 @@ impl PhysicsWorld {
 
                 let mut native_joint = convert_joint_params(params, local_frame1, local_frame2);
                 native_joint.contacts_enabled = joint.is_contacts_enabled();
+                apply_joint_motor(&mut native_joint, &joint.params, &joint.motor_params);
                 let native_handle =
                     self.add_joint(handle, native_body1, native_body2, native_joint);
 
@@ -1975,3 +2502,140 @@ impl Debug for PhysicsWorld {
         write!(f, "PhysicsWorld")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn step(graph: &mut Graph, count: usize) {
+        for _ in 0..count {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut graph = Graph::new();
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 10.0, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::ball(0.5))
+                        .build(&mut graph),
+                ),
+        )
+        .with_lin_vel(Vector3::new(1.0, 2.0, 0.0))
+        .with_ang_vel(Vector3::new(0.0, 3.0, 0.0))
+        .with_can_sleep(false)
+        .build(&mut graph);
+
+        step(&mut graph, 10);
+        let snapshot = graph.physics.snapshot();
+        let state = snapshot.body(body).unwrap().clone();
+
+        step(&mut graph, 10);
+        let continued = graph.physics.snapshot();
+        assert_ne!(continued, snapshot);
+
+        graph.restore_physics(&snapshot);
+        assert_eq!(graph.physics.snapshot(), snapshot);
+        assert_eq!(graph[body].lin_vel(), state.lin_vel);
+        assert!(
+            graph[body]
+                .global_position()
+                .metric_distance(&state.position)
+                < 1.0e-5
+        );
+
+        // The simulation continues from the restored state.
+        step(&mut graph, 10);
+        let restored_continued = graph.physics.snapshot();
+        let expected = continued.body(body).unwrap();
+        let actual = restored_continued.body(body).unwrap();
+        assert!(expected.position.metric_distance(&actual.position) < 1.0e-4);
+        assert!(expected.lin_vel.metric_distance(&actual.lin_vel) < 1.0e-4);
+
+        // Bodies added after the snapshot are left untouched.
+        let new_body = RigidBodyBuilder::new(BaseBuilder::new()).build(&mut graph);
+        step(&mut graph, 1);
+        let new_state = graph.physics.snapshot().body(new_body).cloned();
+        assert!(new_state.is_some());
+        graph.restore_physics(&snapshot);
+        assert_eq!(graph.physics.snapshot().body(new_body).cloned(), new_state);
+    }
+
+    fn box_stack_drift(num_substeps: u32) -> f32 {
+        let mut graph = Graph::new();
+        graph.physics.integration_parameters.num_substeps = num_substeps;
+        graph.physics.integration_parameters.num_solver_iterations = 1;
+
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.5, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::cuboid(10.0, 0.5, 10.0))
+                        .build(&mut graph),
+                ),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        let boxes = (0..10)
+            .map(|i| {
+                let position = Vector3::new(0.0, 0.5 + i as f32, 0.0);
+                let body = RigidBodyBuilder::new(
+                    BaseBuilder::new()
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(position)
+                                .build(),
+                        )
+                        .with_child(
+                            ColliderBuilder::new(BaseBuilder::new())
+                                .with_shape(ColliderShape::cuboid(0.5, 0.5, 0.5))
+                                .build(&mut graph),
+                        ),
+                )
+                .with_can_sleep(false)
+                .build(&mut graph);
+                (body, position)
+            })
+            .collect::<Vec<_>>();
+
+        for _ in 0..120 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 20.0, Default::default());
+        }
+
+        boxes
+            .iter()
+            .map(|(body, position)| graph[*body].global_position().metric_distance(position))
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn test_substeps_improve_stack_stability() {
+        let single = box_stack_drift(1);
+        let substepped = box_stack_drift(8);
+        assert!(substepped <= single);
+        assert!(substepped < 0.1);
+    }
+}
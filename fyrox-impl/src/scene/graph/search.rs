@@ -0,0 +1,293 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Search index over names and tags of scene nodes. See [`GraphSearchIndex`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    graph::SceneGraph,
+    scene::{
+        graph::{event::GraphEvent, Graph},
+        node::Node,
+    },
+};
+use fxhash::FxHashMap;
+use std::sync::mpsc::{channel, Receiver};
+
+/// Defines how a node matches a search query. Variants are sorted by relevance, the most relevant
+/// comes first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SearchMatch {
+    /// The name of the node is equal to the query.
+    ExactName,
+    /// The name of the node starts with the query.
+    NamePrefix,
+    /// The name of the node contains the query.
+    NameSubstring,
+    /// The tag of the node is equal to the query.
+    ExactTag,
+    /// The tag of the node starts with the query.
+    TagPrefix,
+    /// The tag of the node contains the query.
+    TagSubstring,
+}
+
+impl SearchMatch {
+    fn classify(
+        text: &str,
+        query: &str,
+        exact: Self,
+        prefix: Self,
+        substring: Self,
+    ) -> Option<Self> {
+        if text == query {
+            Some(exact)
+        } else if text.starts_with(query) {
+            Some(prefix)
+        } else if text.contains(query) {
+            Some(substring)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    tag: String,
+}
+
+impl Entry {
+    fn new(node: &Node) -> Self {
+        Self {
+            name: node.name().to_lowercase(),
+            tag: node.tag().to_lowercase(),
+        }
+    }
+}
+
+/// Case-insensitive search index over names and tags of scene nodes. The index subscribes to the
+/// events of the graph and keeps itself in sync with added and removed nodes on every call of
+/// [`Self::update`]. The graph does not report changes of names and tags, so the index must be
+/// notified about them explicitly using [`Self::reindex`].
+///
+/// ## Examples
+///
+/// ```rust
+/// # use fyrox_impl::scene::{
+/// #     base::BaseBuilder, graph::{Graph, search::GraphSearchIndex}, pivot::PivotBuilder,
+/// # };
+/// let mut graph = Graph::new();
+/// let mut index = GraphSearchIndex::new(&mut graph);
+///
+/// let player = PivotBuilder::new(BaseBuilder::new().with_name("Player")).build(&mut graph);
+/// index.update(&graph);
+///
+/// assert_eq!(index.search("play"), vec![player.to_base()]);
+/// ```
+#[derive(Debug)]
+pub struct GraphSearchIndex {
+    entries: FxHashMap<Handle<Node>, Entry>,
+    receiver: Receiver<GraphEvent>,
+}
+
+impl GraphSearchIndex {
+    /// Creates a new index, that contains every node of the graph and subscribes to the graph
+    /// events.
+    pub fn new(graph: &mut Graph) -> Self {
+        let (sender, receiver) = channel();
+        graph.event_broadcaster.subscribe(sender);
+        Self {
+            entries: graph
+                .pair_iter()
+                .map(|(handle, node)| (handle, Entry::new(node)))
+                .collect(),
+            receiver,
+        }
+    }
+
+    /// Applies every pending graph event (node addition and removal) to the index.
+    pub fn update(&mut self, graph: &Graph) {
+        for event in self.receiver.try_iter() {
+            match event {
+                GraphEvent::Added(handle) => {
+                    if let Ok(node) = graph.try_get_node(handle) {
+                        self.entries.insert(handle, Entry::new(node));
+                    }
+                }
+                GraphEvent::Removed(handle) => {
+                    self.entries.remove(&handle);
+                }
+            }
+        }
+    }
+
+    /// Updates the name and the tag of the given node in the index. Must be called when the name
+    /// or the tag of the node is changed.
+    pub fn reindex(&mut self, graph: &Graph, handle: Handle<Node>) {
+        match graph.try_get_node(handle) {
+            Ok(node) => {
+                self.entries.insert(handle, Entry::new(node));
+            }
+            Err(_) => {
+                self.entries.remove(&handle);
+            }
+        }
+    }
+
+    /// Returns the total amount of indexed nodes.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the index has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Searches for nodes which names or tags match the query (case-insensitive). Returns matching
+    /// nodes sorted by relevance: name matches come before tag matches, exact matches come before
+    /// prefix matches, which come before substring matches. Nodes with the same kind of match are
+    /// sorted by the length of their names, so the closest matches come first.
+    pub fn search_with_relevance(&self, query: &str) -> Vec<(Handle<Node>, SearchMatch)> {
+        let query = query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results = self
+            .entries
+            .iter()
+            .filter_map(|(handle, entry)| {
+                SearchMatch::classify(
+                    &entry.name,
+                    &query,
+                    SearchMatch::ExactName,
+                    SearchMatch::NamePrefix,
+                    SearchMatch::NameSubstring,
+                )
+                .or_else(|| {
+                    SearchMatch::classify(
+                        &entry.tag,
+                        &query,
+                        SearchMatch::ExactTag,
+                        SearchMatch::TagPrefix,
+                        SearchMatch::TagSubstring,
+                    )
+                })
+                .map(|kind| (*handle, kind, entry))
+            })
+            .collect::<Vec<_>>();
+
+        results.sort_by(|(a_handle, a_kind, a), (b_handle, b_kind, b)| {
+            a_kind
+                .cmp(b_kind)
+                .then(a.name.len().cmp(&b.name.len()))
+                .then(a.name.cmp(&b.name))
+                .then(a_handle.cmp(b_handle))
+        });
+
+        results
+            .into_iter()
+            .map(|(handle, kind, _)| (handle, kind))
+            .collect()
+    }
+
+    /// The same as [`Self::search_with_relevance`], but returns only handles.
+    pub fn search(&self, query: &str) -> Vec<Handle<Node>> {
+        self.search_with_relevance(query)
+            .into_iter()
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        graph::SceneGraph,
+        scene::{
+            base::BaseBuilder,
+            camera::CameraBuilder,
+            graph::{
+                search::{GraphSearchIndex, SearchMatch},
+                Graph,
+            },
+            pivot::PivotBuilder,
+        },
+    };
+
+    #[test]
+    fn test_search() {
+        // The same hierarchy as the scene of the scene gizmo.
+        let mut graph = Graph::new();
+        let camera;
+        let hinge;
+        let pivot = PivotBuilder::new(BaseBuilder::new().with_name("CameraPivot").with_child({
+            hinge = PivotBuilder::new(BaseBuilder::new().with_name("CameraHinge").with_child({
+                camera =
+                    CameraBuilder::new(BaseBuilder::new().with_name("Camera")).build(&mut graph);
+                camera
+            }))
+            .build(&mut graph);
+            hinge
+        }))
+        .build(&mut graph);
+        for name in ["PosX", "NegX", "PosY", "NegY", "PosZ", "NegZ", "Center"] {
+            PivotBuilder::new(BaseBuilder::new().with_name(name)).build(&mut graph);
+        }
+
+        let mut index = GraphSearchIndex::new(&mut graph);
+        assert_eq!(index.len(), graph.node_count() as usize);
+
+        let results = index.search("cam");
+        assert_eq!(
+            results,
+            vec![camera.to_base(), hinge.to_base(), pivot.to_base()]
+        );
+        assert_eq!(index.search("HINGE"), vec![hinge.to_base()]);
+        assert_eq!(
+            index.search_with_relevance("camera")[0],
+            (camera.to_base(), SearchMatch::ExactName)
+        );
+        assert_eq!(index.search("x").len(), 2);
+        assert!(index.search("").is_empty());
+
+        // The index is kept in sync with the graph.
+        let tagged = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Light")
+                .with_tag("Camera Light".to_string()),
+        )
+        .build(&mut graph);
+        graph.remove_node(camera);
+        index.update(&graph);
+        assert_eq!(
+            index.search_with_relevance("cam").last(),
+            Some(&(tagged.to_base(), SearchMatch::TagPrefix))
+        );
+        assert!(!index.search("cam").contains(&camera.to_base()));
+
+        graph[tagged].set_name("Camera Light");
+        index.reindex(&graph, tagged.to_base());
+        assert_eq!(index.search("camera l"), vec![tagged.to_base()]);
+    }
+}
@@ -0,0 +1,475 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Text-based (RON) representation of the scene graph structure. It is meant to be hand-edited or
+//! generated by scripts, see [`TextScene`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector2, Vector3},
+        pool::Handle,
+        reflect::prelude::*,
+        uuid::Uuid,
+    },
+    graph::SceneGraph,
+    scene::{
+        graph::Graph,
+        node::{constructor::NodeConstructorContainer, Node},
+        transform::Transform,
+    },
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::TypeId,
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// A set of possible error variants that can occur during text scene import/export.
+#[derive(Debug)]
+pub enum TextSceneError {
+    /// A parsing error has occurred.
+    ParseError(ron::error::SpannedError),
+
+    /// A serialization error has occurred.
+    SerializationError(ron::Error),
+
+    /// There's no node constructor for the given node type.
+    UnknownNodeType {
+        /// Name of the node.
+        name: String,
+        /// Type uuid of the node.
+        type_uuid: Uuid,
+    },
+}
+
+impl Display for TextSceneError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextSceneError::ParseError(v) => {
+                write!(f, "A parsing error has occurred {v:?}")
+            }
+            TextSceneError::SerializationError(v) => {
+                write!(f, "A serialization error has occurred {v:?}")
+            }
+            TextSceneError::UnknownNodeType { name, type_uuid } => {
+                write!(f, "Node {name} has unknown type {type_uuid}!")
+            }
+        }
+    }
+}
+
+impl From<ron::error::SpannedError> for TextSceneError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseError(e)
+    }
+}
+
+impl From<ron::Error> for TextSceneError {
+    fn from(e: ron::Error) -> Self {
+        Self::SerializationError(e)
+    }
+}
+
+/// A value of a reflected property, that could be represented in text form.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum TextValue {
+    Bool(bool),
+    I32(i32),
+    U32(u32),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Vector2([f32; 2]),
+    Vector3([f32; 3]),
+    /// Quaternion components in `[i, j, k, w]` order.
+    Quaternion([f32; 4]),
+}
+
+impl TextValue {
+    /// Tries to convert a reflected value to its text representation. Returns `None` if the type
+    /// of the value is not supported.
+    pub fn from_reflect(value: &dyn Reflect) -> Option<Self> {
+        let mut result = None;
+        value.as_any(&mut |any| {
+            result = if let Some(v) = any.downcast_ref::<bool>() {
+                Some(Self::Bool(*v))
+            } else if let Some(v) = any.downcast_ref::<i32>() {
+                Some(Self::I32(*v))
+            } else if let Some(v) = any.downcast_ref::<u32>() {
+                Some(Self::U32(*v))
+            } else if let Some(v) = any.downcast_ref::<f32>() {
+                Some(Self::F32(*v))
+            } else if let Some(v) = any.downcast_ref::<f64>() {
+                Some(Self::F64(*v))
+            } else if let Some(v) = any.downcast_ref::<String>() {
+                Some(Self::String(v.clone()))
+            } else if let Some(v) = any.downcast_ref::<Vector2<f32>>() {
+                Some(Self::Vector2((*v).into()))
+            } else if let Some(v) = any.downcast_ref::<Vector3<f32>>() {
+                Some(Self::Vector3((*v).into()))
+            } else {
+                any.downcast_ref::<UnitQuaternion<f32>>()
+                    .map(|v| Self::Quaternion(v.coords.into()))
+            }
+        });
+        result
+    }
+
+    /// Converts the value to a boxed reflected value, that could be used to set a property.
+    pub fn into_reflect(self) -> Box<dyn Reflect> {
+        match self {
+            TextValue::Bool(v) => Box::new(v),
+            TextValue::I32(v) => Box::new(v),
+            TextValue::U32(v) => Box::new(v),
+            TextValue::F32(v) => Box::new(v),
+            TextValue::F64(v) => Box::new(v),
+            TextValue::String(v) => Box::new(v),
+            TextValue::Vector2(v) => Box::new(Vector2::from(v)),
+            TextValue::Vector3(v) => Box::new(Vector3::from(v)),
+            TextValue::Quaternion([i, j, k, w]) => {
+                Box::new(UnitQuaternion::from_quaternion(Quaternion::new(w, i, j, k)))
+            }
+        }
+    }
+}
+
+/// Text representation of a scene node.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TextNode {
+    /// Name of the node.
+    pub name: String,
+    /// Name of the type of the node. It is informational only, the node type is defined by
+    /// [`Self::type_uuid`].
+    #[serde(default)]
+    pub kind: String,
+    /// Type uuid of the node, it is used to create a node of the right type on import.
+    pub type_uuid: Uuid,
+    /// Local position of the node.
+    pub position: [f32; 3],
+    /// Local rotation of the node, Euler angles in degrees.
+    pub rotation: [f32; 3],
+    /// Local scale of the node.
+    pub scale: [f32; 3],
+    /// Other reflected properties of the node, the key is a property path.
+    #[serde(default)]
+    pub properties: BTreeMap<String, TextValue>,
+    /// Children nodes.
+    #[serde(default)]
+    pub children: Vec<TextNode>,
+}
+
+impl TextNode {
+    /// Creates text representation of the given node and all its descendants.
+    pub fn from_node(graph: &Graph, handle: Handle<Node>) -> Self {
+        let node = &graph[handle];
+        let transform = node.local_transform();
+        let (x, y, z) = transform.rotation().euler_angles();
+
+        Self {
+            name: node.name().to_string(),
+            kind: Reflect::type_name(node).to_string(),
+            type_uuid: node.id(),
+            position: (**transform.position()).into(),
+            rotation: [x.to_degrees(), y.to_degrees(), z.to_degrees()],
+            scale: (**transform.scale()).into(),
+            properties: collect_properties(node, false),
+            children: node
+                .children()
+                .iter()
+                .map(|child| Self::from_node(graph, *child))
+                .collect(),
+        }
+    }
+
+    /// Creates a node (and all its descendants) from the text representation and attaches it to
+    /// the given parent.
+    pub fn instantiate(
+        &self,
+        constructors: &NodeConstructorContainer,
+        graph: &mut Graph,
+        parent: Handle<Node>,
+    ) -> Result<Handle<Node>, TextSceneError> {
+        let mut node = constructors.try_create(&self.type_uuid).ok_or_else(|| {
+            TextSceneError::UnknownNodeType {
+                name: self.name.clone(),
+                type_uuid: self.type_uuid,
+            }
+        })?;
+
+        for (path, value) in self.properties.iter() {
+            // Missing or mismatched properties are ignored, it makes hand-editing less strict.
+            (&mut node as &mut dyn Reflect).set_field_by_path(
+                path,
+                value.clone().into_reflect(),
+                &mut |_| {},
+            );
+        }
+
+        node.set_name(&self.name);
+        let [x, y, z] = self.rotation;
+        node.local_transform_mut()
+            .set_position(Vector3::from(self.position))
+            .set_rotation(UnitQuaternion::from_euler_angles(
+                x.to_radians(),
+                y.to_radians(),
+                z.to_radians(),
+            ))
+            .set_scale(Vector3::from(self.scale));
+
+        let handle = graph.add_node(node);
+        graph.link_nodes(handle, parent);
+
+        for child in self.children.iter() {
+            child.instantiate(constructors, graph, handle)?;
+        }
+
+        Ok(handle)
+    }
+}
+
+/// Collects reflected properties of the node that could be represented in text form. Local
+/// position, rotation and scale are skipped unless `include_transform` is set, since the text
+/// representation stores them explicitly.
+pub(crate) fn collect_properties(
+    node: &Node,
+    include_transform: bool,
+) -> BTreeMap<String, TextValue> {
+    let node = node as &dyn Reflect;
+    let properties = node.properties(&[]);
+
+    let transform_paths = properties
+        .iter()
+        .filter(|property| property.type_id == TypeId::of::<Transform>())
+        .map(|property| property.path.clone())
+        .collect::<Vec<_>>();
+    let is_explicit = |path: &str| {
+        !include_transform
+            && transform_paths.iter().any(|transform| {
+                path.strip_prefix(transform.as_str()).is_some_and(|rest| {
+                    matches!(rest, ".local_position" | ".local_rotation" | ".local_scale")
+                })
+            })
+    };
+
+    let mut map = BTreeMap::new();
+    for property in properties {
+        // Items of collections cannot be restored by their paths, so they're skipped too.
+        if property.read_only || property.path.contains('[') || is_explicit(&property.path) {
+            continue;
+        }
+
+        node.resolve_path(&property.path, &mut |result| {
+            if let Some(value) = result.ok().and_then(TextValue::from_reflect) {
+                map.insert(property.path.clone(), value);
+            }
+        });
+    }
+    map
+}
+
+/// Text representation of the scene graph structure: node names, types, transforms, hierarchy and
+/// reflected properties of simple types (numbers, strings, vectors, etc.). Properties that cannot
+/// be represented in text form (resources, handles, GPU data, etc.) are omitted, which means that
+/// a scene restored from the text form will contain default values for such properties.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::scene::{
+/// #     graph::{text::TextScene, Graph},
+/// #     node::constructor::new_node_constructor_container,
+/// # };
+/// fn copy_structure(graph: &Graph) -> Graph {
+///     let text = TextScene::from_graph(graph).to_ron().unwrap();
+///
+///     let mut copy = Graph::new();
+///     TextScene::from_ron(&text)
+///         .unwrap()
+///         .instantiate(&new_node_constructor_container(), &mut copy)
+///         .unwrap();
+///     copy
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TextScene {
+    /// Children nodes of the graph root.
+    pub nodes: Vec<TextNode>,
+}
+
+impl TextScene {
+    /// Creates text representation of the graph. The root node of the graph is not included.
+    pub fn from_graph(graph: &Graph) -> Self {
+        Self {
+            nodes: graph[graph.get_root()]
+                .children()
+                .iter()
+                .map(|child| TextNode::from_node(graph, *child))
+                .collect(),
+        }
+    }
+
+    /// Serializes the scene to a RON string.
+    pub fn to_ron(&self) -> Result<String, TextSceneError> {
+        Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+    }
+
+    /// Deserializes the scene from a RON string.
+    pub fn from_ron(str: &str) -> Result<Self, TextSceneError> {
+        Ok(ron::de::from_str(str)?)
+    }
+
+    /// Instantiates all the nodes in the given graph, they will be attached to the root of the
+    /// graph. Returns handles of the top-level nodes.
+    pub fn instantiate(
+        &self,
+        constructors: &NodeConstructorContainer,
+        graph: &mut Graph,
+    ) -> Result<Vec<Handle<Node>>, TextSceneError> {
+        let root = graph.get_root();
+        self.nodes
+            .iter()
+            .map(|node| node.instantiate(constructors, graph, root))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+        },
+        graph::SceneGraph,
+        scene::{
+            base::BaseBuilder,
+            camera::{Camera, CameraBuilder, Projection},
+            graph::{text::TextScene, Graph},
+            node::{constructor::new_node_constructor_container, Node},
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn assert_same_hierarchy(a: &Graph, a_node: Handle<Node>, b: &Graph, b_node: Handle<Node>) {
+        let a_node = &a[a_node];
+        let b_node = &b[b_node];
+        assert_eq!(a_node.name(), b_node.name());
+        assert_eq!(a_node.id(), b_node.id());
+
+        let a_transform = a_node.local_transform();
+        let b_transform = b_node.local_transform();
+        assert!(
+            a_transform
+                .position()
+                .metric_distance(b_transform.position())
+                < 1e-5
+        );
+        assert!(a_transform.rotation().angle_to(b_transform.rotation()) < 1e-4);
+        assert!(a_transform.scale().metric_distance(b_transform.scale()) < 1e-5);
+
+        assert_eq!(a_node.children().len(), b_node.children().len());
+        for (a_child, b_child) in a_node.children().iter().zip(b_node.children()) {
+            assert_same_hierarchy(a, *a_child, b, *b_child);
+        }
+    }
+
+    #[test]
+    fn test_text_scene_round_trip() {
+        // The same hierarchy as the scene of the scene gizmo.
+        let mut graph = Graph::new();
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("CameraPivot")
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(UnitQuaternion::from_euler_angles(0.0, 0.5, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    PivotBuilder::new(
+                        BaseBuilder::new()
+                            .with_name("CameraHinge")
+                            .with_local_transform(
+                                TransformBuilder::new()
+                                    .with_local_rotation(UnitQuaternion::from_euler_angles(
+                                        0.3, 0.0, 0.0,
+                                    ))
+                                    .build(),
+                            )
+                            .with_child(
+                                CameraBuilder::new(
+                                    BaseBuilder::new().with_name("Camera").with_local_transform(
+                                        TransformBuilder::new()
+                                            .with_local_position(Vector3::new(0.0, 0.0, -3.0))
+                                            .build(),
+                                    ),
+                                )
+                                .with_fov(1.2)
+                                .build(&mut graph),
+                            ),
+                    )
+                    .build(&mut graph),
+                ),
+        )
+        .build(&mut graph);
+        for (name, position) in [
+            ("PosX", Vector3::x()),
+            ("NegX", -Vector3::x()),
+            ("PosY", Vector3::y()),
+            ("NegY", -Vector3::y()),
+            ("PosZ", Vector3::z()),
+            ("NegZ", -Vector3::z()),
+            ("Center", Vector3::default()),
+        ] {
+            PivotBuilder::new(
+                BaseBuilder::new().with_name(name).with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .with_local_scale(Vector3::repeat(0.5))
+                        .build(),
+                ),
+            )
+            .build(&mut graph);
+        }
+
+        let text = TextScene::from_graph(&graph).to_ron().unwrap();
+
+        let mut copy = Graph::new();
+        let nodes = TextScene::from_ron(&text)
+            .unwrap()
+            .instantiate(&new_node_constructor_container(), &mut copy)
+            .unwrap();
+        assert_eq!(nodes.len(), 8);
+
+        assert_same_hierarchy(&graph, graph.get_root(), &copy, copy.get_root());
+
+        // Reflected properties are restored as well.
+        let (camera, _) = copy.find_by_name_from_root("Camera").unwrap();
+        let Projection::Perspective(ref perspective) =
+            copy[camera].cast::<Camera>().unwrap().projection()
+        else {
+            unreachable!()
+        };
+        assert_eq!(perspective.fov, 1.2);
+    }
+}
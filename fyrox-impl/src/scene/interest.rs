@@ -0,0 +1,363 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Interest management for networked games - selection of nodes, that should be replicated to
+//! a particular client. See [`InterestManager`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector3, pool::Handle},
+    fxhash::{FxHashMap, FxHashSet},
+    scene::{
+        accel::{Entry, Octree},
+        graph::Graph,
+        node::Node,
+    },
+};
+
+/// Replication parameters of a node.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InterestEntry {
+    /// Priority of the node. Nodes with higher priority are replicated more often, when the
+    /// bandwidth budget is not enough to replicate every relevant node at once.
+    pub priority: f32,
+    /// Approximate size of the replicated state of the node, in bytes.
+    pub size: usize,
+}
+
+impl Default for InterestEntry {
+    fn default() -> Self {
+        Self {
+            priority: 1.0,
+            size: 32,
+        }
+    }
+}
+
+/// Area of interest of a client.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClientView {
+    /// Position of the client (usually a position of its camera or controlled character).
+    pub position: Vector3<f32>,
+    /// Radius of the area of interest. Nodes, that are farther away, are not replicated.
+    pub radius: f32,
+}
+
+/// An event, that is produced when a node enters or leaves the area of interest of a client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InterestEvent {
+    /// A node has entered the area of interest, it should be spawned on the client.
+    Spawn(Handle<Node>),
+    /// A node has left the area of interest, it should be removed from the client.
+    Despawn(Handle<Node>),
+}
+
+/// Per-client state of interest management.
+#[derive(Clone, Debug, Default)]
+pub struct ClientInterest {
+    relevant: FxHashSet<Handle<Node>>,
+    accumulated_priority: FxHashMap<Handle<Node>, f32>,
+    replication_set: Vec<Handle<Node>>,
+    events: Vec<InterestEvent>,
+}
+
+impl ClientInterest {
+    /// Returns `true` if the given node is in the area of interest of the client.
+    pub fn is_relevant(&self, node: Handle<Node>) -> bool {
+        self.relevant.contains(&node)
+    }
+
+    /// Returns an iterator over every node in the area of interest of the client.
+    pub fn relevant(&self) -> impl Iterator<Item = Handle<Node>> + '_ {
+        self.relevant.iter().copied()
+    }
+
+    /// Returns the nodes, that should be replicated to the client at this tick, sorted by their
+    /// priority. It is a subset of relevant nodes, that fits in the bandwidth budget.
+    pub fn replication_set(&self) -> &[Handle<Node>] {
+        &self.replication_set
+    }
+
+    /// Returns spawn/despawn events produced by the last update.
+    pub fn events(&self) -> &[InterestEvent] {
+        &self.events
+    }
+}
+
+/// Interest manager selects nodes, that should be replicated to clients. A node is relevant to a
+/// client, if its world bounding box intersects the area of interest of the client. Relevant nodes
+/// are found using an octree, that should be rebuilt every network tick using [`Self::rebuild`].
+///
+/// The amount of data sent to a client per tick is limited by the bandwidth budget. When the
+/// relevant nodes do not fit in the budget, they are replicated in the order of their priority,
+/// scaled down by their distance to the client. Priorities of the nodes, that were skipped, are
+/// accumulated, so every relevant node will be replicated eventually.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     scene::{
+/// #         graph::Graph,
+/// #         interest::{ClientInterest, ClientView, InterestEvent, InterestManager},
+/// #     },
+/// # };
+/// fn network_tick(
+///     graph: &Graph,
+///     manager: &mut InterestManager,
+///     clients: &mut [(ClientView, ClientInterest)],
+/// ) {
+///     manager.rebuild(graph);
+///
+///     for (view, interest) in clients.iter_mut() {
+///         manager.update_client(view, interest);
+///
+///         for event in interest.events() {
+///             match event {
+///                 InterestEvent::Spawn(_node) => { /* Send spawn message. */ }
+///                 InterestEvent::Despawn(_node) => { /* Send despawn message. */ }
+///             }
+///         }
+///
+///         for _node in interest.replication_set() {
+///             // Send the state of the node.
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct InterestManager {
+    entries: FxHashMap<Handle<Node>, InterestEntry>,
+    octree: Octree,
+    bandwidth_budget: usize,
+    query_buffer: Vec<Entry>,
+}
+
+impl InterestManager {
+    /// Creates new interest manager with the given bandwidth budget (in bytes per tick).
+    pub fn new(bandwidth_budget: usize) -> Self {
+        Self {
+            entries: Default::default(),
+            octree: Default::default(),
+            bandwidth_budget,
+            query_buffer: Default::default(),
+        }
+    }
+
+    /// Sets new bandwidth budget (in bytes per tick).
+    pub fn set_bandwidth_budget(&mut self, bandwidth_budget: usize) {
+        self.bandwidth_budget = bandwidth_budget;
+    }
+
+    /// Returns current bandwidth budget (in bytes per tick).
+    pub fn bandwidth_budget(&self) -> usize {
+        self.bandwidth_budget
+    }
+
+    /// Marks the given node as replicated. Only registered nodes could be selected for
+    /// replication.
+    pub fn register(&mut self, node: Handle<Node>, entry: InterestEntry) {
+        self.entries.insert(node, entry);
+    }
+
+    /// Removes the given node from the set of replicated nodes. The node will be despawned on
+    /// every client at the next update.
+    pub fn unregister(&mut self, node: Handle<Node>) -> Option<InterestEntry> {
+        self.entries.remove(&node)
+    }
+
+    /// Returns replication parameters of the given node.
+    pub fn entry(&self, node: Handle<Node>) -> Option<&InterestEntry> {
+        self.entries.get(&node)
+    }
+
+    /// Returns a mutable reference to replication parameters of the given node.
+    pub fn entry_mut(&mut self, node: Handle<Node>) -> Option<&mut InterestEntry> {
+        self.entries.get_mut(&node)
+    }
+
+    /// Rebuilds the spatial acceleration structure using the current state of the graph. Should
+    /// be called once per network tick, after the graph was updated.
+    pub fn rebuild(&mut self, graph: &Graph) {
+        self.octree = Octree::new(graph, 32);
+    }
+
+    /// Updates the area of interest of a client and selects nodes, that should be replicated to
+    /// it at this tick. See [`ClientInterest`] docs for more info.
+    pub fn update_client(&mut self, view: &ClientView, client: &mut ClientInterest) {
+        self.octree
+            .sphere_query(view.position, view.radius, &mut self.query_buffer);
+
+        let mut relevant = FxHashSet::default();
+        let mut candidates = Vec::new();
+        for octree_entry in self.query_buffer.iter() {
+            let node = octree_entry.node();
+            let aabb = octree_entry.world_aabb();
+            let Some(entry) = self.entries.get(&node) else {
+                continue;
+            };
+            if !aabb.is_intersects_sphere(view.position, view.radius) || !relevant.insert(node) {
+                continue;
+            }
+            let distance = aabb.center().metric_distance(&view.position);
+            let accumulated = client.accumulated_priority.entry(node).or_default();
+            *accumulated += entry.priority / (1.0 + distance);
+            candidates.push((node, *accumulated, entry.size));
+        }
+
+        client.events.clear();
+        for &node in client.relevant.iter() {
+            if !relevant.contains(&node) {
+                client.events.push(InterestEvent::Despawn(node));
+                client.accumulated_priority.remove(&node);
+            }
+        }
+        for &node in relevant.iter() {
+            if !client.relevant.contains(&node) {
+                client.events.push(InterestEvent::Spawn(node));
+            }
+        }
+        client.relevant = relevant;
+
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        client.replication_set.clear();
+        let mut used = 0;
+        for (node, _, size) in candidates {
+            if used + size <= self.bandwidth_budget {
+                used += size;
+                client.replication_set.push(node);
+                client.accumulated_priority.insert(node, 0.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            interest::{ClientInterest, ClientView, InterestEntry, InterestEvent, InterestManager},
+            node::Node,
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn add_pivot(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .build(graph)
+        .to_base()
+    }
+
+    fn tick(graph: &mut Graph, manager: &mut InterestManager) {
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        manager.rebuild(graph);
+    }
+
+    #[test]
+    fn test_interest_radius() {
+        let mut graph = Graph::new();
+        let near = add_pivot(&mut graph, Vector3::new(3.0, 0.0, 0.0));
+        let far = add_pivot(&mut graph, Vector3::new(100.0, 0.0, 0.0));
+        let unregistered = add_pivot(&mut graph, Vector3::new(1.0, 0.0, 0.0));
+
+        let mut manager = InterestManager::new(1024);
+        manager.register(near, Default::default());
+        manager.register(far, Default::default());
+
+        let view = ClientView {
+            position: Vector3::default(),
+            radius: 10.0,
+        };
+        let mut client = ClientInterest::default();
+
+        tick(&mut graph, &mut manager);
+        manager.update_client(&view, &mut client);
+        assert_eq!(client.replication_set(), &[near]);
+        assert_eq!(client.events(), &[InterestEvent::Spawn(near)]);
+        assert!(!client.is_relevant(far));
+        assert!(!client.is_relevant(unregistered));
+
+        // Nodes crossing the border of the area of interest.
+        graph[near].set_position(Vector3::new(50.0, 0.0, 0.0));
+        graph[far].set_position(Vector3::new(0.0, 5.0, 0.0));
+        tick(&mut graph, &mut manager);
+        manager.update_client(&view, &mut client);
+        assert_eq!(client.replication_set(), &[far]);
+        assert_eq!(client.events().len(), 2);
+        assert!(client.events().contains(&InterestEvent::Spawn(far)));
+        assert!(client.events().contains(&InterestEvent::Despawn(near)));
+
+        // No events when nothing changes.
+        manager.update_client(&view, &mut client);
+        assert!(client.events().is_empty());
+    }
+
+    #[test]
+    fn test_bandwidth_budget() {
+        let mut graph = Graph::new();
+        let important = add_pivot(&mut graph, Vector3::new(4.0, 0.0, 0.0));
+        let other = add_pivot(&mut graph, Vector3::new(-4.0, 0.0, 0.0));
+
+        let entry = InterestEntry {
+            priority: 1.0,
+            size: 100,
+        };
+        let mut manager = InterestManager::new(100);
+        manager.register(
+            important,
+            InterestEntry {
+                priority: 3.0,
+                ..entry
+            },
+        );
+        manager.register(other, entry);
+
+        let view = ClientView {
+            position: Vector3::default(),
+            radius: 10.0,
+        };
+        let mut client = ClientInterest::default();
+
+        tick(&mut graph, &mut manager);
+        manager.update_client(&view, &mut client);
+        assert_eq!(client.replication_set(), &[important]);
+        assert!(client.is_relevant(other));
+
+        // Priority of the skipped node is accumulated, so it is replicated eventually.
+        let mut replicated = false;
+        for _ in 0..4 {
+            manager.update_client(&view, &mut client);
+            replicated |= client.replication_set() == [other];
+        }
+        assert!(replicated);
+    }
+}
@@ -771,3 +771,93 @@ impl JointBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            joint::{JointBuilder, JointParams, RevoluteJoint},
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_revolute_motor_holds_target_angle() {
+        let mut graph = Graph::new();
+        *graph.physics.gravity = Vector3::default();
+
+        let base = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -2.0, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::ball(0.5))
+                        .build(&mut graph),
+                ),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        let door = RigidBodyBuilder::new(
+            BaseBuilder::new().with_child(
+                ColliderBuilder::new(BaseBuilder::new())
+                    .with_shape(ColliderShape::cuboid(0.5, 0.5, 0.5))
+                    .build(&mut graph),
+            ),
+        )
+        .with_can_sleep(false)
+        .build(&mut graph);
+
+        // The hinge rotates around X axis and its pivot is at the center of the door.
+        let joint = JointBuilder::new(BaseBuilder::new())
+            .with_params(JointParams::RevoluteJoint(RevoluteJoint::default()))
+            .with_body1(base)
+            .with_body2(door)
+            .with_contacts_enabled(false)
+            .build(&mut graph);
+
+        let target_angle = 1.0;
+        graph[joint]
+            .set_motor_target_angle_as_revolute(target_angle, 500.0, 100.0, 50.0)
+            .unwrap();
+
+        let door_angle = |graph: &Graph| {
+            graph[door]
+                .local_transform()
+                .rotation()
+                .scaled_axis()
+                .x
+                .abs()
+        };
+
+        let dt = 1.0 / 60.0;
+        for _ in 0..180 {
+            graph.update(Vector2::new(1.0, 1.0), dt, Default::default());
+        }
+        assert!((door_angle(&graph) - target_angle).abs() < 0.01);
+
+        // A small opposing torque must not be able to move the door away from the target.
+        let angle_sign = graph[door]
+            .local_transform()
+            .rotation()
+            .scaled_axis()
+            .x
+            .signum();
+        for _ in 0..180 {
+            graph[door].apply_torque(Vector3::new(-angle_sign * 10.0, 0.0, 0.0));
+            graph.update(Vector2::new(1.0, 1.0), dt, Default::default());
+        }
+        // A motor acts like a spring, so the error is about `torque / stiffness`.
+        assert!((door_angle(&graph) - target_angle).abs() < 0.05);
+        assert!(graph[door].ang_vel().norm() < 0.01);
+    }
+}
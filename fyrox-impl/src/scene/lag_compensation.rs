@@ -0,0 +1,296 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Server-side lag compensation for networked games. See [`LagCompensation`] docs for more info.
+
+use crate::{
+    core::pool::Handle,
+    scene::{
+        graph::{
+            physics::{PhysicsSnapshot, PhysicsWorld},
+            Graph,
+        },
+        node::Node,
+    },
+};
+use std::collections::VecDeque;
+
+/// Lag compensation stores the history of the physics world states, so the server can rewind rigid
+/// bodies to the moment when a client performed some action (for example, a shot) and validate it
+/// against the world as the client saw it. Rewinding happens temporarily, the present state of the
+/// bodies is restored right after the validation.
+///
+/// States between recorded snapshots are interpolated, timestamps outside of the history are
+/// clamped to the oldest (or the newest) snapshot.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::algebra::{Point3, Vector3},
+/// #     scene::{
+/// #         graph::{physics::RayCastOptions, Graph},
+/// #         lag_compensation::LagCompensation,
+/// #     },
+/// # };
+/// fn validate_shot(
+///     graph: &mut Graph,
+///     history: &LagCompensation,
+///     shot_time: f64,
+///     origin: Point3<f32>,
+///     direction: Vector3<f32>,
+/// ) -> bool {
+///     history
+///         .rewind(graph, shot_time, |graph| {
+///             let mut intersections = Vec::new();
+///             graph.physics.cast_ray(
+///                 RayCastOptions {
+///                     ray_origin: origin,
+///                     ray_direction: direction,
+///                     max_len: 100.0,
+///                     groups: Default::default(),
+///                     sort_results: true,
+///                 },
+///                 &mut intersections,
+///             );
+///             !intersections.is_empty()
+///         })
+///         .unwrap_or_default()
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LagCompensation {
+    history: VecDeque<(f64, PhysicsSnapshot)>,
+    capacity: usize,
+}
+
+impl LagCompensation {
+    /// Creates new lag compensation history, that holds up to `capacity` snapshots. The capacity
+    /// should be enough to cover the maximum allowed latency of clients.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            history: Default::default(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Stores the current state of the physics world with the given timestamp (in seconds). Should
+    /// be called after each simulation step. Timestamps must increase, snapshots with the same or
+    /// newer timestamps are replaced.
+    pub fn record(&mut self, time: f64, physics: &PhysicsWorld) {
+        while self
+            .history
+            .back()
+            .is_some_and(|(last_time, _)| *last_time >= time)
+        {
+            self.history.pop_back();
+        }
+
+        if self.history.len() >= self.capacity {
+            self.history.pop_front();
+        }
+
+        self.history.push_back((time, physics.snapshot()));
+    }
+
+    /// Returns the timestamp of the oldest snapshot in the history.
+    pub fn oldest_time(&self) -> Option<f64> {
+        self.history.front().map(|(time, _)| *time)
+    }
+
+    /// Returns the timestamp of the newest snapshot in the history.
+    pub fn newest_time(&self) -> Option<f64> {
+        self.history.back().map(|(time, _)| *time)
+    }
+
+    /// Removes every snapshot from the history.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Returns the state of the physics world at the given time. The state is interpolated between
+    /// the two nearest snapshots, the time is clamped to the recorded history. Returns [`None`] if
+    /// the history is empty.
+    pub fn snapshot_at(&self, time: f64) -> Option<PhysicsSnapshot> {
+        let next = self.history.partition_point(|(t, _)| *t <= time);
+        if next == 0 {
+            return self.history.front().map(|(_, snapshot)| snapshot.clone());
+        }
+        if next == self.history.len() {
+            return self.history.back().map(|(_, snapshot)| snapshot.clone());
+        }
+
+        let (prev_time, prev) = &self.history[next - 1];
+        let (next_time, next) = &self.history[next];
+        let t = ((time - prev_time) / (next_time - prev_time)) as f32;
+        Some(prev.interpolate(next, t))
+    }
+
+    /// Rewinds every recorded rigid body to the given time, calls the given closure and restores
+    /// the present state of the bodies. Scene queries (ray casts, etc.) made in the closure will
+    /// see the rewound state. Returns [`None`] if the history is empty.
+    ///
+    /// Restoring the present state resets forces applied to the bodies since the last simulation
+    /// step, so it is better to validate actions right after the step.
+    pub fn rewind<R>(
+        &self,
+        graph: &mut Graph,
+        time: f64,
+        func: impl FnOnce(&mut Graph) -> R,
+    ) -> Option<R> {
+        let past = self.snapshot_at(time)?;
+        Some(Self::rewind_to(graph, past, func))
+    }
+
+    /// The same as [`Self::rewind`], but rewinds only the given bodies. Other bodies stay in their
+    /// present state.
+    pub fn rewind_bodies<R>(
+        &self,
+        graph: &mut Graph,
+        time: f64,
+        bodies: &[Handle<Node>],
+        func: impl FnOnce(&mut Graph) -> R,
+    ) -> Option<R> {
+        let mut past = self.snapshot_at(time)?;
+        past.bodies.retain(|handle, _| bodies.contains(handle));
+        Some(Self::rewind_to(graph, past, func))
+    }
+
+    fn rewind_to<R>(
+        graph: &mut Graph,
+        past: PhysicsSnapshot,
+        func: impl FnOnce(&mut Graph) -> R,
+    ) -> R {
+        let mut present = graph.physics.snapshot();
+        present
+            .bodies
+            .retain(|handle, _| past.bodies.contains_key(handle));
+
+        graph.restore_physics(&past);
+        graph.physics.update_scene_queries();
+
+        let result = func(graph);
+
+        graph.restore_physics(&present);
+        graph.physics.update_scene_queries();
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Point3, Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::{
+                physics::{Intersection, RayCastOptions},
+                Graph,
+            },
+            lag_compensation::LagCompensation,
+            rigidbody::RigidBodyBuilder,
+        },
+    };
+
+    const DT: f32 = 1.0 / 60.0;
+    const SPEED: f32 = 6.0;
+
+    fn cast_down(graph: &Graph, x: f32) -> Vec<Intersection> {
+        let mut intersections = Vec::new();
+        graph.physics.cast_ray(
+            RayCastOptions {
+                ray_origin: Point3::new(x, 10.0, 0.0),
+                ray_direction: -Vector3::y(),
+                max_len: 20.0,
+                groups: Default::default(),
+                sort_results: true,
+            },
+            &mut intersections,
+        );
+        intersections
+    }
+
+    #[test]
+    fn test_rewind_ray_cast() {
+        let mut graph = Graph::new();
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut graph);
+        let body = RigidBodyBuilder::new(BaseBuilder::new().with_child(collider))
+            .with_gravity_scale(0.0)
+            .with_can_sleep(false)
+            .with_lin_vel(Vector3::new(SPEED, 0.0, 0.0))
+            .build(&mut graph);
+
+        let mut history = LagCompensation::new(120);
+        assert!(history.rewind(&mut graph, 0.0, |_| ()).is_none());
+
+        for tick in 1..=60 {
+            graph.update(Vector2::new(1.0, 1.0), DT, Default::default());
+            history.record(tick as f64 * DT as f64, &graph.physics);
+        }
+
+        let present_position = graph[body].global_position();
+        assert!((present_position.x - SPEED).abs() < 1.0e-3);
+
+        // The client saw the body in between the ticks 15 and 16.
+        let shot_time = 15.5 * DT;
+        let seen_x = SPEED * shot_time;
+        assert!(cast_down(&graph, seen_x).is_empty());
+
+        let hit = history
+            .rewind(&mut graph, shot_time as f64, |graph| {
+                assert!((graph[body].global_position().x - seen_x).abs() < 1.0e-3);
+                cast_down(graph, seen_x).first().cloned()
+            })
+            .unwrap()
+            .expect("The ray must hit the rewound body.");
+        assert_eq!(hit.collider, collider.to_base());
+        assert!(
+            hit.position
+                .coords
+                .metric_distance(&Vector3::new(seen_x, 0.5, 0.0))
+                < 1.0e-3
+        );
+
+        // The present state is restored.
+        assert!(
+            graph[body]
+                .global_position()
+                .metric_distance(&present_position)
+                < 1.0e-5
+        );
+        assert!(cast_down(&graph, seen_x).is_empty());
+        assert_eq!(cast_down(&graph, present_position.x).len(), 1);
+
+        // Timestamps older than the history are clamped.
+        let oldest_x = SPEED * DT;
+        history.rewind(&mut graph, -10.0, |graph| {
+            assert!((graph[body].global_position().x - oldest_x).abs() < 1.0e-3);
+        });
+
+        // Bodies, that are not in the list, are not rewound.
+        history.rewind_bodies(&mut graph, shot_time as f64, &[], |graph| {
+            assert!(cast_down(graph, seen_x).is_empty());
+        });
+    }
+}
@@ -0,0 +1,424 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Area light is a light source with non-zero emitting surface - a rectangle or a disc. It is used to
+//! simulate light panels, windows, screens and other large light sources. Unlike point or spot lights,
+//! area lights produce soft, size-dependent specular highlights.
+//!
+//! # Emitting surface
+//!
+//! Emitting surface of an area light lies in local XZ plane of the light and emits light in negative
+//! direction of local Y axis (the same as spot lights). Size of the surface is affected by scaling of
+//! the light node.
+//!
+//! # Shading model
+//!
+//! Area lights are shaded by integration of clamped cosine distributions over the polygon of the
+//! light (form factors). Diffuse part of the lighting uses the cosine around the surface normal,
+//! specular part uses the cosine around the reflection vector, stretched in tangent directions by GGX
+//! alpha (`roughness²`). The latter is a cheap approximation of GGX lobe (not a fitted linearly
+//! transformed cosine), it makes highlights of larger lights softer, but does not match the shape of
+//! highlights of punctual lights exactly. Discs are approximated by regular polygons with
+//! [`DISC_POLYGON_VERTEX_COUNT`] vertices.
+//!
+//! # Performance notes
+//!
+//! Area lights do not cast shadows and are more expensive to shade than point lights.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        light::{BaseLight, BaseLightBuilder},
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, SceneGraph};
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Amount of vertices of a regular polygon that is used to approximate disc area lights.
+pub const DISC_POLYGON_VERTEX_COUNT: usize = 8;
+
+/// Minimal size of an area light, sizes are clamped to this value.
+pub const MIN_AREA_LIGHT_SIZE: f32 = 0.001;
+
+/// Shape of the emitting surface of an area light.
+#[derive(Copy, Clone, Debug, PartialEq, Visit, Reflect, AsRefStr, EnumString, VariantNames)]
+pub enum AreaLightShape {
+    /// Rectangular emitting surface.
+    Rect {
+        /// Width of the rectangle (along local X axis).
+        #[reflect(min_value = 0.001, step = 0.1)]
+        width: f32,
+        /// Height of the rectangle (along local Z axis).
+        #[reflect(min_value = 0.001, step = 0.1)]
+        height: f32,
+    },
+    /// Disc-shaped emitting surface.
+    Disc {
+        /// Radius of the disc.
+        #[reflect(min_value = 0.001, step = 0.1)]
+        radius: f32,
+    },
+}
+
+uuid_provider!(AreaLightShape = "4b9ad2cb-3ff5-4d85-9d6b-d1b1fa2b8fa4");
+
+impl Default for AreaLightShape {
+    fn default() -> Self {
+        Self::Rect {
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+impl AreaLightShape {
+    /// Returns a copy of the shape with all the sizes clamped to be positive.
+    pub fn clamped(self) -> Self {
+        match self {
+            Self::Rect { width, height } => Self::Rect {
+                width: width.max(MIN_AREA_LIGHT_SIZE),
+                height: height.max(MIN_AREA_LIGHT_SIZE),
+            },
+            Self::Disc { radius } => Self::Disc {
+                radius: radius.max(MIN_AREA_LIGHT_SIZE),
+            },
+        }
+    }
+
+    /// Returns half-extents of the shape along local X and Z axes.
+    pub fn half_extents(&self) -> (f32, f32) {
+        match *self {
+            Self::Rect { width, height } => (width * 0.5, height * 0.5),
+            Self::Disc { radius } => (radius, radius),
+        }
+    }
+
+    /// Returns side and look vectors of the shape scaled by its half-extents. The vectors span the
+    /// emitting surface of the shape, when it is centered at the origin of the given basis.
+    pub fn scaled_axes(
+        &self,
+        side: Vector3<f32>,
+        look: Vector3<f32>,
+    ) -> (Vector3<f32>, Vector3<f32>) {
+        let (half_width, half_height) = self.half_extents();
+        (side.scale(half_width), look.scale(half_height))
+    }
+
+    /// Returns the area of the emitting surface (without scaling).
+    pub fn area(&self) -> f32 {
+        match *self {
+            Self::Rect { width, height } => width * height,
+            Self::Disc { radius } => std::f32::consts::PI * radius * radius,
+        }
+    }
+}
+
+/// See module docs.
+#[derive(Debug, Reflect, Clone, Visit, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct AreaLight {
+    #[component(include)]
+    base_light: BaseLight,
+
+    #[reflect(setter = "set_shape")]
+    shape: InheritableVariable<AreaLightShape>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_distance")]
+    distance: InheritableVariable<f32>,
+}
+
+impl Deref for AreaLight {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base_light.base
+    }
+}
+
+impl DerefMut for AreaLight {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base_light.base
+    }
+}
+
+impl Default for AreaLight {
+    fn default() -> Self {
+        Self {
+            base_light: Default::default(),
+            shape: InheritableVariable::new_modified(AreaLightShape::default()),
+            distance: InheritableVariable::new_modified(10.0),
+        }
+    }
+}
+
+impl TypeUuidProvider for AreaLight {
+    fn type_uuid() -> Uuid {
+        uuid!("a2b6e6ff-4b8e-4ac5-a3a5-3a1b9c5b3c52")
+    }
+}
+
+impl AreaLight {
+    /// Returns a reference to base light.
+    pub fn base_light_ref(&self) -> &BaseLight {
+        &self.base_light
+    }
+
+    /// Returns a reference to base light.
+    pub fn base_light_mut(&mut self) -> &mut BaseLight {
+        &mut self.base_light
+    }
+
+    /// Sets new shape of the emitting surface. Sizes of the shape are clamped to be positive.
+    pub fn set_shape(&mut self, shape: AreaLightShape) -> AreaLightShape {
+        self.shape.set_value_and_mark_modified(shape.clamped())
+    }
+
+    /// Returns current shape of the emitting surface.
+    pub fn shape(&self) -> AreaLightShape {
+        *self.shape
+    }
+
+    /// Sets maximum distance at which the light source affects surfaces.
+    pub fn set_distance(&mut self, distance: f32) -> f32 {
+        self.distance.set_value_and_mark_modified(distance.abs())
+    }
+
+    /// Returns maximum distance at which the light source affects surfaces.
+    pub fn distance(&self) -> f32 {
+        *self.distance
+    }
+
+    /// Returns world-space vertices of the emitting polygon. The renderer builds exactly the same
+    /// polygon to shade surfaces.
+    pub fn world_polygon(&self) -> Vec<Vector3<f32>> {
+        area_light_polygon(
+            &self.shape,
+            self.global_position(),
+            self.side_vector(),
+            self.look_vector(),
+        )
+    }
+}
+
+/// Builds world-space polygon of an area light with the given shape, position and basis vectors
+/// (possibly scaled). See [`AreaLight::world_polygon`] for more info.
+pub fn area_light_polygon(
+    shape: &AreaLightShape,
+    position: Vector3<f32>,
+    side: Vector3<f32>,
+    look: Vector3<f32>,
+) -> Vec<Vector3<f32>> {
+    let (s, l) = shape.scaled_axes(side, look);
+    match *shape {
+        AreaLightShape::Rect { .. } => vec![
+            position - s - l,
+            position - s + l,
+            position + s + l,
+            position + s - l,
+        ],
+        AreaLightShape::Disc { .. } => (0..DISC_POLYGON_VERTEX_COUNT)
+            .map(|i| {
+                let angle = -std::f32::consts::TAU * i as f32 / DISC_POLYGON_VERTEX_COUNT as f32;
+                position - s.scale(angle.cos()) - l.scale(angle.sin())
+            })
+            .collect(),
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for AreaLight {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Area Light", |_| {
+                AreaLightBuilder::new(BaseLightBuilder::new(
+                    BaseBuilder::new().with_name("AreaLight"),
+                ))
+                .build_node()
+                .into()
+            })
+            .with_group("Light")
+    }
+}
+
+impl NodeTrait for AreaLight {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::from_radius(self.distance())
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        // Discard scaling part, light emission distance does not affected by scaling.
+        self.local_bounding_box()
+            .transform(&self.global_transform_without_scaling())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        let polygon = self.world_polygon();
+        for (i, begin) in polygon.iter().enumerate() {
+            ctx.add_line(Line {
+                begin: *begin,
+                end: polygon[(i + 1) % polygon.len()],
+                color: Color::GREEN,
+            });
+        }
+
+        let position = self.global_position();
+        let emit_direction = -self
+            .up_vector()
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        ctx.add_line(Line {
+            begin: position,
+            end: position + emit_direction,
+            color: Color::GREEN,
+        });
+    }
+}
+
+/// Allows you to build area light in declarative manner.
+pub struct AreaLightBuilder {
+    base_light_builder: BaseLightBuilder,
+    shape: AreaLightShape,
+    distance: f32,
+}
+
+impl AreaLightBuilder {
+    /// Creates new builder instance.
+    pub fn new(base_light_builder: BaseLightBuilder) -> Self {
+        Self {
+            base_light_builder,
+            shape: Default::default(),
+            distance: 10.0,
+        }
+    }
+
+    /// Sets desired shape of the emitting surface.
+    pub fn with_shape(mut self, shape: AreaLightShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets desired maximum distance at which the light affects surfaces.
+    pub fn with_distance(mut self, distance: f32) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    /// Builds new instance of area light.
+    pub fn build_area_light(self) -> AreaLight {
+        AreaLight {
+            base_light: self.base_light_builder.build(),
+            shape: self.shape.clamped().into(),
+            distance: self.distance.abs().into(),
+        }
+    }
+
+    /// Builds new instance of area light node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_area_light())
+    }
+
+    /// Builds new instance of area light and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<AreaLight> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            light::{
+                area::{
+                    area_light_polygon, AreaLightBuilder, AreaLightShape, DISC_POLYGON_VERTEX_COUNT,
+                },
+                BaseLightBuilder,
+            },
+        },
+    };
+
+    #[test]
+    fn test_size_is_clamped() {
+        let light = AreaLightBuilder::new(BaseLightBuilder::new(BaseBuilder::new()))
+            .with_shape(AreaLightShape::Rect {
+                width: -1.0,
+                height: 0.0,
+            })
+            .build_area_light();
+        let (hw, hh) = light.shape().half_extents();
+        assert!(hw > 0.0 && hh > 0.0);
+    }
+
+    #[test]
+    fn test_polygon() {
+        let position = Vector3::new(1.0, 2.0, 3.0);
+        let side = Vector3::x().scale(2.0);
+        let look = Vector3::z();
+
+        let rect = AreaLightShape::Rect {
+            width: 1.0,
+            height: 4.0,
+        };
+        let (s, l) = rect.scaled_axes(side, look);
+        assert_eq!(s, Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(l, Vector3::new(0.0, 0.0, 2.0));
+        assert_eq!(
+            area_light_polygon(&rect, position, side, look),
+            vec![
+                Vector3::new(0.0, 2.0, 1.0),
+                Vector3::new(0.0, 2.0, 5.0),
+                Vector3::new(2.0, 2.0, 5.0),
+                Vector3::new(2.0, 2.0, 1.0),
+            ]
+        );
+
+        // Discs are regular polygons inscribed into the (scaled) disc.
+        let disc = AreaLightShape::Disc { radius: 0.5 };
+        let polygon = area_light_polygon(&disc, position, side, look);
+        assert_eq!(polygon.len(), DISC_POLYGON_VERTEX_COUNT);
+        for vertex in polygon {
+            let d = vertex - position;
+            let ellipse = (d.x / 1.0).powi(2) + (d.z / 0.5).powi(2);
+            assert!((ellipse - 1.0).abs() < 1.0e-5);
+            assert_eq!(d.y, 0.0);
+        }
+    }
+}
@@ -21,12 +21,13 @@
 //! Contains all structures and methods to create and manage lights.
 //!
 //! Light sources arte basic building blocks of many scenes in games, it improves
-//! perception of scene and makes it look natural. Fyrox engine supports three kinds
+//! perception of scene and makes it look natural. Fyrox engine supports four kinds
 //! of light sources:
 //!
 //! 1) Directional - similar to sun in real life, its rays are parallel.
 //! 2) Spot - similar to flash light, it has cone light volume and circle spot.
 //! 3) Point - similar to light bulb, it has spherical light volume.
+//! 4) Area - similar to light panel, it has rectangular or disc-shaped emitting surface.
 //!
 //! Each kind of light source is suitable for specific conditions, for example
 //! spot light can be used if you have a character with flashlight, point - if
@@ -41,13 +42,16 @@ use crate::{
         algebra::Vector3,
         color::Color,
         reflect::prelude::*,
+        uuid_provider,
         variable::InheritableVariable,
         visitor::{Visit, VisitResult, Visitor},
     },
     scene::base::{Base, BaseBuilder},
 };
 use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
 
+pub mod area;
 pub mod directional;
 pub mod point;
 pub mod spot;
@@ -64,6 +68,55 @@ pub const DEFAULT_SCATTER_G: f32 = 0.03;
 /// significant value and you'll clearly see light volume with such settings.
 pub const DEFAULT_SCATTER_B: f32 = 0.03;
 
+/// Default size of a light source, that is used for contact-hardening soft shadows.
+pub const DEFAULT_LIGHT_SIZE: f32 = 0.02;
+
+/// Defines how often the shadow map of a light source is updated.
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, Reflect, Visit, AsRefStr, EnumString, VariantNames,
+)]
+pub enum ShadowUpdateCadence {
+    /// The shadow map is updated every frame. This is default option.
+    #[default]
+    EveryFrame,
+    /// The shadow map is updated once per the given amount of frames. Shadows of moving objects
+    /// will lag behind, but it could be a good option for distant lights.
+    Interval {
+        /// Amount of frames between two updates.
+        frames: u32,
+    },
+    /// The shadow map is updated only when the light source moves (or rotates).
+    OnMovement,
+    /// The shadow map is updated only once. It is suitable for static light sources with static
+    /// occluders.
+    Static,
+}
+
+uuid_provider!(ShadowUpdateCadence = "0c9c5d8e-4c1c-4f6e-8b52-6a7a1f0fbd31");
+
+/// Shadow map settings of a light source. Custom settings allow you to reduce the cost of
+/// shadows in large scenes - lights with custom settings use their own shadow map, which is kept
+/// between frames and updated only when needed. Currently only spot and point lights respect
+/// these settings, directional lights use cascaded shadow maps that follow the camera.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Reflect, Visit)]
+pub struct ShadowMapSettings {
+    /// Size of the shadow map in pixels. Zero means that the size from the quality settings of the
+    /// renderer will be used.
+    pub resolution: u32,
+    /// Defines how often the shadow map is updated. See [`ShadowUpdateCadence`] docs for more info.
+    pub cadence: ShadowUpdateCadence,
+}
+
+uuid_provider!(ShadowMapSettings = "6e1f5a8b-1b7a-4c0b-9d2e-5f3c7a9e4b12");
+
+impl ShadowMapSettings {
+    /// Returns `true` if the settings differ from the default ones, which means that the light
+    /// source needs its own shadow map.
+    pub fn is_custom(&self) -> bool {
+        *self != Self::default()
+    }
+}
+
 /// Light scene node. It contains common properties of light such as color,
 /// scattering factor (per color channel) and other useful properties. Exact
 /// behavior defined by specific light kind.
@@ -84,6 +137,15 @@ pub struct BaseLight {
     #[reflect(min_value = 0.0, step = 0.1)]
     #[reflect(setter = "set_intensity")]
     intensity: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.001)]
+    #[reflect(setter = "set_light_size")]
+    light_size: InheritableVariable<f32>,
+
+    #[visit(optional)]
+    #[reflect(setter = "set_shadow_map_settings")]
+    shadow_map_settings: InheritableVariable<ShadowMapSettings>,
 }
 
 impl Deref for BaseLight {
@@ -112,6 +174,8 @@ impl Default for BaseLight {
             )),
             scatter_enabled: InheritableVariable::new_modified(true),
             intensity: InheritableVariable::new_modified(1.0),
+            light_size: InheritableVariable::new_modified(DEFAULT_LIGHT_SIZE),
+            shadow_map_settings: Default::default(),
         }
     }
 }
@@ -162,6 +226,32 @@ impl BaseLight {
         *self.intensity
     }
 
+    /// Sets new size of the light source. The size is used by percentage-closer soft shadows (see
+    /// [`crate::renderer::QualitySettings::use_pcss`]) - the larger the light source is, the wider
+    /// the penumbra of shadows gets with the distance between an occluder and a receiver. The size
+    /// is defined in shadow map texture space (i.e. 0.02 means 2% of the shadow map size). Zero
+    /// size disables contact-hardening for the light, regular percentage-closer filtering will be
+    /// used instead. Default is 0.02.
+    pub fn set_light_size(&mut self, size: f32) -> f32 {
+        self.light_size.set_value_and_mark_modified(size.max(0.0))
+    }
+
+    /// Returns current size of the light source.
+    pub fn light_size(&self) -> f32 {
+        *self.light_size
+    }
+
+    /// Sets new shadow map settings of the light. See [`ShadowMapSettings`] docs for more info.
+    pub fn set_shadow_map_settings(&mut self, settings: ShadowMapSettings) -> ShadowMapSettings {
+        self.shadow_map_settings
+            .set_value_and_mark_modified(settings)
+    }
+
+    /// Returns current shadow map settings of the light.
+    pub fn shadow_map_settings(&self) -> ShadowMapSettings {
+        *self.shadow_map_settings
+    }
+
     /// Returns current scatter factor in linear color space.
     #[inline]
     pub fn scatter_linear(&self) -> Vector3<f32> {
@@ -189,6 +279,8 @@ pub struct BaseLightBuilder {
     scatter_factor: Vector3<f32>,
     scatter_enabled: bool,
     intensity: f32,
+    light_size: f32,
+    shadow_map_settings: ShadowMapSettings,
 }
 
 impl BaseLightBuilder {
@@ -203,6 +295,8 @@ impl BaseLightBuilder {
             scatter_factor: Vector3::new(DEFAULT_SCATTER_R, DEFAULT_SCATTER_G, DEFAULT_SCATTER_B),
             scatter_enabled: true,
             intensity: 1.0,
+            light_size: DEFAULT_LIGHT_SIZE,
+            shadow_map_settings: Default::default(),
         }
     }
 
@@ -230,6 +324,18 @@ impl BaseLightBuilder {
         self
     }
 
+    /// Sets desired size of the light source. See [`BaseLight::set_light_size`] for more info.
+    pub fn with_light_size(mut self, size: f32) -> Self {
+        self.light_size = size;
+        self
+    }
+
+    /// Sets desired shadow map settings. See [`ShadowMapSettings`] docs for more info.
+    pub fn with_shadow_map_settings(mut self, settings: ShadowMapSettings) -> Self {
+        self.shadow_map_settings = settings;
+        self
+    }
+
     /// Creates new instance of base light.
     pub fn build(self) -> BaseLight {
         BaseLight {
@@ -238,6 +344,8 @@ impl BaseLightBuilder {
             scatter: self.scatter_factor.into(),
             scatter_enabled: self.scatter_enabled.into(),
             intensity: self.intensity.into(),
+            light_size: self.light_size.max(0.0).into(),
+            shadow_map_settings: self.shadow_map_settings.into(),
         }
     }
 }
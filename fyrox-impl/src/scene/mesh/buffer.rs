@@ -41,7 +41,7 @@ use std::{
     hash::{Hash, Hasher},
     marker::PhantomData,
     mem::MaybeUninit,
-    ops::{Deref, DerefMut, Index, IndexMut, RangeBounds},
+    ops::{Deref, DerefMut, Index, IndexMut, Range, RangeBounds},
     vec::Drain,
 };
 
@@ -405,6 +405,9 @@ pub struct VertexBuffer {
     layout_hash: u64,
     #[visit(optional)]
     modifications_counter: u64,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    modified_range: Option<Range<usize>>,
 }
 
 fn calculate_layout_hash(layout: &[VertexAttribute]) -> u64 {
@@ -427,6 +430,7 @@ pub struct VertexBufferRefMut<'a> {
 impl Drop for VertexBufferRefMut<'_> {
     fn drop(&mut self) {
         self.vertex_buffer.modifications_counter += 1;
+        self.vertex_buffer.modified_range = Some(0..self.vertex_buffer.data.len());
     }
 }
 
@@ -868,6 +872,7 @@ impl VertexBuffer {
             vertex_size: vertex_size_bytes,
             vertex_count: vertex_count as u32,
             modifications_counter: 0,
+            modified_range: None,
             data: bytes,
             layout_hash: calculate_layout_hash(&dense_layout),
             sparse_layout,
@@ -886,6 +891,7 @@ impl VertexBuffer {
             data: BytesStorage::with_capacity(capacity),
             layout_hash: self.layout_hash,
             modifications_counter: 0,
+            modified_range: None,
         }
     }
 
@@ -904,6 +910,73 @@ impl VertexBuffer {
         self.modifications_counter
     }
 
+    /// Returns a range of bytes that was modified since the last call of [`Self::take_modified_range`]
+    /// (if any). Any modification via [`Self::modify`] marks the entire buffer as modified, use
+    /// [`Self::update_vertices`] to modify only the changed part of the buffer.
+    pub fn modified_range(&self) -> Option<Range<usize>> {
+        self.modified_range.clone()
+    }
+
+    /// Returns a range of bytes that was modified since the last call of this method and resets it.
+    /// It is used by the renderer to upload only the changed part of the buffer to the GPU.
+    pub fn take_modified_range(&mut self) -> Option<Range<usize>> {
+        self.modified_range.take()
+    }
+
+    /// Replaces the content of the buffer with the given vertices. Only the vertices that differ
+    /// from the current content are marked as modified (see [`Self::modified_range`]), which allows
+    /// the renderer to re-upload only the changed range of the buffer to the GPU. If the amount of
+    /// vertices differs from the current one, the entire buffer is replaced. This method fails if
+    /// the size of the vertex type does not match the vertex size of the buffer.
+    pub fn update_vertices<T>(&mut self, vertices: &[T]) -> Result<(), ValidationError>
+    where
+        T: VertexTrait + Pod,
+    {
+        if std::mem::size_of::<T>() != self.vertex_size as usize {
+            return Err(ValidationError::InvalidVertexSize {
+                expected: self.vertex_size,
+                actual: std::mem::size_of::<T>() as u8,
+            });
+        }
+
+        let new_data = array_as_u8_slice(vertices);
+        let range = if new_data.len() == self.data.len() {
+            let mut differences = self
+                .data
+                .iter()
+                .zip(new_data)
+                .enumerate()
+                .filter(|(_, (old, new))| old != new)
+                .map(|(i, _)| i);
+            let Some(first) = differences.next() else {
+                // Nothing has changed.
+                return Ok(());
+            };
+            let last = differences.last().unwrap_or(first);
+
+            // Align the range to vertex boundaries.
+            let vertex_size = self.vertex_size as usize;
+            let range =
+                (first / vertex_size * vertex_size)..((last / vertex_size + 1) * vertex_size);
+            self.data.as_slice_mut()[range.clone()].copy_from_slice(&new_data[range.clone()]);
+
+            match self.modified_range.take() {
+                Some(prev) => prev.start.min(range.start)..prev.end.max(range.end),
+                None => range,
+            }
+        } else {
+            self.data.clear();
+            self.data.extend_from_slice(new_data);
+            self.vertex_count = vertices.len() as u32;
+            0..new_data.len()
+        };
+
+        self.modified_range = Some(range);
+        self.modifications_counter += 1;
+
+        Ok(())
+    }
+
     /// Calculates inner data hash.
     pub fn content_hash(&self) -> u64 {
         calculate_data_hash(&self.data.bytes)
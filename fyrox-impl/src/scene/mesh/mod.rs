@@ -37,8 +37,8 @@ use crate::{
     graph::SceneGraph,
     graphics::ElementRange,
     material::{
-        Material, MaterialResource, MaterialResourceBinding, MaterialResourceExtension,
-        MaterialTextureBinding,
+        shader::ShaderDefinition, Material, MaterialResource, MaterialResourceBinding,
+        MaterialResourceExtension, MaterialTextureBinding,
     },
     renderer::{
         self,
@@ -47,7 +47,7 @@ use crate::{
     },
     resource::texture::PLACEHOLDER,
     scene::{
-        base::{Base, BaseBuilder},
+        base::{Base, BaseBuilder, LevelOfDetail, LodGroup},
         debug::{Line, SceneDrawingContext},
         graph::Graph,
         mesh::{
@@ -56,12 +56,14 @@ use crate::{
                 VertexAttributeUsage, VertexBuffer, VertexBufferRefMut, VertexReadTrait,
                 VertexViewMut, VertexWriteTrait,
             },
+            skinning::{pack_bone_matrices, skin_vertices, SkinningBackend, SkinningMode},
             surface::SurfaceBuilder,
             surface::{BlendShape, Surface, SurfaceData, SurfaceResource},
         },
         node::constructor::NodeConstructor,
         node::{Node, NodeTrait, RdcControlFlow, SyncContext},
     },
+    utils::simplify::simplify,
 };
 use fxhash::{FxHashMap, FxHasher};
 use fyrox_graph::constructor::ConstructorProvider;
@@ -74,6 +76,7 @@ use std::{
 use strum_macros::{AsRefStr, EnumString, VariantNames};
 
 pub mod buffer;
+pub mod skinning;
 pub mod surface;
 pub mod vertex;
 
@@ -201,6 +204,30 @@ impl Clone for BatchContainerWrapper {
     }
 }
 
+/// Result of CPU skinning of a surface along with the input it was produced from.
+#[derive(Debug)]
+struct CpuSkinnedSurface {
+    source_key: u64,
+    skinning_mode: SkinningMode,
+    /// Bone matrices packed by [`pack_bone_matrices`].
+    bone_matrices: Vec<Matrix4<f32>>,
+    blend_shape_weights: Vec<f32>,
+    data: SurfaceResource,
+}
+
+/// Results of CPU skinning of surfaces (surface index -> skinned data). A surface is skinned again
+/// only when its input changes, so a mesh rendered by multiple passes (shadows, multiple cameras,
+/// etc.) is skinned only once per frame.
+#[derive(Default, Debug)]
+struct CpuSkinningCache(Mutex<FxHashMap<usize, CpuSkinnedSurface>>);
+
+impl Clone for CpuSkinningCache {
+    fn clone(&self) -> Self {
+        // Skinned data must not be shared between meshes, it will be re-created on demand.
+        Self::default()
+    }
+}
+
 impl RenderDataBundleStorageTrait for BatchContainer {
     fn push_triangles(
         &mut self,
@@ -353,6 +380,23 @@ pub struct Mesh {
     #[visit(optional)]
     blend_shapes: InheritableVariable<Vec<BlendShape>>,
 
+    /// Defines how bone transforms are blended to deform skinned surfaces of the mesh. See
+    /// [`SkinningMode`] docs for more info.
+    #[visit(optional)]
+    skinning_mode: InheritableVariable<SkinningMode>,
+
+    /// Defines where vertices of skinned surfaces of the mesh are deformed. See [`SkinningBackend`]
+    /// docs for more info.
+    #[visit(optional)]
+    skinning_backend: InheritableVariable<SkinningBackend>,
+
+    /// Materials that are used instead of the materials of the surfaces with the respective
+    /// indices. It allows instances of a mesh to use different materials without modifying the
+    /// surfaces. See [`Mesh::set_material_override`] docs for more info.
+    #[visit(optional)]
+    #[reflect(hidden)]
+    material_overrides: InheritableVariable<FxHashMap<u32, MaterialResource>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     local_bounding_box: Cell<AxisAlignedBoundingBox>,
@@ -368,6 +412,10 @@ pub struct Mesh {
     #[reflect(hidden)]
     #[visit(skip)]
     batch_container: BatchContainerWrapper,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    cpu_skinning_cache: CpuSkinningCache,
 }
 
 impl Default for Mesh {
@@ -382,7 +430,11 @@ impl Default for Mesh {
             batching_mode: Default::default(),
             blend_shapes_property_name: Mesh::DEFAULT_BLEND_SHAPES_PROPERTY_NAME.to_string(),
             blend_shapes: Default::default(),
+            skinning_mode: Default::default(),
+            skinning_backend: Default::default(),
+            material_overrides: Default::default(),
             batch_container: Default::default(),
+            cpu_skinning_cache: Default::default(),
         }
     }
 }
@@ -455,6 +507,156 @@ impl Mesh {
         self.blend_shapes.get_value_mut_and_mark_modified()
     }
 
+    /// Sets new skinning mode of the mesh. See [`SkinningMode`] docs for more info.
+    pub fn set_skinning_mode(&mut self, mode: SkinningMode) -> SkinningMode {
+        self.skinning_mode.set_value_and_mark_modified(mode)
+    }
+
+    /// Returns current skinning mode of the mesh.
+    pub fn skinning_mode(&self) -> SkinningMode {
+        *self.skinning_mode
+    }
+
+    /// Sets new skinning backend of the mesh. See [`SkinningBackend`] docs for more info.
+    pub fn set_skinning_backend(&mut self, backend: SkinningBackend) -> SkinningBackend {
+        self.skinning_backend.set_value_and_mark_modified(backend)
+    }
+
+    /// Returns current skinning backend of the mesh.
+    pub fn skinning_backend(&self) -> SkinningBackend {
+        *self.skinning_backend
+    }
+
+    /// Sets a material that will be used to render a surface with the given index instead of the
+    /// material of the surface. Surface data and the surface itself stay untouched, which allows
+    /// instances of a model to have different materials while sharing everything else. Overrides
+    /// for indices beyond the surface count are ignored. Returns previous override (if any).
+    pub fn set_material_override(
+        &mut self,
+        surface_index: u32,
+        material: MaterialResource,
+    ) -> Option<MaterialResource> {
+        self.material_overrides
+            .get_value_mut_and_mark_modified()
+            .insert(surface_index, material)
+    }
+
+    /// Removes material override of a surface with the given index. Returns the removed override
+    /// (if any).
+    pub fn remove_material_override(&mut self, surface_index: u32) -> Option<MaterialResource> {
+        self.material_overrides
+            .get_value_mut_and_mark_modified()
+            .remove(&surface_index)
+    }
+
+    /// Returns material override of a surface with the given index (if any).
+    pub fn material_override(&self, surface_index: u32) -> Option<&MaterialResource> {
+        self.material_overrides.get(&surface_index)
+    }
+
+    /// Returns a material that is used to render a surface with the given index. It is either the
+    /// material override (if any) or the material of the surface. Returns `None` if there's no
+    /// such surface.
+    pub fn surface_material(&self, surface_index: usize) -> Option<&MaterialResource> {
+        let surface = self.surfaces.get(surface_index)?;
+        Some(
+            self.material_override(surface_index as u32)
+                .unwrap_or(surface.material()),
+        )
+    }
+
+    /// Returns the latest result of CPU skinning of a surface with the given index. The data is
+    /// updated every time when the mesh is rendered and it is available only for skinned surfaces
+    /// that are skinned on CPU (see [`SkinningBackend`] docs for more info). Vertices of the
+    /// data are in world space.
+    pub fn cpu_skinned_surface_data(&self, surface_index: usize) -> Option<SurfaceResource> {
+        self.cpu_skinning_cache
+            .0
+            .safe_lock()
+            .get(&surface_index)
+            .cloned()
+    }
+
+    /// Skins a surface on CPU and returns the skinned data. Blend shapes are applied before
+    /// skinning. Skinned data is reused, if neither the surface nor the bones were changed since the
+    /// last call.
+    fn skin_surface_on_cpu(
+        &self,
+        surface_index: usize,
+        surface: &Surface,
+        bone_matrices: Vec<Matrix4<f32>>,
+    ) -> Option<SurfaceResource> {
+        let source_key = surface.data_ref().key();
+        let skinning_mode = self.skinning_mode();
+        let weights = self.blend_shapes().iter().map(|bs| bs.weight / 100.0);
+
+        let mut cache = self.cpu_skinning_cache.0.safe_lock();
+        if let Some(skinned) = cache.get(&surface_index) {
+            if skinned.source_key == source_key
+                && skinned.skinning_mode == skinning_mode
+                && skinned.bone_matrices == bone_matrices
+                && skinned
+                    .blend_shape_weights
+                    .iter()
+                    .copied()
+                    .eq(weights.clone())
+            {
+                return Some(skinned.data.clone());
+            }
+        }
+
+        let blend_shape_weights = weights.collect::<Vec<_>>();
+        let source = surface.data_ref().data_ref();
+        let blended = source.blend_morph_targets(&blend_shape_weights);
+        let base = blended.as_ref().unwrap_or(&source.vertex_buffer);
+
+        let resource = match cache.remove(&surface_index) {
+            Some(skinned) => skinned.data,
+            None => SurfaceResource::new_embedded(SurfaceData::new(
+                base.clone(),
+                source.geometry_buffer.clone(),
+            )),
+        };
+
+        let mut data = resource.data_ref();
+        if data.vertex_buffer.vertex_count() != base.vertex_count()
+            || data.geometry_buffer.len() != source.geometry_buffer.len()
+        {
+            *data = SurfaceData::new(base.clone(), source.geometry_buffer.clone());
+        }
+        skin_vertices(base, &mut data.vertex_buffer, &bone_matrices, skinning_mode).ok()?;
+        drop(data);
+
+        cache.insert(
+            surface_index,
+            CpuSkinnedSurface {
+                source_key,
+                skinning_mode,
+                bone_matrices,
+                blend_shape_weights,
+                data: resource.clone(),
+            },
+        );
+
+        Some(resource)
+    }
+
+    /// Sets weight (in `[0; 100]` range) of a blend shape with the given name. Returns `false` if
+    /// there is no such blend shape. Weights are additive, see [`SurfaceData::blend_morph_targets`]
+    /// for more info.
+    pub fn set_blend_shape_weight(&mut self, name: &str, weight: f32) -> bool {
+        if let Some(blend_shape) = self
+            .blend_shapes_mut()
+            .iter_mut()
+            .find(|bs| bs.name == name)
+        {
+            blend_shape.weight = weight;
+            true
+        } else {
+            false
+        }
+    }
+
     /// Sets new render path for the mesh.
     pub fn set_render_path(&mut self, render_path: RenderPath) -> RenderPath {
         self.render_path.set_value_and_mark_modified(render_path)
@@ -465,6 +667,81 @@ impl Mesh {
         *self.render_path
     }
 
+    /// Generates levels of detail for the given mesh. Every level is produced by simplification of
+    /// the surfaces of the mesh (see [`simplify`] for more info) to the respective ratio from the
+    /// `ratios` slice (`1.0` - full detail, `0.5` - half of the triangles and so on). Ratios must
+    /// be sorted in descending order. The mesh itself is used as a level for ratios that are
+    /// greater or equal to `1.0`, other levels are created as child mesh nodes with the same
+    /// materials. Distance range (`[0; 1]`) is split evenly across the levels, it could be
+    /// adjusted later. Returns `None` if the handle is invalid or `ratios` is empty.
+    ///
+    /// The resulting group is not assigned to any node, usually it should be set to the mesh
+    /// itself:
+    ///
+    /// ```rust
+    /// # use fyrox_impl::{
+    /// #     core::pool::Handle,
+    /// #     graph::SceneGraph,
+    /// #     scene::{graph::Graph, mesh::Mesh},
+    /// # };
+    /// fn add_lods(graph: &mut Graph, mesh: Handle<Mesh>) {
+    ///     let lod_group = Mesh::generate_lods(graph, mesh, &[1.0, 0.5, 0.25]);
+    ///     if let Ok(mesh) = graph.try_get_mut(mesh) {
+    ///         mesh.set_lod_group(lod_group);
+    ///     }
+    /// }
+    /// ```
+    pub fn generate_lods(
+        graph: &mut Graph,
+        mesh: Handle<Mesh>,
+        ratios: &[f32],
+    ) -> Option<LodGroup> {
+        if ratios.is_empty() {
+            return None;
+        }
+
+        let mesh_ref = graph.try_get(mesh).ok()?;
+        let name = mesh_ref.name().to_owned();
+        let render_path = mesh_ref.render_path();
+        let surfaces = mesh_ref.surfaces().to_vec();
+
+        let level_count = ratios.len() as f32;
+        let mut levels = Vec::with_capacity(ratios.len());
+        for (i, ratio) in ratios.iter().enumerate() {
+            let object = if *ratio >= 1.0 {
+                mesh.to_base()
+            } else {
+                let lod_surfaces = surfaces
+                    .iter()
+                    .filter(|surface| surface.data_ref().is_ok())
+                    .map(|surface| {
+                        let data = simplify(&surface.data_ref().data_ref(), *ratio);
+                        SurfaceBuilder::new(SurfaceResource::new_embedded(data))
+                            .with_material(surface.material().clone())
+                            .with_bones(surface.bones().to_vec())
+                            .with_unique_material(surface.is_unique_material())
+                            .build()
+                    })
+                    .collect();
+
+                let lod = MeshBuilder::new(BaseBuilder::new().with_name(format!("{name}_LOD{i}")))
+                    .with_surfaces(lod_surfaces)
+                    .with_render_path(render_path)
+                    .build(graph);
+                graph.link_nodes(lod, mesh);
+                lod.to_base()
+            };
+
+            levels.push(LevelOfDetail::new(
+                i as f32 / level_count,
+                (i + 1) as f32 / level_count,
+                vec![object],
+            ));
+        }
+
+        Some(LodGroup { levels })
+    }
+
     /// Calculate very accurate bounding box in *world coordinates* including influence of bones.
     /// This method is very heavy and not intended to use every frame!
     pub fn accurate_world_bounding_box(&self, graph: &Graph) -> AxisAlignedBoundingBox {
@@ -697,7 +974,11 @@ impl NodeTrait for Mesh {
             return RdcControlFlow::Continue;
         }
 
-        let sorting_index = ctx.calculate_sorting_index(self.global_position());
+        let sorting_index = if self.render_path() == RenderPath::Forward {
+            ctx.calculate_transparency_sorting_index(self.global_position(), self.render_order())
+        } else {
+            ctx.calculate_sorting_index(self.global_position())
+        };
 
         if let BatchingMode::Static = *self.batching_mode {
             let mut container = self.batch_container.0.safe_lock();
@@ -716,6 +997,7 @@ impl NodeTrait for Mesh {
                         world_transform: Matrix4::identity(),
                         bone_matrices: Default::default(),
                         blend_shapes_weights: Default::default(),
+                        skinning_mode: Default::default(),
                         element_range: ElementRange::Full,
                         node_handle: self.handle(),
                     },
@@ -724,11 +1006,14 @@ impl NodeTrait for Mesh {
 
             RdcControlFlow::Break
         } else {
-            for surface in self.surfaces().iter() {
+            for (surface_index, surface) in self.surfaces().iter().enumerate() {
                 if !surface.data_ref().is_ok() {
                     continue;
                 }
                 let is_skinned = !surface.bones.is_empty();
+                let material = self
+                    .material_override(surface_index as u32)
+                    .unwrap_or(surface.material());
 
                 let world = if is_skinned {
                     Matrix4::identity()
@@ -754,6 +1039,48 @@ impl NodeTrait for Mesh {
 
                 match batching_mode {
                     BatchingMode::None => {
+                        // Both CPU and GPU skinning use the same bone matrices in the form that
+                        // is expected by shaders.
+                        let bone_matrices = pack_bone_matrices(
+                            self.skinning_mode(),
+                            surface
+                                .bones
+                                .iter()
+                                .map(|bone_handle| {
+                                    if let Ok(bone_node) = ctx.graph.try_get_node(*bone_handle) {
+                                        bone_node.global_transform()
+                                            * bone_node.inv_bind_pose_transform()
+                                    } else {
+                                        Matrix4::identity()
+                                    }
+                                })
+                                .collect::<Vec<_>>(),
+                        );
+
+                        // Fall back to CPU skinning, if there are too many bones for a single
+                        // draw call.
+                        if is_skinned
+                            && (self.skinning_backend() == SkinningBackend::Cpu
+                                || bone_matrices.len() > ShaderDefinition::MAX_BONE_MATRICES)
+                        {
+                            if let Some(skinned_data) =
+                                self.skin_surface_on_cpu(surface_index, surface, bone_matrices)
+                            {
+                                ctx.storage.push(
+                                    &skinned_data,
+                                    material,
+                                    self.render_path(),
+                                    sorting_index,
+                                    SurfaceInstanceData {
+                                        world_transform: Matrix4::identity(),
+                                        node_handle: self.handle(),
+                                        ..Default::default()
+                                    },
+                                );
+                            }
+                            continue;
+                        }
+
                         let surface_data = surface.data_ref();
                         let substitute_material = surface_data
                             .data_ref()
@@ -761,7 +1088,7 @@ impl NodeTrait for Mesh {
                             .as_ref()
                             .and_then(|c| c.blend_shape_storage.as_ref())
                             .map(|texture| {
-                                let material_copy = surface.material().deep_copy();
+                                let material_copy = material.deep_copy();
                                 material_copy.data_ref().bind(
                                     &self.blend_shapes_property_name,
                                     MaterialResourceBinding::Texture(MaterialTextureBinding {
@@ -773,29 +1100,18 @@ impl NodeTrait for Mesh {
 
                         ctx.storage.push(
                             surface_data,
-                            substitute_material.as_ref().unwrap_or(surface.material()),
+                            substitute_material.as_ref().unwrap_or(material),
                             self.render_path(),
                             sorting_index,
                             SurfaceInstanceData {
                                 world_transform: world,
-                                bone_matrices: surface
-                                    .bones
-                                    .iter()
-                                    .map(|bone_handle| {
-                                        if let Ok(bone_node) = ctx.graph.try_get_node(*bone_handle)
-                                        {
-                                            bone_node.global_transform()
-                                                * bone_node.inv_bind_pose_transform()
-                                        } else {
-                                            Matrix4::identity()
-                                        }
-                                    })
-                                    .collect::<Vec<_>>(),
+                                bone_matrices,
                                 blend_shapes_weights: self
                                     .blend_shapes()
                                     .iter()
                                     .map(|bs| bs.weight / 100.0)
                                     .collect(),
+                                skinning_mode: self.skinning_mode(),
                                 element_range: ElementRange::Full,
                                 node_handle: self.handle(),
                             },
@@ -810,7 +1126,7 @@ impl NodeTrait for Mesh {
                                 .vertex_buffer
                                 .layout_descriptor()
                                 .collect::<Vec<_>>(),
-                            surface.material(),
+                            material,
                             *self.render_path,
                             sorting_index,
                             self.handle(),
@@ -902,6 +1218,9 @@ pub struct MeshBuilder {
     blend_shapes: Vec<BlendShape>,
     batching_mode: BatchingMode,
     blend_shapes_property_name: String,
+    skinning_mode: SkinningMode,
+    skinning_backend: SkinningBackend,
+    material_overrides: FxHashMap<u32, MaterialResource>,
 }
 
 impl MeshBuilder {
@@ -914,6 +1233,9 @@ impl MeshBuilder {
             blend_shapes: Default::default(),
             batching_mode: BatchingMode::None,
             blend_shapes_property_name: Mesh::DEFAULT_BLEND_SHAPES_PROPERTY_NAME.to_string(),
+            skinning_mode: Default::default(),
+            skinning_backend: Default::default(),
+            material_overrides: Default::default(),
         }
     }
 
@@ -949,6 +1271,28 @@ impl MeshBuilder {
         self
     }
 
+    /// Sets the desired skinning mode. See [`SkinningMode`] docs for more info.
+    pub fn with_skinning_mode(mut self, mode: SkinningMode) -> Self {
+        self.skinning_mode = mode;
+        self
+    }
+
+    /// Sets the desired skinning backend. See [`SkinningBackend`] docs for more info.
+    pub fn with_skinning_backend(mut self, backend: SkinningBackend) -> Self {
+        self.skinning_backend = backend;
+        self
+    }
+
+    /// Sets material overrides of the surfaces (surface index -> material). See
+    /// [`Mesh::set_material_override`] docs for more info.
+    pub fn with_material_overrides(
+        mut self,
+        material_overrides: FxHashMap<u32, MaterialResource>,
+    ) -> Self {
+        self.material_overrides = material_overrides;
+        self
+    }
+
     /// Creates new mesh.
     pub fn build_node(self) -> Node {
         Node::new(Mesh {
@@ -962,6 +1306,10 @@ impl MeshBuilder {
             batching_mode: self.batching_mode.into(),
             batch_container: Default::default(),
             blend_shapes_property_name: self.blend_shapes_property_name,
+            skinning_mode: self.skinning_mode.into(),
+            skinning_backend: self.skinning_backend.into(),
+            material_overrides: self.material_overrides.into(),
+            cpu_skinning_cache: Default::default(),
         })
     }
 
@@ -970,3 +1318,334 @@ impl MeshBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, UnitQuaternion, Vector2, Vector3, Vector4},
+            color::Color,
+        },
+        fxhash::FxHashMap,
+        material::{Material, MaterialResource},
+        renderer::{
+            bundle::{RenderDataBundleStorage, RenderDataBundleStorageOptions},
+            observer::ObserverPosition,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                buffer::{TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexReadTrait},
+                skinning::{blend_bone_matrices, SkinningBackend, SkinningMode},
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                vertex::AnimatedVertex,
+                Mesh, MeshBuilder,
+            },
+            pivot::PivotBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_generate_lods() {
+        let mut graph = Graph::new();
+        let mesh = MeshBuilder::new(BaseBuilder::new().with_name("Sphere"))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut graph);
+
+        let lod_group = Mesh::generate_lods(&mut graph, mesh, &[1.0, 0.5, 0.25]).unwrap();
+        assert_eq!(lod_group.levels.len(), 3);
+        assert_eq!(lod_group.levels[0].objects, vec![mesh.to_base()]);
+
+        let triangle_counts = lod_group
+            .levels
+            .iter()
+            .map(|level| {
+                let mesh = graph[level.objects[0]].cast::<Mesh>().unwrap();
+                let data = mesh.surfaces()[0].data_ref().data_ref();
+                data.geometry_buffer.len()
+            })
+            .collect::<Vec<_>>();
+        assert!(triangle_counts[0] > triangle_counts[1]);
+        assert!(triangle_counts[1] > triangle_counts[2]);
+
+        for (i, level) in lod_group.levels.iter().enumerate() {
+            assert_eq!(level.begin(), i as f32 / 3.0);
+            assert_eq!(level.end(), (i + 1) as f32 / 3.0);
+        }
+    }
+
+    fn colored_material(color: Color) -> MaterialResource {
+        let mut material = Material::standard();
+        material.set_property("diffuseColor", color);
+        MaterialResource::new_embedded(material)
+    }
+
+    #[test]
+    fn test_material_overrides() {
+        let data = SurfaceResource::new_embedded(SurfaceData::make_cube(Matrix4::identity()));
+        let source_material = colored_material(Color::WHITE);
+
+        let mut graph = Graph::new();
+        for color in [Color::RED, Color::GREEN] {
+            MeshBuilder::new(BaseBuilder::new().with_frustum_culling(false))
+                .with_surfaces(vec![SurfaceBuilder::new(data.clone())
+                    .with_material(source_material.clone())
+                    .build()])
+                .with_material_overrides(FxHashMap::from_iter([
+                    (0, colored_material(color)),
+                    // There's no such surface, the override must be ignored.
+                    (5, colored_material(Color::BLUE)),
+                ]))
+                .build(&mut graph);
+        }
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let observer_position = ObserverPosition {
+            translation: Default::default(),
+            z_near: 0.0,
+            z_far: 0.0,
+            view_matrix: Matrix4::identity(),
+            projection_matrix: Matrix4::identity(),
+            view_projection_matrix: Matrix4::identity(),
+        };
+        let storage = RenderDataBundleStorage::from_graph(
+            &graph,
+            Default::default(),
+            0.0,
+            &observer_position,
+            Default::default(),
+            RenderDataBundleStorageOptions {
+                collect_lights: false,
+            },
+            &mut Default::default(),
+        );
+
+        let mut colors = storage
+            .bundles
+            .iter()
+            .filter(|bundle| bundle.data == data)
+            .map(|bundle| {
+                bundle
+                    .material
+                    .data_ref()
+                    .property_group_ref("properties")
+                    .and_then(|group| group.property_ref("diffuseColor"))
+                    .and_then(|property| property.as_color())
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        colors.sort_by_key(|color| color.g);
+        assert_eq!(colors, vec![Color::RED, Color::GREEN]);
+
+        // The source material is left untouched.
+        assert_eq!(
+            source_material
+                .data_ref()
+                .property_group_ref("properties")
+                .and_then(|group| group.property_ref("diffuseColor"))
+                .and_then(|property| property.as_color()),
+            Some(Color::WHITE)
+        );
+    }
+
+    fn skinned_positions(graph: &Graph) -> (SurfaceResource, Vec<Vector3<f32>>) {
+        let storage = RenderDataBundleStorage::from_graph(
+            graph,
+            Default::default(),
+            0.0,
+            &ObserverPosition::default(),
+            Default::default(),
+            RenderDataBundleStorageOptions {
+                collect_lights: false,
+            },
+            &mut Default::default(),
+        );
+        assert_eq!(storage.bundles.len(), 1);
+        let bundle = &storage.bundles[0];
+        assert_eq!(bundle.instances[0].world_transform, Matrix4::identity());
+        let positions = bundle
+            .data
+            .data_ref()
+            .vertex_buffer
+            .iter()
+            .map(|vertex| vertex.read_3_f32(VertexAttributeUsage::Position).unwrap())
+            .collect();
+        (bundle.data.clone(), positions)
+    }
+
+    #[test]
+    fn test_cpu_skinning() {
+        let vertices = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(1.0, 2.0, 3.0),
+        ]
+        .map(|position| AnimatedVertex {
+            position,
+            normal: Vector3::y(),
+            tangent: Vector4::new(1.0, 0.0, 0.0, 1.0),
+            bone_weights: [1.0, 0.0, 0.0, 0.0],
+            ..Default::default()
+        });
+        let data = SurfaceResource::new_embedded(SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices.to_vec()).unwrap(),
+            TriangleBuffer::default(),
+        ));
+
+        let bone_transform = |angle: f32| {
+            TransformBuilder::new()
+                .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                .with_local_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::z_axis(),
+                    angle.to_radians(),
+                ))
+                .build()
+        };
+
+        let mut graph = Graph::new();
+        let bone = PivotBuilder::new(BaseBuilder::new().with_local_transform(bone_transform(60.0)))
+            .build(&mut graph);
+        MeshBuilder::new(BaseBuilder::new().with_frustum_culling(false))
+            .with_surfaces(vec![SurfaceBuilder::new(data.clone())
+                .with_bones(vec![bone])
+                .build()])
+            .with_skinning_mode(SkinningMode::DualQuaternion)
+            .with_skinning_backend(SkinningBackend::Cpu)
+            .build(&mut graph);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let expected = |graph: &Graph| {
+            vertices
+                .iter()
+                .map(|vertex| {
+                    graph[bone]
+                        .global_transform()
+                        .transform_point(&vertex.position.into())
+                        .coords
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let (skinned_data, positions) = skinned_positions(&graph);
+        // Skinned vertices are in world space, the source data is left untouched.
+        assert_ne!(skinned_data, data);
+        for (actual, expected) in positions.iter().zip(expected(&graph)) {
+            assert!((actual - expected).norm() < 1.0e-5);
+        }
+
+        // The surface is skinned again only when the bones move, other passes reuse the data, so
+        // the cleared buffer stays empty.
+        skinned_data.data_ref().vertex_buffer.modify().clear();
+        assert!(skinned_positions(&graph).1.is_empty());
+
+        graph[bone].set_local_transform(bone_transform(30.0));
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        let (_, positions) = skinned_positions(&graph);
+        for (actual, expected) in positions.iter().zip(expected(&graph)) {
+            assert!((actual - expected).norm() < 1.0e-5);
+        }
+    }
+
+    #[test]
+    fn test_gpu_and_cpu_skinning_match() {
+        let vertices = [
+            (Vector3::new(1.0, 0.0, 0.0), [1.0, 0.0, 0.0, 0.0]),
+            (Vector3::new(0.0, 1.0, 0.0), [0.5, 0.5, 0.0, 0.0]),
+            (Vector3::new(1.0, 2.0, 3.0), [0.25, 0.75, 0.0, 0.0]),
+        ]
+        .map(|(position, bone_weights)| AnimatedVertex {
+            position,
+            normal: Vector3::y(),
+            tangent: Vector4::new(1.0, 0.0, 0.0, 1.0),
+            bone_weights,
+            bone_indices: [0, 1, 0, 0],
+            ..Default::default()
+        });
+        let data = SurfaceResource::new_embedded(SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices.to_vec()).unwrap(),
+            TriangleBuffer::default(),
+        ));
+
+        for mode in [SkinningMode::Linear, SkinningMode::DualQuaternion] {
+            let mut graph = Graph::new();
+            let first_bone = PivotBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                        .with_local_rotation(UnitQuaternion::from_euler_angles(0.3, 0.0, 0.5))
+                        .build(),
+                ),
+            )
+            .build(&mut graph);
+            let second_bone = PivotBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, -2.0, 0.5))
+                        .with_local_rotation(UnitQuaternion::from_euler_angles(-0.4, 1.2, 0.0))
+                        .build(),
+                ),
+            )
+            .build(&mut graph);
+            let mesh = MeshBuilder::new(BaseBuilder::new().with_frustum_culling(false))
+                .with_surfaces(vec![SurfaceBuilder::new(data.clone())
+                    .with_bones(vec![first_bone, second_bone])
+                    .build()])
+                .with_skinning_mode(mode)
+                .with_skinning_backend(SkinningBackend::Gpu)
+                .build(&mut graph);
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+            // Do what the vertex shader does with the data uploaded for GPU skinning: bone
+            // matrices of the instance, bone indices and weights of the vertices.
+            let storage = RenderDataBundleStorage::from_graph(
+                &graph,
+                Default::default(),
+                0.0,
+                &ObserverPosition::default(),
+                Default::default(),
+                RenderDataBundleStorageOptions {
+                    collect_lights: false,
+                },
+                &mut Default::default(),
+            );
+            assert_eq!(storage.bundles.len(), 1);
+            let bundle = &storage.bundles[0];
+            assert_eq!(bundle.data, data);
+            let instance = &bundle.instances[0];
+            assert_eq!(instance.skinning_mode, mode);
+            assert_eq!(instance.world_transform, Matrix4::identity());
+            let gpu_positions = vertices
+                .iter()
+                .map(|vertex| {
+                    let influences = [0, 1, 2, 3]
+                        .map(|i| (vertex.bone_indices[i] as usize, vertex.bone_weights[i]));
+                    let matrix = blend_bone_matrices(
+                        &instance.bone_matrices,
+                        &influences,
+                        instance.skinning_mode,
+                    );
+                    matrix.transform_point(&vertex.position.into()).coords
+                })
+                .collect::<Vec<_>>();
+
+            // A vertex that is affected by a single bone follows that bone exactly.
+            let expected = graph[first_bone]
+                .global_transform()
+                .transform_point(&vertices[0].position.into())
+                .coords;
+            assert!((gpu_positions[0] - expected).norm() < 1.0e-5);
+
+            graph[mesh].set_skinning_backend(SkinningBackend::Cpu);
+            let (_, cpu_positions) = skinned_positions(&graph);
+            assert_eq!(cpu_positions.len(), gpu_positions.len());
+            for (cpu, gpu) in cpu_positions.iter().zip(gpu_positions.iter()) {
+                assert!((cpu - gpu).norm() < 1.0e-5, "{mode:?}: {cpu} != {gpu}");
+            }
+        }
+    }
+}
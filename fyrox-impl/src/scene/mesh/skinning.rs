@@ -0,0 +1,415 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Skinning utilities. See [`SkinningMode`] and [`SkinningBackend`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Matrix4, Point3, Quaternion, UnitQuaternion, Vector3, Vector4},
+        math::Matrix4Ext,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::mesh::buffer::{
+        VertexAttributeUsage, VertexBuffer, VertexFetchError, VertexReadTrait, VertexWriteTrait,
+    },
+};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how bone transforms are blended together to deform a skinned mesh.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "2b1f7a4e-5d0c-4f6b-9a8e-3c7d1e2f4a90")]
+#[repr(u32)]
+pub enum SkinningMode {
+    /// Linear blend skinning. Bone matrices are blended linearly, which is fast and supports
+    /// scaling of bones, but it produces "candy-wrapper" artifacts (loss of volume) at twisted
+    /// joints.
+    #[default]
+    Linear = 0,
+
+    /// Dual-quaternion skinning. Bone transforms are converted to dual quaternions and blended
+    /// in a way that preserves volume at twisted joints. Scaling of bones is not supported in
+    /// this mode (it is ignored).
+    DualQuaternion = 1,
+}
+
+/// Defines where vertices of skinned surfaces are deformed.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Ord,
+    Hash,
+    Debug,
+    Visit,
+    Reflect,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "8d3c5e1a-7b4f-4c2d-a6e9-0f1b2c3d4e5f")]
+#[repr(u32)]
+pub enum SkinningBackend {
+    /// Vertices are deformed in the vertex shader, bone matrices are uploaded to a uniform buffer.
+    /// This is the fastest option, but there is a limit of bones per draw call (see
+    /// [`crate::material::shader::ShaderDefinition::MAX_BONE_MATRICES`]). Surfaces that exceed
+    /// the limit are automatically skinned on CPU.
+    #[default]
+    Gpu = 0,
+
+    /// Vertices are deformed on CPU every frame. It is much slower than GPU skinning, but the
+    /// deformed vertices are accessible on CPU side (for example, for physics or picking), see
+    /// [`super::Mesh::cpu_skinned_surface_data`].
+    Cpu = 1,
+}
+
+/// Dual quaternion is a compact representation of a rigid transformation (rotation and
+/// translation), that could be blended without loss of volume.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DualQuaternion {
+    /// Real part, it is the rotation of the transformation.
+    pub real: Quaternion<f32>,
+    /// Dual part, it encodes translation of the transformation.
+    pub dual: Quaternion<f32>,
+}
+
+impl Default for DualQuaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl DualQuaternion {
+    /// Creates a new dual quaternion that does not transform anything.
+    pub fn identity() -> Self {
+        Self {
+            real: Quaternion::identity(),
+            dual: Quaternion::new(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Creates a new dual quaternion from the given rotation and translation.
+    pub fn from_rotation_translation(
+        rotation: UnitQuaternion<f32>,
+        translation: Vector3<f32>,
+    ) -> Self {
+        let real = rotation.into_inner();
+        let dual = (Quaternion::from_imag(translation) * real).scale(0.5);
+        Self { real, dual }
+    }
+
+    /// Creates a new dual quaternion from a rigid transformation matrix. Scale and shear of the
+    /// matrix are discarded.
+    pub fn from_matrix(matrix: &Matrix4<f32>) -> Self {
+        let basis = matrix.basis();
+        // Remove scale from the basis first, otherwise the rotation will be distorted.
+        let basis = Matrix3::from_columns(&[
+            basis.column(0).normalize(),
+            basis.column(1).normalize(),
+            basis.column(2).normalize(),
+        ]);
+        let rotation = UnitQuaternion::from_matrix(&basis);
+        Self::from_rotation_translation(rotation, matrix.position())
+    }
+
+    /// Returns the rotation part of the transformation.
+    pub fn rotation(&self) -> UnitQuaternion<f32> {
+        UnitQuaternion::new_normalize(self.real)
+    }
+
+    /// Returns the translation part of the transformation.
+    pub fn translation(&self) -> Vector3<f32> {
+        (self.dual * self.real.conjugate()).scale(2.0).imag()
+    }
+
+    /// Converts the dual quaternion to a transformation matrix.
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        let mut matrix = self.rotation().to_homogeneous();
+        matrix
+            .fixed_view_mut::<3, 1>(0, 3)
+            .copy_from(&self.translation());
+        matrix
+    }
+
+    /// Packs the dual quaternion in a matrix in a form that is used by shaders: the first column
+    /// stores the real part and the second column stores the dual part (both in `xyzw` order).
+    pub fn to_packed_matrix(&self) -> Matrix4<f32> {
+        let mut matrix = Matrix4::zeros();
+        matrix.set_column(0, &self.real.coords);
+        matrix.set_column(1, &self.dual.coords);
+        matrix
+    }
+
+    /// Unpacks a dual quaternion from a matrix created by [`Self::to_packed_matrix`].
+    pub fn from_packed_matrix(matrix: &Matrix4<f32>) -> Self {
+        Self {
+            real: Quaternion::from(matrix.column(0).into_owned()),
+            dual: Quaternion::from(matrix.column(1).into_owned()),
+        }
+    }
+
+    /// Transforms the given point.
+    pub fn transform_point(&self, point: &Vector3<f32>) -> Vector3<f32> {
+        self.rotation().transform_vector(point) + self.translation()
+    }
+
+    /// Transforms the given vector, translation is ignored.
+    pub fn transform_vector(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        self.rotation().transform_vector(vector)
+    }
+
+    /// Blends a set of weighted dual quaternions. Quaternions are flipped to the hemisphere of the
+    /// first one to take the shortest path, the result is normalized.
+    pub fn blend(items: impl IntoIterator<Item = (DualQuaternion, f32)>) -> Self {
+        let mut items = items.into_iter();
+        let Some((first, first_weight)) = items.next() else {
+            return Self::identity();
+        };
+
+        let mut real = first.real.scale(first_weight);
+        let mut dual = first.dual.scale(first_weight);
+        for (dq, weight) in items {
+            let weight = if first.real.dot(&dq.real) < 0.0 {
+                -weight
+            } else {
+                weight
+            };
+            real += dq.real.scale(weight);
+            dual += dq.dual.scale(weight);
+        }
+
+        let norm = real.norm();
+        if norm <= f32::EPSILON {
+            return Self::identity();
+        }
+        Self {
+            real: real.scale(1.0 / norm),
+            dual: dual.scale(1.0 / norm),
+        }
+    }
+}
+
+/// Skins a single position using linear blend skinning. `influences` is a set of pairs of bone
+/// indices and their weights, missing bones are ignored.
+pub fn skin_position_linear(
+    position: &Vector3<f32>,
+    bone_matrices: &[Matrix4<f32>],
+    influences: &[(usize, f32)],
+) -> Vector3<f32> {
+    influences
+        .iter()
+        .filter_map(|(index, weight)| {
+            bone_matrices.get(*index).map(|matrix| {
+                matrix
+                    .transform_point(&Point3::from(*position))
+                    .coords
+                    .scale(*weight)
+            })
+        })
+        .sum()
+}
+
+/// Skins a single position using dual-quaternion skinning. `influences` is a set of pairs of bone
+/// indices and their weights, missing bones are ignored.
+pub fn skin_position_dual_quaternion(
+    position: &Vector3<f32>,
+    bones: &[DualQuaternion],
+    influences: &[(usize, f32)],
+) -> Vector3<f32> {
+    DualQuaternion::blend(
+        influences
+            .iter()
+            .filter_map(|(index, weight)| bones.get(*index).map(|dq| (*dq, *weight))),
+    )
+    .transform_point(position)
+}
+
+/// Blends bone matrices that affect a vertex into a single transformation matrix, the same way as
+/// the vertex shader does it. `bone_matrices` must be in the form that is uploaded to shaders (see
+/// [`pack_bone_matrices`]). `influences` is a set of pairs of bone indices and their weights,
+/// missing bones are ignored.
+pub(crate) fn blend_bone_matrices(
+    bone_matrices: &[Matrix4<f32>],
+    influences: &[(usize, f32)],
+    mode: SkinningMode,
+) -> Matrix4<f32> {
+    let items = influences
+        .iter()
+        .filter_map(|(index, weight)| bone_matrices.get(*index).map(|m| (m, *weight)));
+    match mode {
+        SkinningMode::Linear => items.fold(Matrix4::zeros(), |acc, (m, weight)| acc + m * weight),
+        SkinningMode::DualQuaternion => DualQuaternion::blend(
+            items.map(|(m, weight)| (DualQuaternion::from_packed_matrix(m), weight)),
+        )
+        .to_matrix(),
+    }
+}
+
+/// Deforms vertices of `source` buffer using the given bone matrices (packed by
+/// [`pack_bone_matrices`]) and writes positions, normals and tangents to `destination` buffer. The
+/// destination buffer must have the same layout and the same amount of vertices as the source
+/// buffer (usually it is just a copy of the source buffer).
+pub(crate) fn skin_vertices(
+    source: &VertexBuffer,
+    destination: &mut VertexBuffer,
+    bone_matrices: &[Matrix4<f32>],
+    mode: SkinningMode,
+) -> Result<(), VertexFetchError> {
+    let mut destination = destination.modify();
+    for (source, mut destination) in source.iter().zip(destination.iter_mut()) {
+        let indices = source.read_4_u8(VertexAttributeUsage::BoneIndices)?;
+        let weights = source.read_4_f32(VertexAttributeUsage::BoneWeight)?;
+        let influences = [
+            (indices.x as usize, weights.x),
+            (indices.y as usize, weights.y),
+            (indices.z as usize, weights.z),
+            (indices.w as usize, weights.w),
+        ];
+        let matrix = blend_bone_matrices(bone_matrices, &influences, mode);
+        let basis = matrix.basis();
+
+        let position = source.read_3_f32(VertexAttributeUsage::Position)?;
+        destination.write_3_f32(
+            VertexAttributeUsage::Position,
+            matrix.transform_point(&Point3::from(position)).coords,
+        )?;
+
+        if let Ok(normal) = source.read_3_f32(VertexAttributeUsage::Normal) {
+            destination.write_3_f32(
+                VertexAttributeUsage::Normal,
+                (basis * normal)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(normal),
+            )?;
+        }
+
+        if let Ok(tangent) = source.read_4_f32(VertexAttributeUsage::Tangent) {
+            let xyz = (basis * tangent.xyz())
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(|| tangent.xyz());
+            destination.write_4_f32(
+                VertexAttributeUsage::Tangent,
+                Vector4::new(xyz.x, xyz.y, xyz.z, tangent.w),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Converts bone matrices into the form that is expected by shaders for the given skinning mode.
+pub(crate) fn pack_bone_matrices(
+    mode: SkinningMode,
+    bone_matrices: Vec<Matrix4<f32>>,
+) -> Vec<Matrix4<f32>> {
+    match mode {
+        SkinningMode::Linear => bone_matrices,
+        SkinningMode::DualQuaternion => bone_matrices
+            .iter()
+            .map(|matrix| DualQuaternion::from_matrix(matrix).to_packed_matrix())
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, UnitQuaternion, Vector3},
+        scene::mesh::skinning::{
+            skin_position_dual_quaternion, skin_position_linear, DualQuaternion,
+        },
+    };
+
+    // Area of a polygon in XZ plane.
+    fn cross_section_area(points: &[Vector3<f32>]) -> f32 {
+        let mut area = 0.0;
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            area += a.x * b.z - b.x * a.z;
+        }
+        area.abs() * 0.5
+    }
+
+    #[test]
+    fn test_dual_quaternion_round_trip() {
+        let matrix = Matrix4::new_translation(&Vector3::new(1.0, 2.0, 3.0))
+            * UnitQuaternion::from_euler_angles(0.3, 0.7, -0.2).to_homogeneous();
+        let dq = DualQuaternion::from_matrix(&matrix);
+        let point = Vector3::new(0.5, -1.0, 2.0);
+        let expected = matrix.transform_point(&point.into()).coords;
+        assert!((dq.transform_point(&point) - expected).norm() < 1.0e-5);
+        assert!((dq.to_matrix() - matrix).norm() < 1.0e-5);
+    }
+
+    #[test]
+    fn test_twisted_cylinder_volume() {
+        // Two-bone cylinder along Y axis, the second bone is twisted by 180 degrees.
+        let bones = [
+            Matrix4::identity(),
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 180.0f32.to_radians())
+                .to_homogeneous(),
+        ];
+        let dual_quaternions = bones.map(|m| DualQuaternion::from_matrix(&m));
+
+        // Mid-section of the cylinder is equally affected by both bones.
+        let influences = [(0, 0.5), (1, 0.5)];
+        let ring = (0..32)
+            .map(|i| {
+                let angle = i as f32 / 32.0 * std::f32::consts::TAU;
+                Vector3::new(angle.cos(), 1.0, angle.sin())
+            })
+            .collect::<Vec<_>>();
+
+        let linear = ring
+            .iter()
+            .map(|p| skin_position_linear(p, &bones, &influences))
+            .collect::<Vec<_>>();
+        let dual_quaternion = ring
+            .iter()
+            .map(|p| skin_position_dual_quaternion(p, &dual_quaternions, &influences))
+            .collect::<Vec<_>>();
+
+        let rest_area = cross_section_area(&ring);
+        let linear_area = cross_section_area(&linear);
+        let dual_quaternion_area = cross_section_area(&dual_quaternion);
+        assert!(dual_quaternion_area > linear_area);
+        assert!((dual_quaternion_area - rest_area).abs() / rest_area < 0.01);
+    }
+}
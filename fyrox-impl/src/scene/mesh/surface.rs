@@ -46,10 +46,11 @@ use crate::{
     material::{Material, MaterialResource, MaterialResourceExtension},
     resource::texture::{TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension},
     scene::{
+        collider::{ConvexHullError, ConvexShape},
         mesh::{
             buffer::{
-                TriangleBuffer, VertexAttributeUsage, VertexBuffer, VertexFetchError,
-                VertexReadTrait, VertexTrait, VertexWriteTrait,
+                TriangleBuffer, ValidationError, VertexAttributeUsage, VertexBuffer,
+                VertexFetchError, VertexReadTrait, VertexTrait, VertexWriteTrait,
             },
             vertex::StaticVertex,
         },
@@ -97,6 +98,9 @@ pub struct BlendShapesContainer {
     pub blend_shapes: Vec<BlendShape>,
     /// A volume texture that stores all blend shapes at once.
     pub blend_shape_storage: Option<TextureResource>,
+    /// Full-precision offsets of every blend shape, they're used for blending on CPU.
+    #[reflect(hidden)]
+    pub morph_targets: Vec<MorphTarget>,
 }
 
 /// A set of offsets for particular vertices.
@@ -114,7 +118,74 @@ pub struct InputBlendShapeData {
     pub tangents: FxHashMap<u32, Vector3<f16>>,
 }
 
+/// Full-precision offsets of vertices of a blend shape (also known as a morph target). Unlike the
+/// packed GPU storage of blend shapes, the offsets are kept in `f32`, which allows blending of
+/// the shapes on CPU without precision loss. See [`SurfaceData::blend_morph_targets`] for more
+/// info.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MorphTarget {
+    /// Default weight of the target in `[0; 100]` range (same as [`BlendShape::weight`]).
+    pub default_weight: f32,
+    /// A name of the target.
+    pub name: String,
+    /// An `index -> position offset` map. Could be empty if the target does not change positions.
+    pub positions: FxHashMap<u32, Vector3<f32>>,
+    /// An `index -> normal offset` map. Could be empty if the target does not change normals.
+    pub normals: FxHashMap<u32, Vector3<f32>>,
+    /// An `index -> tangent offset` map. Could be empty if the target does not change tangents.
+    pub tangents: FxHashMap<u32, Vector3<f32>>,
+}
+
+impl From<&InputBlendShapeData> for MorphTarget {
+    fn from(data: &InputBlendShapeData) -> Self {
+        fn convert(map: &FxHashMap<u32, Vector3<f16>>) -> FxHashMap<u32, Vector3<f32>> {
+            map.iter()
+                .map(|(index, offset)| (*index, offset.map(|c| c.to_f32())))
+                .collect()
+        }
+
+        Self {
+            default_weight: data.default_weight,
+            name: data.name.clone(),
+            positions: convert(&data.positions),
+            normals: convert(&data.normals),
+            tangents: convert(&data.tangents),
+        }
+    }
+}
+
+impl From<&MorphTarget> for InputBlendShapeData {
+    fn from(target: &MorphTarget) -> Self {
+        fn convert(map: &FxHashMap<u32, Vector3<f32>>) -> FxHashMap<u32, Vector3<f16>> {
+            map.iter()
+                .map(|(index, offset)| (*index, offset.map(f16::from_f32)))
+                .collect()
+        }
+
+        Self {
+            default_weight: target.default_weight,
+            name: target.name.clone(),
+            positions: convert(&target.positions),
+            normals: convert(&target.normals),
+            tangents: convert(&target.tangents),
+        }
+    }
+}
+
 impl BlendShapesContainer {
+    /// Creates a new container from the given set of morph targets. Targets are packed into a
+    /// volume texture for GPU blending, full-precision copies are kept for CPU blending.
+    pub fn from_morph_targets(base_shape: &VertexBuffer, morph_targets: Vec<MorphTarget>) -> Self {
+        let input = morph_targets
+            .iter()
+            .map(InputBlendShapeData::from)
+            .collect::<Vec<_>>();
+        Self {
+            morph_targets,
+            ..Self::from_lists(base_shape, &input)
+        }
+    }
+
     /// Packs all blend shapes into one volume texture.
     pub fn from_lists(
         base_shape: &VertexBuffer,
@@ -216,6 +287,7 @@ impl BlendShapesContainer {
                 )
                 .unwrap(),
             ),
+            morph_targets: input_blend_shapes.iter().map(MorphTarget::from).collect(),
         }
     }
 }
@@ -234,6 +306,8 @@ pub struct SurfaceData {
     pub blend_shapes_container: Option<BlendShapesContainer>,
     #[reflect(hidden)]
     pub(crate) cache_index: Arc<AtomicIndex>,
+    #[reflect(hidden)]
+    dynamic: bool,
 }
 
 impl ResourceData for SurfaceData {
@@ -263,9 +337,36 @@ impl SurfaceData {
             geometry_buffer: triangles,
             blend_shapes_container: None,
             cache_index: Arc::new(AtomicIndex::unassigned()),
+            dynamic: false,
         }
     }
 
+    /// Replaces vertices of the surface with the given ones. It is intended for procedural or
+    /// animated geometry that changes every frame. Only the changed range of the vertex buffer is
+    /// re-uploaded to the GPU and the surface is marked as dynamic (see [`Self::set_dynamic`]).
+    /// If the amount of vertices has changed, the GPU buffer is reallocated. See
+    /// [`VertexBuffer::update_vertices`] for more info.
+    pub fn update_vertices<T>(&mut self, vertices: &[T]) -> Result<(), ValidationError>
+    where
+        T: VertexTrait + Pod,
+    {
+        self.dynamic = true;
+        self.vertex_buffer.update_vertices(vertices)
+    }
+
+    /// Sets a usage hint for the renderer, that tells whether the surface data is expected to
+    /// change frequently or not. Dynamic surfaces are stored in GPU memory that is optimized for
+    /// frequent updates.
+    pub fn set_dynamic(&mut self, dynamic: bool) {
+        self.dynamic = dynamic;
+    }
+
+    /// Returns `true` if the surface data is expected to change frequently, `false` - otherwise.
+    /// See [`Self::set_dynamic`] for more info.
+    pub fn is_dynamic(&self) -> bool {
+        self.dynamic
+    }
+
     /// Applies given transform for every spatial part of the data (vertex position, normal, tangent).
     pub fn transform_geometry(&mut self, transform: &Matrix4<f32>) -> Result<(), VertexFetchError> {
         // Discard scale by inverse and transpose given transform (M^-1)^T
@@ -306,6 +407,7 @@ impl SurfaceData {
             geometry_buffer: TriangleBuffer::new(raw.triangles),
             blend_shapes_container: Default::default(),
             cache_index: Arc::new(AtomicIndex::unassigned()),
+            dynamic: false,
         }
     }
 
@@ -511,6 +613,87 @@ impl SurfaceData {
         data
     }
 
+    /// Blends morph targets of the surface on CPU and returns a new vertex buffer with the result.
+    /// `weights` are normalized, which means that `1.0` applies full offsets of a target (unlike
+    /// [`BlendShape::weight`], that uses `[0; 100]` range). Missing weights are treated as zero.
+    ///
+    /// Blending is additive, the result is `base + sum(weight_i * offset_i)`, the same way as it
+    /// is done on GPU. There is no normalization of weights, so if the sum of weights exceeds
+    /// `1.0`, the offsets will be exaggerated. Normals and tangents of the affected vertices are
+    /// re-normalized. Returns `None`, if the surface has no morph targets.
+    pub fn blend_morph_targets(&self, weights: &[f32]) -> Option<VertexBuffer> {
+        let targets = &self.blend_shapes_container.as_ref()?.morph_targets;
+        if targets.is_empty() {
+            return None;
+        }
+
+        let vertex_count = self.vertex_buffer.vertex_count() as usize;
+        let mut positions = vec![Vector3::<f32>::zeros(); vertex_count];
+        let mut normals = vec![Vector3::<f32>::zeros(); vertex_count];
+        let mut tangents = vec![Vector3::<f32>::zeros(); vertex_count];
+        for (target, weight) in targets.iter().zip(weights.iter()) {
+            if *weight == 0.0 {
+                continue;
+            }
+            for (offsets, destination) in [
+                (&target.positions, &mut positions),
+                (&target.normals, &mut normals),
+                (&target.tangents, &mut tangents),
+            ] {
+                for (index, offset) in offsets.iter() {
+                    if let Some(value) = destination.get_mut(*index as usize) {
+                        *value += offset.scale(*weight);
+                    }
+                }
+            }
+        }
+
+        let mut vertex_buffer = self.vertex_buffer.clone();
+        let mut vertex_buffer_mut = vertex_buffer.modify();
+        for (i, mut view) in vertex_buffer_mut.iter_mut().enumerate() {
+            if positions[i] != Vector3::zeros() {
+                if let Ok(position) = view.read_3_f32(VertexAttributeUsage::Position) {
+                    let _ =
+                        view.write_3_f32(VertexAttributeUsage::Position, position + positions[i]);
+                }
+            }
+            if normals[i] != Vector3::zeros() {
+                if let Ok(normal) = view.read_3_f32(VertexAttributeUsage::Normal) {
+                    let normal = (normal + normals[i])
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or(normal);
+                    let _ = view.write_3_f32(VertexAttributeUsage::Normal, normal);
+                }
+            }
+            if tangents[i] != Vector3::zeros() {
+                if let Ok(tangent) = view.read_4_f32(VertexAttributeUsage::Tangent) {
+                    let xyz = (tangent.xyz() + tangents[i])
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(|| tangent.xyz());
+                    let _ = view.write_4_f32(
+                        VertexAttributeUsage::Tangent,
+                        Vector4::new(xyz.x, xyz.y, xyz.z, tangent.w),
+                    );
+                }
+            }
+        }
+        drop(vertex_buffer_mut);
+
+        Some(vertex_buffer)
+    }
+
+    /// Computes convex hull of the vertices of the surface. See [`ConvexShape::from_points`] for
+    /// more info about degenerate cases. The result could be used directly as a collider shape via
+    /// [`crate::scene::collider::ColliderShape::Convex`].
+    pub fn to_convex_hull(&self) -> Result<ConvexShape, ConvexHullError> {
+        let points = self
+            .vertex_buffer
+            .iter()
+            .filter_map(|v| v.read_3_f32(VertexAttributeUsage::Position).ok())
+            .collect::<Vec<_>>();
+        ConvexShape::from_points(&points)
+    }
+
     /// Calculates per-face normals. This method is fast, but have very poor quality, and surface will look facet.
     pub fn calculate_normals(&mut self) -> Result<(), VertexFetchError> {
         let mut vertex_buffer_mut = self.vertex_buffer.modify();
@@ -551,6 +734,106 @@ impl SurfaceData {
         Ok(())
     }
 
+    /// Calculates per-vertex normals. Normal of a vertex is an area-weighted average of the normals
+    /// of the adjacent faces (faces that share the position of the vertex), but only of those
+    /// faces whose normal deviates from the normal of the face of the vertex by no more than the
+    /// given `smoothing_angle` (in radians). In other words, edges between faces with larger angle
+    /// between them will be hard (facet) edges. Vertices that end up with different normals in
+    /// different faces are split (duplicated). Use `0.0` to get per-face (facet) normals and
+    /// [`std::f32::consts::PI`] to get completely smooth normals. Degenerate triangles are ignored.
+    ///
+    /// Splitting changes the amount of vertices, so blend shapes (which are bound to vertex
+    /// indices) must be created after this method is called. Use [`Self::calculate_normals`] if
+    /// the vertices must stay intact.
+    pub fn calculate_smooth_normals(
+        &mut self,
+        smoothing_angle: f32,
+    ) -> Result<(), VertexFetchError> {
+        let positions = self
+            .vertex_buffer
+            .iter()
+            .map(|v| v.read_3_f32(VertexAttributeUsage::Position))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Area-weighted face normals, `None` for degenerate triangles.
+        let face_normals = self
+            .geometry_buffer
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.0.map(|i| positions[i as usize]);
+                let normal = (b - a).cross(&(c - a));
+                normal
+                    .try_normalize(f32::EPSILON)
+                    .map(|direction| (normal, direction))
+            })
+            .collect::<Vec<_>>();
+
+        // Group faces by the positions of their vertices, so the normals are smooth across
+        // vertices that have the same position, but different attributes (texture coordinates,
+        // for example).
+        let position_key = |p: &Vector3<f32>| [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()];
+        let mut adjacent_faces = FxHashMap::<[u32; 3], Vec<usize>>::default();
+        for (face, triangle) in self.geometry_buffer.iter().enumerate() {
+            if face_normals[face].is_some() {
+                for index in triangle.0 {
+                    adjacent_faces
+                        .entry(position_key(&positions[index as usize]))
+                        .or_default()
+                        .push(face);
+                }
+            }
+        }
+
+        let cos_threshold = smoothing_angle.clamp(0.0, std::f32::consts::PI).cos();
+        let mut vertex_normals = vec![None::<Vector3<f32>>; positions.len()];
+        let mut triangles = self.geometry_buffer.triangles_ref().to_vec();
+        let mut vertex_buffer = self.vertex_buffer.modify();
+        for (face, triangle) in triangles.iter_mut().enumerate() {
+            let Some((_, face_direction)) = face_normals[face] else {
+                continue;
+            };
+
+            for index in triangle.0.iter_mut() {
+                let mut sum = Vector3::default();
+                for adjacent in adjacent_faces[&position_key(&positions[*index as usize])].iter() {
+                    if let Some((normal, direction)) = face_normals[*adjacent] {
+                        if direction.dot(&face_direction) >= cos_threshold - f32::EPSILON {
+                            sum += normal;
+                        }
+                    }
+                }
+                let normal = sum.try_normalize(f32::EPSILON).unwrap_or(face_direction);
+
+                match vertex_normals[*index as usize] {
+                    None => {
+                        vertex_normals[*index as usize] = Some(normal);
+                    }
+                    Some(existing) if existing.metric_distance(&normal) <= 1.0e-4 => {}
+                    Some(_) => {
+                        // The vertex is shared with a face with different normal, split it.
+                        vertex_buffer.duplicate(*index as usize);
+                        *index = vertex_buffer.vertex_count() - 1;
+                        vertex_normals.push(Some(normal));
+                    }
+                }
+            }
+        }
+
+        for (i, normal) in vertex_normals.into_iter().enumerate() {
+            if let Some(normal) = normal {
+                vertex_buffer
+                    .get_mut(i)
+                    .unwrap()
+                    .write_3_f32(VertexAttributeUsage::Normal, normal)?;
+            }
+        }
+        drop(vertex_buffer);
+
+        self.geometry_buffer.set_triangles(triangles);
+
+        Ok(())
+    }
+
     /// Creates sphere of specified radius with given slices and stacks. The larger the `slices` and `stacks`, the smoother the sphere will be.
     /// Typical values are [16..32]. The sphere is then transformed by the given transformation matrix, which could be [`Matrix4::identity`]
     /// to not modify the sphere at all.
@@ -1351,6 +1634,11 @@ impl Surface {
         &self.data
     }
 
+    /// Sets new data for the surface.
+    pub fn set_data(&mut self, data: SurfaceResource) {
+        self.data.set_value_and_mark_modified(data);
+    }
+
     /// Returns current material of the surface.
     pub fn material(&self) -> &MaterialResource {
         &self.material
@@ -1538,3 +1826,177 @@ pub static TORUS: LazyLock<BuiltInResource<SurfaceData>> = LazyLock::new(|| {
         ),
     )
 });
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        scene::{
+            collider::{ConvexHullError, ConvexShape},
+            mesh::{
+                buffer::{VertexAttributeUsage, VertexReadTrait},
+                surface::{BlendShapesContainer, MorphTarget, SurfaceData, SurfaceResource},
+                vertex::StaticVertex,
+            },
+        },
+    };
+
+    fn normal(data: &SurfaceData, i: u32) -> Vector3<f32> {
+        data.vertex_buffer
+            .get(i as usize)
+            .unwrap()
+            .read_3_f32(VertexAttributeUsage::Normal)
+            .unwrap()
+    }
+
+    fn position(data: &SurfaceData, i: u32) -> Vector3<f32> {
+        data.vertex_buffer
+            .get(i as usize)
+            .unwrap()
+            .read_3_f32(VertexAttributeUsage::Position)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_calculate_smooth_normals() {
+        let mut cube = SurfaceData::make_cube(Matrix4::identity());
+        cube.calculate_smooth_normals(30.0f32.to_radians()).unwrap();
+        for triangle in cube.geometry_buffer.iter() {
+            let [a, b, c] = triangle.0.map(|i| position(&cube, i));
+            let face_normal = (b - a).cross(&(c - a)).normalize();
+            for i in triangle.0 {
+                assert!(normal(&cube, i).metric_distance(&face_normal) < 1.0e-5);
+            }
+        }
+
+        let mut sphere = SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity());
+        let vertex_count = sphere.vertex_buffer.vertex_count();
+        sphere
+            .calculate_smooth_normals(30.0f32.to_radians())
+            .unwrap();
+        assert_eq!(sphere.vertex_buffer.vertex_count(), vertex_count);
+        for i in 0..vertex_count {
+            let radial = position(&sphere, i).normalize();
+            assert!(normal(&sphere, i).dot(&radial).abs() > 0.99);
+        }
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let cube = SurfaceData::make_cube(Matrix4::identity());
+        let hull = cube.to_convex_hull().unwrap();
+        assert_eq!(hull.vertices.len(), 8);
+        assert_eq!(hull.faces.len(), 6);
+        for face in hull.faces.iter() {
+            assert_eq!(face.indices.len(), 4);
+            let [a, b, c] = [0, 1, 2].map(|i| hull.vertices[face.indices[i] as usize]);
+            // Faces must be facing outside.
+            assert!((b - a).cross(&(c - a)).dot(&a) > 0.0);
+        }
+        assert_eq!(hull.triangles().count(), 12);
+
+        let quad = SurfaceData::make_quad(&Matrix4::identity());
+        let hull = quad.to_convex_hull().unwrap();
+        assert_eq!(hull.vertices.len(), 4);
+        assert_eq!(hull.faces.len(), 2);
+
+        let line = [0.0, 1.0, 2.0].map(|x| Vector3::new(x, x, 0.0));
+        assert_eq!(
+            ConvexShape::from_points(&line),
+            Err(ConvexHullError::Collinear)
+        );
+    }
+
+    #[test]
+    fn test_blend_morph_targets() {
+        let mut quad = SurfaceData::make_quad(&Matrix4::identity());
+        let target_positions = [
+            Vector3::new(-0.5, 0.5, 0.25),
+            Vector3::new(0.5, 0.5, 0.0),
+            Vector3::new(0.5, -0.5, 0.0),
+            Vector3::new(-0.5, -0.75, 0.5),
+        ];
+        let target = MorphTarget {
+            name: "Bulge".to_string(),
+            positions: target_positions
+                .iter()
+                .enumerate()
+                .map(|(i, p)| (i as u32, p - position(&quad, i as u32)))
+                .filter(|(_, offset)| *offset != Vector3::zeros())
+                .collect(),
+            ..Default::default()
+        };
+        quad.blend_shapes_container = Some(BlendShapesContainer::from_morph_targets(
+            &quad.vertex_buffer,
+            vec![target],
+        ));
+
+        let blended = quad.blend_morph_targets(&[1.0]).unwrap();
+        for (view, expected) in blended.iter().zip(target_positions) {
+            assert_eq!(
+                view.read_3_f32(VertexAttributeUsage::Position).unwrap(),
+                expected
+            );
+        }
+
+        let blended = quad.blend_morph_targets(&[0.0]).unwrap();
+        for (i, view) in blended.iter().enumerate() {
+            assert_eq!(
+                view.read_3_f32(VertexAttributeUsage::Position).unwrap(),
+                position(&quad, i as u32)
+            );
+        }
+    }
+
+    #[test]
+    fn test_update_vertices() {
+        let resource = SurfaceResource::new_embedded(SurfaceData::make_cube(Matrix4::identity()));
+        let vertex_size = std::mem::size_of::<StaticVertex>();
+        let mut vertices = resource
+            .data_ref()
+            .vertex_buffer
+            .cast_data_ref::<StaticVertex>()
+            .unwrap()
+            .to_vec();
+
+        let mut data = resource.data_ref();
+        assert!(!data.is_dynamic());
+        let modifications_count = data.vertex_buffer.modifications_count();
+
+        vertices[2].position = Vector3::new(1.0, 2.0, 3.0);
+        vertices[3].position.x += 1.0;
+        data.update_vertices(&vertices).unwrap();
+        assert!(data.is_dynamic());
+        assert_eq!(
+            data.vertex_buffer.modifications_count(),
+            modifications_count + 1
+        );
+        // Only the changed vertices must be re-uploaded.
+        assert_eq!(
+            data.vertex_buffer.take_modified_range(),
+            Some(2 * vertex_size..4 * vertex_size)
+        );
+
+        // Same content - nothing to upload.
+        data.update_vertices(&vertices).unwrap();
+        assert_eq!(
+            data.vertex_buffer.modifications_count(),
+            modifications_count + 1
+        );
+        assert_eq!(data.vertex_buffer.modified_range(), None);
+
+        // Size change - the entire buffer must be re-uploaded.
+        vertices.push(StaticVertex::default());
+        data.update_vertices(&vertices).unwrap();
+        assert_eq!(data.vertex_buffer.vertex_count() as usize, vertices.len());
+        assert_eq!(
+            data.vertex_buffer.modified_range(),
+            Some(0..vertices.len() * vertex_size)
+        );
+        drop(data);
+
+        // The surface data is updated in-place, every user of the resource sees the new vertices.
+        let shared = resource.clone();
+        assert_eq!(position(&shared.data_ref(), 2), Vector3::new(1.0, 2.0, 3.0));
+    }
+}
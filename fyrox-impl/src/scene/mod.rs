@@ -28,36 +28,52 @@ pub mod accel;
 pub mod animation;
 pub mod base;
 pub mod camera;
+pub mod camera_shake;
+pub mod cloth;
 pub mod collider;
+pub mod constraint;
 pub mod debug;
 pub mod decal;
 pub mod dim2;
+pub mod fog;
+pub mod foliage;
 pub mod graph;
+pub mod interest;
 pub mod joint;
+pub mod lag_compensation;
 pub mod light;
 pub mod mesh;
 pub mod navmesh;
 pub mod node;
 pub mod particle_system;
 pub mod pivot;
+pub mod prediction;
 pub mod probe;
 pub mod ragdoll;
+pub mod replication;
 pub mod rigidbody;
+pub mod rope;
 pub mod skybox;
 pub mod sound;
+pub mod spline;
+pub mod spline_follower;
 pub mod sprite;
 pub mod terrain;
 pub mod tilemap;
 pub mod transform;
+pub mod trigger;
+pub mod vehicle;
+pub mod verlet;
+pub mod water;
 
 use crate::{
     asset::{self, io::ResourceIo, manager::ResourceManager, untyped::UntypedResource},
     core::{
-        algebra::Vector2,
+        algebra::{Vector2, Vector3},
         color::Color,
         futures::future::join_all,
         log::{Log, MessageKind},
-        pool::{Handle, Pool, Ticket},
+        pool::{Handle, ObjectOrVariant, Pool, Ticket},
         reflect::prelude::*,
         type_traits::prelude::*,
         variable::InheritableVariable,
@@ -65,14 +81,16 @@ use crate::{
         SafeLock,
     },
     engine::SerializationContext,
-    graph::NodeHandleMap,
+    graph::{NodeHandleMap, SceneGraph},
     graphics::PolygonFillMode,
     resource::texture::TextureResource,
     scene::{
         debug::SceneDrawingContext,
+        fog::FogSettings,
         graph::{Graph, GraphPerformanceStatistics, GraphUpdateSwitches},
+        light::directional::DirectionalLight,
         node::Node,
-        skybox::{SkyBox, SkyBoxKind},
+        skybox::{ProceduralSky, SkyBox, SkyBoxKind},
         sound::SoundEngine,
     },
     utils::navmesh::Navmesh,
@@ -81,6 +99,7 @@ use fxhash::FxHashSet;
 use fyrox_core::dyntype::DynTypeConstructorContainer;
 use fyrox_core::pool::PoolError;
 use std::{
+    any::TypeId,
     fmt::{Display, Formatter},
     ops::{Index, IndexMut},
     path::Path,
@@ -183,13 +202,49 @@ impl IndexMut<Handle<Navmesh>> for NavMeshContainer {
 )]
 #[type_uuid(id = "28f22fe7-22ed-47e1-ae43-779866a46cdf")]
 pub enum EnvironmentLightingSource {
-    /// Sky box of a scene will be the source of lighting.
+    /// Sky box of a scene (or a reflection probe, if the observer is inside of one) will be the source
+    /// of lighting. Prefiltered specular and irradiance maps are computed from the environment cube
+    /// map automatically. If the scene has no environment, the ambient color is used instead.
     #[default]
     SkyBox,
     /// Ambient color of the scene will be the source of lighting.
     AmbientColor,
 }
 
+/// Defines how transparent surfaces of a scene are composed with each other. By default, it is set
+/// to [`TransparencyMode::Sorted`].
+#[derive(
+    Reflect,
+    Visit,
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    AsRefStr,
+    EnumString,
+    VariantNames,
+    TypeUuidProvider,
+)]
+#[type_uuid(id = "8b0c6f2e-5d4a-4f3b-a1e7-2c9d7e6b4f10")]
+pub enum TransparencyMode {
+    /// Transparent surfaces are sorted back-to-front and blended one by one. This mode supports
+    /// any blending function, but sorting is done per-surface, so intersecting transparent surfaces
+    /// may blend incorrectly.
+    #[default]
+    Sorted,
+    /// Weighted blended order-independent transparency. Transparent surfaces (the ones that have
+    /// blending enabled in their forward render pass) are accumulated into a separate pair of
+    /// render targets and then composed over opaque geometry. The result does not depend on the
+    /// order in which the surfaces were drawn, so intersecting surfaces blend consistently. The
+    /// blending function of the materials is ignored in this mode - the surfaces are always
+    /// alpha-blended. Unlike per-pixel linked lists, the memory cost of this mode is bounded -
+    /// it requires only two additional screen-sized textures, no matter how many transparent
+    /// layers there are.
+    WeightedBlended,
+}
+
 /// Rendering options of a scene. It allows you to specify a render target to render the scene to, change its clear color, etc.
 #[derive(Debug, Visit, Reflect, PartialEq)]
 pub struct SceneRenderingOptions {
@@ -220,6 +275,15 @@ pub struct SceneRenderingOptions {
     /// by this coefficient.
     #[visit(optional)]
     pub environment_lighting_brightness: f32,
+
+    /// Fog settings of the scene. See [`FogSettings`] docs for more info.
+    #[visit(optional)]
+    pub fog: FogSettings,
+
+    /// Defines how transparent surfaces are composed with each other. See [`TransparencyMode`]
+    /// docs for more info.
+    #[visit(optional)]
+    pub transparency_mode: TransparencyMode,
 }
 
 impl Default for SceneRenderingOptions {
@@ -231,6 +295,8 @@ impl Default for SceneRenderingOptions {
             ambient_lighting_color: Color::opaque(100, 100, 100),
             environment_lighting_source: Default::default(),
             environment_lighting_brightness: 1.0,
+            fog: Default::default(),
+            transparency_mode: Default::default(),
         }
     }
 }
@@ -244,6 +310,41 @@ impl Clone for SceneRenderingOptions {
             ambient_lighting_color: self.ambient_lighting_color,
             environment_lighting_source: self.environment_lighting_source,
             environment_lighting_brightness: self.environment_lighting_brightness,
+            fog: self.fog.clone(),
+            transparency_mode: self.transparency_mode,
+        }
+    }
+}
+
+/// Binding of a directional light to the sun of a scene. See [`Scene::set_sun`] docs for more info.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct SunBinding {
+    /// A handle of the directional light that represents the sun.
+    pub light: Handle<DirectionalLight>,
+    /// Intensity of the light when the sun is high. The actual intensity of the light decreases
+    /// when the sun approaches the horizon.
+    pub intensity: f32,
+    /// Minimal amount of time (in seconds) between two updates of procedural sky, when the sun
+    /// moves. The sky is baked on CPU, so frequent updates of high-resolution skies could be slow.
+    #[visit(optional)]
+    pub sky_update_interval: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    time_since_sky_update: f32,
+}
+
+impl SunBinding {
+    /// Default value of [`Self::sky_update_interval`].
+    pub const DEFAULT_SKY_UPDATE_INTERVAL: f32 = 0.25;
+}
+
+impl Default for SunBinding {
+    fn default() -> Self {
+        Self {
+            light: Default::default(),
+            intensity: 1.0,
+            sky_update_interval: Self::DEFAULT_SKY_UPDATE_INTERVAL,
+            time_since_sky_update: 0.0,
         }
     }
 }
@@ -270,6 +371,9 @@ pub struct Scene {
     #[reflect(setter = "set_skybox")]
     sky_box: InheritableVariable<Option<SkyBox>>,
 
+    #[reflect(hidden)]
+    sun: Option<SunBinding>,
+
     /// Whether the scene will be updated and rendered or not. Default is true.
     /// This flag allowing you to build a scene manager for your game. For example,
     /// you may have a scene for menu and one per level. Menu's scene is persistent,
@@ -295,6 +399,7 @@ impl Default for Scene {
             performance_statistics: Default::default(),
             enabled: true.into(),
             sky_box: Some(SkyBoxKind::built_in_skybox().clone()).into(),
+            sun: None,
         }
     }
 }
@@ -334,6 +439,57 @@ impl Display for PerformanceStatistics {
     }
 }
 
+/// Defines what to do with handles that point from an exported subtree to the nodes outside of it.
+/// See [`Scene::extract_subtree`] for more info.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExternalReferencePolicy {
+    /// Such handles will be set to [`Handle::NONE`].
+    Null,
+    /// Export will fail with [`SubtreeExportError::ExternalReference`] error.
+    Error,
+}
+
+/// An error that may occur during subtree export.
+#[derive(Debug)]
+pub enum SubtreeExportError {
+    /// The root of the subtree does not exist.
+    InvalidRoot(Handle<Node>),
+    /// A node of the subtree references a node outside of it.
+    ExternalReference {
+        /// Name of the node that has the reference.
+        node: String,
+        /// A handle of the referenced node.
+        target: Handle<Node>,
+    },
+    /// Serialization error.
+    Visit(VisitError),
+}
+
+impl Display for SubtreeExportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubtreeExportError::InvalidRoot(handle) => {
+                write!(f, "Subtree root {handle} does not exist!")
+            }
+            SubtreeExportError::ExternalReference { node, target } => {
+                write!(
+                    f,
+                    "Node {node} references node {target}, which is outside of the subtree!"
+                )
+            }
+            SubtreeExportError::Visit(err) => {
+                write!(f, "Unable to save the subtree: {err:?}")
+            }
+        }
+    }
+}
+
+impl From<VisitError> for SubtreeExportError {
+    fn from(err: VisitError) -> Self {
+        Self::Visit(err)
+    }
+}
+
 /// Scene loader.
 pub struct SceneLoader {
     scene: Scene,
@@ -481,6 +637,7 @@ impl Scene {
             performance_statistics: Default::default(),
             enabled: true.into(),
             sky_box: Some(SkyBoxKind::built_in_skybox().clone()).into(),
+            sun: None,
         }
     }
 
@@ -504,6 +661,100 @@ impl Scene {
         std::mem::replace(self.sky_box.get_value_mut_and_mark_modified(), new)
     }
 
+    /// Binds the given directional light to the sun of the scene. Once bound, the direction of the
+    /// light drives the sun of procedural sky (if any) and the color and intensity of the light
+    /// are changed each frame depending on the elevation of the sun - the light gets warmer and
+    /// dimmer when the sun approaches the horizon. The current intensity of a newly bound light is
+    /// used as the intensity at noon, binding the same light again keeps the settings of the
+    /// binding (use [`Self::sun_binding_mut`] to change them). [`Handle::NONE`] removes the
+    /// binding. The binding is removed automatically if the light is deleted from the scene.
+    /// Returns the previously bound light.
+    pub fn set_sun(&mut self, light: Handle<DirectionalLight>) -> Handle<DirectionalLight> {
+        let prev_binding = self.sun.take();
+        let prev = prev_binding.as_ref().map(|b| b.light).unwrap_or_default();
+        if let Ok(light_ref) = self.graph.try_get(light) {
+            let mut binding = match prev_binding {
+                // The intensity of the light is already affected by the sun elevation.
+                Some(binding) if binding.light == light => binding,
+                _ => SunBinding {
+                    light,
+                    intensity: light_ref.base_light_ref().intensity(),
+                    ..Default::default()
+                },
+            };
+            // The sky must follow the new light immediately.
+            binding.time_since_sky_update = binding.sky_update_interval;
+            self.sun = Some(binding);
+            self.update_sun(0.0);
+        }
+        prev
+    }
+
+    /// Returns a handle of the directional light that is bound to the sun of the scene.
+    pub fn sun(&self) -> Handle<DirectionalLight> {
+        self.sun.as_ref().map(|b| b.light).unwrap_or_default()
+    }
+
+    /// Returns the sun binding (if any). See [`Self::set_sun`] docs for more info.
+    pub fn sun_binding(&self) -> Option<&SunBinding> {
+        self.sun.as_ref()
+    }
+
+    /// Returns the sun binding (if any), it could be used to change the intensity of the sun or
+    /// the update interval of the sky. See [`Self::set_sun`] docs for more info.
+    pub fn sun_binding_mut(&mut self) -> Option<&mut SunBinding> {
+        self.sun.as_mut()
+    }
+
+    fn update_sun(&mut self, dt: f32) {
+        let Some(binding) = self.sun.as_mut() else {
+            return;
+        };
+
+        let Ok(light) = self.graph.try_get_mut(binding.light) else {
+            Log::warn(format!(
+                "The sun light {} does not exist anymore, the sun binding is removed.",
+                binding.light
+            ));
+            self.sun = None;
+            return;
+        };
+
+        let sun_direction = light.up_vector();
+        let color = ProceduralSky::sun_light_color(sun_direction);
+        let intensity = binding.intensity * ProceduralSky::sun_light_intensity(sun_direction);
+        let base_light = light.base_light_mut();
+        if base_light.color() != color {
+            base_light.set_color(color);
+        }
+        if base_light.intensity() != intensity {
+            base_light.set_intensity(intensity);
+        }
+
+        // Regenerate the sky only if the sun has moved noticeably and not more often than
+        // the update interval allows.
+        binding.time_since_sky_update += dt;
+        if binding.time_since_sky_update < binding.sky_update_interval {
+            return;
+        }
+        let sun_direction = sun_direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        let procedural = self
+            .sky_box
+            .as_ref()
+            .and_then(|s| s.procedural())
+            .filter(|p| p.normalized_sun_direction().metric_distance(&sun_direction) > 1.0e-3)
+            .cloned();
+        if let Some(mut procedural) = procedural {
+            binding.time_since_sky_update = 0.0;
+            procedural.set_sun_direction(sun_direction);
+            if let Some(skybox) = self.skybox_mut() {
+                skybox.set_procedural(Some(procedural));
+            }
+        }
+    }
+
     /// Synchronizes the state of the scene with external resources.
     pub fn resolve(&mut self) {
         Log::writeln(MessageKind::Information, "Starting resolve...");
@@ -531,9 +782,25 @@ impl Scene {
     /// no need to call it directly, engine automatically updates all available scenes.
     pub fn update(&mut self, frame_size: Vector2<f32>, dt: f32, switches: GraphUpdateSwitches) {
         self.graph.update(frame_size, dt, switches);
+        self.update_sun(dt);
         self.performance_statistics.graph = self.graph.performance_statistics.clone();
     }
 
+    /// Performs single update tick without a window, which is useful for dedicated servers that run
+    /// the simulation without rendering. It updates physics, animations, transforms and every graph
+    /// node in exactly the same way as [`Self::update`] does, but it does not need the frame size
+    /// and never touches render targets or any other GPU resources. Cameras are updated as if the
+    /// frame size is `1x1` pixel, the same size is used by the engine when it runs without a
+    /// graphics context.
+    ///
+    /// ## Scripts
+    ///
+    /// Scripts and plugins are processed by the engine, not by the scene. Use
+    /// [`crate::engine::Engine::tick_headless`] to advance them along with every scene.
+    pub fn tick_headless(&mut self, dt: f32) {
+        self.update(Vector2::new(1.0, 1.0), dt, Default::default());
+    }
+
     /// Creates deep copy of a scene, filter predicate allows you to filter out nodes
     /// by your criteria.
     pub fn clone_ex<F, Pre, Post>(
@@ -557,6 +824,14 @@ impl Scene {
             post_process_callback,
         );
 
+        let sun = self.sun.clone().and_then(|mut binding| {
+            let mut light = binding.light.to_base();
+            old_new_map.try_map(&mut light).then(|| {
+                binding.light = light.to_variant();
+                binding
+            })
+        });
+
         (
             Self {
                 graph,
@@ -565,6 +840,7 @@ impl Scene {
                 performance_statistics: Default::default(),
                 enabled: self.enabled.clone(),
                 sky_box: self.sky_box.clone(),
+                sun,
             },
             old_new_map,
         )
@@ -581,6 +857,123 @@ impl Scene {
         )
     }
 
+    /// Creates a standalone scene from the given node and all its descendants. The node will be
+    /// attached to the root of the new scene with its local transform. Every descendant is copied
+    /// with all its data, which includes resources and physics bodies/colliders. Scene settings
+    /// (rendering options, skybox, physics settings) are copied as well, the sun binding is kept
+    /// only if the sun is the part of the subtree.
+    ///
+    /// Handles that point from the subtree to the nodes outside of it cannot be preserved. The
+    /// `policy` defines whether such handles will be set to [`Handle::NONE`] or the method will
+    /// fail with [`SubtreeExportError::ExternalReference`] error.
+    pub fn extract_subtree(
+        &self,
+        root: Handle<impl ObjectOrVariant<Node>>,
+        policy: ExternalReferencePolicy,
+    ) -> Result<Scene, SubtreeExportError> {
+        let root = root.to_base();
+        if self.graph.try_get_node(root).is_err() {
+            return Err(SubtreeExportError::InvalidRoot(root));
+        }
+
+        let subtree = self
+            .graph
+            .traverse_handle_iter(root)
+            .collect::<FxHashSet<_>>();
+
+        for handle in subtree.iter() {
+            let node = &self.graph[*handle];
+            let mut external = None;
+            (node as &dyn Reflect).apply_recursively(
+                &mut |object| {
+                    object.as_any(&mut |any| {
+                        if let Some(target) = any.downcast_ref::<Handle<Node>>() {
+                            if target.is_some() && !subtree.contains(target) {
+                                external.get_or_insert(*target);
+                            }
+                        }
+                    })
+                },
+                &[TypeId::of::<UntypedResource>()],
+            );
+            if let Some(target) = external {
+                match policy {
+                    ExternalReferencePolicy::Error => {
+                        return Err(SubtreeExportError::ExternalReference {
+                            node: node.name_owned(),
+                            target,
+                        })
+                    }
+                    ExternalReferencePolicy::Null => Log::warn(format!(
+                        "Node {} references nodes outside of the exported subtree, \
+                        such references will be set to NONE.",
+                        node.name()
+                    )),
+                }
+            }
+        }
+
+        let mut scene = Scene::new();
+        scene.graph.physics = self.graph.physics.clone();
+        scene.graph.physics2d = self.graph.physics2d.clone();
+        scene.rendering_options = self.rendering_options.clone();
+        scene.sky_box = self.sky_box.clone();
+        scene.enabled = self.enabled.clone();
+
+        let (_, old_new_map) = self.graph.copy_node(
+            root,
+            &mut scene.graph,
+            false,
+            &mut |_, _| true,
+            &mut |_, node| {
+                // External handles must be cleared before remapping, otherwise they could point
+                // to random nodes of the new graph.
+                (node as &mut dyn Reflect).apply_recursively_mut(
+                    &mut |object| {
+                        object.as_any_mut(&mut |any| {
+                            if let Some(target) = any.downcast_mut::<Handle<Node>>() {
+                                if !subtree.contains(target) {
+                                    *target = Handle::NONE;
+                                }
+                            }
+                        })
+                    },
+                    &[TypeId::of::<UntypedResource>()],
+                );
+            },
+            &mut |_, _, _| {},
+        );
+
+        scene.sun = self.sun.clone().and_then(|mut binding| {
+            let mut light = binding.light.to_base();
+            old_new_map.try_map(&mut light).then(|| {
+                binding.light = light.to_variant();
+                binding
+            })
+        });
+
+        scene.graph.update_hierarchical_data();
+
+        Ok(scene)
+    }
+
+    /// Saves the given node and all its descendants into a standalone scene file, that could be
+    /// loaded on its own. It is a shortcut for [`Self::extract_subtree`] with
+    /// [`ExternalReferencePolicy::Null`] policy: handles that point outside of the subtree will
+    /// be set to [`Handle::NONE`] and a warning will be written to the log. Use
+    /// [`Self::extract_subtree`] with [`ExternalReferencePolicy::Error`] policy to fail instead.
+    pub fn export_subtree(
+        &self,
+        root: Handle<impl ObjectOrVariant<Node>>,
+        path: &Path,
+    ) -> Result<(), SubtreeExportError> {
+        let mut scene = self.extract_subtree(root, ExternalReferencePolicy::Null)?;
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor)?;
+        visitor.save_binary_to_file(path)?;
+        Ok(())
+    }
+
     fn visit(&mut self, region_name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut region = visitor.enter_region(region_name)?;
 
@@ -589,6 +982,7 @@ impl Scene {
         self.rendering_options
             .visit("RenderingOptions", &mut region)?;
         self.sky_box.visit("SkyBox", &mut region)?;
+        let _ = self.sun.visit("Sun", &mut region);
 
         Ok(())
     }
@@ -751,3 +1145,300 @@ impl IndexMut<Handle<Scene>> for SceneContainer {
         &mut self.pool[index]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{io::FsResourceIo, manager::ResourceManager, untyped::ResourceKind},
+        core::{
+            algebra::{Matrix4, UnitQuaternion, Vector2, Vector3},
+            futures::executor::block_on,
+            pool::Handle,
+            uuid::Uuid,
+        },
+        engine::{self, SerializationContext},
+        graph::SceneGraph,
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            light::{directional::DirectionalLightBuilder, BaseLightBuilder},
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                Mesh, MeshBuilder,
+            },
+            pivot::PivotBuilder,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            skybox::{ProceduralSky, SkyBox},
+            transform::TransformBuilder,
+            ExternalReferencePolicy, Scene, SceneLoader, SubtreeExportError,
+        },
+    };
+    use std::{fs, path::Path, sync::Arc};
+
+    // Rotates the light so that the sun has the given elevation.
+    fn sun_rotation(elevation_degrees: f32) -> UnitQuaternion<f32> {
+        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), (90.0 - elevation_degrees).to_radians())
+    }
+
+    #[test]
+    fn test_sun_binding() {
+        let mut scene = Scene::new();
+        scene.set_skybox(Some(
+            SkyBox::from_procedural(ProceduralSky {
+                resolution: 4,
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+
+        let light = DirectionalLightBuilder::new(
+            BaseLightBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_rotation(sun_rotation(80.0))
+                        .build(),
+                ),
+            )
+            .with_intensity(2.0),
+        )
+        .build(&mut scene.graph);
+
+        scene.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        scene.set_sun(light);
+        assert_eq!(scene.sun(), light);
+        scene.sun_binding_mut().unwrap().sky_update_interval = 0.0;
+
+        let sun_direction = scene.graph[light].up_vector();
+        let procedural = scene.skybox_ref().unwrap().procedural().unwrap();
+        assert!((procedural.normalized_sun_direction() - sun_direction).norm() < 1.0e-3);
+        let noon_color = scene.graph[light].base_light_ref().color();
+        let noon_intensity = scene.graph[light].base_light_ref().intensity();
+
+        // Lower the sun towards the horizon.
+        scene.graph[light]
+            .local_transform_mut()
+            .set_rotation(sun_rotation(3.0));
+        scene.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        let sunset_color = scene.graph[light].base_light_ref().color();
+        let sunset_intensity = scene.graph[light].base_light_ref().intensity();
+        let warmth = |r: u8, b: u8| r as f32 / (b as f32).max(1.0);
+        assert!(warmth(sunset_color.r, sunset_color.b) > warmth(noon_color.r, noon_color.b));
+        assert!(sunset_color.b < noon_color.b);
+        assert!(sunset_intensity < noon_intensity);
+        assert!(noon_intensity <= 2.0);
+
+        // The sky follows the light.
+        let procedural = scene.skybox_ref().unwrap().procedural().unwrap();
+        let sun_direction = scene.graph[light].up_vector();
+        assert!((procedural.normalized_sun_direction() - sun_direction).norm() < 1.0e-3);
+
+        // Binding the same light again keeps the intensity at noon, even though the light is
+        // dimmed now.
+        scene.set_sun(light);
+        assert_eq!(scene.sun_binding().unwrap().intensity, 2.0);
+        assert_eq!(scene.sun_binding().unwrap().sky_update_interval, 0.0);
+
+        // Removal of the light removes the binding.
+        scene.graph.remove_node(light);
+        scene.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert!(scene.sun().is_none());
+        assert!(scene.sun_binding().is_none());
+    }
+
+    #[test]
+    fn test_sun_updates_sky_in_place_with_interval() {
+        let mut scene = Scene::new();
+        scene.set_skybox(Some(
+            SkyBox::from_procedural(ProceduralSky {
+                resolution: 4,
+                ..Default::default()
+            })
+            .unwrap(),
+        ));
+        let light = DirectionalLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_rotation(sun_rotation(60.0))
+                    .build(),
+            ),
+        ))
+        .build(&mut scene.graph);
+        scene.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        scene.set_sun(light);
+        scene.sun_binding_mut().unwrap().sky_update_interval = 0.5;
+
+        let cubemap = scene.skybox_ref().unwrap().cubemap().unwrap();
+        let modifications = cubemap.data_ref().modifications_count();
+        let sky_sun_direction = |scene: &Scene| {
+            scene
+                .skybox_ref()
+                .unwrap()
+                .procedural()
+                .unwrap()
+                .sun_direction
+        };
+        let initial_direction = sky_sun_direction(&scene);
+
+        // The sun moves every frame, but the sky is updated only once per interval.
+        for (i, elevation) in [50.0, 40.0, 30.0].into_iter().enumerate() {
+            scene.graph[light]
+                .local_transform_mut()
+                .set_rotation(sun_rotation(elevation));
+            scene.update(Vector2::new(1.0, 1.0), 0.2, Default::default());
+            if i < 2 {
+                assert_eq!(sky_sun_direction(&scene), initial_direction);
+            }
+        }
+        let sun_direction = scene.graph[light].up_vector();
+        assert!((sky_sun_direction(&scene) - sun_direction).norm() < 1.0e-3);
+
+        // The same cube map is re-baked, no new textures are created.
+        let skybox = scene.skybox_ref().unwrap();
+        assert_eq!(skybox.cubemap_ref(), Some(&cubemap));
+        assert_eq!(cubemap.data_ref().modifications_count(), modifications + 1);
+
+        // Clones of the sky box do not share the cube map, that is updated in place.
+        assert_ne!(skybox.clone().cubemap_ref(), Some(&cubemap));
+    }
+
+    #[test]
+    fn test_export_subtree() {
+        let mut scene = Scene::new();
+        let outside =
+            PivotBuilder::new(BaseBuilder::new().with_name("Outside")).build(&mut scene.graph);
+        let bone = PivotBuilder::new(BaseBuilder::new().with_name("Bone")).build(&mut scene.graph);
+        let mesh = MeshBuilder::new(
+            BaseBuilder::new().with_name("Mesh").with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(3.0, 2.0, 1.0))
+                    .build(),
+            ),
+        )
+        .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_ok(
+            Uuid::new_v4(),
+            ResourceKind::Embedded,
+            SurfaceData::make_cone(16, 1.0, 1.0, &Matrix4::identity()),
+        ))
+        .with_bones(vec![bone, outside])
+        .build()])
+        .build(&mut scene.graph);
+        scene.graph.link_nodes(bone, mesh);
+        let root = PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("Subtree")
+                .with_child(mesh)
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 0.0, 0.0))
+                        .build(),
+                ),
+        )
+        .build(&mut scene.graph);
+        let parent =
+            PivotBuilder::new(BaseBuilder::new().with_name("Parent")).build(&mut scene.graph);
+        scene.graph.link_nodes(root, parent);
+
+        assert!(matches!(
+            scene.extract_subtree(root, ExternalReferencePolicy::Error),
+            Err(SubtreeExportError::ExternalReference { ref node, target })
+                if node == "Mesh" && target == outside
+        ));
+
+        let dir = Path::new("test_export_subtree");
+        if !dir.exists() {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let path = dir.join("subtree.rgs");
+        scene.export_subtree(root, &path).unwrap();
+
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        resource_manager
+            .state()
+            .resource_registry
+            .lock()
+            .set_path(dir.join("resources.registry"));
+        engine::initialize_resource_manager_loaders(
+            &resource_manager,
+            Arc::new(SerializationContext::new()),
+            Default::default(),
+            Default::default(),
+        );
+        resource_manager.update_or_load_registry();
+
+        let loaded = block_on(
+            block_on(SceneLoader::from_file(
+                &path,
+                &FsResourceIo,
+                Arc::new(SerializationContext::new()),
+                Default::default(),
+                resource_manager,
+            ))
+            .unwrap()
+            .0
+            .finish(),
+        );
+        let graph = &loaded.graph;
+
+        // The subtree is attached to the root, other nodes are not exported.
+        let root_children = graph[graph.get_root()].children();
+        assert_eq!(root_children.len(), 1);
+        let root = root_children[0];
+        assert_eq!(graph[root].name(), "Subtree");
+        assert_eq!(
+            **graph[root].local_transform().position(),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+        assert!(graph.find_by_name_from_root("Outside").is_none());
+        assert!(graph.find_by_name_from_root("Parent").is_none());
+        assert_eq!(graph.node_count(), 4);
+
+        let (mesh, _) = graph.find_by_name(root, "Mesh").unwrap();
+        let (bone, _) = graph.find_by_name(root, "Bone").unwrap();
+        assert_eq!(graph[mesh].parent(), root);
+        assert_eq!(
+            **graph[mesh].local_transform().position(),
+            Vector3::new(3.0, 2.0, 1.0)
+        );
+
+        // Internal references are kept, external ones are cleared.
+        let surface = &graph[mesh].cast::<Mesh>().unwrap().surfaces()[0];
+        assert_eq!(surface.bones(), &[bone, Handle::NONE]);
+    }
+
+    #[test]
+    fn test_tick_headless() {
+        let make_scene = || {
+            let mut scene = Scene::new();
+            let body = RigidBodyBuilder::new(
+                BaseBuilder::new()
+                    .with_local_transform(
+                        TransformBuilder::new()
+                            .with_local_position(Vector3::new(0.0, 10.0, 0.0))
+                            .build(),
+                    )
+                    .with_child(
+                        ColliderBuilder::new(BaseBuilder::new())
+                            .with_shape(ColliderShape::ball(0.5))
+                            .build(&mut scene.graph),
+                    ),
+            )
+            .with_body_type(RigidBodyType::Dynamic)
+            .build(&mut scene.graph);
+            (scene, body)
+        };
+
+        let (mut headless, headless_body) = make_scene();
+        let (mut full, full_body) = make_scene();
+        for _ in 0..60 {
+            headless.tick_headless(1.0 / 60.0);
+            full.update(Vector2::new(1920.0, 1080.0), 1.0 / 60.0, Default::default());
+        }
+
+        let headless_position = **headless.graph[headless_body].local_transform().position();
+        let full_position = **full.graph[full_body].local_transform().position();
+        assert!(headless_position.y < 10.0);
+        assert_eq!(headless_position, full_position);
+    }
+}
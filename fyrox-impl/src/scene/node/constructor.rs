@@ -24,12 +24,19 @@ use crate::{
     graph::constructor::{GraphNodeConstructor, GraphNodeConstructorContainer},
     scene::{
         self,
-        animation::{absm::AnimationBlendingStateMachine, AnimationPlayer},
+        animation::{
+            absm::AnimationBlendingStateMachine, sequence::SequencePlayer, AnimationPlayer,
+        },
         camera::Camera,
+        cloth::Cloth,
+        constraint::{attach::AttachConstraint, look_at::LookAtConstraint},
         decal::Decal,
         dim2::{self, rectangle::Rectangle},
+        foliage::Foliage,
         graph::Graph,
-        light::{directional::DirectionalLight, point::PointLight, spot::SpotLight},
+        light::{
+            area::AreaLight, directional::DirectionalLight, point::PointLight, spot::SpotLight,
+        },
         mesh::Mesh,
         navmesh::NavigationalMesh,
         node::Node,
@@ -37,10 +44,16 @@ use crate::{
         pivot::Pivot,
         probe::ReflectionProbe,
         ragdoll::Ragdoll,
-        sound::{listener::Listener, Sound},
+        rope::Rope,
+        sound::{listener::Listener, reverb_zone::ReverbZone, Sound},
+        spline::Spline,
+        spline_follower::SplineFollower,
         sprite::Sprite,
         terrain::Terrain,
         tilemap::TileMap,
+        trigger::Trigger,
+        vehicle::Vehicle,
+        water::WaterVolume,
     },
 };
 
@@ -61,10 +74,12 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<DirectionalLight>();
     container.add::<PointLight>();
     container.add::<SpotLight>();
+    container.add::<AreaLight>();
     container.add::<Mesh>();
     container.add::<ParticleSystem>();
     container.add::<Sound>();
     container.add::<Listener>();
+    container.add::<ReverbZone>();
     container.add::<Camera>();
     container.add::<scene::collider::Collider>();
     container.add::<Decal>();
@@ -75,10 +90,21 @@ pub fn new_node_constructor_container() -> NodeConstructorContainer {
     container.add::<Terrain>();
     container.add::<AnimationPlayer>();
     container.add::<AnimationBlendingStateMachine>();
+    container.add::<SequencePlayer>();
     container.add::<NavigationalMesh>();
     container.add::<Ragdoll>();
     container.add::<TileMap>();
     container.add::<ReflectionProbe>();
+    container.add::<Trigger>();
+    container.add::<WaterVolume>();
+    container.add::<Vehicle>();
+    container.add::<Spline>();
+    container.add::<SplineFollower>();
+    container.add::<LookAtConstraint>();
+    container.add::<AttachConstraint>();
+    container.add::<Cloth>();
+    container.add::<Rope>();
+    container.add::<Foliage>();
 
     container
 }
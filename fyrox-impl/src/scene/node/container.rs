@@ -23,13 +23,14 @@
 
 use crate::{
     core::{
+        log::Log,
         pool::PayloadContainer,
         reflect::prelude::*,
         uuid::Uuid,
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::SerializationContext,
-    scene::node::Node,
+    scene::{base::Base, node::Node, pivot::Pivot},
 };
 use fyrox_core::visitor::error::VisitError;
 
@@ -49,16 +50,70 @@ fn read_node(name: &str, visitor: &mut Visitor) -> Result<Node, VisitError> {
         .get::<SerializationContext>()
         .expect("Visitor environment must contain serialization context!");
 
-    let mut node = serialization_context
-        .node_constructors
-        .try_create(&id)
-        .ok_or_else(|| VisitError::User(format!("Unknown node type uuid {id}!")))?;
+    let Some(mut node) = serialization_context.node_constructors.try_create(&id) else {
+        Log::warn(format!(
+            "Unknown node type uuid {id}! The node will be loaded as a pivot, only its base \
+            properties (name, transform, hierarchy, etc.) will be preserved."
+        ));
+
+        return read_unknown_node("NodeData", &mut region);
+    };
 
     node.visit("NodeData", &mut region)?;
 
     Ok(node)
 }
 
+/// Reads base properties of a node of unknown type (for example, a node saved by a newer version
+/// of the engine, or a node from a plugin that is not loaded) and puts them in a pivot, so the
+/// rest of the scene can still be loaded.
+fn read_unknown_node(name: &str, visitor: &mut Visitor) -> Result<Node, VisitError> {
+    let mut node = Node::new(Pivot::default());
+
+    if !visit_nested_base(&mut **node, name, visitor)? {
+        Log::err(format!(
+            "Unable to find base properties of a node of unknown type in {}! The node will be \
+            replaced with an empty pivot.",
+            visitor.breadcrumbs()
+        ));
+    }
+
+    Ok(node)
+}
+
+fn is_base_region(name: &str, visitor: &mut Visitor) -> Result<bool, VisitError> {
+    let region = visitor.enter_region(name)?;
+    Ok(region.has_region("Transform") && region.has_region("Children"))
+}
+
+/// Searches for the region with base properties of a node and reads them. Some nodes (pivots, for
+/// example) store base properties directly, the others store them in a nested region at any depth
+/// (lights, for example, store them in `BaseLight > Base`). Returns `false` if there is no such
+/// region.
+fn visit_nested_base(
+    base: &mut Base,
+    name: &str,
+    visitor: &mut Visitor,
+) -> Result<bool, VisitError> {
+    if is_base_region(name, visitor)? {
+        base.visit(name, visitor)?;
+        return Ok(true);
+    }
+
+    let mut region = visitor.enter_region(name)?;
+    let names = region
+        .region_names()
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    for name in names {
+        if visit_nested_base(base, &name, &mut region)? {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
 fn write_node(name: &str, node: &mut Node, visitor: &mut Visitor) -> VisitResult {
     let mut region = visitor.enter_region(name)?;
 
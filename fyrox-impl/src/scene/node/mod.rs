@@ -112,6 +112,8 @@ pub struct UpdateContext<'a> {
     pub frame_size: Vector2<f32>,
     /// A time that have passed since last update call.
     pub dt: f32,
+    /// A multiplier for the time step of animations. See [`Graph::set_animation_time_scale`] for more info.
+    pub animation_time_scale: f32,
     /// A reference to a pool with nodes from a scene graph.
     pub nodes: &'a mut NodePool,
     /// A mutable reference to 3D physics world.
@@ -669,6 +671,7 @@ mod test {
         asset::manager::ResourceManager,
         core::{
             algebra::{Matrix4, Vector3},
+            dyntype::DynTypeConstructorContainer,
             futures::executor::block_on,
             impl_component_provider,
             reflect::prelude::*,
@@ -681,13 +684,17 @@ mod test {
         resource::model::{Model, ModelResourceExtension},
         scene::{
             base::BaseBuilder,
+            light::{
+                point::{PointLight, PointLightBuilder},
+                BaseLightBuilder,
+            },
             mesh::{
                 surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
-                MeshBuilder,
+                Mesh, MeshBuilder,
             },
             pivot::PivotBuilder,
             transform::TransformBuilder,
-            Scene,
+            Scene, SceneLoader,
         },
         script::ScriptTrait,
     };
@@ -712,6 +719,18 @@ mod test {
 
     impl ScriptTrait for MyScript {}
 
+    #[derive(Debug, Clone, Reflect, Visit, Default, PartialEq)]
+    struct LootTable {
+        items: Vec<String>,
+        drop_chance: f32,
+    }
+
+    impl TypeUuidProvider for LootTable {
+        fn type_uuid() -> Uuid {
+            uuid!("5b4d2a0c-3f4e-4d59-9c53-8a3d4e0b1f27")
+        }
+    }
+
     fn create_scene() -> Scene {
         let mut scene = Scene::new();
 
@@ -875,4 +894,196 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_user_data_round_trip() {
+        let loot = LootTable {
+            items: vec!["Sword".to_string(), "Shield".to_string()],
+            drop_chance: 0.25,
+        };
+
+        let mut scene = Scene::new();
+        let chest =
+            PivotBuilder::new(BaseBuilder::new().with_name("Chest")).build(&mut scene.graph);
+        assert!(scene.graph[chest].set_user_data(loot.clone()).is_none());
+        assert_eq!(scene.graph[chest].user_data_ref::<LootTable>(), Some(&loot));
+        assert!(scene.graph[chest].user_data_ref::<MyScript>().is_none());
+        scene.graph[chest]
+            .user_data_mut::<LootTable>()
+            .unwrap()
+            .drop_chance = 0.5;
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let load = |constructors: DynTypeConstructorContainer| {
+            let mut visitor = Visitor::load_from_memory(&data).unwrap();
+            let loader = SceneLoader::load(
+                "Scene",
+                Arc::new(SerializationContext::new()),
+                Arc::new(constructors),
+                ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default())),
+                &mut visitor,
+                None,
+            )
+            .unwrap();
+            block_on(loader.finish())
+        };
+
+        let constructors = DynTypeConstructorContainer::default();
+        constructors.add::<LootTable>("LootTable");
+        let loaded = load(constructors);
+        let chest = loaded.graph.find_by_name_from_root("Chest").unwrap().1;
+        assert_eq!(
+            chest.user_data_ref::<LootTable>(),
+            Some(&LootTable {
+                drop_chance: 0.5,
+                ..loot
+            })
+        );
+
+        // Unregistered data is discarded, but the node is still loaded.
+        let loaded = load(DynTypeConstructorContainer::default());
+        let chest = loaded.graph.find_by_name_from_root("Chest").unwrap().1;
+        assert!(!chest.has_user_data());
+    }
+
+    fn load_scene(data: &[u8]) -> Scene {
+        let mut visitor = Visitor::load_from_memory(data).unwrap();
+        let loader = SceneLoader::load(
+            "Scene",
+            Arc::new(SerializationContext::new()),
+            Arc::new(Default::default()),
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default())),
+            &mut visitor,
+            None,
+        )
+        .unwrap();
+        block_on(loader.finish())
+    }
+
+    #[test]
+    fn test_tag_round_trip() {
+        let mut scene = Scene::new();
+        PivotBuilder::new(
+            BaseBuilder::new()
+                .with_name("SpawnPoint")
+                .with_tag("spawn_point".to_string()),
+        )
+        .build(&mut scene.graph);
+        PivotBuilder::new(BaseBuilder::new().with_name("Untagged")).build(&mut scene.graph);
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let text = visitor.save_ascii_to_string();
+        assert!(text.contains("Tag["));
+
+        let loaded = load_scene(text.as_bytes());
+        let spawn_point = loaded.graph.find_by_name_from_root("SpawnPoint").unwrap().1;
+        assert_eq!(spawn_point.tag(), "spawn_point");
+        let untagged = loaded.graph.find_by_name_from_root("Untagged").unwrap().1;
+        assert_eq!(untagged.tag(), "");
+
+        // Emulate a scene saved before tags existed by stripping all "Tag" regions.
+        let mut legacy = String::new();
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let trimmed = line.trim_start_matches('\t');
+            if trimmed.starts_with("Tag[") {
+                if !trimmed.ends_with("{}") {
+                    let closing = format!("{}}}", &line[..line.len() - trimmed.len()]);
+                    lines.by_ref().find(|inner| *inner == closing);
+                }
+                continue;
+            }
+            legacy.push_str(line);
+            legacy.push('\n');
+        }
+        assert!(!legacy.contains("Tag["));
+
+        let loaded = load_scene(legacy.as_bytes());
+        let spawn_point = loaded.graph.find_by_name_from_root("SpawnPoint").unwrap().1;
+        assert_eq!(spawn_point.tag(), "");
+    }
+
+    #[test]
+    fn test_unknown_node_type() {
+        let mut scene = Scene::new();
+        MeshBuilder::new(
+            BaseBuilder::new()
+                .with_name("Unknown")
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                        .build(),
+                )
+                .with_child(
+                    PivotBuilder::new(BaseBuilder::new().with_name("Child"))
+                        .build(&mut scene.graph),
+                ),
+        )
+        .build(&mut scene.graph);
+        PivotBuilder::new(BaseBuilder::new().with_name("Sibling")).build(&mut scene.graph);
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+
+        // Emulate a node type that is not registered, for example a node saved by a newer
+        // version of the engine.
+        let mesh_uuid = format!("TypeUuid<uuid:{}>", Mesh::type_uuid());
+        let text = visitor.save_ascii_to_string();
+        assert!(text.contains(&mesh_uuid));
+        let text = text.replace(&mesh_uuid, &format!("TypeUuid<uuid:{}>", Uuid::new_v4()));
+
+        let loaded = load_scene(text.as_bytes());
+        let (unknown_handle, unknown) = loaded.graph.find_by_name_from_root("Unknown").unwrap();
+        assert!(unknown.is_pivot());
+        assert_eq!(
+            **unknown.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        let (_, child) = loaded.graph.find_by_name_from_root("Child").unwrap();
+        assert_eq!(child.parent(), unknown_handle);
+        assert!(loaded.graph.find_by_name_from_root("Sibling").is_some());
+    }
+
+    #[test]
+    fn test_unknown_light_derived_node_type() {
+        let mut scene = Scene::new();
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new()
+                .with_name("UnknownLight")
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(1.0, 2.0, 3.0))
+                        .build(),
+                )
+                .with_child(
+                    PivotBuilder::new(BaseBuilder::new().with_name("Child"))
+                        .build(&mut scene.graph),
+                ),
+        ))
+        .build(&mut scene.graph);
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+
+        // Base properties of lights are nested in the base light.
+        let light_uuid = format!("TypeUuid<uuid:{}>", PointLight::type_uuid());
+        let text = visitor.save_ascii_to_string();
+        assert!(text.contains(&light_uuid));
+        let text = text.replace(&light_uuid, &format!("TypeUuid<uuid:{}>", Uuid::new_v4()));
+
+        let loaded = load_scene(text.as_bytes());
+        let (unknown_handle, unknown) =
+            loaded.graph.find_by_name_from_root("UnknownLight").unwrap();
+        assert!(unknown.is_pivot());
+        assert_eq!(
+            **unknown.local_transform().position(),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+        let (_, child) = loaded.graph.find_by_name_from_root("Child").unwrap();
+        assert_eq!(child.parent(), unknown_handle);
+    }
 }
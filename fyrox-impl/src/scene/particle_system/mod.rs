@@ -48,6 +48,7 @@ use crate::{
             draw::Vertex,
             emitter::{base::BaseEmitterBuilder, sphere::SphereEmitterBuilder, Emit, Emitter},
             particle::Particle,
+            trail::{build_ribbon, FadingTrail, ParticleTrail},
         },
     },
 };
@@ -62,6 +63,7 @@ use strum_macros::{AsRefStr, EnumString, VariantNames};
 pub(crate) mod draw;
 pub mod emitter;
 pub mod particle;
+pub mod trail;
 
 /// Pseudo-random numbers generator for particle systems.
 #[derive(Debug, Clone, Reflect)]
@@ -260,6 +262,13 @@ pub struct ParticleSystem {
     fadeout_margin: InheritableVariable<f32>,
 
     rng: ParticleSystemRng,
+
+    /// Trail (ribbon) settings of the particle system. See [`ParticleTrail`] docs for more info.
+    #[reflect(setter = "set_trail")]
+    trail: InheritableVariable<ParticleTrail>,
+
+    #[reflect(hidden)]
+    fading_trails: Vec<FadingTrail>,
 }
 
 /// Coordinate system for particles generated by a particle system.
@@ -316,6 +325,7 @@ impl Visit for ParticleSystem {
             .visit("CoordinateSystem", &mut region)?;
         self.fadeout_margin.visit("FadeoutMargin", &mut region)?;
         self.material.visit("Material", &mut region)?;
+        let _ = self.trail.visit("Trail", &mut region);
 
         Ok(())
     }
@@ -386,6 +396,7 @@ impl ParticleSystem {
     pub fn clear_particles(&mut self) {
         self.particles.clear();
         self.free_particles.clear();
+        self.fading_trails.clear();
         for emitter in self.emitters.get_value_mut_silent().iter_mut() {
             emitter.alive_particles = 0;
             emitter.spawned_particles = 0;
@@ -407,6 +418,16 @@ impl ParticleSystem {
         &self.material
     }
 
+    /// Sets new trail settings of the particle system. See [`ParticleTrail`] docs for more info.
+    pub fn set_trail(&mut self, trail: ParticleTrail) -> ParticleTrail {
+        self.trail.set_value_and_mark_modified(trail)
+    }
+
+    /// Returns current trail settings of the particle system.
+    pub fn trail(&self) -> &ParticleTrail {
+        &self.trail
+    }
+
     fn tick(&mut self, dt: f32) {
         for emitter in self.emitters.get_value_mut_silent().iter_mut() {
             emitter.tick(dt);
@@ -437,10 +458,27 @@ impl ParticleSystem {
 
         let acceleration_offset = self.acceleration.scale(dt * dt);
 
+        for fading_trail in self.fading_trails.iter_mut() {
+            fading_trail.time_left -= dt;
+        }
+        self.fading_trails
+            .retain(|fading_trail| fading_trail.time_left > 0.0);
+
+        let trail = &*self.trail;
+
         for (i, particle) in self.particles.iter_mut().enumerate() {
             if particle.alive {
                 particle.lifetime += dt;
                 if particle.lifetime >= particle.initial_lifetime {
+                    if trail.enabled && particle.trail.len() > 1 && trail.fade_out_time > 0.0 {
+                        self.fading_trails.push(FadingTrail {
+                            points: std::mem::take(&mut particle.trail),
+                            color: particle.color,
+                            time_left: trail.fade_out_time,
+                        });
+                    } else {
+                        particle.trail.clear();
+                    }
                     self.free_particles.push(i as u32);
                     if let Some(emitter) = self
                         .emitters
@@ -462,6 +500,10 @@ impl ParticleSystem {
 
                     let k = particle.lifetime / particle.initial_lifetime;
                     particle.color = self.color_over_lifetime.get_color(k);
+
+                    if trail.enabled {
+                        trail.record(&mut particle.trail, particle.position);
+                    }
                 }
             }
         }
@@ -608,7 +650,9 @@ impl NodeTrait for ParticleSystem {
         });
 
         let global_transform = self.global_transform();
-        let sort_index = ctx.calculate_sorting_index(self.global_position());
+        let sort_index =
+            ctx.calculate_transparency_sorting_index(self.global_position(), self.render_order());
+        let observer_position = ctx.observer_position.translation;
 
         ctx.storage.push_triangles(
             ctx.dynamic_surface_cache,
@@ -686,7 +730,64 @@ impl NodeTrait for ParticleSystem {
                         .unwrap();
                 }
 
-                triangle_buffer.push_triangles_iter_with_offset(start_vertex_index, triangles)
+                triangle_buffer.push_triangles_iter_with_offset(start_vertex_index, triangles);
+
+                if self.trail.enabled {
+                    let to_world = |point: &Vector3<f32>| {
+                        if *self.coordinate_system == CoordinateSystem::Local {
+                            global_transform
+                                .transform_point(&Point3::from(*point))
+                                .coords
+                        } else {
+                            *point
+                        }
+                    };
+
+                    let mut ribbon_vertices = Vec::new();
+                    let mut ribbon_triangles = Vec::new();
+
+                    for particle in self.particles.iter().filter(|p| p.alive) {
+                        build_ribbon(
+                            particle.trail.iter().map(to_world),
+                            observer_position,
+                            &self.trail,
+                            particle.color,
+                            particle_alpha_factor,
+                            &mut ribbon_vertices,
+                            &mut ribbon_triangles,
+                        );
+                    }
+
+                    for fading_trail in self.fading_trails.iter() {
+                        let fade = if self.trail.fade_out_time > 0.0 {
+                            (fading_trail.time_left / self.trail.fade_out_time).clamp(0.0, 1.0)
+                        } else {
+                            0.0
+                        };
+                        build_ribbon(
+                            fading_trail.points.iter().map(to_world),
+                            observer_position,
+                            &self.trail,
+                            fading_trail.color,
+                            particle_alpha_factor * fade,
+                            &mut ribbon_vertices,
+                            &mut ribbon_triangles,
+                        );
+                    }
+
+                    let start_vertex_index = vertex_buffer.vertex_count();
+
+                    for vertex in ribbon_vertices {
+                        vertex_buffer
+                            .push_vertex_raw(value_as_u8_slice(&vertex))
+                            .unwrap();
+                    }
+
+                    triangle_buffer.push_triangles_iter_with_offset(
+                        start_vertex_index,
+                        ribbon_triangles.into_iter(),
+                    );
+                }
             },
         );
 
@@ -708,6 +809,7 @@ pub struct ParticleSystemBuilder {
     visible_distance: f32,
     coordinate_system: CoordinateSystem,
     fadeout_margin: f32,
+    trail: ParticleTrail,
 }
 
 impl ParticleSystemBuilder {
@@ -729,6 +831,7 @@ impl ParticleSystemBuilder {
             visible_distance: 30.0,
             coordinate_system: Default::default(),
             fadeout_margin: 1.5,
+            trail: Default::default(),
         }
     }
 
@@ -794,6 +897,12 @@ impl ParticleSystemBuilder {
         self
     }
 
+    /// Sets the desired trail settings. See [`ParticleTrail`] docs for more info.
+    pub fn with_trail(mut self, trail: ParticleTrail) -> Self {
+        self.trail = trail;
+        self
+    }
+
     fn build_particle_system(self) -> ParticleSystem {
         ParticleSystem {
             base: self.base_builder.build_base(),
@@ -808,6 +917,8 @@ impl ParticleSystemBuilder {
             visible_distance: self.visible_distance.into(),
             coordinate_system: self.coordinate_system.into(),
             fadeout_margin: self.fadeout_margin.into(),
+            trail: self.trail.into(),
+            fading_trails: Default::default(),
         }
     }
 
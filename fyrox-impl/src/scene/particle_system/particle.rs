@@ -22,7 +22,7 @@
 //! position, velocity, size, lifetime, etc.
 
 use crate::core::{algebra::Vector3, color::Color, visitor::prelude::*};
-use std::cell::Cell;
+use std::{cell::Cell, collections::VecDeque};
 
 /// See module docs.
 #[derive(Clone, Debug, Visit)]
@@ -53,6 +53,9 @@ pub struct Particle {
     pub(super) lifetime: f32,
     #[visit(skip)]
     pub(super) sqr_distance_to_camera: Cell<f32>,
+    /// Recent positions of the particle (newest first), used to build trails.
+    #[visit(skip)]
+    pub(super) trail: VecDeque<Vector3<f32>>,
 }
 
 impl Default for Particle {
@@ -70,11 +73,18 @@ impl Default for Particle {
             emitter_index: 0,
             color: Color::WHITE,
             sqr_distance_to_camera: Cell::new(0.0),
+            trail: Default::default(),
         }
     }
 }
 
 impl Particle {
+    /// Returns recent positions of the particle (newest first). The history is recorded only if
+    /// trails are enabled in the particle system.
+    pub fn trail(&self) -> &VecDeque<Vector3<f32>> {
+        &self.trail
+    }
+
     /// Sets new position in builder manner.
     pub fn with_position(mut self, position: Vector3<f32>) -> Self {
         self.position = position;
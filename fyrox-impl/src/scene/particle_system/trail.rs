@@ -0,0 +1,240 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Trails (ribbons) allow particles to leave a camera-facing strip behind them. See [`ParticleTrail`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        math::{
+            curve::{Curve, CurveKey, CurveKeyKind},
+            TriangleDefinition,
+        },
+        reflect::prelude::*,
+        visitor::prelude::*,
+    },
+    scene::particle_system::draw::Vertex,
+};
+use std::collections::VecDeque;
+
+/// Trail settings of a particle system. When enabled, every particle records its recent positions
+/// and the renderer builds a camera-facing ribbon strip through them. Width and transparency of the
+/// ribbon are defined by curves, that are sampled in `[0; 1]` range, where `0` is the head of the
+/// trail (the particle itself) and `1` is its tail.
+///
+/// When a particle dies, its trail does not disappear instantly, instead it fades out during
+/// [`Self::fade_out_time`] seconds.
+#[derive(Debug, Clone, PartialEq, Visit, Reflect)]
+pub struct ParticleTrail {
+    /// Whether the trails are enabled or not.
+    pub enabled: bool,
+
+    /// Maximum amount of recorded positions (including the current position of a particle). A
+    /// trail with `N` points consists of `N - 1` ribbon segments.
+    #[reflect(min_value = 2.0)]
+    pub length: u32,
+
+    /// Width of the ribbon along the trail (head to tail).
+    pub width: Curve,
+
+    /// Transparency multiplier along the trail (head to tail).
+    pub alpha: Curve,
+
+    /// Time (in seconds) during which a trail of a dead particle fades out.
+    #[reflect(min_value = 0.0)]
+    pub fade_out_time: f32,
+}
+
+fn linear_curve(head: f32, tail: f32) -> Curve {
+    Curve::from(vec![
+        CurveKey::new(0.0, head, CurveKeyKind::Linear),
+        CurveKey::new(1.0, tail, CurveKeyKind::Linear),
+    ])
+}
+
+impl Default for ParticleTrail {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            length: 16,
+            width: linear_curve(0.1, 0.0),
+            alpha: linear_curve(1.0, 0.0),
+            fade_out_time: 0.5,
+        }
+    }
+}
+
+impl ParticleTrail {
+    /// Records a new position of a particle in the given trail history, discarding the points that
+    /// do not fit in the trail length.
+    pub fn record(&self, history: &mut VecDeque<Vector3<f32>>, position: Vector3<f32>) {
+        history.push_front(position);
+        history.truncate(self.length.max(2) as usize);
+    }
+}
+
+/// A trail of a dead particle, that is still fading out.
+#[derive(Debug, Clone, Default)]
+pub(super) struct FadingTrail {
+    pub(super) points: VecDeque<Vector3<f32>>,
+    pub(super) color: Color,
+    pub(super) time_left: f32,
+}
+
+/// Builds a camera-facing ribbon strip through the given points (head first, in world space). The
+/// generated vertices are appended to `vertices` and the triangles (with indices relative to the
+/// beginning of `vertices`) are appended to `triangles`. Returns the amount of generated segments.
+pub fn build_ribbon(
+    points: impl IntoIterator<Item = Vector3<f32>>,
+    observer_position: Vector3<f32>,
+    trail: &ParticleTrail,
+    color: Color,
+    alpha_factor: f32,
+    vertices: &mut Vec<Vertex>,
+    triangles: &mut Vec<TriangleDefinition>,
+) -> usize {
+    let points = points.into_iter().collect::<Vec<_>>();
+    let count = points.len();
+    if count < 2 {
+        return 0;
+    }
+
+    let mut last_side = Vector3::x();
+    let first_vertex = vertices.len() as u32;
+
+    for (i, point) in points.iter().enumerate() {
+        let t = i as f32 / (count - 1) as f32;
+
+        let prev = points[i.saturating_sub(1)];
+        let next = points[(i + 1).min(count - 1)];
+        let tangent = prev - next;
+        let to_observer = observer_position - point;
+        // Keep the last valid side vector for degenerate cases (zero-length segments or segments
+        // that are looking directly at the observer).
+        if let Some(side) = tangent.cross(&to_observer).try_normalize(f32::EPSILON) {
+            last_side = side;
+        }
+
+        let half_width = trail.width.value_at(t).max(0.0) * 0.5;
+        let alpha = (color.a as f32 * trail.alpha.value_at(t).clamp(0.0, 1.0) * alpha_factor)
+            .clamp(0.0, 255.0) as u8;
+        let color = Color::from_rgba(color.r, color.g, color.b, alpha);
+
+        for (offset, v) in [(half_width, 0.0), (-half_width, 1.0)] {
+            vertices.push(Vertex {
+                position: point + last_side.scale(offset),
+                tex_coord: Vector2::new(t, v),
+                // Zero size and rotation disable billboard expansion in the shader, the ribbon is
+                // already camera-facing.
+                size: 0.0,
+                rotation: 0.0,
+                color,
+            });
+        }
+    }
+
+    let segments = count - 1;
+    for i in 0..segments as u32 {
+        let a = first_vertex + i * 2;
+        triangles.push(TriangleDefinition([a, a + 1, a + 3]));
+        triangles.push(TriangleDefinition([a, a + 3, a + 2]));
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            particle_system::{
+                particle::Particle, trail::build_ribbon, trail::ParticleTrail,
+                ParticleSystemBuilder,
+            },
+        },
+    };
+
+    #[test]
+    fn test_trail_segments() {
+        let mut particle_system = ParticleSystemBuilder::new(BaseBuilder::new())
+            .with_acceleration(Vector3::default())
+            .with_particles(vec![Particle::default()
+                .with_velocity(Vector3::new(0.1, 0.0, 0.0))
+                .with_initial_lifetime(100.0)])
+            .with_trail(ParticleTrail {
+                enabled: true,
+                length: 5,
+                ..Default::default()
+            })
+            .build_particle_system();
+
+        for _ in 0..10 {
+            particle_system.tick(1.0 / 60.0);
+        }
+
+        let particle = &particle_system.particles()[0];
+        assert_eq!(particle.trail.len(), 5);
+
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+        let segments = build_ribbon(
+            particle.trail.iter().cloned(),
+            Vector3::new(0.0, 0.0, -10.0),
+            particle_system.trail(),
+            particle.color,
+            1.0,
+            &mut vertices,
+            &mut triangles,
+        );
+        assert_eq!(segments, 4);
+        assert_eq!(vertices.len(), 10);
+        assert_eq!(triangles.len(), 8);
+    }
+
+    #[test]
+    fn test_dead_particle_trail_fades_out() {
+        let mut particle_system = ParticleSystemBuilder::new(BaseBuilder::new())
+            .with_acceleration(Vector3::default())
+            .with_particles(vec![Particle::default()
+                .with_velocity(Vector3::new(0.1, 0.0, 0.0))
+                .with_initial_lifetime(0.1)])
+            .with_trail(ParticleTrail {
+                enabled: true,
+                length: 5,
+                fade_out_time: 0.5,
+                ..Default::default()
+            })
+            .build_particle_system();
+
+        for _ in 0..4 {
+            particle_system.tick(0.05);
+        }
+        assert_eq!(particle_system.fading_trails.len(), 1);
+
+        for _ in 0..20 {
+            particle_system.tick(0.05);
+        }
+        assert!(particle_system.fading_trails.is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Client-side prediction and reconciliation for networked games. See [`ClientPrediction`] docs
+//! for more info.
+
+use crate::{
+    core::{algebra::Vector3, pool::Handle},
+    scene::{
+        graph::{physics::PhysicsSnapshot, Graph},
+        node::Node,
+        replication::ReplicatedState,
+    },
+};
+use std::collections::VecDeque;
+
+/// A single predicted tick.
+#[derive(Clone, Debug)]
+pub struct PredictedTick<I> {
+    /// Index of the tick.
+    pub tick: u64,
+    /// Input, that was applied at the tick.
+    pub input: I,
+    /// Predicted state of the node after the tick.
+    pub state: ReplicatedState,
+    /// State of the physics world after the tick.
+    pub physics: PhysicsSnapshot,
+}
+
+/// Result of [`ClientPrediction::reconcile`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Reconciliation {
+    /// There is no predicted state for the acknowledged tick (it is either too old or was never
+    /// recorded), the authoritative state was ignored.
+    Ignored,
+    /// The prediction matches the authoritative state (within the misprediction threshold), no
+    /// correction was made.
+    Confirmed,
+    /// The prediction was wrong, the state was rewound to the acknowledged tick and the buffered
+    /// inputs were re-simulated.
+    Corrected {
+        /// Offset between the predicted position of the node before the correction and after it.
+        /// It could be used to smooth the correction visually, by adding a decaying offset to the
+        /// rendered position of the node.
+        offset: Vector3<f32>,
+    },
+}
+
+/// Client-side prediction helper. It stores inputs and predicted states of a node per tick, so
+/// when an authoritative state comes from the server, the helper can compare it with the
+/// prediction for the same tick and, in case of misprediction, rewind the scene to the
+/// authoritative state and re-simulate all the inputs that were not yet acknowledged by the
+/// server. States of all rigid bodies of the graph are rewound using physics snapshots (see
+/// [`PhysicsSnapshot`]).
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::{Vector2, Vector3}, pool::Handle},
+/// #     scene::{
+/// #         graph::Graph, node::Node, prediction::ClientPrediction,
+/// #         replication::ReplicatedState, rigidbody::RigidBody,
+/// #     },
+/// # };
+/// fn simulate(graph: &mut Graph, node: Handle<Node>, input: &Vector3<f32>) {
+///     if let Some(body) = graph[node].cast_mut::<RigidBody>() {
+///         body.set_lin_vel(*input);
+///     }
+///     graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+/// }
+///
+/// fn client_tick(
+///     graph: &mut Graph,
+///     prediction: &mut ClientPrediction<Vector3<f32>>,
+///     tick: u64,
+///     input: Vector3<f32>,
+///     server_state: Option<(u64, ReplicatedState)>,
+/// ) {
+///     let node = prediction.node();
+///
+///     if let Some((acked_tick, state)) = server_state {
+///         prediction.reconcile(graph, acked_tick, &state, |graph, input| {
+///             simulate(graph, node, input)
+///         });
+///     }
+///
+///     simulate(graph, node, &input);
+///     prediction.record(graph, tick, input);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ClientPrediction<I> {
+    node: Handle<Node>,
+    history: VecDeque<PredictedTick<I>>,
+    capacity: usize,
+    misprediction_threshold: f32,
+}
+
+impl<I> ClientPrediction<I> {
+    /// Creates new prediction helper for the given node. `capacity` defines the maximum number of
+    /// ticks that will be stored, it should be enough to cover the round-trip time.
+    pub fn new(node: Handle<Node>, capacity: usize) -> Self {
+        Self {
+            node,
+            history: Default::default(),
+            capacity: capacity.max(1),
+            misprediction_threshold: 0.01,
+        }
+    }
+
+    /// Sets the maximum distance between predicted and authoritative positions, that is
+    /// still considered as a correct prediction. Default is 0.01.
+    pub fn with_misprediction_threshold(mut self, threshold: f32) -> Self {
+        self.set_misprediction_threshold(threshold);
+        self
+    }
+
+    /// Sets the maximum distance between predicted and authoritative positions, that is
+    /// still considered as a correct prediction.
+    pub fn set_misprediction_threshold(&mut self, threshold: f32) {
+        self.misprediction_threshold = threshold.max(0.0);
+    }
+
+    /// Returns current misprediction threshold.
+    pub fn misprediction_threshold(&self) -> f32 {
+        self.misprediction_threshold
+    }
+
+    /// Returns a handle of the predicted node.
+    pub fn node(&self) -> Handle<Node> {
+        self.node
+    }
+
+    /// Returns the ticks, that were not yet acknowledged by the server.
+    pub fn history(&self) -> &VecDeque<PredictedTick<I>> {
+        &self.history
+    }
+
+    /// Removes every stored tick.
+    pub fn clear(&mut self) {
+        self.history.clear();
+    }
+
+    /// Stores the given input with the current state of the graph. Must be called right after
+    /// the tick was simulated with the input.
+    pub fn record(&mut self, graph: &Graph, tick: u64, input: I) {
+        let Some(state) = ReplicatedState::capture(graph, self.node) else {
+            return;
+        };
+
+        if self.history.len() >= self.capacity {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(PredictedTick {
+            tick,
+            input,
+            state,
+            physics: graph.physics.snapshot(),
+        });
+    }
+
+    /// Compares the authoritative state of the node at the given tick with the predicted one. If
+    /// the distance between them exceeds the misprediction threshold, the graph is rewound to
+    /// the authoritative state and every input after the given tick is re-simulated using the
+    /// `simulate` callback. The callback must apply the input and update the graph, exactly as
+    /// it was done when the input was recorded.
+    pub fn reconcile<F>(
+        &mut self,
+        graph: &mut Graph,
+        tick: u64,
+        authoritative: &ReplicatedState,
+        mut simulate: F,
+    ) -> Reconciliation
+    where
+        F: FnMut(&mut Graph, &I),
+    {
+        while self.history.front().is_some_and(|entry| entry.tick < tick) {
+            self.history.pop_front();
+        }
+
+        let Some(acked) = self.history.front() else {
+            return Reconciliation::Ignored;
+        };
+        if acked.tick != tick {
+            return Reconciliation::Ignored;
+        }
+
+        let error = authoritative
+            .position
+            .metric_distance(&acked.state.position);
+        if error <= self.misprediction_threshold {
+            self.history.pop_front();
+            return Reconciliation::Confirmed;
+        }
+
+        let Some(acked) = self.history.pop_front() else {
+            return Reconciliation::Ignored;
+        };
+        let last_predicted = self
+            .history
+            .back()
+            .map_or(acked.state.position, |entry| entry.state.position);
+
+        graph.restore_physics(&acked.physics);
+        authoritative.apply(graph, self.node);
+
+        for entry in self.history.iter_mut() {
+            simulate(graph, &entry.input);
+            if let Some(state) = ReplicatedState::capture(graph, self.node) {
+                entry.state = state;
+            }
+            entry.physics = graph.physics.snapshot();
+        }
+
+        let new_predicted = self
+            .history
+            .back()
+            .map_or(authoritative.position, |entry| entry.state.position);
+
+        Reconciliation::Corrected {
+            offset: last_predicted - new_predicted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            node::Node,
+            prediction::{ClientPrediction, Reconciliation},
+            replication::ReplicatedState,
+            rigidbody::{RigidBody, RigidBodyBuilder},
+        },
+    };
+
+    fn make_scene() -> (Graph, Handle<Node>) {
+        let mut graph = Graph::new();
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.5))
+            .build(&mut graph);
+        let body = RigidBodyBuilder::new(BaseBuilder::new().with_child(collider))
+            .with_gravity_scale(0.0)
+            .with_can_sleep(false)
+            .build(&mut graph);
+        (graph, body.to_base())
+    }
+
+    fn simulate(graph: &mut Graph, body: Handle<Node>, input: f32) {
+        graph[body]
+            .cast_mut::<RigidBody>()
+            .unwrap()
+            .set_lin_vel(Vector3::new(input, 0.0, 0.0));
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+    }
+
+    #[test]
+    fn test_reconciliation() {
+        let (mut client, client_body) = make_scene();
+        let (mut server, server_body) = make_scene();
+        let mut prediction = ClientPrediction::new(client_body, 64);
+
+        for tick in 0..10 {
+            simulate(&mut client, client_body, 1.0);
+            prediction.record(&client, tick, 1.0);
+        }
+        assert_eq!(prediction.history().len(), 10);
+
+        // The server applies the same input at the first tick, so the prediction is correct.
+        simulate(&mut server, server_body, 1.0);
+        let state = ReplicatedState::capture(&server, server_body).unwrap();
+        assert_eq!(
+            prediction.reconcile(&mut client, 0, &state, |_, _| unreachable!()),
+            Reconciliation::Confirmed
+        );
+        assert_eq!(prediction.history().len(), 9);
+
+        // The server moves the body faster at the next few ticks, for example, because of some
+        // power-up, that the client does not know about yet.
+        for _ in 1..5 {
+            simulate(&mut server, server_body, 3.0);
+        }
+        let state = ReplicatedState::capture(&server, server_body).unwrap();
+        let result = prediction.reconcile(&mut client, 4, &state, |graph, input| {
+            simulate(graph, client_body, *input)
+        });
+        let Reconciliation::Corrected { offset } = result else {
+            panic!("Must be corrected, got {result:?}");
+        };
+        assert!(offset.x < 0.0);
+        assert_eq!(prediction.history().len(), 5);
+
+        // The rest of inputs are simulated on the server as usual, and the predicted state must
+        // converge to the authoritative one.
+        for _ in 5..10 {
+            simulate(&mut server, server_body, 1.0);
+        }
+        let server_position = server[server_body].global_position();
+        let client_position = client[client_body].global_position();
+        assert!(server_position.metric_distance(&client_position) < 1.0e-3);
+        let predicted = &prediction.history().back().unwrap().state;
+        assert!(predicted.position.metric_distance(&server_position) < 1.0e-3);
+
+        // Outdated states are ignored.
+        assert_eq!(
+            prediction.reconcile(&mut client, 2, &state, |_, _| unreachable!()),
+            Reconciliation::Ignored
+        );
+    }
+}
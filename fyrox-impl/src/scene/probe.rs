@@ -76,9 +76,29 @@ pub enum UpdateMode {
     EachFrame,
 }
 
+/// Parameters of parallax-corrected (box) projection of a reflection probe. Cube maps store the
+/// environment as if it was infinitely far away, which makes reflections of nearby walls "swim" when
+/// the reflecting surface is far from the capture point. Box projection intersects reflection
+/// vectors with the bounds of the probe and re-projects them from the capture point, which makes
+/// reflections of room-like environments match their actual geometry.
+#[derive(Clone, Copy, Debug)]
+pub struct BoxProjection {
+    /// World-space bounds of the probe.
+    pub bounds: AxisAlignedBoundingBox,
+    /// World-space position from which the environment was captured.
+    pub capture_position: Vector3<f32>,
+}
+
 /// Reflection probe is an object that allows "capturing" a scene content in a cube texture, that
 /// can later be used to render reflections and be used as a source of ambient lighting for a scene.
 ///
+/// ## Bounds
+///
+/// Bounds of the probe is a unit cube transformed by the global transform of the probe. This means
+/// that the size of the probe is defined by its scale. The bounds are used to select the probe for
+/// a camera and for parallax correction of reflections (see [`BoxProjection`]), which can be turned
+/// off by [`Self::box_projection`] flag.
+///
 /// ## Update Mode
 ///
 /// Reflection probe can be updated either once or every frame. The default mode is [`UpdateMode::Once`].
@@ -175,6 +195,10 @@ pub struct ReflectionProbe {
     /// Environment lighting source of the reflection probe.
     pub environment_lighting_source: InheritableVariable<EnvironmentLightingSource>,
 
+    /// A flag, that defines whether the reflections should be parallax-corrected using the bounds
+    /// of the probe or not. See [`BoxProjection`] docs for more info.
+    pub box_projection: InheritableVariable<bool>,
+
     /// A flag, that defines whether the probe should be updated or not.
     #[reflect(hidden)]
     #[visit(skip)]
@@ -198,6 +222,7 @@ impl Default for ReflectionProbe {
             update_mode: Default::default(),
             ambient_lighting_color: Color::repeat_opaque(120).into(),
             environment_lighting_source: Default::default(),
+            box_projection: true.into(),
             need_update: true,
             updated: Cell::new(false),
             render_target: TextureResource::new_cube_render_target(DEFAULT_RESOLUTION as u32),
@@ -234,6 +259,19 @@ impl ReflectionProbe {
         self.global_position() + *self.rendering_position
     }
 
+    /// Returns parallax-correction parameters of the probe, or [`None`] if the box projection is
+    /// disabled.
+    pub fn box_projection(&self) -> Option<BoxProjection> {
+        if *self.box_projection {
+            Some(BoxProjection {
+                bounds: self.base.world_bounding_box(),
+                capture_position: self.global_rendering_position(),
+            })
+        } else {
+            None
+        }
+    }
+
     fn recreate_render_target(&mut self) {
         self.render_target = TextureResource::new_cube_render_target(*self.resolution as u32);
         self.force_update();
@@ -310,6 +348,7 @@ pub struct ReflectionProbeBuilder {
     update_mode: UpdateMode,
     ambient_lighting_color: Color,
     environment_lighting_source: EnvironmentLightingSource,
+    box_projection: bool,
 }
 
 impl ReflectionProbeBuilder {
@@ -324,6 +363,7 @@ impl ReflectionProbeBuilder {
             update_mode: Default::default(),
             ambient_lighting_color: Color::repeat_opaque(120),
             environment_lighting_source: Default::default(),
+            box_projection: true,
         }
     }
 
@@ -372,9 +412,16 @@ impl ReflectionProbeBuilder {
         self
     }
 
-    /// Creates a new reflection probe node.
-    pub fn build_node(self) -> Node {
-        Node::new(ReflectionProbe {
+    /// Enables or disables parallax correction of reflections. See [`BoxProjection`] docs for
+    /// more info.
+    pub fn with_box_projection(mut self, box_projection: bool) -> Self {
+        self.box_projection = box_projection;
+        self
+    }
+
+    /// Creates a new reflection probe.
+    pub fn build_reflection_probe(self) -> ReflectionProbe {
+        ReflectionProbe {
             base: self.base_builder.build_base(),
             rendering_position: self.offset.into(),
             resolution: self.resolution.into(),
@@ -383,10 +430,16 @@ impl ReflectionProbeBuilder {
             update_mode: self.update_mode.into(),
             ambient_lighting_color: self.ambient_lighting_color.into(),
             environment_lighting_source: self.environment_lighting_source.into(),
+            box_projection: self.box_projection.into(),
             need_update: true,
             updated: Cell::new(false),
             render_target: TextureResource::new_cube_render_target(self.resolution as u32),
-        })
+        }
+    }
+
+    /// Creates a new reflection probe node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reflection_probe())
     }
 
     /// Creates a new reflection probe node and adds it to the graph.
@@ -394,3 +447,99 @@ impl ReflectionProbeBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Matrix4, Vector2, Vector3},
+            visitor::{Visit, Visitor},
+        },
+        renderer::{
+            bundle::{RenderDataBundleStorage, RenderDataBundleStorageOptions},
+            observer::ObserverPosition,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            probe::{ReflectionProbe, ReflectionProbeBuilder, UpdateMode},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_box_projection_is_passed_to_renderer() {
+        let mut graph = Graph::new();
+        let probe = ReflectionProbeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_scale(Vector3::repeat(10.0))
+                    .build(),
+            ),
+        )
+        .with_rendering_local_position(Vector3::new(0.0, 1.0, 0.0))
+        .build(&mut graph);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+        // The observer is inside the probe.
+        let observer_position = ObserverPosition {
+            translation: Vector3::new(1.0, 0.0, 0.0),
+            z_near: 0.1,
+            z_far: 100.0,
+            view_matrix: Matrix4::identity(),
+            projection_matrix: Matrix4::identity(),
+            view_projection_matrix: Matrix4::identity(),
+        };
+        let collect = |graph: &Graph| {
+            RenderDataBundleStorage::from_graph(
+                graph,
+                Default::default(),
+                0.0,
+                &observer_position,
+                Default::default(),
+                RenderDataBundleStorageOptions {
+                    collect_lights: false,
+                },
+                &mut Default::default(),
+            )
+        };
+
+        let storage = collect(&graph);
+        assert!(storage.environment_map.is_some());
+        let box_projection = storage.environment_map_projection.unwrap();
+        assert_eq!(box_projection.bounds.min, Vector3::repeat(-5.0));
+        assert_eq!(box_projection.bounds.max, Vector3::repeat(5.0));
+        assert_eq!(box_projection.capture_position, Vector3::new(0.0, 1.0, 0.0));
+
+        // The probe is still used for reflections, but without parallax correction.
+        graph[probe]
+            .box_projection
+            .set_value_and_mark_modified(false);
+        let storage = collect(&graph);
+        assert!(storage.environment_map.is_some());
+        assert!(storage.environment_map_projection.is_none());
+    }
+
+    #[test]
+    fn test_probe_settings_serialization() {
+        let mut probe = ReflectionProbeBuilder::new(BaseBuilder::new())
+            .with_resolution(128)
+            .with_z_far(64.0)
+            .with_update_mode(UpdateMode::EachFrame)
+            .with_box_projection(false)
+            .build_reflection_probe();
+
+        let mut visitor = Visitor::new();
+        probe.visit("Probe", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut loaded = ReflectionProbe::default();
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        loaded.visit("Probe", &mut visitor).unwrap();
+
+        assert_eq!(loaded.resolution(), 128);
+        assert_eq!(*loaded.z_far, 64.0);
+        assert_eq!(*loaded.update_mode, UpdateMode::EachFrame);
+        assert!(loaded.box_projection().is_none());
+    }
+}
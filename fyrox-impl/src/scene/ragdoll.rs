@@ -55,6 +55,13 @@ pub struct Limb {
     pub bone: Handle<Node>,
     /// A handle to a rigid body scene node.
     pub physical_bone: Handle<RigidBody>,
+    /// Defines whether the bone of the limb is driven by physics, when the ragdoll is not active.
+    /// `false` means that the bone is driven by animation (the rigid body follows the bone), `true`
+    /// means that the bone is driven by the rigid body. This allows you to make only a part of the
+    /// body physical (partial ragdoll), for example for hit reactions. An active ragdoll ignores this
+    /// flag and drives every limb with physics.
+    #[visit(optional)]
+    pub physics_driven: bool,
     /// A set of children limbs.
     pub children: Vec<Limb>,
 }
@@ -88,6 +95,16 @@ impl Limb {
 /// to create a ragdoll is to use the editor, and the ragdoll wizard in particular. However, if
 /// you're brave enough you can read this code <https://github.com/FyroxEngine/Fyrox/blob/master/editor/src/utils/ragdoll.rs> -
 /// it creates a ragdoll using a humanoid skeleton.  
+///
+/// ## Partial ragdoll
+///
+/// Inactive ragdoll could still drive a subset of bones with physics, while the rest of the bones
+/// are driven by animation. It is useful for hit reactions, dangling arms, etc. Use
+/// [`Limb::physics_driven`] to mark the limbs that should be driven by physics. The rigid bodies of
+/// such limbs become dynamic, and their bones follow the bodies. Animation-driven limbs keep their rigid bodies kinematic, so the joints between
+/// physics-driven and animation-driven limbs keep the physical part attached to the animated one.
+/// Keep in mind, that the ragdoll must be updated after the animation is applied to the bones,
+/// otherwise the animation will override the physical pose.
 #[derive(Clone, Reflect, Visit, Debug, Default, ComponentProvider)]
 #[reflect(derived_type = "Node")]
 #[visit(optional)]
@@ -172,7 +189,9 @@ impl NodeTrait for Ragdoll {
             let mut need_update_transform = false;
 
             let mut limb_body = mbc.try_get_mut(limb.physical_bone)?;
-            if *self.is_active {
+            // Active ragdoll drives every limb with physics, otherwise only the limbs that are
+            // explicitly marked as physics-driven.
+            if *self.is_active || limb.physics_driven {
                 // Transfer linear and angular velocities to rag doll bodies.
                 if let Some(lin_vel) = new_lin_vel {
                     limb_body.set_lin_vel(lin_vel);
@@ -361,3 +380,214 @@ impl RagdollBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            joint::{BallJoint, JointBuilder, JointParams},
+            node::Node,
+            pivot::PivotBuilder,
+            ragdoll::{Limb, RagdollBuilder},
+            rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+        },
+    };
+
+    fn make_bone(graph: &mut Graph, position: Vector3<f32>, parent: Handle<Node>) -> Handle<Node> {
+        let bone = PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .build(graph)
+        .to_base();
+        if parent.is_some() {
+            graph.link_nodes(bone, parent);
+        }
+        bone
+    }
+
+    fn make_body(graph: &mut Graph, position: Vector3<f32>) -> Handle<RigidBody> {
+        let collider = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.1))
+            .build(graph);
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(position)
+                        .build(),
+                )
+                .with_child(collider),
+        )
+        .with_body_type(RigidBodyType::KinematicPositionBased)
+        .with_can_sleep(false)
+        .build(graph)
+    }
+
+    fn make_joint(
+        graph: &mut Graph,
+        position: Vector3<f32>,
+        body1: Handle<RigidBody>,
+        body2: Handle<RigidBody>,
+    ) {
+        JointBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .with_params(JointParams::BallJoint(BallJoint::default()))
+        .with_body1(body1)
+        .with_body2(body2)
+        .with_contacts_enabled(false)
+        .build(graph);
+    }
+
+    #[test]
+    fn test_partial_ragdoll() {
+        let mut graph = Graph::new();
+
+        let hips = make_bone(&mut graph, Vector3::new(0.0, 1.0, 0.0), Handle::NONE);
+        let spine = make_bone(&mut graph, Vector3::new(0.0, 0.5, 0.0), hips);
+        let arm = make_bone(&mut graph, Vector3::new(0.5, 0.0, 0.0), spine);
+        let fore_arm = make_bone(&mut graph, Vector3::new(0.5, 0.0, 0.0), arm);
+
+        let hips_body = make_body(&mut graph, Vector3::new(0.0, 1.0, 0.0));
+        let spine_body = make_body(&mut graph, Vector3::new(0.0, 1.5, 0.0));
+        let arm_body = make_body(&mut graph, Vector3::new(0.5, 1.5, 0.0));
+        let fore_arm_body = make_body(&mut graph, Vector3::new(1.0, 1.5, 0.0));
+
+        make_joint(
+            &mut graph,
+            Vector3::new(0.0, 1.25, 0.0),
+            hips_body,
+            spine_body,
+        );
+        make_joint(
+            &mut graph,
+            Vector3::new(0.25, 1.5, 0.0),
+            spine_body,
+            arm_body,
+        );
+        make_joint(
+            &mut graph,
+            Vector3::new(0.75, 1.5, 0.0),
+            arm_body,
+            fore_arm_body,
+        );
+
+        // Only the arm is driven by physics, the rest of the skeleton is driven by animation.
+        let ragdoll = RagdollBuilder::new(
+            BaseBuilder::new()
+                .with_child(hips_body)
+                .with_child(spine_body)
+                .with_child(arm_body)
+                .with_child(fore_arm_body),
+        )
+        .with_active(false)
+        .with_root_limb(Limb {
+            bone: hips,
+            physical_bone: hips_body,
+            physics_driven: false,
+            children: vec![Limb {
+                bone: spine,
+                physical_bone: spine_body,
+                physics_driven: false,
+                children: vec![Limb {
+                    bone: arm,
+                    physical_bone: arm_body,
+                    physics_driven: true,
+                    children: vec![Limb {
+                        bone: fore_arm,
+                        physical_bone: fore_arm_body,
+                        physics_driven: true,
+                        children: vec![],
+                    }],
+                }],
+            }],
+        })
+        .build(&mut graph);
+
+        let dt = 1.0 / 60.0;
+        let mut lowest_arm_height = f32::MAX;
+        for frame in 0..60 {
+            // Emulate an animation, that moves the hips and bends the spine.
+            let hips_position = Vector3::new(0.01 * frame as f32, 1.0, 0.0);
+            let spine_position = Vector3::new(0.0, 0.5 + 0.001 * frame as f32, 0.0);
+            graph[hips]
+                .local_transform_mut()
+                .set_position(hips_position);
+            graph[spine]
+                .local_transform_mut()
+                .set_position(spine_position);
+
+            graph.update(Vector2::new(100.0, 100.0), dt, Default::default());
+
+            // Animation-driven bones must keep the animated pose.
+            assert_eq!(**graph[hips].local_transform().position(), hips_position);
+            assert_eq!(**graph[spine].local_transform().position(), spine_position);
+
+            let arm_height =
+                graph[arm_body].global_position().y - graph[spine_body].global_position().y;
+            lowest_arm_height = lowest_arm_height.min(arm_height);
+        }
+
+        assert!(!*graph[ragdoll].is_active);
+        assert_eq!(
+            graph[hips_body].body_type(),
+            RigidBodyType::KinematicPositionBased
+        );
+        assert_eq!(
+            graph[spine_body].body_type(),
+            RigidBodyType::KinematicPositionBased
+        );
+        assert_eq!(graph[arm_body].body_type(), RigidBodyType::Dynamic);
+        assert_eq!(graph[fore_arm_body].body_type(), RigidBodyType::Dynamic);
+
+        // Physics-driven bones follow their rigid bodies.
+        graph.update_hierarchical_data();
+        let arm_position = graph[arm].global_position();
+        assert!(
+            arm_position.metric_distance(&graph[arm_body].global_position()) < 1.0e-3,
+            "{arm_position:?}"
+        );
+
+        // The arm swings down under gravity, but the joints keep it attached to the animated spine
+        // instead of letting it fall freely.
+        let shoulder = graph[spine_body].global_position() + Vector3::new(0.25, 0.0, 0.0);
+        let arm_body_position = graph[arm_body].global_position();
+        assert!(lowest_arm_height < -0.1, "{lowest_arm_height}");
+        assert!(
+            (arm_body_position.metric_distance(&shoulder) - 0.25).abs() < 0.05,
+            "{arm_body_position:?}"
+        );
+        assert!(
+            graph[fore_arm_body]
+                .global_position()
+                .metric_distance(&arm_body_position)
+                < 0.52
+        );
+
+        // Kinematic bodies follow the animated bones.
+        graph.update(Vector2::new(100.0, 100.0), dt, Default::default());
+        graph.update_hierarchical_data();
+        assert!(
+            graph[spine_body]
+                .global_position()
+                .metric_distance(&graph[spine].global_position())
+                < 1.0e-3
+        );
+    }
+}
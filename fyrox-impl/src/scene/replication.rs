@@ -0,0 +1,488 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compact serialization of the dynamic state of scene nodes for networked games. See
+//! [`ReplicatedState`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Quaternion, UnitQuaternion, Vector3},
+        byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt},
+        pool::Handle,
+    },
+    graph::SceneGraph,
+    scene::{animation::AnimationPlayer, graph::Graph, node::Node, rigidbody::RigidBody},
+};
+use std::io::{self, Read, Write};
+
+/// Defines how positions are encoded.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub enum PositionQuantization {
+    /// Positions are stored as is, using 32-bit floats for each component.
+    #[default]
+    None,
+    /// Positions are stored as 16-bit integers for each component, mapped to the given bounds.
+    /// Positions outside the bounds are clamped. The maximum error of the encoding is a half of
+    /// `(max - min) / 65535` for each component.
+    Bounded16 {
+        /// Minimal corner of the bounds.
+        min: Vector3<f32>,
+        /// Maximal corner of the bounds.
+        max: Vector3<f32>,
+    },
+}
+
+/// Defines how the state of nodes is encoded. Both sides (server and client) must use the same
+/// settings.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct ReplicationSettings {
+    /// Encoding of positions.
+    pub position: PositionQuantization,
+    /// If `true`, every component of rotations is stored as a 16-bit integer, instead of a
+    /// 32-bit float.
+    pub quantize_rotation: bool,
+    /// If set, every component of velocities is stored as a 16-bit integer in `[-limit; limit]`
+    /// range. Velocities outside of the range are clamped.
+    pub velocity_limit: Option<f32>,
+}
+
+/// Velocity of a rigid body.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct ReplicatedVelocity {
+    /// Linear velocity.
+    pub linear: Vector3<f32>,
+    /// Angular velocity.
+    pub angular: Vector3<f32>,
+}
+
+/// State of a single animation of an animation player.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct ReplicatedAnimation {
+    /// Time position of the animation, in seconds.
+    pub time_position: f32,
+    /// Whether the animation is enabled or not.
+    pub enabled: bool,
+}
+
+/// Replicated state of a scene node. It contains only the fields, that usually change during the
+/// gameplay:
+///
+/// - Local position and rotation of the node. Scale is not replicated.
+/// - Linear and angular velocities, if the node is a rigid body.
+/// - Time positions and enabled flags of animations, if the node is an animation player.
+///   Animations are matched by their order in the player.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, pool::Handle},
+/// #     scene::{
+/// #         graph::Graph,
+/// #         node::Node,
+/// #         replication::{PositionQuantization, ReplicatedState, ReplicationSettings},
+/// #     },
+/// # };
+/// fn replicate(server: &Graph, client: &mut Graph, node: Handle<Node>) {
+///     let settings = ReplicationSettings {
+///         position: PositionQuantization::Bounded16 {
+///             min: Vector3::repeat(-500.0),
+///             max: Vector3::repeat(500.0),
+///         },
+///         quantize_rotation: true,
+///         velocity_limit: Some(50.0),
+///     };
+///
+///     // Server side.
+///     let mut packet = Vec::new();
+///     if let Some(state) = ReplicatedState::capture(server, node) {
+///         state.write(&settings, &mut packet).unwrap();
+///     }
+///
+///     // Client side.
+///     let state = ReplicatedState::read(&settings, &mut packet.as_slice()).unwrap();
+///     state.apply(client, node);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ReplicatedState {
+    /// Local position of the node.
+    pub position: Vector3<f32>,
+    /// Local rotation of the node.
+    pub rotation: UnitQuaternion<f32>,
+    /// Velocity of the node, if it is a rigid body.
+    pub velocity: Option<ReplicatedVelocity>,
+    /// States of animations, if the node is an animation player.
+    pub animations: Vec<ReplicatedAnimation>,
+}
+
+const HAS_VELOCITY: u8 = 1;
+const HAS_ANIMATIONS: u8 = 2;
+
+fn quantize(value: f32, min: f32, max: f32) -> u16 {
+    let range = max - min;
+    if range <= 0.0 {
+        return 0;
+    }
+    let t = ((value - min) / range).clamp(0.0, 1.0);
+    (t * u16::MAX as f32).round() as u16
+}
+
+fn dequantize(value: u16, min: f32, max: f32) -> f32 {
+    min + (value as f32 / u16::MAX as f32) * (max - min)
+}
+
+fn quantize_unit(value: f32) -> i16 {
+    (value.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn dequantize_unit(value: i16) -> f32 {
+    value as f32 / i16::MAX as f32
+}
+
+fn write_vector(dest: &mut impl Write, v: &Vector3<f32>) -> io::Result<()> {
+    for c in v.iter() {
+        dest.write_f32::<LittleEndian>(*c)?;
+    }
+    Ok(())
+}
+
+fn read_vector(src: &mut impl Read) -> io::Result<Vector3<f32>> {
+    Ok(Vector3::new(
+        src.read_f32::<LittleEndian>()?,
+        src.read_f32::<LittleEndian>()?,
+        src.read_f32::<LittleEndian>()?,
+    ))
+}
+
+fn write_bounded(
+    dest: &mut impl Write,
+    v: &Vector3<f32>,
+    min: &Vector3<f32>,
+    max: &Vector3<f32>,
+) -> io::Result<()> {
+    for i in 0..3 {
+        dest.write_u16::<LittleEndian>(quantize(v[i], min[i], max[i]))?;
+    }
+    Ok(())
+}
+
+fn read_bounded(
+    src: &mut impl Read,
+    min: &Vector3<f32>,
+    max: &Vector3<f32>,
+) -> io::Result<Vector3<f32>> {
+    let mut v = Vector3::default();
+    for i in 0..3 {
+        v[i] = dequantize(src.read_u16::<LittleEndian>()?, min[i], max[i]);
+    }
+    Ok(v)
+}
+
+fn write_velocity(dest: &mut impl Write, v: &Vector3<f32>, limit: Option<f32>) -> io::Result<()> {
+    match limit {
+        Some(limit) => write_bounded(dest, v, &Vector3::repeat(-limit), &Vector3::repeat(limit)),
+        None => write_vector(dest, v),
+    }
+}
+
+fn read_velocity(src: &mut impl Read, limit: Option<f32>) -> io::Result<Vector3<f32>> {
+    match limit {
+        Some(limit) => read_bounded(src, &Vector3::repeat(-limit), &Vector3::repeat(limit)),
+        None => read_vector(src),
+    }
+}
+
+impl ReplicatedState {
+    /// Captures the state of the given node. Returns [`None`] if there's no such node.
+    pub fn capture(graph: &Graph, handle: Handle<Node>) -> Option<Self> {
+        let node = graph.try_get_node(handle).ok()?;
+        let transform = node.local_transform();
+        Some(Self {
+            position: **transform.position(),
+            rotation: **transform.rotation(),
+            velocity: node.cast::<RigidBody>().map(|body| ReplicatedVelocity {
+                linear: body.lin_vel(),
+                angular: body.ang_vel(),
+            }),
+            animations: node
+                .cast::<AnimationPlayer>()
+                .map(|player| {
+                    player
+                        .animations()
+                        .iter()
+                        .map(|animation| ReplicatedAnimation {
+                            time_position: animation.time_position(),
+                            enabled: animation.is_enabled(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Applies the state to the given node. Velocities are applied only to rigid bodies, states
+    /// of animations are applied only to animation players.
+    pub fn apply(&self, graph: &mut Graph, handle: Handle<Node>) {
+        let Ok(node) = graph.try_get_node_mut(handle) else {
+            return;
+        };
+
+        node.local_transform_mut()
+            .set_position(self.position)
+            .set_rotation(self.rotation);
+
+        if let (Some(velocity), Some(body)) = (self.velocity, node.cast_mut::<RigidBody>()) {
+            body.set_lin_vel(velocity.linear);
+            body.set_ang_vel(velocity.angular);
+        }
+
+        if let Some(player) = node.cast_mut::<AnimationPlayer>() {
+            for (animation, state) in player
+                .animations_mut()
+                .get_value_mut_silent()
+                .iter_mut()
+                .zip(self.animations.iter())
+            {
+                animation
+                    .set_enabled(state.enabled)
+                    .set_time_position(state.time_position);
+            }
+        }
+    }
+
+    /// Writes the state to the given destination, using the given settings.
+    pub fn write(&self, settings: &ReplicationSettings, dest: &mut impl Write) -> io::Result<()> {
+        let mut flags = 0;
+        if self.velocity.is_some() {
+            flags |= HAS_VELOCITY;
+        }
+        if !self.animations.is_empty() {
+            flags |= HAS_ANIMATIONS;
+        }
+        dest.write_u8(flags)?;
+
+        match settings.position {
+            PositionQuantization::None => write_vector(dest, &self.position)?,
+            PositionQuantization::Bounded16 { min, max } => {
+                write_bounded(dest, &self.position, &min, &max)?
+            }
+        }
+
+        let q = self.rotation.quaternion().coords;
+        for c in q.iter() {
+            if settings.quantize_rotation {
+                dest.write_i16::<LittleEndian>(quantize_unit(*c))?;
+            } else {
+                dest.write_f32::<LittleEndian>(*c)?;
+            }
+        }
+
+        if let Some(velocity) = self.velocity {
+            write_velocity(dest, &velocity.linear, settings.velocity_limit)?;
+            write_velocity(dest, &velocity.angular, settings.velocity_limit)?;
+        }
+
+        if !self.animations.is_empty() {
+            let count = u16::try_from(self.animations.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many animations!"))?;
+            dest.write_u16::<LittleEndian>(count)?;
+            for animation in self.animations.iter() {
+                dest.write_f32::<LittleEndian>(animation.time_position)?;
+                dest.write_u8(u8::from(animation.enabled))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a state from the given source, using the given settings. The settings must be the
+    /// same as the ones used for writing.
+    pub fn read(settings: &ReplicationSettings, src: &mut impl Read) -> io::Result<Self> {
+        let flags = src.read_u8()?;
+        if flags & !(HAS_VELOCITY | HAS_ANIMATIONS) != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid replicated state flags {flags}!"),
+            ));
+        }
+
+        let position = match settings.position {
+            PositionQuantization::None => read_vector(src)?,
+            PositionQuantization::Bounded16 { min, max } => read_bounded(src, &min, &max)?,
+        };
+
+        let mut q = [0.0; 4];
+        for c in q.iter_mut() {
+            *c = if settings.quantize_rotation {
+                dequantize_unit(src.read_i16::<LittleEndian>()?)
+            } else {
+                src.read_f32::<LittleEndian>()?
+            };
+        }
+        // Quaternion::new takes the real part first, while coordinates are stored as [i, j, k, w].
+        let rotation = UnitQuaternion::new_normalize(Quaternion::new(q[3], q[0], q[1], q[2]));
+
+        let velocity = if flags & HAS_VELOCITY != 0 {
+            Some(ReplicatedVelocity {
+                linear: read_velocity(src, settings.velocity_limit)?,
+                angular: read_velocity(src, settings.velocity_limit)?,
+            })
+        } else {
+            None
+        };
+
+        let mut animations = Vec::new();
+        if flags & HAS_ANIMATIONS != 0 {
+            let count = src.read_u16::<LittleEndian>()?;
+            for _ in 0..count {
+                animations.push(ReplicatedAnimation {
+                    time_position: src.read_f32::<LittleEndian>()?,
+                    enabled: src.read_u8()? != 0,
+                });
+            }
+        }
+
+        Ok(Self {
+            position,
+            rotation,
+            velocity,
+            animations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{UnitQuaternion, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            node::Node,
+            pivot::PivotBuilder,
+            replication::{
+                PositionQuantization, ReplicatedState, ReplicatedVelocity, ReplicationSettings,
+            },
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn round_trip(state: &ReplicatedState, settings: &ReplicationSettings) -> ReplicatedState {
+        let mut data = Vec::new();
+        state.write(settings, &mut data).unwrap();
+        let mut src = data.as_slice();
+        let result = ReplicatedState::read(settings, &mut src).unwrap();
+        assert!(src.is_empty());
+        result
+    }
+
+    #[test]
+    fn test_quantized_round_trip() {
+        let min = Vector3::new(-100.0, -10.0, -100.0);
+        let max = Vector3::new(100.0, 50.0, 100.0);
+        let settings = ReplicationSettings {
+            position: PositionQuantization::Bounded16 { min, max },
+            quantize_rotation: true,
+            velocity_limit: Some(20.0),
+        };
+        let max_error = (max - min) / (u16::MAX as f32 * 2.0);
+
+        let state = ReplicatedState {
+            position: Vector3::new(12.345, -3.21, 99.9),
+            rotation: UnitQuaternion::from_euler_angles(0.3, -1.2, 2.0),
+            velocity: Some(ReplicatedVelocity {
+                linear: Vector3::new(1.0, -2.5, 3.0),
+                angular: Vector3::new(0.0, 0.5, 0.0),
+            }),
+            animations: Vec::new(),
+        };
+        let result = round_trip(&state, &settings);
+        for i in 0..3 {
+            assert!((result.position[i] - state.position[i]).abs() <= max_error[i] * 1.01);
+        }
+        assert!(result.rotation.angle_to(&state.rotation) < 1.0e-3);
+        let velocity = result.velocity.unwrap();
+        assert!((velocity.linear - Vector3::new(1.0, -2.5, 3.0)).amax() < 1.0e-3);
+
+        // Out-of-range values are clamped.
+        let state = ReplicatedState {
+            position: Vector3::new(500.0, -500.0, 0.0),
+            velocity: Some(ReplicatedVelocity {
+                linear: Vector3::new(100.0, 0.0, 0.0),
+                angular: Vector3::default(),
+            }),
+            ..Default::default()
+        };
+        let result = round_trip(&state, &settings);
+        assert_eq!(result.position.x, max.x);
+        assert_eq!(result.position.y, min.y);
+        assert_eq!(result.velocity.unwrap().linear.x, 20.0);
+
+        // Lossless settings preserve the state exactly.
+        assert_eq!(round_trip(&state, &Default::default()), state);
+    }
+
+    #[test]
+    fn test_capture_apply() {
+        let mut server = Graph::new();
+        let mut client = Graph::new();
+        let make_nodes = |graph: &mut Graph| -> (Handle<Node>, Handle<Node>) {
+            let body = RigidBodyBuilder::new(BaseBuilder::new())
+                .with_lin_vel(Vector3::new(1.0, 2.0, 3.0))
+                .build(graph);
+            let pivot = PivotBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(4.0, 5.0, 6.0))
+                        .build(),
+                ),
+            )
+            .build(graph);
+            (body.to_base(), pivot.to_base())
+        };
+        let (server_body, server_pivot) = make_nodes(&mut server);
+        let (client_body, client_pivot) = make_nodes(&mut client);
+        client[client_body].set_position(Vector3::new(9.0, 9.0, 9.0));
+        client[client_pivot].set_position(Vector3::default());
+
+        let settings = ReplicationSettings::default();
+        for (server_node, client_node) in [(server_body, client_body), (server_pivot, client_pivot)]
+        {
+            let state = ReplicatedState::capture(&server, server_node).unwrap();
+            round_trip(&state, &settings).apply(&mut client, client_node);
+            assert_eq!(
+                client[client_node].local_transform().position(),
+                server[server_node].local_transform().position()
+            );
+        }
+
+        let state = ReplicatedState::capture(&server, server_body).unwrap();
+        assert_eq!(state.velocity.unwrap().linear, Vector3::new(1.0, 2.0, 3.0));
+        assert!(ReplicatedState::capture(&server, server_pivot)
+            .unwrap()
+            .velocity
+            .is_none());
+    }
+}
@@ -0,0 +1,790 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Rope is a flexible chain simulated using Verlet integration. See [`Rope`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector2, Vector3, Vector4},
+        math::{aabb::AxisAlignedBoundingBox, TriangleDefinition},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{constructor::ConstructorProvider, SceneGraph},
+    material::{Material, MaterialResource},
+    renderer::{
+        self,
+        bundle::{RenderContext, SurfaceInstanceData},
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::{Graph, NodePool},
+        mesh::{
+            buffer::{TriangleBuffer, VertexBuffer},
+            surface::{SurfaceData, SurfaceResource},
+            vertex::StaticVertex,
+            RenderPath,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, RdcControlFlow, UpdateContext},
+        verlet::{DistanceConstraint, VerletParticle, VerletState},
+    },
+};
+use std::ops::{Deref, DerefMut};
+
+/// Rope is a chain of particles connected with distance constraints. It could be used for
+/// bridges, tethers, cables, chains and so on.
+///
+/// # Simulation
+///
+/// Particles are integrated using Verlet integration and affected by gravity. After the
+/// integration, distance constraints between neighbouring particles are solved iteratively - the
+/// more iterations, the less the rope stretches under its own weight. The simulation is performed
+/// in world coordinates, the solver is shared with [`super::cloth::Cloth`] (see
+/// [`super::verlet`]).
+///
+/// # Endpoints
+///
+/// The first particle of the rope is always pinned - it follows the start node (see
+/// [`Self::set_start`]) or the rope node itself, if the start node is not set. The last particle
+/// follows the end node (see [`Self::set_end`]), if it is set, otherwise the end of the rope is
+/// free. Endpoints are attached to the global positions of the nodes, and they can be attached to
+/// any node including rigid bodies and bones.
+///
+/// When the simulation starts, the rope is laid out as a straight line between its endpoints. If
+/// the end is free, the rope hangs down along the negative Y axis of the rope node.
+///
+/// # Rendering
+///
+/// The rope is rendered as a tube with the given radius, that is generated from the simulated
+/// particles and written to a dynamic surface (see [`SurfaceData::update_vertices`]) every frame.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         node::Node,
+/// #         rope::{Rope, RopeBuilder},
+/// #     },
+/// # };
+/// fn create_tether(graph: &mut Graph, start: Handle<Node>, end: Handle<Node>) -> Handle<Rope> {
+///     RopeBuilder::new(BaseBuilder::new())
+///         .with_start(start)
+///         .with_end(end)
+///         .with_length(5.0)
+///         .with_segment_count(24)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Rope {
+    base: Base,
+
+    #[reflect(setter = "set_start")]
+    start: InheritableVariable<Handle<Node>>,
+
+    #[reflect(setter = "set_end")]
+    end: InheritableVariable<Handle<Node>>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_length")]
+    length: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, step = 1.0)]
+    #[reflect(setter = "set_segment_count")]
+    segment_count: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_gravity")]
+    gravity: InheritableVariable<Vector3<f32>>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_damping")]
+    damping: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_stiffness")]
+    stiffness: InheritableVariable<f32>,
+
+    #[reflect(min_value = 1.0, step = 1.0)]
+    #[reflect(setter = "set_solver_iterations")]
+    solver_iterations: InheritableVariable<u32>,
+
+    #[reflect(min_value = 0.0, step = 0.01)]
+    #[reflect(setter = "set_radius")]
+    radius: InheritableVariable<f32>,
+
+    #[reflect(min_value = 3.0, step = 1.0)]
+    #[reflect(setter = "set_radial_segment_count")]
+    radial_segment_count: InheritableVariable<u32>,
+
+    #[reflect(setter = "set_material")]
+    material: InheritableVariable<MaterialResource>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    state: VerletState,
+}
+
+impl Default for Rope {
+    fn default() -> Self {
+        RopeBuilder::new(BaseBuilder::new()).build_rope()
+    }
+}
+
+impl Deref for Rope {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Rope {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Rope {
+    fn type_uuid() -> Uuid {
+        uuid!("3c9d5e7f-1a2b-4c6d-8e0f-a1b2c3d4e5f6")
+    }
+}
+
+impl Rope {
+    /// Sets a node to which the start of the rope is attached. If the handle is [`Handle::NONE`],
+    /// the start of the rope is attached to the rope node itself.
+    pub fn set_start(&mut self, start: Handle<Node>) -> Handle<Node> {
+        self.start.set_value_and_mark_modified(start)
+    }
+
+    /// Returns a node to which the start of the rope is attached.
+    pub fn start(&self) -> Handle<Node> {
+        *self.start
+    }
+
+    /// Sets a node to which the end of the rope is attached. If the handle is [`Handle::NONE`],
+    /// the end of the rope is free.
+    pub fn set_end(&mut self, end: Handle<Node>) -> Handle<Node> {
+        self.end.set_value_and_mark_modified(end)
+    }
+
+    /// Returns a node to which the end of the rope is attached.
+    pub fn end(&self) -> Handle<Node> {
+        *self.end
+    }
+
+    /// Sets length of the rope (in meters) at rest. Changing the length restarts the simulation.
+    pub fn set_length(&mut self, length: f32) -> f32 {
+        self.reset();
+        self.length.set_value_and_mark_modified(length.max(0.001))
+    }
+
+    /// Returns length of the rope at rest.
+    pub fn length(&self) -> f32 {
+        *self.length
+    }
+
+    /// Sets amount of segments of the rope. More segments make the rope smoother, but the
+    /// simulation becomes more expensive. Changing the amount of segments restarts the
+    /// simulation.
+    pub fn set_segment_count(&mut self, count: u32) -> u32 {
+        self.reset();
+        self.segment_count.set_value_and_mark_modified(count.max(1))
+    }
+
+    /// Returns amount of segments of the rope.
+    pub fn segment_count(&self) -> u32 {
+        *self.segment_count
+    }
+
+    /// Sets gravity acceleration (in m/s²) in world coordinates.
+    pub fn set_gravity(&mut self, gravity: Vector3<f32>) -> Vector3<f32> {
+        self.gravity.set_value_and_mark_modified(gravity)
+    }
+
+    /// Returns gravity acceleration.
+    pub fn gravity(&self) -> Vector3<f32> {
+        *self.gravity
+    }
+
+    /// Sets velocity damping (in 1/s). It defines how fast particles lose their velocity.
+    pub fn set_damping(&mut self, damping: f32) -> f32 {
+        self.damping.set_value_and_mark_modified(damping.max(0.0))
+    }
+
+    /// Returns velocity damping.
+    pub fn damping(&self) -> f32 {
+        *self.damping
+    }
+
+    /// Sets stiffness of the segments in `[0; 1]` range. Lower values make the rope more elastic.
+    pub fn set_stiffness(&mut self, stiffness: f32) -> f32 {
+        self.stiffness
+            .set_value_and_mark_modified(stiffness.clamp(0.0, 1.0))
+    }
+
+    /// Returns stiffness of the segments.
+    pub fn stiffness(&self) -> f32 {
+        *self.stiffness
+    }
+
+    /// Sets amount of iterations of the constraint solver per update. More iterations make the
+    /// rope less stretchy, but the simulation becomes more expensive.
+    pub fn set_solver_iterations(&mut self, iterations: u32) -> u32 {
+        self.solver_iterations
+            .set_value_and_mark_modified(iterations.max(1))
+    }
+
+    /// Returns amount of iterations of the constraint solver per update.
+    pub fn solver_iterations(&self) -> u32 {
+        *self.solver_iterations
+    }
+
+    /// Sets radius of the tube that is used to render the rope.
+    pub fn set_radius(&mut self, radius: f32) -> f32 {
+        self.radius.set_value_and_mark_modified(radius.max(0.0))
+    }
+
+    /// Returns radius of the tube that is used to render the rope.
+    pub fn radius(&self) -> f32 {
+        *self.radius
+    }
+
+    /// Sets amount of segments around the tube that is used to render the rope. Changing the
+    /// amount of segments restarts the simulation.
+    pub fn set_radial_segment_count(&mut self, count: u32) -> u32 {
+        self.reset();
+        self.radial_segment_count
+            .set_value_and_mark_modified(count.max(3))
+    }
+
+    /// Returns amount of segments around the tube that is used to render the rope.
+    pub fn radial_segment_count(&self) -> u32 {
+        *self.radial_segment_count
+    }
+
+    /// Sets a material that will be used to render the rope.
+    pub fn set_material(&mut self, material: MaterialResource) -> MaterialResource {
+        self.material.set_value_and_mark_modified(material)
+    }
+
+    /// Returns current material of the rope.
+    pub fn material(&self) -> &MaterialResource {
+        &self.material
+    }
+
+    /// Returns simulated particles of the rope, from the start to the end. The slice is empty
+    /// until the first update of the rope.
+    pub fn particles(&self) -> &[VerletParticle] {
+        &self.state.particles
+    }
+
+    /// Returns a surface with the current shape of the rope in world coordinates. It is [`None`]
+    /// until the first update of the rope.
+    pub fn surface(&self) -> Option<&SurfaceResource> {
+        self.state.surface.as_ref()
+    }
+
+    /// Drops the current simulation state, the rope will be laid out between its endpoints again
+    /// on the next update.
+    pub fn reset(&mut self) {
+        self.state = Default::default();
+    }
+
+    fn start_position(&self, nodes: &NodePool) -> Vector3<f32> {
+        nodes
+            .try_get(*self.start)
+            .map(|node| node.global_position())
+            .unwrap_or_else(|_| self.global_position())
+    }
+
+    fn end_position(&self, nodes: &NodePool) -> Option<Vector3<f32>> {
+        nodes
+            .try_get(*self.end)
+            .ok()
+            .map(|node| node.global_position())
+    }
+
+    fn initialize(&mut self, nodes: &NodePool) {
+        let segment_count = (*self.segment_count).max(1) as usize;
+        let start = self.start_position(nodes);
+        let end = self.end_position(nodes).unwrap_or_else(|| {
+            let down = -self
+                .up_vector()
+                .try_normalize(f32::EPSILON)
+                .unwrap_or_else(Vector3::y);
+            start + down.scale(*self.length)
+        });
+
+        let mut particles = (0..=segment_count)
+            .map(|i| VerletParticle::new(start.lerp(&end, i as f32 / segment_count as f32)))
+            .collect::<Vec<_>>();
+        particles[0].pinned = true;
+
+        let segment_length = *self.length / segment_count as f32;
+        let constraints = (0..segment_count)
+            .map(|i| DistanceConstraint {
+                a: i,
+                b: i + 1,
+                rest_length: segment_length,
+            })
+            .collect();
+
+        let radial_segment_count = (*self.radial_segment_count).max(3) as usize;
+        let ring_size = radial_segment_count as u32 + 1;
+        let mut triangles = Vec::with_capacity(segment_count * radial_segment_count * 2);
+        for i in 0..segment_count as u32 {
+            for j in 0..radial_segment_count as u32 {
+                let i0 = i * ring_size + j;
+                let i1 = i0 + 1;
+                let i2 = i0 + ring_size;
+                let i3 = i2 + 1;
+                triangles.push(TriangleDefinition([i0, i1, i2]));
+                triangles.push(TriangleDefinition([i1, i3, i2]));
+            }
+        }
+
+        let vertices = make_tube_vertices(&particles, *self.radius, radial_segment_count);
+        let mut data = SurfaceData::new(
+            VertexBuffer::new(vertices.len(), vertices).unwrap(),
+            TriangleBuffer::new(triangles),
+        );
+        data.set_dynamic(true);
+
+        self.state = VerletState {
+            particles,
+            constraints,
+            surface: Some(SurfaceResource::new_embedded(data)),
+            world_bounding_box: Default::default(),
+        };
+        self.state.update_bounding_box(*self.radius * 2.0);
+    }
+
+    fn simulate(&mut self, nodes: &NodePool, dt: f32) {
+        let start = self.start_position(nodes);
+        let end = self.end_position(nodes);
+        let state = &mut self.state;
+
+        state.particles.first_mut().unwrap().teleport(start);
+
+        let last = state.particles.last_mut().unwrap();
+        last.pinned = end.is_some();
+        if let Some(end) = end {
+            last.teleport(end);
+        }
+
+        let gravity = *self.gravity;
+        state.integrate(*self.damping, dt, |_| gravity);
+        state.solve_constraints(*self.stiffness, *self.solver_iterations);
+        state.update_bounding_box(*self.radius * 2.0);
+    }
+
+    fn update_surface(&self) {
+        let Some(surface) = self.state.surface.as_ref() else {
+            return;
+        };
+        let vertices = make_tube_vertices(
+            &self.state.particles,
+            *self.radius,
+            (*self.radial_segment_count).max(3) as usize,
+        );
+        // Vertex layout and amount of vertices never change, so it cannot fail.
+        surface.data_ref().update_vertices(&vertices).unwrap();
+    }
+}
+
+fn make_tube_vertices(
+    particles: &[VerletParticle],
+    radius: f32,
+    radial_segment_count: usize,
+) -> Vec<StaticVertex> {
+    let mut vertices = Vec::with_capacity(particles.len() * (radial_segment_count + 1));
+    let mut length = 0.0;
+    let mut normal: Option<Vector3<f32>> = None;
+    for (i, particle) in particles.iter().enumerate() {
+        let next = particles.get(i + 1).unwrap_or(particle);
+        let prev = if i > 0 { &particles[i - 1] } else { particle };
+        let direction = (next.position - prev.position)
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+
+        // Rings are oriented using parallel transport of the normal of the previous ring, this
+        // way the tube does not twist when the rope bends.
+        let reference = normal
+            .filter(|normal| normal.dot(&direction).abs() < 0.99)
+            .unwrap_or_else(|| {
+                if direction.x.abs() < 0.9 {
+                    Vector3::x()
+                } else {
+                    Vector3::z()
+                }
+            });
+        let ring_normal = (reference - direction.scale(reference.dot(&direction))).normalize();
+        let binormal = direction.cross(&ring_normal);
+        normal = Some(ring_normal);
+
+        length += prev.position.metric_distance(&particle.position);
+        for j in 0..=radial_segment_count {
+            let angle = j as f32 / radial_segment_count as f32 * std::f32::consts::TAU;
+            let (sin, cos) = angle.sin_cos();
+            let vertex_normal = ring_normal.scale(cos) + binormal.scale(sin);
+            let tangent = direction.cross(&vertex_normal);
+            vertices.push(StaticVertex {
+                position: particle.position + vertex_normal.scale(radius),
+                tex_coord: Vector2::new(j as f32 / radial_segment_count as f32, length),
+                normal: vertex_normal,
+                tangent: Vector4::new(tangent.x, tangent.y, tangent.z, 1.0),
+            });
+        }
+    }
+    vertices
+}
+
+impl ConstructorProvider<Node, Graph> for Rope {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Rope", |_| {
+                RopeBuilder::new(BaseBuilder::new().with_name("Rope"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for Rope {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        let radius = *self.radius;
+        AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-radius, -*self.length, -radius),
+            Vector3::new(radius, 0.0, radius),
+        )
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.state.particles.is_empty() {
+            self.local_bounding_box()
+                .transform(&self.global_transform())
+        } else {
+            self.state.world_bounding_box
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.is_globally_enabled() || context.dt <= 0.0 {
+            return;
+        }
+
+        let segment_count = (*self.segment_count).max(1) as usize;
+        if self.state.particles.len() != segment_count + 1 {
+            self.initialize(context.nodes);
+        }
+
+        self.simulate(context.nodes, context.dt);
+        self.update_surface();
+    }
+
+    fn collect_render_data(&self, ctx: &mut RenderContext) -> RdcControlFlow {
+        if !self.should_be_rendered(ctx.frustum, ctx.render_mask) {
+            return RdcControlFlow::Continue;
+        }
+
+        if renderer::is_shadow_pass(ctx.render_pass_name) && !self.cast_shadows() {
+            return RdcControlFlow::Continue;
+        }
+
+        let Some(surface) = self.state.surface.as_ref() else {
+            return RdcControlFlow::Continue;
+        };
+
+        ctx.storage.push(
+            surface,
+            &self.material,
+            RenderPath::Deferred,
+            ctx.calculate_sorting_index(self.world_bounding_box().center()),
+            SurfaceInstanceData {
+                // Vertices of the surface are already in world coordinates.
+                world_transform: Matrix4::identity(),
+                node_handle: self.handle(),
+                ..Default::default()
+            },
+        );
+
+        RdcControlFlow::Continue
+    }
+}
+
+/// Allows you to create a rope in a declarative manner.
+pub struct RopeBuilder {
+    base_builder: BaseBuilder,
+    start: Handle<Node>,
+    end: Handle<Node>,
+    length: f32,
+    segment_count: u32,
+    gravity: Vector3<f32>,
+    damping: f32,
+    stiffness: f32,
+    solver_iterations: u32,
+    radius: f32,
+    radial_segment_count: u32,
+    material: MaterialResource,
+}
+
+impl RopeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            start: Default::default(),
+            end: Default::default(),
+            length: 2.0,
+            segment_count: 16,
+            gravity: Vector3::new(0.0, -9.81, 0.0),
+            damping: 0.5,
+            stiffness: 1.0,
+            solver_iterations: 16,
+            radius: 0.02,
+            radial_segment_count: 8,
+            material: MaterialResource::new_ok(
+                Uuid::new_v4(),
+                Default::default(),
+                Material::standard(),
+            ),
+        }
+    }
+
+    /// Sets desired node to which the start of the rope is attached.
+    pub fn with_start(mut self, start: Handle<Node>) -> Self {
+        self.start = start;
+        self
+    }
+
+    /// Sets desired node to which the end of the rope is attached.
+    pub fn with_end(mut self, end: Handle<Node>) -> Self {
+        self.end = end;
+        self
+    }
+
+    /// Sets desired length of the rope at rest.
+    pub fn with_length(mut self, length: f32) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Sets desired amount of segments of the rope.
+    pub fn with_segment_count(mut self, count: u32) -> Self {
+        self.segment_count = count;
+        self
+    }
+
+    /// Sets desired gravity acceleration.
+    pub fn with_gravity(mut self, gravity: Vector3<f32>) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    /// Sets desired velocity damping.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets desired stiffness of the segments.
+    pub fn with_stiffness(mut self, stiffness: f32) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    /// Sets desired amount of iterations of the constraint solver.
+    pub fn with_solver_iterations(mut self, iterations: u32) -> Self {
+        self.solver_iterations = iterations;
+        self
+    }
+
+    /// Sets desired radius of the tube.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets desired amount of segments around the tube.
+    pub fn with_radial_segment_count(mut self, count: u32) -> Self {
+        self.radial_segment_count = count;
+        self
+    }
+
+    /// Sets desired material.
+    pub fn with_material(mut self, material: MaterialResource) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Creates new rope.
+    pub fn build_rope(self) -> Rope {
+        Rope {
+            base: self.base_builder.build_base(),
+            start: self.start.into(),
+            end: self.end.into(),
+            length: self.length.max(0.001).into(),
+            segment_count: self.segment_count.max(1).into(),
+            gravity: self.gravity.into(),
+            damping: self.damping.max(0.0).into(),
+            stiffness: self.stiffness.clamp(0.0, 1.0).into(),
+            solver_iterations: self.solver_iterations.max(1).into(),
+            radius: self.radius.max(0.0).into(),
+            radial_segment_count: self.radial_segment_count.max(3).into(),
+            material: self.material.into(),
+            state: Default::default(),
+        }
+    }
+
+    /// Creates new rope node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_rope())
+    }
+
+    /// Creates new instance of rope node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Rope> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder, graph::Graph, node::Node, pivot::PivotBuilder, rope::RopeBuilder,
+            transform::TransformBuilder,
+        },
+    };
+
+    fn pivot_at(graph: &mut Graph, position: Vector3<f32>) -> Handle<Node> {
+        PivotBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .build(),
+            ),
+        )
+        .build(graph)
+        .to_base()
+    }
+
+    #[test]
+    fn test_rope_pinned_at_both_ends_forms_catenary() {
+        let mut graph = Graph::new();
+
+        let span = 2.0;
+        let length = 3.0;
+        let start = pivot_at(&mut graph, Vector3::new(-span * 0.5, 0.0, 0.0));
+        let end = pivot_at(&mut graph, Vector3::new(span * 0.5, 0.0, 0.0));
+        let rope = RopeBuilder::new(BaseBuilder::new())
+            .with_start(start)
+            .with_end(end)
+            .with_length(length)
+            .with_segment_count(20)
+            .with_damping(2.0)
+            .build(&mut graph);
+
+        for _ in 0..1200 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+
+        // Find the parameter of the catenary y = a * cosh(x / a) that has the same span and
+        // length: 2 * a * sinh(span / (2 * a)) = length.
+        let (mut min, mut max) = (0.01f32, 100.0f32);
+        for _ in 0..100 {
+            let a = (min + max) * 0.5;
+            if 2.0 * a * (span / (2.0 * a)).sinh() > length {
+                min = a;
+            } else {
+                max = a;
+            }
+        }
+        let a = min;
+        let catenary = |x: f32| a * ((x / a).cosh() - (span / (2.0 * a)).cosh());
+
+        let rope = &graph[rope];
+        let particles = rope.particles();
+        assert_eq!(particles.len(), 21);
+        assert!(rope.surface().is_some());
+
+        // Endpoints stay attached.
+        assert!(
+            particles[0]
+                .position
+                .metric_distance(&Vector3::new(-1.0, 0.0, 0.0))
+                < 1.0e-5
+        );
+        assert!(
+            particles[20]
+                .position
+                .metric_distance(&Vector3::new(1.0, 0.0, 0.0))
+                < 1.0e-5
+        );
+
+        for particle in particles {
+            let position = particle.position;
+            assert!(position.z.abs() < 1.0e-4);
+            assert!((position.y - catenary(position.x)).abs() < 0.05);
+            // The rope is at rest.
+            assert!(position.metric_distance(&particle.previous_position) < 1.0e-4);
+        }
+    }
+
+    #[test]
+    fn test_rope_with_free_end_hangs_down() {
+        let mut graph = Graph::new();
+
+        let start = pivot_at(&mut graph, Vector3::new(0.0, 5.0, 0.0));
+        let rope = RopeBuilder::new(BaseBuilder::new())
+            .with_start(start)
+            .with_length(2.0)
+            .with_damping(2.0)
+            .build(&mut graph);
+
+        for _ in 0..600 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+
+        let particles = graph[rope].particles();
+        let first = particles.first().unwrap().position;
+        let last = particles.last().unwrap().position;
+        assert!(first.metric_distance(&Vector3::new(0.0, 5.0, 0.0)) < 1.0e-5);
+        assert!(last.x.abs() < 1.0e-3 && last.z.abs() < 1.0e-3);
+        assert!((last.y - 3.0).abs() < 0.1);
+    }
+}
@@ -19,12 +19,15 @@
 // SOFTWARE.
 
 //! Skybox is a huge box around a camera. See [`SkyBox`] docs for more info.
+//!
+//! Besides textured sky boxes, there's procedural sky (see [`ProceduralSky`]), which is computed
+//! from the direction of the sun using Preetham's analytic sky model.
 
 use std::{fmt::Display, sync::LazyLock};
 
 use crate::{
     asset::{builtin::BuiltInResource, embedded_data_source, untyped::ResourceKind},
-    core::{log::Log, reflect::prelude::*, uuid_provider, visitor::prelude::*},
+    core::{algebra::Vector3, log::Log, reflect::prelude::*, uuid_provider, visitor::prelude::*},
 };
 use fyrox_core::color::Color;
 use fyrox_texture::{
@@ -38,7 +41,7 @@ use uuid::{uuid, Uuid};
 /// skies and/or some other objects (mountains, buildings, etc.). Usually skyboxes used
 /// in outdoor scenes, however real use of it limited only by your imagination. Skybox
 /// will be drawn first, none of objects could be drawn before skybox.
-#[derive(Debug, Clone, Default, PartialEq, Reflect, Visit, Eq)]
+#[derive(Debug, Default, PartialEq, Reflect, Visit, Eq)]
 pub struct SkyBox {
     /// Texture for front face.
     #[reflect(setter = "set_front")]
@@ -64,6 +67,12 @@ pub struct SkyBox {
     #[reflect(setter = "set_bottom")]
     pub(crate) bottom: Option<TextureResource>,
 
+    /// Parameters of procedural sky. If set, the face textures are ignored and the cube map is
+    /// generated from the parameters. See [`ProceduralSky`] docs for more info.
+    #[reflect(setter = "set_procedural")]
+    #[visit(optional)]
+    pub(crate) procedural: Option<ProceduralSky>,
+
     /// Cubemap texture
     #[reflect(hidden)]
     #[visit(skip)]
@@ -72,6 +81,29 @@ pub struct SkyBox {
 
 uuid_provider!(SkyBox = "45f359f1-e26f-4ace-81df-097f63474c72");
 
+impl Clone for SkyBox {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            top: self.top.clone(),
+            bottom: self.bottom.clone(),
+            procedural: self.procedural.clone(),
+            // Cube map of procedural sky is updated in place (see [`Self::set_procedural`]), so
+            // every sky box must own a separate copy of it.
+            cubemap: match self.procedural {
+                Some(_) => self
+                    .cubemap
+                    .as_ref()
+                    .map(|cubemap| TextureResource::new_embedded(cubemap.data_ref().clone())),
+                None => self.cubemap.clone(),
+            },
+        }
+    }
+}
+
 impl SkyBox {
     /// Creates a new sky box from a single color.
     pub fn from_single_color(color: Color) -> Self {
@@ -165,6 +197,11 @@ impl SkyBox {
     ///
     /// It will fail if provided face's kind is not TextureKind::Rectangle.
     pub fn create_cubemap(&mut self) -> Result<(), SkyBoxError> {
+        if let Some(procedural) = self.procedural.as_ref() {
+            self.cubemap = Some(procedural.create_cubemap()?);
+            return Ok(());
+        }
+
         self.validate()?;
 
         let (kind, pixel_kind, bytes_per_face) =
@@ -222,6 +259,37 @@ impl SkyBox {
         Ok(())
     }
 
+    /// Creates a new procedural sky box. See [`ProceduralSky`] docs for more info.
+    pub fn from_procedural(procedural: ProceduralSky) -> Result<Self, SkyBoxError> {
+        let mut skybox = Self {
+            procedural: Some(procedural),
+            ..Default::default()
+        };
+        skybox.create_cubemap()?;
+        Ok(skybox)
+    }
+
+    /// Sets new parameters of procedural sky and regenerates the cube map. [`None`] switches the
+    /// sky box back to the face textures. If the sky box already has procedural sky with the same
+    /// resolution, the existing cube map is updated in place, instead of creating a new one.
+    pub fn set_procedural(&mut self, procedural: Option<ProceduralSky>) -> Option<ProceduralSky> {
+        let prev = std::mem::replace(&mut self.procedural, procedural);
+        let updated_in_place = prev.is_some()
+            && matches!(
+                (&self.procedural, &self.cubemap),
+                (Some(procedural), Some(cubemap)) if procedural.bake_into(cubemap)
+            );
+        if !updated_in_place {
+            Log::verify(self.create_cubemap());
+        }
+        prev
+    }
+
+    /// Returns parameters of procedural sky (if any).
+    pub fn procedural(&self) -> Option<&ProceduralSky> {
+        self.procedural.as_ref()
+    }
+
     /// Returns slice with all textures, where: 0 - Left, 1 - Right, 2 - Top, 3 - Bottom
     /// 4 - Front, 5 - Back.
     ///
@@ -485,6 +553,7 @@ impl SkyBoxBuilder {
             bottom: self.bottom,
             front: self.front,
             back: self.back,
+            procedural: None,
             cubemap: None,
         };
 
@@ -607,4 +676,426 @@ pub enum SkyBoxKind {
     None,
     /// Specific skybox. One can be built using [`SkyBoxBuilder`].
     Specific(SkyBox),
+    /// Procedural sky, computed from the sun direction. See [`ProceduralSky`] docs for more info.
+    Procedural(ProceduralSky),
+}
+
+impl SkyBoxKind {
+    /// Creates the sky box of this kind. Returns [`None`] for [`SkyBoxKind::None`].
+    pub fn into_skybox(self) -> Result<Option<SkyBox>, SkyBoxError> {
+        match self {
+            SkyBoxKind::Builtin => Ok(Some(Self::built_in_skybox().clone())),
+            SkyBoxKind::None => Ok(None),
+            SkyBoxKind::Specific(skybox) => Ok(Some(skybox)),
+            SkyBoxKind::Procedural(procedural) => SkyBox::from_procedural(procedural).map(Some),
+        }
+    }
+}
+
+/// Procedural sky, that is computed using analytic sky model by Preetham et al. ("A Practical
+/// Analytic Model for Daylight"). The sky is fully defined by the direction to the sun, turbidity
+/// of the atmosphere and the color of the ground. Low sun makes the zenith darker and the horizon
+/// around the sun warmer, once the sun goes below the horizon, the sky smoothly fades to night.
+///
+/// The sky is baked into a cube map with `resolution` pixels per face side on each change of the
+/// parameters, which makes it usable both for rendering and as a source of environment lighting.
+/// The direction of the sun can be driven by a directional light, see [`Self::set_sun_from_light`].
+#[derive(Debug, Clone, Reflect, Visit)]
+pub struct ProceduralSky {
+    /// Direction **to** the sun. Does not need to be normalized.
+    pub sun_direction: Vector3<f32>,
+    /// Turbidity of the atmosphere, it defines the amount of haze. Values in `[2; 10]` range give
+    /// plausible results, where 2 is a clear sky and 10 is a hazy sky.
+    #[reflect(min_value = 1.7, max_value = 10.0, step = 0.1)]
+    pub turbidity: f32,
+    /// Color of the ground (the lower hemisphere).
+    pub ground_color: Color,
+    /// Size of a face of the baked cube map in pixels.
+    #[reflect(min_value = 1.0, max_value = 1024.0)]
+    pub resolution: u32,
+}
+
+uuid_provider!(ProceduralSky = "d51b1e1e-73e5-4f5a-9a0e-0b5d0a0b3d7e");
+
+// Floats are compared bitwise, this makes the comparison reflexive (even for NaNs) and allows the
+// sky box to be `Eq`.
+impl PartialEq for ProceduralSky {
+    fn eq(&self, other: &Self) -> bool {
+        self.sun_direction.map(f32::to_bits) == other.sun_direction.map(f32::to_bits)
+            && self.turbidity.to_bits() == other.turbidity.to_bits()
+            && self.ground_color == other.ground_color
+            && self.resolution == other.resolution
+    }
+}
+
+impl Eq for ProceduralSky {}
+
+impl Default for ProceduralSky {
+    fn default() -> Self {
+        Self {
+            sun_direction: Vector3::new(0.0, 0.5, 1.0),
+            turbidity: 2.5,
+            ground_color: Color::opaque(90, 85, 80),
+            resolution: 64,
+        }
+    }
+}
+
+/// Perez et al. sky luminance distribution function.
+fn perez(theta: f32, gamma: f32, [a, b, c, d, e]: [f32; 5]) -> f32 {
+    (1.0 + a * (b / theta.cos().max(0.01)).exp())
+        * (1.0 + c * (d * gamma).exp() + e * gamma.cos() * gamma.cos())
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Approximates color of a black body with the given temperature (in Kelvins), the approximation
+/// is valid in `[1000; 40000]` range.
+fn color_temperature(kelvin: f32) -> Color {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.69873 * (t - 60.0).powf(-0.13320476)
+    };
+    let g = if t <= 66.0 {
+        99.4708 * t.ln() - 161.11957
+    } else {
+        288.12216 * (t - 60.0).powf(-0.07551485)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.51773 * (t - 10.0).ln() - 305.0448
+    };
+    Color::opaque(
+        r.clamp(0.0, 255.0) as u8,
+        g.clamp(0.0, 255.0) as u8,
+        b.clamp(0.0, 255.0) as u8,
+    )
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let srgb = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+impl ProceduralSky {
+    // Maps sky luminance (in kcd/m²) to [0; 1] range.
+    const EXPOSURE: f32 = 0.06;
+
+    /// Linear color of the sky at night.
+    const NIGHT_COLOR: Vector3<f32> = Vector3::new(0.004, 0.006, 0.015);
+
+    /// Color temperature of the sunlight at the horizon (in Kelvins).
+    const SUNSET_TEMPERATURE: f32 = 1900.0;
+
+    /// Color temperature of the sunlight when the sun is high (in Kelvins).
+    const NOON_TEMPERATURE: f32 = 5800.0;
+
+    /// Calculates color of the sunlight for the given direction to the sun. The color gets warmer
+    /// when the sun approaches the horizon.
+    pub fn sun_light_color(sun_direction: Vector3<f32>) -> Color {
+        let elevation = sun_direction
+            .try_normalize(f32::EPSILON)
+            .map_or(1.0, |d| d.y);
+        let k = smoothstep(-0.05, 0.6, elevation);
+        color_temperature(
+            Self::SUNSET_TEMPERATURE + (Self::NOON_TEMPERATURE - Self::SUNSET_TEMPERATURE) * k,
+        )
+    }
+
+    /// Calculates intensity multiplier (in `[0; 1]` range) of the sunlight for the given direction
+    /// to the sun. The sunlight gets dimmer when the sun approaches the horizon and fades out
+    /// completely when it goes below it.
+    pub fn sun_light_intensity(sun_direction: Vector3<f32>) -> f32 {
+        let elevation = sun_direction
+            .try_normalize(f32::EPSILON)
+            .map_or(1.0, |d| d.y);
+        smoothstep(-0.1, 0.05, elevation) * (0.25 + 0.75 * smoothstep(0.0, 0.5, elevation))
+    }
+
+    /// Sets new direction to the sun.
+    pub fn set_sun_direction(&mut self, direction: Vector3<f32>) {
+        self.sun_direction = direction;
+    }
+
+    /// Sets the direction to the sun from the given directional light. Directional lights emit
+    /// light along the negative direction of their up vector, so the sun is located along the up
+    /// vector.
+    pub fn set_sun_from_light(&mut self, light: &crate::scene::base::Base) {
+        self.sun_direction = light.up_vector();
+    }
+
+    /// Returns normalized direction to the sun.
+    pub fn normalized_sun_direction(&self) -> Vector3<f32> {
+        self.sun_direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y)
+    }
+
+    /// Calculates linear color of the sky in the given direction (from the observer).
+    pub fn sample(&self, direction: Vector3<f32>) -> Vector3<f32> {
+        let direction = direction
+            .try_normalize(f32::EPSILON)
+            .unwrap_or_else(Vector3::y);
+        let sun = self.normalized_sun_direction();
+        let t = self.turbidity.clamp(1.7, 10.0);
+
+        // The model is valid only for the sun above the horizon.
+        let theta_s = sun
+            .y
+            .clamp(0.0, 1.0)
+            .acos()
+            .min(std::f32::consts::FRAC_PI_2 - 0.01);
+        let (t2, t3) = (theta_s * theta_s, theta_s * theta_s * theta_s);
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (std::f32::consts::PI - 2.0 * theta_s);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = t * t * (0.00166 * t3 - 0.00375 * t2 + 0.00209 * theta_s)
+            + t * (-0.02903 * t3 + 0.06377 * t2 - 0.03202 * theta_s + 0.00394)
+            + (0.11693 * t3 - 0.21196 * t2 + 0.06052 * theta_s + 0.25886);
+        let zenith_y = t * t * (0.00275 * t3 - 0.00610 * t2 + 0.00317 * theta_s)
+            + t * (-0.04214 * t3 + 0.08970 * t2 - 0.04153 * theta_s + 0.00516)
+            + (0.15346 * t3 - 0.26756 * t2 + 0.06670 * theta_s + 0.26688);
+
+        let coefficients_luminance = [
+            0.1787 * t - 1.4630,
+            -0.3554 * t + 0.4275,
+            -0.0227 * t + 5.3251,
+            0.1206 * t - 2.5771,
+            -0.0670 * t + 0.3703,
+        ];
+        let coefficients_x = [
+            -0.0193 * t - 0.2592,
+            -0.0665 * t + 0.0008,
+            -0.0004 * t + 0.2125,
+            -0.0641 * t - 0.8989,
+            -0.0033 * t + 0.0452,
+        ];
+        let coefficients_y = [
+            -0.0167 * t - 0.2608,
+            -0.0950 * t + 0.0092,
+            -0.0079 * t + 0.2102,
+            -0.0441 * t - 1.6537,
+            -0.0109 * t + 0.0529,
+        ];
+
+        // Evaluate the sky slightly above the horizon for directions below it.
+        let theta = direction.y.clamp(0.0, 1.0).acos();
+        let gamma = direction.dot(&sun).clamp(-1.0, 1.0).acos();
+        let distribution = |coefficients: [f32; 5]| {
+            perez(theta, gamma, coefficients) / perez(0.0, theta_s, coefficients)
+        };
+
+        let luminance = zenith_luminance * distribution(coefficients_luminance);
+        let x = zenith_x * distribution(coefficients_x);
+        let y = (zenith_y * distribution(coefficients_y)).max(f32::EPSILON);
+
+        // xyY -> XYZ -> linear sRGB.
+        let cx = x / y * luminance;
+        let cz = (1.0 - x - y) / y * luminance;
+        let rgb = Vector3::new(
+            3.2406 * cx - 1.5372 * luminance - 0.4986 * cz,
+            -0.9689 * cx + 1.8758 * luminance + 0.0415 * cz,
+            0.0557 * cx - 0.2040 * luminance + 1.0570 * cz,
+        );
+        let sky = rgb.map(|c| 1.0 - (-c.max(0.0) * Self::EXPOSURE).exp());
+
+        // Fade to night when the sun goes below the horizon.
+        let day_factor = smoothstep(-0.1, 0.05, sun.y);
+        let sky = Self::NIGHT_COLOR.lerp(&sky, day_factor);
+
+        // Ground is lit by the sun and smoothly blended with the horizon.
+        let ground_color = self.ground_color.srgb_to_linear_f32().xyz();
+        let ground = ground_color.scale(0.1 + 0.9 * sun.y.max(0.0)) * day_factor;
+        let ground_factor = smoothstep(0.0, -0.05, direction.y);
+        sky.lerp(&ground, ground_factor)
+    }
+
+    /// Calculates (sRGB) color of the sky in the given direction (from the observer).
+    pub fn sample_color(&self, direction: Vector3<f32>) -> Color {
+        let color = self.sample(direction);
+        Color::opaque(
+            linear_to_srgb(color.x),
+            linear_to_srgb(color.y),
+            linear_to_srgb(color.z),
+        )
+    }
+
+    /// Bakes the sky into RGBA8 pixels of a cube map. The faces are stored in the following order:
+    /// +X, -X, +Y, -Y, +Z, -Z (the same order as in [`SkyBox::textures`]).
+    pub fn bake(&self) -> Vec<u8> {
+        let size = self.resolution.max(1);
+        let mut data = Vec::with_capacity((size * size * 4 * 6) as usize);
+        for face in 0..6 {
+            for row in 0..size {
+                for column in 0..size {
+                    let s = 2.0 * (column as f32 + 0.5) / size as f32 - 1.0;
+                    let t = 2.0 * (row as f32 + 0.5) / size as f32 - 1.0;
+                    let direction = match face {
+                        0 => Vector3::new(1.0, -t, -s),
+                        1 => Vector3::new(-1.0, -t, s),
+                        2 => Vector3::new(s, 1.0, t),
+                        3 => Vector3::new(s, -1.0, -t),
+                        4 => Vector3::new(s, -t, 1.0),
+                        _ => Vector3::new(-s, -t, -1.0),
+                    };
+                    let color = self.sample_color(direction);
+                    data.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+                }
+            }
+        }
+        data
+    }
+
+    /// Bakes the sky into the given cube map, that was previously created by
+    /// [`Self::create_cubemap`]. Returns `false` if the cube map has different size or pixel kind
+    /// (it is left untouched in this case).
+    pub fn bake_into(&self, cubemap: &TextureResource) -> bool {
+        let mut state = cubemap.state();
+        let Some(texture) = state.data() else {
+            return false;
+        };
+        let TextureKind::Cube { size } = texture.kind() else {
+            return false;
+        };
+        if size != self.resolution.max(1) || texture.pixel_kind() != TexturePixelKind::RGBA8 {
+            return false;
+        }
+        let data = self.bake();
+        let mut texture = texture.modify();
+        let bytes = texture.data_mut();
+        if bytes.len() != data.len() {
+            return false;
+        }
+        bytes.copy_from_slice(&data);
+        true
+    }
+
+    /// Bakes the sky into a new cube map.
+    pub fn create_cubemap(&self) -> Result<TextureResource, SkyBoxError> {
+        let cubemap = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Cube {
+                size: self.resolution.max(1),
+            },
+            TexturePixelKind::RGBA8,
+            self.bake(),
+            ResourceKind::Embedded,
+        )
+        .ok_or(SkyBoxError::UnableToBuildCubeMap)?;
+
+        let mut cubemap_ref = cubemap.data_ref();
+        cubemap_ref.set_s_wrap_mode(TextureWrapMode::ClampToEdge);
+        cubemap_ref.set_t_wrap_mode(TextureWrapMode::ClampToEdge);
+        drop(cubemap_ref);
+
+        Ok(cubemap)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::skybox::{ProceduralSky, SkyBox},
+    };
+
+    fn sky_with_sun_elevation(degrees: f32) -> ProceduralSky {
+        let elevation = degrees.to_radians();
+        ProceduralSky {
+            sun_direction: Vector3::new(0.0, elevation.sin(), elevation.cos()),
+            resolution: 16,
+            ..Default::default()
+        }
+    }
+
+    // Average color of the given face of baked cube map.
+    fn face_average(data: &[u8], resolution: u32, face: usize) -> Vector3<f32> {
+        let face_size = (resolution * resolution * 4) as usize;
+        let pixels = &data[face * face_size..(face + 1) * face_size];
+        let mut sum = Vector3::default();
+        for pixel in pixels.chunks(4) {
+            sum += Vector3::new(pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        }
+        sum / (resolution * resolution) as f32
+    }
+
+    #[test]
+    fn test_sun_elevation_shifts_sky_gradient() {
+        let noon = sky_with_sun_elevation(80.0);
+        let sunset = sky_with_sun_elevation(5.0);
+
+        // Zenith gets darker when the sun goes down.
+        let zenith_noon = noon.sample(Vector3::y());
+        let zenith_sunset = sunset.sample(Vector3::y());
+        assert!(zenith_sunset.norm() < zenith_noon.norm());
+        // Zenith is blue during the day.
+        assert!(zenith_noon.z > zenith_noon.x);
+
+        // Horizon around the sun gets warmer.
+        let horizon = Vector3::new(0.0, 0.05, 1.0);
+        let warmth = |c: Vector3<f32>| c.x / c.z.max(f32::EPSILON);
+        assert!(warmth(sunset.sample(horizon)) > warmth(noon.sample(horizon)));
+
+        // The same is visible in the baked cube map: top face (+Y) is darker at sunset, while
+        // the face towards the sun (+Z) is warmer.
+        let noon_data = noon.bake();
+        let sunset_data = sunset.bake();
+        assert_eq!(noon_data.len(), 16 * 16 * 4 * 6);
+        let top_noon = face_average(&noon_data, 16, 2);
+        let top_sunset = face_average(&sunset_data, 16, 2);
+        assert!(top_sunset.norm() < top_noon.norm());
+        let front_noon = face_average(&noon_data, 16, 4);
+        let front_sunset = face_average(&sunset_data, 16, 4);
+        assert!(warmth(front_sunset) > warmth(front_noon));
+    }
+
+    #[test]
+    fn test_night_and_ground() {
+        let night = sky_with_sun_elevation(-30.0);
+        let zenith = night.sample(Vector3::y());
+        assert!(zenith.norm() < 0.05);
+
+        let day = sky_with_sun_elevation(45.0);
+        let ground = day.sample(-Vector3::y());
+        let expected = day
+            .ground_color
+            .srgb_to_linear_f32()
+            .xyz()
+            .scale(0.1 + 0.9 * day.normalized_sun_direction().y);
+        assert!((ground - expected).norm() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_procedural_skybox() {
+        let skybox = SkyBox::from_procedural(sky_with_sun_elevation(30.0)).unwrap();
+        assert!(skybox.cubemap_ref().is_some());
+        assert!(skybox.procedural().is_some());
+    }
+
+    #[test]
+    fn test_procedural_sky_equality() {
+        let sky = sky_with_sun_elevation(30.0);
+        assert_eq!(sky, sky.clone());
+        assert_ne!(sky, sky_with_sun_elevation(31.0));
+
+        // NaN parameters must not break reflexivity of `Eq`.
+        let nan_sky = ProceduralSky {
+            turbidity: f32::NAN,
+            ..sky
+        };
+        assert_eq!(nan_sky, nan_sky.clone());
+    }
 }
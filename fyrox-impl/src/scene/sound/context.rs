@@ -22,19 +22,25 @@
 
 use crate::{
     core::{
+        algebra::Vector3,
         log::{Log, MessageKind},
         pool::Handle,
         visitor::prelude::*,
     },
-    scene::{node::Node, sound::Sound},
+    scene::{
+        node::Node,
+        sound::{reverb_zone::ReverbPreset, Sound},
+    },
 };
-use fxhash::FxHashSet;
+use fxhash::{FxHashMap, FxHashSet};
 use fyrox_sound::{
-    bus::AudioBusGraph,
+    bus::{AudioBus, AudioBusGraph},
     context::DistanceModel,
+    effects::{reverb::Reverb, Effect},
     renderer::Renderer,
     source::{SoundSource, SoundSourceBuilder, Status},
 };
+use std::collections::hash_map::Entry;
 use std::{sync::MutexGuard, time::Duration};
 
 /// Sound context.
@@ -42,6 +48,21 @@ use std::{sync::MutexGuard, time::Duration};
 pub struct SoundContext {
     #[visit(optional)]
     pub(crate) native: fyrox_sound::context::SoundContext,
+    #[visit(skip)]
+    reverb_requests: FxHashMap<String, (i32, ReverbPreset)>,
+    #[visit(skip)]
+    active_reverbs: FxHashMap<String, ActiveReverb>,
+    #[visit(skip)]
+    listener_velocity: Vector3<f32>,
+}
+
+/// A reverb preset, that is currently applied to an audio bus by a reverb zone.
+#[derive(Debug)]
+struct ActiveReverb {
+    preset: ReverbPreset,
+    // Reverb effect of the bus before the zone was applied. `None` means that the bus had no reverb
+    // effect and it was added by the zone.
+    original: Option<Reverb>,
 }
 
 /// Proxy for guarded access to the sound context.
@@ -124,7 +145,12 @@ impl Default for SoundContext {
         // There's no need to serialize native sources, because they'll be re-created automatically.
         state.serialization_options.skip_sources = true;
         drop(state);
-        Self { native }
+        Self {
+            native,
+            reverb_requests: Default::default(),
+            active_reverbs: Default::default(),
+            listener_velocity: Default::default(),
+        }
     }
 }
 
@@ -137,6 +163,9 @@ impl SoundContext {
     pub fn deep_clone(&self) -> Self {
         Self {
             native: self.native.deep_clone(),
+            reverb_requests: Default::default(),
+            active_reverbs: Default::default(),
+            listener_velocity: Default::default(),
         }
     }
 
@@ -147,6 +176,122 @@ impl SoundContext {
         }
     }
 
+    /// Returns a reverb preset, that is currently applied to the audio bus with the given name by
+    /// a reverb zone. See [`super::reverb_zone::ReverbZone`] docs for more info.
+    pub fn active_reverb(&self, bus: &str) -> Option<&ReverbPreset> {
+        self.active_reverbs.get(bus).map(|active| &active.preset)
+    }
+
+    pub(crate) fn listener_position(&self) -> Vector3<f32> {
+        self.native.state().listener().position()
+    }
+
+    /// Returns current velocity of the listener. It is calculated by the listener node using its
+    /// movement across frames.
+    pub fn listener_velocity(&self) -> Vector3<f32> {
+        self.listener_velocity
+    }
+
+    pub(crate) fn set_listener_velocity(&mut self, velocity: Vector3<f32>) {
+        self.listener_velocity = velocity;
+    }
+
+    /// Registers a request from a reverb zone to apply the given preset to an audio bus. Only the
+    /// request with the highest priority will be applied.
+    pub(crate) fn request_reverb(&mut self, bus: &str, priority: i32, preset: &ReverbPreset) {
+        match self.reverb_requests.get_mut(bus) {
+            Some(request) => {
+                if priority > request.0 {
+                    *request = (priority, preset.clone());
+                }
+            }
+            None => {
+                self.reverb_requests
+                    .insert(bus.to_string(), (priority, preset.clone()));
+            }
+        }
+    }
+
+    /// Applies reverb presets requested by reverb zones in the current frame and restores the
+    /// original state of the audio buses that are no longer affected by any zone.
+    pub(crate) fn apply_reverb_zones(&mut self) {
+        if self.reverb_requests.is_empty() && self.active_reverbs.is_empty() {
+            return;
+        }
+
+        let requests = std::mem::take(&mut self.reverb_requests);
+        let mut state = self.native.state();
+
+        self.active_reverbs.retain(|bus_name, active| {
+            if requests.contains_key(bus_name) {
+                return true;
+            }
+
+            if let Some(bus) = find_bus_mut(state.bus_graph_mut(), bus_name) {
+                if let Some(index) = reverb_index(bus) {
+                    match active.original.take() {
+                        Some(original) => {
+                            if let Some(effect) = bus.effect_mut(index) {
+                                *effect = Effect::Reverb(original);
+                            }
+                        }
+                        None => bus.remove_effect(index),
+                    }
+                }
+            }
+
+            false
+        });
+
+        for (bus_name, (_, preset)) in requests {
+            if self
+                .active_reverbs
+                .get(&bus_name)
+                .is_some_and(|active| active.preset == preset)
+            {
+                continue;
+            }
+
+            let fc = state.normalize_frequency(preset.cutoff_frequency);
+
+            let Some(bus) = find_bus_mut(state.bus_graph_mut(), &bus_name) else {
+                continue;
+            };
+
+            let active = match self.active_reverbs.entry(bus_name) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    // Remember the original reverb of the bus, so it could be restored when the
+                    // listener leaves all the zones.
+                    let original = match reverb_index(bus) {
+                        Some(index) => match bus.effect(index) {
+                            Some(Effect::Reverb(reverb)) => Some(reverb.clone()),
+                            _ => None,
+                        },
+                        None => {
+                            bus.add_effect(Effect::Reverb(Reverb::new()));
+                            None
+                        }
+                    };
+                    entry.insert(ActiveReverb {
+                        preset: preset.clone(),
+                        original,
+                    })
+                }
+            };
+
+            if let Some(Effect::Reverb(reverb)) =
+                reverb_index(bus).and_then(|index| bus.effect_mut(index))
+            {
+                reverb.set_wet(preset.wet);
+                reverb.set_decay_time(preset.decay_time);
+                reverb.set_fc(fc);
+            }
+
+            active.preset = preset;
+        }
+    }
+
     pub(crate) fn remove_sound(&mut self, sound: Handle<SoundSource>, name: &str) {
         let mut state = self.native.state();
         if state.is_valid_handle(sound) {
@@ -162,6 +307,18 @@ impl SoundContext {
         }
     }
 
+    pub(crate) fn set_sound_gain(&mut self, sound: &Sound) {
+        if let Ok(source) = self.native.state().try_get_source_mut(sound.native.get()) {
+            source.set_gain(sound.effective_gain());
+        }
+    }
+
+    pub(crate) fn set_sound_pitch(&mut self, sound: &Sound) {
+        if let Ok(source) = self.native.state().try_get_source_mut(sound.native.get()) {
+            source.set_pitch(sound.effective_pitch());
+        }
+    }
+
     pub(crate) fn sync_with_sound(&self, sound: &mut Sound) {
         if let Ok(source) = self.native.state().try_get_source_mut(sound.native.get()) {
             // Sync back.
@@ -203,8 +360,9 @@ impl SoundContext {
             sound.playback_time.try_sync_model(|v| {
                 source.set_playback_time(Duration::from_secs_f32(v));
             });
-            sound.pitch.try_sync_model(|v| {
-                source.set_pitch(v);
+            sound.pitch.try_sync_model(|_| {
+                // Doppler shift must be taken into account.
+                source.set_pitch(sound.effective_pitch());
             });
             sound.looping.try_sync_model(|v| {
                 source.set_looping(v);
@@ -212,8 +370,9 @@ impl SoundContext {
             sound.panning.try_sync_model(|v| {
                 source.set_panning(v);
             });
-            sound.gain.try_sync_model(|v| {
-                source.set_gain(v);
+            sound.gain.try_sync_model(|_| {
+                // Occlusion attenuation must be taken into account.
+                source.set_gain(sound.effective_gain());
             });
             sound
                 .spatial_blend
@@ -234,11 +393,11 @@ impl SoundContext {
             });
         } else {
             match SoundSourceBuilder::new()
-                .with_gain(sound.gain())
+                .with_gain(sound.effective_gain())
                 .with_opt_buffer(sound.buffer())
                 .with_looping(sound.is_looping())
                 .with_panning(sound.panning())
-                .with_pitch(sound.pitch())
+                .with_pitch(sound.effective_pitch())
                 .with_status(sound.status())
                 .with_playback_time(Duration::from_secs_f32(sound.playback_time()))
                 .with_position(sound.global_position())
@@ -270,3 +429,12 @@ impl SoundContext {
         }
     }
 }
+
+fn find_bus_mut<'a>(graph: &'a mut AudioBusGraph, name: &str) -> Option<&'a mut AudioBus> {
+    graph.buses_iter_mut().find(|bus| bus.name() == name)
+}
+
+fn reverb_index(bus: &AudioBus) -> Option<usize> {
+    bus.effects()
+        .position(|effect| matches!(effect, Effect::Reverb(_)))
+}
@@ -26,6 +26,7 @@
 use crate::scene::node::constructor::NodeConstructor;
 use crate::{
     core::{
+        algebra::Vector3,
         math::aabb::AxisAlignedBoundingBox,
         pool::Handle,
         reflect::prelude::*,
@@ -36,7 +37,7 @@ use crate::{
     scene::{
         base::{Base, BaseBuilder},
         graph::Graph,
-        node::{Node, NodeTrait, SyncContext},
+        node::{Node, NodeTrait, SyncContext, UpdateContext},
     },
 };
 use fyrox_graph::constructor::ConstructorProvider;
@@ -57,10 +58,17 @@ use std::ops::{Deref, DerefMut};
 ///
 /// 2D sound sources (with spatial blend == 0.0) are not influenced by listener's position and
 /// orientation.
+///
+/// Velocity of the listener is calculated using its movement across frames, it is used to
+/// calculate the Doppler effect of sound sources. See [`super::Sound::set_doppler_factor`] for
+/// more info.
 #[derive(Visit, Reflect, Default, Clone, Debug, ComponentProvider)]
 #[reflect(derived_type = "Node")]
 pub struct Listener {
     base: Base,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    prev_position: Option<Vector3<f32>>,
 }
 
 impl Deref for Listener {
@@ -123,6 +131,16 @@ impl NodeTrait for Listener {
         native.set_position(self.global_position());
         native.set_orientation_lh(self.look_vector(), self.up_vector());
     }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        // Velocity of the listener is used to calculate the Doppler effect.
+        let position = self.global_position();
+        let velocity = match self.prev_position.replace(position) {
+            Some(prev_position) if context.dt > 0.0 => (position - prev_position) / context.dt,
+            _ => Vector3::default(),
+        };
+        context.sound_context.set_listener_velocity(velocity);
+    }
 }
 
 /// Allows you to create listener in declarative manner.
@@ -140,6 +158,7 @@ impl ListenerBuilder {
     pub fn build_listener(self) -> Listener {
         Listener {
             base: self.base_builder.build_base(),
+            prev_position: None,
         }
     }
 
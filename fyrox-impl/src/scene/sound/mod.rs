@@ -22,7 +22,8 @@
 
 use crate::{
     core::{
-        algebra::Matrix4,
+        algebra::{Matrix4, Point3, Vector3},
+        arrayvec::ArrayVec,
         math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
         pool::Handle,
         reflect::prelude::*,
@@ -34,7 +35,11 @@ use crate::{
     define_with,
     scene::{
         base::{Base, BaseBuilder},
-        graph::Graph,
+        collider::Collider,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
         node::{Node, NodeTrait, SyncContext, UpdateContext},
     },
 };
@@ -71,6 +76,7 @@ use std::{
 
 pub mod context;
 pub mod listener;
+pub mod reverb_zone;
 
 /// Sound source.
 #[derive(Visit, Reflect, Debug, ComponentProvider)]
@@ -125,6 +131,32 @@ pub struct Sound {
     #[visit(optional)]
     audio_bus: InheritableVariable<String>,
 
+    #[visit(optional)]
+    #[reflect(setter = "set_occlusion_enabled")]
+    occlusion_enabled: InheritableVariable<bool>,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    #[reflect(setter = "set_occlusion_gain")]
+    occlusion_gain: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    occlusion: f32,
+
+    #[visit(optional)]
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_doppler_factor")]
+    doppler_factor: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    doppler_shift: f64,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    prev_position: Option<Vector3<f32>>,
+
     #[reflect(hidden)]
     #[visit(skip)]
     pub(crate) native: Cell<Handle<SoundSource>>,
@@ -161,6 +193,12 @@ impl Default for Sound {
             playback_time: Default::default(),
             spatial_blend: InheritableVariable::new_modified(1.0),
             audio_bus: InheritableVariable::new_modified(AudioBusGraph::PRIMARY_BUS.to_string()),
+            occlusion_enabled: InheritableVariable::new_modified(false),
+            occlusion_gain: InheritableVariable::new_modified(0.25),
+            occlusion: 0.0,
+            doppler_factor: InheritableVariable::new_modified(0.0),
+            doppler_shift: 1.0,
+            prev_position: None,
             native: Default::default(),
         }
     }
@@ -183,6 +221,12 @@ impl Clone for Sound {
             playback_time: self.playback_time.clone(),
             spatial_blend: self.spatial_blend.clone(),
             audio_bus: self.audio_bus.clone(),
+            occlusion_enabled: self.occlusion_enabled.clone(),
+            occlusion_gain: self.occlusion_gain.clone(),
+            occlusion: self.occlusion,
+            doppler_factor: self.doppler_factor.clone(),
+            doppler_shift: self.doppler_shift,
+            prev_position: self.prev_position,
             // Do not copy. The copy will have its own native representation.
             native: Default::default(),
         }
@@ -385,6 +429,178 @@ impl Sound {
     pub fn audio_bus(&self) -> &str {
         &self.audio_bus
     }
+
+    /// Enables or disables occlusion of the sound. Occluded sound is muffled when there is any
+    /// geometry (non-sensor 3D colliders) on the line between the sound and the listener. Occlusion
+    /// is checked using ray casting every frame, so it is disabled by default.
+    pub fn set_occlusion_enabled(&mut self, enabled: bool) -> bool {
+        self.occlusion_enabled.set_value_and_mark_modified(enabled)
+    }
+
+    /// Returns `true` if the occlusion is enabled, `false` - otherwise.
+    pub fn is_occlusion_enabled(&self) -> bool {
+        *self.occlusion_enabled
+    }
+
+    /// Sets a gain multiplier, that is applied to the sound when it is fully occluded. Default is
+    /// 0.25.
+    pub fn set_occlusion_gain(&mut self, gain: f32) -> f32 {
+        self.occlusion_gain
+            .set_value_and_mark_modified(gain.clamp(0.0, 1.0))
+    }
+
+    /// Returns a gain multiplier, that is applied to the sound when it is fully occluded.
+    pub fn occlusion_gain(&self) -> f32 {
+        *self.occlusion_gain
+    }
+
+    /// Returns current occlusion factor of the sound in `[0.0; 1.0]` range, where 0.0 means that
+    /// the sound is fully audible and 1.0 - the sound is fully occluded. The factor changes
+    /// smoothly to prevent sudden volume jumps.
+    pub fn occlusion(&self) -> f32 {
+        self.occlusion
+    }
+
+    /// Returns the gain that is actually used for playback. It is equal to the gain of the sound
+    /// multiplied by the occlusion attenuation.
+    pub fn effective_gain(&self) -> f32 {
+        *self.gain * (1.0 + (*self.occlusion_gain - 1.0) * self.occlusion)
+    }
+
+    const OCCLUSION_EPSILON: f32 = 0.001;
+
+    /// Time (in seconds) that is needed to fully occlude the sound or to restore it.
+    const OCCLUSION_FADE_TIME: f32 = 0.1;
+
+    fn is_occluded(&self, listener_position: Vector3<f32>, context: &UpdateContext) -> bool {
+        let position = self.global_position();
+        let distance = position.metric_distance(&listener_position);
+        if distance <= f32::EPSILON {
+            return false;
+        }
+
+        // Rays are cast in both directions and only the hits that are not at the ray origins are
+        // taken into account. This way colliders that contain the sound or the listener (for
+        // example, a capsule of a character with the listener attached to its camera) do not
+        // occlude the sound.
+        let mut query_buffer = ArrayVec::<Intersection, 64>::new();
+        let mut is_blocked = |from: Vector3<f32>, to: Vector3<f32>| {
+            context.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(from),
+                    ray_direction: to - from,
+                    max_len: distance,
+                    groups: Default::default(),
+                    sort_results: false,
+                },
+                &mut query_buffer,
+            );
+            query_buffer.iter().any(|intersection| {
+                intersection.toi > Self::OCCLUSION_EPSILON
+                    && context
+                        .nodes
+                        .try_get_component_of_type::<Collider>(intersection.collider.to_base())
+                        .is_ok_and(|collider| !collider.is_sensor())
+            })
+        };
+
+        is_blocked(position, listener_position) && is_blocked(listener_position, position)
+    }
+
+    fn update_occlusion(&mut self, context: &mut UpdateContext) {
+        let target = if *self.occlusion_enabled
+            && self.spatial_blend() > 0.0
+            && self.is_globally_enabled()
+        {
+            let listener_position = context.sound_context.listener_position();
+            if self.is_occluded(listener_position, &*context) {
+                1.0
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        };
+
+        if self.occlusion != target {
+            let k = (context.dt / Self::OCCLUSION_FADE_TIME).min(1.0);
+            self.occlusion += (target - self.occlusion) * k;
+            if (self.occlusion - target).abs() < 0.001 {
+                self.occlusion = target;
+            }
+            context.sound_context.set_sound_gain(self);
+        }
+    }
+
+    /// Sets Doppler factor of the sound. It defines how much relative velocity of the sound and
+    /// the listener affects the pitch of the sound. 0.0 disables the Doppler effect (default),
+    /// 1.0 - physically correct effect, larger values exaggerate the effect.
+    pub fn set_doppler_factor(&mut self, factor: f32) -> f32 {
+        self.doppler_factor
+            .set_value_and_mark_modified(factor.max(0.0))
+    }
+
+    /// Returns Doppler factor of the sound.
+    pub fn doppler_factor(&self) -> f32 {
+        *self.doppler_factor
+    }
+
+    /// Returns current pitch multiplier caused by the Doppler effect. Values larger than 1.0
+    /// mean that the sound and the listener are getting closer to each other, values less than
+    /// 1.0 - they're moving away from each other. The value is clamped in
+    /// `[1.0 / MAX_DOPPLER_SHIFT; MAX_DOPPLER_SHIFT]` range to prevent extreme pitches (for
+    /// example, when a sound is teleported).
+    pub fn doppler_shift(&self) -> f64 {
+        self.doppler_shift
+    }
+
+    /// Returns the pitch that is actually used for playback. It is equal to the pitch of the sound
+    /// multiplied by the Doppler shift.
+    pub fn effective_pitch(&self) -> f64 {
+        *self.pitch * self.doppler_shift
+    }
+
+    /// Speed of sound (in m/s) used to calculate the Doppler effect.
+    pub const SPEED_OF_SOUND: f32 = 343.0;
+
+    /// Maximum pitch multiplier caused by the Doppler effect.
+    pub const MAX_DOPPLER_SHIFT: f32 = 2.0;
+
+    fn update_doppler(&mut self, context: &mut UpdateContext) {
+        let position = self.global_position();
+        let velocity = match self.prev_position.replace(position) {
+            Some(prev_position) if context.dt > 0.0 => (position - prev_position) / context.dt,
+            _ => Vector3::default(),
+        };
+
+        let factor = *self.doppler_factor;
+        let shift = f64::from(if factor > 0.0 && self.spatial_blend() > 0.0 {
+            let listener_position = context.sound_context.listener_position();
+            match (listener_position - position).try_normalize(f32::EPSILON) {
+                Some(direction) => {
+                    // Velocities are clamped so the sound and the listener never move faster
+                    // than the sound itself.
+                    let max_speed = Self::SPEED_OF_SOUND * 0.99;
+                    let source_speed =
+                        (velocity.dot(&direction) * factor).clamp(-max_speed, max_speed);
+                    let listener_speed =
+                        (context.sound_context.listener_velocity().dot(&direction) * factor)
+                            .clamp(-max_speed, max_speed);
+                    ((Self::SPEED_OF_SOUND - listener_speed)
+                        / (Self::SPEED_OF_SOUND - source_speed))
+                        .clamp(1.0 / Self::MAX_DOPPLER_SHIFT, Self::MAX_DOPPLER_SHIFT)
+                }
+                None => 1.0,
+            }
+        } else {
+            1.0
+        });
+
+        if self.doppler_shift != shift {
+            self.doppler_shift = shift;
+            context.sound_context.set_sound_pitch(self);
+        }
+    }
 }
 
 impl ConstructorProvider<Node, Graph> for Sound {
@@ -448,6 +664,8 @@ impl NodeTrait for Sound {
 
     fn update(&mut self, context: &mut UpdateContext) {
         context.sound_context.sync_with_sound(self);
+        self.update_occlusion(context);
+        self.update_doppler(context);
     }
 
     fn validate(&self, _scene: &Scene) -> Result<(), String> {
@@ -489,6 +707,9 @@ pub struct SoundBuilder {
     playback_time: Duration,
     spatial_blend: f32,
     audio_bus: String,
+    occlusion_enabled: bool,
+    occlusion_gain: f32,
+    doppler_factor: f32,
 }
 
 impl SoundBuilder {
@@ -509,6 +730,9 @@ impl SoundBuilder {
             spatial_blend: 1.0,
             playback_time: Default::default(),
             audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+            occlusion_enabled: false,
+            occlusion_gain: 0.25,
+            doppler_factor: 0.0,
         }
     }
 
@@ -577,6 +801,21 @@ impl SoundBuilder {
         fn with_audio_bus(audio_bus: String)
     );
 
+    define_with!(
+        /// Enables or disables occlusion. See [`Sound::set_occlusion_enabled`] for more info.
+        fn with_occlusion_enabled(occlusion_enabled: bool)
+    );
+
+    define_with!(
+        /// Sets desired occlusion gain. See [`Sound::set_occlusion_gain`] for more info.
+        fn with_occlusion_gain(occlusion_gain: f32)
+    );
+
+    define_with!(
+        /// Sets desired Doppler factor. See [`Sound::set_doppler_factor`] for more info.
+        fn with_doppler_factor(doppler_factor: f32)
+    );
+
     /// Creates a new [`Sound`] node.
     #[must_use]
     pub fn build_sound(self) -> Sound {
@@ -595,6 +834,12 @@ impl SoundBuilder {
             playback_time: self.playback_time.as_secs_f32().into(),
             spatial_blend: self.spatial_blend.into(),
             audio_bus: self.audio_bus.into(),
+            occlusion_enabled: self.occlusion_enabled.into(),
+            occlusion_gain: self.occlusion_gain.into(),
+            occlusion: 0.0,
+            doppler_factor: self.doppler_factor.into(),
+            doppler_shift: 1.0,
+            prev_position: None,
             native: Default::default(),
         }
     }
@@ -610,3 +855,121 @@ impl SoundBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::{RigidBodyBuilder, RigidBodyType},
+            sound::{listener::ListenerBuilder, Sound, SoundBuilder},
+            transform::TransformBuilder,
+        },
+    };
+
+    #[test]
+    fn test_sound_occlusion() {
+        let mut graph = Graph::new();
+
+        ListenerBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let sound = SoundBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 10.0))
+                    .build(),
+            ),
+        )
+        .with_gain(0.8)
+        .with_occlusion_enabled(true)
+        .with_occlusion_gain(0.25)
+        .build(&mut graph);
+
+        let update = |graph: &mut Graph| {
+            for _ in 0..60 {
+                graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+            }
+        };
+
+        // Clear line of sight.
+        update(&mut graph);
+        assert_eq!(graph[sound].occlusion(), 0.0);
+        assert_eq!(graph[sound].effective_gain(), 0.8);
+
+        // A wall between the listener and the sound.
+        let wall = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 0.0, 5.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::cuboid(5.0, 5.0, 0.1))
+                        .build(&mut graph),
+                ),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(&mut graph);
+
+        update(&mut graph);
+        assert_eq!(graph[sound].occlusion(), 1.0);
+        assert!((graph[sound].effective_gain() - 0.2).abs() < 1.0e-5);
+
+        // Removing the wall restores the gain.
+        graph.remove_node(wall);
+        update(&mut graph);
+        assert_eq!(graph[sound].occlusion(), 0.0);
+        assert_eq!(graph[sound].effective_gain(), 0.8);
+    }
+
+    #[test]
+    fn test_doppler_effect() {
+        let mut graph = Graph::new();
+
+        ListenerBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        let sound = SoundBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 0.0, 50.0))
+                    .build(),
+            ),
+        )
+        .with_doppler_factor(1.0)
+        .build(&mut graph);
+
+        let dt = 1.0 / 60.0;
+        let move_sound = |graph: &mut Graph, velocity: f32, frames: usize| {
+            for _ in 0..frames {
+                let position = graph[sound].local_transform().position().z;
+                graph[sound].set_position_xyz(0.0, 0.0, position + velocity * dt);
+                graph.update(Vector2::new(1.0, 1.0), dt, Default::default());
+            }
+        };
+
+        // Moving toward the listener.
+        move_sound(&mut graph, -30.0, 10);
+        let shift = graph[sound].doppler_shift();
+        assert!(shift > 1.0);
+        assert!((shift - 343.0 / (343.0 - 30.0)).abs() < 1.0e-3);
+        assert!(graph[sound].effective_pitch() > graph[sound].pitch());
+
+        // Moving away from the listener.
+        move_sound(&mut graph, 30.0, 10);
+        let shift = graph[sound].doppler_shift();
+        assert!(shift < 1.0);
+        assert!((shift - 343.0 / (343.0 + 30.0)).abs() < 1.0e-3);
+
+        // Extreme velocities must not produce extreme pitches.
+        move_sound(&mut graph, -2000.0, 1);
+        assert_eq!(
+            graph[sound].doppler_shift(),
+            Sound::MAX_DOPPLER_SHIFT as f64
+        );
+    }
+}
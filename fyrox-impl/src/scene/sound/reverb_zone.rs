@@ -0,0 +1,386 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Reverb zone is a volume that applies a reverb preset to an audio bus when the listener is
+//! inside it. See [`ReverbZone`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Point3,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        sound::AudioBusGraph,
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, SceneGraph};
+use std::ops::{Deref, DerefMut};
+
+/// A set of parameters of a reverb effect, that is applied by a [`ReverbZone`].
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct ReverbPreset {
+    /// Amount of reverberated signal in the output in `[0.0; 1.0]` range.
+    #[reflect(min_value = 0.0, max_value = 1.0, step = 0.05)]
+    pub wet: f32,
+    /// Duration of reverberation (in seconds), the larger an environment is, the larger the
+    /// duration should be.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub decay_time: f32,
+    /// Cutoff frequency (in Hz) of reflections. Lower values make reflections more muffled.
+    #[reflect(min_value = 0.0, step = 100.0)]
+    pub cutoff_frequency: f32,
+}
+
+uuid_provider!(ReverbPreset = "0c6a3f2e-91d7-4b8a-a5e4-7f12c8d93b60");
+
+impl Default for ReverbPreset {
+    fn default() -> Self {
+        Self::room()
+    }
+}
+
+impl ReverbPreset {
+    /// A small room with short reverberation.
+    pub fn room() -> Self {
+        Self {
+            wet: 0.2,
+            decay_time: 0.6,
+            cutoff_frequency: 8000.0,
+        }
+    }
+
+    /// A small room with hard walls and bright reflections.
+    pub fn bathroom() -> Self {
+        Self {
+            wet: 0.35,
+            decay_time: 1.2,
+            cutoff_frequency: 10000.0,
+        }
+    }
+
+    /// A large hall with long reverberation.
+    pub fn hall() -> Self {
+        Self {
+            wet: 0.35,
+            decay_time: 2.5,
+            cutoff_frequency: 6000.0,
+        }
+    }
+
+    /// A cave with very long and muffled reverberation.
+    pub fn cave() -> Self {
+        Self {
+            wet: 0.5,
+            decay_time: 4.0,
+            cutoff_frequency: 3000.0,
+        }
+    }
+}
+
+/// Reverb zone is a box-shaped volume, that applies its reverb preset to an audio bus when the
+/// listener is inside the volume. When the listener leaves all the zones, the original state of the
+/// audio bus is restored. If the bus has no reverb effect, it will be added while the listener is
+/// inside a zone.
+///
+/// # Size and transformations
+///
+/// Reverb zone defines a unit cube, its actual size and orientation are defined by the global
+/// transform of the node.
+///
+/// # Overlapping zones
+///
+/// Zones can overlap, in this case the zone with the highest priority (among the zones that
+/// contain the listener and use the same audio bus) wins. Zones with the same priority are resolved
+/// in the order of update. Usually a large zone (a cave) has lower priority than smaller zones
+/// inside it (a small room inside the cave).
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct ReverbZone {
+    base: Base,
+
+    #[reflect(setter = "set_priority")]
+    priority: InheritableVariable<i32>,
+
+    #[reflect(setter = "set_preset")]
+    preset: InheritableVariable<ReverbPreset>,
+
+    #[reflect(setter = "set_audio_bus")]
+    audio_bus: InheritableVariable<String>,
+}
+
+impl Default for ReverbZone {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            priority: 0.into(),
+            preset: Default::default(),
+            audio_bus: AudioBusGraph::PRIMARY_BUS.to_string().into(),
+        }
+    }
+}
+
+impl Deref for ReverbZone {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for ReverbZone {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for ReverbZone {
+    fn type_uuid() -> Uuid {
+        uuid!("e4b19d27-5c3a-4f6e-8d02-a97c1b6f4e35")
+    }
+}
+
+impl ReverbZone {
+    /// Sets priority of the zone. See [`ReverbZone`] docs for more info about overlapping zones.
+    pub fn set_priority(&mut self, priority: i32) -> i32 {
+        self.priority.set_value_and_mark_modified(priority)
+    }
+
+    /// Returns priority of the zone.
+    pub fn priority(&self) -> i32 {
+        *self.priority
+    }
+
+    /// Sets a reverb preset, that will be applied when the listener is inside the zone.
+    pub fn set_preset(&mut self, preset: ReverbPreset) -> ReverbPreset {
+        self.preset.set_value_and_mark_modified(preset)
+    }
+
+    /// Returns current reverb preset of the zone.
+    pub fn preset(&self) -> &ReverbPreset {
+        &self.preset
+    }
+
+    /// Sets a name of an audio bus to which the reverb preset will be applied. Default is the
+    /// primary bus.
+    pub fn set_audio_bus(&mut self, name: String) -> String {
+        self.audio_bus.set_value_and_mark_modified(name)
+    }
+
+    /// Returns a name of an audio bus to which the reverb preset will be applied.
+    pub fn audio_bus(&self) -> &str {
+        &self.audio_bus
+    }
+
+    /// Checks if the given point (in world coordinates) is inside the zone.
+    pub fn contains(&self, point: &Point3<f32>) -> bool {
+        self.global_transform().try_inverse().is_some_and(|inv| {
+            let local = inv.transform_point(point);
+            local.x.abs() <= 0.5 && local.y.abs() <= 0.5 && local.z.abs() <= 0.5
+        })
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for ReverbZone {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Reverb Zone", |_| {
+                ReverbZoneBuilder::new(BaseBuilder::new().with_name("ReverbZone"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Sound")
+    }
+}
+
+impl NodeTrait for ReverbZone {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.is_globally_enabled() {
+            return;
+        }
+
+        let listener_position = Point3::from(context.sound_context.listener_position());
+        if self.contains(&listener_position) {
+            context
+                .sound_context
+                .request_reverb(&self.audio_bus, *self.priority, &self.preset);
+        }
+    }
+}
+
+/// Allows you to create a reverb zone in a declarative manner.
+pub struct ReverbZoneBuilder {
+    base_builder: BaseBuilder,
+    priority: i32,
+    preset: ReverbPreset,
+    audio_bus: String,
+}
+
+impl ReverbZoneBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            priority: 0,
+            preset: Default::default(),
+            audio_bus: AudioBusGraph::PRIMARY_BUS.to_string(),
+        }
+    }
+
+    /// Sets desired priority of the zone.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Sets desired reverb preset.
+    pub fn with_preset(mut self, preset: ReverbPreset) -> Self {
+        self.preset = preset;
+        self
+    }
+
+    /// Sets desired audio bus name.
+    pub fn with_audio_bus(mut self, audio_bus: String) -> Self {
+        self.audio_bus = audio_bus;
+        self
+    }
+
+    /// Creates new reverb zone node.
+    pub fn build_reverb_zone(self) -> ReverbZone {
+        ReverbZone {
+            base: self.base_builder.build_base(),
+            priority: self.priority.into(),
+            preset: self.preset.into(),
+            audio_bus: self.audio_bus.into(),
+        }
+    }
+
+    /// Creates new reverb zone node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_reverb_zone())
+    }
+
+    /// Creates new instance of reverb zone node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<ReverbZone> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            sound::{
+                listener::ListenerBuilder,
+                reverb_zone::{ReverbPreset, ReverbZoneBuilder},
+                AudioBusGraph, Effect,
+            },
+            transform::TransformBuilder,
+        },
+    };
+
+    fn zone_transform(size: f32) -> BaseBuilder {
+        BaseBuilder::new().with_local_transform(
+            TransformBuilder::new()
+                .with_local_scale(Vector3::repeat(size))
+                .build(),
+        )
+    }
+
+    #[test]
+    fn test_overlapping_reverb_zones() {
+        let mut graph = Graph::new();
+
+        let listener = ListenerBuilder::new(BaseBuilder::new()).build(&mut graph);
+
+        // A small room inside a large cave.
+        ReverbZoneBuilder::new(zone_transform(100.0))
+            .with_preset(ReverbPreset::cave())
+            .build(&mut graph);
+        ReverbZoneBuilder::new(zone_transform(4.0))
+            .with_preset(ReverbPreset::room())
+            .with_priority(1)
+            .build(&mut graph);
+
+        let has_reverb = |graph: &Graph| {
+            graph
+                .sound_context
+                .state()
+                .bus_graph_ref()
+                .primary_bus_ref()
+                .effects()
+                .any(|effect| matches!(effect, Effect::Reverb(_)))
+        };
+
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(
+            graph
+                .sound_context
+                .active_reverb(AudioBusGraph::PRIMARY_BUS),
+            Some(&ReverbPreset::room())
+        );
+        assert!(has_reverb(&graph));
+
+        // Leave the room, but stay in the cave.
+        graph[listener].set_position_xyz(10.0, 0.0, 0.0);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(
+            graph
+                .sound_context
+                .active_reverb(AudioBusGraph::PRIMARY_BUS),
+            Some(&ReverbPreset::cave())
+        );
+
+        // Leave all the zones, the reverb effect must be removed.
+        graph[listener].set_position_xyz(100.0, 0.0, 0.0);
+        graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        assert_eq!(
+            graph
+                .sound_context
+                .active_reverb(AudioBusGraph::PRIMARY_BUS),
+            None
+        );
+        assert!(!has_reverb(&graph));
+    }
+}
@@ -0,0 +1,582 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Spline is a smooth curve defined by a set of control points. See [`Spline`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::Vector3,
+        color::Color,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        debug::{Line, SceneDrawingContext},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines how the control points of a spline form the curve.
+#[derive(
+    Default, Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum SplineKind {
+    /// Centripetal-free (uniform) Catmull-Rom spline. The curve passes through every control point.
+    #[default]
+    CatmullRom,
+    /// A chain of cubic Bézier curves. Every segment is defined by four points - two end points and
+    /// two control points between them: `[P0, C0, C1, P1, C2, C3, P2, ...]`. The curve passes only
+    /// through the end points, the control points define the shape of the segment.
+    Bezier,
+}
+
+uuid_provider!(SplineKind = "8a1c4f3e-5b7d-4d2a-9e6f-1c3b5a7d9e20");
+
+/// Spline is a smooth curve defined by a set of control points. It is usually used to define paths
+/// for cameras in cinematics, patrol routes for characters, etc. See [`SplineKind`] for supported
+/// types of curves.
+///
+/// Control points are defined in local coordinates of the node, which means that the spline could
+/// be moved, rotated and scaled as a whole using its transform. The curve is parametrized by a
+/// single value `t` in `[0; 1]` range, where each segment of the spline occupies an equal part of
+/// the range. In case of Catmull-Rom spline it means that `t = i / (n - 1)` (or `t = i / n` for
+/// closed splines) corresponds to the `i`-th control point.
+///
+/// Splines with less than two points are degenerate: a spline without points is a single point at
+/// the origin of the node, and a spline with a single point is that point. Tangent of a degenerate
+/// spline is a zero vector.
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Spline {
+    base: Base,
+
+    #[reflect(setter = "set_kind")]
+    kind: InheritableVariable<SplineKind>,
+
+    #[reflect(setter = "set_control_points")]
+    control_points: InheritableVariable<Vec<Vector3<f32>>>,
+
+    #[reflect(setter = "set_closed")]
+    closed: InheritableVariable<bool>,
+}
+
+impl Default for Spline {
+    fn default() -> Self {
+        SplineBuilder::new(BaseBuilder::new()).build_spline()
+    }
+}
+
+impl Deref for Spline {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Spline {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Spline {
+    fn type_uuid() -> Uuid {
+        uuid!("3f6e2b8a-0d4c-4c1e-b7a9-5e2d8f1c6a47")
+    }
+}
+
+fn catmull_rom(
+    p0: &Vector3<f32>,
+    p1: &Vector3<f32>,
+    p2: &Vector3<f32>,
+    p3: &Vector3<f32>,
+    t: f32,
+) -> Vector3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    (p1.scale(2.0)
+        + (p2 - p0).scale(t)
+        + (p0.scale(2.0) - p1.scale(5.0) + p2.scale(4.0) - p3).scale(t2)
+        + (p1.scale(3.0) - p0 - p2.scale(3.0) + p3).scale(t3))
+    .scale(0.5)
+}
+
+fn catmull_rom_derivative(
+    p0: &Vector3<f32>,
+    p1: &Vector3<f32>,
+    p2: &Vector3<f32>,
+    p3: &Vector3<f32>,
+    t: f32,
+) -> Vector3<f32> {
+    ((p2 - p0)
+        + (p0.scale(2.0) - p1.scale(5.0) + p2.scale(4.0) - p3).scale(2.0 * t)
+        + (p1.scale(3.0) - p0 - p2.scale(3.0) + p3).scale(3.0 * t * t))
+    .scale(0.5)
+}
+
+fn bezier(
+    p0: &Vector3<f32>,
+    c0: &Vector3<f32>,
+    c1: &Vector3<f32>,
+    p1: &Vector3<f32>,
+    t: f32,
+) -> Vector3<f32> {
+    let u = 1.0 - t;
+    p0.scale(u * u * u)
+        + c0.scale(3.0 * u * u * t)
+        + c1.scale(3.0 * u * t * t)
+        + p1.scale(t * t * t)
+}
+
+fn bezier_derivative(
+    p0: &Vector3<f32>,
+    c0: &Vector3<f32>,
+    c1: &Vector3<f32>,
+    p1: &Vector3<f32>,
+    t: f32,
+) -> Vector3<f32> {
+    let u = 1.0 - t;
+    (c0 - p0).scale(3.0 * u * u) + (c1 - c0).scale(6.0 * u * t) + (p1 - c1).scale(3.0 * t * t)
+}
+
+impl Spline {
+    /// Sets new kind of the spline.
+    pub fn set_kind(&mut self, kind: SplineKind) -> SplineKind {
+        self.kind.set_value_and_mark_modified(kind)
+    }
+
+    /// Returns current kind of the spline.
+    pub fn kind(&self) -> SplineKind {
+        *self.kind
+    }
+
+    /// Sets new control points of the spline (in local coordinates).
+    pub fn set_control_points(&mut self, points: Vec<Vector3<f32>>) -> Vec<Vector3<f32>> {
+        self.control_points.set_value_and_mark_modified(points)
+    }
+
+    /// Returns a reference to the control points of the spline (in local coordinates).
+    pub fn control_points(&self) -> &[Vector3<f32>] {
+        &self.control_points
+    }
+
+    /// Returns a mutable reference to the control points of the spline (in local coordinates).
+    pub fn control_points_mut(&mut self) -> &mut Vec<Vector3<f32>> {
+        self.control_points.get_value_mut_and_mark_modified()
+    }
+
+    /// Makes the spline closed or open. Closed spline connects its last control point with the first
+    /// one, forming a loop.
+    pub fn set_closed(&mut self, closed: bool) -> bool {
+        self.closed.set_value_and_mark_modified(closed)
+    }
+
+    /// Returns `true` if the spline is closed, `false` - otherwise.
+    pub fn is_closed(&self) -> bool {
+        *self.closed
+    }
+
+    /// Returns the amount of segments of the spline.
+    pub fn segment_count(&self) -> usize {
+        let count = self.control_points.len();
+        match *self.kind {
+            SplineKind::CatmullRom => {
+                if count < 2 {
+                    0
+                } else if *self.closed {
+                    count
+                } else {
+                    count - 1
+                }
+            }
+            SplineKind::Bezier => {
+                if *self.closed {
+                    count / 3
+                } else {
+                    count.saturating_sub(1) / 3
+                }
+            }
+        }
+    }
+
+    /// Maps the global parameter of the spline to a segment index and a local parameter of the
+    /// segment.
+    fn locate(&self, t: f32, segment_count: usize) -> (usize, f32) {
+        let s = t.clamp(0.0, 1.0) * segment_count as f32;
+        let index = (s.floor() as usize).min(segment_count - 1);
+        (index, s - index as f32)
+    }
+
+    fn point(&self, index: usize) -> &Vector3<f32> {
+        let points: &[Vector3<f32>] = &self.control_points;
+        if *self.closed {
+            &points[index % points.len()]
+        } else {
+            &points[index.min(points.len() - 1)]
+        }
+    }
+
+    fn catmull_rom_points(&self, segment: usize) -> [&Vector3<f32>; 4] {
+        let count = self.control_points.len();
+        let prev = if *self.closed {
+            (segment + count - 1) % count
+        } else {
+            segment.saturating_sub(1)
+        };
+        [
+            self.point(prev),
+            self.point(segment),
+            self.point(segment + 1),
+            self.point(segment + 2),
+        ]
+    }
+
+    fn bezier_points(&self, segment: usize) -> [&Vector3<f32>; 4] {
+        let first = segment * 3;
+        [
+            self.point(first),
+            self.point(first + 1),
+            self.point(first + 2),
+            self.point(first + 3),
+        ]
+    }
+
+    /// Calculates a point on the spline (in local coordinates) at the given parameter in `[0; 1]`
+    /// range.
+    pub fn evaluate(&self, t: f32) -> Vector3<f32> {
+        let points: &[Vector3<f32>] = &self.control_points;
+        let segment_count = self.segment_count();
+        if segment_count == 0 {
+            // Degenerate spline - a Bézier spline with less than four points becomes a straight line.
+            return match points.len() {
+                0 => Vector3::default(),
+                1 => points[0],
+                _ => points[0].lerp(&points[points.len() - 1], t.clamp(0.0, 1.0)),
+            };
+        }
+
+        let (segment, t) = self.locate(t, segment_count);
+        match *self.kind {
+            SplineKind::CatmullRom => {
+                let [p0, p1, p2, p3] = self.catmull_rom_points(segment);
+                catmull_rom(p0, p1, p2, p3, t)
+            }
+            SplineKind::Bezier => {
+                let [p0, c0, c1, p1] = self.bezier_points(segment);
+                bezier(p0, c0, c1, p1, t)
+            }
+        }
+    }
+
+    /// Calculates a tangent of the spline (in local coordinates) at the given parameter in `[0; 1]`
+    /// range. The tangent is the derivative of the curve with respect to the parameter, so it is
+    /// not normalized - its length is proportional to the speed at which a point moves along the
+    /// curve when the parameter changes.
+    pub fn tangent(&self, t: f32) -> Vector3<f32> {
+        let points: &[Vector3<f32>] = &self.control_points;
+        let segment_count = self.segment_count();
+        if segment_count == 0 {
+            return if points.len() < 2 {
+                Vector3::default()
+            } else {
+                points[points.len() - 1] - points[0]
+            };
+        }
+
+        let (segment, t) = self.locate(t, segment_count);
+        let derivative = match *self.kind {
+            SplineKind::CatmullRom => {
+                let [p0, p1, p2, p3] = self.catmull_rom_points(segment);
+                catmull_rom_derivative(p0, p1, p2, p3, t)
+            }
+            SplineKind::Bezier => {
+                let [p0, c0, c1, p1] = self.bezier_points(segment);
+                bezier_derivative(p0, c0, c1, p1, t)
+            }
+        };
+        derivative.scale(segment_count as f32)
+    }
+
+    /// Calculates a point on the spline in world coordinates. See [`Self::evaluate`] for more info.
+    pub fn evaluate_global(&self, t: f32) -> Vector3<f32> {
+        self.global_transform()
+            .transform_point(&self.evaluate(t).into())
+            .coords
+    }
+
+    /// Calculates a tangent of the spline in world coordinates. See [`Self::tangent`] for more info.
+    pub fn tangent_global(&self, t: f32) -> Vector3<f32> {
+        self.global_transform().transform_vector(&self.tangent(t))
+    }
+
+    /// Builds a table, that maps the distance along the spline (in world coordinates) to the
+    /// parameter of the spline. The curve is approximated by a polyline with the given amount of
+    /// samples per segment, more samples gives better precision. See [`ArcLengthTable`] docs for
+    /// more info.
+    pub fn arc_length_table(&self, samples_per_segment: usize) -> ArcLengthTable {
+        let sample_count = (self.segment_count() * samples_per_segment).max(1);
+        let mut samples = Vec::with_capacity(sample_count + 1);
+        let mut prev = self.evaluate_global(0.0);
+        let mut distance = 0.0;
+        samples.push((0.0, 0.0));
+        for i in 1..=sample_count {
+            let t = i as f32 / sample_count as f32;
+            let point = self.evaluate_global(t);
+            distance += point.metric_distance(&prev);
+            samples.push((t, distance));
+            prev = point;
+        }
+        ArcLengthTable { samples }
+    }
+}
+
+/// A table that maps the distance along a spline (in world units) to the parameter of the spline.
+/// The parameter of a spline does not change uniformly with the distance along the curve, because
+/// control points could be placed unevenly. This table allows you to move along the spline with
+/// constant speed. Use [`Spline::arc_length_table`] to build the table.
+#[derive(Clone, Debug, Default)]
+pub struct ArcLengthTable {
+    /// Pairs of `(parameter, distance)`, sorted by both values.
+    samples: Vec<(f32, f32)>,
+}
+
+impl ArcLengthTable {
+    /// Returns total length of the spline.
+    pub fn length(&self) -> f32 {
+        self.samples.last().map_or(0.0, |(_, distance)| *distance)
+    }
+
+    /// Returns the parameter of the spline at the given distance along the curve. The distance is
+    /// clamped to `[0; length]` range.
+    pub fn parameter_at(&self, distance: f32) -> f32 {
+        let distance = distance.clamp(0.0, self.length());
+        let index = self
+            .samples
+            .partition_point(|(_, sample_distance)| *sample_distance < distance);
+        if index == 0 {
+            return self.samples.first().map_or(0.0, |(t, _)| *t);
+        }
+        let Some((t1, d1)) = self.samples.get(index).cloned() else {
+            return 1.0;
+        };
+        let (t0, d0) = self.samples[index - 1];
+        let span = d1 - d0;
+        if span <= f32::EPSILON {
+            t1
+        } else {
+            t0 + (t1 - t0) * (distance - d0) / span
+        }
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Spline {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Spline", |_| {
+                SplineBuilder::new(BaseBuilder::new().with_name("Spline"))
+                    .with_control_points(vec![
+                        Vector3::new(0.0, 0.0, 0.0),
+                        Vector3::new(1.0, 0.0, 1.0),
+                        Vector3::new(2.0, 0.0, 0.0),
+                        Vector3::new(3.0, 0.0, 1.0),
+                    ])
+                    .build_node()
+                    .into()
+            })
+            .with_group("Utils")
+    }
+}
+
+impl NodeTrait for Spline {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        if self.control_points.is_empty() {
+            self.base.local_bounding_box()
+        } else {
+            AxisAlignedBoundingBox::from_points(&self.control_points)
+        }
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn debug_draw(&self, ctx: &mut SceneDrawingContext) {
+        const STEPS_PER_SEGMENT: usize = 16;
+
+        let step_count = (self.segment_count() * STEPS_PER_SEGMENT).max(1);
+        let mut prev = self.evaluate_global(0.0);
+        for i in 1..=step_count {
+            let point = self.evaluate_global(i as f32 / step_count as f32);
+            ctx.add_line(Line {
+                begin: prev,
+                end: point,
+                color: Color::GREEN,
+            });
+            prev = point;
+        }
+
+        let transform = self.global_transform();
+        for point in self.control_points.iter() {
+            ctx.draw_wire_sphere(
+                transform.transform_point(&(*point).into()).coords,
+                0.05,
+                8,
+                Color::ORANGE,
+            );
+        }
+    }
+}
+
+/// Allows you to create a spline in a declarative manner.
+pub struct SplineBuilder {
+    base_builder: BaseBuilder,
+    kind: SplineKind,
+    control_points: Vec<Vector3<f32>>,
+    closed: bool,
+}
+
+impl SplineBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            kind: Default::default(),
+            control_points: Default::default(),
+            closed: false,
+        }
+    }
+
+    /// Sets desired kind of the spline.
+    pub fn with_kind(mut self, kind: SplineKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets desired control points of the spline (in local coordinates).
+    pub fn with_control_points(mut self, control_points: Vec<Vector3<f32>>) -> Self {
+        self.control_points = control_points;
+        self
+    }
+
+    /// Makes the spline closed or open.
+    pub fn with_closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// Creates new spline node.
+    pub fn build_spline(self) -> Spline {
+        Spline {
+            base: self.base_builder.build_base(),
+            kind: self.kind.into(),
+            control_points: self.control_points.into(),
+            closed: self.closed.into(),
+        }
+    }
+
+    /// Creates new spline node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_spline())
+    }
+
+    /// Creates new instance of spline node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Spline> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        scene::{
+            base::BaseBuilder,
+            spline::{SplineBuilder, SplineKind},
+        },
+    };
+
+    fn assert_near(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!(a.metric_distance(&b) < 1.0e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_catmull_rom_passes_through_control_points() {
+        let points = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, 0.0),
+            Vector3::new(3.0, 2.0, 1.0),
+            Vector3::new(4.0, 0.0, -1.0),
+            Vector3::new(6.0, 1.0, 0.0),
+        ];
+
+        let mut spline = SplineBuilder::new(BaseBuilder::new())
+            .with_control_points(points.clone())
+            .build_spline();
+        let last = (points.len() - 1) as f32;
+        for (i, point) in points.iter().enumerate() {
+            assert_near(spline.evaluate(i as f32 / last), *point);
+        }
+        // Tangents at the inner points are defined by the neighbour points.
+        assert_near(
+            spline.tangent(1.0 / last),
+            (points[2] - points[0]).scale(0.5 * last),
+        );
+
+        spline.set_closed(true);
+        let count = points.len() as f32;
+        for (i, point) in points.iter().enumerate() {
+            assert_near(spline.evaluate(i as f32 / count), *point);
+        }
+        assert_near(spline.evaluate(1.0), points[0]);
+
+        // Bézier spline passes through its end points.
+        spline.set_closed(false);
+        spline.set_kind(SplineKind::Bezier);
+        spline.control_points_mut().truncate(4);
+        assert_near(spline.evaluate(0.0), points[0]);
+        assert_near(spline.evaluate(1.0), points[3]);
+        assert_near(spline.tangent(0.0), (points[1] - points[0]).scale(3.0));
+
+        // Degenerate splines.
+        spline.set_kind(SplineKind::CatmullRom);
+        spline.set_control_points(vec![Vector3::new(1.0, 2.0, 3.0)]);
+        assert_near(spline.evaluate(0.5), Vector3::new(1.0, 2.0, 3.0));
+        assert_near(spline.tangent(0.5), Vector3::default());
+        spline.set_control_points(vec![]);
+        assert_near(spline.evaluate(0.5), Vector3::default());
+        assert_near(spline.tangent(0.5), Vector3::default());
+    }
+}
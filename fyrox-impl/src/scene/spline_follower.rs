@@ -0,0 +1,552 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Spline follower is a node that moves along a spline with constant speed. See [`SplineFollower`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix4, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        constraint,
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        spline::{ArcLengthTable, Spline, SplineKind},
+    },
+};
+use fyrox_graph::constructor::ConstructorProvider;
+use std::ops::{Deref, DerefMut};
+use strum_macros::{AsRefStr, EnumString, VariantNames};
+
+/// Defines what happens when a [`SplineFollower`] reaches an end of its spline.
+#[derive(
+    Default, Copy, Clone, Debug, PartialEq, Eq, Visit, Reflect, AsRefStr, EnumString, VariantNames,
+)]
+pub enum SplineFollowMode {
+    /// The follower stops at the end of the spline.
+    Once,
+    /// The follower jumps back to the beginning of the spline and continues moving.
+    #[default]
+    Loop,
+    /// The follower changes its direction at both ends of the spline, moving back and forth.
+    PingPong,
+}
+
+uuid_provider!(SplineFollowMode = "d2b7e4a1-6c3f-4e8d-a5b2-9f1e7c4d3a68");
+
+/// Arc-length table of a spline along with the state of the spline, that was used to build the
+/// table. The table is rebuilt only when the spline changes.
+#[derive(Clone, Debug, Default)]
+struct ArcLengthCache {
+    spline: Handle<Spline>,
+    kind: SplineKind,
+    closed: bool,
+    control_points: Vec<Vector3<f32>>,
+    global_transform: Matrix4<f32>,
+    table: ArcLengthTable,
+}
+
+impl ArcLengthCache {
+    fn is_valid_for(&self, handle: Handle<Spline>, spline: &Spline) -> bool {
+        self.spline == handle
+            && self.kind == spline.kind()
+            && self.closed == spline.is_closed()
+            && self.control_points == spline.control_points()
+            && self.global_transform == spline.global_transform()
+    }
+
+    fn update(&mut self, handle: Handle<Spline>, spline: &Spline, samples_per_segment: usize) {
+        if !self.is_valid_for(handle, spline) {
+            self.spline = handle;
+            self.kind = spline.kind();
+            self.closed = spline.is_closed();
+            self.control_points.clear();
+            self.control_points
+                .extend_from_slice(spline.control_points());
+            self.global_transform = spline.global_transform();
+            self.table = spline.arc_length_table(samples_per_segment);
+        }
+    }
+}
+
+/// Spline follower is a node that moves along a [`Spline`] with constant speed (in world units per
+/// second). The movement uses arc-length parametrization of the spline, which means that the speed
+/// does not depend on the spacing of the control points of the spline. Attach any other node to the
+/// follower to move it along the spline.
+///
+/// Optionally, the follower could orient itself along the tangent of the spline, so its look vector
+/// (`+Z` axis) will point in the direction of the movement. This is useful for vehicles, cameras,
+/// etc.
+///
+/// # Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::pool::Handle,
+/// #     scene::{
+/// #         base::BaseBuilder,
+/// #         graph::Graph,
+/// #         spline::Spline,
+/// #         spline_follower::{SplineFollowMode, SplineFollower, SplineFollowerBuilder},
+/// #     },
+/// # };
+/// fn create_follower(graph: &mut Graph, spline: Handle<Spline>) -> Handle<SplineFollower> {
+///     SplineFollowerBuilder::new(BaseBuilder::new())
+///         .with_spline(spline)
+///         .with_speed(2.0)
+///         .with_mode(SplineFollowMode::PingPong)
+///         .with_look_along_tangent(true)
+///         .build(graph)
+/// }
+/// ```
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct SplineFollower {
+    base: Base,
+
+    #[reflect(setter = "set_spline")]
+    spline: InheritableVariable<Handle<Spline>>,
+
+    #[reflect(setter = "set_speed")]
+    speed: InheritableVariable<f32>,
+
+    #[reflect(setter = "set_mode")]
+    mode: InheritableVariable<SplineFollowMode>,
+
+    #[reflect(setter = "set_look_along_tangent")]
+    look_along_tangent: InheritableVariable<bool>,
+
+    #[reflect(setter = "set_distance")]
+    distance: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    reversed: bool,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    arc_length_cache: ArcLengthCache,
+}
+
+impl Default for SplineFollower {
+    fn default() -> Self {
+        SplineFollowerBuilder::new(BaseBuilder::new()).build_spline_follower()
+    }
+}
+
+impl Deref for SplineFollower {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for SplineFollower {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for SplineFollower {
+    fn type_uuid() -> Uuid {
+        uuid!("5c8e1f2d-3a7b-4d6e-b9c4-2e8f6a1d7b53")
+    }
+}
+
+impl SplineFollower {
+    /// Amount of samples per segment of the spline, that is used to build arc-length table.
+    const SAMPLES_PER_SEGMENT: usize = 32;
+
+    /// Sets a spline to follow.
+    pub fn set_spline(&mut self, spline: Handle<Spline>) -> Handle<Spline> {
+        self.spline.set_value_and_mark_modified(spline)
+    }
+
+    /// Returns a handle of the spline to follow.
+    pub fn spline(&self) -> Handle<Spline> {
+        *self.spline
+    }
+
+    /// Sets movement speed (in world units per second). Negative speed makes the follower to move
+    /// backwards.
+    pub fn set_speed(&mut self, speed: f32) -> f32 {
+        self.speed.set_value_and_mark_modified(speed)
+    }
+
+    /// Returns current movement speed (in world units per second).
+    pub fn speed(&self) -> f32 {
+        *self.speed
+    }
+
+    /// Sets new follow mode. See [`SplineFollowMode`] docs for more info.
+    pub fn set_mode(&mut self, mode: SplineFollowMode) -> SplineFollowMode {
+        self.mode.set_value_and_mark_modified(mode)
+    }
+
+    /// Returns current follow mode.
+    pub fn mode(&self) -> SplineFollowMode {
+        *self.mode
+    }
+
+    /// Defines whether the follower should orient itself along the tangent of the spline or not.
+    pub fn set_look_along_tangent(&mut self, look_along_tangent: bool) -> bool {
+        self.look_along_tangent
+            .set_value_and_mark_modified(look_along_tangent)
+    }
+
+    /// Returns `true` if the follower orients itself along the tangent of the spline, `false` -
+    /// otherwise.
+    pub fn is_looking_along_tangent(&self) -> bool {
+        *self.look_along_tangent
+    }
+
+    /// Sets current distance (in world units) from the beginning of the spline.
+    pub fn set_distance(&mut self, distance: f32) -> f32 {
+        self.distance.set_value_and_mark_modified(distance)
+    }
+
+    /// Returns current distance (in world units) from the beginning of the spline.
+    pub fn distance(&self) -> f32 {
+        *self.distance
+    }
+
+    /// Returns `true` if the follower moves from the end of the spline to its beginning in the
+    /// [`SplineFollowMode::PingPong`] mode.
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    fn advance(&mut self, dt: f32, length: f32) {
+        let offset = *self.speed * dt;
+        let distance = match *self.mode {
+            SplineFollowMode::Once => (*self.distance + offset).clamp(0.0, length),
+            SplineFollowMode::Loop => (*self.distance + offset).rem_euclid(length),
+            SplineFollowMode::PingPong => {
+                // The back and forth movement is a loop over a path that is twice as long as the
+                // spline.
+                let phase = if self.reversed {
+                    2.0 * length - *self.distance
+                } else {
+                    *self.distance
+                };
+                let phase = (phase + offset).rem_euclid(2.0 * length);
+                self.reversed = phase > length;
+                if self.reversed {
+                    2.0 * length - phase
+                } else {
+                    phase
+                }
+            }
+        };
+        self.distance.set_value_silent(distance);
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for SplineFollower {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Spline Follower", |_| {
+                SplineFollowerBuilder::new(BaseBuilder::new().with_name("Spline Follower"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Utils")
+    }
+}
+
+impl NodeTrait for SplineFollower {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        let Ok(spline) = context.nodes.try_get(*self.spline) else {
+            return;
+        };
+
+        self.arc_length_cache
+            .update(*self.spline, spline, Self::SAMPLES_PER_SEGMENT);
+        let length = self.arc_length_cache.table.length();
+        if length <= f32::EPSILON {
+            return;
+        }
+
+        self.advance(context.dt, length);
+
+        let t = self.arc_length_cache.table.parameter_at(*self.distance);
+        let position = spline.evaluate_global(t);
+        let mut direction = spline.tangent_global(t);
+        // Negative speed inverts the direction of the movement in every mode, including the way
+        // back in the ping-pong mode.
+        if self.reversed != (*self.speed < 0.0) {
+            direction = -direction;
+        }
+
+        let parent_inv_transform =
+            constraint::inverse_global_transform(context.nodes, self.parent());
+        let parent_rotation = constraint::global_rotation(context.nodes, self.parent());
+
+        let local_position = parent_inv_transform
+            .transform_point(&position.into())
+            .coords;
+        self.local_transform_mut().set_position(local_position);
+
+        if *self.look_along_tangent {
+            if let Some(rotation) = constraint::look_rotation(&direction) {
+                self.local_transform_mut()
+                    .set_rotation(parent_rotation.inverse() * rotation);
+            }
+        }
+    }
+}
+
+/// Allows you to create a spline follower in a declarative manner.
+pub struct SplineFollowerBuilder {
+    base_builder: BaseBuilder,
+    spline: Handle<Spline>,
+    speed: f32,
+    mode: SplineFollowMode,
+    look_along_tangent: bool,
+    distance: f32,
+}
+
+impl SplineFollowerBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            spline: Default::default(),
+            speed: 1.0,
+            mode: Default::default(),
+            look_along_tangent: false,
+            distance: 0.0,
+        }
+    }
+
+    /// Sets a spline to follow.
+    pub fn with_spline(mut self, spline: Handle<Spline>) -> Self {
+        self.spline = spline;
+        self
+    }
+
+    /// Sets desired movement speed (in world units per second).
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets desired follow mode.
+    pub fn with_mode(mut self, mode: SplineFollowMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Defines whether the follower should orient itself along the tangent of the spline or not.
+    pub fn with_look_along_tangent(mut self, look_along_tangent: bool) -> Self {
+        self.look_along_tangent = look_along_tangent;
+        self
+    }
+
+    /// Sets initial distance (in world units) from the beginning of the spline.
+    pub fn with_distance(mut self, distance: f32) -> Self {
+        self.distance = distance;
+        self
+    }
+
+    /// Creates new spline follower.
+    pub fn build_spline_follower(self) -> SplineFollower {
+        SplineFollower {
+            base: self.base_builder.build_base(),
+            spline: self.spline.into(),
+            speed: self.speed.into(),
+            mode: self.mode.into(),
+            look_along_tangent: self.look_along_tangent.into(),
+            distance: self.distance.into(),
+            reversed: false,
+            arc_length_cache: Default::default(),
+        }
+    }
+
+    /// Creates new spline follower node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_spline_follower())
+    }
+
+    /// Creates new instance of spline follower node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<SplineFollower> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            spline::SplineBuilder,
+            spline_follower::{SplineFollowMode, SplineFollowerBuilder},
+        },
+    };
+
+    #[test]
+    fn test_constant_speed_traversal() {
+        let mut graph = Graph::new();
+
+        // Control points are spaced very unevenly, so a naive parametrization would make the
+        // follower move a lot faster on the long segments.
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_control_points(vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(2.0, 1.0, 0.0),
+                Vector3::new(6.0, 1.0, 0.0),
+                Vector3::new(12.0, 0.0, 0.0),
+            ])
+            .build(&mut graph);
+
+        let speed = 2.0;
+        let dt = 1.0 / 60.0;
+        let follower = SplineFollowerBuilder::new(BaseBuilder::new())
+            .with_spline(spline)
+            .with_speed(speed)
+            .with_mode(SplineFollowMode::Once)
+            .with_look_along_tangent(true)
+            .build(&mut graph);
+
+        // The spline is longer than 12 units, 5 seconds of movement is not enough to reach its end.
+        let mut prev = Vector3::default();
+        for step in 0..300 {
+            graph.update(Vector2::new(100.0, 100.0), dt, Default::default());
+            let position = **graph[follower].local_transform().position();
+            let covered = position.metric_distance(&prev);
+            // Every step must cover the same distance along the curve. Steps are small, so the
+            // chord length is a good approximation of the arc length.
+            assert!(
+                (covered - speed * dt).abs() < 0.05 * speed * dt,
+                "step {step} covered {covered}"
+            );
+            prev = position;
+        }
+        assert!((graph[follower].distance() - 300.0 * speed * dt).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_ping_pong() {
+        let mut graph = Graph::new();
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_control_points(vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(4.0, 0.0, 0.0),
+            ])
+            .build(&mut graph);
+        let follower = SplineFollowerBuilder::new(BaseBuilder::new())
+            .with_spline(spline)
+            .with_speed(1.0)
+            .with_mode(SplineFollowMode::PingPong)
+            .build(&mut graph);
+
+        for _ in 0..5 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        }
+
+        // 5 units of travel over a spline of length 4 - the follower must be on its way back.
+        let follower = &graph[follower];
+        assert!(follower.is_reversed());
+        assert!((follower.distance() - 3.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn test_ping_pong_facing_with_negative_speed() {
+        let mut graph = Graph::new();
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_control_points(vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(4.0, 0.0, 0.0),
+            ])
+            .build(&mut graph);
+        let follower = SplineFollowerBuilder::new(BaseBuilder::new())
+            .with_spline(spline)
+            .with_speed(-1.0)
+            .with_distance(2.0)
+            .with_mode(SplineFollowMode::PingPong)
+            .with_look_along_tangent(true)
+            .build(&mut graph);
+
+        let mut prev = 2.0;
+        for _ in 0..8 {
+            graph.update(Vector2::new(100.0, 100.0), 0.5, Default::default());
+            let follower = &graph[follower];
+            let position = follower.local_transform().position().x;
+            let look = follower
+                .local_transform()
+                .rotation()
+                .transform_vector(&Vector3::z());
+            // The follower must always face the direction of its movement.
+            assert!(look.x.signum() == (position - prev).signum(), "{look:?}");
+            prev = position;
+        }
+    }
+
+    #[test]
+    fn test_arc_length_table_is_rebuilt_on_spline_change() {
+        let mut graph = Graph::new();
+        let spline = SplineBuilder::new(BaseBuilder::new())
+            .with_control_points(vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(4.0, 0.0, 0.0),
+            ])
+            .build(&mut graph);
+        let follower = SplineFollowerBuilder::new(BaseBuilder::new())
+            .with_spline(spline)
+            .with_speed(1.0)
+            .with_mode(SplineFollowMode::Once)
+            .build(&mut graph);
+
+        for _ in 0..6 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        }
+        assert!((graph[follower].distance() - 4.0).abs() < 1.0e-3);
+
+        graph[spline].control_points_mut()[1] = Vector3::new(8.0, 0.0, 0.0);
+        for _ in 0..2 {
+            graph.update(Vector2::new(100.0, 100.0), 1.0, Default::default());
+        }
+        assert!((graph[follower].distance() - 6.0).abs() < 1.0e-3);
+    }
+}
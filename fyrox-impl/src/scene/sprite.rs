@@ -507,3 +507,100 @@ impl SpriteBuilder {
         graph.add_node(self.build_node()).to_variant()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{io::FsResourceIo, manager::ResourceManager, untyped::ResourceKind},
+        core::{
+            color::Color, futures::executor::block_on, math::Rect, uuid::Uuid, visitor::Visitor,
+        },
+        engine::{self, SerializationContext},
+        graph::SceneGraph,
+        material::{Material, MaterialResource},
+        resource::texture::{
+            TextureKind, TexturePixelKind, TextureResource, TextureResourceExtension,
+        },
+        scene::{
+            base::BaseBuilder,
+            sprite::{Sprite, SpriteBuilder},
+            Scene, SceneLoader,
+        },
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_sprite_round_trip() {
+        let pixels = vec![
+            255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255,
+        ];
+        let texture = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2,
+            },
+            TexturePixelKind::RGBA8,
+            pixels.clone(),
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        let mut material = Material::standard_sprite();
+        material.bind("diffuseTexture", texture);
+
+        let mut scene = Scene::new();
+        SpriteBuilder::new(BaseBuilder::new().with_name("Marker"))
+            .with_material(MaterialResource::new_embedded(material))
+            .with_size(1.5)
+            .with_color(Color::opaque(10, 20, 30))
+            .with_rotation(0.75)
+            .with_uv_rect(Rect::new(0.0, 0.0, 0.5, 0.5))
+            .build(&mut scene.graph);
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        engine::initialize_resource_manager_loaders(
+            &resource_manager,
+            Arc::new(SerializationContext::new()),
+            Default::default(),
+            Default::default(),
+        );
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let loader = SceneLoader::load(
+            "Scene",
+            Arc::new(SerializationContext::new()),
+            Default::default(),
+            resource_manager,
+            &mut visitor,
+            None,
+        )
+        .unwrap();
+        let loaded = block_on(loader.finish());
+
+        let (handle, _) = loaded.graph.find_by_name_from_root("Marker").unwrap();
+        let sprite = loaded.graph[handle].cast::<Sprite>().unwrap();
+        assert_eq!(sprite.size(), 1.5);
+        assert_eq!(sprite.color(), Color::opaque(10, 20, 30));
+        assert_eq!(sprite.rotation(), 0.75);
+        assert_eq!(sprite.uv_rect(), Rect::new(0.0, 0.0, 0.5, 0.5));
+
+        let texture = sprite
+            .material()
+            .data_ref()
+            .texture("diffuseTexture")
+            .unwrap();
+        let texture = texture.data_ref();
+        assert!(matches!(
+            texture.kind(),
+            TextureKind::Rectangle {
+                width: 2,
+                height: 2
+            }
+        ));
+        assert_eq!(texture.data(), pixels.as_slice());
+    }
+}
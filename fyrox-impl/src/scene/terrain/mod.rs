@@ -2544,6 +2544,7 @@ impl NodeTrait for Terrain {
                                 world_transform: node_transform,
                                 bone_matrices: Default::default(),
                                 blend_shapes_weights: Default::default(),
+                                skinning_mode: Default::default(),
                                 element_range: ElementRange::Full,
                                 node_handle: self.handle(),
                             },
@@ -2560,6 +2561,7 @@ impl NodeTrait for Terrain {
                                         world_transform: node_transform,
                                         bone_matrices: Default::default(),
                                         blend_shapes_weights: Default::default(),
+                                        skinning_mode: Default::default(),
                                         element_range: self.geometry.quadrants[i],
                                         node_handle: self.handle(),
                                     },
@@ -0,0 +1,302 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Trigger is a volume that detects other colliders entering and leaving it, without any physical
+//! response. See [`Trigger`] docs for more info.
+
+use crate::{
+    core::{
+        math::{aabb::AxisAlignedBoundingBox, m4x4_approx_eq},
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::{Collider, ColliderBuilder, ColliderShape},
+        graph::{
+            physics::{IntersectionEvent, PhysicsWorld},
+            Graph,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, SyncContext},
+    },
+};
+use fyrox_core::algebra::Matrix4;
+use fyrox_graph::{constructor::ConstructorProvider, SceneGraph};
+use rapier3d::geometry::ColliderHandle;
+use std::ops::{Deref, DerefMut};
+
+/// Trigger is a volume that detects other colliders entering and leaving it, without applying any
+/// forces to them. Typical usages are checkpoints, damage zones, audio reverb zones, doors that
+/// open when a player comes close, etc.
+///
+/// Unlike a [`Collider`], trigger does not need a rigid body - it is a standalone sensor collider
+/// that follows the global transform of the node. Trigger detects dynamic and kinematic rigid
+/// bodies as well as static ones. Internally, it is just a collider with `is_sensor` flag set, so
+/// all collider properties (shape, collision groups, etc.) are available via [`Trigger::collider`]
+/// and [`Trigger::collider_mut`].
+///
+/// # Events
+///
+/// Use [`Trigger::events`] to get a list of colliders that entered or left the trigger during the
+/// last physics step and [`Trigger::overlaps`] to get a list of colliders that are currently inside
+/// the trigger.
+#[derive(Debug, Clone, Visit, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Trigger {
+    #[component(include)]
+    collider: Collider,
+}
+
+impl Default for Trigger {
+    fn default() -> Self {
+        let mut collider = Collider::default();
+        collider.set_is_sensor(true);
+        Self { collider }
+    }
+}
+
+impl Deref for Trigger {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.collider
+    }
+}
+
+impl DerefMut for Trigger {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.collider
+    }
+}
+
+impl TypeUuidProvider for Trigger {
+    fn type_uuid() -> Uuid {
+        uuid!("9b3cd5e2-0d4a-4c8e-b6a1-6e2f1f4a8d37")
+    }
+}
+
+impl Trigger {
+    /// Returns a reference to the inner collider of the trigger.
+    pub fn collider(&self) -> &Collider {
+        &self.collider
+    }
+
+    /// Returns a reference to the inner collider of the trigger.
+    pub fn collider_mut(&mut self) -> &mut Collider {
+        &mut self.collider
+    }
+
+    /// Sets new shape of the trigger.
+    pub fn set_shape(&mut self, shape: ColliderShape) -> ColliderShape {
+        self.collider.set_shape(shape)
+    }
+
+    /// Returns current shape of the trigger.
+    pub fn shape(&self) -> &ColliderShape {
+        self.collider.shape()
+    }
+
+    /// Returns an iterator that yields handles of the nodes whose colliders are currently inside
+    /// the trigger.
+    pub fn overlaps<'a>(
+        &self,
+        physics: &'a PhysicsWorld,
+    ) -> impl Iterator<Item = Handle<Node>> + 'a {
+        let self_handle = self.handle();
+        self.collider
+            .intersects(physics)
+            .filter(|pair| pair.has_any_active_contact)
+            .map(move |pair| pair.other(self_handle.to_variant()).to_base())
+    }
+
+    /// Returns an iterator that yields the intersection events of the trigger generated during
+    /// the last physics step. Use [`IntersectionEvent::other`] to get the handle of the node that
+    /// entered or left the trigger.
+    pub fn events<'a>(
+        &self,
+        physics: &'a PhysicsWorld,
+    ) -> impl Iterator<Item = &'a IntersectionEvent> + 'a {
+        let self_handle = self.handle();
+        physics
+            .intersection_events()
+            .iter()
+            .filter(move |event| event.involves(self_handle))
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Trigger {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Trigger", |_| {
+                TriggerBuilder::new(
+                    ColliderBuilder::new(BaseBuilder::new().with_name("Trigger"))
+                        .with_shape(ColliderShape::Cuboid(Default::default())),
+                )
+                .build_node()
+                .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for Trigger {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.collider.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.collider.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn on_removed_from_graph(&mut self, graph: &mut Graph) {
+        graph.physics.remove_collider(self.collider.native.get());
+        self.collider.native.set(ColliderHandle::invalid());
+    }
+
+    fn on_global_transform_changed(
+        &self,
+        new_global_transform: &Matrix4<f32>,
+        context: &mut SyncContext,
+    ) {
+        if !m4x4_approx_eq(new_global_transform, &self.global_transform()) {
+            context
+                .physics
+                .set_trigger_position(&self.collider, new_global_transform);
+        }
+    }
+
+    fn sync_native(&self, self_handle: Handle<Node>, context: &mut SyncContext) {
+        context
+            .physics
+            .sync_to_trigger_node(context.nodes, self_handle, &self.collider);
+    }
+}
+
+/// Allows you to create a trigger in a declarative manner.
+pub struct TriggerBuilder {
+    collider_builder: ColliderBuilder,
+}
+
+impl TriggerBuilder {
+    /// Creates a new trigger builder. Sensor flag of the collider builder is always set to `true`.
+    pub fn new(collider_builder: ColliderBuilder) -> Self {
+        Self { collider_builder }
+    }
+
+    /// Creates trigger node, but does not add it to a graph.
+    pub fn build_trigger(self) -> Trigger {
+        Trigger {
+            collider: self.collider_builder.with_sensor(true).build_collider(),
+        }
+    }
+
+    /// Creates trigger node, but does not add it to a graph.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_trigger())
+    }
+
+    /// Creates trigger node and adds it to the graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Trigger> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::{physics::IntersectionEventKind, Graph},
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+            trigger::TriggerBuilder,
+        },
+    };
+
+    #[test]
+    fn test_trigger_overlap_events() {
+        let mut graph = Graph::new();
+        graph
+            .physics
+            .gravity
+            .set_value_and_mark_modified(Vector3::default());
+
+        let trigger = TriggerBuilder::new(
+            ColliderBuilder::new(BaseBuilder::new())
+                .with_shape(ColliderShape::cuboid(1.0, 1.0, 1.0)),
+        )
+        .build(&mut graph);
+
+        let ball = ColliderBuilder::new(BaseBuilder::new())
+            .with_shape(ColliderShape::ball(0.25))
+            .build(&mut graph);
+        let velocity = Vector3::new(5.0, 0.0, 0.0);
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(-3.0, 0.0, 0.0))
+                        .build(),
+                )
+                .with_child(ball),
+        )
+        .with_lin_vel(velocity)
+        .with_can_sleep(false)
+        .build(&mut graph);
+
+        let mut started = 0;
+        let mut stopped = 0;
+        let mut overlapped = false;
+        for _ in 0..90 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+
+            for event in graph[trigger].events(&graph.physics) {
+                assert_eq!(event.other(trigger.to_base()), ball.to_base());
+                match event.kind {
+                    IntersectionEventKind::Started => started += 1,
+                    IntersectionEventKind::Stopped => stopped += 1,
+                }
+            }
+
+            overlapped |= graph[trigger]
+                .overlaps(&graph.physics)
+                .any(|h| h == ball.to_base());
+        }
+
+        assert_eq!(started, 1);
+        assert_eq!(stopped, 1);
+        assert!(overlapped);
+
+        // The body must pass through the trigger without any deflection.
+        assert!(graph[body].lin_vel().metric_distance(&velocity) < 1.0e-4);
+        let position = graph[body].global_position();
+        assert!(position.x > 1.0);
+        assert!(position.y.abs() < 1.0e-4 && position.z.abs() < 1.0e-4);
+    }
+}
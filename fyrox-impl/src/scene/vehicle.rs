@@ -0,0 +1,768 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Vehicle is a rigid body chassis supported by a set of ray cast wheels. See [`Vehicle`] docs for
+//! more info.
+
+use crate::{
+    core::{
+        algebra::{Point3, UnitQuaternion, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        collider::InteractionGroups,
+        graph::{
+            physics::{Intersection, RayCastOptions},
+            Graph,
+        },
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+        rigidbody::{RigidBody, RigidBodyType},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, SceneGraph};
+use std::ops::{Deref, DerefMut};
+
+/// A single ray cast wheel of a vehicle. A wheel does not have any physical representation, instead
+/// a ray is cast from its connection point along the down vector of the chassis to find the ground.
+/// When the ground is found, the wheel acts like a spring with a damper and applies suspension,
+/// drive, brake and friction forces to the chassis.
+#[derive(Clone, Debug, PartialEq, Visit, Reflect)]
+pub struct Wheel {
+    /// A point (in local coordinates of the chassis) where the suspension is attached to the
+    /// chassis.
+    pub connection_point: Vector3<f32>,
+    /// Length of the suspension when it is not loaded.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub rest_length: f32,
+    /// Radius of the wheel.
+    #[reflect(min_value = 0.0, step = 0.05)]
+    pub radius: f32,
+    /// Stiffness of the suspension spring (in N/m).
+    #[reflect(min_value = 0.0, step = 100.0)]
+    pub stiffness: f32,
+    /// Damping of the suspension (in N·s/m). It defines how fast oscillations of the chassis fade
+    /// out.
+    #[reflect(min_value = 0.0, step = 10.0)]
+    pub damping: f32,
+    /// Friction coefficient of the wheel. Maximum sideways force of the wheel is equal to the
+    /// suspension force multiplied by this coefficient.
+    #[reflect(min_value = 0.0, step = 0.1)]
+    pub friction: f32,
+    /// Whether the wheel is turned by steering input or not.
+    pub steerable: bool,
+    /// Whether the wheel is powered by the engine or not.
+    pub driven: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    in_contact: bool,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    compression: f32,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    contact_point: Vector3<f32>,
+}
+
+uuid_provider!(Wheel = "3f0b8c59-6a1e-4d2f-9e47-b8c2d51a7f06");
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self {
+            connection_point: Default::default(),
+            rest_length: 0.5,
+            radius: 0.35,
+            stiffness: 20000.0,
+            damping: 2000.0,
+            friction: 1.0,
+            steerable: false,
+            driven: true,
+            in_contact: false,
+            compression: 0.0,
+            contact_point: Default::default(),
+        }
+    }
+}
+
+impl Wheel {
+    /// Creates a new wheel attached at the given point of the chassis. Rest of the parameters are
+    /// set to defaults.
+    pub fn new(connection_point: Vector3<f32>) -> Self {
+        Self {
+            connection_point,
+            ..Default::default()
+        }
+    }
+
+    /// Sets length of the suspension when it is not loaded.
+    pub fn with_rest_length(mut self, rest_length: f32) -> Self {
+        self.rest_length = rest_length;
+        self
+    }
+
+    /// Sets radius of the wheel.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets stiffness of the suspension spring.
+    pub fn with_stiffness(mut self, stiffness: f32) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    /// Sets damping of the suspension.
+    pub fn with_damping(mut self, damping: f32) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Sets friction coefficient of the wheel.
+    pub fn with_friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+
+    /// Defines whether the wheel is turned by steering input or not.
+    pub fn with_steerable(mut self, steerable: bool) -> Self {
+        self.steerable = steerable;
+        self
+    }
+
+    /// Defines whether the wheel is powered by the engine or not.
+    pub fn with_driven(mut self, driven: bool) -> Self {
+        self.driven = driven;
+        self
+    }
+
+    /// Returns `true` if the wheel touches the ground, `false` - if it is in the air.
+    pub fn is_in_contact(&self) -> bool {
+        self.in_contact
+    }
+
+    /// Returns current compression of the suspension (in meters). It is always zero for wheels
+    /// in the air.
+    pub fn compression(&self) -> f32 {
+        self.compression
+    }
+
+    /// Returns a point (in world coordinates) where the wheel touches the ground. The value is
+    /// meaningful only if the wheel is in contact with the ground.
+    pub fn contact_point(&self) -> Vector3<f32> {
+        self.contact_point
+    }
+
+    /// Returns current length of the suspension, it could be used to position a visual
+    /// representation of the wheel.
+    pub fn suspension_length(&self) -> f32 {
+        self.rest_length - self.compression
+    }
+
+    fn reset_contact(&mut self) {
+        self.in_contact = false;
+        self.compression = 0.0;
+        self.contact_point = Default::default();
+    }
+}
+
+/// Vehicle is a simple ray cast vehicle model. It consists of a rigid body (chassis) and a set of
+/// [`Wheel`]s. Every wheel casts a ray down from its connection point to find the ground, and when
+/// the ground is found the wheel applies the following forces to the chassis:
+///
+/// - suspension force - a spring with a damper, that keeps the chassis above the ground;
+/// - drive force - engine force distributed evenly across all driven wheels, it is directed along
+///   the forward vector of the wheel (steerable wheels are turned by steering input);
+/// - brake force - opposes forward motion of the wheel;
+/// - sideways friction - opposes sideways sliding of the wheel, it is limited by the suspension
+///   force multiplied by friction coefficient of the wheel.
+///
+/// Wheels in the air do not apply any forces, so an airborne vehicle is just a falling rigid body.
+///
+/// ## Inputs
+///
+/// The vehicle is controlled by throttle, brake and steering inputs. Inputs are not serialized,
+/// they're meant to be set every frame from a game code:
+///
+/// ```rust
+/// # use fyrox_impl::scene::vehicle::Vehicle;
+/// fn drive(vehicle: &mut Vehicle, forward: bool, left: bool) {
+///     vehicle.set_throttle(if forward { 1.0 } else { 0.0 });
+///     vehicle.set_steer(if left { 1.0 } else { 0.0 });
+/// }
+/// ```
+///
+/// ## Limitations
+///
+/// Wheels have no inertia and do not spin, forces are applied at connection points of the wheels.
+/// Colliders of the chassis are ignored by ray casts, but the chassis must not have a collider that
+/// covers the wheels, otherwise the chassis will collide with the ground directly.
+#[derive(Clone, Reflect, Visit, Debug, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct Vehicle {
+    base: Base,
+
+    /// A handle of a rigid body, that is used as a chassis of the vehicle. The body must be dynamic.
+    pub chassis: InheritableVariable<Handle<RigidBody>>,
+
+    /// A set of wheels of the vehicle.
+    pub wheels: InheritableVariable<Vec<Wheel>>,
+
+    #[reflect(min_value = 0.0, step = 100.0)]
+    #[reflect(setter = "set_engine_force")]
+    engine_force: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 100.0)]
+    #[reflect(setter = "set_brake_force")]
+    brake_force: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, max_value = 1.57, step = 0.05)]
+    #[reflect(setter = "set_max_steer_angle")]
+    max_steer_angle: InheritableVariable<f32>,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    throttle: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    brake: f32,
+
+    #[visit(skip)]
+    #[reflect(hidden)]
+    steer: f32,
+}
+
+impl Default for Vehicle {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            chassis: Default::default(),
+            wheels: Default::default(),
+            engine_force: 4000.0.into(),
+            brake_force: 8000.0.into(),
+            max_steer_angle: 35.0f32.to_radians().into(),
+            throttle: 0.0,
+            brake: 0.0,
+            steer: 0.0,
+        }
+    }
+}
+
+impl Deref for Vehicle {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for Vehicle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for Vehicle {
+    fn type_uuid() -> Uuid {
+        uuid!("b7e2c4a1-0f93-4d58-a6c2-71e9d3f5b820")
+    }
+}
+
+impl Vehicle {
+    /// Sets maximum force (in Newtons) of the engine. It is distributed evenly across all driven
+    /// wheels.
+    pub fn set_engine_force(&mut self, force: f32) -> f32 {
+        self.engine_force
+            .set_value_and_mark_modified(force.max(0.0))
+    }
+
+    /// Returns maximum force of the engine.
+    pub fn engine_force(&self) -> f32 {
+        *self.engine_force
+    }
+
+    /// Sets maximum force (in Newtons) of the brakes. It is distributed evenly across all wheels.
+    pub fn set_brake_force(&mut self, force: f32) -> f32 {
+        self.brake_force.set_value_and_mark_modified(force.max(0.0))
+    }
+
+    /// Returns maximum force of the brakes.
+    pub fn brake_force(&self) -> f32 {
+        *self.brake_force
+    }
+
+    /// Sets maximum angle (in radians) of steerable wheels.
+    pub fn set_max_steer_angle(&mut self, angle: f32) -> f32 {
+        self.max_steer_angle
+            .set_value_and_mark_modified(angle.max(0.0))
+    }
+
+    /// Returns maximum angle of steerable wheels.
+    pub fn max_steer_angle(&self) -> f32 {
+        *self.max_steer_angle
+    }
+
+    /// Sets throttle input in `[-1.0; 1.0]` range. Negative values drive the vehicle backwards.
+    pub fn set_throttle(&mut self, throttle: f32) {
+        self.throttle = throttle.clamp(-1.0, 1.0);
+    }
+
+    /// Returns current throttle input.
+    pub fn throttle(&self) -> f32 {
+        self.throttle
+    }
+
+    /// Sets brake input in `[0.0; 1.0]` range.
+    pub fn set_brake(&mut self, brake: f32) {
+        self.brake = brake.clamp(0.0, 1.0);
+    }
+
+    /// Returns current brake input.
+    pub fn brake(&self) -> f32 {
+        self.brake
+    }
+
+    /// Sets steering input in `[-1.0; 1.0]` range. Positive values rotate steerable wheels
+    /// counterclockwise around the up vector of the chassis.
+    pub fn set_steer(&mut self, steer: f32) {
+        self.steer = steer.clamp(-1.0, 1.0);
+    }
+
+    /// Returns current steering input.
+    pub fn steer(&self) -> f32 {
+        self.steer
+    }
+
+    /// Returns a reference to a wheel with the given index.
+    pub fn wheel(&self, index: usize) -> Option<&Wheel> {
+        self.wheels.get(index)
+    }
+
+    /// Returns `true` if at least one wheel touches the ground.
+    pub fn is_grounded(&self) -> bool {
+        self.wheels.iter().any(|w| w.in_contact)
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for Vehicle {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Vehicle", |_| {
+                VehicleBuilder::new(BaseBuilder::new().with_name("Vehicle"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for Vehicle {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.local_bounding_box()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.base.world_bounding_box()
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, ctx: &mut UpdateContext) {
+        let chassis_handle = *self.chassis;
+
+        let chassis_state = ctx
+            .nodes
+            .try_get(chassis_handle)
+            .ok()
+            .filter(|chassis| {
+                self.is_globally_enabled() && chassis.body_type() == RigidBodyType::Dynamic
+            })
+            .map(|chassis| chassis.native.get())
+            .and_then(|native| {
+                Some((
+                    native,
+                    ctx.physics.body_state(native)?,
+                    ctx.physics.body_mass(native)?,
+                ))
+            });
+
+        let Some((native, state, mass)) = chassis_state else {
+            self.wheels
+                .get_value_mut_silent()
+                .iter_mut()
+                .for_each(Wheel::reset_contact);
+            return;
+        };
+
+        let dt = ctx.dt.max(f32::EPSILON);
+        let up = state.rotation * Vector3::y();
+        let look = state.rotation * Vector3::z();
+        let wheel_count = self.wheels.len().max(1) as f32;
+        let driven_count = self.wheels.iter().filter(|w| w.driven).count().max(1) as f32;
+        let drive_force = self.throttle * *self.engine_force / driven_count;
+        let brake_force = self.brake * *self.brake_force / wheel_count;
+        let steer_rotation =
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.steer * *self.max_steer_angle);
+
+        let mut forces = Vec::with_capacity(self.wheels.len());
+        let mut query_buffer = Vec::<Intersection>::default();
+        for wheel in self.wheels.get_value_mut_silent().iter_mut() {
+            let origin = state.position + state.rotation * wheel.connection_point;
+            let max_len = wheel.rest_length + wheel.radius;
+
+            ctx.physics.cast_ray(
+                RayCastOptions {
+                    ray_origin: Point3::from(origin),
+                    ray_direction: -up,
+                    max_len,
+                    groups: InteractionGroups::default(),
+                    sort_results: true,
+                },
+                &mut query_buffer,
+            );
+
+            // Colliders of the chassis must be ignored, otherwise the wheels will "stand" on the
+            // chassis itself.
+            let Some(hit) = query_buffer.iter().find(|i| {
+                ctx.nodes
+                    .try_borrow(i.collider.to_base())
+                    .is_ok_and(|c| c.parent() != chassis_handle.to_base())
+            }) else {
+                // The wheel is in the air.
+                wheel.reset_contact();
+                continue;
+            };
+
+            wheel.in_contact = true;
+            wheel.compression = (max_len - hit.toi).clamp(0.0, wheel.rest_length);
+            wheel.contact_point = hit.position.coords;
+
+            let velocity = ctx
+                .physics
+                .body_velocity_at_point(native, origin)
+                .unwrap_or_default();
+
+            // Suspension can only push the chassis, but never pull it to the ground.
+            let suspension_force =
+                (wheel.stiffness * wheel.compression - wheel.damping * velocity.dot(&up)).max(0.0);
+
+            let mut wheel_forward = if wheel.steerable {
+                state.rotation * (steer_rotation * Vector3::z())
+            } else {
+                look
+            };
+            // Project the forward vector on the ground plane, so the vehicle won't be pushed into
+            // the ground or thrown in the air on slopes.
+            let normal = hit.normal;
+            wheel_forward -= normal.scale(wheel_forward.dot(&normal));
+            let Some(wheel_forward) = wheel_forward.try_normalize(f32::EPSILON) else {
+                forces.push((up.scale(suspension_force), origin));
+                continue;
+            };
+            let wheel_side = normal.cross(&wheel_forward);
+
+            let mut force = up.scale(suspension_force);
+
+            if wheel.driven {
+                force += wheel_forward.scale(drive_force);
+            }
+
+            // Brakes and friction must not push the vehicle in the opposite direction, so their
+            // forces are limited by the force required to stop the wheel in a single step.
+            let forward_speed = velocity.dot(&wheel_forward);
+            let stopping_force = forward_speed.abs() * mass / (wheel_count * dt);
+            force -= wheel_forward.scale(forward_speed.signum() * brake_force.min(stopping_force));
+
+            let side_speed = velocity.dot(&wheel_side);
+            let max_friction_force = wheel.friction * suspension_force;
+            let friction_force =
+                (side_speed.abs() * mass / (wheel_count * dt)).min(max_friction_force);
+            force -= wheel_side.scale(side_speed.signum() * friction_force);
+
+            forces.push((force, origin));
+        }
+
+        if forces.is_empty() {
+            return;
+        }
+
+        if let Ok(chassis) = ctx.nodes.try_get_mut(chassis_handle) {
+            if self.throttle != 0.0 {
+                chassis.wake_up();
+            }
+            for (force, point) in forces {
+                chassis.apply_force_at_point(force, point);
+            }
+        }
+    }
+}
+
+/// Allows you to create a vehicle in a declarative manner.
+pub struct VehicleBuilder {
+    base_builder: BaseBuilder,
+    chassis: Handle<RigidBody>,
+    wheels: Vec<Wheel>,
+    engine_force: f32,
+    brake_force: f32,
+    max_steer_angle: f32,
+}
+
+impl VehicleBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            chassis: Default::default(),
+            wheels: Default::default(),
+            engine_force: 4000.0,
+            brake_force: 8000.0,
+            max_steer_angle: 35.0f32.to_radians(),
+        }
+    }
+
+    /// Sets desired chassis of the vehicle.
+    pub fn with_chassis(mut self, chassis: Handle<RigidBody>) -> Self {
+        self.chassis = chassis;
+        self
+    }
+
+    /// Sets desired set of wheels of the vehicle.
+    pub fn with_wheels(mut self, wheels: Vec<Wheel>) -> Self {
+        self.wheels = wheels;
+        self
+    }
+
+    /// Sets desired maximum force of the engine.
+    pub fn with_engine_force(mut self, force: f32) -> Self {
+        self.engine_force = force;
+        self
+    }
+
+    /// Sets desired maximum force of the brakes.
+    pub fn with_brake_force(mut self, force: f32) -> Self {
+        self.brake_force = force;
+        self
+    }
+
+    /// Sets desired maximum angle of steerable wheels.
+    pub fn with_max_steer_angle(mut self, angle: f32) -> Self {
+        self.max_steer_angle = angle;
+        self
+    }
+
+    /// Creates new vehicle node.
+    pub fn build_vehicle(self) -> Vehicle {
+        Vehicle {
+            base: self.base_builder.build_base(),
+            chassis: self.chassis.into(),
+            wheels: self.wheels.into(),
+            engine_force: self.engine_force.into(),
+            brake_force: self.brake_force.into(),
+            max_steer_angle: self.max_steer_angle.into(),
+            throttle: 0.0,
+            brake: 0.0,
+            steer: 0.0,
+        }
+    }
+
+    /// Creates new vehicle node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_vehicle())
+    }
+
+    /// Creates new instance of vehicle node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<Vehicle> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::{
+            algebra::{Vector2, Vector3},
+            pool::Handle,
+        },
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::{RigidBody, RigidBodyBuilder, RigidBodyType},
+            transform::TransformBuilder,
+            vehicle::{Vehicle, VehicleBuilder, Wheel},
+        },
+    };
+
+    // Creates a 400 kg vehicle on a flat ground and lets it settle down on the suspension.
+    fn make_settled_vehicle(graph: &mut Graph) -> (Handle<Vehicle>, Handle<RigidBody>) {
+        // Ground with its top side at y = 0.
+        RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, -0.5, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::cuboid(100.0, 0.5, 100.0))
+                        .build(graph),
+                ),
+        )
+        .with_body_type(RigidBodyType::Static)
+        .build(graph);
+
+        // 400 kg chassis.
+        let chassis = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 1.1, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::cuboid(1.0, 0.25, 2.0))
+                        .with_density(Some(100.0))
+                        .build(graph),
+                ),
+        )
+        .build(graph);
+
+        let wheels = [(-0.9, -1.5), (0.9, -1.5), (-0.9, 1.5), (0.9, 1.5)]
+            .into_iter()
+            .map(|(x, z)| {
+                Wheel::new(Vector3::new(x, -0.25, z))
+                    .with_rest_length(0.5)
+                    .with_radius(0.3)
+                    .with_stiffness(20000.0)
+                    .with_damping(2000.0)
+                    .with_steerable(z > 0.0)
+            })
+            .collect::<Vec<_>>();
+
+        let vehicle = VehicleBuilder::new(BaseBuilder::new())
+            .with_chassis(chassis)
+            .with_wheels(wheels)
+            .with_engine_force(4000.0)
+            .build(graph);
+
+        for _ in 0..180 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+
+        (vehicle, chassis)
+    }
+
+    #[test]
+    fn test_vehicle() {
+        let mut graph = Graph::new();
+        let (vehicle, chassis) = make_settled_vehicle(&mut graph);
+        let dt = 1.0 / 60.0;
+
+        let weight = 400.0 * 9.81;
+        let expected_compression = weight / (4.0 * 20000.0);
+        for wheel in graph[vehicle].wheels.iter() {
+            assert!(wheel.is_in_contact());
+            assert!((wheel.compression() - expected_compression).abs() < 0.01);
+        }
+        assert!(graph[chassis].lin_vel().norm() < 0.05);
+
+        let start = graph[chassis].global_position();
+
+        graph[vehicle].set_throttle(1.0);
+        for _ in 0..120 {
+            graph.update(Vector2::new(1.0, 1.0), dt, Default::default());
+        }
+
+        let offset = graph[chassis].global_position() - start;
+        assert!(offset.z > 1.0);
+        assert!(offset.x.abs() < 0.1);
+        assert!(graph[vehicle].is_grounded());
+    }
+
+    #[test]
+    fn test_sideways_friction_is_limited() {
+        let dt = 1.0 / 60.0;
+        let mut side_speeds = Vec::new();
+        for direction in [1.0, -1.0] {
+            let mut graph = Graph::new();
+            let (_, chassis) = make_settled_vehicle(&mut graph);
+
+            graph[chassis].set_lin_vel(Vector3::new(3.0 * direction, 0.0, 0.0));
+            for _ in 0..6 {
+                graph.update(Vector2::new(1.0, 1.0), dt, Default::default());
+            }
+
+            // Friction is limited by the grip of the wheels (about 1 g of deceleration), so the
+            // vehicle keeps sliding for a while, no matter in which direction.
+            let side_speed = graph[chassis].lin_vel().x * direction;
+            assert!(side_speed > 1.5 && side_speed < 3.0, "{side_speed}");
+            side_speeds.push(side_speed);
+
+            // But eventually stops without being pushed to the opposite direction.
+            for _ in 0..60 {
+                graph.update(Vector2::new(1.0, 1.0), dt, Default::default());
+            }
+            assert!(graph[chassis].lin_vel().x.abs() < 0.1);
+        }
+        assert!((side_speeds[0] - side_speeds[1]).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_airborne_vehicle() {
+        let mut graph = Graph::new();
+
+        let chassis = RigidBodyBuilder::new(
+            BaseBuilder::new().with_child(
+                ColliderBuilder::new(BaseBuilder::new())
+                    .with_shape(ColliderShape::cuboid(1.0, 0.25, 2.0))
+                    .build(&mut graph),
+            ),
+        )
+        .build(&mut graph);
+
+        let vehicle = VehicleBuilder::new(BaseBuilder::new())
+            .with_chassis(chassis)
+            .with_wheels(vec![Wheel::new(Vector3::new(0.0, -0.25, 0.0))])
+            .build(&mut graph);
+
+        graph[vehicle].set_throttle(1.0);
+        for _ in 0..60 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+
+        // No ground - no forces from the wheels, the vehicle is just falling.
+        assert!(!graph[vehicle].is_grounded());
+        assert_eq!(graph[vehicle].wheel(0).unwrap().compression(), 0.0);
+        assert!(graph[chassis].lin_vel().x.abs() < 1.0e-5);
+        assert!(graph[chassis].lin_vel().z.abs() < 1.0e-5);
+        assert!(graph[chassis].lin_vel().y < -5.0);
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Verlet integration of particles connected with distance constraints. It is shared by the
+//! simulated soft bodies, such as [`super::rope::Rope`] and [`super::cloth::Cloth`].
+
+use crate::{
+    core::{algebra::Vector3, math::aabb::AxisAlignedBoundingBox},
+    scene::mesh::surface::SurfaceResource,
+};
+
+/// A simulated point of a soft body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerletParticle {
+    /// Current position of the particle in world coordinates.
+    pub position: Vector3<f32>,
+    /// Position of the particle at the previous simulation step in world coordinates. Velocity
+    /// of the particle is implicitly defined by the difference between the current and the
+    /// previous positions.
+    pub previous_position: Vector3<f32>,
+    /// Pinned particles are not affected by forces and constraints, they just follow their pins.
+    pub pinned: bool,
+}
+
+impl VerletParticle {
+    pub(crate) fn new(position: Vector3<f32>) -> Self {
+        Self {
+            position,
+            previous_position: position,
+            pinned: false,
+        }
+    }
+
+    /// Moves the particle to the given position and drops its velocity.
+    pub(crate) fn teleport(&mut self, position: Vector3<f32>) {
+        self.position = position;
+        self.previous_position = position;
+    }
+}
+
+/// Keeps two particles at the given distance from each other.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct DistanceConstraint {
+    pub a: usize,
+    pub b: usize,
+    pub rest_length: f32,
+}
+
+impl DistanceConstraint {
+    /// Creates a constraint that keeps the current distance between the particles.
+    pub fn new(particles: &[VerletParticle], a: usize, b: usize) -> Self {
+        Self {
+            a,
+            b,
+            rest_length: particles[a]
+                .position
+                .metric_distance(&particles[b].position),
+        }
+    }
+
+    pub fn length(&self, particles: &[VerletParticle]) -> f32 {
+        particles[self.a]
+            .position
+            .metric_distance(&particles[self.b].position)
+    }
+
+    /// Moves both particles towards the given distance. Pinned particles are not moved, the whole
+    /// correction is applied to the other particle.
+    pub fn solve(&self, particles: &mut [VerletParticle], length: f32, stiffness: f32) {
+        let a = &particles[self.a];
+        let b = &particles[self.b];
+        let weight_a = if a.pinned { 0.0 } else { 1.0 };
+        let weight_b = if b.pinned { 0.0 } else { 1.0 };
+        let total_weight = weight_a + weight_b;
+        if total_weight == 0.0 {
+            return;
+        }
+
+        let delta = b.position - a.position;
+        let distance = delta.norm();
+        if distance <= f32::EPSILON {
+            return;
+        }
+
+        let correction = delta.scale(stiffness * (distance - length) / (distance * total_weight));
+        particles[self.a].position += correction.scale(weight_a);
+        particles[self.b].position -= correction.scale(weight_b);
+    }
+}
+
+/// Simulation state of a soft body.
+#[derive(Debug, Default)]
+pub(crate) struct VerletState {
+    pub particles: Vec<VerletParticle>,
+    pub constraints: Vec<DistanceConstraint>,
+    pub surface: Option<SurfaceResource>,
+    pub world_bounding_box: AxisAlignedBoundingBox,
+}
+
+// The simulation state must not be shared between copies of a node, otherwise the copies will
+// write to the same surface. A copy re-creates its state on the next update.
+impl Clone for VerletState {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl VerletState {
+    /// Integrates free particles using Verlet integration. `acceleration` provides acceleration of
+    /// a particle with the given index.
+    pub fn integrate<F>(&mut self, damping: f32, dt: f32, mut acceleration: F)
+    where
+        F: FnMut(usize) -> Vector3<f32>,
+    {
+        let velocity_scale = (1.0 - damping * dt).clamp(0.0, 1.0);
+        for (i, particle) in self.particles.iter_mut().enumerate() {
+            if particle.pinned {
+                continue;
+            }
+
+            let velocity = (particle.position - particle.previous_position).scale(velocity_scale);
+            particle.previous_position = particle.position;
+            particle.position += velocity + acceleration(i).scale(dt * dt);
+        }
+    }
+
+    /// Solves all the distance constraints iteratively, the more iterations, the closer the
+    /// particles to their rest distances.
+    pub fn solve_constraints(&mut self, stiffness: f32, iterations: u32) {
+        for _ in 0..iterations {
+            for constraint in self.constraints.iter() {
+                constraint.solve(&mut self.particles, constraint.rest_length, stiffness);
+            }
+        }
+    }
+
+    /// Recalculates world-space bounding box of the particles, inflated by the given margin.
+    pub fn update_bounding_box(&mut self, margin: f32) {
+        let mut aabb = AxisAlignedBoundingBox::default();
+        for particle in self.particles.iter() {
+            aabb.add_point(particle.position);
+        }
+        aabb.inflate(Vector3::repeat(margin));
+        self.world_bounding_box = aabb;
+    }
+}
@@ -0,0 +1,304 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Water volume applies buoyancy and drag to rigid bodies submerged in it. See [`WaterVolume`]
+//! docs for more info.
+
+use crate::{
+    core::{
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        reflect::prelude::*,
+        type_traits::prelude::*,
+        uuid::{uuid, Uuid},
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    scene::{
+        base::{Base, BaseBuilder},
+        graph::Graph,
+        node::{constructor::NodeConstructor, Node, NodeTrait, UpdateContext},
+    },
+};
+use fyrox_graph::{constructor::ConstructorProvider, SceneGraph};
+use std::ops::{Deref, DerefMut};
+
+/// Water volume is an axis-aligned box filled with fluid, that applies buoyant force and drag to
+/// every dynamic 3D rigid body submerged in it. The top side of the box is the surface of the
+/// fluid.
+///
+/// # Size and transformations
+///
+/// Water volume defines a unit cube, its actual size is defined by the global scale of the node.
+/// For example, a volume with scale (10.0, 2.0, 10.0) will be a pool with 10x10 meters surface
+/// and 2 meters depth. The volume is always axis-aligned, so a rotated volume is replaced with its
+/// bounding box.
+///
+/// # Physics
+///
+/// Buoyant force follows Archimedes' principle - it is equal to the weight of the displaced fluid
+/// and directed against the gravity. Displaced volume is approximated by the volume of the body
+/// colliders multiplied by the submerged fraction of the body's bounding box, so a body floats
+/// when its density is less than the density of the fluid. Linear and angular drag are scaled by
+/// the submerged fraction as well. Bodies that are fully above the surface are not affected.
+#[derive(Debug, Visit, Clone, Reflect, ComponentProvider)]
+#[reflect(derived_type = "Node")]
+pub struct WaterVolume {
+    base: Base,
+
+    #[reflect(min_value = 0.0, step = 10.0)]
+    #[reflect(setter = "set_density")]
+    density: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_linear_drag")]
+    linear_drag: InheritableVariable<f32>,
+
+    #[reflect(min_value = 0.0, step = 0.1)]
+    #[reflect(setter = "set_angular_drag")]
+    angular_drag: InheritableVariable<f32>,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        Self {
+            base: Default::default(),
+            density: 1000.0.into(),
+            linear_drag: 1.0.into(),
+            angular_drag: 1.0.into(),
+        }
+    }
+}
+
+impl Deref for WaterVolume {
+    type Target = Base;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl DerefMut for WaterVolume {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.base
+    }
+}
+
+impl TypeUuidProvider for WaterVolume {
+    fn type_uuid() -> Uuid {
+        uuid!("5d1a6f8e-3b2c-4e7a-9c41-2f8d0b6e7a13")
+    }
+}
+
+impl WaterVolume {
+    /// Sets density of the fluid (in kg/m³). Default is 1000.0, which is the density of the water.
+    pub fn set_density(&mut self, density: f32) -> f32 {
+        self.density.set_value_and_mark_modified(density.max(0.0))
+    }
+
+    /// Returns density of the fluid.
+    pub fn density(&self) -> f32 {
+        *self.density
+    }
+
+    /// Sets linear drag coefficient (in 1/s). It defines how fast linear velocity of a fully
+    /// submerged body decreases.
+    pub fn set_linear_drag(&mut self, drag: f32) -> f32 {
+        self.linear_drag.set_value_and_mark_modified(drag.max(0.0))
+    }
+
+    /// Returns linear drag coefficient.
+    pub fn linear_drag(&self) -> f32 {
+        *self.linear_drag
+    }
+
+    /// Sets angular drag coefficient (in 1/s). It defines how fast angular velocity of a fully
+    /// submerged body decreases.
+    pub fn set_angular_drag(&mut self, drag: f32) -> f32 {
+        self.angular_drag.set_value_and_mark_modified(drag.max(0.0))
+    }
+
+    /// Returns angular drag coefficient.
+    pub fn angular_drag(&self) -> f32 {
+        *self.angular_drag
+    }
+
+    /// Returns height of the surface of the fluid in world coordinates.
+    pub fn surface_height(&self) -> f32 {
+        self.world_bounding_box().max.y
+    }
+}
+
+impl ConstructorProvider<Node, Graph> for WaterVolume {
+    fn constructor() -> NodeConstructor {
+        NodeConstructor::new::<Self>()
+            .with_variant("Water Volume", |_| {
+                WaterVolumeBuilder::new(BaseBuilder::new().with_name("WaterVolume"))
+                    .build_node()
+                    .into()
+            })
+            .with_group("Physics")
+    }
+}
+
+impl NodeTrait for WaterVolume {
+    fn local_bounding_box(&self) -> AxisAlignedBoundingBox {
+        AxisAlignedBoundingBox::unit()
+    }
+
+    fn world_bounding_box(&self) -> AxisAlignedBoundingBox {
+        self.local_bounding_box()
+            .transform(&self.global_transform())
+    }
+
+    fn id(&self) -> Uuid {
+        Self::type_uuid()
+    }
+
+    fn update(&mut self, context: &mut UpdateContext) {
+        if !self.is_globally_enabled() {
+            return;
+        }
+
+        context.physics.apply_buoyancy(
+            &self.world_bounding_box(),
+            *self.density,
+            *self.linear_drag,
+            *self.angular_drag,
+            context.dt,
+        );
+    }
+}
+
+/// Allows you to create a water volume in a declarative manner.
+pub struct WaterVolumeBuilder {
+    base_builder: BaseBuilder,
+    density: f32,
+    linear_drag: f32,
+    angular_drag: f32,
+}
+
+impl WaterVolumeBuilder {
+    /// Creates a new instance of the builder.
+    pub fn new(base_builder: BaseBuilder) -> Self {
+        Self {
+            base_builder,
+            density: 1000.0,
+            linear_drag: 1.0,
+            angular_drag: 1.0,
+        }
+    }
+
+    /// Sets desired density of the fluid.
+    pub fn with_density(mut self, density: f32) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Sets desired linear drag coefficient.
+    pub fn with_linear_drag(mut self, drag: f32) -> Self {
+        self.linear_drag = drag;
+        self
+    }
+
+    /// Sets desired angular drag coefficient.
+    pub fn with_angular_drag(mut self, drag: f32) -> Self {
+        self.angular_drag = drag;
+        self
+    }
+
+    /// Creates new water volume node.
+    pub fn build_water_volume(self) -> WaterVolume {
+        WaterVolume {
+            base: self.base_builder.build_base(),
+            density: self.density.into(),
+            linear_drag: self.linear_drag.into(),
+            angular_drag: self.angular_drag.into(),
+        }
+    }
+
+    /// Creates new water volume node.
+    pub fn build_node(self) -> Node {
+        Node::new(self.build_water_volume())
+    }
+
+    /// Creates new instance of water volume node and puts it in the given graph.
+    pub fn build(self, graph: &mut Graph) -> Handle<WaterVolume> {
+        graph.add_node(self.build_node()).to_variant()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Vector2, Vector3},
+        scene::{
+            base::BaseBuilder,
+            collider::{ColliderBuilder, ColliderShape},
+            graph::Graph,
+            rigidbody::RigidBodyBuilder,
+            transform::TransformBuilder,
+            water::WaterVolumeBuilder,
+        },
+    };
+
+    #[test]
+    fn test_floating_equilibrium() {
+        let mut graph = Graph::new();
+
+        // 20x20 meters pool with the surface at y = 0.
+        let water = WaterVolumeBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, -5.0, 0.0))
+                    .with_local_scale(Vector3::new(20.0, 10.0, 20.0))
+                    .build(),
+            ),
+        )
+        .with_linear_drag(3.0)
+        .build(&mut graph);
+
+        // A ball with a half of the density of water.
+        let body = RigidBodyBuilder::new(
+            BaseBuilder::new()
+                .with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(0.0, 2.0, 0.0))
+                        .build(),
+                )
+                .with_child(
+                    ColliderBuilder::new(BaseBuilder::new())
+                        .with_shape(ColliderShape::ball(0.5))
+                        .with_density(Some(500.0))
+                        .build(&mut graph),
+                ),
+        )
+        .build(&mut graph);
+
+        for _ in 0..600 {
+            graph.update(Vector2::new(1.0, 1.0), 1.0 / 60.0, Default::default());
+        }
+
+        assert!(graph[water].surface_height().abs() < 1.0e-5);
+        // At equilibrium a half of the ball is submerged, so its center is at the surface level.
+        assert!(graph[body].global_position().y.abs() < 0.05);
+        assert!(graph[body].lin_vel().norm() < 0.05);
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Ambient occlusion baking. See [`AmbientOcclusionBaker`] docs for more info.
+
+use crate::{
+    core::{
+        algebra::{Matrix3, Point3, Vector3, Vector4},
+        math::Matrix4Ext,
+        pool::Handle,
+    },
+    graph::{SceneGraph, SceneGraphNode},
+    scene::{
+        graph::Graph,
+        mesh::{
+            buffer::{
+                VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
+                VertexFetchError, VertexReadTrait, VertexWriteTrait,
+            },
+            surface::SurfaceResource,
+            Mesh,
+        },
+        node::Node,
+    },
+    utils::tracer::{hemisphere_directions, TriangleTracer},
+};
+
+/// A set of parameters for ambient occlusion baking.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmbientOcclusionOptions {
+    /// Amount of rays that are traced for each point. The more rays, the smoother the result is,
+    /// but also the slower the baking is. Default value is 64.
+    pub ray_count: usize,
+    /// Maximum distance at which a surface is considered as an occluder. Surfaces that are farther
+    /// than this distance do not affect the point. Default value is 1.0.
+    pub max_distance: f32,
+}
+
+impl Default for AmbientOcclusionOptions {
+    fn default() -> Self {
+        Self {
+            ray_count: 64,
+            max_distance: 1.0,
+        }
+    }
+}
+
+/// Ambient occlusion baker calculates how much of the hemisphere above each point of a surface is
+/// blocked by nearby geometry. The result is in `[0; 1]` range, where `1.0` means that the point
+/// is fully exposed and `0.0` means that the point is fully occluded. Baked values can be written
+/// into vertex colors of meshes via [`AmbientOcclusionBaker::bake_vertex_colors`].
+///
+/// Occluders are closer surfaces contribute more to the occlusion, the contribution linearly fades
+/// out to zero at [`AmbientOcclusionOptions::max_distance`].
+pub struct AmbientOcclusionBaker {
+    tracer: TriangleTracer,
+    options: AmbientOcclusionOptions,
+}
+
+impl AmbientOcclusionBaker {
+    /// Creates a new baker without any occluders.
+    pub fn new(options: AmbientOcclusionOptions) -> Self {
+        Self {
+            tracer: TriangleTracer::new(),
+            options,
+        }
+    }
+
+    /// Creates a new baker and adds every enabled mesh of the graph, that passes the filter, as an
+    /// occluder. Make sure that global transforms of the graph are up to date.
+    pub fn from_graph<F>(graph: &Graph, options: AmbientOcclusionOptions, mut filter: F) -> Self
+    where
+        F: FnMut(Handle<Node>, &Node) -> bool,
+    {
+        let mut baker = Self::new(options);
+        for (handle, node) in graph.pair_iter() {
+            if !filter(handle, node) || !node.is_globally_enabled() {
+                continue;
+            }
+
+            if let Some(mesh) = node.cast::<Mesh>() {
+                let transform = mesh.global_transform();
+                for surface in mesh.surfaces() {
+                    let data = surface.data_ref();
+                    if !data.is_ok() {
+                        continue;
+                    }
+                    let data = data.data_ref();
+                    let positions = data
+                        .vertex_buffer
+                        .iter()
+                        .map(|view| {
+                            transform
+                                .transform_point(&Point3::from(
+                                    view.read_3_f32(VertexAttributeUsage::Position)
+                                        .unwrap_or_default(),
+                                ))
+                                .coords
+                        })
+                        .collect::<Vec<_>>();
+                    baker.add_triangles(
+                        data.geometry_buffer
+                            .iter()
+                            .map(|t| t.0.map(|i| positions[i as usize]))
+                            .collect(),
+                    );
+                }
+            }
+        }
+        baker
+    }
+
+    /// Adds a set of world-space triangles as an occluder.
+    pub fn add_triangles(&mut self, triangles: Vec<[Vector3<f32>; 3]>) {
+        self.tracer.add_mesh(triangles);
+    }
+
+    /// Returns current baking options.
+    pub fn options(&self) -> &AmbientOcclusionOptions {
+        &self.options
+    }
+
+    /// Calculates ambient occlusion factor at the given world-space point with the given normal.
+    /// Returns `1.0` if there are no occluders around the point.
+    pub fn ambient_occlusion_at(&self, position: Vector3<f32>, normal: Vector3<f32>) -> f32 {
+        let Some(normal) = normal.try_normalize(f32::EPSILON) else {
+            return 1.0;
+        };
+        if self.options.ray_count == 0 || self.options.max_distance <= 0.0 {
+            return 1.0;
+        }
+
+        // Offset the origin a bit to prevent self-intersections.
+        let origin = position + normal.scale(1.0e-3);
+        let occlusion = hemisphere_directions(normal, self.options.ray_count)
+            .into_iter()
+            .filter_map(|direction| {
+                self.tracer
+                    .trace(origin, direction, self.options.max_distance)
+            })
+            .map(|hit| 1.0 - hit.distance / self.options.max_distance)
+            .sum::<f32>();
+
+        (1.0 - occlusion / self.options.ray_count as f32).clamp(0.0, 1.0)
+    }
+
+    /// Bakes ambient occlusion into vertex colors of every surface of the given mesh. Ambient
+    /// occlusion is written into RGB channels of the color attribute, alpha channel is kept as is.
+    /// If a surface does not have the color attribute, it will be added. Every surface of the mesh
+    /// receives its own copy of surface data, because the baked values depend on the location of
+    /// the mesh. Make sure that global transforms of the graph are up to date.
+    pub fn bake_vertex_colors(
+        &self,
+        graph: &mut Graph,
+        mesh: Handle<Mesh>,
+    ) -> Result<(), VertexFetchError> {
+        let Ok(mesh) = graph.try_get_mut(mesh) else {
+            return Ok(());
+        };
+
+        let transform = mesh.global_transform();
+        let normal_matrix = transform
+            .basis()
+            .try_inverse()
+            .map(|m| m.transpose())
+            .unwrap_or_else(Matrix3::identity);
+
+        for surface in mesh.surfaces_mut() {
+            let data = surface.data_ref();
+            if !data.is_ok() {
+                continue;
+            }
+            let mut data = (*data.data_ref()).clone();
+
+            let ambient_occlusion = data
+                .vertex_buffer
+                .iter()
+                .map(|view| {
+                    let position = transform
+                        .transform_point(&Point3::from(
+                            view.read_3_f32(VertexAttributeUsage::Position)?,
+                        ))
+                        .coords;
+                    let normal = normal_matrix * view.read_3_f32(VertexAttributeUsage::Normal)?;
+                    Ok(self.ambient_occlusion_at(position, normal))
+                })
+                .collect::<Result<Vec<_>, VertexFetchError>>()?;
+
+            {
+                let mut vertex_buffer = data.vertex_buffer.modify();
+                if !vertex_buffer.has_attribute(VertexAttributeUsage::Color) {
+                    let shader_location = vertex_buffer.find_free_shader_location();
+                    vertex_buffer
+                        .add_attribute(
+                            VertexAttributeDescriptor {
+                                usage: VertexAttributeUsage::Color,
+                                data_type: VertexAttributeDataType::U8,
+                                size: 4,
+                                divisor: 0,
+                                shader_location,
+                                normalized: true,
+                            },
+                            [255u8; 4],
+                        )
+                        .unwrap();
+                }
+                for (mut view, ao) in vertex_buffer.iter_mut().zip(ambient_occlusion) {
+                    let alpha = view.read_4_u8(VertexAttributeUsage::Color)?.w;
+                    let value = (ao * 255.0) as u8;
+                    view.write_4_u8(
+                        VertexAttributeUsage::Color,
+                        Vector4::new(value, value, value, alpha),
+                    )?;
+                }
+            }
+
+            surface.set_data(SurfaceResource::new_embedded(data));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector3},
+        scene::{
+            base::BaseBuilder,
+            graph::Graph,
+            mesh::{
+                buffer::{VertexAttributeUsage, VertexReadTrait},
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                MeshBuilder,
+            },
+        },
+        utils::ao::{AmbientOcclusionBaker, AmbientOcclusionOptions},
+    };
+    use fyrox_graph::SceneGraph;
+
+    #[test]
+    fn test_corner_is_darker_than_exposed_point() {
+        let mut baker = AmbientOcclusionBaker::new(AmbientOcclusionOptions::default());
+        // Floor in XZ plane and a wall in YZ plane at x = -1.
+        baker.add_triangles(vec![
+            [
+                Vector3::new(-1.0, 0.0, -1.0),
+                Vector3::new(-1.0, 0.0, 1.0),
+                Vector3::new(2.0, 0.0, 1.0),
+            ],
+            [
+                Vector3::new(-1.0, 0.0, -1.0),
+                Vector3::new(2.0, 0.0, 1.0),
+                Vector3::new(2.0, 0.0, -1.0),
+            ],
+        ]);
+        baker.add_triangles(vec![
+            [
+                Vector3::new(-1.0, 0.0, -1.0),
+                Vector3::new(-1.0, 2.0, -1.0),
+                Vector3::new(-1.0, 2.0, 1.0),
+            ],
+            [
+                Vector3::new(-1.0, 0.0, -1.0),
+                Vector3::new(-1.0, 2.0, 1.0),
+                Vector3::new(-1.0, 0.0, 1.0),
+            ],
+        ]);
+
+        let corner = baker.ambient_occlusion_at(Vector3::new(-0.9, 0.0, 0.0), Vector3::y());
+        let exposed = baker.ambient_occlusion_at(Vector3::new(1.5, 0.0, 0.0), Vector3::y());
+        assert!(corner < exposed);
+        assert_eq!(exposed, 1.0);
+    }
+
+    #[test]
+    fn test_open_scene_is_not_occluded() {
+        let mut graph = Graph::new();
+        let mesh = MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_quad(&Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut graph);
+        graph.update_hierarchical_data();
+
+        let baker = AmbientOcclusionBaker::from_graph(&graph, Default::default(), |_, _| true);
+        baker.bake_vertex_colors(&mut graph, mesh).unwrap();
+
+        let data = graph.try_get(mesh).unwrap().surfaces()[0].data();
+        let data = data.data_ref();
+        for view in data.vertex_buffer.iter() {
+            let color = view.read_4_u8(VertexAttributeUsage::Color).unwrap();
+            assert_eq!(color.x, 255);
+        }
+    }
+}
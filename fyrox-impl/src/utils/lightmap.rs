@@ -23,6 +23,19 @@
 //! # Performance
 //!
 //! This is CPU lightmapper, its performance is linear with core count of your CPU.
+//!
+//! # Indirect lighting
+//!
+//! Direct lighting is calculated first, then the light reflected between surfaces is gathered for
+//! the given amount of bounces. Every bounce traces a set of rays in the hemisphere above each
+//! texel and accumulates the light of the previous bounce at the hit points. All surfaces are
+//! treated as diffuse with constant albedo.
+//!
+//! # Caching
+//!
+//! Every lightmap stores a hash of its input data (geometry, lights and generation settings), which
+//! could be used to skip generation if nothing has changed since the last time. See
+//! [`Lightmap::is_up_to_date`] docs for more info.
 
 #![forbid(unsafe_code)]
 
@@ -30,7 +43,10 @@ use crate::{
     asset::manager::{ResourceManager, ResourceRegistrationError},
     core::{
         algebra::{Matrix3, Matrix4, Point3, Vector2, Vector3},
-        math::{Matrix4Ext, TriangleDefinition},
+        math::{
+            barycentric_is_inside, barycentric_to_world, get_barycentric_coords, Matrix4Ext,
+            TriangleDefinition,
+        },
         pool::Handle,
         reflect::prelude::*,
         visitor::{prelude::*, BinaryBlob},
@@ -50,9 +66,13 @@ use crate::{
         node::Node,
         Scene,
     },
-    utils::{uvgen, uvgen::SurfaceDataPatch},
+    utils::{
+        tracer::{hemisphere_directions, TriangleTracer},
+        uvgen,
+        uvgen::SurfaceDataPatch,
+    },
 };
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHasher};
 use fyrox_core::{ok_or_continue, warn, Uuid};
 use fyrox_graph::SceneGraphNode;
 use fyrox_material::Material;
@@ -63,6 +83,7 @@ use lightmap::light::{
 use rayon::prelude::*;
 use std::{
     fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
     ops::Deref,
     path::Path,
     sync::{
@@ -186,6 +207,12 @@ pub struct Lightmap {
     // We don't need to inspect patches, because they contain no useful data.
     #[reflect(hidden)]
     pub patches: FxHashMap<u64, SurfaceDataPatchWrapper>,
+
+    /// Hash of the input data and the settings that were used to generate the lightmap. Zero means
+    /// that the hash is unknown. See [`Self::is_up_to_date`] for more info.
+    #[visit(optional)] // Backward compatibility
+    #[reflect(hidden)]
+    pub input_hash: u64,
 }
 
 impl Default for Lightmap {
@@ -195,17 +222,108 @@ impl Default for Lightmap {
             second_tex_coord_location: 6,
             map: Default::default(),
             patches: Default::default(),
+            input_hash: 0,
         }
     }
 }
 
+/// Albedo of all surfaces, it defines how much of the incoming light is reflected by a surface.
+const INDIRECT_ALBEDO: f32 = 0.5;
+
+/// Amount of rays that are used to gather indirect light for a single texel.
+const INDIRECT_SAMPLE_COUNT: usize = 32;
+
 struct Instance {
     owner: Handle<Node>,
     source_data: SurfaceResource,
     data: Option<lightmap::input::Mesh>,
+    geometry: BakeGeometry,
     transform: Matrix4<f32>,
 }
 
+#[derive(Clone, Default)]
+struct BakeVertex {
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+    tex_coord: Vector2<f32>,
+}
+
+/// World-space copy of instance geometry, it is used to trace rays for indirect lighting.
+#[derive(Default)]
+struct BakeGeometry {
+    vertices: Vec<BakeVertex>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl BakeGeometry {
+    fn world_triangles(&self) -> Vec<[Vector3<f32>; 3]> {
+        self.triangles
+            .iter()
+            .map(|t| t.map(|i| self.vertices[i as usize].position))
+            .collect()
+    }
+}
+
+/// A texel of a lightmap that is covered by some triangle.
+struct Texel {
+    index: usize,
+    position: Vector3<f32>,
+    normal: Vector3<f32>,
+}
+
+/// Light stored in a lightmap in linear floating-point form.
+#[derive(Clone)]
+struct LightBuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vector3<f32>>,
+}
+
+impl LightBuffer {
+    fn from_rgb8(width: usize, height: usize, bytes: &[u8]) -> Self {
+        Self {
+            width,
+            height,
+            pixels: bytes
+                .chunks_exact(3)
+                .map(|rgb| Vector3::new(rgb[0], rgb[1], rgb[2]).cast::<f32>() / 255.0)
+                .collect(),
+        }
+    }
+
+    fn zeroed(&self) -> Self {
+        Self {
+            width: self.width,
+            height: self.height,
+            pixels: vec![Vector3::default(); self.pixels.len()],
+        }
+    }
+
+    fn sample(&self, tex_coord: Vector2<f32>) -> Vector3<f32> {
+        if self.pixels.is_empty() {
+            return Vector3::default();
+        }
+        let x = ((tex_coord.x * self.width as f32).max(0.0) as usize).min(self.width - 1);
+        let y = ((tex_coord.y * self.height as f32).max(0.0) as usize).min(self.height - 1);
+        self.pixels[y * self.width + x]
+    }
+
+    fn into_texture(self) -> Texture {
+        Texture::from_bytes(
+            TextureKind::Rectangle {
+                width: self.width as u32,
+                height: self.height as u32,
+            },
+            TexturePixelKind::RGB8,
+            self.pixels
+                .iter()
+                .flat_map(|p| [p.x, p.y, p.z].map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8))
+                .collect(),
+        )
+        .unwrap()
+    }
+}
+
 /// Small helper that allows you stop lightmap generation in any time.
 #[derive(Clone, Default)]
 pub struct CancellationToken(pub Arc<AtomicBool>);
@@ -239,6 +357,8 @@ pub enum ProgressStage {
     GeometryCaching = 2,
     /// Actual lightmap generation.
     CalculatingLight = 3,
+    /// Gathering light reflected between surfaces.
+    CalculatingIndirectLight = 4,
 }
 
 impl Display for ProgressStage {
@@ -256,6 +376,9 @@ impl Display for ProgressStage {
             ProgressStage::CalculatingLight => {
                 write!(f, "Calculating Light")
             }
+            ProgressStage::CalculatingIndirectLight => {
+                write!(f, "Calculating Indirect Light")
+            }
         }
     }
 }
@@ -287,6 +410,7 @@ impl ProgressData {
             1 => ProgressStage::UvGeneration,
             2 => ProgressStage::GeometryCaching,
             3 => ProgressStage::CalculatingLight,
+            4 => ProgressStage::CalculatingIndirectLight,
             _ => unreachable!(),
         }
     }
@@ -368,6 +492,79 @@ pub struct LightmapInputData {
     data_set: FxHashMap<u64, SurfaceResource>,
     instances: Vec<Instance>,
     lights: FxHashMap<Handle<Node>, LightDefinition>,
+    content_hash: u64,
+}
+
+fn hash_floats<H: Hasher>(hasher: &mut H, values: &[f32]) {
+    for value in values {
+        hasher.write_u32(value.to_bits());
+    }
+}
+
+fn hash_light_definition<H: Hasher>(hasher: &mut H, definition: &LightDefinition) {
+    match definition {
+        LightDefinition::Directional(directional) => {
+            hasher.write_u8(0);
+            hash_floats(hasher, &[directional.intensity]);
+            hash_floats(hasher, directional.direction.as_slice());
+            hash_floats(hasher, directional.color.as_slice());
+        }
+        LightDefinition::Spot(spot) => {
+            hasher.write_u8(1);
+            hash_floats(
+                hasher,
+                &[spot.intensity, spot.edge0, spot.edge1, spot.distance],
+            );
+            hash_floats(hasher, spot.direction.as_slice());
+            hash_floats(hasher, spot.position.as_slice());
+            hash_floats(hasher, spot.color.as_slice());
+        }
+        LightDefinition::Point(point) => {
+            hasher.write_u8(2);
+            hash_floats(hasher, &[point.intensity, point.radius]);
+            hash_floats(hasher, point.position.as_slice());
+            hash_floats(hasher, point.color.as_slice());
+        }
+    }
+}
+
+/// Calculates a hash of the triangles of the given surface data. The hash does not depend on the
+/// order of triangles and vertices, so it is not affected by the changes made by the lightmapper
+/// (duplicated vertices and second texture coordinates). This allows to check whether the geometry
+/// has changed since the lightmap was generated.
+fn geometry_hash(data: &SurfaceData) -> u64 {
+    let vertex_hashes = data
+        .vertex_buffer
+        .iter()
+        .map(|view| {
+            let mut hasher = FxHasher::default();
+            let position = view
+                .read_3_f32(VertexAttributeUsage::Position)
+                .unwrap_or_default();
+            hash_floats(&mut hasher, position.as_slice());
+            hasher.finish()
+        })
+        .collect::<Vec<_>>();
+
+    data.geometry_buffer.iter().fold(0u64, |hash, triangle| {
+        let mut triangle_hashes = triangle
+            .0
+            .map(|i| vertex_hashes.get(i as usize).copied().unwrap_or_default());
+        triangle_hashes.sort_unstable();
+        let mut hasher = FxHasher::default();
+        triangle_hashes.hash(&mut hasher);
+        hash.wrapping_add(hasher.finish())
+    })
+}
+
+/// Combines hash of input data with the generation settings.
+fn bake_hash(input_hash: u64, texels_per_unit: u32, uv_spacing: f32, bounce_count: u32) -> u64 {
+    let mut hasher = FxHasher::default();
+    hasher.write_u64(input_hash);
+    hasher.write_u32(texels_per_unit);
+    hasher.write_u32(uv_spacing.to_bits());
+    hasher.write_u32(bounce_count);
+    hasher.finish()
 }
 
 fn has_lightmap_texture_binding_point(
@@ -544,19 +741,53 @@ impl LightmapInputData {
                         transform: global_transform,
                         // Calculated down below.
                         data: None,
+                        geometry: Default::default(),
                     });
                 }
             }
         }
 
+        // Nodes that do not contribute to the lightmap do not affect the hash, which means that
+        // the lightmap will be considered up-to-date if such nodes are moved.
+        let mut hasher = FxHasher::default();
+        texture_name.hash(&mut hasher);
+        hasher.write_u8(second_tex_coord_location);
+        let mut geometry_hashes = FxHashMap::default();
+        for instance in instances.iter() {
+            let data_hash = *geometry_hashes
+                .entry(instance.source_data.key())
+                .or_insert_with(|| geometry_hash(&instance.source_data.data_ref()));
+            instance.owner.hash(&mut hasher);
+            hasher.write_u64(data_hash);
+            hash_floats(&mut hasher, instance.transform.as_slice());
+        }
+        let mut light_hashes = lights
+            .iter()
+            .map(|(handle, definition)| {
+                let mut hasher = FxHasher::default();
+                handle.hash(&mut hasher);
+                hash_light_definition(&mut hasher, definition);
+                hasher.finish()
+            })
+            .collect::<Vec<_>>();
+        light_hashes.sort_unstable();
+        light_hashes.hash(&mut hasher);
+
         Ok(Self {
             texture_name: texture_name.to_string(),
             second_tex_coord_location,
             data_set,
             instances,
             lights,
+            content_hash: hasher.finish(),
         })
     }
+
+    /// Returns a hash of the input data. It includes geometry of every surface that will receive a
+    /// lightmap, global transforms of their meshes and parameters of every light source.
+    pub fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
 }
 
 impl Lightmap {
@@ -572,6 +803,22 @@ impl Lightmap {
         Ok(lightmap)
     }
 
+    /// Checks whether the lightmap was generated using the same input data and generation
+    /// settings, which means that there is no need to generate it again. The lightmap becomes
+    /// outdated when any of the contributing meshes or lights is moved or modified, nodes that
+    /// do not contribute to the lightmap do not affect it.
+    pub fn is_up_to_date(
+        &self,
+        data: &LightmapInputData,
+        texels_per_unit: u32,
+        uv_spacing: f32,
+        bounce_count: u32,
+    ) -> bool {
+        self.input_hash != 0
+            && self.input_hash
+                == bake_hash(data.content_hash, texels_per_unit, uv_spacing, bounce_count)
+    }
+
     /// Saves a light map to the given file. Keep in mind, that the textures should be saved separately first, via
     /// [`Self::save_textures`] method.
     pub fn save<P: AsRef<Path>>(&mut self, path: P) -> VisitResult {
@@ -589,12 +836,35 @@ impl Lightmap {
     /// lightmap will be generated, but also it will be slow to generate.
     /// `progress_indicator` allows you to get info about current progress.
     /// `cancellation_token` allows you to stop generation in any time.
+    ///
+    /// Only direct lighting is baked, use [`Self::new_with_bounces`] to bake indirect lighting too.
     pub fn new(
         data: LightmapInputData,
         texels_per_unit: u32,
         uv_spacing: f32,
         cancellation_token: CancellationToken,
         progress_indicator: ProgressIndicator,
+    ) -> Result<Self, LightmapGenerationError> {
+        Self::new_with_bounces(
+            data,
+            texels_per_unit,
+            uv_spacing,
+            0,
+            cancellation_token,
+            progress_indicator,
+        )
+    }
+
+    /// The same as [`Self::new`], but also bakes indirect lighting. `bounce_count` defines how
+    /// many times the light is reflected between surfaces, zero means that only direct lighting
+    /// will be baked.
+    pub fn new_with_bounces(
+        data: LightmapInputData,
+        texels_per_unit: u32,
+        uv_spacing: f32,
+        bounce_count: u32,
+        cancellation_token: CancellationToken,
+        progress_indicator: ProgressIndicator,
     ) -> Result<Self, LightmapGenerationError> {
         let LightmapInputData {
             texture_name,
@@ -602,6 +872,7 @@ impl Lightmap {
             data_set,
             mut instances,
             lights,
+            content_hash,
         } = data;
 
         progress_indicator.set_stage(ProgressStage::UvGeneration, data_set.len() as u32);
@@ -674,17 +945,27 @@ impl Lightmap {
                         })
                         .collect::<Vec<_>>();
 
-                    instance.data = Some(
-                        lightmap::input::Mesh::new(
-                            world_vertices,
-                            data.geometry_buffer
-                                .triangles_ref()
-                                .iter()
-                                .map(|t| t.0)
-                                .collect(),
-                        )
-                        .unwrap(),
-                    );
+                    let triangles = data
+                        .geometry_buffer
+                        .triangles_ref()
+                        .iter()
+                        .map(|t| t.0)
+                        .collect::<Vec<_>>();
+
+                    instance.geometry = BakeGeometry {
+                        vertices: world_vertices
+                            .iter()
+                            .map(|v| BakeVertex {
+                                position: v.world_position,
+                                normal: v.world_normal,
+                                tex_coord: v.second_tex_coord,
+                            })
+                            .collect(),
+                        triangles: triangles.clone(),
+                    };
+
+                    instance.data =
+                        Some(lightmap::input::Mesh::new(world_vertices, triangles).unwrap());
 
                     progress_indicator.advance_progress();
 
@@ -695,28 +976,52 @@ impl Lightmap {
 
         progress_indicator.set_stage(ProgressStage::CalculatingLight, instances.len() as u32);
 
-        let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
         let meshes = instances
             .iter_mut()
             .filter_map(|i| i.data.take())
             .collect::<Vec<_>>();
         let light_definitions = lights.values().cloned().collect::<Vec<_>>();
-        for (mesh, instance) in meshes.iter().zip(instances.iter()) {
+        let mut buffers = Vec::with_capacity(meshes.len());
+        for mesh in meshes.iter() {
             if cancellation_token.is_cancelled() {
                 return Err(LightmapGenerationError::Cancelled);
             }
 
-            let lightmap = generate_lightmap(mesh, &meshes, &light_definitions, texels_per_unit);
+            buffers.push(generate_lightmap(
+                mesh,
+                &meshes,
+                &light_definitions,
+                texels_per_unit,
+            ));
+
+            progress_indicator.advance_progress();
+        }
+
+        if bounce_count > 0 {
+            progress_indicator.set_stage(
+                ProgressStage::CalculatingIndirectLight,
+                bounce_count * instances.len() as u32,
+            );
+
+            add_indirect_light(
+                &instances,
+                &mut buffers,
+                bounce_count,
+                &cancellation_token,
+                &progress_indicator,
+            )?;
+        }
+
+        let mut map: FxHashMap<Handle<Node>, Vec<LightmapEntry>> = FxHashMap::default();
+        for (buffer, instance) in buffers.into_iter().zip(instances.iter()) {
             map.entry(instance.owner).or_default().push(LightmapEntry {
                 texture: Some(TextureResource::new_ok(
                     Uuid::new_v4(),
                     Default::default(),
-                    lightmap,
+                    buffer.into_texture(),
                 )),
                 lights: lights.keys().cloned().collect(),
             });
-
-            progress_indicator.advance_progress();
         }
 
         Ok(Self {
@@ -724,6 +1029,7 @@ impl Lightmap {
             second_tex_coord_location,
             map,
             patches,
+            input_hash: bake_hash(content_hash, texels_per_unit, uv_spacing, bounce_count),
         })
     }
 
@@ -759,31 +1065,149 @@ impl Lightmap {
 ///
 /// This method is has linear complexity - the more complex mesh you pass, the more
 /// time it will take. Required time increases drastically if you enable shadows and
-/// global illumination (TODO), because in this case your data will be raytraced.
+/// global illumination, because in this case your data will be raytraced.
 fn generate_lightmap(
     mesh: &lightmap::input::Mesh,
     other_meshes: &[lightmap::input::Mesh],
     lights: &[LightDefinition],
     texels_per_unit: u32,
-) -> Texture {
+) -> LightBuffer {
     let map = lightmap::LightMap::new(mesh, other_meshes, lights, texels_per_unit as usize);
 
-    Texture::from_bytes(
-        TextureKind::Rectangle {
-            width: map.width as u32,
-            height: map.height as u32,
-        },
-        TexturePixelKind::RGB8,
-        map.pixels,
-    )
-    .unwrap()
+    LightBuffer::from_rgb8(map.width, map.height, &map.pixels)
+}
+
+/// Finds every texel covered by the triangles of the given geometry (in the second texture
+/// coordinates space) and calculates its world-space position and normal.
+fn rasterize_texels(geometry: &BakeGeometry, width: usize, height: usize) -> Vec<Texel> {
+    let mut texels = Vec::new();
+    let mut covered = vec![false; width * height];
+    let scale = Vector2::new(width as f32, height as f32);
+    for triangle in geometry.triangles.iter() {
+        let [a, b, c] = triangle.map(|i| &geometry.vertices[i as usize]);
+        let [ta, tb, tc] = [a, b, c].map(|v| v.tex_coord.component_mul(&scale).push(0.0));
+
+        let min = ta.inf(&tb).inf(&tc);
+        let max = ta.sup(&tb).sup(&tc);
+        let x_range =
+            (min.x.floor().max(0.0) as usize)..(max.x.ceil().max(0.0) as usize).min(width);
+        let y_range =
+            (min.y.floor().max(0.0) as usize)..(max.y.ceil().max(0.0) as usize).min(height);
+
+        for y in y_range {
+            for x in x_range.clone() {
+                let index = y * width + x;
+                if covered[index] {
+                    continue;
+                }
+
+                let center = Vector3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+                let bary = get_barycentric_coords(&center, &ta, &tb, &tc);
+                if !barycentric_is_inside(bary) {
+                    continue;
+                }
+
+                covered[index] = true;
+                texels.push(Texel {
+                    index,
+                    position: barycentric_to_world(bary, a.position, b.position, c.position),
+                    normal: barycentric_to_world(bary, a.normal, b.normal, c.normal)
+                        .try_normalize(f32::EPSILON)
+                        .unwrap_or_else(Vector3::y),
+                });
+            }
+        }
+    }
+    texels
+}
+
+/// Gathers light reflected between surfaces and adds it to the given direct lighting.
+fn add_indirect_light(
+    instances: &[Instance],
+    buffers: &mut [LightBuffer],
+    bounce_count: u32,
+    cancellation_token: &CancellationToken,
+    progress_indicator: &ProgressIndicator,
+) -> Result<(), LightmapGenerationError> {
+    let mut tracer = TriangleTracer::new();
+    let mut max_distance = 0.0f32;
+    for instance in instances {
+        let triangles = instance.geometry.world_triangles();
+        for vertex in instance.geometry.vertices.iter() {
+            max_distance = max_distance.max(vertex.position.norm());
+        }
+        tracer.add_mesh(triangles);
+    }
+    // Every point of the scene is within this distance from any other point.
+    let max_distance = 2.0 * max_distance + 1.0;
+
+    let texels = instances
+        .par_iter()
+        .zip(buffers.par_iter())
+        .map(|(instance, buffer)| rasterize_texels(&instance.geometry, buffer.width, buffer.height))
+        .collect::<Vec<_>>();
+
+    // Light of the previous bounce, it is direct lighting for the first one.
+    let mut previous = buffers.to_vec();
+    for _ in 0..bounce_count {
+        let bounce = texels
+            .par_iter()
+            .zip(previous.par_iter())
+            .map(|(texels, buffer)| {
+                if cancellation_token.is_cancelled() {
+                    return Err(LightmapGenerationError::Cancelled);
+                }
+
+                let mut result = buffer.zeroed();
+                for texel in texels {
+                    let origin = texel.position + texel.normal.scale(1.0e-3);
+                    let mut gathered = Vector3::default();
+                    for direction in hemisphere_directions(texel.normal, INDIRECT_SAMPLE_COUNT) {
+                        let Some(hit) = tracer.trace(origin, direction, max_distance) else {
+                            continue;
+                        };
+                        let geometry = &instances[hit.mesh].geometry;
+                        let [a, b, c] = geometry.triangles[hit.triangle]
+                            .map(|i| &geometry.vertices[i as usize]);
+                        let normal =
+                            barycentric_to_world(hit.barycentric, a.normal, b.normal, c.normal);
+                        // Back faces do not reflect any light.
+                        if normal.dot(&direction) >= 0.0 {
+                            continue;
+                        }
+                        let tex_coord = a.tex_coord.scale(hit.barycentric.0)
+                            + b.tex_coord.scale(hit.barycentric.1)
+                            + c.tex_coord.scale(hit.barycentric.2);
+                        gathered += previous[hit.mesh].sample(tex_coord);
+                    }
+                    // Directions are cosine-weighted, so the average is the irradiance estimate.
+                    result.pixels[texel.index] =
+                        gathered.scale(INDIRECT_ALBEDO / INDIRECT_SAMPLE_COUNT as f32);
+                }
+
+                progress_indicator.advance_progress();
+
+                Ok(result)
+            })
+            .collect::<Result<Vec<_>, LightmapGenerationError>>()?;
+
+        for (buffer, bounce) in buffers.iter_mut().zip(bounce.iter()) {
+            for (pixel, indirect) in buffer.pixels.iter_mut().zip(bounce.pixels.iter()) {
+                *pixel += *indirect;
+            }
+        }
+
+        previous = bounce;
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
     use crate::{
         asset::ResourceData,
-        core::algebra::{Matrix4, Vector3},
+        core::algebra::{Matrix4, UnitQuaternion, Vector3},
         scene::{
             base::BaseBuilder,
             light::{point::PointLightBuilder, BaseLightBuilder},
@@ -792,11 +1216,14 @@ mod test {
                 surface::{SurfaceBuilder, SurfaceData},
                 MeshBuilder,
             },
+            node::Node,
+            pivot::PivotBuilder,
             transform::TransformBuilder,
             Scene,
         },
         utils::lightmap::{Lightmap, LightmapInputData},
     };
+    use fyrox_core::pool::Handle;
     use fyrox_resource::untyped::ResourceKind;
     use std::path::Path;
     use uuid::Uuid;
@@ -853,4 +1280,133 @@ mod test {
             }
         }
     }
+
+    fn average_brightness(lightmap: &Lightmap, node: Handle<Node>) -> f32 {
+        let texture = lightmap.map[&node][0].texture.clone().unwrap();
+        let data = texture.data_ref();
+        let bytes = data.data();
+        bytes.iter().map(|b| *b as f32).sum::<f32>() / bytes.len() as f32
+    }
+
+    #[test]
+    fn test_facing_surface_is_brighter() {
+        let mut scene = Scene::new();
+
+        let mut make_quad = |angle: f32, x: f32| {
+            MeshBuilder::new(
+                BaseBuilder::new().with_local_transform(
+                    TransformBuilder::new()
+                        .with_local_position(Vector3::new(x, 0.0, 0.0))
+                        .with_local_rotation(UnitQuaternion::from_axis_angle(
+                            &Vector3::x_axis(),
+                            angle.to_radians(),
+                        ))
+                        .build(),
+                ),
+            )
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_quad(&Matrix4::identity()),
+            ))
+            .build()])
+            .build(&mut scene.graph)
+        };
+
+        // Faces +Y (towards the light) and -Y (away from the light) respectively.
+        let facing = make_quad(90.0, -1.0);
+        let away = make_quad(-90.0, 1.0);
+
+        PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(4.0)
+        .build(&mut scene.graph);
+
+        scene.graph.update_hierarchical_data();
+
+        let data = LightmapInputData::from_scene(
+            "lightmapTexture",
+            6,
+            &scene,
+            |_, _| true,
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+
+        let lightmap =
+            Lightmap::new_with_bounces(data, 16, 0.005, 1, Default::default(), Default::default())
+                .unwrap();
+
+        assert!(average_brightness(&lightmap, facing) > average_brightness(&lightmap, away));
+    }
+
+    #[test]
+    fn test_lightmap_cache_invalidation() {
+        let mut scene = Scene::new();
+
+        MeshBuilder::new(BaseBuilder::new())
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_quad(&Matrix4::new_rotation(Vector3::new(
+                    90.0f32.to_radians(),
+                    0.0,
+                    0.0,
+                ))),
+            ))
+            .build()])
+            .build(&mut scene.graph);
+
+        let light = PointLightBuilder::new(BaseLightBuilder::new(
+            BaseBuilder::new().with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(Vector3::new(0.0, 1.0, 0.0))
+                    .build(),
+            ),
+        ))
+        .with_radius(4.0)
+        .build(&mut scene.graph);
+
+        let dynamic = PivotBuilder::new(BaseBuilder::new()).build(&mut scene.graph);
+
+        scene.graph.update_hierarchical_data();
+
+        let input = |scene: &Scene| {
+            LightmapInputData::from_scene(
+                "lightmapTexture",
+                6,
+                scene,
+                |_, _| true,
+                Default::default(),
+                Default::default(),
+            )
+            .unwrap()
+        };
+
+        let lightmap = Lightmap::new(
+            input(&scene),
+            16,
+            0.005,
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        assert!(lightmap.is_up_to_date(&input(&scene), 16, 0.005, 0));
+        // Different settings require rebaking.
+        assert!(!lightmap.is_up_to_date(&input(&scene), 32, 0.005, 0));
+
+        scene.graph[dynamic]
+            .local_transform_mut()
+            .set_position(Vector3::new(5.0, 0.0, 0.0));
+        scene.graph.update_hierarchical_data();
+        assert!(lightmap.is_up_to_date(&input(&scene), 16, 0.005, 0));
+
+        scene.graph[light]
+            .local_transform_mut()
+            .set_position(Vector3::new(0.0, 2.0, 0.0));
+        scene.graph.update_hierarchical_data();
+        assert!(!lightmap.is_up_to_date(&input(&scene), 16, 0.005, 0));
+    }
 }
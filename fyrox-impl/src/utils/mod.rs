@@ -22,11 +22,16 @@
 
 //! Utilities module provides set of commonly used algorithms.
 
+pub mod ao;
 pub mod astar;
 pub mod behavior;
 pub mod lightmap;
 pub mod navmesh;
+pub mod random;
 pub mod raw_mesh;
+pub mod scene_converter;
+pub mod simplify;
+pub mod tracer;
 pub mod uvgen;
 
 use crate::{
@@ -0,0 +1,332 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Deterministic scene randomization. See [`SceneRandom`] docs for more info.
+
+#![warn(missing_docs)]
+
+use crate::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+    },
+    rand::{Rng, SeedableRng},
+    resource::model::{ModelResource, ModelResourceExtension},
+    scene::{node::Node, transform::Transform, Scene},
+};
+use rand_chacha::ChaCha8Rng;
+
+/// A set of ranges that is used to produce random local transforms. Rotation ranges are defined
+/// by Euler angles (in radians) for each axis. Scale is always uniform.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransformRanges {
+    /// Minimal offset of a node from its base position.
+    pub position_min: Vector3<f32>,
+    /// Maximal offset of a node from its base position.
+    pub position_max: Vector3<f32>,
+    /// Minimal Euler angles (in radians).
+    pub rotation_min: Vector3<f32>,
+    /// Maximal Euler angles (in radians).
+    pub rotation_max: Vector3<f32>,
+    /// Minimal uniform scale.
+    pub scale_min: f32,
+    /// Maximal uniform scale.
+    pub scale_max: f32,
+}
+
+impl Default for TransformRanges {
+    fn default() -> Self {
+        Self {
+            position_min: Vector3::default(),
+            position_max: Vector3::default(),
+            rotation_min: Vector3::default(),
+            rotation_max: Vector3::default(),
+            scale_min: 1.0,
+            scale_max: 1.0,
+        }
+    }
+}
+
+impl TransformRanges {
+    /// Creates ranges that only randomize rotation around the Y axis in full circle. This is the
+    /// most common case for scattering vegetation, rocks, etc.
+    pub fn random_yaw() -> Self {
+        Self {
+            rotation_max: Vector3::new(0.0, std::f32::consts::TAU, 0.0),
+            ..Default::default()
+        }
+    }
+}
+
+/// Seeded pseudo-random number generator for procedural scene layouts. The same seed always
+/// reproduces the same sequence of values, and so the same layout, which makes it suitable for
+/// level generation that must be identical across runs or across clients in multiplayer games.
+///
+/// The generator is [`ChaCha8Rng`], its stream of numbers does not depend on the platform and never
+/// changes between releases (unlike `StdRng`, which could switch to another algorithm at any time).
+/// Values derived from the stream (ranges, for example) stay the same within one major version of
+/// `rand`.
+///
+/// ## Example
+///
+/// ```rust
+/// # use fyrox_impl::{
+/// #     core::{algebra::Vector3, math::aabb::AxisAlignedBoundingBox},
+/// #     resource::model::ModelResource,
+/// #     scene::Scene,
+/// #     utils::random::{SceneRandom, TransformRanges},
+/// # };
+/// fn scatter_trees(tree: &ModelResource, scene: &mut Scene) {
+///     let mut random = SceneRandom::new(42);
+///     let region = AxisAlignedBoundingBox::from_min_max(
+///         Vector3::new(-50.0, 0.0, -50.0),
+///         Vector3::new(50.0, 0.0, 50.0),
+///     );
+///     random.scatter(tree, scene, &region, 100, &TransformRanges::random_yaw());
+/// }
+/// ```
+pub struct SceneRandom {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl SceneRandom {
+    /// Creates a new generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Returns the seed that was used to create the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Resets the generator to its initial state, so the same sequence of values will be produced
+    /// again.
+    pub fn reset(&mut self) {
+        self.rng = ChaCha8Rng::seed_from_u64(self.seed);
+    }
+
+    /// Returns a reference to the inner generator, it could be used to generate values of any
+    /// type supported by `rand`.
+    pub fn rng_mut(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
+    /// Returns a random number in `[min; max)` range. Returns `min` if the range is empty.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        if max > min {
+            self.rng.gen_range(min..max)
+        } else {
+            min
+        }
+    }
+
+    /// Returns a random vector with each component in its respective range.
+    pub fn vector(&mut self, min: Vector3<f32>, max: Vector3<f32>) -> Vector3<f32> {
+        Vector3::new(
+            self.range(min.x, max.x),
+            self.range(min.y, max.y),
+            self.range(min.z, max.z),
+        )
+    }
+
+    /// Returns a random point inside the given bounding box.
+    pub fn point_in_aabb(&mut self, aabb: &AxisAlignedBoundingBox) -> Vector3<f32> {
+        self.vector(aabb.min, aabb.max)
+    }
+
+    /// Produces a random local transform with the given base position.
+    pub fn transform(&mut self, position: Vector3<f32>, ranges: &TransformRanges) -> Transform {
+        let mut transform = Transform::identity();
+        self.randomize_transform(&mut transform, position, ranges);
+        transform
+    }
+
+    /// Randomizes the given transform using the given base position and the ranges.
+    pub fn randomize_transform(
+        &mut self,
+        transform: &mut Transform,
+        position: Vector3<f32>,
+        ranges: &TransformRanges,
+    ) {
+        let offset = self.vector(ranges.position_min, ranges.position_max);
+        let angles = self.vector(ranges.rotation_min, ranges.rotation_max);
+        let scale = self.range(ranges.scale_min, ranges.scale_max);
+        transform
+            .set_position(position + offset)
+            .set_rotation(UnitQuaternion::from_euler_angles(
+                angles.x, angles.y, angles.z,
+            ))
+            .set_scale(Vector3::repeat(scale));
+    }
+
+    /// Picks a random variant using the weight of each variant. Variants with non-positive
+    /// weights are never picked. Returns `None` if there is nothing to pick from.
+    pub fn pick_weighted<'a, V>(&mut self, variants: &'a [(V, f32)]) -> Option<&'a V> {
+        let total = variants
+            .iter()
+            .map(|(_, weight)| weight.max(0.0))
+            .sum::<f32>();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut point = self.range(0.0, total);
+        let mut last = None;
+        for (variant, weight) in variants {
+            if *weight <= 0.0 {
+                continue;
+            }
+            if point < *weight {
+                return Some(variant);
+            }
+            point -= *weight;
+            last = Some(variant);
+        }
+        // Guards against floating-point imprecision at the end of the range.
+        last
+    }
+
+    /// Instantiates the given prefab `count` times at random points inside the region. Each
+    /// instance gets a random transform using the given ranges. Returns handles of the instances
+    /// in the order of creation.
+    pub fn scatter(
+        &mut self,
+        prefab: &ModelResource,
+        scene: &mut Scene,
+        region: &AxisAlignedBoundingBox,
+        count: usize,
+        ranges: &TransformRanges,
+    ) -> Vec<Handle<Node>> {
+        (0..count)
+            .map(|_| {
+                let position = self.point_in_aabb(region);
+                let transform = self.transform(position, ranges);
+                prefab
+                    .begin_instantiation(scene)
+                    .with_transform(transform)
+                    .finish()
+            })
+            .collect()
+    }
+
+    /// Same as [`Self::scatter`], but picks a prefab for each instance from the weighted set of
+    /// variants.
+    pub fn scatter_weighted(
+        &mut self,
+        variants: &[(ModelResource, f32)],
+        scene: &mut Scene,
+        region: &AxisAlignedBoundingBox,
+        count: usize,
+        ranges: &TransformRanges,
+    ) -> Vec<Handle<Node>> {
+        let mut instances = Vec::with_capacity(count);
+        for _ in 0..count {
+            let Some(prefab) = self.pick_weighted(variants) else {
+                break;
+            };
+            let position = self.point_in_aabb(region);
+            let transform = self.transform(position, ranges);
+            instances.push(
+                prefab
+                    .begin_instantiation(scene)
+                    .with_transform(transform)
+                    .finish(),
+            );
+        }
+        instances
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::Resource,
+        core::{algebra::Vector3, math::aabb::AxisAlignedBoundingBox},
+        graph::NodeMapping,
+        resource::model::{Model, ModelResource},
+        scene::{base::BaseBuilder, pivot::PivotBuilder, Scene},
+        utils::random::{SceneRandom, TransformRanges},
+    };
+
+    fn make_prefab() -> ModelResource {
+        let mut scene = Scene::new();
+        PivotBuilder::new(BaseBuilder::new().with_name("Rock")).build(&mut scene.graph);
+        Resource::new_embedded(Model::new(NodeMapping::UseNames, scene))
+    }
+
+    #[test]
+    fn test_scatter_is_deterministic() {
+        let prefab = make_prefab();
+        let region = AxisAlignedBoundingBox::from_min_max(
+            Vector3::new(-10.0, 0.0, -10.0),
+            Vector3::new(10.0, 2.0, 10.0),
+        );
+        let ranges = TransformRanges {
+            scale_min: 0.5,
+            scale_max: 2.0,
+            ..TransformRanges::random_yaw()
+        };
+
+        let mut layouts = Vec::new();
+        for _ in 0..2 {
+            let mut scene = Scene::new();
+            let mut random = SceneRandom::new(1234);
+            let instances = random.scatter(&prefab, &mut scene, &region, 10, &ranges);
+            assert_eq!(instances.len(), 10);
+            layouts.push(
+                instances
+                    .iter()
+                    .map(|h| {
+                        let transform = scene.graph[*h].local_transform();
+                        (
+                            **transform.position(),
+                            **transform.rotation(),
+                            **transform.scale(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        assert_eq!(layouts[0], layouts[1]);
+
+        // Instances must be spread, not stacked at the same point.
+        assert_ne!(layouts[0][0].0, layouts[0][1].0);
+        for (position, _, scale) in layouts[0].iter() {
+            assert!(scale.x >= 0.5 && scale.x <= 2.0);
+            assert!(position.x >= -10.0 && position.x <= 10.0);
+            assert!(position.z >= -10.0 && position.z <= 10.0);
+        }
+    }
+
+    #[test]
+    fn test_pick_weighted() {
+        let mut random = SceneRandom::new(7);
+        let variants = [("never", 0.0), ("always", 1.0)];
+        for _ in 0..32 {
+            assert_eq!(random.pick_weighted(&variants), Some(&"always"));
+        }
+        assert_eq!(random.pick_weighted::<u32>(&[]), None);
+    }
+}
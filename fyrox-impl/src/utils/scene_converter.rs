@@ -0,0 +1,636 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//! Headless scene conversion pipeline. It loads a scene, applies a set of optimizations to it
+//! (mesh simplification, light map baking, texture compression) and writes the optimized scene
+//! back. It does not require a window or a graphics context, which makes it suitable for build
+//! pipelines. See [`convert_scene_file`] docs for more info.
+
+use crate::{
+    asset::manager::{ResourceManager, ResourceRegistrationError},
+    core::visitor::prelude::*,
+    engine::SerializationContext,
+    fxhash::{FxHashMap, FxHashSet},
+    graph::SceneGraph,
+    material::{Material, MaterialResource},
+    resource::texture::{CompressionOptions, Texture, TextureResource},
+    scene::{
+        mesh::{surface::SurfaceResource, Mesh},
+        Scene, SceneLoader,
+    },
+    utils::{
+        lightmap::{
+            CancellationToken, Lightmap, LightmapGenerationError, LightmapInputData,
+            ProgressIndicator,
+        },
+        simplify::simplify,
+    },
+};
+use fyrox_core::dyntype::DynTypeConstructorContainer;
+use std::{
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Light map baking options of the scene converter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightmapBakeOptions {
+    /// Name of the texture property in the materials, that will be used to bind the light maps.
+    pub texture_name: String,
+    /// Location of the second texture coordinates attribute in the vertex buffers.
+    pub second_tex_coord_location: u8,
+    /// Amount of texels per unit of surface area.
+    pub texels_per_unit: u32,
+    /// Relative spacing between UV elements.
+    pub uv_spacing: f32,
+    /// Amount of light bounces for global illumination.
+    pub bounce_count: u32,
+    /// Optional path to a directory, where the light map textures will be saved. If not set, the
+    /// textures will be embedded in the output scene.
+    pub texture_path: Option<PathBuf>,
+}
+
+impl Default for LightmapBakeOptions {
+    fn default() -> Self {
+        Self {
+            texture_name: "lightmapTexture".to_string(),
+            second_tex_coord_location: 6,
+            texels_per_unit: 64,
+            uv_spacing: 0.005,
+            bounce_count: 1,
+            texture_path: None,
+        }
+    }
+}
+
+/// A set of operations, that will be applied to a scene by the converter. Operations are applied
+/// in the following order: mesh simplification, light map baking, texture compression. Default
+/// config does nothing.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SceneConverterConfig {
+    /// Optional ratio of triangles to keep in every mesh of the scene (`0.5` - keep a half of the
+    /// triangles and so on). See [`simplify`] for more info.
+    pub simplification: Option<f32>,
+    /// Optional light map baking options.
+    pub lightmap: Option<LightmapBakeOptions>,
+    /// Compression that will be applied to the textures of the scene. Only embedded textures
+    /// (including baked light maps) could be compressed, compression of the external textures is
+    /// defined by their import options.
+    pub texture_compression: CompressionOptions,
+}
+
+/// Statistics of a single conversion step.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StepStatistics {
+    /// Mesh simplification statistics.
+    Simplification {
+        /// Amount of unique surfaces that were simplified.
+        surfaces: usize,
+        /// Total amount of triangles of all meshes before simplification.
+        triangles_before: usize,
+        /// Total amount of triangles of all meshes after simplification.
+        triangles_after: usize,
+    },
+    /// Light map baking statistics.
+    LightmapBake {
+        /// Amount of surfaces that received a light map.
+        surfaces: usize,
+        /// Total size of the light map textures (in bytes).
+        texture_bytes: usize,
+    },
+    /// Texture compression statistics.
+    TextureCompression {
+        /// Amount of textures that were compressed.
+        compressed: usize,
+        /// Amount of textures that were left as is (external, already compressed or unsupported).
+        skipped: usize,
+        /// Total size of the compressed textures before compression (in bytes).
+        bytes_before: usize,
+        /// Total size of the compressed textures after compression (in bytes).
+        bytes_after: usize,
+    },
+}
+
+/// A report of a single conversion step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StepReport {
+    /// Statistics of the step.
+    pub statistics: StepStatistics,
+    /// Time that was spent on the step.
+    pub duration: Duration,
+}
+
+/// A report of the entire conversion.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConversionReport {
+    /// Reports of every performed step in execution order.
+    pub steps: Vec<StepReport>,
+}
+
+impl Display for ConversionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in self.steps.iter() {
+            match step.statistics {
+                StepStatistics::Simplification {
+                    surfaces,
+                    triangles_before,
+                    triangles_after,
+                } => write!(
+                    f,
+                    "Simplification: {surfaces} surfaces, {triangles_before} -> {triangles_after} triangles"
+                )?,
+                StepStatistics::LightmapBake {
+                    surfaces,
+                    texture_bytes,
+                } => write!(
+                    f,
+                    "Light map bake: {surfaces} surfaces, {texture_bytes} bytes of textures"
+                )?,
+                StepStatistics::TextureCompression {
+                    compressed,
+                    skipped,
+                    bytes_before,
+                    bytes_after,
+                } => write!(
+                    f,
+                    "Texture compression: {compressed} compressed, {skipped} skipped, \
+                    {bytes_before} -> {bytes_after} bytes"
+                )?,
+            }
+            writeln!(f, " ({:.3} s)", step.duration.as_secs_f32())?;
+        }
+        Ok(())
+    }
+}
+
+/// An error that may occur during scene conversion.
+#[derive(Debug)]
+pub enum SceneConversionError {
+    /// Unable to load or save a scene.
+    Visit(VisitError),
+    /// Light map generation has failed.
+    Lightmap(LightmapGenerationError),
+    /// Unable to register baked light map textures.
+    LightmapRegistration(ResourceRegistrationError),
+    /// Unable to apply the light map to the scene.
+    LightmapApply(&'static str),
+}
+
+impl std::error::Error for SceneConversionError {}
+
+impl Display for SceneConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneConversionError::Visit(err) => write!(f, "Scene serialization error: {err:?}"),
+            SceneConversionError::Lightmap(err) => {
+                write!(f, "Light map generation error: {err}")
+            }
+            SceneConversionError::LightmapRegistration(err) => {
+                write!(f, "Unable to register light map textures: {err}")
+            }
+            SceneConversionError::LightmapApply(err) => {
+                write!(f, "Unable to apply the light map: {err}")
+            }
+        }
+    }
+}
+
+impl From<VisitError> for SceneConversionError {
+    fn from(err: VisitError) -> Self {
+        Self::Visit(err)
+    }
+}
+
+impl From<LightmapGenerationError> for SceneConversionError {
+    fn from(err: LightmapGenerationError) -> Self {
+        Self::Lightmap(err)
+    }
+}
+
+impl From<ResourceRegistrationError> for SceneConversionError {
+    fn from(err: ResourceRegistrationError) -> Self {
+        Self::LightmapRegistration(err)
+    }
+}
+
+fn count_triangles(scene: &Scene) -> usize {
+    scene
+        .graph
+        .linear_iter()
+        .filter_map(|node| node.cast::<Mesh>())
+        .flat_map(|mesh| mesh.surfaces())
+        .filter_map(|surface| {
+            let data = surface.data_ref().data_ref();
+            data.as_loaded_ref()
+                .map(|data| data.geometry_buffer.triangles_ref().len())
+        })
+        .sum()
+}
+
+fn simplify_meshes(scene: &mut Scene, ratio: f32) -> StepStatistics {
+    let triangles_before = count_triangles(scene);
+
+    // Surface data could be shared across multiple meshes, simplify it only once and keep it shared.
+    let mut simplified = FxHashMap::<SurfaceResource, SurfaceResource>::default();
+    for node in scene.graph.linear_iter_mut() {
+        let Some(mesh) = node.cast_mut::<Mesh>() else {
+            continue;
+        };
+        for surface in mesh.surfaces_mut() {
+            let source = surface.data();
+            let new_data = if let Some(new_data) = simplified.get(&source) {
+                new_data.clone()
+            } else {
+                let Some(data) = source
+                    .data_ref()
+                    .as_loaded_ref()
+                    .map(|data| simplify(data, ratio))
+                else {
+                    continue;
+                };
+                let new_data = SurfaceResource::new_embedded(data);
+                simplified.insert(source, new_data.clone());
+                new_data
+            };
+            surface.set_data(new_data);
+        }
+    }
+
+    StepStatistics::Simplification {
+        surfaces: simplified.len(),
+        triangles_before,
+        triangles_after: count_triangles(scene),
+    }
+}
+
+fn bake_lightmap(
+    scene: &mut Scene,
+    options: &LightmapBakeOptions,
+    resource_manager: &ResourceManager,
+) -> Result<StepStatistics, SceneConversionError> {
+    let input = LightmapInputData::from_scene(
+        &options.texture_name,
+        options.second_tex_coord_location,
+        scene,
+        |_, _| true,
+        CancellationToken::new(),
+        ProgressIndicator::new(),
+    )?;
+    let lightmap = Lightmap::new_with_bounces(
+        input,
+        options.texels_per_unit,
+        options.uv_spacing,
+        options.bounce_count,
+        CancellationToken::new(),
+        ProgressIndicator::new(),
+    )?;
+    if let Some(path) = options.texture_path.as_ref() {
+        lightmap.save_textures(path, resource_manager.clone())?;
+    }
+
+    let entries = lightmap.map.values().flatten();
+    let surfaces = entries.clone().count();
+    let texture_bytes = entries
+        .filter_map(|entry| entry.texture.as_ref())
+        .filter_map(|texture| texture.data_ref().as_loaded_ref().map(|t| t.data().len()))
+        .sum();
+
+    scene
+        .graph
+        .set_lightmap(Some(lightmap))
+        .map_err(SceneConversionError::LightmapApply)?;
+
+    Ok(StepStatistics::LightmapBake {
+        surfaces,
+        texture_bytes,
+    })
+}
+
+fn collect_textures(scene: &Scene) -> FxHashSet<TextureResource> {
+    let mut textures = FxHashSet::default();
+
+    let add_material = |material: &MaterialResource, textures: &mut FxHashSet<_>| {
+        if let Some(material) = material.data_ref().as_loaded_ref() {
+            textures.extend(material.bindings().values().filter_map(|b| b.as_texture()));
+        }
+    };
+
+    for resource in scene.collect_used_resources() {
+        if let Some(texture) = resource.try_cast::<Texture>() {
+            textures.insert(texture);
+        } else if let Some(material) = resource.try_cast::<Material>() {
+            add_material(&material, &mut textures);
+        }
+    }
+
+    // Surfaces with light maps have unique copies of materials, that are not always visible
+    // as resources of the scene.
+    for mesh in scene.graph.linear_iter().filter_map(|n| n.cast::<Mesh>()) {
+        for surface in mesh.surfaces() {
+            add_material(surface.material(), &mut textures);
+        }
+    }
+
+    if let Some(lightmap) = scene.graph.lightmap() {
+        textures.extend(
+            lightmap
+                .map
+                .values()
+                .flatten()
+                .filter_map(|entry| entry.texture.clone()),
+        );
+    }
+
+    textures
+}
+
+fn compress_textures(scene: &Scene, compression: CompressionOptions) -> StepStatistics {
+    let mut compressed = 0;
+    let mut skipped = 0;
+    let mut bytes_before = 0;
+    let mut bytes_after = 0;
+
+    for texture in collect_textures(scene) {
+        let is_embedded = texture.kind().is_embedded();
+        let mut state = texture.data_ref();
+        match state.as_loaded_mut() {
+            Some(data) if is_embedded => {
+                let size = data.data().len();
+                if data.compress(compression) {
+                    compressed += 1;
+                    bytes_before += size;
+                    bytes_after += data.data().len();
+                } else {
+                    skipped += 1;
+                }
+            }
+            _ => skipped += 1,
+        }
+    }
+
+    StepStatistics::TextureCompression {
+        compressed,
+        skipped,
+        bytes_before,
+        bytes_after,
+    }
+}
+
+fn measure<T>(func: impl FnOnce() -> T) -> (T, Duration) {
+    let begin = Instant::now();
+    let result = func();
+    (result, begin.elapsed())
+}
+
+/// Applies every operation from the given config to the scene and returns a report with statistics
+/// of every performed step. The resource manager is used to register baked light map textures
+/// (see [`LightmapBakeOptions::texture_path`]).
+pub fn convert_scene(
+    scene: &mut Scene,
+    config: &SceneConverterConfig,
+    resource_manager: &ResourceManager,
+) -> Result<ConversionReport, SceneConversionError> {
+    let mut report = ConversionReport::default();
+
+    if let Some(ratio) = config.simplification {
+        let (statistics, duration) = measure(|| simplify_meshes(scene, ratio));
+        report.steps.push(StepReport {
+            statistics,
+            duration,
+        });
+    }
+
+    if let Some(options) = config.lightmap.as_ref() {
+        let (statistics, duration) = measure(|| bake_lightmap(scene, options, resource_manager));
+        report.steps.push(StepReport {
+            statistics: statistics?,
+            duration,
+        });
+    }
+
+    if config.texture_compression != CompressionOptions::NoCompression {
+        let (statistics, duration) =
+            measure(|| compress_textures(scene, config.texture_compression));
+        report.steps.push(StepReport {
+            statistics,
+            duration,
+        });
+    }
+
+    Ok(report)
+}
+
+/// Loads a scene from the `source` file, applies every operation from the given config to it (see
+/// [`convert_scene`]) and saves the optimized scene to the `dest` file in binary format. This is
+/// the entry point for command-line tools, it does not require a window or a graphics context. The
+/// resource manager must have the loaders registered (see
+/// [`crate::engine::initialize_resource_manager_loaders`]) and the resource registry must be loaded.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// # use fyrox_impl::{
+/// #     asset::{io::FsResourceIo, manager::ResourceManager},
+/// #     core::{futures::executor::block_on, task::TaskPool},
+/// #     engine::{initialize_resource_manager_loaders, SerializationContext},
+/// #     utils::scene_converter::{convert_scene_file, SceneConverterConfig},
+/// # };
+/// # use std::{path::Path, sync::Arc};
+/// let serialization_context = Arc::new(SerializationContext::new());
+/// let resource_manager =
+///     ResourceManager::new(Arc::new(FsResourceIo), Arc::new(TaskPool::new()));
+/// initialize_resource_manager_loaders(
+///     &resource_manager,
+///     serialization_context.clone(),
+///     Default::default(),
+///     Default::default(),
+/// );
+/// resource_manager.update_or_load_registry();
+///
+/// let config = SceneConverterConfig {
+///     simplification: Some(0.5),
+///     ..Default::default()
+/// };
+///
+/// let report = block_on(convert_scene_file(
+///     Path::new("data/level.rgs"),
+///     Path::new("data/level_optimized.rgs"),
+///     &config,
+///     serialization_context,
+///     Default::default(),
+///     resource_manager,
+/// ))
+/// .unwrap();
+///
+/// println!("{report}");
+/// ```
+pub async fn convert_scene_file(
+    source: &Path,
+    dest: &Path,
+    config: &SceneConverterConfig,
+    serialization_context: Arc<SerializationContext>,
+    dyn_type_constructors: Arc<DynTypeConstructorContainer>,
+    resource_manager: ResourceManager,
+) -> Result<ConversionReport, SceneConversionError> {
+    let io = resource_manager.resource_io();
+    let (loader, _) = SceneLoader::from_file(
+        source,
+        &*io,
+        serialization_context,
+        dyn_type_constructors,
+        resource_manager.clone(),
+    )
+    .await?;
+    let mut scene = loader.finish().await;
+
+    let report = convert_scene(&mut scene, config, &resource_manager)?;
+
+    let mut visitor = Visitor::new();
+    scene.save("Scene", &mut visitor)?;
+    visitor.save_binary_to_file(dest)?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        asset::{io::FsResourceIo, manager::ResourceManager, untyped::ResourceKind},
+        core::{algebra::Matrix4, futures::executor::block_on, uuid::Uuid, visitor::Visitor},
+        engine::{self, SerializationContext},
+        graph::SceneGraph,
+        material::{Material, MaterialResource},
+        resource::texture::{
+            CompressionOptions, TextureKind, TexturePixelKind, TextureResource,
+            TextureResourceExtension,
+        },
+        scene::{
+            base::BaseBuilder,
+            mesh::{
+                surface::{SurfaceBuilder, SurfaceData, SurfaceResource},
+                Mesh, MeshBuilder,
+            },
+            Scene, SceneLoader,
+        },
+        utils::scene_converter::{convert_scene, SceneConverterConfig, StepStatistics},
+    };
+    use std::sync::Arc;
+
+    #[test]
+    fn test_convert_scene() {
+        let texture = TextureResource::from_bytes(
+            Uuid::new_v4(),
+            TextureKind::Rectangle {
+                width: 8,
+                height: 8,
+            },
+            TexturePixelKind::RGBA8,
+            vec![127; 8 * 8 * 4],
+            ResourceKind::Embedded,
+        )
+        .unwrap();
+        let mut material = Material::standard();
+        material.bind("diffuseTexture", texture);
+
+        let mut scene = Scene::new();
+        MeshBuilder::new(BaseBuilder::new().with_name("Sphere"))
+            .with_surfaces(vec![SurfaceBuilder::new(SurfaceResource::new_embedded(
+                SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity()),
+            ))
+            .with_material(MaterialResource::new_embedded(material))
+            .build()])
+            .build(&mut scene.graph);
+
+        let resource_manager =
+            ResourceManager::new(Arc::new(FsResourceIo), Arc::new(Default::default()));
+        engine::initialize_resource_manager_loaders(
+            &resource_manager,
+            Arc::new(SerializationContext::new()),
+            Default::default(),
+            Default::default(),
+        );
+
+        let config = SceneConverterConfig {
+            simplification: Some(0.5),
+            texture_compression: CompressionOptions::Speed,
+            ..Default::default()
+        };
+        let report = convert_scene(&mut scene, &config, &resource_manager).unwrap();
+        assert_eq!(report.steps.len(), 2);
+
+        let StepStatistics::Simplification {
+            surfaces,
+            triangles_before,
+            triangles_after,
+        } = report.steps[0].statistics
+        else {
+            panic!("Simplification must be the first step!")
+        };
+        assert_eq!(surfaces, 1);
+        assert!(triangles_after < triangles_before);
+
+        let StepStatistics::TextureCompression {
+            compressed,
+            bytes_before,
+            bytes_after,
+            ..
+        } = report.steps[1].statistics
+        else {
+            panic!("Texture compression must be the second step!")
+        };
+        assert_eq!(compressed, 1);
+        assert!(bytes_after < bytes_before);
+
+        let mut visitor = Visitor::new();
+        scene.save("Scene", &mut visitor).unwrap();
+        let data = visitor.save_binary_to_vec().unwrap();
+
+        let mut visitor = Visitor::load_from_memory(&data).unwrap();
+        let loader = SceneLoader::load(
+            "Scene",
+            Arc::new(SerializationContext::new()),
+            Default::default(),
+            resource_manager,
+            &mut visitor,
+            None,
+        )
+        .unwrap();
+        let loaded = block_on(loader.finish());
+
+        let (_, mesh) = loaded.graph.find_by_name_from_root("Sphere").unwrap();
+        let surface = &mesh.cast::<Mesh>().unwrap().surfaces()[0];
+        assert_eq!(
+            surface
+                .data_ref()
+                .data_ref()
+                .geometry_buffer
+                .triangles_ref()
+                .len(),
+            triangles_after
+        );
+        let texture = surface
+            .material()
+            .data_ref()
+            .texture("diffuseTexture")
+            .unwrap();
+        assert_eq!(texture.data_ref().pixel_kind(), TexturePixelKind::DXT1RGBA);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Mesh simplification utils. See [`simplify`] docs for more info.
+
+use crate::{
+    core::{algebra::Vector3, math::TriangleDefinition},
+    fxhash::FxHashMap,
+    scene::mesh::{
+        buffer::{TriangleBuffer, VertexAttributeUsage, VertexReadTrait},
+        surface::SurfaceData,
+    },
+};
+
+/// Reduces the amount of triangles of the given surface data to the given `ratio` of the original
+/// triangle count (`1.0` - keep every triangle, `0.5` - keep half of the triangles and so on). The
+/// method iteratively collapses the shortest edges of the mesh and removes degenerated triangles,
+/// which preserves the overall shape of the mesh. Vertex attributes are kept as is (vertices are
+/// only removed, never moved), which makes the method suitable for any vertex layout. Unused
+/// vertices are removed from the resulting vertex buffer. Blend shapes are not preserved.
+///
+/// The resulting triangle count may be slightly larger than requested, if there are no more edges
+/// that could be collapsed.
+pub fn simplify(data: &SurfaceData, ratio: f32) -> SurfaceData {
+    let source_triangles = data.geometry_buffer.triangles_ref();
+    let target = (source_triangles.len() as f32 * ratio.clamp(0.0, 1.0)).ceil() as usize;
+    if target >= source_triangles.len() {
+        return SurfaceData::new(data.vertex_buffer.clone(), data.geometry_buffer.clone());
+    }
+
+    let positions = data
+        .vertex_buffer
+        .iter()
+        .map(|v| {
+            v.read_3_f32(VertexAttributeUsage::Position)
+                .unwrap_or_default()
+        })
+        .collect::<Vec<Vector3<f32>>>();
+
+    let mut triangles = source_triangles.to_vec();
+    let mut remap = (0..positions.len() as u32).collect::<Vec<_>>();
+    while triangles.len() > target {
+        // Count triangles that share each edge, every collapse of an edge removes them.
+        let mut edges = FxHashMap::<(u32, u32), usize>::default();
+        for triangle in triangles.iter() {
+            for edge in triangle.edges() {
+                let key = (edge.a.min(edge.b), edge.a.max(edge.b));
+                *edges.entry(key).or_default() += 1;
+            }
+        }
+        let mut edges = edges.into_iter().collect::<Vec<_>>();
+        edges.sort_by(|((a0, b0), _), ((a1, b1), _)| {
+            let l0 = positions[*a0 as usize].metric_distance(&positions[*b0 as usize]);
+            let l1 = positions[*a1 as usize].metric_distance(&positions[*b1 as usize]);
+            l0.total_cmp(&l1)
+        });
+
+        // Collapse as many independent edges as possible in a single pass.
+        let mut touched = vec![false; positions.len()];
+        let mut remaining = triangles.len();
+        let mut collapsed = false;
+        for ((a, b), count) in edges {
+            if remaining <= target {
+                break;
+            }
+            if touched[a as usize] || touched[b as usize] {
+                continue;
+            }
+            touched[a as usize] = true;
+            touched[b as usize] = true;
+            remap[b as usize] = a;
+            remaining = remaining.saturating_sub(count);
+            collapsed = true;
+        }
+
+        if !collapsed {
+            break;
+        }
+
+        triangles = triangles
+            .into_iter()
+            .map(|triangle| TriangleDefinition(triangle.0.map(|i| remap[i as usize])))
+            .filter(|triangle| {
+                let [a, b, c] = triangle.0;
+                a != b && b != c && c != a
+            })
+            .collect();
+    }
+
+    // Remove unused vertices.
+    let vertex_size = data.vertex_buffer.vertex_size() as usize;
+    let raw_data = data.vertex_buffer.raw_data();
+    let mut new_indices = FxHashMap::<u32, u32>::default();
+    let mut vertex_buffer = data.vertex_buffer.clone_empty(raw_data.len());
+    {
+        let mut vertex_buffer = vertex_buffer.modify();
+        for triangle in triangles.iter_mut() {
+            for index in triangle.0.iter_mut() {
+                *index = *new_indices.entry(*index).or_insert_with(|| {
+                    let start = *index as usize * vertex_size;
+                    vertex_buffer
+                        .push_vertex_raw(&raw_data[start..(start + vertex_size)])
+                        .expect("Vertex size must match!");
+                    vertex_buffer.vertex_count() - 1
+                });
+            }
+        }
+    }
+
+    SurfaceData::new(vertex_buffer, TriangleBuffer::new(triangles))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Matrix4, scene::mesh::surface::SurfaceData, utils::simplify::simplify,
+    };
+
+    #[test]
+    fn test_simplify() {
+        let data = SurfaceData::make_sphere(16, 16, 1.0, &Matrix4::identity());
+        let count = data.geometry_buffer.len();
+
+        let same = simplify(&data, 1.0);
+        assert_eq!(same.geometry_buffer.len(), count);
+
+        let half = simplify(&data, 0.5);
+        assert!(half.geometry_buffer.len() < count);
+        assert!(half.geometry_buffer.len() >= count / 2);
+        assert!(half.vertex_buffer.vertex_count() < data.vertex_buffer.vertex_count());
+        for triangle in half.geometry_buffer.iter() {
+            for index in triangle.0 {
+                assert!(index < half.vertex_buffer.vertex_count());
+            }
+        }
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A simple CPU ray tracer over static triangle meshes. It is used by light and ambient occlusion
+//! bakers, it is not meant to be used in real-time.
+
+use crate::core::{
+    algebra::Vector3,
+    math::{aabb::AxisAlignedBoundingBox, get_barycentric_coords, ray::Ray},
+};
+
+/// A result of ray tracing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceHit {
+    /// Index of the mesh (in order of [`TriangleTracer::add_mesh`] calls).
+    pub mesh: usize,
+    /// Index of the triangle in the mesh.
+    pub triangle: usize,
+    /// Distance from the ray origin to the hit point.
+    pub distance: f32,
+    /// World-space position of the hit point.
+    pub position: Vector3<f32>,
+    /// Barycentric coordinates of the hit point in the triangle.
+    pub barycentric: (f32, f32, f32),
+}
+
+struct TracerMesh {
+    triangles: Vec<[Vector3<f32>; 3]>,
+    bounds: AxisAlignedBoundingBox,
+}
+
+/// A set of static triangle meshes (in world space), that could be used to trace rays.
+#[derive(Default)]
+pub struct TriangleTracer {
+    meshes: Vec<TracerMesh>,
+}
+
+impl TriangleTracer {
+    /// Creates a new empty tracer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new mesh to the tracer and returns its index.
+    pub fn add_mesh(&mut self, triangles: Vec<[Vector3<f32>; 3]>) -> usize {
+        let mut bounds = AxisAlignedBoundingBox::default();
+        for triangle in triangles.iter() {
+            for vertex in triangle {
+                bounds.add_point(*vertex);
+            }
+        }
+        // Flat meshes produce degenerate bounds, inflate them a bit to keep slab test stable.
+        let margin = Vector3::repeat(1.0e-3);
+        bounds.min -= margin;
+        bounds.max += margin;
+        self.meshes.push(TracerMesh { triangles, bounds });
+        self.meshes.len() - 1
+    }
+
+    /// Returns total amount of meshes in the tracer.
+    pub fn mesh_count(&self) -> usize {
+        self.meshes.len()
+    }
+
+    /// Finds the closest intersection of the ray with the meshes. `direction` must be normalized,
+    /// intersections farther than `max_distance` are ignored.
+    pub fn trace(
+        &self,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        max_distance: f32,
+    ) -> Option<TraceHit> {
+        let ray = Ray::new(origin, direction.scale(max_distance));
+        let mut closest: Option<TraceHit> = None;
+        for (mesh_index, mesh) in self.meshes.iter().enumerate() {
+            if mesh.triangles.is_empty() || ray.aabb_intersection(&mesh.bounds).is_none() {
+                continue;
+            }
+
+            for (triangle_index, triangle) in mesh.triangles.iter().enumerate() {
+                if let Some((t, position)) = ray.triangle_intersection(triangle) {
+                    let distance = t * max_distance;
+                    if closest.as_ref().is_none_or(|hit| distance < hit.distance) {
+                        closest = Some(TraceHit {
+                            mesh: mesh_index,
+                            triangle: triangle_index,
+                            distance,
+                            position,
+                            barycentric: get_barycentric_coords(
+                                &position,
+                                &triangle[0],
+                                &triangle[1],
+                                &triangle[2],
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+        closest
+    }
+
+    /// Checks whether the ray hits anything within the given distance. `direction` must be
+    /// normalized.
+    pub fn is_occluded(
+        &self,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        max_distance: f32,
+    ) -> bool {
+        let ray = Ray::new(origin, direction.scale(max_distance));
+        self.meshes.iter().any(|mesh| {
+            !mesh.triangles.is_empty()
+                && ray.aabb_intersection(&mesh.bounds).is_some()
+                && mesh
+                    .triangles
+                    .iter()
+                    .any(|triangle| ray.triangle_intersection(triangle).is_some())
+        })
+    }
+}
+
+/// Generates a deterministic set of `count` cosine-weighted directions in the hemisphere around
+/// the given (normalized) normal. Directions are distributed evenly using golden angle spiral.
+pub fn hemisphere_directions(normal: Vector3<f32>, count: usize) -> Vec<Vector3<f32>> {
+    let tangent = if normal.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    }
+    .cross(&normal)
+    .normalize();
+    let bitangent = normal.cross(&tangent);
+
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    (0..count)
+        .map(|i| {
+            // Cosine-weighted distribution: uniform points on a disk projected on the hemisphere.
+            let r = ((i as f32 + 0.5) / count as f32).sqrt();
+            let phi = i as f32 * golden_angle;
+            let (x, y) = (r * phi.cos(), r * phi.sin());
+            let z = (1.0 - r * r).max(0.0).sqrt();
+            (tangent.scale(x) + bitangent.scale(y) + normal.scale(z)).normalize()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::Vector3,
+        utils::tracer::{hemisphere_directions, TriangleTracer},
+    };
+
+    #[test]
+    fn test_trace() {
+        let mut tracer = TriangleTracer::new();
+        tracer.add_mesh(vec![[
+            Vector3::new(-1.0, 0.0, -1.0),
+            Vector3::new(1.0, 0.0, -1.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ]]);
+
+        let hit = tracer
+            .trace(Vector3::new(0.0, 2.0, 0.0), -Vector3::y(), 10.0)
+            .unwrap();
+        assert!((hit.distance - 2.0).abs() < 1.0e-5);
+        assert!(tracer.is_occluded(Vector3::new(0.0, 2.0, 0.0), -Vector3::y(), 10.0));
+        assert!(!tracer.is_occluded(Vector3::new(0.0, 2.0, 0.0), -Vector3::y(), 1.0));
+        assert!(tracer
+            .trace(Vector3::new(0.0, 2.0, 0.0), Vector3::y(), 10.0)
+            .is_none());
+
+        for direction in hemisphere_directions(Vector3::y(), 16) {
+            assert!(direction.y > 0.0);
+        }
+    }
+}
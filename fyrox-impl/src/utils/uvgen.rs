@@ -21,3 +21,191 @@
 //! UV generation utils.
 
 pub use uvgen::{generate_uvs, SurfaceDataPatch};
+
+use crate::{
+    core::algebra::Vector2,
+    scene::mesh::{
+        buffer::{
+            VertexAttributeDataType, VertexAttributeDescriptor, VertexAttributeUsage,
+            VertexReadTrait, VertexWriteTrait,
+        },
+        surface::SurfaceData,
+    },
+    utils::lightmap::{apply_surface_data_patch, LightmapGenerationError},
+};
+
+/// Options of [`unwrap_uvs`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct UvUnwrapOptions {
+    /// Amount of texels between charts in the atlas. It prevents lightmap bleeding between charts
+    /// when the lightmap is sampled with filtering.
+    pub padding: u32,
+    /// Amount of texels per one unit of world space. Defines the resolution of the atlas.
+    pub texels_per_unit: f32,
+    /// If `true`, existing texture coordinates in the first channel are kept intact, otherwise
+    /// the generated coordinates will be written to the first channel as well. It could be useful
+    /// for meshes without texture coordinates.
+    pub preserve_tex_coords: bool,
+    /// Shader location of the second texture coordinates attribute, it is used only if the vertex
+    /// buffer does not have such attribute.
+    pub tex_coord_binding_point: u8,
+}
+
+impl Default for UvUnwrapOptions {
+    fn default() -> Self {
+        Self {
+            padding: 2,
+            texels_per_unit: 16.0,
+            preserve_tex_coords: true,
+            tex_coord_binding_point: 6,
+        }
+    }
+}
+
+/// A result of [`unwrap_uvs`].
+#[derive(Clone, Debug)]
+pub struct UvAtlas {
+    /// Approximate size of the square atlas in texels for the requested texel density, it could
+    /// be used as the size of the lightmap texture.
+    pub size: u32,
+    /// The patch, that was applied to the surface data. Its `data_id` is the content hash of the
+    /// surface data before unwrapping.
+    pub patch: SurfaceDataPatch,
+}
+
+/// Generates non-overlapping texture coordinates in `[0; 1]` range for the second texture
+/// coordinates channel, which is used for lightmaps. This is a wrapper over [`generate_uvs`], that
+/// converts the padding and the texel density to the spacing between charts and applies the result
+/// to the surface data. Vertices that are shared between charts are duplicated.
+pub fn unwrap_uvs(
+    data: &mut SurfaceData,
+    options: &UvUnwrapOptions,
+) -> Result<UvAtlas, LightmapGenerationError> {
+    let positions = data
+        .vertex_buffer
+        .iter()
+        .map(|v| v.read_3_f32(VertexAttributeUsage::Position))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut area = 0.0;
+    for triangle in data.geometry_buffer.iter() {
+        let [a, b, c] = triangle.0.map(|i| positions.get(i as usize));
+        let (Some(a), Some(b), Some(c)) = (a, b, c) else {
+            return Err(LightmapGenerationError::InvalidIndex);
+        };
+        area += (b - a).cross(&(c - a)).norm() * 0.5;
+    }
+    let texels_per_unit = options.texels_per_unit.max(f32::EPSILON);
+    let size = ((area.sqrt() * texels_per_unit).ceil() as u32).max(1);
+
+    let mut patch = generate_uvs(
+        positions.into_iter(),
+        data.geometry_buffer.iter().map(|t| t.0),
+        options.padding as f32 / size as f32,
+    )
+    .ok_or(LightmapGenerationError::InvalidIndex)?;
+    patch.data_id = data.content_hash();
+
+    apply_surface_data_patch(data, &patch, options.tex_coord_binding_point);
+
+    if !options.preserve_tex_coords {
+        if !data
+            .vertex_buffer
+            .has_attribute(VertexAttributeUsage::TexCoord0)
+        {
+            let shader_location = data.vertex_buffer.find_free_shader_location();
+            data.vertex_buffer
+                .modify()
+                .add_attribute(
+                    VertexAttributeDescriptor {
+                        usage: VertexAttributeUsage::TexCoord0,
+                        data_type: VertexAttributeDataType::F32,
+                        size: 2,
+                        divisor: 0,
+                        shader_location,
+                        normalized: false,
+                    },
+                    Vector2::<f32>::default(),
+                )
+                .expect("Attribute must not exist!");
+        }
+
+        let mut vertex_buffer = data.vertex_buffer.modify();
+        for (mut view, tex_coord) in vertex_buffer.iter_mut().zip(patch.second_tex_coords.iter()) {
+            view.write_2_f32(VertexAttributeUsage::TexCoord0, *tex_coord)?;
+        }
+    }
+
+    Ok(UvAtlas { size, patch })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        core::algebra::{Matrix4, Vector2},
+        scene::mesh::{
+            buffer::{VertexAttributeUsage, VertexReadTrait},
+            surface::SurfaceData,
+        },
+        utils::uvgen::{unwrap_uvs, UvUnwrapOptions},
+    };
+
+    fn read_tex_coords(data: &SurfaceData, usage: VertexAttributeUsage) -> Vec<Vector2<f32>> {
+        data.vertex_buffer
+            .iter()
+            .map(|v| v.read_2_f32(usage).unwrap())
+            .collect()
+    }
+
+    // Separating axis test for two triangles, touching triangles are not overlapping.
+    fn overlaps(a: [Vector2<f32>; 3], b: [Vector2<f32>; 3]) -> bool {
+        for triangle in [a, b] {
+            for i in 0..3 {
+                let edge = triangle[(i + 1) % 3] - triangle[i];
+                let axis = Vector2::new(-edge.y, edge.x);
+                let project = |t: [Vector2<f32>; 3]| {
+                    let d = t.map(|p| p.dot(&axis));
+                    (d[0].min(d[1]).min(d[2]), d[0].max(d[1]).max(d[2]))
+                };
+                let (min_a, max_a) = project(a);
+                let (min_b, max_b) = project(b);
+                if max_a <= min_b + 1.0e-6 || max_b <= min_a + 1.0e-6 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    #[test]
+    fn test_unwrap_box() {
+        let mut data = SurfaceData::make_cube(Matrix4::identity());
+        let tex_coords = read_tex_coords(&data, VertexAttributeUsage::TexCoord0);
+
+        let atlas = unwrap_uvs(&mut data, &UvUnwrapOptions::default()).unwrap();
+        assert!(atlas.size > 0);
+
+        // Existing texture coordinates are preserved.
+        assert_eq!(
+            read_tex_coords(&data, VertexAttributeUsage::TexCoord0)[..tex_coords.len()],
+            tex_coords
+        );
+
+        let lightmap_tex_coords = read_tex_coords(&data, VertexAttributeUsage::TexCoord1);
+        for tex_coord in lightmap_tex_coords.iter() {
+            assert!((0.0..=1.0).contains(&tex_coord.x));
+            assert!((0.0..=1.0).contains(&tex_coord.y));
+        }
+
+        let triangles = data
+            .geometry_buffer
+            .iter()
+            .map(|t| t.0.map(|i| lightmap_tex_coords[i as usize]))
+            .collect::<Vec<_>>();
+        for (i, a) in triangles.iter().enumerate() {
+            for b in triangles[(i + 1)..].iter() {
+                assert!(!overlaps(*a, *b));
+            }
+        }
+    }
+}
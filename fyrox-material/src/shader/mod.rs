@@ -242,13 +242,14 @@
 //!
 //! Property group. Provided for each rendered surface instance.
 //!
-//! | Name                 | Type       | Description                                 |
-//! |----------------------|------------|---------------------------------------------|
-//! | worldMatrix          | `mat4`     | Local-to-world transformation.              |
-//! | worldViewProjection  | `mat4`     | Local-to-clip-space transform.              |
-//! | blendShapesCount     | `int`      | Total amount of blend shapes.               |
-//! | useSkeletalAnimation | `bool`     | Whether skinned meshes is rendering or not. |
-//! | blendShapesWeights   | `vec4[32]` | Blend shape weights.                        |
+//! | Name                      | Type       | Description                                          |
+//! |---------------------------|------------|------------------------------------------------------|
+//! | worldMatrix               | `mat4`     | Local-to-world transformation.                       |
+//! | worldViewProjection       | `mat4`     | Local-to-clip-space transform.                       |
+//! | blendShapesCount          | `int`      | Total amount of blend shapes.                        |
+//! | useSkeletalAnimation      | `bool`     | Whether skinned meshes is rendering or not.          |
+//! | useDualQuaternionSkinning | `bool`     | Whether bone matrices store packed dual quaternions. |
+//! | blendShapesWeights        | `vec4[32]` | Blend shape weights.                                 |
 //!
 //! ### `fyrox_boneMatrices`
 //!
@@ -274,6 +275,7 @@
 //! | zNear                | `float`    | Near clipping plane location.                    |
 //! | zFar                 | `float`    | Far clipping plane location.                     |
 //! | zRange               | `float`    | `zFar - zNear`                                   |
+//! | viewMatrix           | `mat4`     | World-to-view-space transformation.              |
 //!
 //! ### `fyrox_lightData`
 //!
@@ -288,13 +290,34 @@
 //!
 //! Property group. Information about visible light sources
 //!
-//! | Name              | Type       | Description                                             |
-//! |------------------ |------------|---------------------------------------------------------|
-//! | lightCount        | `int`      | Total amount of light sources visible on screen.        |
-//! | lightsColorRadius | `vec4[16]` | Color (xyz) and radius (w) of light source              |
-//! | lightsParameters  | `vec2[16]` | Hot-spot cone angle cos (x) and half cone angle cos (y) |
-//! | lightsPosition    | `vec3[16]` | World-space light position.                             |
-//! | lightsDirection   | `vec3[16]` | World-space light direction                             |
+//! | Name              | Type        | Description                                                      |
+//! |-------------------|-------------|------------------------------------------------------------------|
+//! | lightCount        | `int`       | Total amount of light sources visible on screen.                 |
+//! | lightsColorRadius | `vec4[16]`  | Color (xyz) and radius (w) of light source                       |
+//! | lightsParameters  | `vec2[16]`  | Hot-spot cone angle cos (x) and half cone angle cos (y)          |
+//! | lightsPosition    | `vec3[16]`  | World-space light position.                                      |
+//! | lightsDirection   | `vec3[16]`  | World-space light direction                                      |
+//! | lightsIntensity   | `float[16]` | Intensity of light source.                                       |
+//! | lightsAreaSide    | `vec3[16]`  | Side vector of area light scaled by half-width, zero otherwise.  |
+//! | lightsAreaLook    | `vec3[16]`  | Look vector of area light scaled by half-height, zero otherwise. |
+//!
+//! Area lights have `1` in the first component of `lightsParameters` if they are discs, `0` if they
+//! are rectangles. `S_ForwardLight` function could be used to calculate lighting from any kind of
+//! the light sources.
+//!
+//! ### `fyrox_shadowData`
+//!
+//! Property group. Contains cascaded shadow maps info of a directional light, that is used to
+//! cast shadows in forward passes (see `S_CsmShadowFactor`). Shadow maps themselves are provided
+//! by `fyrox_shadowCascade0`, `fyrox_shadowCascade1` and `fyrox_shadowCascade2` textures.
+//!
+//! | Name                  | Type       | Description                                                 |
+//! |-----------------------|------------|-------------------------------------------------------------|
+//! | shadowLightIndex      | `int`      | Index of the light in `fyrox_lightsBlock`, -1 if no shadow. |
+//! | lightViewProjMatrices | `mat4[3]`  | World-to-light-clip-space transformation of each cascade.   |
+//! | cascadeDistances      | `float[3]` | View-space distance at which each cascade ends.             |
+//! | shadowBias            | `float`    | Depth bias of the shadow.                                   |
+//! | softShadows           | `bool`     | Whether to use percentage-closer filtering or not.          |
 //!
 //! ### `fyrox_graphicsSettings`
 //!
@@ -304,6 +327,24 @@
 //! |--------|------------|---------------------------------------------------|
 //! | usePom | `bool`     | Whether to use parallax occlusion mapping or not. |
 //!
+//! ### `fyrox_fogData`
+//!
+//! Property group. Contains fog settings of the scene, it should be used by forward passes to fog
+//! the surfaces the same way as the deferred renderer does (see `S_FogFactor` shader function).
+//!
+//! | Name                | Type    | Description                                                    |
+//! |---------------------|---------|----------------------------------------------------------------|
+//! | fogColor            | `vec4`  | Linear color of the fog.                                       |
+//! | fogEnabled          | `bool`  | Whether the fog is enabled or not. Always `false` for shadows. |
+//! | fogMode             | `int`   | Index of the fog mode (linear, exponential, squared exp.).     |
+//! | fogStart            | `float` | Distance at which the linear fog starts.                       |
+//! | fogEnd              | `float` | Distance at which the linear fog fully covers the surfaces.    |
+//! | fogDensity          | `float` | Density of the exponential fog.                                |
+//! | heightFogEnabled    | `bool`  | Whether the height fog is enabled or not (see `S_HeightFog`).  |
+//! | heightFogBaseHeight | `float` | Height at which the density of the height fog is at maximum.   |
+//! | heightFogFalloff    | `float` | How fast the density of the height fog decreases with height.  |
+//! | heightFogDensity    | `float` | Density of the height fog at the base height.                  |
+//!
 //! ### `fyrox_sceneDepth`
 //!
 //! Texture. Contains depth values of scene. Available **only** after opaque geometry is
@@ -317,6 +358,11 @@
 //! ),
 //! ```
 //!
+//! ### `fyrox_shadowCascade0`, `fyrox_shadowCascade1`, `fyrox_shadowCascade2`
+//!
+//! Textures. Contain cascaded shadow maps of the light described by `fyrox_shadowData`. A white
+//! texture (no shadows) is bound, if there's no such light.
+//!
 //! ### `fyrox_widgetData`
 //!
 //! Property group. Contains UI widget-specific data, that can be used in UI shaders.
@@ -624,6 +670,9 @@ impl ShaderDefinition {
     /// Maximum number of gradient values per widget.
     pub const MAX_GRADIENT_VALUE_COUNT: usize = 16;
 
+    /// Maximum number of shadow cascades of a directional light in the shadow data block.
+    pub const MAX_SHADOW_CASCADES: usize = 3;
+
     fn find_shader_line_locations(&mut self, str: &str) {
         let mut line_ends = Vec::new();
         for (i, ch) in str.bytes().enumerate() {
@@ -717,6 +766,7 @@ impl ShaderDefinition {
                         ShaderProperty::new_float("zNear"),
                         ShaderProperty::new_float("zFar"),
                         ShaderProperty::new_float("zRange"),
+                        ShaderProperty::new_matrix4("viewMatrix"),
                     ]);
                 }
                 "fyrox_lightData" => {
@@ -730,6 +780,21 @@ impl ShaderDefinition {
                     properties.clear();
                     properties.extend([ShaderProperty::new_bool("usePOM")]);
                 }
+                "fyrox_fogData" => {
+                    properties.clear();
+                    properties.extend([
+                        ShaderProperty::new_vector4("fogColor"),
+                        ShaderProperty::new_bool("fogEnabled"),
+                        ShaderProperty::new_int("fogMode"),
+                        ShaderProperty::new_float("fogStart"),
+                        ShaderProperty::new_float("fogEnd"),
+                        ShaderProperty::new_float("fogDensity"),
+                        ShaderProperty::new_bool("heightFogEnabled"),
+                        ShaderProperty::new_float("heightFogBaseHeight"),
+                        ShaderProperty::new_float("heightFogFalloff"),
+                        ShaderProperty::new_float("heightFogDensity"),
+                    ]);
+                }
                 "fyrox_lightsBlock" => {
                     properties.clear();
                     properties.extend([
@@ -738,6 +803,25 @@ impl ShaderDefinition {
                         ShaderProperty::new_vec2_f32_array("lightsParameters", Self::MAX_LIGHTS),
                         ShaderProperty::new_vec3_f32_array("lightsPosition", Self::MAX_LIGHTS),
                         ShaderProperty::new_vec3_f32_array("lightsDirection", Self::MAX_LIGHTS),
+                        ShaderProperty::new_f32_array("lightsIntensity", Self::MAX_LIGHTS),
+                        ShaderProperty::new_vec3_f32_array("lightsAreaSide", Self::MAX_LIGHTS),
+                        ShaderProperty::new_vec3_f32_array("lightsAreaLook", Self::MAX_LIGHTS),
+                    ])
+                }
+                "fyrox_shadowData" => {
+                    properties.clear();
+                    properties.extend([
+                        ShaderProperty::new_int("shadowLightIndex"),
+                        ShaderProperty::new_mat4_f32_array(
+                            "lightViewProjMatrices",
+                            Self::MAX_SHADOW_CASCADES,
+                        ),
+                        ShaderProperty::new_f32_array(
+                            "cascadeDistances",
+                            Self::MAX_SHADOW_CASCADES,
+                        ),
+                        ShaderProperty::new_float("shadowBias"),
+                        ShaderProperty::new_bool("softShadows"),
                     ])
                 }
                 "fyrox_instanceData" => {
@@ -747,6 +831,7 @@ impl ShaderDefinition {
                         ShaderProperty::new_matrix4("worldViewProjection"),
                         ShaderProperty::new_int("blendShapesCount"),
                         ShaderProperty::new_bool("useSkeletalAnimation"),
+                        ShaderProperty::new_bool("useDualQuaternionSkinning"),
                         ShaderProperty::new_vec4_f32_array(
                             "blendShapesWeights",
                             Self::MAX_BLEND_SHAPE_WEIGHT_GROUPS,
@@ -1100,6 +1185,7 @@ mod test {
     use crate::shader::{
         RenderPassDefinition, SamplerFallback, ShaderDefinition, ShaderResource,
         ShaderResourceDefinition, ShaderResourceExtension, ShaderResourceKind, ShaderSourceCode,
+        STANDARD, STANDARD_PARTICLE_SYSTEM, STANDARD_SPRITE, STANDARD_TERRAIN, STANDARD_TWOSIDES,
     };
     use fyrox_graphics::gpu_program::SamplerKind;
     use fyrox_resource::untyped::ResourceKind;
@@ -1176,4 +1262,57 @@ mod test {
 
         assert_eq!(data.definition, reference_definition);
     }
+
+    #[test]
+    fn test_standard_forward_passes_apply_fog() {
+        for shader in [
+            &STANDARD,
+            &STANDARD_TWOSIDES,
+            &STANDARD_TERRAIN,
+            &STANDARD_PARTICLE_SYSTEM,
+            &STANDARD_SPRITE,
+        ] {
+            let data = shader.resource.data_ref();
+            let definition = &data.definition;
+
+            let fog_data = definition
+                .resources
+                .iter()
+                .find(|resource| resource.name.as_str() == "fyrox_fogData")
+                .unwrap();
+            let ShaderResourceKind::PropertyGroup(ref properties) = fog_data.kind else {
+                panic!("{}: fog data must be a property group", definition.name);
+            };
+            // Must match the layout of the block written by the renderer.
+            assert_eq!(
+                properties
+                    .iter()
+                    .map(|property| property.name.as_str())
+                    .collect::<Vec<_>>(),
+                [
+                    "fogColor",
+                    "fogEnabled",
+                    "fogMode",
+                    "fogStart",
+                    "fogEnd",
+                    "fogDensity",
+                    "heightFogEnabled",
+                    "heightFogBaseHeight",
+                    "heightFogFalloff",
+                    "heightFogDensity",
+                ]
+            );
+
+            let forward = definition
+                .passes
+                .iter()
+                .find(|pass| pass.name == "Forward")
+                .unwrap();
+            assert!(
+                forward.fragment_shader.0.contains("S_FogFactor"),
+                "{}",
+                definition.name
+            );
+        }
+    }
 }
@@ -54,6 +54,13 @@
             ]),
             binding: 4
         ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 5
+        ),
     ],
 
     disabled_passes: ["GBuffer", "DirectionalShadow", "PointShadow", "SpotShadow"],
@@ -165,6 +172,15 @@
 
                    FragColor = vec4(lighting, 1.0) * color * S_SRGBToLinear(texture(diffuseTexture, texCoord)).r;
                    FragColor.a *= depthOpacity;
+                   if (fyrox_fogData.fogEnabled) {
+                       float viewDepth = max(-(fyrox_cameraData.viewMatrix * vec4(fragmentPosition, 1.0)).z, 0.0);
+                       float fogFactor = S_FogFactor(fyrox_fogData.fogMode, fyrox_fogData.fogStart, fyrox_fogData.fogEnd, fyrox_fogData.fogDensity, viewDepth);
+                       if (fyrox_fogData.heightFogEnabled) {
+                           float heightFog = S_HeightFog(fyrox_cameraData.position, fragmentPosition, fyrox_fogData.heightFogBaseHeight, fyrox_fogData.heightFogFalloff, fyrox_fogData.heightFogDensity);
+                           fogFactor = 1.0 - (1.0 - fogFactor) * (1.0 - heightFog);
+                       }
+                       FragColor.rgb = mix(FragColor.rgb, fyrox_fogData.fogColor.rgb, fogFactor);
+                   }
                }
                "#,
         )
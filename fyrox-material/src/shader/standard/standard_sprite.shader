@@ -21,6 +21,13 @@
             ]),
             binding: 1
         ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 2
+        ),
     ],
 
     disabled_passes: ["GBuffer", "DirectionalShadow", "PointShadow", "SpotShadow"],
@@ -68,6 +75,7 @@
 
                 out vec2 texCoord;
                 out vec4 color;
+                out vec3 fragmentPosition;
 
                 void main()
                 {
@@ -81,7 +89,9 @@
                     vec2 vertexOffset = S_RotateVec2(vec2(dx, dy), rotation);
                     vec4 worldPosition = fyrox_instanceData.worldMatrix * vec4(vertexPosition, 1.0);
                     vec3 offset = (vertexOffset.x * fyrox_cameraData.sideVector + vertexOffset.y * fyrox_cameraData.upVector) * size;
-                    gl_Position = fyrox_cameraData.viewProjectionMatrix * (worldPosition + vec4(offset.x, offset.y, offset.z, 0.0));
+                    vec4 finalPosition = worldPosition + vec4(offset.x, offset.y, offset.z, 0.0);
+                    fragmentPosition = finalPosition.xyz;
+                    gl_Position = fyrox_cameraData.viewProjectionMatrix * finalPosition;
                 }
                "#,
 
@@ -91,10 +101,20 @@
 
                 in vec2 texCoord;
                 in vec4 color;
+                in vec3 fragmentPosition;
 
                 void main()
                 {
                     FragColor = color * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+                    if (fyrox_fogData.fogEnabled) {
+                        float viewDepth = max(-(fyrox_cameraData.viewMatrix * vec4(fragmentPosition, 1.0)).z, 0.0);
+                        float fogFactor = S_FogFactor(fyrox_fogData.fogMode, fyrox_fogData.fogStart, fyrox_fogData.fogEnd, fyrox_fogData.fogDensity, viewDepth);
+                        if (fyrox_fogData.heightFogEnabled) {
+                            float heightFog = S_HeightFog(fyrox_cameraData.position, fragmentPosition, fyrox_fogData.heightFogBaseHeight, fyrox_fogData.heightFogFalloff, fyrox_fogData.heightFogDensity);
+                            fogFactor = 1.0 - (1.0 - fogFactor) * (1.0 - heightFog);
+                        }
+                        FragColor.rgb = mix(FragColor.rgb, fyrox_fogData.fogColor.rgb, fogFactor);
+                    }
                 }
                "#,
         )
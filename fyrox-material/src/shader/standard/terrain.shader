@@ -57,6 +57,21 @@
             kind: Texture(kind: Sampler2D, fallback: White),
             binding: 10
         ),
+        (
+            name: "fyrox_shadowCascade0",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 11
+        ),
+        (
+            name: "fyrox_shadowCascade1",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 12
+        ),
+        (
+            name: "fyrox_shadowCascade2",
+            kind: Texture(kind: Sampler2D, fallback: White),
+            binding: 13
+        ),
         (
             name: "properties",
             kind: PropertyGroup([
@@ -119,6 +134,27 @@
             ]),
             binding: 4
         ),
+        (
+            name: "fyrox_fogData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 5
+        ),
+        (
+            name: "fyrox_lightsBlock",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 6
+        ),
+        (
+            name: "fyrox_shadowData",
+            kind: PropertyGroup([
+                // Autogenerated
+            ]),
+            binding: 7
+        ),
     ],
 
     passes: [
@@ -293,15 +329,24 @@
                 scissor_box: None
             ),
             vertex_shader:
-               r#"
+                r#"
                 layout(location = 0) in vec3 vertexPosition;
                 layout(location = 1) in vec2 vertexTexCoord;
+                layout(location = 2) in vec3 vertexNormal;
+                layout(location = 3) in vec4 vertexTangent;
+                layout(location = 6) in vec2 vertexSecondTexCoord;
 
                 out vec3 position;
+                out vec3 normal;
                 out vec2 texCoord;
+                out vec3 tangent;
+                out vec3 binormal;
+                out vec2 secondTexCoord;
 
                 void main()
                 {
+                    // Each node has tex coords in [0; 1] range, here we must scale and offset it
+                    // to match the actual position.
                     vec2 actualTexCoords = vec2(vertexTexCoord * properties.nodeUvOffsets.zw + properties.nodeUvOffsets.xy);
                     vec2 heightSize = vec2(textureSize(heightMapTexture, 0));
                     vec2 innerSize = heightSize - 3.0;
@@ -309,26 +354,86 @@
                     vec2 heightCoords = (actualTexCoords * innerSize + 1.5) * pixelSize;
                     float height = texture(heightMapTexture, heightCoords).r;
                     vec4 finalVertexPosition = vec4(vertexPosition.x, height, vertexPosition.z, 1.0);
+                    float hx0 = texture(heightMapTexture, heightCoords + vec2(-1.0, 0.0) * pixelSize).r;
+                    float hx1 = texture(heightMapTexture, heightCoords + vec2(1.0, 0.0) * pixelSize).r;
+                    float hy0 = texture(heightMapTexture, heightCoords + vec2(0.0, -1.0) * pixelSize).r;
+                    float hy1 = texture(heightMapTexture, heightCoords + vec2(0.0, 1.0) * pixelSize).r;
 
-                    gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
+                    vec3 n = vec3((hx0 - hx1) / 2.0, 1.0, (hy0 - hy1) / 2.0);
+                    vec3 tan = vec3(n.y, -n.x, 0.0);
+
+                    mat3 nm = mat3(fyrox_instanceData.worldMatrix);
+                    normal = normalize(nm * n);
+                    tangent = normalize(nm * tan);
+                    binormal = normalize(-1.0 * cross(normal, tangent));
                     texCoord = actualTexCoords;
+                    position = vec3(fyrox_instanceData.worldMatrix * finalVertexPosition);
+                    secondTexCoord = vertexSecondTexCoord;
+                    gl_Position = fyrox_instanceData.worldViewProjection * finalVertexPosition;
                 }
-               "#,
-
-           fragment_shader:
-               r#"
-                uniform vec4 diffuseColor;
-
+                "#,
+            fragment_shader:
+                r#"
                 out vec4 FragColor;
 
+                in vec3 position;
+                in vec3 normal;
                 in vec2 texCoord;
+                in vec3 tangent;
+                in vec3 binormal;
+                in vec2 secondTexCoord;
 
                 void main()
                 {
                     if (texture(holeMaskTexture, texCoord).r < 0.5) discard;
-                    FragColor = properties.diffuseColor * S_SRGBToLinear(texture(diffuseTexture, texCoord));
+
+                    mat3 tangentSpace = mat3(tangent, binormal, normal);
+                    vec2 tc = texCoord * properties.texCoordScale;
+
+                    vec4 diffuseColor = properties.diffuseColor * S_SRGBToLinear(texture(diffuseTexture, tc));
+
+                    TPBRContext ctx;
+                    ctx.albedo = diffuseColor.rgb;
+                    ctx.fragmentNormal = normalize(tangentSpace * (texture(normalTexture, tc).xyz * 2.0 - 1.0));
+                    ctx.metallic = texture(metallicTexture, tc).r;
+                    ctx.roughness = texture(roughnessTexture, tc).r;
+                    ctx.viewVector = normalize(fyrox_cameraData.position - position);
+
+                    vec3 ambient = fyrox_lightData.ambientLightColor.rgb
+                        + properties.emissionStrength * texture(emissionTexture, tc).rgb
+                        + texture(lightmapTexture, secondTexCoord).rgb;
+                    vec3 lighting = ambient * ctx.albedo * texture(aoTexture, tc).r;
+
+                    float viewDepth = max(-(fyrox_cameraData.viewMatrix * vec4(position, 1.0)).z, 0.0);
+
+                    for (int i = 0; i < min(fyrox_lightsBlock.lightCount, 16); ++i) {
+                        vec4 colorRadius = fyrox_lightsBlock.lightsColorRadius[i];
+                        colorRadius.rgb = S_SRGBToLinear(vec4(colorRadius.rgb, 1.0)).rgb * fyrox_lightsBlock.lightsIntensity[i];
+                        vec3 light = S_ForwardLight(ctx, position, fyrox_lightsBlock.lightsPosition[i],
+                            fyrox_lightsBlock.lightsDirection[i], colorRadius, fyrox_lightsBlock.lightsParameters[i],
+                            fyrox_lightsBlock.lightsAreaSide[i], fyrox_lightsBlock.lightsAreaLook[i]);
+                        if (i == fyrox_shadowData.shadowLightIndex) {
+                            light *= S_CsmShadowFactor(fyrox_shadowData.softShadows, fyrox_shadowData.shadowBias,
+                                position, viewDepth, fyrox_shadowData.lightViewProjMatrices,
+                                fyrox_shadowData.cascadeDistances, fyrox_shadowCascade0, fyrox_shadowCascade1,
+                                fyrox_shadowCascade2);
+                        }
+                        lighting += light;
+                    }
+
+                    // Layers are blended using their masks, just like in the G-Buffer pass.
+                    FragColor = vec4(lighting, diffuseColor.a * texture(maskTexture, texCoord).r);
+
+                    if (fyrox_fogData.fogEnabled) {
+                        float fogFactor = S_FogFactor(fyrox_fogData.fogMode, fyrox_fogData.fogStart, fyrox_fogData.fogEnd, fyrox_fogData.fogDensity, viewDepth);
+                        if (fyrox_fogData.heightFogEnabled) {
+                            float heightFog = S_HeightFog(fyrox_cameraData.position, position, fyrox_fogData.heightFogBaseHeight, fyrox_fogData.heightFogFalloff, fyrox_fogData.heightFogDensity);
+                            fogFactor = 1.0 - (1.0 - fogFactor) * (1.0 - heightFog);
+                        }
+                        FragColor.rgb = mix(FragColor.rgb, fyrox_fogData.fogColor.rgb, fogFactor);
+                    }
                 }
-               "#,
+                "#,
         ),
         (
             name: "SpotShadow",
@@ -20,10 +20,11 @@
 
 //! A set of useful scripts that can be used to in your game.
 
-use crate::camera::FlyingCameraController;
+use crate::{camera::FlyingCameraController, light::LightAnimation};
 use fyrox::script::constructor::ScriptConstructorContainer;
 
 pub mod camera;
+pub mod light;
 
 /// Registers every script from the crate in the given constructor container. Use it, if you want to register all
 /// available scripts at once. Typical usage could be like this:
@@ -49,4 +50,5 @@ pub mod camera;
 /// ```
 pub fn register(container: &ScriptConstructorContainer) {
     container.add::<FlyingCameraController>("Fyrox Flying Camera Controller");
+    container.add::<LightAnimation>("Fyrox Light Animation");
 }
@@ -0,0 +1,277 @@
+// Copyright (c) 2019-present Dmitry Stepanov and Fyrox Engine contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Light animation script is used to modulate intensity and color of a light source over time. It could
+//! be used to create flickering torches, faulty lamps, pulsating lights, etc. See [`LightAnimation`] docs
+//! for more info.
+
+use fyrox::{
+    core::{
+        color::Color,
+        color_gradient::ColorGradient,
+        impl_component_provider,
+        math::curve::{Curve, CurveKey, CurveKeyKind},
+        reflect::prelude::*,
+        uuid_provider,
+        variable::InheritableVariable,
+        visitor::prelude::*,
+    },
+    graph::{SceneGraph, SceneGraphNode},
+    plugin::error::GameResult,
+    scene::light::BaseLight,
+    script::{ScriptContext, ScriptTrait},
+};
+
+/// Light animation script modulates intensity (and optionally color) of a light source over time. It
+/// can be assigned to any light node (point, spot, directional). The intensity of the light is calculated
+/// as `initial_intensity * curve(t) * (1.0 + noise_amplitude * noise(t))`, where `curve(t)` is the value
+/// of [`Self::intensity_curve`] (looped over its length) and `noise(t)` is a smooth deterministic noise in
+/// `[-1; 1]` range, defined by [`Self::seed`] and [`Self::noise_frequency`].
+///
+/// Initial intensity and color of the light are captured when the script is updated for the first time.
+#[derive(Visit, Reflect, Debug, Clone)]
+pub struct LightAnimation {
+    /// A curve, that defines intensity multiplier over time. The curve is looped over its length. Empty
+    /// curve is treated as constant `1.0`.
+    #[visit(optional)]
+    pub intensity_curve: InheritableVariable<Curve>,
+
+    /// Amplitude of the random noise applied on top of the intensity curve. Zero disables the noise.
+    #[reflect(min_value = 0.0)]
+    #[visit(optional)]
+    pub noise_amplitude: InheritableVariable<f32>,
+
+    /// Frequency (in Hz) of the random noise.
+    #[reflect(min_value = 0.0)]
+    #[visit(optional)]
+    pub noise_frequency: InheritableVariable<f32>,
+
+    /// Seed of the random noise. The same seed always produces the same flickering pattern.
+    #[visit(optional)]
+    pub seed: InheritableVariable<u32>,
+
+    /// Whether to animate the color of the light or not.
+    #[visit(optional)]
+    pub animate_color: InheritableVariable<bool>,
+
+    /// A gradient, that defines color multiplier over time. It is sampled in `[0; 1]` range over
+    /// [`Self::color_period`] seconds.
+    #[visit(optional)]
+    pub color_gradient: InheritableVariable<ColorGradient>,
+
+    /// Duration (in seconds) of a single loop of the color gradient.
+    #[reflect(min_value = 0.0)]
+    #[visit(optional)]
+    pub color_period: InheritableVariable<f32>,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    pub time: f32,
+
+    #[reflect(hidden)]
+    #[visit(skip)]
+    initial_state: Option<(f32, Color)>,
+}
+
+impl Default for LightAnimation {
+    fn default() -> Self {
+        Self {
+            intensity_curve: Curve::from(vec![CurveKey::new(0.0, 1.0, CurveKeyKind::Constant)])
+                .into(),
+            noise_amplitude: 0.25.into(),
+            noise_frequency: 10.0.into(),
+            seed: 0.into(),
+            animate_color: false.into(),
+            color_gradient: Default::default(),
+            color_period: 1.0.into(),
+            time: 0.0,
+            initial_state: None,
+        }
+    }
+}
+
+impl_component_provider!(LightAnimation);
+uuid_provider!(LightAnimation = "2f5bd3a4-1c5e-4b4e-9a0f-6c1f0d6e4b3a");
+
+fn hash(seed: u32, i: i32) -> f32 {
+    let mut x = (i as u32).wrapping_mul(0x9E37_79B1) ^ seed.wrapping_mul(0x85EB_CA77);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+}
+
+/// Smooth value noise in `[-1; 1]` range.
+fn value_noise(seed: u32, t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let a = hash(seed, i as i32);
+    let b = hash(seed, i as i32 + 1);
+    let s = f * f * (3.0 - 2.0 * f);
+    a + (b - a) * s
+}
+
+impl LightAnimation {
+    /// Calculates intensity multiplier at the given time.
+    pub fn intensity_factor(&self, time: f32) -> f32 {
+        let curve_factor = if self.intensity_curve.is_empty() {
+            1.0
+        } else {
+            let length = self.intensity_curve.max_location();
+            let location = if length > 0.0 {
+                time.rem_euclid(length)
+            } else {
+                0.0
+            };
+            self.intensity_curve.value_at(location)
+        };
+
+        let noise_factor = if *self.noise_amplitude > 0.0 {
+            1.0 + *self.noise_amplitude * value_noise(*self.seed, time * *self.noise_frequency)
+        } else {
+            1.0
+        };
+
+        (curve_factor * noise_factor).max(0.0)
+    }
+
+    /// Calculates color multiplier at the given time. Returns white color if the color animation is
+    /// disabled.
+    pub fn color_factor(&self, time: f32) -> Color {
+        if *self.animate_color {
+            let location = if *self.color_period > 0.0 {
+                time.rem_euclid(*self.color_period) / *self.color_period
+            } else {
+                0.0
+            };
+            self.color_gradient.get_color(location)
+        } else {
+            Color::WHITE
+        }
+    }
+
+    /// Advances the animation by the given time step and applies it to the given light.
+    pub fn animate(&mut self, light: &mut BaseLight, dt: f32) {
+        let (initial_intensity, initial_color) = *self
+            .initial_state
+            .get_or_insert_with(|| (light.intensity(), light.color()));
+
+        self.time += dt;
+
+        light.set_intensity(initial_intensity * self.intensity_factor(self.time));
+
+        if *self.animate_color {
+            let factor = self.color_factor(self.time).as_frgb();
+            let color = initial_color.as_frgb().component_mul(&factor);
+            light.set_color(Color::from(color));
+        }
+    }
+}
+
+impl ScriptTrait for LightAnimation {
+    fn on_update(&mut self, context: &mut ScriptContext) -> GameResult {
+        let node = context.scene.graph.try_get_node_mut(context.handle)?;
+        if let Some(light) = node.component_mut::<BaseLight>() {
+            self.animate(light, context.dt);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::light::LightAnimation;
+    use fyrox::{
+        core::math::curve::{Curve, CurveKey, CurveKeyKind},
+        scene::{base::BaseBuilder, light::BaseLightBuilder},
+    };
+
+    #[test]
+    fn test_constant_curve_keeps_intensity() {
+        let mut light = BaseLightBuilder::new(BaseBuilder::new())
+            .with_intensity(2.0)
+            .build();
+
+        let mut animation = LightAnimation {
+            noise_amplitude: 0.0.into(),
+            ..Default::default()
+        };
+
+        for _ in 0..100 {
+            animation.animate(&mut light, 1.0 / 60.0);
+            assert_eq!(light.intensity(), 2.0);
+        }
+    }
+
+    #[test]
+    fn test_sine_curve_oscillates_within_bounds() {
+        let mut light = BaseLightBuilder::new(BaseBuilder::new())
+            .with_intensity(2.0)
+            .build();
+
+        let keys = (0..=32)
+            .map(|i| {
+                let t = i as f32 / 32.0;
+                CurveKey::new(
+                    t,
+                    1.0 + 0.5 * (t * std::f32::consts::TAU).sin(),
+                    CurveKeyKind::Linear,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut animation = LightAnimation {
+            intensity_curve: Curve::from(keys).into(),
+            noise_amplitude: 0.0.into(),
+            ..Default::default()
+        };
+
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for _ in 0..240 {
+            animation.animate(&mut light, 1.0 / 60.0);
+            let intensity = light.intensity();
+            assert!((1.0 - 1.0e-3..=3.0 + 1.0e-3).contains(&intensity));
+            min = min.min(intensity);
+            max = max.max(intensity);
+        }
+
+        assert!(min < 1.1);
+        assert!(max > 2.9);
+    }
+
+    #[test]
+    fn test_noise_is_deterministic() {
+        let animation = LightAnimation {
+            seed: 42.into(),
+            ..Default::default()
+        };
+        let other = animation.clone();
+
+        for i in 0..100 {
+            let t = i as f32 * 0.013;
+            let factor = animation.intensity_factor(t);
+            assert_eq!(factor, other.intensity_factor(t));
+            assert!((0.75 - 1.0e-3..=1.25 + 1.0e-3).contains(&factor));
+        }
+    }
+}
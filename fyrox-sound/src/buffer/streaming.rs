@@ -42,6 +42,15 @@
 //! }
 //! ```
 //!
+//! # Decoding
+//!
+//! Data is decoded in small chunks on a separate thread and stored in a ring buffer, so the mixer never
+//! waits for the decoder under normal conditions. The decoder thread wraps the stream around when it
+//! reaches the end, so looping sounds continue from the beginning without any gaps. If the decoder
+//! cannot keep up with the mixer (for example, when the system is under heavy load), the missing part
+//! is replaced with silence and a warning is written to the log. On WebAssembly, where threads are not
+//! available, data is decoded on demand by the mixer.
+//!
 //! # Notes
 //!
 //! Streaming buffer cannot be shared across multiple source. On attempt to create a source with a streaming
@@ -53,9 +62,12 @@ use crate::{
     decoder::Decoder,
     error::SoundError,
 };
-use fyrox_core::{reflect::prelude::*, visitor::prelude::*};
+use fyrox_core::{log::Log, reflect::prelude::*, visitor::prelude::*, SafeLock};
 use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
     ops::{Deref, DerefMut},
+    sync::{Arc, Condvar, Mutex},
     time::Duration,
 };
 
@@ -71,7 +83,10 @@ pub struct StreamingBuffer {
     pub(crate) use_count: usize,
     #[visit(skip)]
     #[reflect(hidden)]
-    streaming_source: StreamingSource,
+    stream: Option<Stream>,
+    #[visit(skip)]
+    #[reflect(hidden)]
+    last_block: bool,
 }
 
 #[derive(Debug, Default)]
@@ -138,32 +153,272 @@ impl StreamingSource {
     }
 
     #[inline]
-    fn read_next_samples_block_into(&mut self, buffer: &mut Vec<f32>) -> usize {
-        buffer.clear();
-        let count = StreamingBuffer::STREAM_SAMPLE_COUNT * self.channel_count();
+    fn next_sample(&mut self) -> Option<f32> {
         match self {
-            StreamingSource::Decoder(decoder) => {
-                for _ in 0..count {
-                    if let Some(sample) = decoder.next() {
-                        buffer.push(sample)
-                    } else {
-                        break;
-                    }
+            StreamingSource::Decoder(decoder) => decoder.next(),
+            StreamingSource::Raw(raw_streaming) => raw_streaming.next(),
+            StreamingSource::Null => None,
+        }
+    }
+
+    /// Reads up to `count` samples into the buffer. Returns `true` if the end of the stream was
+    /// reached.
+    #[inline]
+    fn read_samples_into(&mut self, buffer: &mut Vec<f32>, count: usize) -> bool {
+        for _ in 0..count {
+            match self.next_sample() {
+                Some(sample) => buffer.push(sample),
+                None => return true,
+            }
+        }
+        false
+    }
+}
+
+/// State of the ring buffer, that is shared between the decoder and the mixer.
+#[derive(Default)]
+struct RingState {
+    /// Decoded samples in interleaved format.
+    samples: VecDeque<f32>,
+    /// Maximum amount of samples that the decoder will keep in the ring.
+    capacity: usize,
+    /// Total amount of samples that were pushed to the ring.
+    written: u64,
+    /// Total amount of samples that were taken from the ring.
+    read: u64,
+    /// Positions (in terms of `written`) where the stream ends and wraps around to its beginning.
+    stream_ends: VecDeque<u64>,
+    /// Incremented on every seek, it is used to discard chunks that were decoded before seeking.
+    generation: u64,
+    seek_request: Option<Duration>,
+    /// `true` if the first block after seeking was not read yet. The mixer waits for such block
+    /// without a timeout, because the decoder has to start decoding from scratch.
+    seeking: bool,
+    /// `true` if the stream has no data at all.
+    exhausted: bool,
+    shutdown: bool,
+}
+
+impl RingState {
+    /// Returns the amount of samples until the next end of the stream (if any).
+    fn samples_until_end(&self) -> Option<usize> {
+        self.stream_ends
+            .front()
+            .map(|end| end.saturating_sub(self.read) as usize)
+    }
+
+    /// Checks if the ring has enough data to provide a block of the given size. The decoder writes
+    /// an end of the stream right after the last chunk, so the data beyond the block guarantees
+    /// that the block does not end the stream.
+    fn can_provide(&self, count: usize) -> bool {
+        self.samples_until_end().is_some_and(|end| end <= count)
+            || self.samples.len() > count
+            || self.exhausted
+            || self.shutdown
+    }
+}
+
+#[derive(Default)]
+struct Ring {
+    state: Mutex<RingState>,
+    condvar: Condvar,
+}
+
+/// Decoder, that fills the ring buffer.
+struct StreamDecoder {
+    source: StreamingSource,
+    ring: Arc<Ring>,
+    chunk: Vec<f32>,
+}
+
+impl StreamDecoder {
+    /// Amount of samples per channel that are decoded at once.
+    const CHUNK_SIZE: usize = 4096;
+
+    /// Decodes next chunk of data and puts it in the ring buffer. Waits until there is free space
+    /// in the ring if `wait` is `true`, otherwise returns immediately. Returns `false` if there is
+    /// nothing to decode.
+    fn decode_next_chunk(&mut self, wait: bool) -> bool {
+        let (seek_request, generation) = {
+            let mut state = self.ring.state.safe_lock().unwrap();
+            loop {
+                if state.shutdown {
+                    return false;
+                }
+                if state.seek_request.is_some()
+                    || (!state.exhausted && state.samples.len() < state.capacity)
+                {
+                    break;
+                }
+                if !wait {
+                    return false;
+                }
+                state = self.ring.condvar.wait(state).unwrap();
+            }
+            (state.seek_request.take(), state.generation)
+        };
+
+        if let Some(location) = seek_request {
+            if let Err(err) = self.source.time_seek(location) {
+                Log::err(format!(
+                    "Unable to seek streaming source to {location:?}. Reason: {err:?}"
+                ));
+            }
+        }
+
+        self.chunk.clear();
+        let channel_count = self.source.channel_count().max(1);
+        let end_of_stream = self
+            .source
+            .read_samples_into(&mut self.chunk, Self::CHUNK_SIZE * channel_count);
+        if end_of_stream {
+            if let Err(err) = self.source.rewind() {
+                Log::err(format!(
+                    "Unable to rewind streaming source. Reason: {err:?}"
+                ));
+            }
+        }
+
+        let mut state = self.ring.state.safe_lock().unwrap();
+        // Discard the chunk if a seek was requested while it was decoding.
+        if state.generation == generation {
+            let written = state.written;
+            let is_empty_stream = self.chunk.is_empty()
+                && end_of_stream
+                && state.stream_ends.back() == Some(&written);
+            if is_empty_stream {
+                state.exhausted = true;
+            } else {
+                state.written += self.chunk.len() as u64;
+                state.samples.extend(self.chunk.drain(..));
+                if end_of_stream {
+                    let end = state.written;
+                    state.stream_ends.push_back(end);
                 }
             }
-            StreamingSource::Raw(raw_streaming) => {
-                for _ in 0..count {
-                    if let Some(sample) = raw_streaming.next() {
-                        buffer.push(sample)
-                    } else {
-                        break;
-                    }
+        }
+        self.ring.condvar.notify_all();
+
+        true
+    }
+}
+
+/// A stream of decoded data.
+struct Stream {
+    ring: Arc<Ring>,
+    #[cfg(not(target_arch = "wasm32"))]
+    thread: Option<std::thread::JoinHandle<()>>,
+    #[cfg(target_arch = "wasm32")]
+    decoder: StreamDecoder,
+}
+
+impl Debug for Stream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Stream").finish()
+    }
+}
+
+impl Stream {
+    fn new(decoder: StreamDecoder) -> Self {
+        let ring = decoder.ring.clone();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut decoder = decoder;
+            let thread = std::thread::Builder::new()
+                .name("StreamingDecoder".to_string())
+                .spawn(move || while decoder.decode_next_chunk(true) {})
+                .map_err(|err| {
+                    Log::err(format!(
+                        "Unable to spawn streaming decoder thread. Reason: {err:?}"
+                    ))
+                })
+                .ok();
+            Self { ring, thread }
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            Self { ring, decoder }
+        }
+    }
+
+    fn seek(&mut self, location: Duration) {
+        let mut state = self.ring.state.safe_lock().unwrap();
+        state.generation += 1;
+        state.samples.clear();
+        state.read = state.written;
+        state.stream_ends.clear();
+        state.seek_request = Some(location);
+        state.seeking = true;
+        state.exhausted = false;
+        self.ring.condvar.notify_all();
+    }
+
+    /// Takes up to `count` samples from the ring. Returns `true` if the samples are the last
+    /// samples of the stream.
+    fn read(&mut self, buffer: &mut Vec<f32>, count: usize) -> bool {
+        // There are no threads on WebAssembly, so decode the data on demand.
+        #[cfg(target_arch = "wasm32")]
+        let mut state = {
+            while !self.ring.state.safe_lock().unwrap().can_provide(count) {
+                if !self.decoder.decode_next_chunk(false) {
+                    break;
                 }
             }
-            StreamingSource::Null => (),
+            self.ring.state.safe_lock().unwrap()
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut state = {
+            let state = self.ring.state.safe_lock().unwrap();
+            if state.seeking && self.thread.is_some() {
+                // Otherwise the first block after seeking would be almost entirely replaced with
+                // silence.
+                self.ring
+                    .condvar
+                    .wait_while(state, |state| !state.can_provide(count))
+                    .unwrap()
+            } else {
+                self.ring
+                    .condvar
+                    .wait_timeout_while(state, StreamingBuffer::UNDERRUN_TIMEOUT, |state| {
+                        !state.can_provide(count)
+                    })
+                    .unwrap()
+                    .0
+            }
+        };
+        state.seeking = false;
+
+        let samples_until_end = state.samples_until_end();
+        let count = count
+            .min(state.samples.len())
+            .min(samples_until_end.unwrap_or(usize::MAX));
+        buffer.extend(state.samples.drain(..count));
+        state.read += count as u64;
+
+        let last = samples_until_end == Some(count);
+        if last {
+            state.stream_ends.pop_front();
         }
 
-        buffer.len()
+        // Free space in the ring, the decoder can continue.
+        self.ring.condvar.notify_all();
+
+        last
+    }
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        self.ring.state.safe_lock().unwrap().shutdown = true;
+        self.ring.condvar.notify_all();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
     }
 }
 
@@ -171,6 +426,10 @@ impl StreamingBuffer {
     /// Defines amount of samples `per channel` which each streaming buffer will use for internal buffer.
     pub const STREAM_SAMPLE_COUNT: usize = 44100;
 
+    /// Maximum amount of time that the mixer waits for the decoder, when there is not enough decoded
+    /// data. If the data is still not ready after this time, silence is played instead.
+    pub const UNDERRUN_TIMEOUT: Duration = Duration::from_millis(10);
+
     /// Creates new streaming buffer using given data source. May fail if data source has unsupported format
     /// or it has corrupted data. Length of internal generic buffer cannot be changed but can be fetched from
     /// `StreamingBuffer::STREAM_SAMPLE_COUNT`
@@ -182,10 +441,24 @@ impl StreamingBuffer {
     pub fn new(source: DataSource) -> Result<Self, SoundError> {
         let mut streaming_source = StreamingSource::new(source)?;
 
+        // The first block is decoded right away, so the buffer could be played immediately.
         let mut samples = Vec::new();
         let channel_count = streaming_source.channel_count();
-        streaming_source.read_next_samples_block_into(&mut samples);
+        let last_block = streaming_source
+            .read_samples_into(&mut samples, Self::STREAM_SAMPLE_COUNT * channel_count);
         debug_assert_eq!(samples.len() % channel_count, 0);
+        if last_block {
+            streaming_source.rewind()?;
+        }
+
+        let ring = Arc::new(Ring {
+            state: Mutex::new(RingState {
+                // Keep two blocks ahead of the mixer.
+                capacity: 2 * Self::STREAM_SAMPLE_COUNT * channel_count,
+                ..Default::default()
+            }),
+            condvar: Default::default(),
+        });
 
         Ok(Self {
             generic: GenericBuffer {
@@ -195,24 +468,65 @@ impl StreamingBuffer {
                 channel_duration_in_samples: streaming_source.channel_duration_in_samples(),
             },
             use_count: 0,
-            streaming_source,
+            stream: Some(Stream::new(StreamDecoder {
+                source: streaming_source,
+                ring,
+                chunk: Default::default(),
+            })),
+            last_block,
         })
     }
 
+    /// Returns `true` if the current block is the last block of the stream, the next block will
+    /// contain the data from the beginning of the stream.
     #[inline]
+    pub(crate) fn is_last_block(&self) -> bool {
+        self.last_block
+    }
+
     pub(crate) fn read_next_block(&mut self) {
-        self.streaming_source
-            .read_next_samples_block_into(&mut self.generic.samples);
+        self.generic.samples.clear();
+        self.last_block = false;
+
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+
+        let count = Self::STREAM_SAMPLE_COUNT * self.generic.channel_count.max(1);
+        self.last_block = stream.read(&mut self.generic.samples, count);
+
+        let len = self.generic.samples.len();
+        if len == 0 && self.last_block {
+            // The stream ended exactly at the end of the previous block, but the end was not
+            // known at the time of reading. Play a single frame of silence, because the block
+            // must not be empty.
+            self.generic
+                .samples
+                .resize(self.generic.channel_count.max(1), 0.0);
+        } else if len < count && !self.last_block {
+            Log::warn(format!(
+                "Streaming buffer underrun: {} samples were not decoded in time and will be \
+                replaced with silence.",
+                count - len
+            ));
+            self.generic.samples.resize(count, 0.0);
+        }
     }
 
+    /// Moves the stream to the beginning.
     #[inline]
     pub(crate) fn rewind(&mut self) -> Result<(), SoundError> {
-        self.streaming_source.rewind()
+        self.time_seek(Duration::from_secs(0))
     }
 
+    /// Moves the stream to the given location. Actual seeking is performed by the decoder, errors
+    /// are written to the log.
     #[inline]
     pub(crate) fn time_seek(&mut self, location: Duration) -> Result<(), SoundError> {
-        self.streaming_source.time_seek(location)
+        if let Some(stream) = self.stream.as_mut() {
+            stream.seek(location);
+        }
+        Ok(())
     }
 }
 
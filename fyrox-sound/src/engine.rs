@@ -67,7 +67,9 @@ impl SoundEngine {
         })))
     }
 
-    /// Tries to initialize default audio output device.
+    /// Tries to initialize default audio output device. The audio backend can't enumerate or select
+    /// output devices, the default output device of the OS is always used. Call
+    /// [`Self::destroy_audio_output_device`] and this method again to switch to a new default device.
     pub fn initialize_audio_output_device(&self) -> Result<(), Box<dyn Error>> {
         let state = self.clone();
 
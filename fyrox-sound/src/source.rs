@@ -487,26 +487,24 @@ impl SoundSource {
     pub fn set_playback_time(&mut self, time: Duration) {
         if let Some(buffer) = self.buffer.as_ref() {
             if let Some(buffer) = buffer.state().data() {
+                let time = time.clamp(Duration::from_secs(0), buffer.duration());
                 if let SoundBuffer::Streaming(ref mut streaming) = *buffer {
                     // Make sure decoder is at right position.
-                    if streaming
-                        .time_seek(time.clamp(Duration::from_secs(0), streaming.duration()))
-                        .is_err()
-                    {
+                    if streaming.time_seek(time).is_err() {
                         Log::warn("error while setting decoder position");
                     }
                 }
                 // Set absolute position first.
-                self.playback_pos = (time.as_secs_f64() * buffer.sample_rate as f64)
-                    .clamp(0.0, buffer.duration().as_secs_f64());
+                self.playback_pos = time.as_secs_f64() * buffer.sample_rate as f64;
                 // Then adjust buffer read position.
                 self.buf_read_pos = match *buffer {
                     SoundBuffer::Streaming(ref mut streaming) => {
                         // Make sure to load correct data into buffer from decoder.
                         streaming.read_next_block();
                         // Streaming sources has different buffer read position because
-                        // buffer contains only small portion of data.
-                        self.playback_pos % (StreamingBuffer::STREAM_SAMPLE_COUNT as f64)
+                        // buffer contains only small portion of data, that starts right at
+                        // the new position.
+                        self.playback_pos.fract()
                     }
                     SoundBuffer::Generic(_) => self.playback_pos,
                 };
@@ -549,12 +547,9 @@ impl SoundSource {
             let len = buffer.samples().len();
             let mut end_reached = true;
             if let SoundBuffer::Streaming(streaming) = buffer {
-                // Means that this is the last available block.
-                if len != channel_count * StreamingBuffer::STREAM_SAMPLE_COUNT {
-                    let _ = streaming.rewind();
-                } else {
-                    end_reached = false;
-                }
+                // The decoder wraps the stream around by itself, so the next block will contain
+                // the data from the beginning of the stream.
+                end_reached = streaming.is_last_block();
                 self.prev_buffer_sample = get_last_sample(streaming);
                 streaming.read_next_block();
             }
@@ -900,3 +895,124 @@ impl SoundSourceBuilder {
         Ok(source)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::{
+            DataSource, RawStreamingDataSource, SoundBufferResource, SoundBufferResourceExtension,
+        },
+        error::SoundError,
+        source::{SoundSourceBuilder, Status},
+    };
+    use std::time::Duration;
+
+    // Produces a mono ramp, where each sample is equal to its index in the stream.
+    #[derive(Debug)]
+    struct Ramp {
+        position: usize,
+        length: usize,
+    }
+
+    impl Iterator for Ramp {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.position < self.length {
+                let sample = self.position as f32;
+                self.position += 1;
+                Some(sample)
+            } else {
+                None
+            }
+        }
+    }
+
+    impl RawStreamingDataSource for Ramp {
+        fn sample_rate(&self) -> usize {
+            44100
+        }
+
+        fn channel_count(&self) -> usize {
+            1
+        }
+
+        fn time_seek(&mut self, duration: Duration) -> Result<(), SoundError> {
+            self.position = ((duration.as_secs_f64() * 44100.0) as usize).min(self.length);
+            Ok(())
+        }
+
+        fn channel_duration_in_samples(&self) -> usize {
+            self.length
+        }
+    }
+
+    #[test]
+    fn test_streaming_source_seamless_loop() {
+        // Length of the stream is not a multiple of the block size on purpose.
+        let length = 50000;
+
+        let buffer = SoundBufferResource::new_streaming(DataSource::RawStreaming(Box::new(Ramp {
+            position: 0,
+            length,
+        })))
+        .unwrap();
+
+        let mut source = SoundSourceBuilder::new()
+            .with_buffer(buffer)
+            .with_looping(true)
+            .with_status(Status::Playing)
+            .build()
+            .unwrap();
+
+        let chunk = 1000;
+        let mut expected = 0;
+        // Go through the loop point a few times.
+        for _ in 0..(3 * length / chunk) {
+            source.render(chunk);
+            for &(left, right) in source.frame_samples.iter() {
+                let sample = (expected % length) as f32;
+                assert_eq!(left, sample);
+                assert_eq!(right, sample);
+                expected += 1;
+            }
+        }
+        assert_eq!(source.status(), Status::Playing);
+    }
+
+    #[test]
+    fn test_streaming_source_seek() {
+        let length = 100000;
+
+        let buffer = SoundBufferResource::new_streaming(DataSource::RawStreaming(Box::new(Ramp {
+            position: 0,
+            length,
+        })))
+        .unwrap();
+
+        let mut source = SoundSourceBuilder::new()
+            .with_buffer(buffer)
+            .with_status(Status::Playing)
+            .build()
+            .unwrap();
+
+        source.render(1000);
+
+        // The decoder is far ahead at this moment, the data after the seek must be decoded from
+        // the new position and must not be replaced with silence.
+        let position = 66150;
+        source.set_playback_time(Duration::from_millis(1500));
+        assert_eq!(source.playback_time(), Duration::from_millis(1500));
+
+        let chunk = 1000;
+        let mut expected = position;
+        for _ in 0..10 {
+            source.render(chunk);
+            for &(left, right) in source.frame_samples.iter() {
+                assert_eq!(left, expected as f32);
+                assert_eq!(right, expected as f32);
+                expected += 1;
+            }
+        }
+    }
+}
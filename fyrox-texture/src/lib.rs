@@ -2047,6 +2047,48 @@ impl Texture {
     pub fn modify(&mut self) -> TextureDataRefMut<'_> {
         TextureDataRefMut { texture: self }
     }
+
+    /// Tries to compress the texture in-place using the given compression options. It is the same
+    /// compression that is applied at import stage, but it could be used for textures that are
+    /// already loaded (for example - procedural or embedded ones). Only rectangle textures with
+    /// uncompressed 8-bit pixel formats could be compressed, every other texture is left untouched.
+    /// Returns `true` if the texture was compressed, `false` - otherwise.
+    pub fn compress(&mut self, compression: CompressionOptions) -> bool {
+        let TextureKind::Rectangle { width, height } = self.kind else {
+            return false;
+        };
+
+        if compression == CompressionOptions::NoCompression || self.is_render_target {
+            return false;
+        }
+
+        let mut bytes = Vec::new();
+        let mut new_pixel_kind = self.pixel_kind;
+        let mut offset = 0;
+        for mip in 0..self.mip_count as usize {
+            let size = bytes_in_mip_level(self.kind, self.pixel_kind, mip) as usize;
+            let Some(level) = self.bytes.get(offset..offset + size) else {
+                return false;
+            };
+            let Some((compressed, pixel_kind)) = try_compress(
+                self.pixel_kind,
+                level,
+                width.shr(mip).max(1) as usize,
+                height.shr(mip).max(1) as usize,
+                compression,
+            ) else {
+                return false;
+            };
+            bytes.extend_from_slice(&compressed);
+            new_pixel_kind = pixel_kind;
+            offset += size;
+        }
+
+        self.bytes = bytes.into();
+        self.pixel_kind = new_pixel_kind;
+        self.modifications_counter += 1;
+        true
+    }
 }
 
 /// A special reference holder that provides mutable access to content of the
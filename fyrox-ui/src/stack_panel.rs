@@ -294,10 +294,47 @@ impl StackPanelBuilder {
 #[cfg(test)]
 mod test {
     use crate::stack_panel::StackPanelBuilder;
-    use crate::{test::test_widget_deletion, widget::WidgetBuilder};
+    use crate::{
+        button::{ButtonBuilder, ButtonMessage},
+        core::algebra::Vector2,
+        test::{test_widget_deletion, UserInterfaceTestingExtension},
+        widget::WidgetBuilder,
+        UiNode, UserInterface,
+    };
 
     #[test]
     fn test_deletion() {
         test_widget_deletion(|ctx| StackPanelBuilder::new(WidgetBuilder::new()).build(ctx));
     }
+
+    #[test]
+    fn test_vertical_layout_and_hit_test() {
+        let screen_size = Vector2::repeat(1000.0);
+        let mut ui = UserInterface::new(screen_size);
+        let ctx = &mut ui.build_ctx();
+        let first = ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+            .with_text("First")
+            .build(ctx);
+        let second = ButtonBuilder::new(WidgetBuilder::new().with_width(100.0).with_height(30.0))
+            .with_text("Second")
+            .build(ctx);
+        StackPanelBuilder::new(WidgetBuilder::new().with_child(first).with_child(second))
+            .build(ctx);
+        ui.poll_all_messages();
+
+        let first_bounds = ui[first].screen_bounds();
+        let second_bounds = ui[second].screen_bounds();
+        assert_eq!(first_bounds.size, Vector2::new(100.0, 30.0));
+        assert_eq!(second_bounds.size, Vector2::new(100.0, 30.0));
+        assert!(first_bounds.position.y + first_bounds.size.y <= second_bounds.position.y);
+
+        ui.click(second_bounds.center());
+        let mut clicked = Vec::new();
+        while let Some(message) = ui.poll_message() {
+            if let Some(ButtonMessage::Click) = message.data() {
+                clicked.push(message.destination());
+            }
+        }
+        assert_eq!(clicked, vec![second.to_base::<UiNode>()]);
+    }
 }
@@ -473,6 +473,11 @@ use {name}::{{fyrox::plugin::Plugin, Game}};
 pub fn fyrox_plugin() -> Box<dyn Plugin> {{
     Box::new(Game::default())
 }}
+
+#[no_mangle]
+pub extern "C" fn fyrox_plugin_abi_version() -> *const std::ffi::c_char {{
+    {name}::fyrox::plugin::dylib::PLUGIN_ABI_VERSION.as_ptr()
+}}
 "#,
         ),
     )